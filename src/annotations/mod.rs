@@ -0,0 +1,624 @@
+//! Worktree annotations (purpose note, PR link, template, and when, by whom
+//! and from which base the worktree was created) and their mirror in
+//! `refs/notes/rsworktree`.
+//!
+//! Annotations live in `.rsworktree/meta/<worktree>.toml`; worktrees created
+//! before that still read them from the repository config
+//! (`branch.<name>.rsworktree*`) until they change. With `notes.mirror`
+//! enabled in `preferences.json` every change is also written to the notes
+//! ref, which `rsworktree notes sync` exchanges with a remote so annotations
+//! survive re-clones and can be shared across machines; the notes ref holds
+//! them per branch.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use git2::{ErrorCode, Oid, Repository, Signature};
+use serde::{Deserialize, Serialize};
+
+use crate::{Repo, editor::CONFIG_FILE_NAME, worktree};
+
+/// Directory in `.rsworktree` holding one `<worktree>.toml` per worktree.
+pub(crate) const META_DIR: &str = "meta";
+/// Notes ref holding one JSON document per annotated branch.
+const NOTES_REF: &str = "refs/notes/rsworktree";
+/// Scratch ref the remote notes are fetched into during a sync.
+const REMOTE_NOTES_REF: &str = "refs/notes/rsworktree-remote";
+
+#[derive(Debug, Default, Deserialize)]
+struct FileFormat {
+    #[serde(default)]
+    notes: Option<NotesConfig>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct NotesConfig {
+    #[serde(default)]
+    mirror: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Annotations {
+    pub(crate) branch: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) pr: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) template: Option<String>,
+    /// Key of the tracker issue the branch was created for, e.g. `PROJ-123`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) issue: Option<String>,
+    /// Branch the worktree branch was created from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) base: Option<String>,
+    /// Seconds since the Unix epoch at which `create` added the worktree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) created_at: Option<u64>,
+    /// `user.name` of whoever created the worktree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) creator: Option<String>,
+    /// Seconds since the Unix epoch of the last change; the newer side wins
+    /// when both sides changed the same field.
+    #[serde(default)]
+    pub(crate) updated_at: u64,
+}
+
+/// What `sync` changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct SyncSummary {
+    /// Branches whose local annotations were updated from the remote.
+    pub(crate) updated: usize,
+    /// Annotated branches now present in the notes ref.
+    pub(crate) total: usize,
+}
+
+impl Annotations {
+    /// Reads the annotations of `worktree`, which has `branch` checked out.
+    pub(crate) fn load(repo: &Repo, worktree: &str, branch: &str) -> color_eyre::Result<Self> {
+        let path = meta_path(&repo.rsworktree_dir(), worktree);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Self::load_legacy(repo.git(), branch);
+            }
+            Err(error) => {
+                return Err(error)
+                    .wrap_err_with(|| eyre::eyre!("failed to read `{}`", path.display()));
+            }
+        };
+        let mut table = text
+            .parse::<toml::Table>()
+            .wrap_err_with(|| eyre::eyre!("failed to parse `{}`", path.display()))?;
+        // The branch may have been renamed since the file was written.
+        table.insert("branch".to_owned(), toml::Value::String(branch.to_owned()));
+        table
+            .try_into()
+            .wrap_err_with(|| eyre::eyre!("invalid metadata in `{}`", path.display()))
+    }
+
+    /// Reads the annotations rsworktree kept in the repository config before
+    /// the metadata files.
+    fn load_legacy(repo: &Repository, branch: &str) -> color_eyre::Result<Self> {
+        let config = repo
+            .config()
+            .and_then(|mut config| config.snapshot())
+            .wrap_err("failed to read repository config")?;
+        let read = |field: &str| config.get_string(&config_key(branch, field)).ok();
+        let read_time = |field: &str| {
+            config
+                .get_i64(&config_key(branch, field))
+                .ok()
+                .and_then(|value| u64::try_from(value).ok())
+        };
+
+        Ok(Self {
+            branch: branch.to_owned(),
+            note: read("Note"),
+            pr: read("Pr"),
+            template: read("Template"),
+            issue: read("Issue"),
+            base: read("Base"),
+            created_at: read_time("Created"),
+            creator: read("Creator"),
+            updated_at: read_time("Updated").unwrap_or_default(),
+        })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.note.is_none()
+            && self.pr.is_none()
+            && self.template.is_none()
+            && self.issue.is_none()
+            && self.base.is_none()
+            && self.created_at.is_none()
+            && self.creator.is_none()
+    }
+
+    /// Writes the annotations to the metadata file of `worktree`.
+    fn store(&self, repo: &Repo, worktree: &str) -> color_eyre::Result<()> {
+        let path = meta_path(&repo.rsworktree_dir(), worktree);
+        let text = toml::to_string(self).wrap_err("failed to serialize annotations")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| eyre::eyre!("failed to create `{}`", parent.display()))?;
+        }
+        fs::write(&path, text).wrap_err_with(|| eyre::eyre!("failed to write `{}`", path.display()))
+    }
+
+    /// Combines two versions of the same branch annotations field by field.
+    ///
+    /// A field set on only one side is kept; when both sides disagree the
+    /// more recently updated side wins, preferring `self` on ties.
+    fn merge(self, other: Self) -> Self {
+        let self_wins = self.updated_at >= other.updated_at;
+        let pick = |ours: Option<String>, theirs: Option<String>| match (ours, theirs) {
+            (Some(ours), Some(theirs)) => Some(if self_wins { ours } else { theirs }),
+            (ours, theirs) => ours.or(theirs),
+        };
+        Self {
+            note: pick(self.note, other.note),
+            pr: pick(self.pr, other.pr),
+            template: pick(self.template, other.template),
+            issue: pick(self.issue, other.issue),
+            base: pick(self.base, other.base),
+            // The worktree was first created at the earlier time.
+            created_at: match (self.created_at, other.created_at) {
+                (Some(ours), Some(theirs)) => Some(ours.min(theirs)),
+                (ours, theirs) => ours.or(theirs),
+            },
+            creator: pick(self.creator, other.creator),
+            updated_at: self.updated_at.max(other.updated_at),
+            branch: self.branch,
+        }
+    }
+}
+
+/// Applies `update` to the annotations of `worktree`, which has `branch`
+/// checked out, stores them and mirrors them into the notes ref when
+/// `notes.mirror` is enabled.
+pub(crate) fn record(
+    repo: &Repo,
+    worktree: &str,
+    branch: &str,
+    update: impl FnOnce(&mut Annotations),
+) -> color_eyre::Result<Annotations> {
+    let mut annotations = Annotations::load(repo, worktree, branch)?;
+    update(&mut annotations);
+    annotations.updated_at = now();
+    annotations.store(repo, worktree)?;
+    if mirror_enabled(&repo.rsworktree_dir()) {
+        write_note(repo.git(), NOTES_REF, &annotations)?;
+    }
+    Ok(annotations)
+}
+
+/// The metadata file of `worktree`.
+pub(crate) fn meta_path(worktrees_dir: &Path, worktree: &str) -> PathBuf {
+    worktrees_dir
+        .join(META_DIR)
+        .join(format!("{worktree}.toml"))
+}
+
+/// Moves the metadata of a renamed worktree along with it.
+pub(crate) fn rename(worktrees_dir: &Path, old: &str, new: &str) -> color_eyre::Result<()> {
+    let from = meta_path(worktrees_dir, old);
+    if !from.exists() {
+        return Ok(());
+    }
+    let to = meta_path(worktrees_dir, new);
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .wrap_err_with(|| eyre::eyre!("failed to create `{}`", parent.display()))?;
+    }
+    fs::rename(&from, &to)
+        .wrap_err_with(|| eyre::eyre!("failed to move `{}` to `{}`", from.display(), to.display()))
+}
+
+/// Deletes the metadata of a removed worktree; the notes ref keeps its mirror.
+pub(crate) fn remove(worktrees_dir: &Path, worktree: &str) -> color_eyre::Result<()> {
+    let path = meta_path(worktrees_dir, worktree);
+    match fs::remove_file(&path) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => {
+            Err(error).wrap_err_with(|| eyre::eyre!("failed to remove `{}`", path.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Merges local annotations with the notes ref on `remote` and pushes the result.
+pub(crate) fn sync(repo: &Repo, remote: &str) -> color_eyre::Result<SyncSummary> {
+    let git_repo = repo.git();
+    let workdir = git_repo.workdir().unwrap_or(git_repo.path());
+
+    let fetch_spec = format!("+{NOTES_REF}:{REMOTE_NOTES_REF}");
+    let fetched = run_git(workdir, &["fetch", "--quiet", remote, &fetch_spec])?;
+    let remote_tip = match fetched {
+        Ok(()) => git_repo.refname_to_id(REMOTE_NOTES_REF).ok(),
+        Err(stderr) if stderr.contains("couldn't find remote ref") => None,
+        Err(stderr) => {
+            return Err(eyre::eyre!(
+                "failed to fetch annotations from `{remote}`: {stderr}"
+            ));
+        }
+    };
+
+    let mut merged: BTreeMap<String, Annotations> = BTreeMap::new();
+    let mut add = |annotations: Annotations| {
+        let branch = annotations.branch.clone();
+        let combined = match merged.remove(&branch) {
+            Some(existing) => existing.merge(annotations),
+            None => annotations,
+        };
+        merged.insert(branch, combined);
+    };
+    // Annotations are stored per worktree, the notes ref holds them per branch.
+    let worktrees = worktree::load_all(repo)?
+        .into_iter()
+        .filter(|worktree| worktree.managed)
+        .filter_map(|worktree| Some((worktree.status.branch?, worktree.name)))
+        .collect::<BTreeMap<_, _>>();
+    for (branch, worktree) in &worktrees {
+        let annotations = Annotations::load(repo, worktree, branch)?;
+        if !annotations.is_empty() {
+            add(annotations);
+        }
+    }
+    for annotations in read_notes(git_repo, NOTES_REF)? {
+        add(annotations);
+    }
+    if remote_tip.is_some() {
+        for annotations in read_notes(git_repo, REMOTE_NOTES_REF)? {
+            add(annotations);
+        }
+    }
+
+    let mut summary = SyncSummary {
+        total: merged.len(),
+        ..SyncSummary::default()
+    };
+    for annotations in merged.values() {
+        if let Some(worktree) = worktrees.get(&annotations.branch) {
+            let current = Annotations::load(repo, worktree, &annotations.branch)?;
+            if current != *annotations {
+                annotations.store(repo, worktree)?;
+                summary.updated += 1;
+            }
+        }
+        if read_note(git_repo, NOTES_REF, &annotations.branch)?.as_ref() != Some(annotations) {
+            write_note(git_repo, NOTES_REF, annotations)?;
+        }
+    }
+
+    if let Some(remote_tip) = remote_tip {
+        join_histories(git_repo, remote_tip)?;
+        if let Ok(mut reference) = git_repo.find_reference(REMOTE_NOTES_REF) {
+            let _ = reference.delete();
+        }
+    }
+
+    if git_repo.refname_to_id(NOTES_REF).is_ok() {
+        let push_spec = format!("{NOTES_REF}:{NOTES_REF}");
+        run_git(workdir, &["push", "--quiet", remote, &push_spec])?
+            .map_err(|stderr| eyre::eyre!("failed to push annotations to `{remote}`: {stderr}"))?;
+    }
+
+    Ok(summary)
+}
+
+fn mirror_enabled(rsworktree_dir: &Path) -> bool {
+    fs::read_to_string(rsworktree_dir.join(CONFIG_FILE_NAME))
+        .ok()
+        .and_then(|text| serde_json::from_str::<FileFormat>(&text).ok())
+        .and_then(|parsed| parsed.notes)
+        .is_some_and(|notes| notes.mirror)
+}
+
+fn config_key(branch: &str, field: &str) -> String {
+    format!("branch.{branch}.rsworktree{field}")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// The object a branch's note is attached to: a blob naming the branch, so
+/// the note stays put while the branch moves.
+fn note_target(repo: &Repository, branch: &str) -> color_eyre::Result<Oid> {
+    repo.blob(format!("rsworktree:{branch}").as_bytes())
+        .wrap_err_with(|| eyre::eyre!("failed to write note key for `{branch}`"))
+}
+
+fn signature(repo: &Repository) -> color_eyre::Result<Signature<'static>> {
+    repo.signature()
+        .or_else(|_| Signature::now("rsworktree", "rsworktree@localhost"))
+        .wrap_err("failed to create a signature for the notes commit")
+}
+
+fn write_note(
+    repo: &Repository,
+    notes_ref: &str,
+    annotations: &Annotations,
+) -> color_eyre::Result<()> {
+    let target = note_target(repo, &annotations.branch)?;
+    let body =
+        serde_json::to_string_pretty(annotations).wrap_err("failed to serialize annotations")?;
+    let signature = signature(repo)?;
+    repo.note(&signature, &signature, Some(notes_ref), target, &body, true)
+        .wrap_err_with(|| {
+            eyre::eyre!(
+                "failed to write annotations for `{}` to `{notes_ref}`",
+                annotations.branch
+            )
+        })?;
+    Ok(())
+}
+
+fn read_note(
+    repo: &Repository,
+    notes_ref: &str,
+    branch: &str,
+) -> color_eyre::Result<Option<Annotations>> {
+    let target = note_target(repo, branch)?;
+    Ok(repo
+        .find_note(Some(notes_ref), target)
+        .ok()
+        .and_then(|note| note.message().map(str::to_owned))
+        .and_then(|message| serde_json::from_str(&message).ok()))
+}
+
+/// All parseable annotations stored in `notes_ref`; foreign notes are skipped.
+fn read_notes(repo: &Repository, notes_ref: &str) -> color_eyre::Result<Vec<Annotations>> {
+    let notes = match repo.notes(Some(notes_ref)) {
+        Ok(notes) => notes,
+        Err(error) if error.code() == ErrorCode::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(eyre::eyre!("failed to read `{notes_ref}`: {error}"));
+        }
+    };
+
+    let mut annotations = Vec::new();
+    for entry in notes {
+        let (_, target) = entry.wrap_err_with(|| eyre::eyre!("failed to read `{notes_ref}`"))?;
+        if let Some(parsed) = repo
+            .find_note(Some(notes_ref), target)
+            .ok()
+            .and_then(|note| note.message().map(str::to_owned))
+            .and_then(|message| serde_json::from_str::<Annotations>(&message).ok())
+        {
+            annotations.push(parsed);
+        }
+    }
+    Ok(annotations)
+}
+
+/// Makes the local notes ref a descendant of `remote_tip` so it can be pushed
+/// as a fast-forward; the local tree already holds the merged notes.
+fn join_histories(repo: &Repository, remote_tip: Oid) -> color_eyre::Result<()> {
+    let Ok(local_tip) = repo.refname_to_id(NOTES_REF) else {
+        return Ok(());
+    };
+    if local_tip == remote_tip || repo.graph_descendant_of(local_tip, remote_tip)? {
+        return Ok(());
+    }
+
+    let local = repo.find_commit(local_tip)?;
+    let remote = repo.find_commit(remote_tip)?;
+    let tree = local.tree()?;
+    let signature = signature(repo)?;
+    repo.commit(
+        Some(NOTES_REF),
+        &signature,
+        &signature,
+        "Merge rsworktree annotations",
+        &tree,
+        &[&local, &remote],
+    )
+    .wrap_err("failed to merge annotation histories")?;
+    Ok(())
+}
+
+/// Runs git, returning the trimmed stderr as the inner error when it fails.
+fn run_git(dir: &Path, args: &[&str]) -> color_eyre::Result<Result<(), String>> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .wrap_err_with(|| eyre::eyre!("failed to run `git {}`", args.join(" ")))?;
+    if output.status.success() {
+        Ok(Ok(()))
+    } else {
+        Ok(Err(String::from_utf8_lossy(&output.stderr)
+            .trim()
+            .to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) -> color_eyre::Result<()> {
+        let status = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .status()?;
+        if !status.success() {
+            return Err(eyre::eyre!("git {:?} failed", args));
+        }
+        Ok(())
+    }
+
+    fn clone_with_worktree(remote: &Path, dir: &Path) -> color_eyre::Result<Repo> {
+        git(dir, &["clone", "--quiet", remote.to_str().unwrap(), "."])?;
+        git(dir, &["branch", "feature/notes", "origin/feature/notes"])?;
+        git(
+            dir,
+            &[
+                "worktree",
+                "add",
+                "--quiet",
+                ".rsworktree/feature/notes",
+                "feature/notes",
+            ],
+        )?;
+        Repo::discover_from(dir)
+    }
+
+    fn annotations(note: Option<&str>, pr: Option<&str>, updated_at: u64) -> Annotations {
+        Annotations {
+            branch: "feature/notes".into(),
+            note: note.map(String::from),
+            pr: pr.map(String::from),
+            template: None,
+            issue: None,
+            base: None,
+            created_at: None,
+            creator: None,
+            updated_at,
+        }
+    }
+
+    #[test]
+    fn merge_keeps_fields_from_both_sides_and_prefers_newer_values() {
+        let local = Annotations {
+            created_at: Some(5),
+            creator: Some("Ada".into()),
+            ..annotations(Some("local purpose"), None, 10)
+        };
+        let remote = Annotations {
+            created_at: Some(3),
+            ..annotations(Some("remote purpose"), Some("https://example.com/pr/1"), 20)
+        };
+
+        let merged = local.merge(remote);
+
+        assert_eq!(merged.note.as_deref(), Some("remote purpose"));
+        assert_eq!(merged.pr.as_deref(), Some("https://example.com/pr/1"));
+        assert_eq!(merged.created_at, Some(3));
+        assert_eq!(merged.creator.as_deref(), Some("Ada"));
+        assert_eq!(merged.updated_at, 20);
+    }
+
+    #[test]
+    fn record_stores_annotations_in_the_meta_file() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        git(dir.path(), &["init", "--quiet"])?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        record(&repo, "feature/notes", "feature/notes", |annotations| {
+            annotations.note = Some("try the new parser".into());
+        })?;
+
+        let path = meta_path(&repo.rsworktree_dir(), "feature/notes");
+        assert!(fs::read_to_string(path)?.contains("note = \"try the new parser\"\n"));
+        let loaded = Annotations::load(&repo, "feature/notes", "feature/notes")?;
+        assert_eq!(loaded.note.as_deref(), Some("try the new parser"));
+        assert!(loaded.updated_at > 0);
+        assert!(repo.git().refname_to_id(NOTES_REF).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_falls_back_to_annotations_in_config() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        git(dir.path(), &["init", "--quiet"])?;
+        git(
+            dir.path(),
+            &[
+                "config",
+                "branch.feature/notes.rsworktreeNote",
+                "from before",
+            ],
+        )?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        let loaded = Annotations::load(&repo, "feature/notes", "feature/notes")?;
+        assert_eq!(loaded.note.as_deref(), Some("from before"));
+
+        let updated = record(&repo, "feature/notes", "feature/notes", |annotations| {
+            annotations.pr = Some("https://example.com/pr/4".into());
+        })?;
+        assert_eq!(updated.note.as_deref(), Some("from before"));
+        assert!(meta_path(&repo.rsworktree_dir(), "feature/notes").is_file());
+
+        Ok(())
+    }
+
+    #[test]
+    fn record_mirrors_into_notes_ref_when_enabled() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        git(dir.path(), &["init", "--quiet"])?;
+        let repo = Repo::discover_from(dir.path())?;
+        let rsworktree_dir = repo.ensure_rsworktree_dir()?;
+        fs::write(
+            rsworktree_dir.join(CONFIG_FILE_NAME),
+            r#"{ "notes": { "mirror": true } }"#,
+        )?;
+
+        record(&repo, "feature/notes", "feature/notes", |annotations| {
+            annotations.pr = Some("https://example.com/pr/3".into());
+        })?;
+
+        let mirrored = read_note(repo.git(), NOTES_REF, "feature/notes")?;
+        assert_eq!(
+            mirrored.and_then(|annotations| annotations.pr).as_deref(),
+            Some("https://example.com/pr/3")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sync_shares_annotations_between_clones() -> color_eyre::Result<()> {
+        let origin = TempDir::new()?;
+        git(origin.path(), &["init", "--quiet"])?;
+        std::fs::write(origin.path().join("README.md"), "test")?;
+        git(origin.path(), &["add", "README.md"])?;
+        git(
+            origin.path(),
+            &["commit", "--quiet", "-m", "Initial commit"],
+        )?;
+        git(origin.path(), &["branch", "feature/notes"])?;
+
+        let first_dir = TempDir::new()?;
+        let first = clone_with_worktree(origin.path(), first_dir.path())?;
+        let second_dir = TempDir::new()?;
+        let second = clone_with_worktree(origin.path(), second_dir.path())?;
+
+        record(&first, "feature/notes", "feature/notes", |annotations| {
+            annotations.note = Some("purpose from first".into());
+        })?;
+        sync(&first, "origin")?;
+
+        record(&second, "feature/notes", "feature/notes", |annotations| {
+            annotations.pr = Some("https://example.com/pr/7".into());
+        })?;
+        let summary = sync(&second, "origin")?;
+        assert_eq!(summary.updated, 1);
+        assert_eq!(summary.total, 1);
+
+        let merged = Annotations::load(&second, "feature/notes", "feature/notes")?;
+        assert_eq!(merged.note.as_deref(), Some("purpose from first"));
+        assert_eq!(merged.pr.as_deref(), Some("https://example.com/pr/7"));
+
+        sync(&first, "origin")?;
+        let first_view = Annotations::load(&first, "feature/notes", "feature/notes")?;
+        assert_eq!(first_view.pr.as_deref(), Some("https://example.com/pr/7"));
+
+        Ok(())
+    }
+}
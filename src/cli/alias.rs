@@ -0,0 +1,304 @@
+//! Config-defined command aliases.
+//!
+//! Aliases live under `aliases` in `preferences.json` and map a name to a
+//! command line, e.g. `"draft": "review {1} --draft"`. Placeholders `{1}`, `{2}`,
+//! ... insert the alias arguments by position and `{*}` inserts every argument
+//! not consumed by a positional placeholder; leftover arguments are appended.
+//! Aliases may refer to other aliases but never shadow a built-in command.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsString,
+    fs,
+};
+
+use clap::CommandFactory;
+use color_eyre::eyre::{self, WrapErr};
+use serde::Deserialize;
+
+use super::Cli;
+use crate::{Repo, editor::CONFIG_FILE_NAME};
+
+#[derive(Debug, Default, Deserialize)]
+struct FileFormat {
+    #[serde(default)]
+    aliases: BTreeMap<String, String>,
+}
+
+/// Expands a user-defined alias in `args` (including the program name).
+///
+/// Arguments are returned unchanged when the command is built in or when no
+/// repository, and therefore no configuration, can be found.
+pub(crate) fn expand_aliases(args: Vec<OsString>) -> color_eyre::Result<Vec<OsString>> {
+    let Some(index) = command_position(&args) else {
+        return Ok(args);
+    };
+    if is_builtin(&args[index].to_string_lossy()) {
+        return Ok(args);
+    }
+    let Ok(repo) = Repo::discover() else {
+        return Ok(args);
+    };
+
+    let aliases = load_aliases(&repo)?;
+    expand(args, index, &aliases)
+}
+
+fn load_aliases(repo: &Repo) -> color_eyre::Result<BTreeMap<String, String>> {
+    let config_path = repo.rsworktree_dir().join(CONFIG_FILE_NAME);
+    let Ok(contents) = fs::read_to_string(&config_path) else {
+        return Ok(BTreeMap::new());
+    };
+    let parsed: FileFormat = serde_json::from_str(&contents)
+        .wrap_err_with(|| eyre::eyre!("failed to parse `{}`", config_path.display()))?;
+    Ok(parsed.aliases)
+}
+
+/// Index of the command in `args`, skipping global flags and the values of
+/// those that take one, such as `--host <host>`.
+fn command_position(args: &[OsString]) -> Option<usize> {
+    let command = Cli::command();
+    let valued_flags = command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && arg.get_action().takes_values())
+        .flat_map(|arg| {
+            let long = arg.get_long().map(|long| format!("--{long}"));
+            let short = arg.get_short().map(|short| format!("-{short}"));
+            long.into_iter().chain(short)
+        })
+        .collect::<BTreeSet<_>>();
+
+    let mut index = 1;
+    while let Some(arg) = args.get(index) {
+        let arg = arg.to_string_lossy();
+        if !arg.starts_with('-') {
+            return Some(index);
+        }
+        index += if valued_flags.contains(arg.as_ref()) {
+            2
+        } else {
+            1
+        };
+    }
+    None
+}
+
+fn is_builtin(name: &str) -> bool {
+    name == "help"
+        || Cli::command().get_subcommands().any(|command| {
+            command.get_name() == name || command.get_all_aliases().any(|alias| alias == name)
+        })
+}
+
+fn expand(
+    mut args: Vec<OsString>,
+    index: usize,
+    aliases: &BTreeMap<String, String>,
+) -> color_eyre::Result<Vec<OsString>> {
+    let mut seen = BTreeSet::new();
+
+    loop {
+        let name = args[index].to_string_lossy().into_owned();
+        if is_builtin(&name) {
+            return Ok(args);
+        }
+        let Some(template) = aliases.get(&name) else {
+            return Ok(args);
+        };
+        if !seen.insert(name.clone()) {
+            return Err(eyre::eyre!("alias `{name}` expands to itself"));
+        }
+
+        let alias_args: Vec<String> = args[index + 1..]
+            .iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        let expanded = expand_template(&name, template, &alias_args)?;
+        if expanded.is_empty() {
+            return Err(eyre::eyre!("alias `{name}` is empty"));
+        }
+
+        args.truncate(index);
+        args.extend(expanded.into_iter().map(OsString::from));
+    }
+}
+
+fn expand_template(name: &str, template: &str, args: &[String]) -> color_eyre::Result<Vec<String>> {
+    let tokens = shell_words::split(template)
+        .wrap_err_with(|| eyre::eyre!("alias `{name}` is not a valid command line"))?;
+
+    let mut used = vec![false; args.len()];
+    let mut rest_requested = false;
+    let mut expanded = Vec::with_capacity(tokens.len());
+
+    for token in &tokens {
+        if token == "{*}" {
+            rest_requested = true;
+            expanded.push(None);
+            continue;
+        }
+        let mut value = String::with_capacity(token.len());
+        let mut remainder = token.as_str();
+        while let Some(start) = remainder.find('{') {
+            let Some(length) = remainder[start..].find('}') else {
+                break;
+            };
+            let inner = &remainder[start + 1..start + length];
+            let Ok(position) = inner.parse::<usize>() else {
+                value.push_str(&remainder[..=start]);
+                remainder = &remainder[start + 1..];
+                continue;
+            };
+            let argument = position
+                .checked_sub(1)
+                .and_then(|offset| args.get(offset).map(|arg| (offset, arg)));
+            let Some((offset, argument)) = argument else {
+                return Err(eyre::eyre!(
+                    "alias `{name}` expects at least {position} argument(s), got {}",
+                    args.len()
+                ));
+            };
+            used[offset] = true;
+            value.push_str(&remainder[..start]);
+            value.push_str(argument);
+            remainder = &remainder[start + length + 1..];
+        }
+        value.push_str(remainder);
+        expanded.push(Some(value));
+    }
+
+    let rest: Vec<String> = args
+        .iter()
+        .zip(&used)
+        .filter(|(_, used)| !**used)
+        .map(|(arg, _)| arg.clone())
+        .collect();
+
+    let mut result = Vec::with_capacity(expanded.len() + rest.len());
+    for token in expanded {
+        match token {
+            Some(token) => result.push(token),
+            None => result.extend(rest.iter().cloned()),
+        }
+    }
+    if !rest_requested {
+        result.extend(rest);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn os_args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    fn aliases(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, template)| ((*name).to_owned(), (*template).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn expand_substitutes_positional_placeholders() -> color_eyre::Result<()> {
+        let aliases = aliases(&[("feat", "create feature/{1} --base {2}")]);
+        let args = os_args(&["rsworktree", "feat", "login", "develop"]);
+
+        let expanded = expand(args, 1, &aliases)?;
+
+        assert_eq!(
+            expanded,
+            os_args(&["rsworktree", "create", "feature/login", "--base", "develop"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn expand_appends_unused_arguments() -> color_eyre::Result<()> {
+        let aliases = aliases(&[("mr", "review {1}")]);
+        let args = os_args(&["rsworktree", "--plain", "mr", "feature", "--draft"]);
+
+        let expanded = expand(args, 2, &aliases)?;
+
+        assert_eq!(
+            expanded,
+            os_args(&["rsworktree", "--plain", "review", "feature", "--draft"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn expand_places_rest_placeholder() -> color_eyre::Result<()> {
+        let aliases = aliases(&[("rv", "review {1} {*} --fill")]);
+        let args = os_args(&["rsworktree", "rv", "feature", "--draft", "--web"]);
+
+        let expanded = expand(args, 1, &aliases)?;
+
+        assert_eq!(
+            expanded,
+            os_args(&[
+                "rsworktree",
+                "review",
+                "feature",
+                "--draft",
+                "--web",
+                "--fill"
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn expand_follows_nested_aliases_and_rejects_loops() -> color_eyre::Result<()> {
+        let nested = aliases(&[("f", "feat {1}"), ("feat", "create feature/{1}")]);
+        let expanded = expand(os_args(&["rsworktree", "f", "x"]), 1, &nested)?;
+        assert_eq!(expanded, os_args(&["rsworktree", "create", "feature/x"]));
+
+        let looping = aliases(&[("a", "b"), ("b", "a")]);
+        let error = expand(os_args(&["rsworktree", "a"]), 1, &looping)
+            .expect_err("alias loop should be rejected");
+        assert!(error.to_string().contains("expands to itself"));
+        Ok(())
+    }
+
+    #[test]
+    fn expand_reports_missing_arguments() {
+        let aliases = aliases(&[("feat", "create feature/{1}")]);
+
+        let error = expand(os_args(&["rsworktree", "feat"]), 1, &aliases)
+            .expect_err("missing argument should fail");
+
+        assert!(error.to_string().contains("expects at least 1 argument"));
+    }
+
+    #[test]
+    fn builtin_commands_are_never_shadowed() -> color_eyre::Result<()> {
+        let aliases = aliases(&[("ls", "rm everything")]);
+        let args = os_args(&["rsworktree", "ls"]);
+
+        assert!(is_builtin("ls"));
+        assert!(is_builtin("i"));
+        assert_eq!(expand(args.clone(), 1, &aliases)?, args);
+        Ok(())
+    }
+
+    #[test]
+    fn command_position_skips_global_flags() {
+        assert_eq!(
+            command_position(&os_args(&["rsworktree", "--plain", "ls"])),
+            Some(2)
+        );
+        assert_eq!(command_position(&os_args(&["rsworktree", "--help"])), None);
+        assert_eq!(
+            command_position(&os_args(&["rsworktree", "--host", "box", "draft"])),
+            Some(3)
+        );
+        assert_eq!(
+            command_position(&os_args(&["rsworktree", "--host=box:src/app", "draft"])),
+            Some(2)
+        );
+    }
+}
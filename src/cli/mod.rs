@@ -1,27 +1,74 @@
-use std::{env, path::PathBuf};
+mod alias;
+mod remote;
 
-use clap::{Parser, Subcommand};
+use std::{env, path::PathBuf, time::Duration};
+
+use clap::{ArgGroup, CommandFactory, Parser, Subcommand};
 
 use color_eyre::eyre::{self, WrapErr};
 
 use crate::{
-    GitProvider, Repo,
+    GitProvider, Repo, WorktreeManager,
     commands::{
+        archive::{ArchiveCommand, UnarchiveCommand},
         cd::CdCommand,
-        create::CreateCommand,
+        ci::CiCommand,
+        clean::CleanCommand,
+        completions::{CompleteCommand, CompletionKind, CompletionsCommand},
+        config::{ConfigCommand, ExcludeCommand},
+        create::{CreateCommand, pattern::pattern_name},
+        diff::DiffCommand,
+        doctor::DoctorCommand,
+        du::{ByteSize, DiskUsageCommand, DiskUsageSort},
+        editor::EditorCommand,
+        export::{ExportCommand, ExportFormat},
+        hooks::{HookListCommand, HookLogsCommand, HookRunCommand, HooksCommand},
+        info::InfoCommand,
         interactive,
-        list::ListCommand,
+        list::{Column, ListCommand},
+        lock::{LockCommand, UnlockCommand},
         merge::MergeCommand,
-        open::OpenCommand,
+        notes::{NotesCommand, NotesSyncCommand, NotesUpdate},
+        open::{OpenCommand, find_by_name},
+        pr::{CloseOptions, MessageSource, PrCheckoutCommand, PrCommand},
+        prune::PruneCommand,
+        recent::{self, RecentCommand},
+        rename::RenameCommand,
         review::{ReviewCommand, ReviewOptions},
-        rm::RemoveCommand,
+        rm::{LocalBranchStatus, RemoteBranchStatus, RemoveCommand},
+        shell_init::{Shell, ShellInitCommand},
+        stash::{StashCommand, StashPopCommand},
+        stats::StatsCommand,
+        status::StatusCommand,
+        switch::SwitchCommand,
+        sync::SyncCommand,
+        ui,
+        workspace::WorkspaceCommand,
     },
     editor::resolve_provider_preference,
+    hooks::HookName,
+    i18n,
+    issues::IssueTracker,
+    output::{self, OutputMode},
+    provider::{MergeStrategy, ReviewVerdict},
+    telemetry,
+    templates::Template,
 };
 
 #[derive(Parser, Debug)]
 #[command(name = "rsworktree", version, about = "Manage Git worktrees more easily", long_about = None)]
 pub struct Cli {
+    /// Disable colors and decorations and label every line of output.
+    #[arg(long, global = true)]
+    plain: bool,
+    /// Print long-running steps as plain lines instead of spinners, as in CI logs.
+    #[arg(long = "no-progress", visible_alias = "quiet", global = true)]
+    no_progress: bool,
+    /// Run the command with rsworktree on another machine over ssh, in the
+    /// repository at <dir> when given; `worktree open` opens it in a local
+    /// VS Code or Cursor attached to the host.
+    #[arg(long, global = true, value_name = "host[:dir]")]
+    host: Option<remote::RemoteHost>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -31,21 +78,92 @@ enum Commands {
     /// Create a worktree under the repo-local `.rsworktree` directory.
     Create(CreateArgs),
     /// List worktrees managed in `.rsworktree`.
-    Ls,
+    Ls(LsArgs),
     /// Open a shell in the given worktree.
     Cd(CdArgs),
+    /// Change the current shell's directory to a worktree (needs `shell-init`).
+    Switch(SwitchArgs),
+    /// List the worktrees opened last, or open one of them by its number.
+    Recent(RecentArgs),
+    /// Print the shell function that lets `switch` change directories.
+    ShellInit(ShellInitArgs),
+    /// Print a shell completion script, including worktree and branch names.
+    Completions(CompletionsArgs),
+    /// Print worktree or branch names for the completion scripts.
+    #[command(name = "__complete", hide = true)]
+    Complete(CompleteArgs),
+    /// Show everything known about a worktree.
+    Info(InfoArgs),
+    /// Show branch, upstream, changes and PR/MR state of every worktree.
+    Status(StatusArgs),
+    /// Export a worktree as a git bundle or a tarball.
+    Export(ExportArgs),
+    /// Compare the branches or, with `--working`, the working trees of two worktrees.
+    Diff(DiffArgs),
+    /// Run `git config` in a worktree, with `--worktree` for per-worktree settings.
+    Config(ConfigArgs),
+    /// Add patterns to a worktree's own exclude file, or list them.
+    Exclude(ExcludeArgs),
+    /// Configure the editor `worktree open` uses.
+    #[command(subcommand)]
+    Editor(EditorCommands),
     /// Interactively browse and open worktrees.
     #[command(alias = "i")]
     Interactive,
+    /// Browse worktrees with live git status, open, create and remove them, and view their diff and log.
+    Ui,
     /// Worktree scoped commands.
     #[command(subcommand)]
     Worktree(WorktreeCommands),
     /// Remove a worktree tracked in `.rsworktree`.
+    #[command(alias = "remove")]
     Rm(RmArgs),
-    /// Create a pull/merge request for the worktree's branch (supports GitHub and GitLab).
+    /// Rename a worktree, keeping git's worktree metadata intact.
+    #[command(alias = "mv")]
+    Rename(RenameArgs),
+    /// Save a worktree's branch, changes and untracked files to `.rsworktree/archive` and remove it.
+    Archive(ArchiveArgs),
+    /// Recreate an archived worktree.
+    Unarchive(UnarchiveArgs),
+    /// Lock a worktree so `rm` and `prune` leave it alone, e.g. on a removable drive.
+    Lock(LockArgs),
+    /// Unlock a worktree locked with `lock` or `git worktree lock`.
+    Unlock(UnlockArgs),
+    /// Stash the uncommitted changes of a worktree, or restore them with `stash pop <worktree>`.
+    Stash(StashArgs),
+    /// Remove worktrees that are merged, lost their upstream branch, are missing or went stale.
+    Prune(PruneArgs),
+    /// Create a pull/merge request for the worktree's branch (supports GitHub, GitLab, Bitbucket, Gitea and Azure DevOps).
     Review(ReviewArgs),
-    /// Merge the pull/merge request for the current or named worktree (supports GitHub and GitLab).
+    /// Merge the pull/merge request for the current or named worktree (supports GitHub, GitLab, Bitbucket, Gitea and Azure DevOps).
     Merge(MergeArgs),
+    /// Start and follow CI runs for a worktree's branch (supports GitHub, GitLab, Bitbucket, Gitea and Azure DevOps).
+    #[command(subcommand)]
+    Ci(CiCommands),
+    /// Act on the pull/merge request of a worktree's branch (supports GitHub, GitLab, Bitbucket, Gitea and Azure DevOps).
+    #[command(subcommand)]
+    Pr(PrCommands),
+    /// Annotate worktrees and share the annotations through git notes.
+    #[command(subcommand)]
+    Notes(NotesCommands),
+    /// Write the note of a worktree in `$VISUAL` or `$EDITOR`, like `notes edit`.
+    Note(NotesEditArgs),
+    /// Install and update a shared set of hooks from a git URL or directory.
+    #[command(subcommand)]
+    Hooks(HooksCommands),
+    /// Summarize locally recorded editor launches, hook runs, setup steps and worktree usage.
+    Stats(StatsArgs),
+    /// Report the disk usage of every worktree and of the shared object store.
+    Du(DuArgs),
+    /// Delete build artifacts such as `target/` and `node_modules/` from worktrees.
+    Clean(CleanArgs),
+    /// Check git, the provider CLI, the editor, tmux, hooks and configuration, and suggest fixes.
+    Doctor(DoctorArgs),
+    /// Fetch and fast-forward every worktree whose branch is behind its upstream.
+    Sync(SyncArgs),
+    /// Create and open a worktree in every repository of a workspace from the per-user config.toml.
+    #[command(subcommand, alias = "workspace")]
+    Ws(WsCommands),
 }
 
 #[derive(Subcommand, Debug)]
@@ -54,13 +172,139 @@ enum WorktreeCommands {
     Open(OpenArgs),
 }
 
+#[derive(Subcommand, Debug)]
+enum EditorCommands {
+    /// Set the editor for the repository or, with `--worktree`, for one worktree.
+    Set(EditorSetArgs),
+}
+
+#[derive(Subcommand, Debug)]
+enum CiCommands {
+    /// Start a CI run for the worktree's branch.
+    Trigger(CiTriggerArgs),
+    /// Show the latest CI run for the worktree's branch.
+    Status(CiStatusArgs),
+}
+
+#[derive(Subcommand, Debug)]
+enum PrCommands {
+    /// Push the worktree's branch and open a pull/merge request, like `review`.
+    #[command(alias = "submit")]
+    Create(ReviewArgs),
+    /// Post a comment on the pull/merge request.
+    Comment(PrCommentArgs),
+    /// Approve the pull/merge request or request changes.
+    Review(PrReviewArgs),
+    /// Close the pull/merge request without merging and optionally clean up.
+    Close(PrCloseArgs),
+    /// Check out a pull/merge request into a new worktree named after its branch and open it.
+    Checkout(PrCheckoutArgs),
+}
+
+#[derive(Subcommand, Debug)]
+enum NotesCommands {
+    /// Show the annotations of a worktree.
+    Show(NotesShowArgs),
+    /// Set or clear the annotations of a worktree.
+    Set(NotesSetArgs),
+    /// Write the note of a worktree in `$VISUAL` or `$EDITOR`.
+    Edit(NotesEditArgs),
+    /// Merge annotations with `refs/notes/rsworktree` on a remote and push them.
+    Sync(NotesSyncArgs),
+}
+
+#[derive(Subcommand, Debug)]
+enum HooksCommands {
+    /// Fetch hooks listed in a source's `rsworktree-hooks.json`, verify and install them.
+    Install(HooksInstallArgs),
+    /// Reinstall hooks from the source recorded by `hooks install`.
+    Update(HooksUpdateArgs),
+    /// List the hook output captured with `hooks.captureOutput`, or print one log.
+    Logs(HooksLogsArgs),
+    /// Run a hook for an existing worktree, or show what it would run.
+    Run(HooksRunArgs),
+    /// List the hooks, whether their scripts are executable and what else they run.
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+enum WsCommands {
+    /// Create the worktree and branch in every repository of the workspace.
+    Create(WsCreateArgs),
+    /// Open the worktrees of the workspace as tmux windows or one multi-root editor window.
+    Open(WsOpenArgs),
+}
+
 #[derive(Parser, Debug)]
 struct CreateArgs {
     /// Name of the worktree (also used as the branch name)
-    name: String,
+    #[arg(required_unless_present = "issue")]
+    name: Option<String>,
     /// Branch to base the new worktree branch on
-    #[arg(long)]
+    #[arg(long, value_name = "branch")]
     base: Option<String>,
+    /// Succeed when the worktree or branch already exists and matches the request
+    #[arg(long = "exists-ok")]
+    exists_ok: bool,
+    /// Re-run the post-create hook when the worktree already exists
+    #[arg(long = "rerun-setup", requires = "exists_ok")]
+    rerun_setup: bool,
+    /// Apply a patch or mailbox file (`-` for stdin) to the new worktree
+    #[arg(long, value_name = "patch", conflicts_with = "exists_ok")]
+    apply: Option<PathBuf>,
+    /// Import the branch from a git bundle written by `rsworktree export`
+    #[arg(
+        long = "from-bundle",
+        value_name = "bundle",
+        conflicts_with_all = ["base", "exists_ok"]
+    )]
+    from_bundle: Option<PathBuf>,
+    /// Check out a remote branch (`<branch>` on origin or `<remote>/<branch>`) as a tracking branch
+    #[arg(
+        long = "from-remote",
+        value_name = "branch",
+        conflicts_with_all = ["base", "from_bundle", "exists_ok"]
+    )]
+    from_remote: Option<String>,
+    /// Apply `.rsworktree/templates/<name>.toml` (base branch, name prefix, files to copy and hooks)
+    #[arg(long, value_name = "name")]
+    template: Option<String>,
+    /// Copy untracked files matching a glob (e.g. `.env*`) from the main worktree; repeatable
+    #[arg(long, value_name = "glob")]
+    copy: Vec<String>,
+    /// Check out only these directories (and the files at the root) with a sparse checkout
+    #[arg(long, value_name = "path", num_args = 1.., conflicts_with = "exists_ok")]
+    sparse: Vec<String>,
+    /// Check out every file even when `worktree.sparse` is set in `preferences.json`
+    #[arg(long = "no-sparse", conflicts_with = "sparse")]
+    no_sparse: bool,
+    /// Leave submodules uninitialized (overrides `worktree.submodules`)
+    #[arg(long = "no-submodules")]
+    no_submodules: bool,
+    /// Fetch only the commit each submodule is pinned to (`git submodule update --depth 1`)
+    #[arg(long = "shallow-submodules", conflicts_with = "no_submodules")]
+    shallow_submodules: bool,
+    /// Kill the post-create hook after this many seconds (overrides `hooks.timeout`; 0 waits forever)
+    #[arg(long = "hook-timeout", value_name = "seconds")]
+    hook_timeout: Option<u64>,
+    /// Name the worktree after a Jira or Linear issue (e.g. `PROJ-123`) and record its key for `review`
+    #[arg(long, value_name = "key")]
+    issue: Option<String>,
+    /// Kind of change filled into `{type}` of `branch_pattern` (e.g. `fix` or `feat`)
+    #[arg(long = "type", value_name = "type", conflicts_with = "issue")]
+    kind: Option<String>,
+    /// Borrow objects from another clone of the repository (git alternates) instead of storing them twice
+    #[arg(long, value_name = "path")]
+    reference: Option<PathBuf>,
+    /// Fail instead of waiting when another rsworktree command is changing the worktrees
+    #[arg(long = "no-wait")]
+    no_wait: bool,
+    /// Print a tab-separated `created` or `exists` record instead of entering the worktree
+    #[arg(long)]
+    porcelain: bool,
+    /// Print the name, path, branch, base, hooks run and duration as JSON instead of entering the worktree
+    #[arg(long, conflicts_with = "porcelain")]
+    json: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -72,23 +316,460 @@ struct CdArgs {
     print: bool,
 }
 
+#[derive(Parser, Debug)]
+struct LsArgs {
+    /// Print one line per worktree from a template, e.g. '{name}\t{branch}\t{ahead}/{behind}'
+    #[arg(long, value_name = "template")]
+    format: Option<String>,
+    /// Print the worktrees as a JSON array (name, path, branch, head, dirty, last_commit_at)
+    #[arg(long, conflicts_with = "format")]
+    json: bool,
+    /// Columns of the table, comma separated (name, branch, age, state, sync, pr)
+    #[arg(
+        long,
+        value_name = "columns",
+        value_delimiter = ',',
+        conflicts_with_all = ["format", "json"]
+    )]
+    columns: Vec<Column>,
+    /// Add the creation time, base branch and note of every worktree
+    #[arg(short, long, conflicts_with_all = ["format", "json", "columns"])]
+    long: bool,
+    /// Add a column with the pull/merge request of every worktree
+    #[arg(long, conflicts_with_all = ["format", "json"])]
+    remote: bool,
+    /// Add a column with how many stash entries were made on every worktree's branch
+    #[arg(long, conflicts_with_all = ["format", "json", "porcelain"])]
+    stashes: bool,
+    /// Ignore cached pull/merge request states and query the provider again
+    #[arg(long, conflicts_with = "json")]
+    refresh: bool,
+    /// Git provider to query for `{pr.*}` placeholders and the PR column (github, gitlab, bitbucket, gitea, azuredevops or gerrit)
+    #[arg(long, value_name = "provider")]
+    provider: Option<String>,
+    /// Print one tab-separated `worktree` record per worktree, in a format kept stable for scripts
+    #[arg(long, conflicts_with_all = ["format", "json", "columns", "long", "remote"])]
+    porcelain: bool,
+}
+
+#[derive(Parser, Debug)]
+struct InfoArgs {
+    /// Name of the worktree to describe (defaults to the current worktree)
+    name: Option<String>,
+    /// Git provider to query for the PR state (github, gitlab, bitbucket, gitea, azuredevops or gerrit)
+    #[arg(long, value_name = "provider")]
+    provider: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct PruneArgs {
+    /// Prune without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+    /// Only list the worktrees that would be pruned
+    #[arg(long, conflicts_with = "yes")]
+    dry_run: bool,
+    /// Also prune worktrees without a commit for this many days
+    #[arg(long, value_name = "days")]
+    older_than: Option<u64>,
+    /// Also prune locked worktrees
+    #[arg(long)]
+    force: bool,
+    /// Fail instead of waiting when another rsworktree command is changing the worktrees
+    #[arg(long = "no-wait")]
+    no_wait: bool,
+}
+
+#[derive(Parser, Debug)]
+struct StatusArgs {
+    /// Git provider to query for open PRs/MRs (github, gitlab, bitbucket, gitea, azuredevops or gerrit)
+    #[arg(long, value_name = "provider")]
+    provider: Option<String>,
+    /// Ignore cached pull/merge request states and query the provider again
+    #[arg(long)]
+    refresh: bool,
+    /// Print one tab-separated `status` record per worktree, in a format kept stable for scripts
+    #[arg(long)]
+    porcelain: bool,
+}
+
+#[derive(Parser, Debug)]
+struct ExportArgs {
+    /// Name of the worktree to export (defaults to the current worktree)
+    name: Option<String>,
+    /// Write a git bundle of the worktree branch (default)
+    #[arg(long, conflicts_with = "tar")]
+    bundle: bool,
+    /// Write a gzipped tarball of the working tree without ignored files
+    #[arg(long)]
+    tar: bool,
+    /// Output file (defaults to `<name>.bundle` or `<name>.tar.gz` in the current directory)
+    #[arg(short, long, value_name = "path")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct NotesShowArgs {
+    /// Name of the worktree (defaults to the current worktree)
+    name: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct NotesSetArgs {
+    /// Name of the worktree (defaults to the current worktree)
+    name: Option<String>,
+    /// Purpose of the worktree; an empty value clears it
+    #[arg(long, value_name = "text")]
+    note: Option<String>,
+    /// Link to the pull/merge request; an empty value clears it
+    #[arg(long, value_name = "url")]
+    pr: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct NotesEditArgs {
+    /// Name of the worktree (defaults to the current worktree)
+    name: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct NotesSyncArgs {
+    /// Remote to exchange annotations with
+    #[arg(long, default_value = "origin")]
+    remote: String,
+}
+
+#[derive(Parser, Debug)]
+struct HooksInstallArgs {
+    /// Git URL or local directory containing `rsworktree-hooks.json`
+    source: String,
+    /// Branch or tag to fetch from a git source
+    #[arg(long = "ref", value_name = "ref")]
+    reference: Option<String>,
+    /// Overwrite hooks that were added or changed locally
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Parser, Debug)]
+struct HooksUpdateArgs {
+    /// Overwrite hooks that were changed locally
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Parser, Debug)]
+struct HooksLogsArgs {
+    /// Print the most recent log whose name starts with this, e.g. `post-create`
+    #[arg(value_name = "log")]
+    name: Option<String>,
+    /// Print the path of the log instead of its contents
+    #[arg(long, requires = "name")]
+    path: bool,
+}
+
+#[derive(Parser, Debug)]
+struct HooksRunArgs {
+    /// Hook to run: pre-create, post-create, pre-remove or post-remove
+    hook: HookName,
+    /// Name of the worktree (defaults to the current worktree)
+    #[arg(short, long, value_name = "worktree")]
+    worktree: Option<String>,
+    /// Print the script, commands, tasks and environment instead of running them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Parser, Debug)]
+struct SwitchArgs {
+    /// Name of the worktree to switch to; short names like `login` match `feature/login`, `-` is the previous one
+    #[arg(value_name = "worktree")]
+    name: String,
+}
+
+#[derive(Parser, Debug)]
+struct RecentArgs {
+    /// Number of a worktree in the listing to open instead of listing them
+    #[arg(value_name = "number")]
+    number: Option<usize>,
+    /// Number of worktrees to list
+    #[arg(short = 'n', long, value_name = "count", default_value_t = recent::DEFAULT_LIMIT)]
+    limit: usize,
+    /// Order by how often the worktrees were opened instead of when
+    #[arg(long = "most-used")]
+    most_used: bool,
+}
+
+#[derive(Parser, Debug)]
+struct ShellInitArgs {
+    /// Shell to generate the function for (bash, zsh, fish or powershell)
+    shell: Shell,
+}
+
+#[derive(Parser, Debug)]
+struct CompletionsArgs {
+    /// Shell to generate completions for (bash, zsh, fish or powershell)
+    shell: Shell,
+}
+
+#[derive(Parser, Debug)]
+struct CompleteArgs {
+    /// Values to print (worktrees or branches)
+    kind: CompletionKind,
+}
+
+#[derive(Parser, Debug)]
+struct StatsArgs {
+    /// Show the statistics as an interactive terminal dashboard
+    #[arg(long)]
+    ui: bool,
+}
+
+#[derive(Parser, Debug)]
+struct DiffArgs {
+    /// Worktree to compare from
+    from: String,
+    /// Worktree to compare to
+    to: String,
+    /// Compare the working trees, uncommitted and untracked files included
+    #[arg(long)]
+    working: bool,
+    /// Print a diffstat instead of the patch
+    #[arg(long)]
+    stat: bool,
+    /// Open the differences in `git difftool` with this tool (e.g. `meld`)
+    #[arg(long, value_name = "tool", conflicts_with = "stat")]
+    tool: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct DuArgs {
+    /// Order of the worktrees (size, largest first, or name)
+    #[arg(long, value_name = "order", default_value = "size")]
+    sort: DiskUsageSort,
+    /// Hide worktrees smaller than this, e.g. 500M or 2G
+    #[arg(long, value_name = "size")]
+    min_size: Option<ByteSize>,
+    /// Leave out files ignored by git, such as `target/` or `node_modules/`
+    #[arg(long)]
+    skip_ignored: bool,
+}
+
+#[derive(Parser, Debug)]
+struct CleanArgs {
+    /// Name of the worktree to clean (defaults to the current worktree)
+    name: Option<String>,
+    /// Clean every worktree
+    #[arg(long, conflicts_with = "name")]
+    all: bool,
+    /// Only list the artifact directories and how much space they take
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Parser, Debug)]
+struct DoctorArgs {
+    /// Git provider whose CLI is checked (github, gitlab, bitbucket, gitea, azuredevops or gerrit)
+    #[arg(long, value_name = "provider")]
+    provider: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct SyncArgs {
+    /// Keep syncing on an interval and whenever the main worktree's branch moves
+    #[arg(long)]
+    watch: bool,
+    /// Seconds between two syncs with `--watch`
+    #[arg(
+        long,
+        value_name = "seconds",
+        default_value_t = 300,
+        requires = "watch"
+    )]
+    interval: u64,
+    /// Fail instead of waiting when another rsworktree command is changing the worktrees
+    #[arg(long = "no-wait")]
+    no_wait: bool,
+}
+
+#[derive(Parser, Debug)]
+struct WsCreateArgs {
+    /// Name of the worktree and branch to create in each repository
+    name: String,
+    /// Branch to start from in each repository (defaults to its current `HEAD`)
+    #[arg(long)]
+    base: Option<String>,
+    /// Workspace to use (defaults to the one containing the current repository)
+    #[arg(short, long)]
+    workspace: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct WsOpenArgs {
+    /// Name of the worktree to open in each repository
+    name: String,
+    /// Workspace to use (defaults to the one containing the current repository)
+    #[arg(short, long)]
+    workspace: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct ConfigArgs {
+    /// Read and write the worktree's own `config.worktree`, enabling
+    /// `extensions.worktreeConfig` when needed
+    #[arg(long)]
+    worktree: bool,
+    /// Name of the worktree (defaults to the current worktree)
+    #[arg(short, long, value_name = "worktree")]
+    name: Option<String>,
+    /// Arguments passed to `git config`
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+struct EditorSetArgs {
+    /// Only open this worktree with the editor
+    #[arg(long, value_name = "name")]
+    worktree: Option<String>,
+    /// Open a shell instead; the command, if given, is the terminal emulator to start it in
+    #[arg(long)]
+    terminal: bool,
+    /// Editor command followed by its arguments, e.g. `code --new-window`
+    #[arg(
+        required_unless_present = "terminal",
+        trailing_var_arg = true,
+        allow_hyphen_values = true
+    )]
+    command: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+struct ExcludeArgs {
+    /// Name of the worktree (defaults to the current worktree)
+    #[arg(short, long, value_name = "worktree")]
+    name: Option<String>,
+    /// Patterns to exclude; lists the current patterns when omitted
+    patterns: Vec<String>,
+}
+
 #[derive(Parser, Debug)]
 struct RmArgs {
-    /// Name of the worktree to remove
+    /// Name of the worktree to remove (as accepted by `open`)
+    #[arg(value_name = "worktree")]
     name: String,
-    /// Force removal even if the worktree has uncommitted changes
+    /// Force removal even if the worktree has uncommitted changes or is locked
     #[arg(long)]
     force: bool,
+    /// Also delete the worktree's local branch
+    #[arg(long = "delete-branch")]
+    delete_branch: bool,
+    /// Also delete the local branch and, once merged (or with `--force`), the remote branch
+    #[arg(long)]
+    remote: bool,
+    /// Fail instead of waiting when another rsworktree command is changing the worktrees
+    #[arg(long = "no-wait")]
+    no_wait: bool,
+    /// Print a tab-separated `removed` or `absent` record instead of the progress messages
+    #[arg(long)]
+    porcelain: bool,
+}
+
+#[derive(Parser, Debug)]
+struct ArchiveArgs {
+    /// Name of the worktree to archive (as accepted by `open`)
+    #[arg(required_unless_present = "list")]
+    name: Option<String>,
+    /// List the archived worktrees instead
+    #[arg(long, conflicts_with = "name")]
+    list: bool,
+}
+
+#[derive(Parser, Debug)]
+struct UnarchiveArgs {
+    /// Name of the archived worktree, as shown by `archive --list`
+    name: String,
+}
+
+#[derive(Parser, Debug)]
+struct LockArgs {
+    /// Name of the worktree to lock (as accepted by `open`)
+    #[arg(value_name = "worktree")]
+    name: String,
+    /// Why the worktree is locked, shown by `git worktree list`
+    #[arg(long)]
+    reason: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct UnlockArgs {
+    /// Name of the worktree to unlock (as accepted by `open`)
+    #[arg(value_name = "worktree")]
+    name: String,
+}
+
+#[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct StashArgs {
+    #[command(subcommand)]
+    command: Option<StashCommands>,
+    /// Name of the worktree whose changes to stash (as accepted by `open`)
+    #[arg(value_name = "worktree", required = true)]
+    name: Option<String>,
+    /// Describe the stash entry with this message
+    #[arg(short, long)]
+    message: Option<String>,
+    /// Stash untracked files too
+    #[arg(short = 'u', long)]
+    include_untracked: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum StashCommands {
+    /// Restore the newest changes stashed from a worktree's branch.
+    Pop(StashPopArgs),
+}
+
+#[derive(Parser, Debug)]
+struct StashPopArgs {
+    /// Name of the worktree to restore the changes of (as accepted by `open`)
+    #[arg(value_name = "worktree")]
+    name: String,
+}
+
+#[derive(Parser, Debug)]
+struct RenameArgs {
+    /// Name of the worktree to rename (as accepted by `open`)
+    #[arg(value_name = "worktree")]
+    name: String,
+    /// New name under `.rsworktree`
+    #[arg(value_name = "new-name")]
+    new_name: String,
+    /// Also rename the worktree's branch to the new name
+    #[arg(short = 'm', long = "move-branch")]
+    move_branch: bool,
+    /// Fail instead of waiting when another rsworktree command is changing the worktrees
+    #[arg(long = "no-wait")]
+    no_wait: bool,
 }
 
 #[derive(Parser, Debug)]
 struct OpenArgs {
-    /// Name of the worktree to open
-    #[arg(required_unless_present = "path")]
+    /// Name of the worktree to open, `-` for the previous one; pick one interactively when omitted
+    #[arg(value_name = "worktree")]
     name: Option<String>,
     /// Open a worktree by absolute path instead of managed name
     #[arg(long, value_name = "path", conflicts_with = "name")]
     path: Option<PathBuf>,
+    /// Fail instead of showing the picker when no worktree is given
+    #[arg(long = "no-interactive")]
+    no_interactive: bool,
+    /// Open a shell in the worktree instead of the editor
+    #[arg(long)]
+    shell: bool,
+    /// Block until the editor is closed and fail when it exits with an error
+    #[arg(long, conflicts_with = "shell")]
+    wait: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -110,10 +791,11 @@ struct ReviewArgs {
     /// Remote to push the branch to before creating the PR
     #[arg(long, default_value = "origin")]
     remote: String,
-    /// Request reviews from the given user handles
-    #[arg(long = "reviewer", value_name = "login")]
+    /// Request reviews from the given user handles; `auto` picks the CODEOWNERS
+    /// of the changed files, or `reviewers` from config.toml
+    #[arg(long = "reviewer", alias = "reviewers", value_name = "login")]
     reviewers: Vec<String>,
-    /// Git provider to use (github or gitlab)
+    /// Git provider to use (github, gitlab, bitbucket, gitea, azuredevops or gerrit)
     #[arg(long, value_name = "provider")]
     provider: Option<String>,
     /// Additional arguments passed directly to `gh pr create` or `glab mr create`
@@ -128,72 +810,590 @@ struct MergeArgs {
     /// Remove the remote branch after merging
     #[arg(long = "remove")]
     remove_remote: bool,
-    /// Git provider to use (github or gitlab)
+    /// Update the base branch, then remove the worktree and its local branch after merging
+    #[arg(long)]
+    cleanup: bool,
+    /// With --cleanup, stash uncommitted changes of the checked-out base branch while pulling it
+    #[arg(long, requires = "cleanup")]
+    autostash: bool,
+    /// How to merge (merge, squash or rebase; defaults to `merge_strategy` in config.toml, else merge)
+    #[arg(long, value_name = "strategy")]
+    strategy: Option<MergeStrategy>,
+    /// Git provider to use (github, gitlab, bitbucket, gitea, azuredevops or gerrit)
+    #[arg(long, value_name = "provider")]
+    provider: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct CiTriggerArgs {
+    /// Name of the worktree to run CI for (defaults to the current worktree)
+    name: Option<String>,
+    /// GitHub Actions workflow file or name to dispatch (defaults to `ci.workflow`)
+    #[arg(long, value_name = "workflow")]
+    workflow: Option<String>,
+    /// Git provider to use (github, gitlab, bitbucket, gitea or azuredevops)
+    #[arg(long, value_name = "provider")]
+    provider: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct CiStatusArgs {
+    /// Name of the worktree to show CI for (defaults to the current worktree)
+    name: Option<String>,
+    /// Keep polling until the run has finished
+    #[arg(long)]
+    watch: bool,
+    /// Seconds between two polls with `--watch`
+    #[arg(long, value_name = "seconds", default_value_t = 10)]
+    interval: u64,
+    /// Git provider to use (github, gitlab, bitbucket, gitea or azuredevops)
+    #[arg(long, value_name = "provider")]
+    provider: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct PrCommentArgs {
+    /// Name of the worktree whose PR to comment on (defaults to the current worktree)
+    name: Option<String>,
+    /// Comment text; opens the git editor when neither this nor `--file` is given
+    #[arg(short, long, value_name = "text", conflicts_with = "file")]
+    message: Option<String>,
+    /// Read the comment from a file (`-` for standard input)
+    #[arg(short = 'F', long, value_name = "path")]
+    file: Option<PathBuf>,
+    /// Git provider to use (github, gitlab, bitbucket, gitea or azuredevops)
+    #[arg(long, value_name = "provider")]
+    provider: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+#[command(group(ArgGroup::new("verdict").required(true).args(["approve", "request_changes"])))]
+struct PrReviewArgs {
+    /// Name of the worktree whose PR to review (defaults to the current worktree)
+    name: Option<String>,
+    /// Approve the pull/merge request
+    #[arg(long)]
+    approve: bool,
+    /// Request changes; opens the git editor when no message is given
+    #[arg(long)]
+    request_changes: bool,
+    /// Review message
+    #[arg(short, long, value_name = "text", conflicts_with = "file")]
+    message: Option<String>,
+    /// Read the review message from a file (`-` for standard input)
+    #[arg(short = 'F', long, value_name = "path")]
+    file: Option<PathBuf>,
+    /// Git provider to use (github, gitlab, bitbucket, gitea or azuredevops)
+    #[arg(long, value_name = "provider")]
+    provider: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct PrCheckoutArgs {
+    /// Number of the pull/merge request
+    number: u64,
+    /// Only create the worktree without opening it in the editor
+    #[arg(long = "no-open")]
+    no_open: bool,
+    /// Git provider to use (github, gitlab, bitbucket, gitea or azuredevops)
+    #[arg(long, value_name = "provider")]
+    provider: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct PrCloseArgs {
+    /// Name of the worktree whose PR to close (defaults to the current worktree)
+    name: Option<String>,
+    /// Comment explaining why the PR is closed
+    #[arg(short, long, value_name = "text")]
+    comment: Option<String>,
+    /// Delete the branch on its remote
+    #[arg(long)]
+    delete_remote: bool,
+    /// Remove the worktree and its local branch
+    #[arg(long)]
+    remove: bool,
+    /// Same as `--delete-remote --remove`
+    #[arg(long)]
+    cleanup: bool,
+    /// Remove the worktree even if it has uncommitted changes
+    #[arg(long)]
+    force: bool,
+    /// Do not ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+    /// Git provider to use (github, gitlab, bitbucket, gitea or azuredevops)
     #[arg(long, value_name = "provider")]
     provider: Option<String>,
 }
 
 pub fn run() -> color_eyre::Result<()> {
-    let cli = Cli::parse();
+    let args = alias::expand_aliases(env::args_os().collect())?;
+    let cli = Cli::parse_from(&args);
+    output::init(OutputMode::detect(cli.plain));
+    if cli.no_progress {
+        output::disable_progress();
+    }
+    output::install_error_hook()?;
+    telemetry::init_tracing();
+    let args_label = args
+        .iter()
+        .skip(1)
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let _span = tracing::info_span!("rsworktree", args = %args_label).entered();
+    // Runs from shell profiles, which are usually outside any repository.
+    match &cli.command {
+        Commands::ShellInit(args) => return ShellInitCommand::new(args.shell).execute(),
+        Commands::Completions(args) => {
+            return CompletionsCommand::new(args.shell, Cli::command()).execute();
+        }
+        Commands::Complete(args) => return CompleteCommand::new(args.kind).execute(),
+        _ => {}
+    }
+    if let Some(host) = &cli.host {
+        return remote::run(host, &cli.command, &args);
+    }
+    // Workspaces span repositories, so they also run outside of one.
+    if let Commands::Ws(command) = &cli.command {
+        return workspace(command);
+    }
     let repo = Repo::discover()?;
+    i18n::init(i18n::resolve_locale_preference(&repo));
 
     match cli.command {
         Commands::Create(args) => {
-            let command = CreateCommand::new(args.name, args.base);
-            command.execute(&repo)?;
+            let template = args
+                .template
+                .map(|name| Template::load(&repo.rsworktree_dir(), &name))
+                .transpose()?;
+            let (name, issue) = match args.issue {
+                Some(key) => {
+                    let tracker = IssueTracker::load(&repo.rsworktree_dir())?;
+                    let issue = tracker.fetch(&key)?;
+                    let name = args.name.unwrap_or_else(|| tracker.worktree_name(&issue));
+                    (name, Some(issue))
+                }
+                None => {
+                    let name = args.name.unwrap_or_default();
+                    // A branch from a remote or a bundle keeps its name.
+                    let pattern = repo
+                        .config()?
+                        .branch_pattern
+                        .filter(|_| args.from_remote.is_none() && args.from_bundle.is_none());
+                    let name = match pattern {
+                        Some(pattern) => pattern_name(
+                            &repo,
+                            &pattern,
+                            &name,
+                            args.kind.as_deref(),
+                            args.exists_ok,
+                        )?,
+                        None if args.kind.is_some() => {
+                            return Err(eyre::eyre!(
+                                "`--type` needs a `branch_pattern` in `config.toml`"
+                            ));
+                        }
+                        None => name,
+                    };
+                    (name, None)
+                }
+            };
+            let command = CreateCommand::new(name, args.base)
+                .with_exists_ok(args.exists_ok)
+                .with_rerun_setup(args.rerun_setup)
+                .with_patch(args.apply)
+                .with_bundle(args.from_bundle)
+                .with_from_remote(args.from_remote)
+                .with_reference(args.reference)
+                .with_template(template)
+                .with_copy(args.copy)
+                .with_sparse(args.sparse)
+                .with_full_checkout(args.no_sparse)
+                .with_no_submodules(args.no_submodules)
+                .with_shallow_submodules(args.shallow_submodules)
+                .with_hook_timeout(args.hook_timeout.map(Duration::from_secs))
+                .with_issue(issue)
+                .with_no_wait(args.no_wait);
+            if args.json {
+                let report = output::silenced(|| command.create_with_report(&repo, true))?;
+                let json = serde_json::to_string_pretty(&report.to_json())
+                    .wrap_err("failed to serialize the created worktree")?;
+                println!("{json}");
+            } else if args.porcelain {
+                let report = output::silenced(|| command.create_with_report(&repo, true))?;
+                output::porcelain::print([output::porcelain::record(
+                    report.outcome.as_str(),
+                    [report.name, report.path.display().to_string()],
+                )]);
+            } else {
+                command.execute(&repo)?;
+            }
         }
-        Commands::Ls => {
-            let command = ListCommand;
+        Commands::Ls(args) => {
+            let provider = resolve_provider(&args.provider, &repo)?;
+            let mut command = ListCommand::new(&repo, provider)
+                .with_format(args.format)
+                .with_json(args.json)
+                .with_columns(args.columns)
+                .with_long(args.long)
+                .with_porcelain(args.porcelain)
+                .with_remote(args.remote)
+                .with_stashes(args.stashes)
+                .with_refresh(args.refresh);
             command.execute(&repo)?;
         }
         Commands::Cd(args) => {
             let command = CdCommand::new(args.name, args.print);
             command.execute(&repo)?;
         }
-        Commands::Interactive => {
-            interactive::run(&repo)?;
+        Commands::Switch(args) => {
+            SwitchCommand::new(args.name).execute(&repo)?;
         }
-        Commands::Worktree(command) => match command {
-            WorktreeCommands::Open(args) => {
-                let command = OpenCommand::new(args.name, args.path);
-                command.execute(&repo)?;
-            }
-        },
-        Commands::Rm(args) => {
-            let command = RemoveCommand::new(args.name, args.force);
-            let _ = command.execute(&repo)?;
+        Commands::Recent(args) => {
+            RecentCommand::new()
+                .with_limit(args.limit)
+                .with_most_used(args.most_used)
+                .with_pick(args.number)
+                .execute(&repo)?;
+        }
+        Commands::ShellInit(_)
+        | Commands::Completions(_)
+        | Commands::Complete(_)
+        | Commands::Ws(_) => {
+            unreachable!("handled before discovering the repository")
+        }
+        Commands::Info(args) => {
+            let worktree_name = resolve_worktree_name(args.name, &repo, "info")?;
+            let provider = resolve_provider(&args.provider, &repo)?;
+            let mut command = InfoCommand::new(worktree_name, provider, &repo);
+            command.execute(&repo)?;
+        }
+        Commands::Prune(args) => {
+            PruneCommand::new()
+                .with_yes(args.yes)
+                .with_dry_run(args.dry_run)
+                .with_stale_days(args.older_than)
+                .with_force(args.force)
+                .with_no_wait(args.no_wait)
+                .execute(&repo)?;
         }
-        Commands::Review(args) => {
-            let worktree_name = resolve_worktree_name(args.name, &repo, "review")?;
+        Commands::Status(args) => {
             let provider = resolve_provider(&args.provider, &repo)?;
-            let options = ReviewOptions {
-                name: worktree_name,
-                push: !args.no_push,
-                draft: args.draft,
-                fill: args.fill,
-                web: args.web,
-                remote: args.remote,
-                reviewers: args.reviewers,
-                extra_args: args.extra,
-                provider,
+            StatusCommand::new(provider, &repo)
+                .with_refresh(args.refresh)
+                .with_porcelain(args.porcelain)
+                .execute(&repo)?;
+        }
+        Commands::Export(args) => {
+            let worktree_name = resolve_worktree_name(args.name, &repo, "export")?;
+            let format = if args.tar {
+                ExportFormat::Tar
+            } else {
+                ExportFormat::Bundle
             };
-            let mut command = ReviewCommand::new(options);
+            let command = ExportCommand::new(worktree_name, format).with_output(args.output);
             command.execute(&repo)?;
         }
+        Commands::Diff(args) => {
+            DiffCommand::new(args.from, args.to)
+                .with_working(args.working)
+                .with_stat(args.stat)
+                .with_tool(args.tool)
+                .execute(&repo)?;
+        }
+        Commands::Config(args) => {
+            let worktree_name = resolve_worktree_name(args.name, &repo, "config")?;
+            ConfigCommand::new(worktree_name, args.worktree, args.args).execute(&repo)?;
+        }
+        Commands::Exclude(args) => {
+            let worktree_name = resolve_worktree_name(args.name, &repo, "exclude")?;
+            ExcludeCommand::new(worktree_name, args.patterns).execute(&repo)?;
+        }
+        Commands::Editor(command) => match command {
+            EditorCommands::Set(args) => {
+                EditorCommand::new(args.worktree, args.command)
+                    .with_terminal(args.terminal)
+                    .set(&repo)?;
+            }
+        },
+        Commands::Interactive => {
+            interactive::run(&repo)?;
+        }
+        Commands::Ui => {
+            ui::run(&WorktreeManager::new(repo))?;
+        }
+        Commands::Worktree(command) => match command {
+            WorktreeCommands::Open(args) => {
+                let command = OpenCommand::new(args.name, args.path)
+                    .with_interactive(!args.no_interactive)
+                    .with_shell(args.shell)
+                    .with_wait(args.wait);
+                command.execute(&repo)?;
+            }
+        },
+        Commands::Rm(args) => {
+            // Accept the same shorthands as `open`; unknown names are reported by `rm` itself.
+            let command = match find_by_name(&args.name, &repo)? {
+                Some(resolved) => RemoveCommand::for_resolved(&repo, resolved, args.force),
+                None => RemoveCommand::new(args.name, args.force),
+            }
+            .with_remove_local_branch(args.delete_branch || args.remote)
+            .with_remove_remote_branch(args.remote)
+            .with_no_wait(args.no_wait);
+            if args.porcelain {
+                // Scripts get no shell, even when they ran inside the removed worktree.
+                let command = command.with_spawn_shell(false);
+                let path = command.worktree_path(&repo);
+                let existed = path.exists();
+                let outcome = output::silenced(|| command.execute(&repo))?;
+                let local = match outcome.local_branch {
+                    Some(LocalBranchStatus::Deleted) => "deleted",
+                    Some(LocalBranchStatus::NotFound) => "not-found",
+                    None => "",
+                };
+                let remote = match outcome.remote_branch {
+                    Some(RemoteBranchStatus::Deleted) => "deleted",
+                    Some(RemoteBranchStatus::NotFound) => "not-found",
+                    None => "",
+                };
+                output::porcelain::print([output::porcelain::record(
+                    if existed { "removed" } else { "absent" },
+                    [command.name(), &path.display().to_string(), local, remote],
+                )]);
+            } else {
+                let _ = command.execute(&repo)?;
+            }
+        }
+        Commands::Rename(args) => {
+            RenameCommand::new(args.name, args.new_name)
+                .with_rename_branch(args.move_branch)
+                .with_no_wait(args.no_wait)
+                .execute(&repo)?;
+        }
+        Commands::Archive(args) => match args.name {
+            Some(name) => ArchiveCommand::new(name).execute(&repo)?,
+            None => ArchiveCommand::list(&repo)?,
+        },
+        Commands::Unarchive(args) => {
+            UnarchiveCommand::new(args.name).execute(&repo)?;
+        }
+        Commands::Lock(args) => {
+            LockCommand::new(args.name)
+                .with_reason(args.reason)
+                .execute(&repo)?;
+        }
+        Commands::Unlock(args) => {
+            UnlockCommand::new(args.name).execute(&repo)?;
+        }
+        Commands::Stash(args) => match (args.command, args.name) {
+            (Some(StashCommands::Pop(pop)), _) => StashPopCommand::new(pop.name).execute(&repo)?,
+            (None, Some(name)) => StashCommand::new(name)
+                .with_message(args.message)
+                .with_include_untracked(args.include_untracked)
+                .execute(&repo)?,
+            (None, None) => unreachable!("clap requires a worktree without a subcommand"),
+        },
+        Commands::Review(args) => review(args, &repo, "review")?,
         Commands::Merge(args) => {
             let worktree_name = resolve_worktree_name(args.name, &repo, "merge")?;
             let provider = resolve_provider(&args.provider, &repo)?;
-            let mut command = MergeCommand::new(worktree_name, provider);
+            let mut command = MergeCommand::new(worktree_name, provider, &repo);
             if args.remove_remote {
                 command.enable_remove_remote();
             }
+            if args.cleanup {
+                command.enable_cleanup();
+            }
+            if args.autostash {
+                command.enable_autostash();
+            }
+            if let Some(strategy) = args.strategy {
+                command.set_strategy(strategy);
+            }
             command.execute(&repo)?;
         }
+        Commands::Ci(command) => match command {
+            CiCommands::Trigger(args) => {
+                let worktree_name = resolve_worktree_name(args.name, &repo, "ci trigger")?;
+                let provider = resolve_provider(&args.provider, &repo)?;
+                let mut command = CiCommand::new(worktree_name, provider, &repo);
+                command.trigger(&repo, args.workflow)?;
+            }
+            CiCommands::Status(args) => {
+                let worktree_name = resolve_worktree_name(args.name, &repo, "ci status")?;
+                let provider = resolve_provider(&args.provider, &repo)?;
+                let mut command = CiCommand::new(worktree_name, provider, &repo)
+                    .with_poll_interval(Duration::from_secs(args.interval));
+                command.status(&repo, args.watch)?;
+            }
+        },
+        Commands::Pr(command) => match command {
+            PrCommands::Create(args) => review(args, &repo, "pr create")?,
+            PrCommands::Comment(args) => {
+                let worktree_name = resolve_worktree_name(args.name, &repo, "pr comment")?;
+                let provider = resolve_provider(&args.provider, &repo)?;
+                let source = match (args.message, args.file) {
+                    (Some(text), _) => MessageSource::Text(text),
+                    (None, Some(path)) => MessageSource::File(path),
+                    (None, None) => MessageSource::Editor,
+                };
+                let mut command = PrCommand::new(worktree_name, provider, &repo);
+                command.comment(&repo, source)?;
+            }
+            PrCommands::Review(args) => {
+                let worktree_name = resolve_worktree_name(args.name, &repo, "pr review")?;
+                let provider = resolve_provider(&args.provider, &repo)?;
+                let verdict = if args.approve {
+                    ReviewVerdict::Approve
+                } else {
+                    ReviewVerdict::RequestChanges
+                };
+                let source = match (args.message, args.file) {
+                    (Some(text), _) => Some(MessageSource::Text(text)),
+                    (None, Some(path)) => Some(MessageSource::File(path)),
+                    (None, None) => None,
+                };
+                let mut command = PrCommand::new(worktree_name, provider, &repo);
+                command.review(&repo, verdict, source)?;
+            }
+            PrCommands::Close(args) => {
+                let worktree_name = resolve_worktree_name(args.name, &repo, "pr close")?;
+                let provider = resolve_provider(&args.provider, &repo)?;
+                let options = CloseOptions {
+                    comment: args.comment,
+                    delete_remote_branch: args.delete_remote || args.cleanup,
+                    remove_worktree: args.remove || args.cleanup,
+                    force: args.force,
+                    assume_yes: args.yes,
+                };
+                let mut command = PrCommand::new(worktree_name, provider, &repo);
+                command.close(&repo, options)?;
+            }
+            PrCommands::Checkout(args) => {
+                let provider = resolve_provider(&args.provider, &repo)?;
+                let mut command =
+                    PrCheckoutCommand::new(args.number, provider, &repo).with_open(!args.no_open);
+                command.execute(&repo)?;
+            }
+        },
+        Commands::Notes(command) => match command {
+            NotesCommands::Show(args) => {
+                let worktree_name = resolve_worktree_name(args.name, &repo, "notes show")?;
+                NotesCommand::new(worktree_name).show(&repo)?;
+            }
+            NotesCommands::Set(args) => {
+                let worktree_name = resolve_worktree_name(args.name, &repo, "notes set")?;
+                let update = NotesUpdate {
+                    note: args.note,
+                    pr: args.pr,
+                };
+                NotesCommand::new(worktree_name).set(&repo, update)?;
+            }
+            NotesCommands::Edit(args) => {
+                let worktree_name = resolve_worktree_name(args.name, &repo, "notes edit")?;
+                NotesCommand::new(worktree_name).edit(&repo)?;
+            }
+            NotesCommands::Sync(args) => {
+                NotesSyncCommand::new(args.remote).execute(&repo)?;
+            }
+        },
+        Commands::Note(args) => {
+            let worktree_name = resolve_worktree_name(args.name, &repo, "note")?;
+            NotesCommand::new(worktree_name).edit(&repo)?;
+        }
+        Commands::Hooks(command) => match command {
+            HooksCommands::Install(args) => {
+                HooksCommand::new(args.force).install(&repo, &args.source, args.reference)?;
+            }
+            HooksCommands::Update(args) => {
+                HooksCommand::new(args.force).update(&repo)?;
+            }
+            HooksCommands::Logs(args) => {
+                HookLogsCommand::new(args.name)
+                    .with_path(args.path)
+                    .execute(&repo)?;
+            }
+            HooksCommands::Run(args) => {
+                let worktree_name = resolve_worktree_name(args.worktree, &repo, "hooks run")?;
+                HookRunCommand::new(args.hook, worktree_name)
+                    .with_dry_run(args.dry_run)
+                    .execute(&repo)?;
+            }
+            HooksCommands::List => {
+                HookListCommand::new().execute(&repo)?;
+            }
+        },
+        Commands::Stats(args) => {
+            StatsCommand::new(args.ui).execute(&repo)?;
+        }
+        Commands::Du(args) => {
+            DiskUsageCommand::new()
+                .with_sort(args.sort)
+                .with_min_size(args.min_size.unwrap_or_default().0)
+                .with_skip_ignored(args.skip_ignored)
+                .execute(&repo)?;
+        }
+        Commands::Clean(args) => {
+            let command = if args.all {
+                CleanCommand::all()
+            } else {
+                CleanCommand::new(resolve_worktree_name(args.name, &repo, "clean")?)
+            };
+            command.with_dry_run(args.dry_run).execute(&repo)?;
+        }
+        Commands::Doctor(args) => {
+            let provider = resolve_provider(&args.provider, &repo)?;
+            DoctorCommand::new()
+                .with_provider(provider)
+                .execute(&repo)?;
+        }
+        Commands::Sync(args) => {
+            SyncCommand::new()
+                .with_watch(args.watch)
+                .with_interval(Duration::from_secs(args.interval))
+                .with_no_wait(args.no_wait)
+                .execute(&repo)?;
+        }
     }
 
     Ok(())
 }
 
+fn review(args: ReviewArgs, repo: &Repo, command_name: &str) -> color_eyre::Result<()> {
+    let worktree_name = resolve_worktree_name(args.name, repo, command_name)?;
+    let provider = resolve_provider(&args.provider, repo)?;
+    let options = ReviewOptions {
+        name: worktree_name,
+        push: !args.no_push,
+        draft: args.draft,
+        fill: args.fill,
+        web: args.web,
+        remote: args.remote,
+        reviewers: args.reviewers,
+        extra_args: args.extra,
+        provider,
+    };
+    ReviewCommand::new(options, repo).execute(repo)
+}
+
+fn workspace(command: &WsCommands) -> color_eyre::Result<()> {
+    let current = Repo::discover().ok();
+    if let Some(repo) = &current {
+        i18n::init(i18n::resolve_locale_preference(repo));
+    }
+    match command {
+        WsCommands::Create(args) => WorkspaceCommand::new(args.name.clone())
+            .with_workspace(args.workspace.clone())
+            .with_base(args.base.clone())
+            .create(current.as_ref()),
+        WsCommands::Open(args) => WorkspaceCommand::new(args.name.clone())
+            .with_workspace(args.workspace.clone())
+            .open(current.as_ref()),
+    }
+}
+
 fn resolve_provider(
     cli_provider: &Option<String>,
     repo: &Repo,
@@ -343,76 +1543,831 @@ mod tests {
     }
 
     #[test]
-    fn parses_interactive_command_and_alias() -> color_eyre::Result<()> {
-        let interactive = Cli::try_parse_from(["rsworktree", "interactive"])
-            .expect("interactive subcommand should parse");
-        assert!(matches!(interactive.command, Commands::Interactive));
+    fn parses_interactive_command_and_alias() -> color_eyre::Result<()> {
+        let interactive = Cli::try_parse_from(["rsworktree", "interactive"])
+            .expect("interactive subcommand should parse");
+        assert!(matches!(interactive.command, Commands::Interactive));
+
+        let alias =
+            Cli::try_parse_from(["rsworktree", "i"]).expect("interactive alias should parse");
+        assert!(matches!(alias.command, Commands::Interactive));
+
+        Ok(())
+    }
+
+    #[test]
+    fn parses_ui_command() {
+        let cli = Cli::try_parse_from(["rsworktree", "ui"]).expect("ui subcommand should parse");
+        assert!(matches!(cli.command, Commands::Ui));
+    }
+
+    #[test]
+    fn parses_du_command() -> color_eyre::Result<()> {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "du",
+            "--sort",
+            "name",
+            "--min-size",
+            "500M",
+            "--skip-ignored",
+        ])?;
+        match cli.command {
+            Commands::Du(args) => {
+                assert_eq!(args.sort, DiskUsageSort::Name);
+                assert_eq!(args.min_size, Some(ByteSize(500 * 1024 * 1024)));
+                assert!(args.skip_ignored);
+            }
+            _ => panic!("expected Du command"),
+        }
+
+        assert!(Cli::try_parse_from(["rsworktree", "du", "--sort", "age"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parses_global_host_option() -> color_eyre::Result<()> {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "worktree",
+            "open",
+            "feature",
+            "--host",
+            "dev-box:/srv/app",
+        ])?;
+        let host = cli.host.expect("host should be parsed");
+        assert_eq!(host.host, "dev-box");
+        assert_eq!(host.dir.as_deref(), Some("/srv/app"));
+        assert!(matches!(
+            cli.command,
+            Commands::Worktree(WorktreeCommands::Open(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn parses_doctor_command() -> color_eyre::Result<()> {
+        let cli = Cli::try_parse_from(["rsworktree", "doctor", "--provider", "gitlab"])?;
+        match cli.command {
+            Commands::Doctor(args) => assert_eq!(args.provider.as_deref(), Some("gitlab")),
+            _ => panic!("expected Doctor command"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parses_sync_command() -> color_eyre::Result<()> {
+        let cli = Cli::try_parse_from(["rsworktree", "sync", "--watch", "--interval", "60"])?;
+        match cli.command {
+            Commands::Sync(args) => {
+                assert!(args.watch);
+                assert_eq!(args.interval, 60);
+            }
+            _ => panic!("expected Sync command"),
+        }
+        assert!(Cli::try_parse_from(["rsworktree", "sync", "--interval", "60"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parses_global_no_progress_flag() -> color_eyre::Result<()> {
+        let cli = Cli::try_parse_from(["rsworktree", "create", "feature", "--no-progress"])?;
+        assert!(cli.no_progress);
+        let cli = Cli::try_parse_from(["rsworktree", "--quiet", "rm", "feature"])?;
+        assert!(cli.no_progress);
+        let cli = Cli::try_parse_from(["rsworktree", "ls"])?;
+        assert!(!cli.no_progress);
+        Ok(())
+    }
+
+    #[test]
+    fn parses_clean_command() -> color_eyre::Result<()> {
+        let cli = Cli::try_parse_from(["rsworktree", "clean", "--all", "--dry-run"])?;
+        match cli.command {
+            Commands::Clean(args) => {
+                assert!(args.all);
+                assert!(args.dry_run);
+                assert_eq!(args.name, None);
+            }
+            _ => panic!("expected Clean command"),
+        }
+
+        assert!(Cli::try_parse_from(["rsworktree", "clean", "feature", "--all"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parses_ws_commands() -> color_eyre::Result<()> {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "ws",
+            "create",
+            "payments-fix",
+            "--base",
+            "main",
+            "-w",
+            "payments",
+        ])?;
+        match cli.command {
+            Commands::Ws(WsCommands::Create(args)) => {
+                assert_eq!(args.name, "payments-fix");
+                assert_eq!(args.base.as_deref(), Some("main"));
+                assert_eq!(args.workspace.as_deref(), Some("payments"));
+            }
+            _ => panic!("expected Ws Create command"),
+        }
+
+        let cli = Cli::try_parse_from(["rsworktree", "workspace", "open", "payments-fix"])?;
+        assert!(matches!(
+            cli.command,
+            Commands::Ws(WsCommands::Open(WsOpenArgs {
+                workspace: None,
+                ..
+            }))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn parses_ci_trigger_and_status_commands() -> color_eyre::Result<()> {
+        let trigger = Cli::try_parse_from([
+            "rsworktree",
+            "ci",
+            "trigger",
+            "feature",
+            "--workflow",
+            "ci.yml",
+        ])
+        .expect("ci trigger should parse");
+        let Commands::Ci(CiCommands::Trigger(args)) = trigger.command else {
+            panic!("expected ci trigger command");
+        };
+        assert_eq!(args.name.as_deref(), Some("feature"));
+        assert_eq!(args.workflow.as_deref(), Some("ci.yml"));
+
+        let status = Cli::try_parse_from(["rsworktree", "ci", "status", "--watch"])
+            .expect("ci status should parse");
+        let Commands::Ci(CiCommands::Status(args)) = status.command else {
+            panic!("expected ci status command");
+        };
+        assert!(args.name.is_none());
+        assert!(args.watch);
+        assert_eq!(args.interval, 10);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parses_pr_comment_sources() {
+        let cli = Cli::try_parse_from(["rsworktree", "pr", "comment", "-m", "Ready"])
+            .expect("pr comment with message should parse");
+        let Commands::Pr(PrCommands::Comment(args)) = cli.command else {
+            panic!("expected pr comment command");
+        };
+        assert!(args.name.is_none());
+        assert_eq!(args.message.as_deref(), Some("Ready"));
+
+        let cli = Cli::try_parse_from(["rsworktree", "pr", "comment", "feature", "-F", "-"])
+            .expect("pr comment with file should parse");
+        let Commands::Pr(PrCommands::Comment(args)) = cli.command else {
+            panic!("expected pr comment command");
+        };
+        assert_eq!(args.name.as_deref(), Some("feature"));
+        assert_eq!(args.file, Some(PathBuf::from("-")));
+
+        assert!(
+            Cli::try_parse_from(["rsworktree", "pr", "comment", "-m", "a", "-F", "b"]).is_err(),
+            "--message and --file are exclusive"
+        );
+    }
+
+    #[test]
+    fn parses_pr_review_verdicts() {
+        let cli = Cli::try_parse_from(["rsworktree", "pr", "review", "--approve"])
+            .expect("pr review --approve should parse");
+        let Commands::Pr(PrCommands::Review(args)) = cli.command else {
+            panic!("expected pr review command");
+        };
+        assert!(args.approve);
+        assert!(!args.request_changes);
+
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "pr",
+            "review",
+            "feature",
+            "--request-changes",
+            "-m",
+            "Needs tests",
+        ])
+        .expect("pr review --request-changes should parse");
+        let Commands::Pr(PrCommands::Review(args)) = cli.command else {
+            panic!("expected pr review command");
+        };
+        assert!(args.request_changes);
+        assert_eq!(args.message.as_deref(), Some("Needs tests"));
+
+        assert!(
+            Cli::try_parse_from(["rsworktree", "pr", "review"]).is_err(),
+            "a verdict is required"
+        );
+        assert!(
+            Cli::try_parse_from([
+                "rsworktree",
+                "pr",
+                "review",
+                "--approve",
+                "--request-changes"
+            ])
+            .is_err(),
+            "verdicts are exclusive"
+        );
+    }
+
+    #[test]
+    fn parses_pr_close_with_cleanup() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "pr",
+            "close",
+            "feature",
+            "--cleanup",
+            "-c",
+            "Superseded",
+            "--yes",
+        ])
+        .expect("pr close should parse");
+        let Commands::Pr(PrCommands::Close(args)) = cli.command else {
+            panic!("expected pr close command");
+        };
+        assert_eq!(args.name.as_deref(), Some("feature"));
+        assert!(args.cleanup);
+        assert!(args.yes);
+        assert!(!args.remove);
+        assert_eq!(args.comment.as_deref(), Some("Superseded"));
+    }
+
+    #[test]
+    fn parses_pr_checkout() {
+        let cli = Cli::try_parse_from(["rsworktree", "pr", "checkout", "42", "--no-open"])
+            .expect("pr checkout should parse");
+        let Commands::Pr(PrCommands::Checkout(args)) = cli.command else {
+            panic!("expected pr checkout command");
+        };
+        assert_eq!(args.number, 42);
+        assert!(args.no_open);
+        assert!(Cli::try_parse_from(["rsworktree", "pr", "checkout", "feature"]).is_err());
+    }
+
+    #[test]
+    fn parses_config_passthrough_arguments() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "config",
+            "--worktree",
+            "-n",
+            "feature",
+            "--get",
+            "core.sshCommand",
+        ])
+        .expect("config should parse");
+        let Commands::Config(args) = cli.command else {
+            panic!("expected config command");
+        };
+        assert!(args.worktree);
+        assert_eq!(args.name.as_deref(), Some("feature"));
+        assert_eq!(args.args, vec!["--get", "core.sshCommand"]);
+    }
+
+    #[test]
+    fn parses_editor_set_command() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "editor",
+            "set",
+            "--worktree",
+            "backend",
+            "rustrover",
+            "--wait",
+        ])
+        .expect("editor set should parse");
+        let Commands::Editor(EditorCommands::Set(args)) = cli.command else {
+            panic!("expected editor set command");
+        };
+        assert_eq!(args.worktree.as_deref(), Some("backend"));
+        assert_eq!(args.command, vec!["rustrover", "--wait"]);
+
+        let cli = Cli::try_parse_from(["rsworktree", "editor", "set", "--terminal"])
+            .expect("editor set --terminal should parse without a command");
+        let Commands::Editor(EditorCommands::Set(args)) = cli.command else {
+            panic!("expected editor set command");
+        };
+        assert!(args.terminal);
+        assert!(args.command.is_empty());
+        assert!(Cli::try_parse_from(["rsworktree", "editor", "set"]).is_err());
+    }
+
+    #[test]
+    fn parses_notes_commands() {
+        let cli = Cli::try_parse_from(["rsworktree", "notes", "set", "feature", "--note", "Spike"])
+            .expect("notes set should parse");
+        let Commands::Notes(NotesCommands::Set(args)) = cli.command else {
+            panic!("expected notes set command");
+        };
+        assert_eq!(args.name.as_deref(), Some("feature"));
+        assert_eq!(args.note.as_deref(), Some("Spike"));
+        assert_eq!(args.pr, None);
+
+        let cli = Cli::try_parse_from(["rsworktree", "notes", "edit", "feature"])
+            .expect("notes edit should parse");
+        let Commands::Notes(NotesCommands::Edit(args)) = cli.command else {
+            panic!("expected notes edit command");
+        };
+        assert_eq!(args.name.as_deref(), Some("feature"));
+
+        let cli =
+            Cli::try_parse_from(["rsworktree", "note", "feature"]).expect("note should parse");
+        let Commands::Note(args) = cli.command else {
+            panic!("expected note command");
+        };
+        assert_eq!(args.name.as_deref(), Some("feature"));
+
+        let cli =
+            Cli::try_parse_from(["rsworktree", "notes", "sync"]).expect("notes sync should parse");
+        let Commands::Notes(NotesCommands::Sync(args)) = cli.command else {
+            panic!("expected notes sync command");
+        };
+        assert_eq!(args.remote, "origin");
+    }
+
+    #[test]
+    fn parses_hooks_install_with_ref() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "hooks",
+            "install",
+            "https://example.com/team/hooks.git",
+            "--ref",
+            "v2",
+        ])
+        .expect("hooks install should parse");
+        let Commands::Hooks(HooksCommands::Install(args)) = cli.command else {
+            panic!("expected hooks install command");
+        };
+        assert_eq!(args.source, "https://example.com/team/hooks.git");
+        assert_eq!(args.reference.as_deref(), Some("v2"));
+        assert!(!args.force);
+    }
+
+    #[test]
+    fn parses_hooks_logs_command() {
+        let cli = Cli::try_parse_from(["rsworktree", "hooks", "logs", "post-create", "--path"])
+            .expect("hooks logs should parse");
+        let Commands::Hooks(HooksCommands::Logs(args)) = cli.command else {
+            panic!("expected hooks logs command");
+        };
+        assert_eq!(args.name.as_deref(), Some("post-create"));
+        assert!(args.path);
+        assert!(Cli::try_parse_from(["rsworktree", "hooks", "logs", "--path"]).is_err());
+    }
+
+    #[test]
+    fn parses_hooks_run_command() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "hooks",
+            "run",
+            "post-create",
+            "--worktree",
+            "feature",
+            "--dry-run",
+        ])
+        .expect("hooks run should parse");
+        let Commands::Hooks(HooksCommands::Run(args)) = cli.command else {
+            panic!("expected hooks run command");
+        };
+        assert_eq!(args.hook, HookName::PostCreate);
+        assert_eq!(args.worktree.as_deref(), Some("feature"));
+        assert!(args.dry_run);
+        assert!(Cli::try_parse_from(["rsworktree", "hooks", "run", "post-merge"]).is_err());
+    }
+
+    #[test]
+    fn resolve_worktree_name_infers_from_cwd_inside_worktree() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        init_git_repo(&repo_dir)?;
+        let repo = Repo::discover_from(repo_dir.path())?;
+        let worktree_dir = repo.ensure_worktrees_dir()?.join("feature/nested");
+        fs::create_dir_all(&worktree_dir)?;
+
+        let _guard = DirGuard::change_to(&worktree_dir)?;
+        let resolved = resolve_worktree_name(None, &repo, "review")?;
+        assert_eq!(resolved, "feature/nested");
+
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_worktree_name_requires_running_inside_worktree() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        init_git_repo(&repo_dir)?;
+        let repo = Repo::discover_from(repo_dir.path())?;
+        let _guard = DirGuard::change_to(repo.root())?;
+
+        let err = resolve_worktree_name(None, &repo, "review").unwrap_err();
+        assert!(err.to_string().contains("must be run from inside"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_worktree_name_rejects_rsworktree_root() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        init_git_repo(&repo_dir)?;
+        let repo = Repo::discover_from(repo_dir.path())?;
+        let worktrees_dir = repo.ensure_worktrees_dir()?;
+        let _guard = DirGuard::change_to(&worktrees_dir)?;
+
+        let err = resolve_worktree_name(None, &repo, "review").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Run `rsworktree review` from inside")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn parses_create_command_with_base() {
+        let cli = Cli::try_parse_from(["rsworktree", "create", "feature/test", "--base", "develop"])
+            .expect("create with base should parse");
+        match cli.command {
+            Commands::Create(args) => {
+                assert_eq!(args.name.as_deref(), Some("feature/test"));
+                assert_eq!(args.base, Some("develop".into()));
+                assert!(!args.exists_ok);
+            }
+            _ => panic!("expected Create command"),
+        }
+    }
+
+    #[test]
+    fn parses_create_command_with_exists_ok() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "create",
+            "feature/test",
+            "--exists-ok",
+            "--rerun-setup",
+        ])
+        .expect("create with exists-ok should parse");
+        match cli.command {
+            Commands::Create(args) => {
+                assert!(args.exists_ok);
+                assert!(args.rerun_setup);
+            }
+            _ => panic!("expected Create command"),
+        }
+
+        assert!(
+            Cli::try_parse_from(["rsworktree", "create", "feature/test", "--rerun-setup"])
+                .is_err(),
+            "--rerun-setup requires --exists-ok"
+        );
+    }
+
+    #[test]
+    fn parses_create_command_with_apply() {
+        let cli = Cli::try_parse_from(["rsworktree", "create", "feature/test", "--apply", "-"])
+            .expect("create with apply should parse");
+        match cli.command {
+            Commands::Create(args) => assert_eq!(args.apply, Some(PathBuf::from("-"))),
+            _ => panic!("expected Create command"),
+        }
+
+        assert!(
+            Cli::try_parse_from([
+                "rsworktree",
+                "create",
+                "feature/test",
+                "--apply",
+                "fix.patch",
+                "--exists-ok",
+            ])
+            .is_err(),
+            "--apply conflicts with --exists-ok"
+        );
+    }
+
+    #[test]
+    fn parses_create_command_with_sparse_paths() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "create",
+            "feature/test",
+            "--sparse",
+            "packages/api",
+            "packages/shared",
+        ])
+        .expect("create with sparse should parse");
+        match cli.command {
+            Commands::Create(args) => {
+                assert_eq!(args.sparse, vec!["packages/api", "packages/shared"]);
+                assert!(!args.no_sparse);
+            }
+            _ => panic!("expected Create command"),
+        }
+        assert!(
+            Cli::try_parse_from([
+                "rsworktree",
+                "create",
+                "feature/test",
+                "--sparse",
+                "packages/api",
+                "--no-sparse",
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn parses_create_command_with_submodule_flags() {
+        let cli = Cli::try_parse_from(["rsworktree", "create", "feature", "--shallow-submodules"])
+            .expect("create with --shallow-submodules should parse");
+        match cli.command {
+            Commands::Create(args) => {
+                assert!(args.shallow_submodules);
+                assert!(!args.no_submodules);
+            }
+            _ => panic!("expected Create command"),
+        }
+        assert!(
+            Cli::try_parse_from([
+                "rsworktree",
+                "create",
+                "feature",
+                "--no-submodules",
+                "--shallow-submodules",
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn parses_create_command_with_reference() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "create",
+            "feature/test",
+            "--reference",
+            "/mnt/fast/app",
+        ])
+        .expect("create with reference should parse");
+        match cli.command {
+            Commands::Create(args) => {
+                assert_eq!(args.reference, Some(PathBuf::from("/mnt/fast/app")));
+            }
+            other => panic!("expected create command, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_create_command_with_copy() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "create",
+            "feature/test",
+            "--copy",
+            ".env*",
+            "--copy",
+            "apps/*/.env",
+        ])
+        .expect("create with copy should parse");
+        match cli.command {
+            Commands::Create(args) => assert_eq!(args.copy, vec![".env*", "apps/*/.env"]),
+            _ => panic!("expected Create command"),
+        }
+    }
+
+    #[test]
+    fn parses_create_command_with_hook_timeout() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "create",
+            "feature/test",
+            "--hook-timeout",
+            "30",
+        ])
+        .expect("create with hook timeout should parse");
+        match cli.command {
+            Commands::Create(args) => assert_eq!(args.hook_timeout, Some(30)),
+            _ => panic!("expected Create command"),
+        }
+    }
+
+    #[test]
+    fn parses_lock_command_with_reason() {
+        let cli = Cli::try_parse_from(["rsworktree", "lock", "usb", "--reason", "on a usb drive"])
+            .expect("lock should parse");
+        match cli.command {
+            Commands::Lock(args) => {
+                assert_eq!(args.name, "usb");
+                assert_eq!(args.reason.as_deref(), Some("on a usb drive"));
+            }
+            _ => panic!("expected Lock command"),
+        }
+        let cli =
+            Cli::try_parse_from(["rsworktree", "unlock", "usb"]).expect("unlock should parse");
+        assert!(matches!(cli.command, Commands::Unlock(args) if args.name == "usb"));
+    }
+
+    #[test]
+    fn parses_stash_and_stash_pop() {
+        let cli = Cli::try_parse_from(["rsworktree", "stash", "feature", "-u", "-m", "parked"])
+            .expect("stash should parse");
+        match cli.command {
+            Commands::Stash(args) => {
+                assert!(args.command.is_none());
+                assert_eq!(args.name.as_deref(), Some("feature"));
+                assert_eq!(args.message.as_deref(), Some("parked"));
+                assert!(args.include_untracked);
+            }
+            _ => panic!("expected Stash command"),
+        }
 
-        let alias =
-            Cli::try_parse_from(["rsworktree", "i"]).expect("interactive alias should parse");
-        assert!(matches!(alias.command, Commands::Interactive));
+        let cli = Cli::try_parse_from(["rsworktree", "stash", "pop", "feature"])
+            .expect("stash pop should parse");
+        match cli.command {
+            Commands::Stash(args) => assert!(matches!(
+                args.command,
+                Some(StashCommands::Pop(pop)) if pop.name == "feature"
+            )),
+            _ => panic!("expected Stash command"),
+        }
 
-        Ok(())
+        assert!(Cli::try_parse_from(["rsworktree", "stash"]).is_err());
+        assert!(Cli::try_parse_from(["rsworktree", "stash", "pop"]).is_err());
     }
 
     #[test]
-    fn resolve_worktree_name_infers_from_cwd_inside_worktree() -> color_eyre::Result<()> {
-        let repo_dir = TempDir::new()?;
-        init_git_repo(&repo_dir)?;
-        let repo = Repo::discover_from(repo_dir.path())?;
-        let worktree_dir = repo.ensure_worktrees_dir()?.join("feature/nested");
-        fs::create_dir_all(&worktree_dir)?;
-
-        let _guard = DirGuard::change_to(&worktree_dir)?;
-        let resolved = resolve_worktree_name(None, &repo, "review")?;
-        assert_eq!(resolved, "feature/nested");
+    fn parses_archive_command_with_name_or_list() {
+        let cli = Cli::try_parse_from(["rsworktree", "archive", "experiment"])
+            .expect("archive with name should parse");
+        match cli.command {
+            Commands::Archive(args) => {
+                assert_eq!(args.name.as_deref(), Some("experiment"));
+                assert!(!args.list);
+            }
+            _ => panic!("expected Archive command"),
+        }
 
-        Ok(())
+        let cli = Cli::try_parse_from(["rsworktree", "archive", "--list"])
+            .expect("archive --list should parse");
+        assert!(matches!(cli.command, Commands::Archive(args) if args.list));
+        assert!(Cli::try_parse_from(["rsworktree", "archive"]).is_err());
     }
 
     #[test]
-    fn resolve_worktree_name_requires_running_inside_worktree() -> color_eyre::Result<()> {
-        let repo_dir = TempDir::new()?;
-        init_git_repo(&repo_dir)?;
-        let repo = Repo::discover_from(repo_dir.path())?;
-        let _guard = DirGuard::change_to(repo.root())?;
+    fn parses_create_command_with_issue_instead_of_name() {
+        let cli = Cli::try_parse_from(["rsworktree", "create", "--issue", "PROJ-123"])
+            .expect("create with issue should parse");
+        match cli.command {
+            Commands::Create(args) => {
+                assert_eq!(args.name, None);
+                assert_eq!(args.issue.as_deref(), Some("PROJ-123"));
+            }
+            _ => panic!("expected Create command"),
+        }
 
-        let err = resolve_worktree_name(None, &repo, "review").unwrap_err();
-        assert!(err.to_string().contains("must be run from inside"));
+        assert!(Cli::try_parse_from(["rsworktree", "create"]).is_err());
+    }
 
-        Ok(())
+    #[test]
+    fn parses_create_command_with_type() {
+        let cli = Cli::try_parse_from(["rsworktree", "create", "Fix login crash", "--type", "fix"])
+            .expect("create with type should parse");
+        match cli.command {
+            Commands::Create(args) => {
+                assert_eq!(args.name.as_deref(), Some("Fix login crash"));
+                assert_eq!(args.kind.as_deref(), Some("fix"));
+            }
+            _ => panic!("expected Create command"),
+        }
     }
 
     #[test]
-    fn resolve_worktree_name_rejects_rsworktree_root() -> color_eyre::Result<()> {
-        let repo_dir = TempDir::new()?;
-        init_git_repo(&repo_dir)?;
-        let repo = Repo::discover_from(repo_dir.path())?;
-        let worktrees_dir = repo.ensure_worktrees_dir()?;
-        let _guard = DirGuard::change_to(&worktrees_dir)?;
+    fn parses_create_command_with_from_remote() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "create",
+            "review/login",
+            "--from-remote",
+            "upstream/feature/login",
+        ])
+        .expect("create from remote should parse");
+        match cli.command {
+            Commands::Create(args) => {
+                assert_eq!(args.from_remote.as_deref(), Some("upstream/feature/login"))
+            }
+            _ => panic!("expected Create command"),
+        }
 
-        let err = resolve_worktree_name(None, &repo, "review").unwrap_err();
         assert!(
-            err.to_string()
-                .contains("Run `rsworktree review` from inside")
+            Cli::try_parse_from([
+                "rsworktree",
+                "create",
+                "feature",
+                "--from-remote",
+                "feature",
+                "--base",
+                "main",
+            ])
+            .is_err(),
+            "--from-remote conflicts with --base"
         );
-
-        Ok(())
     }
 
     #[test]
-    fn parses_create_command_with_base() {
-        let cli = Cli::try_parse_from(["rsworktree", "create", "feature/test", "--base", "develop"])
-            .expect("create with base should parse");
+    fn parses_create_command_with_template() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "create",
+            "--template",
+            "backend-bugfix",
+            "my-fix",
+        ])
+        .expect("create with template should parse");
         match cli.command {
             Commands::Create(args) => {
-                assert_eq!(args.name, "feature/test");
-                assert_eq!(args.base, Some("develop".into()));
+                assert_eq!(args.name.as_deref(), Some("my-fix"));
+                assert_eq!(args.template.as_deref(), Some("backend-bugfix"));
             }
             _ => panic!("expected Create command"),
         }
     }
 
+    #[test]
+    fn parses_export_command() {
+        let cli =
+            Cli::try_parse_from(["rsworktree", "export", "feature", "--tar", "-o", "out.tgz"])
+                .expect("export with tar should parse");
+        match cli.command {
+            Commands::Export(args) => {
+                assert_eq!(args.name.as_deref(), Some("feature"));
+                assert!(args.tar);
+                assert_eq!(args.output, Some(PathBuf::from("out.tgz")));
+            }
+            _ => panic!("expected Export command"),
+        }
+
+        assert!(
+            Cli::try_parse_from(["rsworktree", "export", "feature", "--tar", "--bundle"]).is_err(),
+            "--tar conflicts with --bundle"
+        );
+    }
+
+    #[test]
+    fn parses_diff_command() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "diff",
+            "idea-a",
+            "idea-b",
+            "--working",
+            "--stat",
+        ])
+        .expect("diff should parse");
+        match cli.command {
+            Commands::Diff(args) => {
+                assert_eq!(args.from, "idea-a");
+                assert_eq!(args.to, "idea-b");
+                assert!(args.working);
+                assert!(args.stat);
+                assert_eq!(args.tool, None);
+            }
+            _ => panic!("expected Diff command"),
+        }
+
+        assert!(Cli::try_parse_from(["rsworktree", "diff", "idea-a"]).is_err());
+        assert!(
+            Cli::try_parse_from(["rsworktree", "diff", "a", "b", "--stat", "--tool", "meld"])
+                .is_err()
+        );
+        assert!(
+            Cli::try_parse_from([
+                "rsworktree",
+                "create",
+                "feature",
+                "--from-bundle",
+                "feature.bundle",
+                "--base",
+                "main",
+            ])
+            .is_err(),
+            "--from-bundle conflicts with --base"
+        );
+    }
+
     #[test]
     fn parses_cd_command_with_print_flag() {
         let cli = Cli::try_parse_from(["rsworktree", "cd", "my-worktree", "--print"])
@@ -426,6 +2381,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_info_command_with_provider() {
+        let cli = Cli::try_parse_from(["rsworktree", "info", "feature/test", "--provider", "gitlab"])
+            .expect("info with provider should parse");
+        match cli.command {
+            Commands::Info(args) => {
+                assert_eq!(args.name, Some("feature/test".into()));
+                assert_eq!(args.provider, Some("gitlab".into()));
+            }
+            _ => panic!("expected Info command"),
+        }
+    }
+
     #[test]
     fn parses_rm_command_with_force_flag() {
         let cli = Cli::try_parse_from(["rsworktree", "rm", "old-worktree", "--force"])
@@ -439,6 +2407,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_remove_alias_with_delete_branch() {
+        let cli = Cli::try_parse_from(["rsworktree", "remove", "old-worktree", "--delete-branch"])
+            .expect("remove alias should parse");
+        match cli.command {
+            Commands::Rm(args) => {
+                assert_eq!(args.name, "old-worktree");
+                assert!(args.delete_branch);
+                assert!(!args.remote);
+            }
+            _ => panic!("expected Rm command"),
+        }
+    }
+
+    #[test]
+    fn parses_rm_command_with_remote_flag() {
+        let cli = Cli::try_parse_from(["rsworktree", "rm", "old-worktree", "--remote"])
+            .expect("rm with remote should parse");
+        match cli.command {
+            Commands::Rm(args) => {
+                assert!(args.remote);
+                assert!(!args.force);
+            }
+            _ => panic!("expected Rm command"),
+        }
+    }
+
+    #[test]
+    fn parses_no_wait_for_mutating_commands() {
+        for command in [
+            &["create", "feature"][..],
+            &["rm", "feature"],
+            &["rename", "feature", "login"],
+            &["prune"],
+        ] {
+            let cli = Cli::try_parse_from(
+                ["rsworktree"]
+                    .iter()
+                    .chain(command)
+                    .chain(&["--no-wait"]),
+            )
+            .expect("--no-wait should parse");
+            let no_wait = match cli.command {
+                Commands::Create(args) => args.no_wait,
+                Commands::Rm(args) => args.no_wait,
+                Commands::Rename(args) => args.no_wait,
+                Commands::Prune(args) => args.no_wait,
+                _ => panic!("unexpected command"),
+            };
+            assert!(no_wait, "{command:?}");
+        }
+    }
+
     #[test]
     fn parses_review_with_all_flags() {
         let cli = Cli::try_parse_from([
@@ -475,6 +2496,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_pr_create_and_submit_alias() {
+        let cli = Cli::try_parse_from([
+            "rsworktree",
+            "pr",
+            "create",
+            "--draft",
+            "--reviewer",
+            "alice",
+        ])
+        .expect("pr create should parse");
+        match cli.command {
+            Commands::Pr(PrCommands::Create(args)) => {
+                assert_eq!(args.name, None);
+                assert!(args.draft);
+                assert_eq!(args.reviewers, vec!["alice"]);
+                assert_eq!(args.remote, "origin");
+            }
+            _ => panic!("expected Pr Create command"),
+        }
+
+        let alias = Cli::try_parse_from(["rsworktree", "pr", "submit", "feature", "--web"])
+            .expect("pr submit alias should parse");
+        assert!(matches!(
+            alias.command,
+            Commands::Pr(PrCommands::Create(ReviewArgs { web: true, .. }))
+        ));
+    }
+
     #[test]
     fn parses_merge_with_remove_flag() {
         let cli = Cli::try_parse_from(["rsworktree", "merge", "feature", "--remove"])
@@ -483,9 +2533,42 @@ mod tests {
             Commands::Merge(args) => {
                 assert_eq!(args.name, Some("feature".into()));
                 assert!(args.remove_remote);
+                assert!(!args.cleanup);
+            }
+            _ => panic!("expected Merge command"),
+        }
+    }
+
+    #[test]
+    fn parses_merge_with_cleanup_flag() {
+        let cli = Cli::try_parse_from(["rsworktree", "merge", "--cleanup"])
+            .expect("merge with cleanup should parse");
+        match cli.command {
+            Commands::Merge(args) => {
+                assert!(args.name.is_none());
+                assert!(args.cleanup);
+                assert!(!args.autostash);
+                assert!(args.strategy.is_none());
             }
             _ => panic!("expected Merge command"),
         }
+
+        assert!(Cli::try_parse_from(["rsworktree", "merge", "--autostash"]).is_err());
+        let cli = Cli::try_parse_from(["rsworktree", "merge", "--cleanup", "--autostash"])
+            .expect("merge with autostash should parse");
+        assert!(matches!(cli.command, Commands::Merge(args) if args.autostash));
+    }
+
+    #[test]
+    fn parses_merge_with_strategy() -> color_eyre::Result<()> {
+        let cli = Cli::try_parse_from(["rsworktree", "merge", "--strategy", "squash"])?;
+        match cli.command {
+            Commands::Merge(args) => assert_eq!(args.strategy, Some(MergeStrategy::Squash)),
+            _ => panic!("expected Merge command"),
+        }
+
+        assert!(Cli::try_parse_from(["rsworktree", "merge", "--strategy", "octopus"]).is_err());
+        Ok(())
     }
 
     #[test]
@@ -515,9 +2598,230 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_worktree_open_without_name() {
+        let cli = Cli::try_parse_from(["rsworktree", "worktree", "open", "--no-interactive"])
+            .expect("worktree open without a name should parse");
+        match cli.command {
+            Commands::Worktree(WorktreeCommands::Open(args)) => {
+                assert!(args.name.is_none());
+                assert!(args.no_interactive);
+                assert!(!args.shell);
+            }
+            _ => panic!("expected Worktree Open command"),
+        }
+    }
+
+    #[test]
+    fn parses_worktree_open_with_shell() {
+        let cli = Cli::try_parse_from(["rsworktree", "worktree", "open", "api", "--shell"])
+            .expect("worktree open --shell should parse");
+        match cli.command {
+            Commands::Worktree(WorktreeCommands::Open(args)) => {
+                assert_eq!(args.name.as_deref(), Some("api"));
+                assert!(args.shell);
+            }
+            _ => panic!("expected Worktree Open command"),
+        }
+    }
+
+    #[test]
+    fn parses_worktree_open_with_wait() {
+        let cli = Cli::try_parse_from(["rsworktree", "worktree", "open", "api", "--wait"])
+            .expect("worktree open --wait should parse");
+        match cli.command {
+            Commands::Worktree(WorktreeCommands::Open(args)) => assert!(args.wait),
+            _ => panic!("expected Worktree Open command"),
+        }
+        assert!(
+            Cli::try_parse_from(["rsworktree", "worktree", "open", "api", "--wait", "--shell"])
+                .is_err()
+        );
+    }
+
     #[test]
     fn parses_ls_command() {
         let cli = Cli::try_parse_from(["rsworktree", "ls"]).expect("ls should parse");
-        assert!(matches!(cli.command, Commands::Ls));
+        assert!(matches!(cli.command, Commands::Ls(_)));
+    }
+
+    #[test]
+    fn parses_ls_command_with_format() {
+        let cli = Cli::try_parse_from(["rsworktree", "ls", "--format", "{name}\t{branch}"])
+            .expect("ls with format should parse");
+        match cli.command {
+            Commands::Ls(args) => {
+                assert_eq!(args.format.as_deref(), Some("{name}\t{branch}"));
+                assert_eq!(args.provider, None);
+            }
+            _ => panic!("expected Ls command"),
+        }
+    }
+
+    #[test]
+    fn parses_switch_and_shell_init_commands() {
+        let cli =
+            Cli::try_parse_from(["rsworktree", "switch", "login"]).expect("switch should parse");
+        let Commands::Switch(args) = cli.command else {
+            panic!("expected Switch command");
+        };
+        assert_eq!(args.name, "login");
+
+        let cli =
+            Cli::try_parse_from(["rsworktree", "switch", "-"]).expect("switch - should parse");
+        let Commands::Switch(args) = cli.command else {
+            panic!("expected Switch command");
+        };
+        assert_eq!(args.name, "-");
+
+        let cli = Cli::try_parse_from(["rsworktree", "shell-init", "fish"])
+            .expect("shell-init should parse");
+        let Commands::ShellInit(args) = cli.command else {
+            panic!("expected ShellInit command");
+        };
+        assert_eq!(args.shell, Shell::Fish);
+
+        assert!(Cli::try_parse_from(["rsworktree", "shell-init", "tcsh"]).is_err());
+    }
+
+    #[test]
+    fn parses_completions_commands() {
+        let cli = Cli::try_parse_from(["rsworktree", "completions", "pwsh"])
+            .expect("completions should parse");
+        let Commands::Completions(args) = cli.command else {
+            panic!("expected Completions command");
+        };
+        assert_eq!(args.shell, Shell::Powershell);
+
+        let cli = Cli::try_parse_from(["rsworktree", "__complete", "branches"])
+            .expect("__complete should parse");
+        let Commands::Complete(args) = cli.command else {
+            panic!("expected Complete command");
+        };
+        assert_eq!(args.kind, CompletionKind::Branches);
+    }
+
+    #[test]
+    fn parses_prune_command() {
+        let cli = Cli::try_parse_from(["rsworktree", "prune", "-y", "--older-than", "30"])
+            .expect("prune should parse");
+        let Commands::Prune(args) = cli.command else {
+            panic!("expected Prune command");
+        };
+        assert!(args.yes);
+        assert!(!args.dry_run);
+        assert_eq!(args.older_than, Some(30));
+        assert!(!args.force);
+        assert!(!args.no_wait);
+
+        assert!(Cli::try_parse_from(["rsworktree", "prune", "--yes", "--dry-run"]).is_err());
+    }
+
+    #[test]
+    fn parses_status_command_with_provider() {
+        let cli = Cli::try_parse_from(["rsworktree", "status", "--provider", "gitlab"])
+            .expect("status should parse");
+        let Commands::Status(args) = cli.command else {
+            panic!("expected Status command");
+        };
+        assert_eq!(args.provider.as_deref(), Some("gitlab"));
+    }
+
+    #[test]
+    fn parses_ls_command_with_json() {
+        let cli =
+            Cli::try_parse_from(["rsworktree", "ls", "--json"]).expect("ls --json should parse");
+        match cli.command {
+            Commands::Ls(args) => assert!(args.json),
+            _ => panic!("expected Ls command"),
+        }
+
+        assert!(Cli::try_parse_from(["rsworktree", "ls", "--json", "--format", "{name}"]).is_err());
+    }
+
+    #[test]
+    fn parses_ls_command_with_long() {
+        let cli = Cli::try_parse_from(["rsworktree", "ls", "-l"]).expect("ls -l should parse");
+        match cli.command {
+            Commands::Ls(args) => assert!(args.long),
+            _ => panic!("expected Ls command"),
+        }
+
+        assert!(Cli::try_parse_from(["rsworktree", "ls", "--long", "--json"]).is_err());
+    }
+
+    #[test]
+    fn parses_ls_command_with_stashes() {
+        let cli = Cli::try_parse_from(["rsworktree", "ls", "--long", "--stashes"])
+            .expect("ls --stashes should parse");
+        match cli.command {
+            Commands::Ls(args) => assert!(args.stashes && args.long),
+            _ => panic!("expected Ls command"),
+        }
+
+        assert!(Cli::try_parse_from(["rsworktree", "ls", "--stashes", "--json"]).is_err());
+    }
+
+    #[test]
+    fn parses_porcelain_flags() {
+        for command in ["ls", "status"] {
+            let cli = Cli::try_parse_from(["rsworktree", command, "--porcelain"])
+                .expect("--porcelain should parse");
+            match cli.command {
+                Commands::Ls(args) => assert!(args.porcelain),
+                Commands::Status(args) => assert!(args.porcelain),
+                _ => panic!("expected Ls or Status command"),
+            }
+        }
+
+        let cli = Cli::try_parse_from(["rsworktree", "create", "feature", "--porcelain"])
+            .expect("create --porcelain should parse");
+        match cli.command {
+            Commands::Create(args) => assert!(args.porcelain),
+            _ => panic!("expected Create command"),
+        }
+
+        let cli = Cli::try_parse_from(["rsworktree", "rm", "feature", "--porcelain"])
+            .expect("rm --porcelain should parse");
+        match cli.command {
+            Commands::Rm(args) => assert!(args.porcelain),
+            _ => panic!("expected Rm command"),
+        }
+
+        assert!(Cli::try_parse_from(["rsworktree", "ls", "--porcelain", "--json"]).is_err());
+    }
+
+    #[test]
+    fn parses_create_with_json() {
+        let cli = Cli::try_parse_from(["rsworktree", "create", "feature", "--json"])
+            .expect("create --json should parse");
+        match cli.command {
+            Commands::Create(args) => assert!(args.json),
+            _ => panic!("expected Create command"),
+        }
+
+        assert!(
+            Cli::try_parse_from(["rsworktree", "create", "feature", "--json", "--porcelain"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parses_recent_command() {
+        let cli = Cli::try_parse_from(["rsworktree", "recent"]).expect("recent should parse");
+        let Commands::Recent(args) = cli.command else {
+            panic!("expected Recent command");
+        };
+        assert_eq!(args.number, None);
+        assert_eq!(args.limit, recent::DEFAULT_LIMIT);
+
+        let cli = Cli::try_parse_from(["rsworktree", "recent", "2", "-n", "5", "--most-used"])
+            .expect("recent with options should parse");
+        let Commands::Recent(args) = cli.command else {
+            panic!("expected Recent command");
+        };
+        assert_eq!(args.number, Some(2));
+        assert_eq!(args.limit, 5);
+        assert!(args.most_used);
     }
 }
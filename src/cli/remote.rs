@@ -0,0 +1,277 @@
+//! `--host`: drives rsworktree on a remote machine over `ssh`.
+//!
+//! Commands are forwarded to `rsworktree` on the host, which has to be
+//! installed there, and run in the directory given after the host name
+//! (`dev-box:~/src/app`) or else in the remote home directory. `worktree open`
+//! is the exception: the worktree path is looked up remotely and opened in a
+//! local VS Code or Cursor attached with `--remote ssh-remote+<host>`, or
+//! connection instructions are printed when neither is installed.
+
+use std::{
+    env,
+    ffi::OsString,
+    io::{self, IsTerminal},
+    process::{Command, Stdio},
+    str::FromStr,
+};
+
+use color_eyre::eyre::{self, WrapErr};
+
+use super::{Commands, OpenArgs, WorktreeCommands};
+use crate::{
+    editor::{find_on_path, resolve_program},
+    output,
+};
+
+/// Overrides the `ssh` program used for `--host`.
+pub const SSH_ENV: &str = "RSWORKTREE_SSH";
+
+/// Local editors that can attach to a host through VS Code's remote SSH extension.
+const REMOTE_EDITORS: [&str; 2] = ["code", "cursor"];
+
+/// The value of `--host`: an ssh destination, optionally followed by `:` and
+/// the repository directory on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RemoteHost {
+    pub(crate) host: String,
+    pub(crate) dir: Option<String>,
+}
+
+impl FromStr for RemoteHost {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (host, dir) = match s.split_once(':') {
+            Some((host, dir)) => (host, Some(dir).filter(|dir| !dir.is_empty())),
+            None => (s, None),
+        };
+        if host.trim().is_empty() {
+            return Err(format!(
+                "invalid host '{s}', expected <host> or <host>:<dir>"
+            ));
+        }
+        // `ssh` would take it for an option such as `-oProxyCommand=...`.
+        if host.starts_with('-') {
+            return Err(format!("invalid host '{s}', a host cannot start with '-'"));
+        }
+        Ok(Self {
+            host: host.to_owned(),
+            dir: dir.map(str::to_owned),
+        })
+    }
+}
+
+/// Runs `command` on `host`; `args` is the full command line.
+pub(super) fn run(
+    host: &RemoteHost,
+    command: &Commands,
+    args: &[OsString],
+) -> color_eyre::Result<()> {
+    if let Commands::Worktree(WorktreeCommands::Open(OpenArgs {
+        name: Some(name),
+        path: None,
+        shell: false,
+        ..
+    })) = command
+    {
+        return open(host, name);
+    }
+    forward(host, &without_host_flag(args))
+}
+
+/// `args` without the program name and the `--host` option.
+fn without_host_flag(args: &[OsString]) -> Vec<String> {
+    let mut forwarded = Vec::new();
+    let mut args = args.iter().skip(1).map(|arg| arg.to_string_lossy());
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            forwarded.push(arg.into_owned());
+            forwarded.extend(args.by_ref().map(|arg| arg.into_owned()));
+        } else if arg == "--host" {
+            args.next();
+        } else if !arg.starts_with("--host=") {
+            forwarded.push(arg.into_owned());
+        }
+    }
+    forwarded
+}
+
+/// The shell command line run on the host for `rsworktree <args>`.
+fn remote_script(host: &RemoteHost, args: &[String]) -> String {
+    let command =
+        shell_words::join(std::iter::once("rsworktree").chain(args.iter().map(String::as_str)));
+    match &host.dir {
+        // A quoted `~` would not be expanded by the remote shell.
+        Some(dir) => match dir.strip_prefix("~/") {
+            Some(rest) => format!("cd \"$HOME\"/{} && {command}", shell_words::quote(rest)),
+            None => format!("cd {} && {command}", shell_words::quote(dir)),
+        },
+        None => command,
+    }
+}
+
+fn ssh_program() -> OsString {
+    env::var_os(SSH_ENV)
+        .filter(|program| !program.is_empty())
+        .unwrap_or_else(|| OsString::from("ssh"))
+}
+
+fn forward(host: &RemoteHost, args: &[String]) -> color_eyre::Result<()> {
+    let mut command = Command::new(ssh_program());
+    // Prompts, pickers and `ui` need a terminal on the remote side.
+    if io::stdin().is_terminal() && io::stdout().is_terminal() {
+        command.arg("-t");
+    }
+    let status = command
+        .arg("--")
+        .arg(&host.host)
+        .arg(remote_script(host, args))
+        .status()
+        .wrap_err("failed to run `ssh`")?;
+    if !status.success() {
+        return Err(eyre::eyre!(
+            "`rsworktree` on `{}` failed ({})",
+            host.host,
+            status
+        ));
+    }
+    Ok(())
+}
+
+fn open(host: &RemoteHost, name: &str) -> color_eyre::Result<()> {
+    let args = ["cd".to_owned(), "--print".to_owned(), name.to_owned()];
+    let output = Command::new(ssh_program())
+        .arg("--")
+        .arg(&host.host)
+        .arg(remote_script(host, &args))
+        .stdin(Stdio::null())
+        .output()
+        .wrap_err("failed to run `ssh`")?;
+    if !output.status.success() {
+        return Err(eyre::eyre!(
+            "failed to resolve worktree `{}` on `{}`: {}",
+            name,
+            host.host,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let path = stdout
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .ok_or_else(|| eyre::eyre!("`{}` printed no path for `{}`", host.host, name))?;
+
+    let Some(editor) = find_on_path(&REMOTE_EDITORS) else {
+        output::info(tr!(
+            "Worktree `{}` is at `{}` on `{}`. Connect with:",
+            name,
+            path,
+            host.host
+        ));
+        for line in connection_instructions(&host.host, path) {
+            output::item(line);
+        }
+        return Ok(());
+    };
+
+    Command::new(resolve_program(editor.as_ref()))
+        .args(remote_editor_args(&host.host, path))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .wrap_err_with(|| eyre::eyre!("failed to launch `{editor}`"))?;
+    output::success(tr!("Opened `{}` on `{}` in `{}`.", name, host.host, editor));
+    Ok(())
+}
+
+fn remote_editor_args(host: &str, path: &str) -> [String; 3] {
+    [
+        "--remote".to_owned(),
+        format!("ssh-remote+{host}"),
+        path.to_owned(),
+    ]
+}
+
+fn connection_instructions(host: &str, path: &str) -> [String; 2] {
+    let shell = format!("cd {} && exec \"$SHELL\" -l", shell_words::quote(path));
+    [
+        format!(
+            "ssh -t {} {}",
+            shell_words::quote(host),
+            shell_words::quote(&shell)
+        ),
+        format!("vscode://vscode-remote/ssh-remote+{host}{path}"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_may_name_the_remote_directory() {
+        assert_eq!(
+            "dev-box".parse(),
+            Ok(RemoteHost {
+                host: "dev-box".into(),
+                dir: None,
+            })
+        );
+        assert_eq!(
+            "me@dev-box:~/src/app".parse(),
+            Ok(RemoteHost {
+                host: "me@dev-box".into(),
+                dir: Some("~/src/app".into()),
+            })
+        );
+        assert!(":/srv/app".parse::<RemoteHost>().is_err());
+        assert!("-oProxyCommand=touch /tmp/x".parse::<RemoteHost>().is_err());
+    }
+
+    #[test]
+    fn forwards_the_command_line_without_the_host() {
+        let args = [
+            "rsworktree",
+            "--host",
+            "dev-box",
+            "ls",
+            "--host=x",
+            "--",
+            "--host",
+        ]
+        .map(OsString::from);
+        assert_eq!(without_host_flag(&args), ["ls", "--", "--host"]);
+
+        let host = "dev-box:~/src/my app".parse::<RemoteHost>().expect("host");
+        assert_eq!(
+            remote_script(&host, &["create".into(), "feature x".into()]),
+            "cd \"$HOME\"/'src/my app' && rsworktree create 'feature x'"
+        );
+        let host = "dev-box:/srv/app".parse::<RemoteHost>().expect("host");
+        assert_eq!(
+            remote_script(&host, &["ls".into()]),
+            "cd /srv/app && rsworktree ls"
+        );
+    }
+
+    #[test]
+    fn open_attaches_the_editor_to_the_host() {
+        assert_eq!(
+            remote_editor_args("dev-box", "/srv/app/.rsworktree/feature"),
+            [
+                "--remote",
+                "ssh-remote+dev-box",
+                "/srv/app/.rsworktree/feature"
+            ]
+        );
+        assert_eq!(
+            connection_instructions("dev-box", "/srv/app/.rsworktree/feature"),
+            [
+                "ssh -t dev-box 'cd /srv/app/.rsworktree/feature && exec \"$SHELL\" -l'",
+                "vscode://vscode-remote/ssh-remote+dev-box/srv/app/.rsworktree/feature",
+            ]
+        );
+    }
+}
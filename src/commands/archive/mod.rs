@@ -0,0 +1,394 @@
+//! `archive` and `unarchive`: park an inactive worktree under
+//! `.rsworktree/archive/<name>` and bring it back later.
+//!
+//! An archive holds a git bundle of the branch, the uncommitted changes as a
+//! binary diff, a tarball of the untracked files and an `archive.json`
+//! manifest. Ignored files such as build output are not kept.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use owo_colors::{OwoColorize, Stream};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Repo,
+    commands::{
+        create::{CreateCommand, base_config_key},
+        export::{worktree_branch, write_bundle, write_tarball},
+        info::format_age,
+        open::resolve_by_name,
+        rename::remove_empty_parents,
+        rm::RemoveCommand,
+    },
+    output,
+};
+
+/// Directory inside `.rsworktree` holding the archives.
+pub const ARCHIVE_DIR: &str = "archive";
+const MANIFEST_FILE: &str = "archive.json";
+const BUNDLE_FILE: &str = "branch.bundle";
+const CHANGES_FILE: &str = "changes.patch";
+const UNTRACKED_FILE: &str = "untracked.tar.gz";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    name: String,
+    branch: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base: Option<String>,
+    head: String,
+    /// Seconds since the Unix epoch.
+    archived_at: u64,
+}
+
+#[derive(Debug)]
+pub struct ArchiveCommand {
+    name: String,
+}
+
+impl ArchiveCommand {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let resolved = resolve_by_name(&self.name, repo)?;
+        let branch = worktree_branch(&resolved.path)?;
+        let archive_dir = repo.rsworktree_dir().join(ARCHIVE_DIR).join(&resolved.name);
+        if archive_dir.join(MANIFEST_FILE).exists() {
+            return Err(eyre::eyre!(
+                "`{}` is already archived; run `rsworktree unarchive {}` or delete `{}` first",
+                resolved.name,
+                resolved.name,
+                archive_dir.display()
+            ));
+        }
+
+        fs::create_dir_all(&archive_dir)
+            .wrap_err_with(|| eyre::eyre!("failed to create `{}`", archive_dir.display()))?;
+        if let Err(error) =
+            write_archive(repo, &resolved.name, &resolved.path, &branch, &archive_dir)
+        {
+            let _ = fs::remove_dir_all(&archive_dir);
+            remove_empty_parents(&archive_dir, &repo.rsworktree_dir().join(ARCHIVE_DIR));
+            return Err(error);
+        }
+
+        RemoveCommand::new(resolved.name.clone(), true)
+            .with_quiet(true)
+            .with_spawn_shell(false)
+            .with_remove_local_branch(true)
+            .execute(repo)
+            .wrap_err_with(|| {
+                eyre::eyre!(
+                    "archived `{}` to `{}` but failed to remove the worktree",
+                    resolved.name,
+                    archive_dir.display()
+                )
+            })?;
+
+        output::success(tr!(
+            "Archived worktree `{}` to `{}`.",
+            format_name(&resolved.name),
+            format_path(&archive_dir)
+        ));
+        output::hint(tr!(
+            "Restore it with `rsworktree unarchive {}`.",
+            resolved.name
+        ));
+        Ok(())
+    }
+
+    /// Prints the archived worktrees with the time they were archived.
+    pub fn list(repo: &Repo) -> color_eyre::Result<()> {
+        let manifests = read_manifests(&repo.rsworktree_dir().join(ARCHIVE_DIR))?;
+        if manifests.is_empty() {
+            output::info(tr!("No archived worktrees."));
+            return Ok(());
+        }
+        let now = now();
+        output::heading(tr!("Archived worktrees"));
+        for manifest in manifests {
+            output::item(tr!(
+                "{} ({}, archived {})",
+                format_name(&manifest.name),
+                manifest.branch,
+                format_age(now.saturating_sub(manifest.archived_at))
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct UnarchiveCommand {
+    name: String,
+}
+
+impl UnarchiveCommand {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let archives_dir = repo.rsworktree_dir().join(ARCHIVE_DIR);
+        let archive_dir = archives_dir.join(&self.name);
+        let manifest = read_manifest(&archive_dir).ok_or_else(|| {
+            eyre::eyre!(
+                "no archived worktree named `{}`; run `rsworktree archive --list` to see the archives",
+                self.name
+            )
+        })?;
+        let worktree_path = repo.worktrees_dir().join(&manifest.name);
+        if worktree_path.exists() {
+            return Err(eyre::eyre!(
+                "`{}` already exists; remove or rename it before unarchiving",
+                worktree_path.display()
+            ));
+        }
+
+        let changes = archive_dir.join(CHANGES_FILE);
+        CreateCommand::new(manifest.name.clone(), manifest.base.clone())
+            .with_bundle(Some(archive_dir.join(BUNDLE_FILE)))
+            .with_patch(changes.exists().then_some(changes))
+            .create_without_enter(repo, false)?;
+
+        let untracked = archive_dir.join(UNTRACKED_FILE);
+        if untracked.exists() {
+            extract_tarball(&untracked, &worktree_path)?;
+        }
+
+        fs::remove_dir_all(&archive_dir)
+            .wrap_err_with(|| eyre::eyre!("failed to delete `{}`", archive_dir.display()))?;
+        remove_empty_parents(&archive_dir, &archives_dir);
+        // Only succeeds once the last archive is gone.
+        let _ = fs::remove_dir(&archives_dir);
+
+        output::success(tr!(
+            "Restored worktree `{}` from the archive.",
+            format_name(&manifest.name)
+        ));
+        output::hint(tr!("Enter it with `rsworktree switch {}`.", manifest.name));
+        Ok(())
+    }
+}
+
+fn write_archive(
+    repo: &Repo,
+    name: &str,
+    worktree_path: &Path,
+    branch: &str,
+    archive_dir: &Path,
+) -> color_eyre::Result<()> {
+    write_bundle(worktree_path, branch, &archive_dir.join(BUNDLE_FILE))?;
+
+    let changes = git_output(worktree_path, &["diff", "--binary", "HEAD"])?;
+    if !changes.is_empty() {
+        fs::write(archive_dir.join(CHANGES_FILE), &changes)
+            .wrap_err("failed to write the uncommitted changes")?;
+    }
+
+    let untracked = untracked_files(worktree_path)?;
+    if !untracked.is_empty() {
+        write_tarball(worktree_path, &untracked, &archive_dir.join(UNTRACKED_FILE))?;
+    }
+
+    let head = git2::Repository::open(worktree_path)
+        .and_then(|git| git.head()?.peel_to_commit().map(|commit| commit.id()))
+        .wrap_err_with(|| eyre::eyre!("failed to read HEAD of `{}`", worktree_path.display()))?;
+    let base = repo
+        .git()
+        .config()
+        .and_then(|config| config.get_string(&base_config_key(branch)))
+        .ok();
+    let manifest = Manifest {
+        name: name.to_owned(),
+        branch: branch.to_owned(),
+        base,
+        head: head.to_string(),
+        archived_at: now(),
+    };
+    let contents = serde_json::to_string_pretty(&manifest)?;
+    fs::write(archive_dir.join(MANIFEST_FILE), contents)
+        .wrap_err("failed to write the archive manifest")
+}
+
+fn untracked_files(worktree_path: &Path) -> color_eyre::Result<Vec<String>> {
+    let listing = git_output(
+        worktree_path,
+        &["ls-files", "-z", "--others", "--exclude-standard"],
+    )?;
+    Ok(String::from_utf8_lossy(&listing)
+        .split('\0')
+        .filter(|file| !file.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+fn git_output(dir: &Path, args: &[&str]) -> color_eyre::Result<Vec<u8>> {
+    let command_line = format!("git {}", args.join(" "));
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .wrap_err_with(|| eyre::eyre!("failed to run `{command_line}`"))?;
+    if !output.status.success() {
+        return Err(eyre::eyre!(
+            "`{command_line}` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+fn extract_tarball(tarball: &Path, destination: &Path) -> color_eyre::Result<()> {
+    let output = Command::new("tar")
+        .arg("-xzf")
+        .arg(tarball)
+        .arg("-C")
+        .arg(destination)
+        .output()
+        .wrap_err("failed to run `tar`")?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(eyre::eyre!(
+        "`tar` failed to restore the untracked files: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+fn read_manifest(archive_dir: &Path) -> Option<Manifest> {
+    let text = fs::read_to_string(archive_dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Manifests of every archive below `archives_dir`, sorted by name.
+fn read_manifests(archives_dir: &Path) -> color_eyre::Result<Vec<Manifest>> {
+    if !archives_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut manifests = Vec::new();
+    let mut pending = vec![archives_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        if let Some(manifest) = read_manifest(&dir) {
+            manifests.push(manifest);
+            continue;
+        }
+        for entry in fs::read_dir(&dir)
+            .wrap_err_with(|| eyre::eyre!("failed to read `{}`", dir.display()))?
+        {
+            let path: PathBuf = entry.wrap_err("failed to read directory entry")?.path();
+            if path.is_dir() {
+                pending.push(path);
+            }
+        }
+    }
+    manifests.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(manifests)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+fn format_name(name: &str) -> String {
+    format!(
+        "{}",
+        name.if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan().bold()))
+    )
+}
+
+fn format_path(path: &Path) -> String {
+    let raw = path.display().to_string();
+    format!(
+        "{}",
+        raw.as_str()
+            .if_supports_color(Stream::Stdout, |text| format!("{}", text.blue()))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    fn run(dir: &Path, args: &[&str]) -> color_eyre::Result<String> {
+        let output = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()?;
+        if !output.status.success() {
+            return Err(eyre::eyre!(
+                "git {:?} failed: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn init_repo(dir: &Path) -> color_eyre::Result<Repo> {
+        run(dir, &["init"])?;
+        fs::write(dir.join("README.md"), "test")?;
+        run(dir, &["add", "README.md"])?;
+        run(dir, &["commit", "-m", "Initial commit"])?;
+        Repo::discover_from(dir)
+    }
+
+    #[test]
+    fn archive_and_unarchive_round_trip() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let repo = init_repo(dir.path())?;
+        CreateCommand::new("feature/parked".into(), None).create_without_enter(&repo, true)?;
+        let worktree_path = repo.worktrees_dir().join("feature/parked");
+        fs::write(worktree_path.join("done.txt"), "committed")?;
+        run(&worktree_path, &["add", "done.txt"])?;
+        run(&worktree_path, &["commit", "-m", "Add done"])?;
+        let head = run(&worktree_path, &["rev-parse", "HEAD"])?;
+        fs::write(worktree_path.join("README.md"), "edited")?;
+        fs::write(worktree_path.join("scratch.txt"), "untracked")?;
+
+        ArchiveCommand::new("parked".into()).execute(&repo)?;
+
+        assert!(!worktree_path.exists());
+        assert!(run(dir.path(), &["branch", "--list", "feature/parked"])?.is_empty());
+        let archive_dir = repo
+            .worktrees_dir()
+            .join(ARCHIVE_DIR)
+            .join("feature/parked");
+        for file in [MANIFEST_FILE, BUNDLE_FILE, CHANGES_FILE, UNTRACKED_FILE] {
+            assert!(archive_dir.join(file).is_file(), "missing {file}");
+        }
+        let manifests = read_manifests(&repo.rsworktree_dir().join(ARCHIVE_DIR))?;
+        assert_eq!(manifests.len(), 1);
+        assert_eq!(manifests[0].branch, "feature/parked");
+        assert!(ArchiveCommand::new("parked".into()).execute(&repo).is_err());
+
+        UnarchiveCommand::new("feature/parked".into()).execute(&repo)?;
+
+        assert_eq!(run(&worktree_path, &["rev-parse", "HEAD"])?, head);
+        assert_eq!(
+            fs::read_to_string(worktree_path.join("README.md"))?,
+            "edited"
+        );
+        assert_eq!(
+            fs::read_to_string(worktree_path.join("scratch.txt"))?,
+            "untracked"
+        );
+        assert!(!repo.rsworktree_dir().join(ARCHIVE_DIR).exists());
+        Ok(())
+    }
+}
@@ -1,21 +1,42 @@
-use std::{path::Path, process::Command};
+use std::{cell::RefCell, path::Path, process::Command};
 
 use color_eyre::eyre::{self, WrapErr};
 use owo_colors::{OwoColorize, Stream};
 
 pub(crate) const SHELL_OVERRIDE_ENV: &str = "RSWORKTREE_SHELL";
 
-use crate::Repo;
+use crate::{
+    Repo,
+    commands::open::multiplexer,
+    output,
+    process::{CommandOutput, CommandRunner, SystemCommandRunner, command_failure},
+    telemetry::{Event, EventStore},
+};
 
 #[derive(Debug)]
-pub struct CdCommand {
+pub struct CdCommand<R = SystemCommandRunner> {
     name: String,
     print_only: bool,
+    runner: RefCell<R>,
 }
 
 impl CdCommand {
     pub fn new(name: String, print_only: bool) -> Self {
-        Self { name, print_only }
+        Self::with_runner(name, print_only, SystemCommandRunner)
+    }
+}
+
+impl<R> CdCommand<R>
+where
+    R: CommandRunner,
+{
+    /// Runs the subshell and `tmux` through `runner`.
+    pub fn with_runner(name: String, print_only: bool, runner: R) -> Self {
+        Self {
+            name,
+            print_only,
+            runner: RefCell::new(runner),
+        }
     }
 
     pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
@@ -33,6 +54,10 @@ impl CdCommand {
         let canonical = worktree_path
             .canonicalize()
             .wrap_err_with(|| eyre::eyre!("failed to resolve `{}`", worktree_path.display()))?;
+        EventStore::new(&repo.rsworktree_dir()).record(Event::WorktreeUsed {
+            worktree: self.name.clone(),
+            command: String::from("cd"),
+        });
 
         if self.print_only {
             let path_raw = format!("{}", canonical.display());
@@ -46,8 +71,12 @@ impl CdCommand {
             return Ok(());
         }
 
-        // Check if we're in a tmux session
-        if std::env::var("TMUX").is_ok() {
+        // Check if we're in a tmux session, or in Windows Terminal on Windows
+        if cfg!(windows) {
+            if multiplexer::is_windows_terminal(|name| std::env::var_os(name).is_some()) {
+                return self.execute_windows_terminal(repo, &canonical);
+            }
+        } else if std::env::var("TMUX").is_ok() {
             return self.execute_tmux(repo, &canonical);
         }
 
@@ -61,82 +90,91 @@ impl CdCommand {
         cmd.args(args);
         cmd.current_dir(canonical);
         cmd.env("PWD", canonical.as_os_str());
-        cmd.status()
+        self.runner
+            .borrow_mut()
+            .status(&mut cmd)
             .wrap_err("failed to spawn subshell")?
-            .success()
+            .success
             .then_some(())
             .ok_or_else(|| eyre::eyre!("subshell exited with a non-zero status"))
     }
 
-    fn execute_tmux(&self, repo: &Repo, canonical: &Path) -> color_eyre::Result<()> {
-        let project_name = repo
-            .root()
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown");
+    /// Opens the worktree in a new tab running the default profile's shell.
+    fn execute_windows_terminal(&self, repo: &Repo, canonical: &Path) -> color_eyre::Result<()> {
+        let tab_name = multiplexer::window_name(repo, &self.name, canonical);
+
+        multiplexer::open_windows_terminal_tab(
+            &mut *self.runner.borrow_mut(),
+            &tab_name,
+            canonical,
+            &[],
+        )?;
+        let tab_label = format_with_color(&tab_name, |text| format!("{}", text.cyan().bold()));
+        output::success(tr!("Opened Windows Terminal tab `{}`", tab_label));
+        Ok(())
+    }
 
-        let session_name = format!("{}/{}", project_name, self.name);
+    fn execute_tmux(&self, repo: &Repo, canonical: &Path) -> color_eyre::Result<()> {
+        let session_name = multiplexer::window_name(repo, &self.name, canonical);
 
         // Check if session with this name already exists
-        let list_output = Command::new("tmux")
-            .args(["list-sessions", "-F", "#{session_name}"])
-            .output()
+        let list_output = self
+            .tmux(&["list-sessions", "-F", "#{session_name}"])
             .wrap_err("failed to list tmux sessions")?;
 
-        let existing_sessions = String::from_utf8_lossy(&list_output.stdout);
-        let session_exists = existing_sessions
+        let session_exists = list_output
+            .stdout
             .lines()
             .any(|line| line.trim() == session_name);
 
         if session_exists {
             // Switch to existing session
-            let status = Command::new("tmux")
-                .args(["switch-client", "-t", &session_name])
-                .status()
-                .wrap_err("failed to switch to tmux session")?;
-
-            if !status.success() {
-                return Err(eyre::eyre!("failed to switch to tmux session `{}`", session_name));
-            }
-
-            let session_label = format_with_color(&session_name, |text| {
-                format!("{}", text.cyan().bold())
-            });
-            println!("Switched to tmux session `{}`", session_label);
+            self.checked_tmux(&["switch-client", "-t", &session_name])
+                .wrap_err_with(|| {
+                    eyre::eyre!("failed to switch to tmux session `{}`", session_name)
+                })?;
+
+            let session_label =
+                format_with_color(&session_name, |text| format!("{}", text.cyan().bold()));
+            output::success(tr!("Switched to tmux session `{}`", session_label));
         } else {
             // Create new session (detached) then switch to it
-            let status = Command::new("tmux")
-                .args([
-                    "new-session",
-                    "-d",
-                    "-s",
-                    &session_name,
-                    "-c",
-                    &canonical.display().to_string(),
-                ])
-                .status()
-                .wrap_err("failed to create tmux session")?;
-
-            if !status.success() {
-                return Err(eyre::eyre!("failed to create tmux session `{}`", session_name));
-            }
+            self.checked_tmux(&[
+                "new-session",
+                "-d",
+                "-s",
+                &session_name,
+                "-c",
+                &canonical.display().to_string(),
+            ])
+            .wrap_err_with(|| eyre::eyre!("failed to create tmux session `{}`", session_name))?;
 
             // Switch to the new session
-            let status = Command::new("tmux")
-                .args(["switch-client", "-t", &session_name])
-                .status()
-                .wrap_err("failed to switch to tmux session")?;
+            self.checked_tmux(&["switch-client", "-t", &session_name])
+                .wrap_err_with(|| {
+                    eyre::eyre!("failed to switch to tmux session `{}`", session_name)
+                })?;
+
+            let session_label =
+                format_with_color(&session_name, |text| format!("{}", text.cyan().bold()));
+            output::success(tr!("Created tmux session `{}`", session_label));
+        }
 
-            if !status.success() {
-                return Err(eyre::eyre!("failed to switch to tmux session `{}`", session_name));
-            }
+        Ok(())
+    }
 
-            let session_label = format_with_color(&session_name, |text| {
-                format!("{}", text.cyan().bold())
-            });
-            println!("Created tmux session `{}`", session_label);
-        }
+    fn tmux(&self, args: &[&str]) -> color_eyre::Result<CommandOutput> {
+        let args = args.iter().map(|arg| (*arg).to_owned()).collect::<Vec<_>>();
+        self.runner.borrow_mut().run("tmux", Path::new("."), &args)
+    }
 
+    /// Runs `tmux` with `args`, failing when it exits unsuccessfully.
+    fn checked_tmux(&self, args: &[&str]) -> color_eyre::Result<()> {
+        let output = self.tmux(args)?;
+        if !output.success {
+            let args = args.iter().map(|arg| (*arg).to_owned()).collect::<Vec<_>>();
+            return Err(command_failure("tmux", &args, &output));
+        }
         Ok(())
     }
 }
@@ -154,6 +192,15 @@ pub(crate) fn shell_command() -> (String, Vec<String>) {
         return (override_shell, Vec::new());
     }
 
+    // `SHELL` from Git Bash names an MSYS path that Windows cannot start.
+    if cfg!(windows) {
+        let shell = std::env::var("COMSPEC")
+            .ok()
+            .filter(|shell| !shell.trim().is_empty())
+            .unwrap_or_else(|| "cmd.exe".into());
+        return (shell, Vec::new());
+    }
+
     if let Ok(shell) = std::env::var("SHELL")
         && !shell.trim().is_empty()
     {
@@ -174,7 +221,7 @@ mod tests {
 
     use tempfile::TempDir;
 
-    use crate::{Repo, commands::create::CreateCommand};
+    use crate::{Repo, commands::create::CreateCommand, process::ScriptedRunner};
 
     fn init_git_repo(dir: &TempDir) -> color_eyre::Result<()> {
         run(dir, ["git", "init"])?;
@@ -240,6 +287,40 @@ mod tests {
         assert!(command.execute(&repo).is_err());
     }
 
+    #[test]
+    fn tmux_switches_to_the_session_creating_it_first() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let session = multiplexer::window_name(&repo, "feature/test", dir.path());
+
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::success("main\n"))
+            .push(CommandOutput::success(""))
+            .push(CommandOutput::success(""));
+        CdCommand::with_runner("feature/test".into(), false, runner.clone())
+            .execute_tmux(&repo, dir.path())?;
+        let calls = runner.calls();
+        assert_eq!(calls[0].args, ["list-sessions", "-F", "#{session_name}"]);
+        assert_eq!(calls[1].args[..4], ["new-session", "-d", "-s", &session]);
+        assert_eq!(calls[2].args, ["switch-client", "-t", &session]);
+
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::success(format!("main\n{session}\n")))
+            .push(CommandOutput::failure(1, "no current client"));
+        let error = CdCommand::with_runner("feature/test".into(), false, runner.clone())
+            .execute_tmux(&repo, dir.path())
+            .expect_err("switch-client failed");
+        assert!(
+            format!("{error:?}").contains("no current client"),
+            "{error:?}"
+        );
+        assert_eq!(runner.calls().len(), 2);
+        Ok(())
+    }
+
     struct EnvGuard {
         key: &'static str,
         previous: Option<std::ffi::OsString>,
@@ -0,0 +1,369 @@
+//! `ci trigger` and `ci status`: start and follow the CI run of a worktree's branch.
+//!
+//! GitHub Actions runs are dispatched with `gh workflow run`, which needs the
+//! workflow to start; it is taken from `--workflow` or `ci.workflow` in
+//! `preferences.json`. GitLab pipelines are started with `glab ci run`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use owo_colors::{OwoColorize, Stream};
+use serde::Deserialize;
+
+use crate::{
+    GitProvider, Repo,
+    editor::CONFIG_FILE_NAME,
+    output,
+    process::{CommandRunner, SystemCommandRunner, command_failure},
+    provider::{PipelineState, PipelineStatus, ProviderBackend, ProviderClient, RequestBackend},
+};
+
+/// Delay between two status queries of `ci status --watch`.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default, Deserialize)]
+struct FileFormat {
+    #[serde(default)]
+    ci: Option<CiConfig>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CiConfig {
+    #[serde(default)]
+    workflow: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct CiCommand<R = SystemCommandRunner> {
+    name: String,
+    provider: GitProvider,
+    client: ProviderClient,
+    poll_interval: Duration,
+    runner: R,
+}
+
+impl CiCommand {
+    /// Runs the provider operations through the backend configured for `repo`.
+    pub fn new(name: String, provider: GitProvider, repo: &Repo) -> Self {
+        Self::with_runner(name, provider, SystemCommandRunner)
+            .with_backend(ProviderBackend::resolve(repo))
+    }
+}
+
+impl<R> CiCommand<R>
+where
+    R: CommandRunner,
+{
+    pub fn with_runner(name: String, provider: GitProvider, runner: R) -> Self {
+        Self {
+            name,
+            provider,
+            client: provider.client(ProviderBackend::Cli),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            runner,
+        }
+    }
+
+    /// Runs the provider operations through `backend` rather than the CLI.
+    pub fn with_backend(mut self, backend: ProviderBackend) -> Self {
+        self.client = self.provider.client(backend);
+        self
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Starts a CI run for the worktree's branch.
+    pub fn trigger(&mut self, repo: &Repo, workflow: Option<String>) -> color_eyre::Result<()> {
+        let worktree_path = self.ensure_worktree_path(repo)?;
+        let branch = self.determine_branch(&worktree_path)?;
+        let workflow = workflow
+            .or_else(|| configured_workflow(&repo.rsworktree_dir()))
+            .unwrap_or_default();
+        if self.provider == GitProvider::GitHub && workflow.is_empty() {
+            return Err(eyre::eyre!(
+                "GitHub needs the workflow to run; pass `--workflow <file>` or set `ci.workflow` in `{}`",
+                CONFIG_FILE_NAME
+            ));
+        }
+
+        let branch_label = format_with_color(&branch, |text| format!("{}", text.magenta().bold()));
+        output::info(tr!(
+            "Starting {} CI for `{}`...",
+            self.provider.display_name(),
+            branch_label
+        ));
+
+        self.client
+            .trigger_ci(&mut self.runner, &worktree_path, &branch, &workflow)?;
+
+        output::success(tr!("Started CI for branch `{}`.", branch_label));
+        output::hint(tr!(
+            "follow it with `rsworktree ci status {} --watch`",
+            self.name
+        ));
+        Ok(())
+    }
+
+    /// Reports the latest CI run of the worktree's branch.
+    ///
+    /// With `watch`, polls until the run has finished and returns its final
+    /// state. A failed or canceled run is an error, so scripts can chain on it.
+    pub fn status(
+        &mut self,
+        repo: &Repo,
+        watch: bool,
+    ) -> color_eyre::Result<Option<PipelineStatus>> {
+        let worktree_path = self.ensure_worktree_path(repo)?;
+        let branch = self.determine_branch(&worktree_path)?;
+        let branch_label = format_with_color(&branch, |text| format!("{}", text.magenta().bold()));
+
+        let Some(mut status) = self.fetch_status(&worktree_path, &branch)? else {
+            output::info(tr!("No CI runs found for branch `{}`.", branch_label));
+            return Ok(None);
+        };
+        print_status(&status, &branch_label);
+        if let Some(url) = &status.url {
+            output::field("URL", url);
+        }
+
+        while watch && !status.state.is_finished() {
+            thread::sleep(self.poll_interval);
+            let Some(next) = self.fetch_status(&worktree_path, &branch)? else {
+                continue;
+            };
+            if next.id != status.id || next.state != status.state {
+                print_status(&next, &branch_label);
+            }
+            status = next;
+        }
+
+        if matches!(status.state, PipelineState::Failed | PipelineState::Canceled) {
+            return Err(eyre::eyre!(
+                "CI run #{} for branch `{branch}` {}",
+                status.id,
+                status.state.as_str()
+            ));
+        }
+        Ok(Some(status))
+    }
+
+    fn fetch_status(
+        &mut self,
+        worktree_path: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<Option<PipelineStatus>> {
+        self.client
+            .ci_status(&mut self.runner, worktree_path, branch)
+    }
+
+    fn ensure_worktree_path(&self, repo: &Repo) -> color_eyre::Result<PathBuf> {
+        let worktrees_dir = repo.ensure_worktrees_dir()?;
+        let worktree_path = worktrees_dir.join(&self.name);
+        if !worktree_path.exists() {
+            return Err(eyre::eyre!(
+                "worktree `{}` does not exist under `{}`",
+                self.name,
+                worktrees_dir.display()
+            ));
+        }
+        Ok(worktree_path)
+    }
+
+    fn determine_branch(&mut self, worktree_path: &Path) -> color_eyre::Result<String> {
+        let args = vec![
+            "rev-parse".to_owned(),
+            "--abbrev-ref".to_owned(),
+            "HEAD".to_owned(),
+        ];
+        let output = self
+            .runner
+            .run("git", worktree_path, &args)
+            .wrap_err("failed to determine current branch with `git rev-parse`")?;
+
+        if !output.success {
+            return Err(command_failure("git", &args, &output));
+        }
+
+        let branch = output.stdout.trim();
+        if branch.is_empty() || branch == "HEAD" {
+            return Err(eyre::eyre!(
+                "worktree `{}` is not on a branch; CI runs are tied to branches",
+                self.name
+            ));
+        }
+
+        Ok(branch.to_owned())
+    }
+}
+
+fn configured_workflow(rsworktree_dir: &Path) -> Option<String> {
+    fs::read_to_string(rsworktree_dir.join(CONFIG_FILE_NAME))
+        .ok()
+        .and_then(|text| serde_json::from_str::<FileFormat>(&text).ok())
+        .and_then(|parsed| parsed.ci)
+        .and_then(|ci| ci.workflow)
+        .filter(|workflow| !workflow.trim().is_empty())
+}
+
+fn print_status(status: &PipelineStatus, branch_label: &str) {
+    let run = match &status.name {
+        Some(name) => format!("{name} #{}", status.id),
+        None => format!("#{}", status.id),
+    };
+    let state = status.state.as_str();
+    let message = tr!("CI {} for `{}`: {}", run, branch_label, state);
+    match status.state {
+        PipelineState::Success => output::success(message),
+        PipelineState::Failed => output::error(message),
+        _ => output::info(message),
+    }
+}
+
+fn format_with_color(value: &str, paint: impl Fn(&str) -> String) -> String {
+    value
+        .if_supports_color(Stream::Stdout, |text| paint(text))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    use crate::process::{CommandOutput, ScriptedRunner};
+
+    fn repo_with_worktree(name: &str) -> color_eyre::Result<(TempDir, Repo)> {
+        let dir = TempDir::new()?;
+        fs::create_dir_all(dir.path().join(".rsworktree").join(name))?;
+        git2::Repository::init(dir.path())?;
+        let repo = Repo::discover_from(dir.path())?;
+        Ok((dir, repo))
+    }
+
+    fn github_run(status: &str, conclusion: &str) -> String {
+        format!(
+            r#"[{{"databaseId":7,"workflowName":"CI","status":"{status}","conclusion":"{conclusion}","url":"https://github.com/o/r/actions/runs/7"}}]"#
+        )
+    }
+
+    #[test]
+    fn trigger_dispatches_configured_workflow() -> color_eyre::Result<()> {
+        let (dir, repo) = repo_with_worktree("feature")?;
+        fs::write(
+            dir.path().join(".rsworktree").join(CONFIG_FILE_NAME),
+            r#"{"ci": {"workflow": "ci.yml"}}"#,
+        )?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success(""));
+
+        let mut command =
+            CiCommand::with_runner("feature".into(), GitProvider::GitHub, runner.clone());
+        command.trigger(&repo, None)?;
+
+        let call = &runner.calls()[1];
+        assert_eq!(call.program, "gh");
+        assert_eq!(call.args, ["workflow", "run", "ci.yml", "--ref", "feature"]);
+        Ok(())
+    }
+
+    #[test]
+    fn trigger_requires_workflow_on_github_only() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+
+        let mut command =
+            CiCommand::with_runner("feature".into(), GitProvider::GitHub, runner.clone());
+        let error = command
+            .trigger(&repo, None)
+            .expect_err("workflow should be required");
+        assert!(error.to_string().contains("--workflow"));
+
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success(""));
+        let mut command =
+            CiCommand::with_runner("feature".into(), GitProvider::GitLab, runner.clone());
+        command.trigger(&repo, None)?;
+        assert_eq!(runner.calls()[1].program, "glab");
+        Ok(())
+    }
+
+    #[test]
+    fn status_watch_polls_until_run_finishes() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success(github_run("queued", "")));
+        runner.push(CommandOutput::success(github_run("in_progress", "")));
+        runner.push(CommandOutput::success(github_run("completed", "success")));
+
+        let mut command =
+            CiCommand::with_runner("feature".into(), GitProvider::GitHub, runner.clone())
+                .with_poll_interval(Duration::ZERO);
+        let status = command.status(&repo, true)?.expect("run should be found");
+
+        assert_eq!(status.state, PipelineState::Success);
+        assert_eq!(runner.calls().len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn status_fails_when_the_run_failed() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success(github_run("in_progress", "")));
+        runner.push(CommandOutput::success(github_run("completed", "failure")));
+
+        let mut command = CiCommand::with_runner("feature".into(), GitProvider::GitHub, runner)
+            .with_poll_interval(Duration::ZERO);
+        let error = command
+            .status(&repo, true)
+            .expect_err("a failed run should be an error");
+
+        assert!(error.to_string().contains("failed"));
+        Ok(())
+    }
+
+    #[test]
+    fn status_without_watch_queries_once() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success("[]"));
+
+        let mut command =
+            CiCommand::with_runner("feature".into(), GitProvider::GitHub, runner.clone());
+
+        assert_eq!(command.status(&repo, false)?, None);
+        assert_eq!(runner.calls().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn status_surfaces_provider_failure() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::failure(1, "boom"));
+
+        let mut command = CiCommand::with_runner("feature".into(), GitProvider::GitLab, runner);
+        let error = command
+            .status(&repo, false)
+            .expect_err("failure should be reported");
+
+        assert!(error.to_string().contains("glab ci get"));
+        Ok(())
+    }
+}
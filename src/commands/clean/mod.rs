@@ -0,0 +1,325 @@
+//! `rsworktree clean`: removes build artifacts such as `target/` or
+//! `node_modules/` from worktrees.
+//!
+//! Patterns come from `artifacts` in `config.toml`, or else from the project
+//! files found in the worktree root, and are matched like `--copy` patterns
+//! relative to the worktree. Only directories git ignores are removed, so a
+//! committed `dist/` stays where it is.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use git2::Repository;
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    Repo,
+    commands::{
+        info::{disk_usage, format_size},
+        list::{find_managed_worktrees, format_worktree},
+        open::resolve_by_name,
+    },
+    output,
+    worktree::copy::matches_components,
+};
+
+/// Artifact patterns used when the project file on the left exists.
+const DEFAULT_ARTIFACTS: [(&str, &[&str]); 9] = [
+    ("Cargo.toml", &["target"]),
+    (
+        "package.json",
+        &["**/node_modules", "**/.next", "**/dist", "**/.turbo"],
+    ),
+    (
+        "pyproject.toml",
+        &[
+            "**/__pycache__",
+            ".pytest_cache",
+            ".mypy_cache",
+            ".ruff_cache",
+        ],
+    ),
+    ("setup.py", &["**/__pycache__", "build", ".pytest_cache"]),
+    ("pom.xml", &["**/target"]),
+    ("build.gradle", &["**/build", ".gradle"]),
+    ("build.gradle.kts", &["**/build", ".gradle"]),
+    ("go.mod", &["bin"]),
+    ("mix.exs", &["_build", "deps"]),
+];
+
+#[derive(Debug, Default)]
+pub struct CleanCommand {
+    /// The worktree to clean; `None` cleans all of them.
+    name: Option<String>,
+    dry_run: bool,
+}
+
+impl CleanCommand {
+    pub fn new(name: String) -> Self {
+        Self {
+            name: Some(name),
+            dry_run: false,
+        }
+    }
+
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Only list the artifact directories and their size.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Cleans the worktrees and returns the number of bytes freed, or that
+    /// would be freed in a dry run.
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<u64> {
+        let worktrees = match &self.name {
+            Some(name) => {
+                let resolved = resolve_by_name(name, repo)?;
+                vec![(resolved.name, resolved.path)]
+            }
+            None => {
+                let worktrees_dir = repo.ensure_worktrees_dir()?;
+                find_managed_worktrees(&worktrees_dir)?
+                    .into_iter()
+                    .map(|relative| (format_worktree(&relative), worktrees_dir.join(relative)))
+                    .collect()
+            }
+        };
+        if worktrees.is_empty() {
+            output::info(tr!("No worktrees to clean."));
+            return Ok(0);
+        }
+
+        let configured = repo.config()?.artifacts;
+        let mut total = 0;
+        for (name, path) in &worktrees {
+            let patterns = if configured.is_empty() {
+                default_artifacts(path)
+            } else {
+                configured.clone()
+            };
+            total += self.clean(name, path, &patterns)?;
+        }
+
+        if worktrees.len() > 1 {
+            let total = format_size(total);
+            if self.dry_run {
+                output::info(tr!("Would free {} in total.", total));
+            } else {
+                output::success(tr!("Freed {} in total.", total));
+            }
+        }
+        Ok(total)
+    }
+
+    fn clean(&self, name: &str, path: &Path, patterns: &[String]) -> color_eyre::Result<u64> {
+        let artifacts = find_artifacts(path, patterns)?;
+        if artifacts.is_empty() {
+            output::info(tr!("Nothing to clean in `{}`.", name));
+            return Ok(0);
+        }
+
+        let styled_name = format!(
+            "{}",
+            name.if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
+        );
+        output::heading(tr!("Artifacts in `{}`:", styled_name));
+        let mut freed = 0;
+        for artifact in artifacts {
+            output::item(format!(
+                "{} ({})",
+                artifact.path.display(),
+                format_size(artifact.size)
+            ));
+            if self.dry_run {
+                freed += artifact.size;
+                continue;
+            }
+            match fs::remove_dir_all(path.join(&artifact.path)) {
+                Ok(()) => freed += artifact.size,
+                Err(err) => output::warn(tr!(
+                    "could not remove `{}`: {}",
+                    artifact.path.display(),
+                    err
+                )),
+            }
+        }
+
+        if self.dry_run {
+            output::info(tr!("Would free {} in `{}`.", format_size(freed), name));
+        } else {
+            output::success(tr!("Freed {} in `{}`.", format_size(freed), name));
+        }
+        Ok(freed)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Artifact {
+    /// Relative to the worktree.
+    path: PathBuf,
+    size: u64,
+}
+
+/// Patterns for the kinds of projects found in the worktree root.
+fn default_artifacts(worktree_path: &Path) -> Vec<String> {
+    let mut patterns = Vec::<String>::new();
+    for (marker, artifacts) in DEFAULT_ARTIFACTS {
+        if worktree_path.join(marker).exists() {
+            for artifact in artifacts {
+                if !patterns.iter().any(|pattern| pattern == artifact) {
+                    patterns.push((*artifact).to_owned());
+                }
+            }
+        }
+    }
+    patterns
+}
+
+/// Ignored directories below `worktree_path` matching one of `patterns`.
+fn find_artifacts(worktree_path: &Path, patterns: &[String]) -> color_eyre::Result<Vec<Artifact>> {
+    let patterns = patterns
+        .iter()
+        .map(|pattern| pattern.trim().trim_start_matches("./").trim_matches('/'))
+        .filter(|pattern| !pattern.is_empty())
+        .map(|pattern| pattern.split('/').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut artifacts = Vec::new();
+    if patterns.is_empty() {
+        return Ok(artifacts);
+    }
+    let git = Repository::open(worktree_path).wrap_err_with(|| {
+        eyre::eyre!("failed to open worktree at `{}`", worktree_path.display())
+    })?;
+
+    let mut pending = vec![Vec::<String>::new()];
+    while let Some(components) = pending.pop() {
+        let dir = worktree_path.join(components.iter().collect::<PathBuf>());
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                continue;
+            }
+            let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+                continue;
+            };
+            if components.is_empty() && name == ".git" {
+                continue;
+            }
+            let mut child = components.clone();
+            child.push(name);
+            let relative = child.iter().collect::<PathBuf>();
+            let ignored = git.is_path_ignored(&relative).unwrap_or(false);
+
+            if ignored
+                && patterns
+                    .iter()
+                    .any(|pattern| matches_components(pattern, &child, false))
+            {
+                artifacts.push(Artifact {
+                    size: disk_usage(&entry.path()),
+                    path: relative,
+                });
+            } else if !ignored
+                && patterns
+                    .iter()
+                    .any(|pattern| matches_components(pattern, &child, true))
+            {
+                pending.push(child);
+            }
+        }
+    }
+
+    artifacts.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(artifacts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::process::Command;
+
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) -> color_eyre::Result<()> {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()?;
+        assert!(status.success(), "git {args:?} failed");
+        Ok(())
+    }
+
+    #[test]
+    fn default_artifacts_follow_the_project_files() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        assert!(default_artifacts(dir.path()).is_empty());
+
+        fs::write(dir.path().join("Cargo.toml"), "")?;
+        fs::write(dir.path().join("pom.xml"), "")?;
+        assert_eq!(default_artifacts(dir.path()), ["target", "**/target"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn removes_ignored_artifacts_and_keeps_tracked_ones() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let root = dir.path();
+        git(root, &["init", "-b", "main"])?;
+        fs::write(root.join(".gitignore"), "target/\nnode_modules/\n")?;
+        fs::write(root.join("Cargo.toml"), "")?;
+        fs::write(root.join("package.json"), "{}")?;
+        fs::create_dir_all(root.join("dist"))?;
+        fs::write(root.join("dist/app.js"), "committed")?;
+        git(root, &["add", "."])?;
+        git(root, &["commit", "-m", "init"])?;
+        git(root, &["worktree", "add", ".rsworktree/feature"])?;
+
+        let worktree = root.join(".rsworktree/feature");
+        fs::create_dir_all(worktree.join("target/debug"))?;
+        fs::write(worktree.join("target/debug/app"), vec![0; 2048])?;
+        fs::create_dir_all(worktree.join("web/node_modules/react"))?;
+        fs::write(worktree.join("web/node_modules/react/index.js"), "react")?;
+        let repo = Repo::discover_from(root)?;
+
+        let patterns = default_artifacts(&worktree);
+        let artifacts = find_artifacts(&worktree, &patterns)?;
+        let paths = artifacts
+            .iter()
+            .map(|artifact| artifact.path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("target"),
+                Path::new("web").join("node_modules")
+            ]
+        );
+
+        let would_free = CleanCommand::new("feature".into())
+            .with_dry_run(true)
+            .execute(&repo)?;
+        assert!(would_free >= 2048);
+        assert!(worktree.join("target").exists());
+
+        let freed = CleanCommand::all().execute(&repo)?;
+        assert_eq!(freed, would_free);
+        assert!(!worktree.join("target").exists());
+        assert!(!worktree.join("web/node_modules").exists());
+        assert!(worktree.join("dist/app.js").exists());
+        assert!(worktree.join("web").exists());
+
+        Ok(())
+    }
+}
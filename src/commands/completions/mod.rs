@@ -0,0 +1,183 @@
+//! `completions`: shell completion scripts for bash, zsh, fish and PowerShell.
+//!
+//! `clap_complete` generates the scripts from the clap command tree. Worktree
+//! and branch names change all the time, so arguments named `worktree` or
+//! `branch` are completed by calling back into the hidden `rsworktree
+//! __complete <worktrees|branches>` command when the user presses tab.
+
+mod script;
+
+use std::{fmt, str::FromStr};
+
+use git2::BranchType;
+
+use crate::{
+    Repo,
+    commands::list::{find_worktrees, format_worktree},
+    commands::shell_init::Shell,
+};
+
+/// The name of the hidden subcommand the scripts call for dynamic values.
+pub(crate) const COMPLETE_COMMAND: &str = "__complete";
+
+#[derive(Debug)]
+pub struct CompletionsCommand {
+    shell: Shell,
+    command: clap::Command,
+}
+
+impl CompletionsCommand {
+    /// Generates the script for `shell` from the `command` tree.
+    pub fn new(shell: Shell, command: clap::Command) -> Self {
+        Self { shell, command }
+    }
+
+    pub fn execute(mut self) -> color_eyre::Result<()> {
+        print!("{}", script::render(self.shell, &mut self.command));
+        Ok(())
+    }
+}
+
+/// Values completed at runtime by `__complete`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Worktrees,
+    Branches,
+}
+
+impl fmt::Display for CompletionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompletionKind::Worktrees => write!(f, "worktrees"),
+            CompletionKind::Branches => write!(f, "branches"),
+        }
+    }
+}
+
+impl FromStr for CompletionKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "worktrees" => Ok(CompletionKind::Worktrees),
+            "branches" => Ok(CompletionKind::Branches),
+            _ => Err(format!(
+                "unknown completion '{s}', expected 'worktrees' or 'branches'"
+            )),
+        }
+    }
+}
+
+/// Prints one completion candidate per line.
+///
+/// Completion runs on every tab press, also outside repositories, so any
+/// failure just yields no candidates.
+#[derive(Debug)]
+pub struct CompleteCommand {
+    kind: CompletionKind,
+}
+
+impl CompleteCommand {
+    pub fn new(kind: CompletionKind) -> Self {
+        Self { kind }
+    }
+
+    pub fn execute(&self) -> color_eyre::Result<()> {
+        let Ok(repo) = Repo::discover() else {
+            return Ok(());
+        };
+        for candidate in self.candidates(&repo) {
+            println!("{candidate}");
+        }
+        Ok(())
+    }
+
+    fn candidates(&self, repo: &Repo) -> Vec<String> {
+        match self.kind {
+            CompletionKind::Worktrees => worktree_names(repo),
+            CompletionKind::Branches => branch_names(repo),
+        }
+    }
+}
+
+fn worktree_names(repo: &Repo) -> Vec<String> {
+    let mut names = find_worktrees(repo)
+        .unwrap_or_default()
+        .iter()
+        .map(|path| format_worktree(path))
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+/// Local branches followed by remote-tracking branches such as `origin/main`.
+fn branch_names(repo: &Repo) -> Vec<String> {
+    let mut names = Vec::new();
+    for branch_type in [BranchType::Local, BranchType::Remote] {
+        let Ok(branches) = repo.git().branches(Some(branch_type)) else {
+            continue;
+        };
+        let mut found = branches
+            .filter_map(Result::ok)
+            .filter_map(|(branch, _)| branch.name().ok().flatten().map(str::to_owned))
+            .filter(|name| !name.ends_with("/HEAD"))
+            .collect::<Vec<_>>();
+        found.sort();
+        names.extend(found);
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn parses_completion_kinds() {
+        assert_eq!(
+            "worktrees".parse::<CompletionKind>(),
+            Ok(CompletionKind::Worktrees)
+        );
+        assert_eq!(
+            "branches".parse::<CompletionKind>(),
+            Ok(CompletionKind::Branches)
+        );
+        assert!("tags".parse::<CompletionKind>().is_err());
+    }
+
+    #[test]
+    fn candidates_list_worktrees_and_branches() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let git = git2::Repository::init(dir.path())?;
+        let signature = git2::Signature::now("Test", "test@example.com")?;
+        let tree = git.find_tree(git.index()?.write_tree()?)?;
+        let commit = git.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])?;
+        git.branch("feature/login", &git.find_commit(commit)?, false)?;
+        for name in ["feature/login", "hotfix"] {
+            let path = dir.path().join(".rsworktree").join(name);
+            fs::create_dir_all(&path)?;
+            fs::write(path.join(".git"), "gitdir: elsewhere")?;
+        }
+        let repo = Repo::discover_from(dir.path())?;
+        let head = repo
+            .git()
+            .head()?
+            .shorthand()
+            .unwrap_or_default()
+            .to_owned();
+
+        assert_eq!(
+            CompleteCommand::new(CompletionKind::Worktrees).candidates(&repo),
+            ["feature/login", "hotfix"]
+        );
+        assert_eq!(
+            CompleteCommand::new(CompletionKind::Branches).candidates(&repo),
+            ["feature/login".to_owned(), head]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,491 @@
+//! Completion scripts generated by `clap_complete`, plus a small wrapper for
+//! worktree and branch names.
+//!
+//! `clap_complete` renders subcommands, options and file arguments from the
+//! clap command tree. Its scripts cannot call back into the binary, so each
+//! shell gets a wrapper that tracks the subcommand path typed so far and,
+//! where an argument takes a worktree or branch, offers the output of
+//! `rsworktree __complete` instead of the generated candidates.
+
+use std::fmt::Write;
+
+use clap::{Arg, Command};
+
+use super::{COMPLETE_COMMAND, CompletionKind};
+use crate::commands::shell_init::Shell;
+
+/// Where the PowerShell wrapper is spliced into the generated script block.
+const POWERSHELL_ANCHOR: &str = "    $completions = @(switch ($command) {";
+
+/// Renders the completion script for `shell`.
+pub(super) fn render(shell: Shell, command: &mut Command) -> String {
+    command.build();
+    let bin = command.get_name().to_owned();
+    let generator = match shell {
+        Shell::Bash => clap_complete::Shell::Bash,
+        Shell::Zsh => clap_complete::Shell::Zsh,
+        Shell::Fish => clap_complete::Shell::Fish,
+        Shell::Powershell => clap_complete::Shell::PowerShell,
+    };
+    let mut generated = Vec::new();
+    clap_complete::generate(generator, command, &bin, &mut generated);
+
+    let mut tree = Tree {
+        bin,
+        transitions: Vec::new(),
+        values: Vec::new(),
+        hidden: Vec::new(),
+    };
+    collect(command, &[], &mut tree);
+    let script = without_hidden(shell, &String::from_utf8_lossy(&generated), &tree.hidden);
+    match shell {
+        Shell::Bash => bash(&tree, script),
+        Shell::Zsh => zsh(&tree, script),
+        Shell::Fish => fish(&tree, script),
+        Shell::Powershell => powershell(&tree, script),
+    }
+}
+
+/// What the wrappers need to know about the command tree.
+struct Tree {
+    bin: String,
+    transitions: Vec<Transition>,
+    values: Vec<Value>,
+    /// Hidden subcommands, which `clap_complete` still offers.
+    hidden: Vec<String>,
+}
+
+/// A word that moves from one subcommand path to another.
+struct Transition {
+    from: String,
+    word: String,
+    to: String,
+}
+
+/// An argument whose values the wrapper decides on.
+struct Value {
+    /// The name and aliases of each subcommand leading to the argument.
+    levels: Vec<Vec<String>>,
+    /// The option spelling, or `None` for the first positional argument.
+    flag: Option<String>,
+    /// `None` for an option of a command with dynamic positional values,
+    /// whose own values are left to the generated script.
+    kind: Option<CompletionKind>,
+}
+
+impl Tree {
+    /// The canonical path of `levels`, e.g. `rsworktree pr checkout`.
+    fn path(&self, levels: &[Vec<String>]) -> String {
+        std::iter::once(self.bin.as_str())
+            .chain(levels.iter().map(|words| words[0].as_str()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn kind(arg: &Arg) -> Option<CompletionKind> {
+    let value_name = arg.get_value_names()?.first()?;
+    match value_name.as_str() {
+        "worktree" => Some(CompletionKind::Worktrees),
+        "branch" => Some(CompletionKind::Branches),
+        _ => None,
+    }
+}
+
+fn collect(command: &Command, levels: &[Vec<String>], tree: &mut Tree) {
+    let positional = command
+        .get_positionals()
+        .find(|arg| !arg.is_hide_set())
+        .and_then(kind);
+    if positional.is_some() {
+        tree.values.push(Value {
+            levels: levels.to_vec(),
+            flag: None,
+            kind: positional,
+        });
+    }
+    let options = command.get_arguments().filter(|arg| {
+        !arg.is_positional() && !arg.is_hide_set() && arg.get_action().takes_values()
+    });
+    for arg in options {
+        let kind = kind(arg);
+        if kind.is_none() && positional.is_none() {
+            continue;
+        }
+        let flags = arg
+            .get_short()
+            .map(|short| format!("-{short}"))
+            .into_iter()
+            .chain(arg.get_long().map(|long| format!("--{long}")));
+        for flag in flags {
+            tree.values.push(Value {
+                levels: levels.to_vec(),
+                flag: Some(flag),
+                kind,
+            });
+        }
+    }
+
+    for subcommand in command.get_subcommands() {
+        let name = subcommand.get_name().to_owned();
+        if subcommand.is_hide_set() {
+            if !tree.hidden.contains(&name) {
+                tree.hidden.push(name);
+            }
+            continue;
+        }
+        let words = std::iter::once(name)
+            .chain(subcommand.get_all_aliases().map(ToOwned::to_owned))
+            .collect::<Vec<_>>();
+        let mut subcommand_levels = levels.to_vec();
+        subcommand_levels.push(words.clone());
+        for word in words {
+            tree.transitions.push(Transition {
+                from: tree.path(levels),
+                word,
+                to: tree.path(&subcommand_levels),
+            });
+        }
+        collect(subcommand, &subcommand_levels, tree);
+    }
+}
+
+/// Drops the `hidden` subcommands from the candidates of a generated script.
+fn without_hidden(shell: Shell, script: &str, hidden: &[String]) -> String {
+    let mut kept = String::new();
+    for line in script.lines() {
+        let offers = |name: &String| match shell {
+            Shell::Bash => false,
+            Shell::Zsh => line.starts_with(&format!("'{name}:")),
+            Shell::Fish => line.contains(&format!("-a \"{name}\"")),
+            Shell::Powershell => line.contains(&format!("[CompletionResult]::new('{name}',")),
+        };
+        if hidden.iter().any(offers) {
+            continue;
+        }
+        match line.split_once("opts=\"") {
+            Some((indent, words)) if shell == Shell::Bash => {
+                let words = words
+                    .trim_end_matches('"')
+                    .split(' ')
+                    .filter(|word| !hidden.iter().any(|name| name == word))
+                    .collect::<Vec<_>>();
+                let _ = writeln!(kept, "{indent}opts=\"{}\"", words.join(" "));
+            }
+            _ => {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
+    }
+    kept
+}
+
+/// Quotes `text` for POSIX shells.
+fn sh_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+fn powershell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// The part of the bash and zsh wrappers that sets `kind` to the names to
+/// offer, or to `none` where the generated function should complete.
+fn posix_kind(tree: &Tree, words: &str, first: usize, current: &str) -> String {
+    let mut script = String::new();
+    let _ = writeln!(
+        script,
+        r#"    local cur="${{{words}[{current}]}}" prev="${{{words}[{current}-1]}}"
+    local cmd={} kind= word i
+    for ((i = {first}; i < {current}; i++)); do
+        word="${{{words}[i]}}"
+        case "$cmd $word" in"#,
+        tree.bin
+    );
+    for transition in &tree.transitions {
+        let _ = writeln!(
+            script,
+            "            {}) cmd={} ;;",
+            sh_quote(&format!("{} {}", transition.from, transition.word)),
+            sh_quote(&transition.to)
+        );
+    }
+    script.push_str("        esac\n    done\n    case \"$cmd $prev\" in\n");
+    for value in &tree.values {
+        let Some(flag) = &value.flag else {
+            continue;
+        };
+        let kind = value
+            .kind
+            .map_or_else(|| "none".to_owned(), |kind| kind.to_string());
+        let _ = writeln!(
+            script,
+            "        {}) kind={kind} ;;",
+            sh_quote(&format!("{} {flag}", tree.path(&value.levels)))
+        );
+    }
+    script.push_str(
+        "    esac\n    if [[ -z \"$kind\" && \"$cur\" != -* ]]; then\n        case \"$cmd\" in\n",
+    );
+    for value in tree.values.iter().filter(|value| value.flag.is_none()) {
+        if let Some(kind) = value.kind {
+            let _ = writeln!(
+                script,
+                "            {}) kind={kind} ;;",
+                sh_quote(&tree.path(&value.levels))
+            );
+        }
+    }
+    script.push_str("        esac\n    fi\n");
+    script
+}
+
+fn bash(tree: &Tree, mut script: String) -> String {
+    let bin = &tree.bin;
+    let _ = write!(
+        script,
+        r#"
+_{bin}_names() {{
+{}    if [[ -n "$kind" && "$kind" != none ]]; then
+        COMPREPLY=($(compgen -W "$(command {bin} {COMPLETE_COMMAND} "$kind" 2>/dev/null)" -- "$cur"))
+        return
+    fi
+    _{bin} "$@"
+}}
+
+complete -F _{bin}_names -o bashdefault -o default {bin}
+"#,
+        posix_kind(tree, "COMP_WORDS", 1, "COMP_CWORD")
+    );
+    script
+}
+
+fn zsh(tree: &Tree, mut script: String) -> String {
+    let bin = &tree.bin;
+    // The generated script ends by registering `_{bin}`; the wrapper
+    // registers itself instead.
+    if let Some(end) = script.find(&format!("if [ \"$funcstack[1]\" = \"_{bin}\" ]; then")) {
+        script.truncate(end);
+    }
+    let _ = write!(
+        script,
+        r#"_{bin}_names() {{
+{}    if [[ -n "$kind" && "$kind" != none ]]; then
+        local -a names
+        names=(${{(f)"$(command {bin} {COMPLETE_COMMAND} "$kind" 2>/dev/null)"}})
+        compadd -a names
+        return
+    fi
+    _{bin} "$@"
+}}
+
+compdef _{bin}_names {bin}
+if [ "$funcstack[1]" = "_{bin}" ]; then
+    _{bin}_names "$@"
+fi
+"#,
+        posix_kind(tree, "words", 2, "CURRENT")
+    );
+    script
+}
+
+fn fish(tree: &Tree, mut script: String) -> String {
+    let bin = &tree.bin;
+    for value in &tree.values {
+        let Some(kind) = value.kind else {
+            continue;
+        };
+        let mut condition = match value.levels.split_first() {
+            Some((first, _)) => format!("__fish_{bin}_using_subcommand {}", first.join(" ")),
+            None => format!("__fish_{bin}_needs_command"),
+        };
+        for words in value.levels.iter().skip(1) {
+            let _ = write!(
+                condition,
+                "; and __fish_seen_subcommand_from {}",
+                words.join(" ")
+            );
+        }
+        let option = match &value.flag {
+            Some(flag) => match flag.strip_prefix("--") {
+                Some(long) => format!(" -l {long} -r"),
+                None => format!(" -s {} -r", &flag[1..]),
+            },
+            None => String::new(),
+        };
+        let _ = writeln!(
+            script,
+            "complete -c {bin} -n \"{condition}\"{option} -f -a '(command {bin} {COMPLETE_COMMAND} {kind})'"
+        );
+    }
+    script
+}
+
+fn powershell(tree: &Tree, script: String) -> String {
+    let Some(anchor) = script.find(POWERSHELL_ANCHOR) else {
+        return script;
+    };
+    let bin = &tree.bin;
+    // `$command` is the generated script's subcommand path, e.g. `rsworktree;rm`.
+    let mut names = String::from(
+        r#"    $previous = @($commandElements | Where-Object { $_.Extent.EndOffset -lt $cursorPosition })[-1]
+    $names = switch ("$command $previous") {
+"#,
+    );
+    for value in &tree.values {
+        let Some(flag) = &value.flag else {
+            continue;
+        };
+        let kind = value
+            .kind
+            .map_or_else(|| "none".to_owned(), |kind| kind.to_string());
+        for path in powershell_paths(bin, &value.levels) {
+            let _ = writeln!(
+                names,
+                "        {} {{ '{kind}' }}",
+                powershell_quote(&format!("{path} {flag}"))
+            );
+        }
+    }
+    names.push_str(
+        "    }\n    if (-not $names -and -not $wordToComplete.StartsWith('-')) {\n        $names = switch ($command) {\n",
+    );
+    for value in tree.values.iter().filter(|value| value.flag.is_none()) {
+        let Some(kind) = value.kind else {
+            continue;
+        };
+        for path in powershell_paths(bin, &value.levels) {
+            let _ = writeln!(
+                names,
+                "            {} {{ '{kind}' }}",
+                powershell_quote(&path)
+            );
+        }
+    }
+    let _ = write!(
+        names,
+        r#"        }}
+    }}
+    if ($names -and $names -ne 'none') {{
+        return @(& {bin} {COMPLETE_COMMAND} $names 2>$null) |
+            Where-Object {{ $_ -like "$wordToComplete*" }} |
+            ForEach-Object {{ [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_) }}
+    }}
+
+"#
+    );
+
+    let mut script = script;
+    script.insert_str(anchor, &names);
+    script
+}
+
+/// Every spelling of `levels` as the generated PowerShell script joins it,
+/// aliases included.
+fn powershell_paths(bin: &str, levels: &[Vec<String>]) -> Vec<String> {
+    let mut paths = vec![bin.to_owned()];
+    for words in levels {
+        paths = paths
+            .iter()
+            .flat_map(|path| words.iter().map(move |word| format!("{path};{word}")))
+            .collect();
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::process::Command as Process;
+
+    fn command() -> Command {
+        Command::new("wt")
+            .arg(
+                Arg::new("plain")
+                    .long("plain")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .subcommand(
+                Command::new("rm")
+                    .about("Remove a worktree.")
+                    .alias("remove")
+                    .arg(Arg::new("name").value_name("worktree"))
+                    .arg(Arg::new("message").short('m').long("message"))
+                    .arg(
+                        Arg::new("force")
+                            .long("force")
+                            .help("Force removal")
+                            .action(clap::ArgAction::SetTrue),
+                    ),
+            )
+            .subcommand(
+                Command::new("create")
+                    .about("Create a worktree.")
+                    .arg(Arg::new("name"))
+                    .arg(Arg::new("base").long("base").value_name("branch"))
+                    .arg(
+                        Arg::new("apply")
+                            .long("apply")
+                            .value_parser(clap::value_parser!(std::path::PathBuf)),
+                    ),
+            )
+            .subcommand(Command::new("__complete").about("Print names.").hide(true))
+    }
+
+    #[test]
+    fn bash_wraps_the_generated_function_for_dynamic_values() {
+        let script = render(Shell::Bash, &mut command());
+
+        assert!(script.contains("_wt() {"));
+        assert!(script.contains("opts=\"-h --plain --help rm create help\""));
+        assert!(script.contains("'wt rm') cmd='wt rm' ;;"));
+        assert!(script.contains("'wt remove') cmd='wt rm' ;;"));
+        assert!(script.contains("'wt create --base') kind=branches ;;"));
+        assert!(script.contains("'wt rm --message') kind=none ;;"));
+        assert!(script.contains("'wt rm') kind=worktrees ;;"));
+        assert!(!script.contains("'wt create --apply')"));
+        assert!(script.contains("command wt __complete \"$kind\""));
+        assert!(script.ends_with("complete -F _wt_names -o bashdefault -o default wt\n"));
+
+        let status = Process::new("bash")
+            .arg("-n")
+            .arg("-c")
+            .arg(&script)
+            .status();
+        if let Ok(status) = status {
+            assert!(status.success(), "bash rejected the script:\n{script}");
+        }
+    }
+
+    #[test]
+    fn zsh_fish_and_powershell_complete_names_dynamically() {
+        let zsh = render(Shell::Zsh, &mut command());
+        assert!(zsh.starts_with("#compdef wt\n"));
+        assert!(zsh.contains("'rm:Remove a worktree.'"));
+        assert!(!zsh.contains("'__complete:"));
+        assert!(zsh.contains("'wt rm') kind=worktrees ;;"));
+        assert!(zsh.contains("compdef _wt_names wt\n"));
+        assert!(!zsh.contains("compdef _wt wt"));
+
+        let fish = render(Shell::Fish, &mut command());
+        assert!(fish.contains("-f -a \"rm\" -d 'Remove a worktree.'"));
+        assert!(!fish.contains("-a \"__complete\""));
+        assert!(fish.contains(
+            "complete -c wt -n \"__fish_wt_using_subcommand create\" -l base -r -f -a '(command wt __complete branches)'"
+        ));
+        assert!(fish.contains(
+            "complete -c wt -n \"__fish_wt_using_subcommand rm remove\" -f -a '(command wt __complete worktrees)'"
+        ));
+
+        let powershell = render(Shell::Powershell, &mut command());
+        assert!(!powershell.contains("[CompletionResult]::new('__complete',"));
+        assert!(powershell.contains("'wt;create --base' { 'branches' }"));
+        assert!(powershell.contains("'wt;remove -m' { 'none' }"));
+        assert!(powershell.contains("'wt;remove' { 'worktrees' }"));
+        let names = powershell.find("& wt __complete $names");
+        let generated = powershell.find(POWERSHELL_ANCHOR);
+        assert!(names.is_some() && names < generated);
+    }
+}
@@ -0,0 +1,92 @@
+use std::process::Command;
+
+use color_eyre::eyre::{self, WrapErr};
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{Repo, commands::open::resolve_by_name, output, worktree::config};
+
+/// Runs `git config` inside a worktree, optionally scoped with `--worktree`.
+#[derive(Debug)]
+pub struct ConfigCommand {
+    name: String,
+    worktree_scope: bool,
+    args: Vec<String>,
+}
+
+impl ConfigCommand {
+    pub fn new(name: String, worktree_scope: bool, args: Vec<String>) -> Self {
+        Self {
+            name,
+            worktree_scope,
+            args,
+        }
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let resolved = resolve_by_name(&self.name, repo)?;
+
+        let mut command = Command::new("git");
+        command.arg("config").current_dir(&resolved.path);
+        if self.worktree_scope {
+            if config::ensure_extension(repo.git())? {
+                output::note(tr!(
+                    "Enabled `extensions.worktreeConfig` for this repository."
+                ));
+            }
+            command.arg("--worktree");
+        }
+        let status = command
+            .args(&self.args)
+            .status()
+            .wrap_err("failed to run `git config`")?;
+        if status.success() {
+            return Ok(());
+        }
+        Err(eyre::eyre!("`git config` exited with {status}"))
+    }
+}
+
+/// Adds or lists patterns in a worktree's own exclude file.
+#[derive(Debug)]
+pub struct ExcludeCommand {
+    name: String,
+    patterns: Vec<String>,
+}
+
+impl ExcludeCommand {
+    pub fn new(name: String, patterns: Vec<String>) -> Self {
+        Self { name, patterns }
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let resolved = resolve_by_name(&self.name, repo)?;
+        let name = format!(
+            "{}",
+            resolved
+                .name
+                .as_str()
+                .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan().bold()))
+        );
+
+        if self.patterns.is_empty() {
+            let patterns = config::list_excludes(&resolved.path)?;
+            if patterns.is_empty() {
+                output::info(tr!("No per-worktree excludes for `{}`.", name));
+            } else {
+                output::heading(tr!("Per-worktree excludes for `{}`", name));
+                for pattern in patterns {
+                    output::item(pattern);
+                }
+            }
+            return Ok(());
+        }
+
+        let added = config::add_excludes(repo.git(), &resolved.path, &self.patterns)?;
+        if added.is_empty() {
+            output::info(tr!("All patterns are already excluded in `{}`.", name));
+        } else {
+            output::success(tr!("Excluded {} in `{}`.", added.join(", "), name));
+        }
+        Ok(())
+    }
+}
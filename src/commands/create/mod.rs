@@ -1,35 +1,274 @@
-use std::fs;
+pub(crate) mod pattern;
+
+use std::{
+    cell::{OnceCell, RefCell},
+    fs,
+    io::{self, IsTerminal, Read},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use color_eyre::eyre::{self, Context};
 
 use owo_colors::{OwoColorize, Stream};
 
-use git2::{ErrorCode, WorktreeAddOptions};
+use git2::{BranchType, ErrorCode, WorktreeAddOptions, WorktreePruneOptions};
 
-use crate::{Repo, commands::cd::CdCommand};
+use crate::{
+    Repo, annotations,
+    commands::{cd::CdCommand, open::OpenCommand},
+    config::Config,
+    output,
+    process::{CommandRunner, SystemCommandRunner},
+    repo::{
+        ensure_unreserved_name,
+        signing::{self, Signing},
+    },
+};
 use crate::hooks::{HookContext, HookName, HookRunner};
+use crate::issues::Issue;
+use crate::webhook::{WebhookEvent, WebhookNotifier};
+use crate::telemetry::{Event, EventStore};
+use crate::templates::Template;
+use crate::worktree::{
+    self,
+    ports::PortRegistry,
+    render::{TemplateContext, render_templates},
+    submodules::{self, SubmoduleMode},
+};
 
 #[derive(Debug)]
-pub struct CreateCommand {
+pub struct CreateCommand<R = SystemCommandRunner> {
     name: String,
     base: Option<String>,
+    exists_ok: bool,
+    rerun_setup: bool,
+    patch: Option<PathBuf>,
+    bundle: Option<PathBuf>,
+    copy: Vec<String>,
+    sparse: Vec<String>,
+    full_checkout: bool,
+    no_submodules: bool,
+    shallow_submodules: bool,
+    hook_timeout: Option<Duration>,
+    from_remote: Option<String>,
+    reference: Option<PathBuf>,
+    template: Option<Template>,
+    issue: Option<Issue>,
+    no_wait: bool,
+    /// The name printed by the `pre-create` hook in place of `name`.
+    renamed: OnceCell<String>,
+    /// The hooks run by the last `create`, in order.
+    hooks_run: RefCell<Vec<HookName>>,
+    /// Runs the `git` commands libgit2 has no counterpart for.
+    runner: RefCell<R>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CreateOutcome {
     AlreadyExists,
     Created,
+    /// The branch was checked out in another worktree, which was opened instead.
+    OpenedExisting,
+}
+
+impl CreateOutcome {
+    /// The name used for the outcome in `--porcelain` and `--json` output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AlreadyExists => "exists",
+            Self::Created => "created",
+            Self::OpenedExisting => "opened",
+        }
+    }
+}
+
+/// What a `create` did, returned by [`CreateCommand::create_with_report`] and
+/// printed by `create --json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateReport {
+    pub outcome: CreateOutcome,
+    pub name: String,
+    /// Absolute path of the worktree.
+    pub path: PathBuf,
+    pub branch: String,
+    /// Branch the worktree was created from, as recorded for `merge --cleanup`.
+    pub base: Option<String>,
+    /// The hooks that ran, in order; hooks without a script, inline command
+    /// or task are left out.
+    pub hooks: Vec<HookName>,
+    pub duration: Duration,
+}
+
+impl CreateReport {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "outcome": self.outcome.as_str(),
+            "name": self.name,
+            "path": self.path,
+            "branch": self.branch,
+            "base": self.base,
+            "hooks": self.hooks.iter().map(HookName::as_str).collect::<Vec<_>>(),
+            "duration_ms": self.duration.as_millis() as u64,
+        })
+    }
 }
 
 impl CreateCommand {
     pub fn new(name: String, base: Option<String>) -> Self {
-        Self { name, base }
+        Self::with_runner(name, base, SystemCommandRunner)
+    }
+}
+
+impl<R> CreateCommand<R>
+where
+    R: CommandRunner,
+{
+    pub fn with_runner(name: String, base: Option<String>, runner: R) -> Self {
+        Self {
+            name,
+            base,
+            exists_ok: false,
+            rerun_setup: false,
+            patch: None,
+            bundle: None,
+            copy: Vec::new(),
+            sparse: Vec::new(),
+            full_checkout: false,
+            no_submodules: false,
+            shallow_submodules: false,
+            hook_timeout: None,
+            from_remote: None,
+            reference: None,
+            template: None,
+            issue: None,
+            no_wait: false,
+            renamed: OnceCell::new(),
+            hooks_run: RefCell::new(Vec::new()),
+            runner: RefCell::new(runner),
+        }
+    }
+
+    /// Accept an existing worktree/branch after verifying it matches the request.
+    pub fn with_exists_ok(mut self, exists_ok: bool) -> Self {
+        self.exists_ok = exists_ok;
+        self
+    }
+
+    /// Re-run the `post-create` hook when the worktree already exists.
+    pub fn with_rerun_setup(mut self, rerun_setup: bool) -> Self {
+        self.rerun_setup = rerun_setup;
+        self
+    }
+
+    /// Apply a patch file (or stdin for `-`) to the new worktree.
+    ///
+    /// Mailbox patches from `git format-patch` are committed with `git am`;
+    /// plain diffs are applied to the working tree and left uncommitted.
+    pub fn with_patch(mut self, patch: Option<PathBuf>) -> Self {
+        self.patch = patch;
+        self
+    }
+
+    /// Import the worktree branch from a git bundle written by `rsworktree export`.
+    pub fn with_bundle(mut self, bundle: Option<PathBuf>) -> Self {
+        self.bundle = bundle;
+        self
+    }
+
+    /// Copy untracked files matching these globs from the main worktree, in
+    /// addition to the `worktree.copy` patterns from `preferences.json`.
+    pub fn with_copy(mut self, patterns: Vec<String>) -> Self {
+        self.copy = patterns;
+        self
+    }
+
+    /// Limit the checkout to these directories with a cone-mode sparse
+    /// checkout, in place of the `worktree.sparse` directories from
+    /// `preferences.json`.
+    pub fn with_sparse(mut self, paths: Vec<String>) -> Self {
+        self.sparse = paths;
+        self
+    }
+
+    /// Check out every file even when `worktree.sparse` is set.
+    pub fn with_full_checkout(mut self, full_checkout: bool) -> Self {
+        self.full_checkout = full_checkout;
+        self
+    }
+
+    /// Kill the post-create hook after this long instead of after `hooks.timeout`.
+    /// Leave submodules uninitialized, whatever `worktree.submodules` says.
+    pub fn with_no_submodules(mut self, no_submodules: bool) -> Self {
+        self.no_submodules = no_submodules;
+        self
+    }
+
+    /// Fetch only the commit each submodule is pinned to.
+    pub fn with_shallow_submodules(mut self, shallow: bool) -> Self {
+        self.shallow_submodules = shallow;
+        self
+    }
+
+    pub fn with_hook_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.hook_timeout = timeout;
+        self
+    }
+
+    /// Check out the worktree branch from a remote branch, e.g. `feature/x`
+    /// on `origin` or `upstream/feature/x`, instead of branching off a base.
+    ///
+    /// Without it, a branch missing locally but known as `origin/<name>` is
+    /// checked out from `origin`.
+    pub fn with_from_remote(mut self, branch: Option<String>) -> Self {
+        self.from_remote = branch;
+        self
+    }
+
+    /// Borrow objects from another clone of the repository, see
+    /// [`Repo::attach_alternate`], before fetching and checking out.
+    pub fn with_reference(mut self, reference: Option<PathBuf>) -> Self {
+        self.reference = reference;
+        self
+    }
+
+    /// Apply a template from `.rsworktree/templates`: prefix the name, start
+    /// from its base unless one was given, copy its files and run its hooks.
+    pub fn with_template(mut self, template: Option<Template>) -> Self {
+        if let Some(template) = &template {
+            self.name = template.worktree_name(&self.name);
+            if self.base.is_none() {
+                self.base = template.base().map(str::to_owned);
+            }
+        }
+        self.template = template;
+        self
+    }
+
+    /// Record the tracker issue the worktree is for; its key and title become
+    /// the `issue` and `note` annotations of a new branch.
+    pub(crate) fn with_issue(mut self, issue: Option<Issue>) -> Self {
+        self.issue = issue;
+        self
+    }
+
+    /// Fail instead of waiting when another command holds the repository lock.
+    pub fn with_no_wait(mut self, no_wait: bool) -> Self {
+        self.no_wait = no_wait;
+        self
+    }
+
+    /// The worktree name, including the prefix of a template, or the name
+    /// the `pre-create` hook replaced it with.
+    pub fn name(&self) -> &str {
+        self.renamed.get().unwrap_or(&self.name)
     }
 
     pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
-        let outcome = self.create_internal(repo, false)?;
+        let outcome = self.create_internal(repo, false, true)?;
         match outcome {
             CreateOutcome::Created | CreateOutcome::AlreadyExists => self.enter_worktree(repo),
+            CreateOutcome::OpenedExisting => Ok(()),
         }
     }
 
@@ -38,69 +277,141 @@ impl CreateCommand {
         repo: &Repo,
         quiet: bool,
     ) -> color_eyre::Result<CreateOutcome> {
-        self.create_internal(repo, quiet)
+        self.create_internal(repo, quiet, false)
+    }
+
+    /// Like [`CreateCommand::create_without_enter`], reporting where the
+    /// worktree is, which hooks ran and how long it took.
+    pub fn create_with_report(&self, repo: &Repo, quiet: bool) -> color_eyre::Result<CreateReport> {
+        self.hooks_run.borrow_mut().clear();
+        let started = Instant::now();
+        let outcome = self.create_internal(repo, quiet, false)?;
+        let name = self.name().to_owned();
+        let base = repo
+            .git()
+            .config()
+            .and_then(|config| config.get_string(&base_config_key(&name)))
+            .ok();
+        Ok(CreateReport {
+            outcome,
+            path: repo.worktrees_dir().join(&name),
+            branch: name.clone(),
+            name,
+            base,
+            hooks: self.hooks_run.take(),
+            duration: started.elapsed(),
+        })
     }
 
     fn enter_worktree(&self, repo: &Repo) -> color_eyre::Result<()> {
-        CdCommand::new(self.name.clone(), false).execute(repo)
+        CdCommand::new(self.name().to_owned(), false).execute(repo)
     }
 
-    fn create_internal(&self, repo: &Repo, quiet: bool) -> color_eyre::Result<CreateOutcome> {
+    /// With `offer_open`, a branch checked out in another worktree may be
+    /// opened there after confirmation instead of failing.
+    fn create_internal(
+        &self,
+        repo: &Repo,
+        quiet: bool,
+        offer_open: bool,
+    ) -> color_eyre::Result<CreateOutcome> {
+        let _lock = repo.lock(!self.no_wait)?;
         let worktrees_dir = repo.ensure_worktrees_dir()?;
-        let worktree_path = worktrees_dir.join(&self.name);
-        let target_branch = self.name.as_str();
+        self.run_pre_create_hook(repo)?;
+        ensure_unreserved_name(self.name())?;
+        let worktree_path = worktrees_dir.join(self.name());
+        let target_branch = self.name();
         let base_branch = self.base.as_deref();
+        if let Some(pattern) = repo.config()?.protected_pattern(target_branch) {
+            return Err(eyre::eyre!(
+                "`{}` is a protected branch (`{}` in `protected_branches`); pick another name for the worktree",
+                target_branch,
+                pattern
+            ));
+        }
 
         if worktree_path.exists() {
+            if self.exists_ok {
+                self.verify_existing_worktree(repo.git(), &worktree_path)?;
+            }
             if !quiet {
                 let name = format!(
                     "{}",
-                    self.name
-                        .as_str()
-                        .if_supports_color(Stream::Stdout, |text| {
-                            format!("{}", text.cyan().bold())
-                        })
+                    self.name().if_supports_color(Stream::Stdout, |text| {
+                        format!("{}", text.cyan().bold())
+                    })
                 );
-                println!(
+                output::info(tr!(
                     "Worktree `{}` already exists at `{}`.",
                     name,
                     worktree_path.display()
-                );
+                ));
+            }
+            if self.rerun_setup {
+                self.run_post_create_hook(repo, &worktree_path)?;
             }
             return Ok(CreateOutcome::AlreadyExists);
         }
 
-        if let Some(parent) = worktree_path.parent() {
-            fs::create_dir_all(parent).wrap_err_with(|| {
-                eyre::eyre!("failed to prepare directory `{}`", parent.display())
-            })?;
+        if let Some(existing) = repo.worktree_for_branch(target_branch)? {
+            if offer_open && confirm_open_existing(target_branch, &existing.path)? {
+                OpenCommand::new(None, Some(existing.path)).execute(repo)?;
+                return Ok(CreateOutcome::OpenedExisting);
+            }
+            return Err(eyre::eyre!(
+                "branch `{}` is already checked out at `{}`; open it with `rsworktree worktree open --path {}`",
+                target_branch,
+                existing.path.display(),
+                existing.path.display()
+            ));
+        }
+
+        let patch = self.patch.as_deref().map(Patch::read).transpose()?;
+        if let Some(reference) = &self.reference {
+            let (objects_dir, attached) = repo.attach_alternate(reference)?;
+            if attached && !quiet {
+                output::info(tr!(
+                    "Sharing objects with `{}`; keep that clone in place.",
+                    objects_dir.display()
+                ));
+            }
         }
 
         let git_repo = repo.git();
-        let reference = prepare_branch(git_repo, target_branch, base_branch)?;
-        let metadata_name = worktree_metadata_name(&self.name);
-        let mut opts = WorktreeAddOptions::new();
-        opts.reference(Some(&reference));
-        git_repo
-            .worktree(&metadata_name, &worktree_path, Some(&opts))
-            .wrap_err_with(|| {
-                eyre::eyre!(
-                    "failed to add worktree `{}` at `{}`",
-                    target_branch,
-                    worktree_path.display()
-                )
-            })?;
+        let remote_branch = self.resolve_remote_branch(git_repo)?;
+        let mut transaction = CreateTransaction::new(git_repo);
+        if let Err(error) = self.provision(
+            repo,
+            &worktree_path,
+            patch.as_ref(),
+            remote_branch.as_ref(),
+            &mut transaction,
+        ) {
+            for failure in transaction.rollback() {
+                output::warn(tr!("rollback incomplete: {}", failure));
+            }
+            return Err(error.wrap_err(format!(
+                "failed to create worktree `{}`; partial changes were rolled back",
+                self.name()
+            )));
+        }
 
-        // Run post-create hook if it exists
-        let hook_runner = HookRunner::new(&worktrees_dir);
-        let hook_context = HookContext {
-            worktree_name: self.name.clone(),
-            worktree_path: worktree_path.clone(),
-            branch: target_branch.to_string(),
-            base_branch: base_branch.map(String::from),
-            base_path: worktrees_dir.clone(),
-        };
-        hook_runner.run_hook(HookName::PostCreate, &hook_context)?;
+        let rsworktree_dir = repo.rsworktree_dir();
+        EventStore::new(&rsworktree_dir).record(Event::WorktreeCreated {
+            worktree: self.name().to_owned(),
+            branch: target_branch.to_owned(),
+            base: self.base.clone(),
+        });
+        WebhookNotifier::new(&rsworktree_dir, &repo.name()).notify(
+            WebhookEvent::Create,
+            self.name(),
+            Some(target_branch),
+            tr!(
+                "Created worktree `{}` at `{}`.",
+                self.name(),
+                worktree_path.display()
+            ),
+        );
 
         if !quiet {
             let name = format!(
@@ -116,6 +427,9 @@ impl CreateCommand {
                     .as_str()
                     .if_supports_color(Stream::Stdout, |text| { format!("{}", text.blue()) })
             );
+            let base_branch = base_branch
+                .map(str::to_owned)
+                .or_else(|| remote_branch.as_ref().map(RemoteBranch::tracking_name));
             if let Some(base) = base_branch {
                 let base = format!(
                     "{}",
@@ -123,14 +437,613 @@ impl CreateCommand {
                         format!("{}", text.magenta().bold())
                     })
                 );
-                println!("Created worktree `{}` at `{}` from `{}`.", name, path, base);
+                output::success(tr!(
+                    "Created worktree `{}` at `{}` from `{}`.",
+                    name,
+                    path,
+                    base
+                ));
             } else {
-                println!("Created worktree `{}` at `{}`.", name, path);
+                output::success(tr!("Created worktree `{}` at `{}`.", name, path));
+            }
+            match &patch {
+                Some(patch) if patch.is_mailbox() => {
+                    output::note(tr!("Applied `{}` with `git am`.", patch.label));
+                }
+                Some(patch) => output::note(tr!(
+                    "Applied `{}` to the working tree; the changes are left uncommitted for review.",
+                    patch.label
+                )),
+                None => {}
             }
         }
 
         Ok(CreateOutcome::Created)
     }
+
+    fn provision(
+        &self,
+        repo: &Repo,
+        worktree_path: &Path,
+        patch: Option<&Patch>,
+        remote_branch: Option<&RemoteBranch>,
+        transaction: &mut CreateTransaction<'_>,
+    ) -> color_eyre::Result<()> {
+        let git_repo = repo.git();
+        let target_branch = self.name();
+        let base_branch = self.base.as_deref();
+        let rsworktree_dir = repo.rsworktree_dir();
+        let events = EventStore::new(&rsworktree_dir);
+
+        if let Some(parent) = worktree_path.parent() {
+            transaction.record_dir(parent);
+            fs::create_dir_all(parent).wrap_err_with(|| {
+                eyre::eyre!("failed to prepare directory `{}`", parent.display())
+            })?;
+        }
+
+        let existing_branch = git_repo
+            .find_reference(&format!("refs/heads/{target_branch}"))
+            .ok()
+            .and_then(|reference| reference.target());
+        if self.exists_ok
+            && let Some(tip) = existing_branch
+        {
+            self.verify_base(git_repo, tip)?;
+        }
+        if let Some(bundle) = self.bundle.as_deref() {
+            if existing_branch.is_some() {
+                return Err(eyre::eyre!(
+                    "branch `{target_branch}` already exists; remove it or choose another name to import `{}`",
+                    bundle.display()
+                ));
+            }
+            transaction.record_branch(target_branch);
+            events.time_step(target_branch, "fetch-bundle", || {
+                output::step(
+                    tr!(
+                        "Fetching `{}` from `{}`...",
+                        target_branch,
+                        bundle.display()
+                    ),
+                    || {
+                        fetch_bundle(
+                            &mut *self.runner.borrow_mut(),
+                            git_repo,
+                            bundle,
+                            target_branch,
+                        )
+                    },
+                )
+            })?;
+        }
+        if let Some(remote_branch) = remote_branch {
+            if existing_branch.is_some() {
+                return Err(eyre::eyre!(
+                    "branch `{target_branch}` already exists; remove it or choose another name to check out `{}`",
+                    remote_branch.tracking_name()
+                ));
+            }
+            events.time_step(target_branch, "fetch-remote", || {
+                output::step(
+                    tr!("Fetching `{}`...", remote_branch.tracking_name()),
+                    || fetch_remote_branch(&mut *self.runner.borrow_mut(), git_repo, remote_branch),
+                )
+            })?;
+            transaction.record_branch(target_branch);
+            create_tracking_branch(git_repo, target_branch, remote_branch)?;
+        }
+        let branch_is_new = existing_branch.is_none();
+        let reference = prepare_branch(git_repo, target_branch, base_branch)?;
+        if branch_is_new {
+            transaction.record_branch(target_branch);
+        }
+        // The base of a remote branch is unknown; `merge --cleanup` then uses
+        // the remote's default branch.
+        if branch_is_new && remote_branch.is_none() {
+            record_base_branch(git_repo, target_branch, base_branch)?;
+        }
+        if branch_is_new && let Some(template) = &self.template {
+            record_template(git_repo, target_branch, template)?;
+        }
+        transaction.record_annotations(&rsworktree_dir, self.name());
+        let issue = self.issue.as_ref().filter(|_| branch_is_new);
+        record_creation(repo, self.name(), target_branch, issue)?;
+
+        let metadata_name = worktree_metadata_name(self.name());
+        transaction.record_worktree(&metadata_name, worktree_path);
+        let sparse = self.sparse_paths(&rsworktree_dir);
+        if sparse.is_empty() {
+            let mut opts = WorktreeAddOptions::new();
+            opts.reference(Some(&reference));
+            events.time_step(target_branch, "worktree-add", || {
+                output::step(tr!("Checking out `{}`...", target_branch), || {
+                    git_repo
+                        .worktree(&metadata_name, worktree_path, Some(&opts))
+                        .wrap_err_with(|| {
+                            eyre::eyre!(
+                                "failed to add worktree `{}` at `{}`",
+                                target_branch,
+                                worktree_path.display()
+                            )
+                        })
+                })
+            })?;
+        } else {
+            events.time_step(target_branch, "worktree-add", || {
+                output::step(
+                    tr!(
+                        "Checking out `{}` limited to {}...",
+                        target_branch,
+                        sparse.join(", ")
+                    ),
+                    || {
+                        add_sparse_worktree(
+                            &mut *self.runner.borrow_mut(),
+                            git_repo,
+                            target_branch,
+                            worktree_path,
+                            &sparse,
+                            transaction,
+                        )
+                    },
+                )
+            })?;
+        }
+
+        events.time_step(target_branch, "worktree-config", || {
+            worktree::config::apply_defaults(git_repo, &rsworktree_dir, worktree_path)
+        })?;
+
+        let submodule_mode = self.submodule_mode(&rsworktree_dir);
+        if submodule_mode != SubmoduleMode::Off && submodules::has_submodules(worktree_path) {
+            // A submodule that cannot be fetched should not cost the worktree.
+            let updated = events.time_step(target_branch, "submodules", || {
+                output::step(tr!("Initializing submodules..."), || {
+                    submodules::update(worktree_path, submodule_mode)
+                })
+            });
+            if let Err(error) = updated {
+                output::warn(tr!("Could not initialize the submodules: {}", error));
+                output::hint(tr!(
+                    "run `git submodule update --init --recursive` in the worktree"
+                ));
+            }
+        }
+
+        let mut copy_patterns = worktree::config::copy_patterns(&rsworktree_dir);
+        if let Some(template) = &self.template {
+            copy_patterns.extend(template.copy_patterns().iter().cloned());
+        }
+        copy_patterns.extend(self.copy.iter().cloned());
+        if let Some(source) = git_repo.workdir()
+            && !copy_patterns.is_empty()
+        {
+            let copied = events.time_step(target_branch, "copy-files", || {
+                worktree::copy::copy_matching(source, worktree_path, &copy_patterns)
+            })?;
+            for path in copied {
+                output::info(tr!("Copied `{}` into the new worktree.", path.display()));
+            }
+        }
+
+        let templates = Config::load(&rsworktree_dir)?.templates;
+        if let Some(templates) = templates.filter(|templates| !templates.files.is_empty()) {
+            let context = TemplateContext {
+                worktree: self.name(),
+                branch: target_branch,
+                base: base_branch,
+                path: worktree_path,
+            };
+            let mut allocated = false;
+            let rendered = events.time_step(target_branch, "render-templates", || {
+                render_templates(
+                    &templates,
+                    &rsworktree_dir,
+                    git_repo.workdir().unwrap_or(worktree_path),
+                    &context,
+                    &mut allocated,
+                )
+            });
+            if allocated {
+                transaction.record_ports(&rsworktree_dir, self.name());
+            }
+            for path in rendered? {
+                output::info(tr!("Rendered `{}` from its template.", path.display()));
+            }
+        }
+
+        if let Some(patch) = patch {
+            events.time_step(target_branch, "apply-patch", || {
+                patch.apply(&mut *self.runner.borrow_mut(), worktree_path)
+            })?;
+        }
+
+        events.time_step(target_branch, "post-create-hook", || {
+            self.run_post_create_hook(repo, worktree_path)
+        })
+    }
+
+    /// How to initialize submodules: the options first, then `worktree.submodules`.
+    fn submodule_mode(&self, rsworktree_dir: &Path) -> SubmoduleMode {
+        if self.no_submodules {
+            return SubmoduleMode::Off;
+        }
+        if self.shallow_submodules {
+            return SubmoduleMode::Shallow;
+        }
+        worktree::config::submodule_mode(rsworktree_dir)
+    }
+
+    /// The directories to limit the checkout to; empty for a full checkout.
+    fn sparse_paths(&self, rsworktree_dir: &Path) -> Vec<String> {
+        if self.full_checkout {
+            return Vec::new();
+        }
+        if !self.sparse.is_empty() {
+            return self.sparse.clone();
+        }
+        worktree::config::sparse_paths(rsworktree_dir)
+    }
+
+    /// The remote branch to check out: the `--from-remote` branch, or
+    /// `origin/<name>` when only the remote has the branch.
+    fn resolve_remote_branch(
+        &self,
+        git_repo: &git2::Repository,
+    ) -> color_eyre::Result<Option<RemoteBranch>> {
+        if let Some(spec) = self.from_remote.as_deref() {
+            return RemoteBranch::parse(git_repo, spec).map(Some);
+        }
+        if self.base.is_some()
+            || self.bundle.is_some()
+            || git_repo
+                .find_reference(&format!("refs/heads/{}", self.name()))
+                .is_ok()
+        {
+            return Ok(None);
+        }
+        let remote_branch = RemoteBranch {
+            remote: "origin".to_owned(),
+            branch: self.name().to_owned(),
+            fetch_optional: true,
+        };
+        Ok(git_repo
+            .find_reference(&remote_branch.tracking_ref())
+            .is_ok()
+            .then_some(remote_branch))
+    }
+
+    /// Lets the `pre-create` hook veto the worktree or replace its name.
+    fn run_pre_create_hook(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let worktrees_dir = repo.worktrees_dir();
+        let mut hook_runner = HookRunner::new(&repo.rsworktree_dir()).with_repository(&repo.name());
+        if let Some(timeout) = self.hook_timeout {
+            hook_runner = hook_runner.with_timeout(timeout);
+        }
+        let hook_context = HookContext {
+            worktree_name: self.name.clone(),
+            worktree_path: worktrees_dir.join(&self.name),
+            branch: self.name.clone(),
+            base_branch: self.base.clone(),
+            base_path: worktrees_dir.clone(),
+        };
+        if let Some(template) = &self.template {
+            hook_runner = template.apply_to_hooks(hook_runner, &hook_context);
+        }
+        if hook_runner.is_configured(HookName::PreCreate) {
+            self.hooks_run.borrow_mut().push(HookName::PreCreate);
+        }
+        let Some(name) = hook_runner.run_pre_create(&hook_context)? else {
+            return Ok(());
+        };
+        if !git2::Branch::name_is_valid(&name)? {
+            return Err(eyre::eyre!(
+                "the pre-create hook replaced `{}` with `{name}`, which is not a valid branch name",
+                self.name
+            ));
+        }
+        output::info(tr!(
+            "The pre-create hook renamed `{}` to `{}`.",
+            self.name,
+            name
+        ));
+        // A second `create` with this command asks the hook again, which
+        // should answer the same.
+        let _ = self.renamed.set(name);
+        Ok(())
+    }
+
+    fn run_post_create_hook(&self, repo: &Repo, worktree_path: &Path) -> color_eyre::Result<()> {
+        let mut hook_runner = HookRunner::new(&repo.rsworktree_dir()).with_repository(&repo.name());
+        if let Some(timeout) = self.hook_timeout {
+            hook_runner = hook_runner.with_timeout(timeout);
+        }
+        let hook_context = HookContext {
+            worktree_name: self.name().to_owned(),
+            worktree_path: worktree_path.to_path_buf(),
+            branch: self.name().to_owned(),
+            base_branch: self.base.clone(),
+            base_path: repo.worktrees_dir(),
+        };
+        if let Some(template) = &self.template {
+            hook_runner = template.apply_to_hooks(hook_runner, &hook_context);
+        }
+        if hook_runner.is_configured(HookName::PostCreate) {
+            self.hooks_run.borrow_mut().push(HookName::PostCreate);
+        }
+        hook_runner.run_hook(HookName::PostCreate, &hook_context)
+    }
+
+    fn verify_existing_worktree(
+        &self,
+        git_repo: &git2::Repository,
+        worktree_path: &Path,
+    ) -> color_eyre::Result<()> {
+        let worktree = git2::Repository::open(worktree_path).wrap_err_with(|| {
+            eyre::eyre!(
+                "`{}` exists but is not a git worktree",
+                worktree_path.display()
+            )
+        })?;
+        let head = worktree
+            .head()
+            .wrap_err_with(|| eyre::eyre!("failed to read HEAD of `{}`", worktree_path.display()))?;
+
+        let checked_out = head.shorthand().unwrap_or_default();
+        if !head.is_branch() || checked_out != self.name() {
+            return Err(eyre::eyre!(
+                "worktree `{}` exists but has `{}` checked out instead of `{}`",
+                self.name(),
+                if head.is_branch() { checked_out } else { "a detached HEAD" },
+                self.name()
+            ));
+        }
+
+        match head.target() {
+            Some(tip) => self.verify_base(git_repo, tip),
+            None => Ok(()),
+        }
+    }
+
+    /// Ensures the requested base is contained in the existing branch tip.
+    fn verify_base(&self, git_repo: &git2::Repository, tip: git2::Oid) -> color_eyre::Result<()> {
+        let Some(base) = self.base.as_deref() else {
+            return Ok(());
+        };
+
+        let base_commit = git_repo
+            .revparse_single(base)
+            .and_then(|object| object.peel_to_commit())
+            .wrap_err_with(|| eyre::eyre!("failed to resolve base reference `{base}`"))?
+            .id();
+
+        let contains_base = base_commit == tip
+            || git_repo
+                .graph_descendant_of(tip, base_commit)
+                .wrap_err("failed to compare branch with base")?;
+        if !contains_base {
+            return Err(eyre::eyre!(
+                "branch `{}` already exists but is not based on `{}`",
+                self.name(),
+                base
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Where [`Patch::apply`] writes the patch for `git`, in the worktree's git directory.
+const PATCH_FILE: &str = "RSWORKTREE_PATCH";
+
+/// Patch contents passed to `create --apply`, read before anything is created.
+struct Patch {
+    label: String,
+    contents: Vec<u8>,
+}
+
+impl Patch {
+    fn read(path: &Path) -> color_eyre::Result<Self> {
+        if path == Path::new("-") {
+            let mut contents = Vec::new();
+            io::stdin()
+                .read_to_end(&mut contents)
+                .wrap_err("failed to read patch from stdin")?;
+            return Ok(Self {
+                label: "<stdin>".into(),
+                contents,
+            });
+        }
+
+        let contents = fs::read(path)
+            .wrap_err_with(|| eyre::eyre!("failed to read patch `{}`", path.display()))?;
+        Ok(Self {
+            label: path.display().to_string(),
+            contents,
+        })
+    }
+
+    /// Whether the patch is a mailbox (`git format-patch`, mailing list) rather
+    /// than a plain diff.
+    fn is_mailbox(&self) -> bool {
+        String::from_utf8_lossy(&self.contents)
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .is_some_and(|line| line.starts_with("From ") || line.starts_with("From:"))
+    }
+
+    /// Applies the patch with `git am` or `git apply`, which read it from a
+    /// file in the worktree's git directory.
+    fn apply(
+        &self,
+        runner: &mut impl CommandRunner,
+        worktree_path: &Path,
+    ) -> color_eyre::Result<()> {
+        let command = if self.is_mailbox() {
+            ["am", "--quiet"].as_slice()
+        } else {
+            ["apply"].as_slice()
+        };
+        let git_dir = git2::Repository::open(worktree_path)
+            .wrap_err_with(|| eyre::eyre!("failed to open worktree `{}`", worktree_path.display()))?
+            .path()
+            .to_path_buf();
+        let file = git_dir.join(PATCH_FILE);
+        fs::write(&file, &self.contents)
+            .wrap_err_with(|| eyre::eyre!("failed to write `{}`", file.display()))?;
+        let mut args = command
+            .iter()
+            .map(|arg| (*arg).to_owned())
+            .collect::<Vec<_>>();
+        args.push(file.display().to_string());
+        let output = runner.run("git", worktree_path, &args);
+        let _ = fs::remove_file(&file);
+        let output = output.wrap_err("failed to run `git` to apply the patch")?;
+        if output.success {
+            return Ok(());
+        }
+
+        if self.is_mailbox() {
+            let _ = runner.run("git", worktree_path, &["am".into(), "--abort".into()]);
+        }
+        let stderr = &output.stderr;
+        if self.is_mailbox() && signing::is_signing_failure(stderr) {
+            let signing = git2::Repository::open(worktree_path)
+                .and_then(|repo| repo.config()?.snapshot())
+                .ok()
+                .and_then(|config| Signing::load(&config));
+            if let Some(signing) = signing {
+                return Err(eyre::eyre!(
+                    "failed to apply `{}` with `git am`: commit.gpgsign is set but `{}` could not sign the commit ({} signing); {}: {}",
+                    self.label,
+                    signing.program,
+                    signing.format.as_str(),
+                    signing.hint(),
+                    stderr.trim()
+                ));
+            }
+        }
+        Err(eyre::eyre!(
+            "failed to apply `{}` with `git {}`: {}",
+            self.label,
+            command[0],
+            stderr.trim()
+        ))
+    }
+}
+
+/// Undo log for a single `create` run.
+///
+/// Every side effect is recorded before it happens so a failure at any later
+/// step can remove the partial worktree, the new branch and new directories.
+struct CreateTransaction<'repo> {
+    repo: &'repo git2::Repository,
+    created_dir: Option<PathBuf>,
+    created_branch: Option<String>,
+    worktree: Option<(String, PathBuf)>,
+    /// The worktrees directory and the worktree name ports were allocated for.
+    ports: Option<(PathBuf, String)>,
+    /// The worktrees directory and the worktree name metadata was written for.
+    annotations: Option<(PathBuf, String)>,
+}
+
+impl<'repo> CreateTransaction<'repo> {
+    fn new(repo: &'repo git2::Repository) -> Self {
+        Self {
+            repo,
+            created_dir: None,
+            created_branch: None,
+            worktree: None,
+            ports: None,
+            annotations: None,
+        }
+    }
+
+    /// Remembers the outermost missing ancestor of `dir`, if any.
+    fn record_dir(&mut self, dir: &Path) {
+        let mut outermost = None;
+        let mut current = Some(dir);
+        while let Some(candidate) = current {
+            if candidate.exists() {
+                break;
+            }
+            outermost = Some(candidate.to_path_buf());
+            current = candidate.parent();
+        }
+        self.created_dir = outermost;
+    }
+
+    fn record_branch(&mut self, branch: &str) {
+        self.created_branch = Some(branch.to_owned());
+    }
+
+    fn record_worktree(&mut self, metadata_name: &str, path: &Path) {
+        self.worktree = Some((metadata_name.to_owned(), path.to_path_buf()));
+    }
+
+    fn record_ports(&mut self, rsworktree_dir: &Path, worktree: &str) {
+        self.ports = Some((rsworktree_dir.to_path_buf(), worktree.to_owned()));
+    }
+
+    fn record_annotations(&mut self, rsworktree_dir: &Path, worktree: &str) {
+        self.annotations = Some((rsworktree_dir.to_path_buf(), worktree.to_owned()));
+    }
+
+    /// Reverts the recorded steps in reverse order and returns the ones that failed.
+    fn rollback(self) -> Vec<String> {
+        let mut failures = Vec::new();
+
+        if let Some((rsworktree_dir, worktree)) = &self.ports
+            && let Err(error) = PortRegistry::new(rsworktree_dir).release(worktree)
+        {
+            failures.push(format!(
+                "failed to release the ports of `{worktree}`: {error}"
+            ));
+        }
+
+        if let Some((rsworktree_dir, worktree)) = &self.annotations
+            && let Err(error) = annotations::remove(rsworktree_dir, worktree)
+        {
+            failures.push(format!(
+                "failed to remove the metadata of `{worktree}`: {error}"
+            ));
+        }
+
+        if let Some((metadata_name, path)) = &self.worktree {
+            if let Ok(worktree) = self.repo.find_worktree(metadata_name) {
+                let mut prune_opts = WorktreePruneOptions::new();
+                prune_opts.valid(true).locked(true).working_tree(true);
+                if let Err(error) = worktree.prune(Some(&mut prune_opts)) {
+                    failures.push(format!("failed to prune worktree `{metadata_name}`: {error}"));
+                }
+            }
+            if path.exists()
+                && let Err(error) = fs::remove_dir_all(path)
+            {
+                failures.push(format!("failed to remove `{}`: {error}", path.display()));
+            }
+        }
+
+        if let Some(branch) = &self.created_branch {
+            let deleted = self
+                .repo
+                .find_branch(branch, BranchType::Local)
+                .and_then(|mut reference| reference.delete());
+            if let Err(error) = deleted {
+                failures.push(format!("failed to delete branch `{branch}`: {error}"));
+            }
+        }
+
+        if let Some(dir) = &self.created_dir
+            && dir.exists()
+            && let Err(error) = fs::remove_dir_all(dir)
+        {
+            failures.push(format!("failed to remove `{}`: {error}", dir.display()));
+        }
+
+        failures
+    }
 }
 
 fn prepare_branch<'repo>(
@@ -158,20 +1071,333 @@ fn prepare_branch<'repo>(
     }
 }
 
-fn worktree_metadata_name(name: &str) -> String {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::Hasher;
+/// Adds a worktree for `branch` with only `paths` and the files at the root
+/// checked out.
+///
+/// libgit2 always checks out every file, so the worktree is added with the
+/// `git` executable without a checkout, which is filled after setting up a
+/// cone-mode sparse checkout.
+fn add_sparse_worktree(
+    runner: &mut impl CommandRunner,
+    repo: &git2::Repository,
+    branch: &str,
+    worktree_path: &Path,
+    paths: &[String],
+    transaction: &mut CreateTransaction<'_>,
+) -> color_eyre::Result<()> {
+    let mut run_git = |dir: &Path, args: Vec<String>| -> color_eyre::Result<()> {
+        let output = runner
+            .run("git", dir, &args)
+            .wrap_err("failed to run `git`")?;
+        if output.success {
+            return Ok(());
+        }
+        Err(eyre::eyre!(
+            "failed to add sparse worktree `{}` at `{}`: {}",
+            branch,
+            worktree_path.display(),
+            output.stderr.trim()
+        ))
+    };
 
-    let sanitized: String = name
-        .chars()
-        .map(|ch| match ch {
-            '/' | '\\' => '-',
-            ch if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.') => ch,
-            _ => '-',
-        })
-        .collect();
+    run_git(
+        repo.workdir().unwrap_or(repo.path()),
+        vec![
+            "worktree".into(),
+            "add".into(),
+            "--quiet".into(),
+            "--no-checkout".into(),
+            worktree_path.display().to_string(),
+            branch.into(),
+        ],
+    )?;
+    // git names the metadata directory after the worktree directory.
+    if let Some(name) = git2::Repository::open(worktree_path)
+        .ok()
+        .and_then(|worktree| worktree.path().file_name().map(|name| name.to_owned()))
+    {
+        transaction.record_worktree(&name.to_string_lossy(), worktree_path);
+    }
 
-    let mut hasher = DefaultHasher::new();
+    let mut sparse_args = vec!["sparse-checkout".into(), "set".into(), "--cone".into()];
+    sparse_args.extend(paths.iter().cloned());
+    run_git(worktree_path, sparse_args)?;
+    run_git(
+        worktree_path,
+        vec!["read-tree".into(), "-mu".into(), "HEAD".into()],
+    )
+}
+
+/// Fetches the branch stored in `bundle` into `refs/heads/<branch>`.
+///
+/// A head named like the branch is preferred; otherwise the bundle must contain
+/// exactly one branch.
+fn fetch_bundle(
+    runner: &mut impl CommandRunner,
+    repo: &git2::Repository,
+    bundle: &Path,
+    branch: &str,
+) -> color_eyre::Result<()> {
+    let bundle = std::path::absolute(bundle)
+        .wrap_err_with(|| eyre::eyre!("failed to resolve bundle `{}`", bundle.display()))?;
+    let workdir = repo.workdir().unwrap_or(repo.path());
+    let mut run_git = |args: Vec<String>| -> color_eyre::Result<String> {
+        let output = runner
+            .run("git", workdir, &args)
+            .wrap_err("failed to run `git`")?;
+        if !output.success {
+            return Err(eyre::eyre!(
+                "failed to read bundle `{}`: {}",
+                bundle.display(),
+                output.stderr.trim()
+            ));
+        }
+        Ok(output.stdout)
+    };
+
+    let listing = run_git(vec![
+        "bundle".into(),
+        "list-heads".into(),
+        bundle.display().to_string(),
+    ])?;
+    let heads: Vec<&str> = listing
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .filter(|name| name.starts_with("refs/heads/"))
+        .collect();
+    let wanted = format!("refs/heads/{branch}");
+    let head = match heads.as_slice() {
+        _ if heads.contains(&wanted.as_str()) => wanted.as_str(),
+        [only] => only,
+        _ => {
+            return Err(eyre::eyre!(
+                "bundle `{}` does not contain `{wanted}`; available branches: {}",
+                bundle.display(),
+                if heads.is_empty() {
+                    "none".to_owned()
+                } else {
+                    heads.join(", ")
+                }
+            ));
+        }
+    };
+
+    let refspec = format!("{head}:{wanted}");
+    run_git(vec![
+        "fetch".into(),
+        "--quiet".into(),
+        bundle.display().to_string(),
+        refspec,
+    ])?;
+    Ok(())
+}
+
+/// A branch on a remote that the worktree branch is checked out from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RemoteBranch {
+    remote: String,
+    branch: String,
+    /// Detected from an existing remote-tracking branch, which may be used
+    /// when it cannot be refreshed, e.g. offline.
+    fetch_optional: bool,
+}
+
+impl RemoteBranch {
+    /// Reads `<remote>/<branch>` when the first component names a remote of
+    /// `repo`, and `<branch>` on `origin` otherwise.
+    fn parse(repo: &git2::Repository, spec: &str) -> color_eyre::Result<Self> {
+        let spec = spec.trim().trim_start_matches("refs/heads/");
+        let remotes = repo.remotes().wrap_err("failed to list remotes")?;
+        let (remote, branch) = spec
+            .split_once('/')
+            .filter(|(remote, _)| remotes.iter().flatten().any(|name| name == *remote))
+            .unwrap_or(("origin", spec));
+        if branch.is_empty() {
+            return Err(eyre::eyre!("`{spec}` does not name a remote branch"));
+        }
+        Ok(Self {
+            remote: remote.to_owned(),
+            branch: branch.to_owned(),
+            fetch_optional: false,
+        })
+    }
+
+    /// The remote-tracking branch, e.g. `origin/feature/x`.
+    fn tracking_name(&self) -> String {
+        format!("{}/{}", self.remote, self.branch)
+    }
+
+    fn tracking_ref(&self) -> String {
+        format!("refs/remotes/{}/{}", self.remote, self.branch)
+    }
+}
+
+/// Updates the remote-tracking branch of `remote_branch` from its remote.
+fn fetch_remote_branch(
+    runner: &mut impl CommandRunner,
+    repo: &git2::Repository,
+    remote_branch: &RemoteBranch,
+) -> color_eyre::Result<()> {
+    let refspec = format!(
+        "+refs/heads/{}:{}",
+        remote_branch.branch,
+        remote_branch.tracking_ref()
+    );
+    let args = vec![
+        "fetch".into(),
+        "--quiet".into(),
+        remote_branch.remote.clone(),
+        refspec,
+    ];
+    let output = runner
+        .run("git", repo.workdir().unwrap_or(repo.path()), &args)
+        .wrap_err("failed to run `git fetch`")?;
+    if output.success {
+        return Ok(());
+    }
+
+    let stderr = &output.stderr;
+    if remote_branch.fetch_optional {
+        output::warn(tr!(
+            "Could not fetch `{}`; using the last fetched state: {}",
+            remote_branch.tracking_name(),
+            stderr.trim()
+        ));
+        return Ok(());
+    }
+    Err(eyre::eyre!(
+        "failed to fetch `{}`: {}",
+        remote_branch.tracking_name(),
+        stderr.trim()
+    ))
+}
+
+/// Creates `branch` at the remote-tracking branch and sets it as upstream.
+fn create_tracking_branch(
+    repo: &git2::Repository,
+    branch: &str,
+    remote_branch: &RemoteBranch,
+) -> color_eyre::Result<()> {
+    let tracking_name = remote_branch.tracking_name();
+    let commit = repo
+        .find_reference(&remote_branch.tracking_ref())
+        .and_then(|reference| reference.peel_to_commit())
+        .wrap_err_with(|| eyre::eyre!("failed to resolve `{tracking_name}`"))?;
+    repo.branch(branch, &commit, false).wrap_err_with(|| {
+        eyre::eyre!("failed to create branch `{branch}` from `{tracking_name}`")
+    })?;
+    // Written directly, as `Branch::set_upstream` fails when no fetch refspec
+    // covers the branch, e.g. in single-branch clones.
+    repo.config()
+        .and_then(|mut config| {
+            config.set_str(&format!("branch.{branch}.remote"), &remote_branch.remote)?;
+            config.set_str(
+                &format!("branch.{branch}.merge"),
+                &format!("refs/heads/{}", remote_branch.branch),
+            )
+        })
+        .wrap_err_with(|| eyre::eyre!("failed to set `{tracking_name}` as upstream of `{branch}`"))
+}
+
+fn confirm_open_existing(branch: &str, path: &Path) -> color_eyre::Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    output::prompt(tr!(
+        "Branch `{}` is already checked out at `{}`. Open it instead? [y/N] ",
+        branch,
+        path.display()
+    ))
+    .ok();
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .wrap_err("failed to read user input")?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "Yes" | "YES"))
+}
+
+/// Git config key remembering which branch a worktree branch was created from.
+pub(crate) fn base_config_key(branch: &str) -> String {
+    format!("branch.{branch}.rsworktreeBase")
+}
+
+fn record_base_branch(
+    repo: &git2::Repository,
+    branch: &str,
+    base: Option<&str>,
+) -> color_eyre::Result<()> {
+    let base_name = match base {
+        Some(base) => base.to_owned(),
+        None => match repo.head().ok().and_then(|head| head.shorthand().map(String::from)) {
+            Some(name) => name,
+            None => return Ok(()),
+        },
+    };
+
+    repo.config()
+        .and_then(|mut config| config.set_str(&base_config_key(branch), &base_name))
+        .wrap_err_with(|| eyre::eyre!("failed to record base branch for `{branch}`"))
+}
+
+/// Git config key remembering the template a worktree branch was created with.
+pub(crate) fn template_config_key(branch: &str) -> String {
+    format!("branch.{branch}.rsworktreeTemplate")
+}
+
+fn record_template(
+    repo: &git2::Repository,
+    branch: &str,
+    template: &Template,
+) -> color_eyre::Result<()> {
+    repo.config()
+        .and_then(|mut config| config.set_str(&template_config_key(branch), template.name()))
+        .wrap_err_with(|| eyre::eyre!("failed to record template for `{branch}`"))
+}
+
+/// Records when and by whom the worktree was created, and the issue it was
+/// created for, shown by `notes show`, `ls --long` and `status`.
+fn record_creation(
+    repo: &Repo,
+    worktree: &str,
+    branch: &str,
+    issue: Option<&Issue>,
+) -> color_eyre::Result<()> {
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let creator = repo
+        .git()
+        .config()
+        .and_then(|mut config| config.snapshot())
+        .and_then(|config| config.get_string("user.name"))
+        .ok();
+    annotations::record(repo, worktree, branch, |annotations| {
+        annotations.created_at = Some(created_at);
+        annotations.creator = creator;
+        if let Some(issue) = issue {
+            annotations.issue = Some(issue.key.clone());
+            annotations.note = Some(issue.title.clone());
+        }
+    })
+    .wrap_err_with(|| eyre::eyre!("failed to record the creation of `{worktree}`"))?;
+    Ok(())
+}
+
+fn worktree_metadata_name(name: &str) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+
+    let sanitized: String = name
+        .chars()
+        .map(|ch| match ch {
+            '/' | '\\' => '-',
+            ch if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.') => ch,
+            _ => '-',
+        })
+        .collect();
+
+    let mut hasher = DefaultHasher::new();
     hasher.write(name.as_bytes());
     let hash = hasher.finish();
 
@@ -192,7 +1418,11 @@ mod tests {
 
     use tempfile::TempDir;
 
-    use crate::{Repo, commands::cd::SHELL_OVERRIDE_ENV};
+    use crate::{
+        Repo,
+        commands::cd::SHELL_OVERRIDE_ENV,
+        process::{CommandOutput, ScriptedRunner},
+    };
 
     fn init_git_repo(dir: &TempDir) -> color_eyre::Result<()> {
         run(dir, ["git", "init"])?;
@@ -334,4 +1564,651 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn remote_branch_prefix_must_name_a_remote() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = git2::Repository::open(dir.path())?;
+        repo.remote("upstream", "https://example.com/app.git")?;
+
+        let upstream = RemoteBranch::parse(&repo, "upstream/feature/login")?;
+        assert_eq!(
+            (upstream.remote.as_str(), upstream.branch.as_str()),
+            ("upstream", "feature/login")
+        );
+        assert_eq!(
+            upstream.tracking_ref(),
+            "refs/remotes/upstream/feature/login"
+        );
+
+        let origin = RemoteBranch::parse(&repo, "feature/login")?;
+        assert_eq!(origin.tracking_name(), "origin/feature/login");
+        assert!(!origin.fetch_optional);
+
+        Ok(())
+    }
+
+    #[test]
+    fn record_base_branch_defaults_to_current_head() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+
+        let repo = git2::Repository::open(dir.path())?;
+        let head = repo.head()?.shorthand().map(String::from).unwrap();
+
+        record_base_branch(&repo, "feature/one", Some("develop"))?;
+        record_base_branch(&repo, "feature/two", None)?;
+
+        let config = repo.config()?.snapshot()?;
+        assert_eq!(config.get_str(&base_config_key("feature/one"))?, "develop");
+        assert_eq!(config.get_str(&base_config_key("feature/two"))?, head);
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_records_when_and_by_whom() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        run(&dir, ["git", "config", "user.name", "Ada"])?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        CreateCommand::new("feature/meta".into(), None).create_without_enter(&repo, true)?;
+
+        let annotations =
+            crate::annotations::Annotations::load(&repo, "feature/meta", "feature/meta")?;
+        assert!(
+            annotations
+                .created_at
+                .is_some_and(|created_at| created_at > 0)
+        );
+        assert_eq!(annotations.creator.as_deref(), Some("Ada"));
+        assert_eq!(
+            annotations.base,
+            repo.git().head()?.shorthand().map(String::from)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn create_with_report_lists_hooks_and_base() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let head = repo.git().head()?.shorthand().map(String::from);
+        fs::write(
+            repo.ensure_rsworktree_dir()?.join("config.toml"),
+            "[hooks]\npost-create = [\"true\"]\n",
+        )?;
+
+        let report = CreateCommand::new("feature/report".into(), None)
+            .create_with_report(&repo, true)?;
+
+        assert_eq!(report.outcome, CreateOutcome::Created);
+        assert_eq!(report.branch, "feature/report");
+        assert_eq!(report.path, repo.worktrees_dir().join("feature/report"));
+        assert_eq!(report.base, head);
+        assert_eq!(report.hooks, [HookName::PostCreate]);
+        let json = report.to_json();
+        assert_eq!(json["outcome"], "created");
+        assert_eq!(json["hooks"][0], "post-create");
+        assert!(json["duration_ms"].is_u64());
+        Ok(())
+    }
+
+    #[test]
+    fn create_refuses_protected_branch_names() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let worktrees_dir = repo.ensure_worktrees_dir()?;
+        fs::write(
+            worktrees_dir.join("config.toml"),
+            "protected_branches = [\"main\", \"release/*\"]\n",
+        )?;
+
+        let error = CreateCommand::new("release/2.0".into(), None)
+            .create_without_enter(&repo, true)
+            .expect_err("release branches are protected");
+        assert!(
+            error
+                .to_string()
+                .contains("`release/*` in `protected_branches`")
+        );
+        assert!(!worktrees_dir.join("release/2.0").exists());
+        assert!(
+            repo.git()
+                .find_branch("release/2.0", BranchType::Local)
+                .is_err()
+        );
+
+        CreateCommand::new("feature/release".into(), None).create_without_enter(&repo, true)?;
+        Ok(())
+    }
+
+    #[test]
+    fn create_refuses_reserved_names() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        for name in ["state", "meta", "logs/x", "config.toml"] {
+            let error = CreateCommand::new(name.into(), None)
+                .create_without_enter(&repo, true)
+                .expect_err("internal names are reserved");
+            assert!(error.to_string().contains("is reserved"), "{error}");
+            assert!(repo.git().find_branch(name, BranchType::Local).is_err());
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_hook_in_fail_fast_mode_rolls_back_create() -> color_eyre::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let worktrees_dir = repo.ensure_worktrees_dir()?;
+
+        let hooks_dir = worktrees_dir.join("hooks");
+        fs::create_dir_all(&hooks_dir)?;
+        let hook_path = hooks_dir.join("post-create");
+        fs::write(&hook_path, "#!/bin/sh\nexit 1\n")?;
+        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))?;
+        fs::write(
+            worktrees_dir.join("preferences.json"),
+            r#"{"hooks": {"mode": "fail-fast"}}"#,
+        )?;
+
+        let command = CreateCommand::new("feature/nested/broken".into(), None);
+        let err = command.create_without_enter(&repo, true).unwrap_err();
+        assert!(err.to_string().contains("rolled back"));
+
+        assert!(!worktrees_dir.join("feature").exists());
+        let git = repo.git();
+        assert!(git.find_branch("feature/nested/broken", BranchType::Local).is_err());
+        assert!(git.worktrees()?.is_empty());
+        assert!(
+            git.config()?
+                .snapshot()?
+                .get_str(&base_config_key("feature/nested/broken"))
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn refuses_branch_checked_out_in_another_worktree() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let elsewhere = TempDir::new()?;
+        let path = elsewhere.path().join("feature-x");
+        let status = StdCommand::new("git")
+            .current_dir(dir.path())
+            .args(["worktree", "add", "-b", "feature-x"])
+            .arg(&path)
+            .status()?;
+        assert!(status.success());
+        let repo = Repo::discover_from(dir.path())?;
+
+        let existing = repo.worktree_for_branch("feature-x")?.expect("worktree");
+        assert_eq!(existing.path.canonicalize()?, path.canonicalize()?);
+
+        let err = CreateCommand::new("feature-x".into(), None)
+            .create_without_enter(&repo, true)
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("is already checked out at"),
+            "{err:#}"
+        );
+        assert!(!repo.worktrees_dir().join("feature-x").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn rollback_keeps_preexisting_branch_and_directories() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let worktrees_dir = repo.ensure_worktrees_dir()?;
+        fs::create_dir_all(worktrees_dir.join("feature"))?;
+
+        let git = repo.git();
+        let head = git.head()?.peel_to_commit()?;
+        git.branch("feature/kept", &head, false)?;
+
+        let mut transaction = CreateTransaction::new(git);
+        transaction.record_dir(&worktrees_dir.join("feature"));
+        let failures = transaction.rollback();
+
+        assert!(failures.is_empty());
+        assert!(worktrees_dir.join("feature").exists());
+        assert!(git.find_branch("feature/kept", BranchType::Local).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn exists_ok_accepts_matching_worktree() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        let command = CreateCommand::new("feature/again".into(), Some("HEAD".into()))
+            .with_exists_ok(true);
+        assert_eq!(
+            command.create_without_enter(&repo, true)?,
+            CreateOutcome::Created
+        );
+        assert_eq!(
+            command.create_without_enter(&repo, true)?,
+            CreateOutcome::AlreadyExists
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn exists_ok_rejects_branch_not_based_on_requested_base() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        run(&dir, ["git", "checkout", "-q", "-b", "other"])?;
+        fs::write(dir.path().join("other.txt"), "other")?;
+        run(&dir, ["git", "add", "other.txt"])?;
+        run(
+            &dir,
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-q",
+                "-m",
+                "Other commit",
+            ],
+        )?;
+        run(&dir, ["git", "checkout", "-q", "-"])?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        CreateCommand::new("feature/base".into(), None).create_without_enter(&repo, true)?;
+
+        let err = CreateCommand::new("feature/base".into(), Some("other".into()))
+            .with_exists_ok(true)
+            .create_without_enter(&repo, true)
+            .unwrap_err();
+        assert!(err.to_string().contains("is not based on `other`"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn exists_ok_rejects_directory_with_different_branch() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        CreateCommand::new("feature/one".into(), None).create_without_enter(&repo, true)?;
+        let worktree_path = repo.worktrees_dir().join("feature/one");
+        StdCommand::new("git")
+            .current_dir(&worktree_path)
+            .args(["checkout", "-q", "-b", "feature/two"])
+            .status()?;
+
+        let err = CreateCommand::new("feature/one".into(), None)
+            .with_exists_ok(true)
+            .create_without_enter(&repo, true)
+            .unwrap_err();
+        assert!(err.to_string().contains("has `feature/two` checked out"));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rerun_setup_runs_post_create_hook_again() -> color_eyre::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let hooks_dir = repo.ensure_rsworktree_dir()?.join("hooks");
+        fs::create_dir_all(&hooks_dir)?;
+        let counter = dir.path().join("hook-count");
+        let hook_path = hooks_dir.join("post-create");
+        fs::write(
+            &hook_path,
+            format!("#!/bin/sh\necho run >> {:?}\n", counter),
+        )?;
+        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))?;
+
+        let command = CreateCommand::new("feature/setup".into(), None)
+            .with_exists_ok(true)
+            .with_rerun_setup(true);
+        command.create_without_enter(&repo, true)?;
+        command.create_without_enter(&repo, true)?;
+
+        assert_eq!(fs::read_to_string(&counter)?.lines().count(), 2);
+
+        Ok(())
+    }
+
+    const README_DIFF: &str = "diff --git a/README.md b/README.md
+--- a/README.md
++++ b/README.md
+@@ -1 +1 @@
+-test
+\\ No newline at end of file
++patched
+";
+
+    const NOTES_MAILBOX: &str =
+        "From 0000000000000000000000000000000000000000 Mon Sep 17 00:00:00 2001
+From: Test <test@example.com>
+Date: Mon, 1 Jan 2024 00:00:00 +0000
+Subject: [PATCH] Add notes
+
+---
+diff --git a/NOTES.md b/NOTES.md
+new file mode 100644
+--- /dev/null
++++ b/NOTES.md
+@@ -0,0 +1 @@
++notes
+-- 
+2.43.0
+";
+
+    #[test]
+    fn apply_leaves_plain_diff_uncommitted() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let patch_path = dir.path().join("readme.diff");
+        fs::write(&patch_path, README_DIFF)?;
+
+        CreateCommand::new("feature/diff".into(), None)
+            .with_patch(Some(patch_path))
+            .create_without_enter(&repo, true)?;
+
+        let worktree_path = repo.worktrees_dir().join("feature/diff");
+        assert_eq!(
+            fs::read_to_string(worktree_path.join("README.md"))?,
+            "patched\n"
+        );
+        let head = repo
+            .git()
+            .revparse_single("feature/diff")?
+            .peel_to_commit()?;
+        assert_eq!(head.summary(), Some("Initial commit"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn apply_commits_mailbox_patch_with_git_am() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        run(&dir, ["git", "config", "user.name", "Test"])?;
+        run(&dir, ["git", "config", "user.email", "test@example.com"])?;
+        let repo = Repo::discover_from(dir.path())?;
+        let patch_path = dir.path().join("notes.patch");
+        fs::write(&patch_path, NOTES_MAILBOX)?;
+
+        CreateCommand::new("feature/mbox".into(), None)
+            .with_patch(Some(patch_path))
+            .create_without_enter(&repo, true)?;
+
+        let worktree_path = repo.worktrees_dir().join("feature/mbox");
+        assert_eq!(
+            fs::read_to_string(worktree_path.join("NOTES.md"))?,
+            "notes\n"
+        );
+        let head = repo
+            .git()
+            .revparse_single("feature/mbox")?
+            .peel_to_commit()?;
+        assert_eq!(head.summary(), Some("Add notes"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn apply_explains_commit_signing_failure() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        run(&dir, ["git", "config", "user.name", "Test"])?;
+        run(&dir, ["git", "config", "user.email", "test@example.com"])?;
+        run(&dir, ["git", "config", "commit.gpgsign", "true"])?;
+        run(&dir, ["git", "config", "gpg.program", "false"])?;
+        let repo = Repo::discover_from(dir.path())?;
+        let patch_path = dir.path().join("notes.patch");
+        fs::write(&patch_path, NOTES_MAILBOX)?;
+
+        let err = CreateCommand::new("feature/signed".into(), None)
+            .with_patch(Some(patch_path))
+            .create_without_enter(&repo, true)
+            .unwrap_err();
+
+        let message = format!("{err:?}");
+        assert!(message.contains("`false` could not sign the commit (openpgp signing)"));
+        assert!(message.contains("echo test | false --clear-sign"));
+        assert!(!repo.worktrees_dir().join("feature/signed").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn failing_patch_rolls_back_create() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let patch_path = dir.path().join("stale.diff");
+        fs::write(&patch_path, README_DIFF.replace("-test", "-something else"))?;
+
+        let err = CreateCommand::new("feature/stale".into(), None)
+            .with_patch(Some(patch_path))
+            .create_without_enter(&repo, true)
+            .unwrap_err();
+
+        assert!(format!("{err:?}").contains("failed to apply"));
+        assert!(!repo.worktrees_dir().join("feature/stale").exists());
+        assert!(
+            repo.git()
+                .find_branch("feature/stale", BranchType::Local)
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn apply_runs_git_am_through_the_runner() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let patch_path = dir.path().join("notes.patch");
+        fs::write(&patch_path, NOTES_MAILBOX)?;
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::failure(128, "error: patch failed"))
+            .push(CommandOutput::success(""));
+
+        let err = CreateCommand::with_runner("feature/scripted".into(), None, runner.clone())
+            .with_patch(Some(patch_path))
+            .create_without_enter(&repo, true)
+            .unwrap_err();
+
+        assert!(format!("{err:?}").contains("with `git am`: error: patch failed"));
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].args[..2], ["am", "--quiet"]);
+        assert!(calls[0].args[2].ends_with(PATCH_FILE));
+        assert_eq!(calls[1].args, ["am", "--abort"]);
+        assert!(!repo.worktrees_dir().join("feature/scripted").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_remote_fetches_through_the_runner() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::failure(
+            128,
+            "fatal: couldn't find remote ref",
+        ));
+
+        let err = CreateCommand::with_runner("feature/remote".into(), None, runner.clone())
+            .with_from_remote(Some("feature/remote".into()))
+            .create_without_enter(&repo, true)
+            .unwrap_err();
+
+        assert!(format!("{err:?}").contains("failed to fetch `origin/feature/remote`"));
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(
+            calls[0].args,
+            [
+                "fetch",
+                "--quiet",
+                "origin",
+                "+refs/heads/feature/remote:refs/remotes/origin/feature/remote"
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_bundle_imports_branch_into_new_worktree() -> color_eyre::Result<()> {
+        let source = TempDir::new()?;
+        init_git_repo(&source)?;
+        run(&source, ["git", "checkout", "-q", "-b", "feature/shared"])?;
+        fs::write(source.path().join("shared.txt"), "shared")?;
+        run(&source, ["git", "add", "shared.txt"])?;
+        run(
+            &source,
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "Add shared file",
+            ],
+        )?;
+        let bundle = source.path().join("shared.bundle");
+        let status = StdCommand::new("git")
+            .current_dir(source.path())
+            .args(["bundle", "create", "--quiet"])
+            .arg(&bundle)
+            .arg("refs/heads/feature/shared")
+            .status()?;
+        assert!(status.success());
+
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        CreateCommand::new("feature/imported".into(), None)
+            .with_bundle(Some(bundle))
+            .create_without_enter(&repo, true)?;
+
+        let worktree_path = repo.worktrees_dir().join("feature/imported");
+        assert_eq!(
+            fs::read_to_string(worktree_path.join("shared.txt"))?,
+            "shared"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sparse_checkout_limits_the_worktree_to_the_given_directories() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        for package in ["api", "web"] {
+            let package_dir = dir.path().join("packages").join(package);
+            fs::create_dir_all(&package_dir)?;
+            fs::write(package_dir.join("lib.rs"), package)?;
+        }
+        run(&dir, ["git", "add", "packages"])?;
+        run(
+            &dir,
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "Add packages",
+            ],
+        )?;
+        let repo = Repo::discover_from(dir.path())?;
+        let worktrees_dir = repo.ensure_worktrees_dir()?;
+        fs::write(
+            worktrees_dir.join("preferences.json"),
+            r#"{ "worktree": { "sparse": ["packages/web"] } }"#,
+        )?;
+
+        CreateCommand::new("feature/api".into(), None)
+            .with_sparse(vec!["packages/api".into()])
+            .create_without_enter(&repo, true)?;
+        let api = worktrees_dir.join("feature/api");
+        assert!(api.join("README.md").exists());
+        assert!(api.join("packages/api/lib.rs").exists());
+        assert!(!api.join("packages/web").exists());
+
+        CreateCommand::new("feature/web".into(), None).create_without_enter(&repo, true)?;
+        let web = worktrees_dir.join("feature/web");
+        assert!(web.join("packages/web/lib.rs").exists());
+        assert!(!web.join("packages/api").exists());
+
+        CreateCommand::new("feature/full".into(), None)
+            .with_full_checkout(true)
+            .create_without_enter(&repo, true)?;
+        let full = worktrees_dir.join("feature/full");
+        assert!(full.join("packages/api/lib.rs").exists());
+        assert!(full.join("packages/web/lib.rs").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn templates_are_rendered_with_allocated_ports() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        fs::write(
+            dir.path().join(".env.template"),
+            "APP={{worktree}}\nPORT={{port}}\n",
+        )?;
+        let repo = Repo::discover_from(dir.path())?;
+        let worktrees_dir = repo.ensure_worktrees_dir()?;
+        fs::write(
+            worktrees_dir.join("config.toml"),
+            "[templates]\nfiles = [\".env.template\"]\nports = 2\nfirst_port = 42000\n",
+        )?;
+
+        CreateCommand::new("feature/env".into(), None).create_without_enter(&repo, true)?;
+
+        let range = PortRegistry::new(&worktrees_dir).allocate("feature/env", 42000, 2)?;
+        assert!(range.start >= 42000);
+        assert_eq!(
+            fs::read_to_string(worktrees_dir.join("feature/env/.env"))?,
+            format!("APP=feature/env\nPORT={}\n", range.start)
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,233 @@
+//! Worktree names generated from `branch_pattern` in `config.toml`.
+//!
+//! With `branch_pattern = "{user}/{type}/{slug}"`, `create "Fix login crash"
+//! --type fix` creates `dfeyer/fix/fix-login-crash`. `{slug}` is the name
+//! given to `create`, `{type}` the value of `--type` and `{user}` the local
+//! part of `user.email`, or else `$USER`. Accented Latin letters are folded
+//! to ASCII and other letters are kept in lowercase; a segment left empty,
+//! such as `{type}` without `--type`, is dropped.
+
+use std::{env, mem};
+
+use color_eyre::eyre;
+use git2::BranchType;
+
+use crate::Repo;
+
+/// Longest slug in characters; longer titles are cut at a word boundary.
+const MAX_SLUG_LEN: usize = 48;
+
+/// The worktree name for `title` under `pattern`. Unless `reuse` allows an
+/// existing worktree or branch of that name, `-2`, `-3`, ... is appended
+/// until the name is free.
+pub(crate) fn pattern_name(
+    repo: &Repo,
+    pattern: &str,
+    title: &str,
+    kind: Option<&str>,
+    reuse: bool,
+) -> color_eyre::Result<String> {
+    let user = pattern.contains("{user}").then(|| user(repo)).flatten();
+    let name = render(pattern, title, kind, user.as_deref())?;
+    if reuse {
+        return Ok(name);
+    }
+    let taken = |name: &str| {
+        repo.worktrees_dir().join(name).exists()
+            || repo.git().find_branch(name, BranchType::Local).is_ok()
+    };
+    let mut candidate = name.clone();
+    let mut suffix = 1;
+    while taken(&candidate) {
+        suffix += 1;
+        candidate = format!("{name}-{suffix}");
+    }
+    Ok(candidate)
+}
+
+/// Fills the placeholders of `pattern` and drops empty path segments.
+fn render(
+    pattern: &str,
+    title: &str,
+    kind: Option<&str>,
+    user: Option<&str>,
+) -> color_eyre::Result<String> {
+    let slug = slugify(title);
+    if slug.is_empty() {
+        return Err(eyre::eyre!(
+            "`{}` contains no letters or digits to name the branch after",
+            title
+        ));
+    }
+    let rendered = pattern
+        .replace("{slug}", &slug)
+        .replace("{type}", &kind.map(slugify).unwrap_or_default())
+        .replace("{user}", &user.map(slugify).unwrap_or_default());
+    if let Some(start) = rendered.find('{') {
+        let placeholder = rendered[start..]
+            .split_inclusive('}')
+            .next()
+            .unwrap_or_default();
+        return Err(eyre::eyre!(
+            "unknown placeholder `{}` in `branch_pattern`; use {{user}}, {{type}} and {{slug}}",
+            placeholder
+        ));
+    }
+    Ok(rendered
+        .split('/')
+        .map(|segment| segment.trim_matches(['-', '_', '.']))
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+/// `text` in lowercase words joined by `-`, with accented Latin letters
+/// folded to ASCII, cut at a word boundary.
+pub(crate) fn slugify(text: &str) -> String {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for ch in text.chars().flat_map(char::to_lowercase) {
+        if let Some(folded) = fold(ch) {
+            word.push_str(folded);
+        } else if ch.is_alphanumeric() {
+            word.push(ch);
+        } else if !word.is_empty() {
+            words.push(mem::take(&mut word));
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    let mut slug = String::new();
+    let mut len = 0;
+    for word in words {
+        let word_len = word.chars().count();
+        if len > 0 && len + 1 + word_len > MAX_SLUG_LEN {
+            break;
+        }
+        if len > 0 {
+            slug.push('-');
+            len += 1;
+        }
+        slug.extend(word.chars().take(MAX_SLUG_LEN - len));
+        len += word_len.min(MAX_SLUG_LEN - len);
+    }
+    slug
+}
+
+/// The ASCII spelling of a lowercase accented Latin letter.
+fn fold(ch: char) -> Option<&'static str> {
+    Some(match ch {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' | 'ŧ' | 'ț' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    })
+}
+
+/// The local part of `user.email`, or else the login name.
+fn user(repo: &Repo) -> Option<String> {
+    repo.git()
+        .config()
+        .and_then(|config| config.get_string("user.email"))
+        .ok()
+        .and_then(|email| email.split_once('@').map(|(local, _)| local.to_owned()))
+        .or_else(|| env::var("USER").ok())
+        .or_else(|| env::var("USERNAME").ok())
+        .filter(|user| !user.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{path::Path, process::Command};
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn slugify_folds_accents_and_keeps_other_letters() {
+        assert_eq!(slugify("Fix login crash"), "fix-login-crash");
+        assert_eq!(slugify("Über größe"), "uber-grosse");
+        assert_eq!(slugify("Crème brûlée: ÉTÉ!"), "creme-brulee-ete");
+        assert_eq!(slugify("Поиск по сайту"), "поиск-по-сайту");
+        assert_eq!(slugify(" -- "), "");
+
+        let long = slugify(&"wörter ".repeat(20));
+        assert!(long.chars().count() <= MAX_SLUG_LEN, "{long}");
+        assert!(long.ends_with("worter"), "{long}");
+    }
+
+    #[test]
+    fn render_fills_placeholders_and_drops_empty_segments() -> color_eyre::Result<()> {
+        let pattern = "{user}/{type}/{slug}";
+        assert_eq!(
+            render(pattern, "Fix login crash", Some("fix"), Some("dfeyer"))?,
+            "dfeyer/fix/fix-login-crash"
+        );
+        assert_eq!(
+            render(pattern, "Fix login crash", None, Some("dfeyer"))?,
+            "dfeyer/fix-login-crash"
+        );
+        assert_eq!(
+            render("{type}-{slug}", "Add search", None, None)?,
+            "add-search"
+        );
+        assert!(render("{team}/{slug}", "Add search", None, None).is_err());
+        assert!(render(pattern, "!!", None, None).is_err());
+        Ok(())
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> color_eyre::Result<()> {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()?;
+        assert!(status.success(), "git {args:?} failed");
+        Ok(())
+    }
+
+    #[test]
+    fn pattern_name_suffixes_taken_names() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let root = dir.path();
+        git(root, &["init", "-b", "main"])?;
+        git(root, &["config", "user.email", "dfeyer@example.com"])?;
+        git(root, &["commit", "--allow-empty", "-m", "init"])?;
+        git(root, &["branch", "dfeyer/fix/login"])?;
+        let repo = Repo::discover_from(root)?;
+        let pattern = "{user}/{type}/{slug}";
+
+        let name = pattern_name(&repo, pattern, "Login", Some("fix"), false)?;
+        assert_eq!(name, "dfeyer/fix/login-2");
+        std::fs::create_dir_all(repo.worktrees_dir().join(&name))?;
+        let name = pattern_name(&repo, pattern, "Login", Some("fix"), false)?;
+        assert_eq!(name, "dfeyer/fix/login-3");
+
+        let name = pattern_name(&repo, pattern, "Login", Some("fix"), true)?;
+        assert_eq!(name, "dfeyer/fix/login");
+        Ok(())
+    }
+}
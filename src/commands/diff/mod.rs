@@ -0,0 +1,220 @@
+//! `diff`: compare the branches of two worktrees, or with `--working` their
+//! working trees, through `git diff` or `git difftool`.
+
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Output},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+
+use crate::{Repo, commands::open::resolve_by_name};
+
+/// Index written while snapshotting a working tree, in the worktree's git directory.
+const SNAPSHOT_INDEX: &str = "rsworktree-diff-index";
+
+#[derive(Debug)]
+pub struct DiffCommand {
+    from: String,
+    to: String,
+    working: bool,
+    stat: bool,
+    tool: Option<String>,
+}
+
+impl DiffCommand {
+    /// Shows what changes from worktree `from` to worktree `to`.
+    pub fn new(from: String, to: String) -> Self {
+        Self {
+            from,
+            to,
+            working: false,
+            stat: false,
+            tool: None,
+        }
+    }
+
+    /// Compare the working trees, uncommitted and untracked files included,
+    /// instead of the committed branches.
+    pub fn with_working(mut self, working: bool) -> Self {
+        self.working = working;
+        self
+    }
+
+    /// Print a diffstat instead of the patch.
+    pub fn with_stat(mut self, stat: bool) -> Self {
+        self.stat = stat;
+        self
+    }
+
+    /// Open the differences with `git difftool --tool <tool>` instead of printing them.
+    pub fn with_tool(mut self, tool: Option<String>) -> Self {
+        self.tool = tool;
+        self
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let from = resolve_by_name(&self.from, repo)?;
+        let to = resolve_by_name(&self.to, repo)?;
+        let (from, to) = if self.working {
+            (snapshot(&from.path)?, snapshot(&to.path)?)
+        } else {
+            (head(&from.path)?, head(&to.path)?)
+        };
+
+        let args = self.git_args(&from, &to);
+        let status = Command::new("git")
+            .args(&args)
+            .current_dir(repo.root())
+            .status()
+            .wrap_err_with(|| eyre::eyre!("failed to run `git {}`", args[0]))?;
+        if status.success() {
+            return Ok(());
+        }
+        Err(eyre::eyre!(
+            "`git {}` exited with code {}",
+            args[0],
+            status.code().unwrap_or(-1)
+        ))
+    }
+
+    fn git_args(&self, from: &str, to: &str) -> Vec<String> {
+        let mut args = match &self.tool {
+            Some(tool) => vec![
+                "difftool".to_owned(),
+                "--no-prompt".to_owned(),
+                format!("--tool={tool}"),
+            ],
+            None => vec!["diff".to_owned()],
+        };
+        if self.stat {
+            args.push("--stat".to_owned());
+        }
+        args.extend([from.to_owned(), to.to_owned()]);
+        args
+    }
+}
+
+/// The commit checked out in the worktree.
+fn head(worktree_path: &Path) -> color_eyre::Result<String> {
+    let git_repo = git2::Repository::open(worktree_path).wrap_err_with(|| {
+        eyre::eyre!("failed to open worktree at `{}`", worktree_path.display())
+    })?;
+    let commit = git_repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .wrap_err_with(|| eyre::eyre!("failed to read HEAD of `{}`", worktree_path.display()))?;
+    Ok(commit.id().to_string())
+}
+
+/// Writes the working tree as a tree object, untracked files included and
+/// ignored ones left out, using a copy of the index so that the worktree's
+/// own index and staged changes are left alone.
+fn snapshot(worktree_path: &Path) -> color_eyre::Result<String> {
+    let git_repo = git2::Repository::open(worktree_path).wrap_err_with(|| {
+        eyre::eyre!("failed to open worktree at `{}`", worktree_path.display())
+    })?;
+    let index = git_repo.path().join(SNAPSHOT_INDEX);
+    let tree = write_snapshot(&git_repo, worktree_path, &index);
+    let _ = fs::remove_file(&index);
+    tree
+}
+
+fn write_snapshot(
+    git_repo: &git2::Repository,
+    worktree_path: &Path,
+    index: &Path,
+) -> color_eyre::Result<String> {
+    // Starting from the real index keeps its stat cache, so unchanged files
+    // are not hashed again.
+    let real_index = git_repo.path().join("index");
+    if real_index.exists() {
+        fs::copy(&real_index, index)
+            .wrap_err_with(|| eyre::eyre!("failed to copy `{}`", real_index.display()))?;
+    }
+    let git = |args: &[&str]| -> color_eyre::Result<Output> {
+        let output = Command::new("git")
+            .args(args)
+            .env("GIT_INDEX_FILE", index)
+            .current_dir(worktree_path)
+            .output()
+            .wrap_err_with(|| eyre::eyre!("failed to run `git {}`", args.join(" ")))?;
+        if output.status.success() {
+            return Ok(output);
+        }
+        Err(eyre::eyre!(
+            "`git {}` failed in `{}`: {}",
+            args.join(" "),
+            worktree_path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    };
+    git(&["add", "--all"])?;
+    let output = git(&["write-tree"])?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    use crate::commands::create::CreateCommand;
+
+    fn git(dir: &Path, args: &[&str]) -> color_eyre::Result<String> {
+        let output = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()?;
+        assert!(output.status.success(), "git {args:?} failed");
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    #[test]
+    fn git_args_pass_stat_and_tool_through() {
+        let command = DiffCommand::new("a".into(), "b".into()).with_stat(true);
+        assert_eq!(
+            command.git_args("1111", "2222"),
+            ["diff", "--stat", "1111", "2222"]
+        );
+
+        let command = DiffCommand::new("a".into(), "b".into()).with_tool(Some("meld".into()));
+        assert_eq!(
+            command.git_args("1111", "2222"),
+            ["difftool", "--no-prompt", "--tool=meld", "1111", "2222"]
+        );
+    }
+
+    #[test]
+    fn snapshot_includes_uncommitted_and_untracked_files() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        git(dir.path(), &["init", "--quiet"])?;
+        fs::write(dir.path().join(".gitignore"), ".rsworktree/\ntarget/\n")?;
+        git(dir.path(), &["add", ".gitignore"])?;
+        git(dir.path(), &["commit", "--quiet", "-m", "Initial"])?;
+        let repo = Repo::discover_from(dir.path())?;
+        CreateCommand::new("feature/a".into(), None).create_without_enter(&repo, true)?;
+        CreateCommand::new("feature/b".into(), None).create_without_enter(&repo, true)?;
+        let worktree_a = repo.worktrees_dir().join("feature/a");
+        let worktree_b = repo.worktrees_dir().join("feature/b");
+
+        assert_eq!(head(&worktree_a)?, head(&worktree_b)?);
+        assert_eq!(snapshot(&worktree_a)?, snapshot(&worktree_b)?);
+
+        fs::write(worktree_b.join("new.txt"), "draft\n")?;
+        fs::create_dir(worktree_b.join("target"))?;
+        fs::write(worktree_b.join("target/build.log"), "ignored\n")?;
+        let tree = snapshot(&worktree_b)?;
+        let files = git(dir.path(), &["ls-tree", "-r", "--name-only", &tree])?;
+        assert_eq!(files.lines().collect::<Vec<_>>(), [".gitignore", "new.txt"]);
+        assert_eq!(
+            git(&worktree_b, &["status", "--porcelain"])?,
+            "?? new.txt\n"
+        );
+        assert_eq!(head(&worktree_a)?, head(&worktree_b)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,544 @@
+//! `rsworktree doctor`: checks the tools and settings rsworktree relies on.
+//!
+//! Every check prints one line, and a problem is followed by a hint on how to
+//! fix it. A missing optional tool, such as tmux or the provider CLI, only
+//! warns; the command fails when a check fails that breaks other commands, so
+//! it can also gate a setup script.
+
+use std::{ffi::OsStr, process::Command};
+
+use color_eyre::eyre;
+
+use crate::{
+    GitProvider, Repo,
+    editor::{
+        CONFIG_FILE_NAME, EditorPreferenceResolution, PreferenceMissingReason, detect_editor,
+        locate, resolve_editor_preference, url_template,
+    },
+    hooks::{HookName, HookRunner, is_executable},
+    output,
+    provider::ProviderBackend,
+    repo::signing::{Signing, SigningFormat},
+};
+
+/// The oldest git with `git worktree remove`, which `pr checkout` relies on.
+const MIN_GIT_VERSION: (u32, u32) = (2, 17);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    /// Something optional is missing; the commands that need it say so.
+    Warning,
+    /// Something other commands rely on is broken.
+    Failure,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Check {
+    status: Status,
+    message: String,
+    /// How to fix the problem, for a warning or failure.
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(message: String) -> Self {
+        Self {
+            status: Status::Ok,
+            message,
+            fix: None,
+        }
+    }
+
+    fn warning(message: String, fix: String) -> Self {
+        Self {
+            status: Status::Warning,
+            message,
+            fix: Some(fix),
+        }
+    }
+
+    fn failure(message: String, fix: String) -> Self {
+        Self {
+            status: Status::Failure,
+            message,
+            fix: Some(fix),
+        }
+    }
+
+    fn print(&self) {
+        match self.status {
+            Status::Ok => output::success(&self.message),
+            Status::Warning => output::warn(&self.message),
+            Status::Failure => output::error(&self.message),
+        }
+        if let Some(fix) = &self.fix {
+            output::hint(fix);
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DoctorCommand {
+    provider: GitProvider,
+}
+
+impl DoctorCommand {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The provider whose CLI is looked up.
+    pub fn with_provider(mut self, provider: GitProvider) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let checks = self.run_checks(repo);
+        for check in &checks {
+            check.print();
+        }
+
+        let count = |status| checks.iter().filter(|check| check.status == status).count();
+        let failures = count(Status::Failure);
+        if failures > 0 {
+            return Err(eyre::eyre!(
+                "{failures} check(s) failed; see the hints above"
+            ));
+        }
+        match count(Status::Warning) {
+            0 => output::success(tr!("Everything rsworktree needs is in place.")),
+            warnings => output::note(tr!(
+                "Everything rsworktree needs is in place; {} optional check(s) reported a problem.",
+                warnings
+            )),
+        }
+        Ok(())
+    }
+
+    fn run_checks(&self, repo: &Repo) -> Vec<Check> {
+        let mut checks = vec![check_git(), check_worktrees(repo), check_config(repo)];
+        checks.extend(check_alternates(repo));
+        checks.extend(check_signing(repo));
+        checks.extend(check_editor(repo));
+        checks.push(check_provider(repo, self.provider));
+        checks.push(check_tmux());
+        checks.extend(check_hooks(repo));
+        checks
+    }
+}
+
+fn check_git() -> Check {
+    let fix = || {
+        tr!(
+            "install git {}.{} or later from https://git-scm.com",
+            MIN_GIT_VERSION.0,
+            MIN_GIT_VERSION.1
+        )
+    };
+    let Some(stdout) = command_stdout("git", &["--version"]) else {
+        return Check::failure(tr!("git is not installed or not on PATH."), fix());
+    };
+    match parse_git_version(&stdout) {
+        Some((version, number)) if number >= MIN_GIT_VERSION => {
+            Check::ok(tr!("git {} is installed.", version))
+        }
+        Some((version, _)) => Check::failure(
+            tr!(
+                "git {} is too old for worktrees as rsworktree uses them.",
+                version
+            ),
+            fix(),
+        ),
+        None => Check::warning(
+            tr!("Could not read the git version from `{}`.", stdout.trim()),
+            fix(),
+        ),
+    }
+}
+
+/// The version in `git version 2.39.3 (Apple Git-146)` and its major and
+/// minor number.
+fn parse_git_version(stdout: &str) -> Option<(String, (u32, u32))> {
+    let version = stdout.trim().strip_prefix("git version ")?;
+    let version = version.split_whitespace().next()?;
+    let mut numbers = version.split('.').map(|part| part.parse::<u32>().ok());
+    let major = numbers.next()??;
+    let minor = numbers.next()??;
+    Some((version.to_owned(), (major, minor)))
+}
+
+fn check_worktrees(repo: &Repo) -> Check {
+    let worktrees = match repo.git_worktrees() {
+        Ok(worktrees) => worktrees,
+        Err(error) => {
+            return Check::failure(
+                tr!("`git worktree list` failed: {}", error.root_cause()),
+                tr!("run `git worktree list` in the repository to see what git reports"),
+            );
+        }
+    };
+    let prunable = worktrees
+        .iter()
+        .filter(|worktree| worktree.prunable)
+        .count();
+    if prunable > 0 {
+        return Check::warning(
+            tr!(
+                "{} worktree(s) registered with git no longer exist on disk.",
+                prunable
+            ),
+            tr!("run `git worktree prune` to forget them"),
+        );
+    }
+    Check::ok(tr!("git lists {} worktree(s).", worktrees.len()))
+}
+
+/// Only reported for repositories that borrow objects from other clones.
+fn check_alternates(repo: &Repo) -> Option<Check> {
+    let alternates = match repo.alternates() {
+        Ok(alternates) if alternates.is_empty() => return None,
+        Ok(alternates) => alternates,
+        Err(error) => {
+            return Some(Check::failure(
+                error.to_string(),
+                tr!("check the permissions of `.git/objects/info/alternates`"),
+            ));
+        }
+    };
+    if let Some(missing) = alternates.iter().find(|alternate| !alternate.is_dir()) {
+        return Some(Check::failure(
+            tr!(
+                "The clone objects are borrowed from is gone: `{}`.",
+                missing.display()
+            ),
+            tr!(
+                "restore that clone, or remove its line from `.git/objects/info/alternates` and fetch again"
+            ),
+        ));
+    }
+    Some(Check::ok(tr!(
+        "Objects are shared with {} other clone(s).",
+        alternates.len()
+    )))
+}
+
+/// Only reported when git signs commits (`commit.gpgsign`), which the
+/// commits `create --apply` makes with `git am` are then too.
+fn check_signing(repo: &Repo) -> Option<Check> {
+    let config = repo
+        .git()
+        .config()
+        .and_then(|mut config| config.snapshot())
+        .ok()?;
+    let signing = Signing::load(&config)?;
+    if locate(OsStr::new(&signing.program)).is_none() {
+        return Some(Check::warning(
+            tr!(
+                "Commits are signed with {}, but `{}` is not installed.",
+                signing.format.as_str(),
+                signing.program
+            ),
+            tr!(
+                "install `{}`, or point `gpg.{}.program` at it",
+                signing.program,
+                signing.format.as_str()
+            ),
+        ));
+    }
+    if signing.format == SigningFormat::Ssh
+        && signing.key.is_none()
+        && config.get_string("gpg.ssh.defaultKeyCommand").is_err()
+    {
+        return Some(Check::warning(
+            tr!("Commits are signed with ssh, but no signing key is set."),
+            tr!(
+                "run `git config --global user.signingkey ~/.ssh/id_ed25519.pub` with your public key"
+            ),
+        ));
+    }
+    Some(Check::ok(tr!(
+        "Commits are signed with {} using `{}`.",
+        signing.format.as_str(),
+        signing.program
+    )))
+}
+
+fn check_config(repo: &Repo) -> Check {
+    match repo.config() {
+        Ok(_) => Check::ok(tr!("`config.toml` settings are valid.")),
+        Err(error) => Check::failure(
+            tr!("`{}` is invalid: {}", error.path.display(), error.message),
+            tr!("fix or remove the setting, then run `rsworktree doctor` again"),
+        ),
+    }
+}
+
+/// Problems with `config.toml` are left to [`check_config`].
+fn check_editor(repo: &Repo) -> Option<Check> {
+    let set_editor = || tr!("set one with `rsworktree editor set <command>` or `$EDITOR`");
+    let resolution = match resolve_editor_preference(repo) {
+        Ok(resolution) => resolution,
+        Err(error) => {
+            return Some(Check::failure(
+                tr!("Could not resolve the editor: {}", error.root_cause()),
+                set_editor(),
+            ));
+        }
+    };
+    let check = match resolution {
+        EditorPreferenceResolution::Found(preference) => {
+            let command = preference.command.to_string_lossy().into_owned();
+            if url_template(&preference.command).is_some() {
+                Check::ok(tr!("Editor `{}` opens worktrees through a URL.", command))
+            } else if locate(&preference.command).is_some() {
+                Check::ok(tr!("Editor `{}` is installed.", command))
+            } else {
+                Check::failure(
+                    tr!("Editor `{}` is not on PATH.", command),
+                    tr!("install it or change the editor with `rsworktree editor set <command>`"),
+                )
+            }
+        }
+        EditorPreferenceResolution::Missing(PreferenceMissingReason::NotConfigured) => {
+            match detect_editor() {
+                Some(preference) => Check::ok(tr!(
+                    "No editor configured; `{}` found on PATH will be used.",
+                    preference.command.to_string_lossy()
+                )),
+                None => Check::warning(
+                    tr!("No editor is configured or found on PATH."),
+                    set_editor(),
+                ),
+            }
+        }
+        EditorPreferenceResolution::Missing(PreferenceMissingReason::ConfigInvalid {
+            path,
+            error,
+        }) => {
+            if path.file_name() != Some(OsStr::new(CONFIG_FILE_NAME)) {
+                return None;
+            }
+            Check::failure(
+                tr!("`{}` is invalid: {}", path.display(), error),
+                tr!("fix the file or set the editor again with `rsworktree editor set <command>`"),
+            )
+        }
+        EditorPreferenceResolution::Missing(PreferenceMissingReason::EnvInvalid {
+            variable,
+            error,
+        }) => Check::failure(
+            tr!(
+                "`${}` cannot be used as the editor: {}",
+                variable.name(),
+                error
+            ),
+            tr!("fix `${}` or {}", variable.name(), set_editor()),
+        ),
+    };
+    Some(check)
+}
+
+fn check_provider(repo: &Repo, provider: GitProvider) -> Check {
+    if ProviderBackend::resolve(repo) == ProviderBackend::Api {
+        return match provider.client(ProviderBackend::Api).unavailable() {
+            None => Check::ok(tr!("{} operations call its API.", provider.display_name())),
+            Some(reason) => Check::warning(
+                tr!(
+                    "The {} API cannot be used: {}",
+                    provider.display_name(),
+                    reason
+                ),
+                tr!(
+                    "fix the above, or set `provider_backend = \"cli\"` in `config.toml` to run `{}`",
+                    provider.cli_program()
+                ),
+            ),
+        };
+    }
+    let program = provider.cli_program();
+    if locate(OsStr::new(program)).is_some() {
+        return Check::ok(tr!(
+            "`{}` for {} is installed.",
+            program,
+            provider.display_name()
+        ));
+    }
+    Check::warning(
+        tr!(
+            "`{}` for {} is not on PATH; `review`, `merge` and `pr` need it.",
+            program,
+            provider.display_name()
+        ),
+        tr!(
+            "install `{}`, or select your provider with `--provider` or `provider` in `config.toml`",
+            program
+        ),
+    )
+}
+
+fn check_tmux() -> Check {
+    match command_stdout("tmux", &["-V"]) {
+        Some(version) => Check::ok(tr!("{} is installed.", version.trim())),
+        None => Check::warning(
+            tr!("tmux is not installed."),
+            tr!("install tmux to open worktrees in tmux windows and sessions"),
+        ),
+    }
+}
+
+fn check_hooks(repo: &Repo) -> Vec<Check> {
+    let runner = HookRunner::new(&repo.rsworktree_dir());
+    let scripts = HookName::ALL
+        .iter()
+        .filter_map(|hook| runner.script_path(*hook))
+        .collect::<Vec<_>>();
+    let checks = scripts
+        .iter()
+        .filter(|path| !is_executable(path))
+        .map(|path| {
+            Check::failure(
+                tr!("Hook `{}` is not executable.", path.display()),
+                tr!("run `chmod +x {}`", path.display()),
+            )
+        })
+        .collect::<Vec<_>>();
+    if checks.is_empty() {
+        return vec![Check::ok(tr!(
+            "{} hook script(s) found, all executable.",
+            scripts.len()
+        ))];
+    }
+    checks
+}
+
+/// The standard output of `program`, or `None` when it cannot be run or fails.
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn git_version_is_read_from_the_version_line() {
+        assert_eq!(
+            parse_git_version("git version 2.39.3 (Apple Git-146)\n"),
+            Some(("2.39.3".into(), (2, 39)))
+        );
+        assert_eq!(
+            parse_git_version("git version 2.45.1.windows.1"),
+            Some(("2.45.1.windows.1".into(), (2, 45)))
+        );
+        assert_eq!(parse_git_version("hub version 2.14.2"), None);
+        assert!((2, 9) < MIN_GIT_VERSION);
+    }
+
+    #[test]
+    fn doctor_reports_invalid_config_and_hooks() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        git2::Repository::init(dir.path())?;
+        let repo = Repo::discover_from(dir.path())?;
+        let rsworktree_dir = repo.ensure_rsworktree_dir()?;
+        fs::write(rsworktree_dir.join("config.toml"), "[editor\n")?;
+        fs::create_dir_all(rsworktree_dir.join("hooks"))?;
+        fs::write(rsworktree_dir.join("hooks/post-create"), "#!/bin/sh\n")?;
+
+        let checks = DoctorCommand::new().run_checks(&repo);
+        let failures = checks
+            .iter()
+            .filter(|check| check.status == Status::Failure)
+            .map(|check| check.message.as_str())
+            .collect::<Vec<_>>();
+        assert!(
+            failures
+                .iter()
+                .any(|message| message.contains("config.toml")),
+            "{failures:?}"
+        );
+        if cfg!(unix) {
+            assert!(
+                failures
+                    .iter()
+                    .any(|message| message.contains("post-create")),
+                "{failures:?}"
+            );
+        }
+        assert!(DoctorCommand::new().execute(&repo).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn doctor_reports_a_missing_reference_clone() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        git2::Repository::init(dir.path())?;
+        let repo = Repo::discover_from(dir.path())?;
+        assert_eq!(check_alternates(&repo), None);
+
+        let reference = TempDir::new()?;
+        git2::Repository::init(reference.path())?;
+        repo.attach_alternate(reference.path())?;
+        let check = check_alternates(&repo).expect("alternates are checked");
+        assert_eq!(check.status, Status::Ok);
+
+        drop(reference);
+        let check = check_alternates(&repo).expect("alternates are checked");
+        assert_eq!(check.status, Status::Failure);
+        Ok(())
+    }
+
+    #[test]
+    fn doctor_checks_commit_signing_when_enabled() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let git_repo = git2::Repository::init(dir.path())?;
+        let repo = Repo::discover_from(dir.path())?;
+        let mut config = git_repo.config()?.open_level(git2::ConfigLevel::Local)?;
+        config.set_bool("commit.gpgsign", false)?;
+        assert_eq!(check_signing(&repo), None);
+
+        config.set_bool("commit.gpgsign", true)?;
+        config.set_str("gpg.format", "ssh")?;
+        config.set_str("gpg.ssh.program", "rsworktree-missing-ssh-keygen")?;
+        let check = check_signing(&repo).expect("signing is checked");
+        assert_eq!(check.status, Status::Warning);
+        assert!(check.message.contains("is not installed"), "{check:?}");
+
+        config.set_str("gpg.ssh.program", "git")?;
+        let check = check_signing(&repo).expect("signing is checked");
+        assert_eq!(check.status, Status::Warning);
+        assert!(check.message.contains("no signing key"), "{check:?}");
+
+        config.set_str("user.signingkey", "~/.ssh/id_ed25519.pub")?;
+        let check = check_signing(&repo).expect("signing is checked");
+        assert_eq!(check.status, Status::Ok);
+        Ok(())
+    }
+
+    #[test]
+    fn doctor_checks_the_api_when_it_replaces_the_cli() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        git2::Repository::init(dir.path())?;
+        let repo = Repo::discover_from(dir.path())?;
+        let rsworktree_dir = repo.ensure_rsworktree_dir()?;
+        fs::write(
+            rsworktree_dir.join("config.toml"),
+            "provider_backend = \"api\"\n",
+        )?;
+
+        // Gitea has no API backend.
+        let check = check_provider(&repo, GitProvider::Gitea);
+        assert_eq!(check.status, Status::Warning);
+        assert!(check.message.contains("API cannot be used"), "{check:?}");
+        Ok(())
+    }
+}
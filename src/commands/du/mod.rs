@@ -0,0 +1,282 @@
+use std::{fs, path::Path, str::FromStr};
+
+use color_eyre::eyre::{self, WrapErr};
+use git2::Repository;
+use owo_colors::OwoColorize;
+
+use crate::{
+    Repo,
+    commands::{
+        info::{disk_usage, format_size},
+        list::{find_managed_worktrees, format_worktree},
+    },
+    output::{Cell, Table},
+};
+
+/// Order of the worktrees in the `du` report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiskUsageSort {
+    /// Largest worktree first.
+    #[default]
+    Size,
+    Name,
+}
+
+impl FromStr for DiskUsageSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "size" => Ok(Self::Size),
+            "name" => Ok(Self::Name),
+            _ => Err(format!("unknown sort '{s}', expected one of: size, name")),
+        }
+    }
+}
+
+/// A byte count given as `512`, `300K`, `1.5G` or `2GiB`, in binary units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ByteSize(pub u64);
+
+impl FromStr for ByteSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        let split = text
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(text.len());
+        let (number, unit) = text.split_at(split);
+        let shift = match unit.trim().to_ascii_lowercase().as_str() {
+            "" | "b" => 0,
+            "k" | "kb" | "kib" => 10,
+            "m" | "mb" | "mib" => 20,
+            "g" | "gb" | "gib" => 30,
+            "t" | "tb" | "tib" => 40,
+            _ => {
+                return Err(format!(
+                    "unknown size unit in '{s}', expected B, K, M, G or T"
+                ));
+            }
+        };
+        let value = number
+            .parse::<f64>()
+            .map_err(|_| format!("invalid size '{s}', expected e.g. 500M or 2G"))?;
+        Ok(Self((value * (1_u64 << shift) as f64) as u64))
+    }
+}
+
+/// Reports the disk usage of every worktree and of the shared object store.
+#[derive(Debug, Default)]
+pub struct DiskUsageCommand {
+    sort: DiskUsageSort,
+    min_size: u64,
+    skip_ignored: bool,
+}
+
+impl DiskUsageCommand {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_sort(mut self, sort: DiskUsageSort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Hide worktrees smaller than `bytes`; the totals still include them.
+    pub fn with_min_size(mut self, bytes: u64) -> Self {
+        self.min_size = bytes;
+        self
+    }
+
+    /// Leave out files matched by the worktree's `.gitignore` rules, such as
+    /// `target/` or `node_modules/`.
+    pub fn with_skip_ignored(mut self, skip_ignored: bool) -> Self {
+        self.skip_ignored = skip_ignored;
+        self
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let report = self.collect(repo)?;
+        for line in self.render(&report) {
+            println!("{line}");
+        }
+        Ok(())
+    }
+
+    fn collect(&self, repo: &Repo) -> color_eyre::Result<DiskUsageReport> {
+        let worktrees_dir = repo.worktrees_dir();
+        let relatives = if worktrees_dir.is_dir() {
+            find_managed_worktrees(&worktrees_dir)?
+        } else {
+            Vec::new()
+        };
+
+        let mut worktrees = relatives
+            .into_iter()
+            .map(|relative| {
+                let path = worktrees_dir.join(&relative);
+                let bytes = if self.skip_ignored {
+                    unignored_disk_usage(&path)?
+                } else {
+                    disk_usage(&path)
+                };
+                Ok((format_worktree(&relative), bytes))
+            })
+            .collect::<color_eyre::Result<Vec<_>>>()?;
+        match self.sort {
+            DiskUsageSort::Size => {
+                worktrees.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then(a_name.cmp(b_name)))
+            }
+            DiskUsageSort::Name => worktrees.sort(),
+        }
+
+        Ok(DiskUsageReport {
+            objects: disk_usage(&repo.git().commondir().join("objects")),
+            worktrees,
+        })
+    }
+
+    fn render(&self, report: &DiskUsageReport) -> Vec<String> {
+        let mut table = Table::new().with_header(vec![tr!("SIZE"), tr!("WORKTREE")]);
+        for (name, bytes) in &report.worktrees {
+            if *bytes >= self.min_size {
+                table.push_row(vec![
+                    Cell::new(format_size(*bytes)),
+                    Cell::new(name.clone()),
+                ]);
+            }
+        }
+        table.push_row(vec![
+            Cell::new(format_size(report.objects)),
+            Cell::new(tr!("(shared object store)")).with_style(dimmed),
+        ]);
+        table.push_row(vec![
+            Cell::new(format_size(report.total())),
+            Cell::new(tr!("(total)")).with_style(dimmed),
+        ]);
+        table.render()
+    }
+}
+
+#[derive(Debug)]
+struct DiskUsageReport {
+    /// Worktree names and their size in bytes, in report order.
+    worktrees: Vec<(String, u64)>,
+    objects: u64,
+}
+
+impl DiskUsageReport {
+    fn total(&self) -> u64 {
+        self.objects + self.worktrees.iter().map(|(_, bytes)| bytes).sum::<u64>()
+    }
+}
+
+/// Like [`disk_usage`], without the files and directories git ignores.
+fn unignored_disk_usage(path: &Path) -> color_eyre::Result<u64> {
+    let git = Repository::open(path)
+        .wrap_err_with(|| eyre::eyre!("failed to open worktree at `{}`", path.display()))?;
+    let mut total = 0;
+    let mut pending = vec![path.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let entry_path = entry.path();
+            let Ok(metadata) = entry_path.symlink_metadata() else {
+                continue;
+            };
+            let ignored = entry_path
+                .strip_prefix(path)
+                .is_ok_and(|relative| git.is_path_ignored(relative).unwrap_or(false));
+            if ignored {
+                continue;
+            }
+            if metadata.is_dir() {
+                pending.push(entry_path);
+            } else {
+                total += metadata.len();
+            }
+        }
+    }
+
+    Ok(total)
+}
+
+fn dimmed(text: &str) -> String {
+    format!("{}", text.dimmed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::process::Command;
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn byte_size_accepts_binary_units() {
+        assert_eq!("512".parse(), Ok(ByteSize(512)));
+        assert_eq!("300K".parse(), Ok(ByteSize(300 * 1024)));
+        assert_eq!("1.5g".parse(), Ok(ByteSize(3 * 512 * 1024 * 1024)));
+        assert_eq!("2 MiB".parse(), Ok(ByteSize(2 * 1024 * 1024)));
+        assert!("10X".parse::<ByteSize>().is_err());
+        assert!("M".parse::<ByteSize>().is_err());
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> color_eyre::Result<()> {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()?;
+        assert!(status.success(), "git {args:?} failed");
+        Ok(())
+    }
+
+    #[test]
+    fn reports_worktrees_by_size_and_skips_ignored_files() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let root = dir.path();
+        git(root, &["init", "-b", "main"])?;
+        fs::write(root.join(".gitignore"), "target/\n")?;
+        git(root, &["add", "."])?;
+        git(root, &["commit", "-m", "init"])?;
+        for (name, size) in [("small", 10), ("large", 4096)] {
+            git(root, &["worktree", "add", &format!(".rsworktree/{name}")])?;
+            let path = root.join(".rsworktree").join(name);
+            fs::create_dir_all(path.join("target/debug"))?;
+            fs::write(path.join("target/debug/app"), vec![0; size])?;
+        }
+        let repo = Repo::discover_from(root)?;
+
+        let command = DiskUsageCommand::new();
+        let report = command.collect(&repo)?;
+        let names = report
+            .worktrees
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["large", "small"]);
+        assert!(report.worktrees[0].1 >= 4096);
+        assert!(report.objects > 0);
+
+        let lines = command.with_min_size(1024).render(&report);
+        assert!(lines.iter().any(|line| line.ends_with("large")));
+        assert!(!lines.iter().any(|line| line.ends_with("small")));
+        assert!(lines.iter().any(|line| line.contains("(total)")));
+
+        let report = DiskUsageCommand::new()
+            .with_skip_ignored(true)
+            .with_sort(DiskUsageSort::Name)
+            .collect(&repo)?;
+        assert_eq!(report.worktrees[0].0, "large");
+        assert!(report.worktrees.iter().all(|(_, bytes)| *bytes < 1024));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use std::ffi::OsString;
+
+use color_eyre::eyre;
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    Repo,
+    commands::open::resolve_by_name,
+    editor::{
+        CONFIG_FILE_NAME, EditorKind, EditorPreference, EditorPreferenceSource,
+        save_editor_preference,
+    },
+    output,
+};
+
+/// Stores the editor `worktree open` uses, for the repository or one worktree.
+#[derive(Debug)]
+pub struct EditorCommand {
+    worktree: Option<String>,
+    command: Vec<String>,
+    terminal: bool,
+}
+
+impl EditorCommand {
+    /// `command` is the editor program followed by its arguments.
+    pub fn new(worktree: Option<String>, command: Vec<String>) -> Self {
+        Self {
+            worktree,
+            command,
+            terminal: false,
+        }
+    }
+
+    /// Open a shell instead of an editor; `command`, which may then be
+    /// empty, names the terminal emulator to start it in.
+    pub fn with_terminal(mut self, terminal: bool) -> Self {
+        self.terminal = terminal;
+        self
+    }
+
+    pub fn set(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let (program, args) = match self.command.split_first() {
+            Some((program, args)) => (program.as_str(), args),
+            None if self.terminal => ("", &[][..]),
+            None => return Err(eyre::eyre!("an editor command is required")),
+        };
+        if program.trim().is_empty() && !self.terminal {
+            return Err(eyre::eyre!("the editor command must not be empty"));
+        }
+        let worktree = match &self.worktree {
+            Some(name) => Some(resolve_by_name(name, repo)?.name),
+            None => None,
+        };
+
+        let rsworktree_dir = repo.ensure_rsworktree_dir()?;
+        let preference = EditorPreference {
+            command: OsString::from(program),
+            args: args.iter().map(OsString::from).collect(),
+            kind: if self.terminal {
+                EditorKind::Terminal
+            } else {
+                EditorKind::Editor
+            },
+            source: EditorPreferenceSource::ConfigFile(rsworktree_dir.join(CONFIG_FILE_NAME)),
+            fallbacks: Vec::new(),
+        };
+        let path = save_editor_preference(&rsworktree_dir, worktree.as_deref(), &preference)?;
+
+        let command_label = format!(
+            "{}",
+            self.command
+                .join(" ")
+                .as_str()
+                .if_supports_color(Stream::Stdout, |text| format!("{}", text.green().bold()))
+        );
+        match worktree {
+            Some(worktree) => {
+                let worktree_label = format!(
+                    "{}",
+                    worktree
+                        .as_str()
+                        .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
+                );
+                if self.terminal {
+                    output::success(tr!("Worktree `{}` now opens a shell.", worktree_label));
+                } else {
+                    output::success(tr!(
+                        "Worktree `{}` now opens with `{}`.",
+                        worktree_label,
+                        command_label
+                    ));
+                }
+            }
+            None if self.terminal => output::success(tr!("Worktrees now open a shell.")),
+            None => output::success(tr!("Worktrees now open with `{}`.", command_label)),
+        }
+        if self.terminal && !program.is_empty() {
+            output::info(tr!(
+                "Outside tmux, Zellij, kitty, WezTerm and Windows Terminal the shell starts in `{}`.",
+                command_label
+            ));
+        }
+        output::info(tr!("Saved to `{}`.", path.display()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use crate::editor::{EditorPreferenceResolution, resolve_worktree_editor_preference};
+
+    #[test]
+    fn worktree_editor_overrides_repository_editor() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        git2::Repository::init(dir.path())?;
+        let repo = Repo::discover_from(dir.path())?;
+        let worktrees_dir = repo.ensure_worktrees_dir()?;
+        for worktree in ["backend/api", "frontend/web"] {
+            fs::create_dir_all(worktrees_dir.join(worktree))?;
+            fs::write(worktrees_dir.join(worktree).join(".git"), "")?;
+        }
+        fs::write(
+            repo.rsworktree_dir().join(CONFIG_FILE_NAME),
+            r#"{"provider": "gitlab", "editor": {"command": "code"}}"#,
+        )?;
+
+        EditorCommand::new(
+            Some("api".into()),
+            vec!["rustrover".into(), "--wait".into()],
+        )
+        .set(&repo)?;
+
+        let preferences = fs::read_to_string(repo.rsworktree_dir().join(CONFIG_FILE_NAME))?;
+        assert!(preferences.contains(r#""provider": "gitlab""#));
+
+        let command = |worktree| match resolve_worktree_editor_preference(&repo, worktree) {
+            Ok(EditorPreferenceResolution::Found(preference)) => {
+                (preference.command, preference.args)
+            }
+            other => panic!("expected an editor, got {other:?}"),
+        };
+        assert_eq!(
+            command("backend/api"),
+            (OsString::from("rustrover"), vec![OsString::from("--wait")])
+        );
+        assert_eq!(
+            command("frontend/web"),
+            (OsString::from("code"), Vec::new())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn terminal_preference_needs_no_command() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        git2::Repository::init(dir.path())?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        EditorCommand::new(None, Vec::new())
+            .with_terminal(true)
+            .set(&repo)?;
+        let preferences = fs::read_to_string(repo.rsworktree_dir().join(CONFIG_FILE_NAME))?;
+        assert!(
+            preferences.contains(r#""kind": "terminal""#),
+            "{preferences}"
+        );
+        match resolve_worktree_editor_preference(&repo, "any")? {
+            EditorPreferenceResolution::Found(preference) => {
+                assert_eq!(preference.kind, EditorKind::Terminal);
+                assert!(preference.command.is_empty());
+            }
+            other => panic!("expected a terminal preference, got {other:?}"),
+        }
+        assert!(EditorCommand::new(None, Vec::new()).set(&repo).is_err());
+
+        Ok(())
+    }
+}
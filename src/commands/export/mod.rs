@@ -0,0 +1,301 @@
+use std::{
+    env,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{Repo, commands::open::resolve_by_name, output};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// A git bundle containing the full history of the worktree branch.
+    #[default]
+    Bundle,
+    /// A gzipped tarball of the working tree, without ignored files.
+    Tar,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Bundle => "bundle",
+            ExportFormat::Tar => "tar.gz",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ExportCommand {
+    name: String,
+    format: ExportFormat,
+    output: Option<PathBuf>,
+}
+
+impl ExportCommand {
+    pub fn new(name: String, format: ExportFormat) -> Self {
+        Self {
+            name,
+            format,
+            output: None,
+        }
+    }
+
+    /// Write the export to `output` instead of `<name>.<ext>` in the current directory.
+    pub fn with_output(mut self, output: Option<PathBuf>) -> Self {
+        self.output = output;
+        self
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let resolved = resolve_by_name(&self.name, repo)?;
+        let destination = self.destination()?;
+
+        match self.format {
+            ExportFormat::Bundle => {
+                let branch = worktree_branch(&resolved.path)?;
+                write_bundle(&resolved.path, &branch, &destination)?;
+                output::success(tr!(
+                    "Exported branch `{}` as a git bundle to `{}`.",
+                    format_name(&branch),
+                    format_path(&destination)
+                ));
+                output::hint(tr!(
+                    "Recreate it elsewhere with `rsworktree create {} --from-bundle {}`.",
+                    branch,
+                    destination.display()
+                ));
+            }
+            ExportFormat::Tar => {
+                write_tarball(&resolved.path, &export_files(&resolved.path)?, &destination)?;
+                output::success(tr!(
+                    "Exported worktree `{}` as a tarball to `{}`.",
+                    format_name(&resolved.name),
+                    format_path(&destination)
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn destination(&self) -> color_eyre::Result<PathBuf> {
+        let path = match &self.output {
+            Some(path) => path.clone(),
+            None => {
+                let stem = self.name.replace(['/', '\\'], "-");
+                PathBuf::from(format!("{stem}.{}", self.format.extension()))
+            }
+        };
+        if path.is_absolute() {
+            return Ok(path);
+        }
+        let cwd = env::current_dir().wrap_err("failed to determine the current directory")?;
+        Ok(cwd.join(path))
+    }
+}
+
+pub(crate) fn worktree_branch(worktree_path: &Path) -> color_eyre::Result<String> {
+    let git_repo = git2::Repository::open(worktree_path).wrap_err_with(|| {
+        eyre::eyre!("failed to open worktree at `{}`", worktree_path.display())
+    })?;
+    let head = git_repo
+        .head()
+        .wrap_err_with(|| eyre::eyre!("failed to read HEAD of `{}`", worktree_path.display()))?;
+    match head.shorthand() {
+        Some(branch) if head.is_branch() => Ok(branch.to_owned()),
+        _ => Err(eyre::eyre!(
+            "worktree `{}` has a detached HEAD; check out a branch before exporting a bundle",
+            worktree_path.display()
+        )),
+    }
+}
+
+pub(crate) fn write_bundle(
+    worktree_path: &Path,
+    branch: &str,
+    destination: &Path,
+) -> color_eyre::Result<()> {
+    let output = Command::new("git")
+        .arg("bundle")
+        .arg("create")
+        .arg("--quiet")
+        .arg(destination)
+        .arg(format!("refs/heads/{branch}"))
+        .current_dir(worktree_path)
+        .output()
+        .wrap_err("failed to run `git bundle create`")?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(eyre::eyre!(
+        "`git bundle create` failed: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+/// Packs `files`, relative to `worktree_path`, into a gzipped tarball.
+pub(crate) fn write_tarball(
+    worktree_path: &Path,
+    files: &[String],
+    destination: &Path,
+) -> color_eyre::Result<()> {
+    let mut child = Command::new("tar")
+        .arg("-czf")
+        .arg(destination)
+        .arg("-C")
+        .arg(worktree_path)
+        .args(["--null", "-T", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .wrap_err("failed to run `tar`")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        for file in files {
+            stdin
+                .write_all(file.as_bytes())
+                .and_then(|_| stdin.write_all(b"\0"))
+                .wrap_err("failed to pass the file list to `tar`")?;
+        }
+    }
+    let output = child
+        .wait_with_output()
+        .wrap_err("failed to wait for `tar`")?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(eyre::eyre!(
+        "`tar` failed: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+/// Tracked and untracked files of the worktree, honouring `.gitignore`.
+fn export_files(worktree_path: &Path) -> color_eyre::Result<Vec<String>> {
+    let output = Command::new("git")
+        .args([
+            "ls-files",
+            "-z",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+        ])
+        .current_dir(worktree_path)
+        .output()
+        .wrap_err("failed to run `git ls-files`")?;
+    if !output.status.success() {
+        return Err(eyre::eyre!(
+            "`git ls-files` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let mut files: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|file| !file.is_empty())
+        // Tracked files deleted in the working tree are still listed by `--cached`.
+        .filter(|file| worktree_path.join(file).symlink_metadata().is_ok())
+        .map(String::from)
+        .collect();
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn format_name(name: &str) -> String {
+    format!(
+        "{}",
+        name.if_supports_color(Stream::Stdout, |text| {
+            format!("{}", text.green().bold())
+        })
+    )
+}
+
+fn format_path(path: &Path) -> String {
+    let raw = path.display().to_string();
+    format!(
+        "{}",
+        raw.as_str()
+            .if_supports_color(Stream::Stdout, |text| format!("{}", text.blue()))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use crate::commands::create::CreateCommand;
+
+    fn run(dir: &Path, args: &[&str]) -> color_eyre::Result<String> {
+        let output = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()?;
+        if !output.status.success() {
+            return Err(eyre::eyre!(
+                "git {:?} failed: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn init_repo(dir: &Path) -> color_eyre::Result<Repo> {
+        run(dir, &["init"])?;
+        fs::write(dir.join("README.md"), "test")?;
+        run(dir, &["add", "README.md"])?;
+        run(dir, &["commit", "-m", "Initial commit"])?;
+        Repo::discover_from(dir)
+    }
+
+    #[test]
+    fn exports_branch_history_as_bundle() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let repo = init_repo(dir.path())?;
+        CreateCommand::new("feature/export".into(), None).create_without_enter(&repo, true)?;
+        let worktree_path = repo.worktrees_dir().join("feature/export");
+        fs::write(worktree_path.join("change.txt"), "change")?;
+        run(&worktree_path, &["add", "change.txt"])?;
+        run(&worktree_path, &["commit", "-m", "Add change"])?;
+
+        let destination = dir.path().join("export.bundle");
+        ExportCommand::new("feature/export".into(), ExportFormat::Bundle)
+            .with_output(Some(destination.clone()))
+            .execute(&repo)?;
+
+        let heads = run(
+            dir.path(),
+            &["bundle", "list-heads", destination.to_str().unwrap()],
+        )?;
+        assert!(heads.contains("refs/heads/feature/export"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn tarball_skips_ignored_files() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let repo = init_repo(dir.path())?;
+        CreateCommand::new("feature/tar".into(), None).create_without_enter(&repo, true)?;
+        let worktree_path = repo.worktrees_dir().join("feature/tar");
+        fs::write(worktree_path.join(".gitignore"), "build/\n")?;
+        fs::create_dir_all(worktree_path.join("build"))?;
+        fs::write(worktree_path.join("build/output.bin"), "binary")?;
+        fs::write(worktree_path.join("notes.txt"), "untracked")?;
+
+        let files = export_files(&worktree_path)?;
+
+        assert_eq!(files, vec![".gitignore", "README.md", "notes.txt"]);
+
+        Ok(())
+    }
+}
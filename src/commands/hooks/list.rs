@@ -0,0 +1,64 @@
+//! `hooks list`: which hooks a repository has and what each of them runs.
+
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    Repo,
+    hooks::{HookName, HookRunner, is_executable},
+    output,
+};
+
+#[derive(Debug, Default)]
+pub struct HookListCommand;
+
+impl HookListCommand {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let runner = HookRunner::new(&repo.ensure_rsworktree_dir()?);
+        output::heading(tr!("Hooks in `{}`:", runner.hooks_dir().display()));
+        for hook in HookName::ALL {
+            let name = format!(
+                "{}",
+                hook.as_str()
+                    .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
+            );
+            let mut parts = Vec::new();
+            match runner.script_path(hook) {
+                Some(script) if is_executable(&script) => {
+                    parts.push(tr!("script `{}`", script.display()));
+                }
+                Some(script) => {
+                    parts.push(tr!(
+                        "script `{}` (not executable, run `chmod +x` on it)",
+                        script.display()
+                    ));
+                }
+                None => {}
+            }
+            let scripts = runner.directory_scripts(hook).len();
+            if scripts > 0 {
+                parts.push(tr!(
+                    "{} script(s) in `{}`",
+                    scripts,
+                    format!("{}.d", hook.as_str())
+                ));
+            }
+            let commands = runner.inline_commands(hook).len();
+            if commands > 0 {
+                parts.push(tr!("{} command(s) from `preferences.json`", commands));
+            }
+            let tasks = runner.tasks(hook).len();
+            if tasks > 0 {
+                parts.push(tr!("{} task(s)", tasks));
+            }
+            if parts.is_empty() {
+                parts.push(tr!("none"));
+            }
+            output::item(format!("{name}: {}", parts.join(", ")));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+//! `hooks logs`: browse the hook output captured with `hooks.captureOutput`.
+
+use std::fs;
+
+use color_eyre::eyre::{self, WrapErr};
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{Repo, hooks::HookRunner, output};
+
+#[derive(Debug)]
+pub struct HookLogsCommand {
+    name: Option<String>,
+    path: bool,
+}
+
+impl HookLogsCommand {
+    /// Lists the logs, or prints the most recent one whose name starts with `name`.
+    pub fn new(name: Option<String>) -> Self {
+        Self { name, path: false }
+    }
+
+    /// Print the path of the selected log instead of its contents.
+    pub fn with_path(mut self, path: bool) -> Self {
+        self.path = path;
+        self
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let runner = HookRunner::new(&repo.ensure_rsworktree_dir()?);
+        let logs = runner.logs()?;
+
+        let Some(name) = &self.name else {
+            if logs.is_empty() {
+                output::info(tr!(
+                    "No hook logs in `{}`; set `hooks.captureOutput` to `true` in `preferences.json` to write them.",
+                    runner.logs_dir().display()
+                ));
+                return Ok(());
+            }
+            output::heading(tr!("Hook logs in `{}`:", runner.logs_dir().display()));
+            for log in &logs {
+                let (header, footer) = log.summary();
+                let name = log
+                    .name
+                    .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()));
+                output::item(format!(
+                    "{name}: {} — {}",
+                    header.unwrap_or_default(),
+                    footer.unwrap_or_else(|| tr!("unfinished"))
+                ));
+            }
+            return Ok(());
+        };
+
+        let Some(log) = logs.iter().find(|log| log.name.starts_with(name.as_str())) else {
+            return Err(eyre::eyre!(
+                "no hook log matches `{name}`; run `rsworktree hooks logs` to list them"
+            ));
+        };
+        if self.path {
+            println!("{}", log.path.display());
+            return Ok(());
+        }
+        let contents = fs::read(&log.path)
+            .wrap_err_with(|| eyre::eyre!("failed to read `{}`", log.path.display()))?;
+        print!("{}", String::from_utf8_lossy(&contents));
+        Ok(())
+    }
+}
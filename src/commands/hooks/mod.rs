@@ -0,0 +1,551 @@
+//! `rsworktree hooks install|update`: shared hook sets fetched from a path or git URL.
+//! `hooks run`, `hooks list` and `hooks logs` live in their own modules.
+//!
+//! A hook source contains a `rsworktree-hooks.json` manifest listing each hook
+//! with the git blob id (`git hash-object <file>`) of its script:
+//!
+//! ```json
+//! {
+//!   "version": "1.2.0",
+//!   "mode": "fail-fast",
+//!   "hooks": { "post-create": { "path": "hooks/post-create", "checksum": "3b18e5…" } }
+//! }
+//! ```
+
+mod list;
+mod logs;
+mod run;
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Component, Path, PathBuf},
+    process::Command,
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use git2::{ObjectType, Oid};
+use owo_colors::{OwoColorize, Stream};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    Repo,
+    editor::CONFIG_FILE_NAME,
+    hooks::{HookFailureMode, HookRunner},
+    output,
+};
+
+pub use list::HookListCommand;
+pub use logs::HookLogsCommand;
+pub use run::HookRunCommand;
+
+/// Manifest file expected at the root of a hook source.
+pub const MANIFEST_FILE: &str = "rsworktree-hooks.json";
+
+const STATE_DIR: &str = "state";
+const STATE_FILE: &str = "hooks-source.json";
+const CHECKOUT_DIR: &str = "hooks-source.checkout";
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    version: String,
+    /// Failure mode written to `hooks.mode` in `preferences.json`.
+    #[serde(default)]
+    mode: Option<HookFailureMode>,
+    hooks: BTreeMap<String, ManifestHook>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestHook {
+    path: PathBuf,
+    checksum: String,
+}
+
+/// What was installed last, so `update` can refetch it and spot local edits.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct InstallState {
+    source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reference: Option<String>,
+    version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+    /// Installed hook names and their checksums.
+    hooks: BTreeMap<String, String>,
+}
+
+#[derive(Debug)]
+pub struct HooksCommand {
+    force: bool,
+}
+
+impl HooksCommand {
+    /// With `force`, hooks that were added or edited locally are overwritten.
+    pub fn new(force: bool) -> Self {
+        Self { force }
+    }
+
+    pub fn install(
+        &self,
+        repo: &Repo,
+        source: &str,
+        reference: Option<String>,
+    ) -> color_eyre::Result<()> {
+        let rsworktree_dir = repo.ensure_rsworktree_dir()?;
+        let previous = load_state(&rsworktree_dir)?;
+        let state = self.install_from(&rsworktree_dir, source, reference, previous.as_ref())?;
+        output::success(tr!(
+            "Installed {} hook(s) from `{}` (version {}).",
+            state.hooks.len(),
+            state.source,
+            format_version(&state.version)
+        ));
+        for name in state.hooks.keys() {
+            output::item(name);
+        }
+        Ok(())
+    }
+
+    pub fn update(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let rsworktree_dir = repo.ensure_rsworktree_dir()?;
+        let Some(previous) = load_state(&rsworktree_dir)? else {
+            return Err(eyre::eyre!(
+                "no hook source installed; run `rsworktree hooks install <git-url|path>` first"
+            ));
+        };
+
+        let state = self.install_from(
+            &rsworktree_dir,
+            &previous.source,
+            previous.reference.clone(),
+            Some(&previous),
+        )?;
+        if state.version == previous.version && state.commit == previous.commit {
+            output::info(tr!(
+                "Hooks from `{}` are up to date (version {}).",
+                state.source,
+                format_version(&state.version)
+            ));
+        } else {
+            output::success(tr!(
+                "Updated hooks from `{}` to version {} (was {}).",
+                state.source,
+                format_version(&state.version),
+                previous.version
+            ));
+        }
+        Ok(())
+    }
+
+    fn install_from(
+        &self,
+        rsworktree_dir: &Path,
+        source: &str,
+        reference: Option<String>,
+        previous: Option<&InstallState>,
+    ) -> color_eyre::Result<InstallState> {
+        let checkout = fetch_source(rsworktree_dir, source, reference.as_deref())?;
+        let manifest = read_manifest(&checkout.dir)?;
+        let scripts = verify(&checkout.dir, &manifest)?;
+
+        let hooks_dir = HookRunner::new(rsworktree_dir).hooks_dir();
+        let installed = previous.map(|state| &state.hooks);
+        for (name, (_, checksum)) in &scripts {
+            let target = hooks_dir.join(name);
+            if !self.force && !is_managed(&target, installed.and_then(|hooks| hooks.get(name))) {
+                let existing = checksum_of(&target)?;
+                if existing.as_deref() != Some(checksum.as_str()) {
+                    return Err(eyre::eyre!(
+                        "hook `{name}` was added or changed locally; pass `--force` to overwrite it"
+                    ));
+                }
+            }
+        }
+
+        fs::create_dir_all(&hooks_dir)
+            .wrap_err_with(|| eyre::eyre!("failed to create `{}`", hooks_dir.display()))?;
+        for (name, (script, _)) in &scripts {
+            let target = hooks_dir.join(name);
+            fs::copy(script, &target).wrap_err_with(|| {
+                eyre::eyre!("failed to install hook `{name}` to `{}`", target.display())
+            })?;
+            make_executable(&target)?;
+        }
+
+        // Hooks dropped from the manifest are removed unless they were edited locally.
+        for (name, checksum) in installed.into_iter().flatten() {
+            let target = hooks_dir.join(name);
+            if !scripts.contains_key(name) && is_managed(&target, Some(checksum)) {
+                fs::remove_file(&target)
+                    .wrap_err_with(|| eyre::eyre!("failed to remove `{}`", target.display()))?;
+            }
+        }
+
+        if let Some(mode) = manifest.mode {
+            write_failure_mode(rsworktree_dir, mode)?;
+        }
+
+        let state = InstallState {
+            source: source.to_owned(),
+            reference,
+            version: manifest.version,
+            commit: checkout.commit.clone(),
+            hooks: scripts
+                .into_iter()
+                .map(|(name, (_, checksum))| (name, checksum))
+                .collect(),
+        };
+        save_state(rsworktree_dir, &state)?;
+        Ok(state)
+    }
+}
+
+/// A fetched hook source; git checkouts are removed again when dropped.
+struct Checkout {
+    dir: PathBuf,
+    commit: Option<String>,
+    temporary: bool,
+}
+
+impl Drop for Checkout {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+fn fetch_source(
+    rsworktree_dir: &Path,
+    source: &str,
+    reference: Option<&str>,
+) -> color_eyre::Result<Checkout> {
+    let local = Path::new(source);
+    if local.is_dir() {
+        if reference.is_some() {
+            return Err(eyre::eyre!(
+                "`--ref` only applies to git URLs; `{source}` is a local directory"
+            ));
+        }
+        return Ok(Checkout {
+            dir: local.to_path_buf(),
+            commit: None,
+            temporary: false,
+        });
+    }
+
+    let dir = rsworktree_dir.join(STATE_DIR).join(CHECKOUT_DIR);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)
+            .wrap_err_with(|| eyre::eyre!("failed to clear `{}`", dir.display()))?;
+    }
+    let mut checkout = Checkout {
+        dir,
+        commit: None,
+        temporary: true,
+    };
+
+    let mut clone = Command::new("git");
+    clone.args(["clone", "--quiet", "--depth", "1"]);
+    if let Some(reference) = reference {
+        clone.args(["--branch", reference]);
+    }
+    let output = clone
+        .arg(source)
+        .arg(&checkout.dir)
+        .output()
+        .wrap_err("failed to run `git clone`")?;
+    if !output.status.success() {
+        return Err(eyre::eyre!(
+            "failed to fetch hooks from `{source}`: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    checkout.commit = git2::Repository::open(&checkout.dir)
+        .ok()
+        .and_then(|repo| repo.head().ok()?.target())
+        .map(|oid| oid.to_string());
+    Ok(checkout)
+}
+
+fn read_manifest(dir: &Path) -> color_eyre::Result<Manifest> {
+    let path = dir.join(MANIFEST_FILE);
+    let contents = fs::read_to_string(&path).wrap_err_with(|| {
+        eyre::eyre!(
+            "hook source has no `{MANIFEST_FILE}` (looked in `{}`)",
+            dir.display()
+        )
+    })?;
+    serde_json::from_str(&contents)
+        .wrap_err_with(|| eyre::eyre!("failed to parse `{}`", path.display()))
+}
+
+/// Checks every script against its manifest checksum before anything is installed.
+fn verify(
+    dir: &Path,
+    manifest: &Manifest,
+) -> color_eyre::Result<BTreeMap<String, (PathBuf, String)>> {
+    let mut scripts = BTreeMap::new();
+    for (name, hook) in &manifest.hooks {
+        if !is_plain_file_name(Path::new(name)) {
+            return Err(eyre::eyre!(
+                "invalid hook name `{name}` in `{MANIFEST_FILE}`"
+            ));
+        }
+        if !hook
+            .path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(eyre::eyre!(
+                "hook `{name}` points outside the hook source: `{}`",
+                hook.path.display()
+            ));
+        }
+
+        let script = dir.join(&hook.path);
+        let actual = checksum_of(&script)?.ok_or_else(|| {
+            eyre::eyre!(
+                "hook `{name}` is missing from the source: `{}`",
+                hook.path.display()
+            )
+        })?;
+        let expected = hook.checksum.trim().to_ascii_lowercase();
+        if actual != expected {
+            return Err(eyre::eyre!(
+                "checksum mismatch for hook `{name}`: expected {expected}, found {actual}"
+            ));
+        }
+        scripts.insert(name.clone(), (script, actual));
+    }
+    Ok(scripts)
+}
+
+fn is_plain_file_name(path: &Path) -> bool {
+    let mut components = path.components();
+    matches!(components.next(), Some(Component::Normal(_))) && components.next().is_none()
+}
+
+/// Git blob id of `path`, the same value `git hash-object` prints.
+fn checksum_of(path: &Path) -> color_eyre::Result<Option<String>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    Oid::hash_file(ObjectType::Blob, path)
+        .map(|oid| Some(oid.to_string()))
+        .wrap_err_with(|| eyre::eyre!("failed to hash `{}`", path.display()))
+}
+
+/// Whether `target` is missing or still matches what the last install wrote.
+fn is_managed(target: &Path, installed_checksum: Option<&String>) -> bool {
+    match checksum_of(target) {
+        Ok(None) => true,
+        Ok(Some(current)) => installed_checksum == Some(&current),
+        Err(_) => false,
+    }
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> color_eyre::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)
+        .wrap_err_with(|| eyre::eyre!("failed to read `{}`", path.display()))?
+        .permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(path, permissions)
+        .wrap_err_with(|| eyre::eyre!("failed to make `{}` executable", path.display()))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> color_eyre::Result<()> {
+    Ok(())
+}
+
+fn write_failure_mode(rsworktree_dir: &Path, mode: HookFailureMode) -> color_eyre::Result<()> {
+    let path = rsworktree_dir.join(CONFIG_FILE_NAME);
+    let mut preferences = match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str::<Value>(&contents)
+            .wrap_err_with(|| eyre::eyre!("failed to parse `{}`", path.display()))?,
+        Err(_) => Value::Object(Default::default()),
+    };
+    let Some(root) = preferences.as_object_mut() else {
+        return Err(eyre::eyre!("`{}` is not a JSON object", path.display()));
+    };
+    let hooks = root
+        .entry("hooks")
+        .or_insert_with(|| Value::Object(Default::default()));
+    let Some(hooks) = hooks.as_object_mut() else {
+        return Err(eyre::eyre!(
+            "`hooks` in `{}` is not a JSON object",
+            path.display()
+        ));
+    };
+    hooks.insert("mode".into(), Value::String(mode.as_str().into()));
+
+    let contents =
+        serde_json::to_string_pretty(&preferences).wrap_err("failed to serialize preferences")?;
+    fs::write(&path, contents + "\n")
+        .wrap_err_with(|| eyre::eyre!("failed to write `{}`", path.display()))
+}
+
+fn state_path(rsworktree_dir: &Path) -> PathBuf {
+    rsworktree_dir.join(STATE_DIR).join(STATE_FILE)
+}
+
+fn load_state(rsworktree_dir: &Path) -> color_eyre::Result<Option<InstallState>> {
+    let path = state_path(rsworktree_dir);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(&path)
+        .wrap_err_with(|| eyre::eyre!("failed to read `{}`", path.display()))?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .wrap_err_with(|| eyre::eyre!("failed to parse `{}`", path.display()))
+}
+
+fn save_state(rsworktree_dir: &Path, state: &InstallState) -> color_eyre::Result<()> {
+    let path = state_path(rsworktree_dir);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .wrap_err_with(|| eyre::eyre!("failed to create `{}`", parent.display()))?;
+    }
+    let contents =
+        serde_json::to_string_pretty(state).wrap_err("failed to serialize hook state")?;
+    fs::write(&path, contents).wrap_err_with(|| eyre::eyre!("failed to write `{}`", path.display()))
+}
+
+fn format_version(version: &str) -> String {
+    format!(
+        "{}",
+        version.if_supports_color(Stream::Stdout, |text| format!("{}", text.green()))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    fn init_repo(dir: &Path) -> color_eyre::Result<Repo> {
+        let status = Command::new("git")
+            .args(["init", "--quiet"])
+            .current_dir(dir)
+            .status()?;
+        assert!(status.success());
+        Repo::discover_from(dir)
+    }
+
+    fn write_source(dir: &Path, version: &str, script: &str) -> color_eyre::Result<()> {
+        fs::create_dir_all(dir.join("hooks"))?;
+        let script_path = dir.join("hooks/post-create");
+        fs::write(&script_path, script)?;
+        let checksum = Oid::hash_file(ObjectType::Blob, &script_path)?;
+        fs::write(
+            dir.join(MANIFEST_FILE),
+            format!(
+                r#"{{ "version": "{version}", "hooks": {{ "post-create": {{ "path": "hooks/post-create", "checksum": "{checksum}" }} }} }}"#
+            ),
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn install_copies_verified_hooks_and_records_state() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        let repo = init_repo(repo_dir.path())?;
+        let source = TempDir::new()?;
+        write_source(source.path(), "1.0.0", "#!/bin/sh\necho setup\n")?;
+
+        HooksCommand::new(false).install(&repo, source.path().to_str().unwrap(), None)?;
+
+        let hook = repo.rsworktree_dir().join("hooks/post-create");
+        assert_eq!(fs::read_to_string(&hook)?, "#!/bin/sh\necho setup\n");
+        let state = load_state(&repo.rsworktree_dir())?.expect("state should be recorded");
+        assert_eq!(state.version, "1.0.0");
+        assert!(state.hooks.contains_key("post-create"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn install_rejects_checksum_mismatch() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        let repo = init_repo(repo_dir.path())?;
+        let source = TempDir::new()?;
+        write_source(source.path(), "1.0.0", "#!/bin/sh\necho setup\n")?;
+        fs::write(
+            source.path().join("hooks/post-create"),
+            "#!/bin/sh\nrm -rf ~\n",
+        )?;
+
+        let error = HooksCommand::new(false)
+            .install(&repo, source.path().to_str().unwrap(), None)
+            .expect_err("tampered hook should be rejected");
+
+        assert!(error.to_string().contains("checksum mismatch"));
+        assert!(!repo.rsworktree_dir().join("hooks/post-create").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn update_installs_new_version_but_keeps_local_edits_without_force() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        let repo = init_repo(repo_dir.path())?;
+        let source = TempDir::new()?;
+        let source_path = source.path().to_str().unwrap();
+        write_source(source.path(), "1.0.0", "#!/bin/sh\necho v1\n")?;
+        HooksCommand::new(false).install(&repo, source_path, None)?;
+
+        write_source(source.path(), "1.1.0", "#!/bin/sh\necho v2\n")?;
+        HooksCommand::new(false).update(&repo)?;
+        let hook = repo.rsworktree_dir().join("hooks/post-create");
+        assert_eq!(fs::read_to_string(&hook)?, "#!/bin/sh\necho v2\n");
+
+        fs::write(&hook, "#!/bin/sh\necho local tweak\n")?;
+        write_source(source.path(), "1.2.0", "#!/bin/sh\necho v3\n")?;
+        let error = HooksCommand::new(false)
+            .update(&repo)
+            .expect_err("local edits should be protected");
+        assert!(error.to_string().contains("--force"));
+
+        HooksCommand::new(true).update(&repo)?;
+        assert_eq!(fs::read_to_string(&hook)?, "#!/bin/sh\necho v3\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn install_writes_failure_mode_from_manifest() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        let repo = init_repo(repo_dir.path())?;
+        let rsworktree_dir = repo.ensure_rsworktree_dir()?;
+        fs::write(
+            rsworktree_dir.join(CONFIG_FILE_NAME),
+            r#"{ "provider": "gitlab" }"#,
+        )?;
+        let source = TempDir::new()?;
+        write_source(source.path(), "1.0.0", "#!/bin/sh\n")?;
+        let manifest = fs::read_to_string(source.path().join(MANIFEST_FILE))?.replacen(
+            '{',
+            r#"{ "mode": "fail-fast","#,
+            1,
+        );
+        fs::write(source.path().join(MANIFEST_FILE), manifest)?;
+
+        HooksCommand::new(false).install(&repo, source.path().to_str().unwrap(), None)?;
+
+        assert_eq!(
+            HookRunner::new(&rsworktree_dir).mode(),
+            HookFailureMode::FailFast
+        );
+        let preferences = fs::read_to_string(rsworktree_dir.join(CONFIG_FILE_NAME))?;
+        assert!(preferences.contains("\"provider\": \"gitlab\""));
+
+        Ok(())
+    }
+}
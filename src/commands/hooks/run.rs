@@ -0,0 +1,167 @@
+//! `hooks run`: run a hook for an existing worktree outside of `create` and
+//! `rm`, or with `--dry-run` show what it would run, to iterate on hook
+//! scripts without creating throwaway worktrees.
+
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    Repo,
+    commands::open::resolve_by_name,
+    hooks::{HookContext, HookFailureMode, HookName, HookRunner, is_executable},
+    output,
+};
+
+#[derive(Debug)]
+pub struct HookRunCommand {
+    hook: HookName,
+    worktree: String,
+    dry_run: bool,
+}
+
+impl HookRunCommand {
+    /// Runs `hook` for `worktree`, with the environment `create` and `rm` give it.
+    pub fn new(hook: HookName, worktree: String) -> Self {
+        Self {
+            hook,
+            worktree,
+            dry_run: false,
+        }
+    }
+
+    /// Only print the script, commands, tasks and variables the hook would run with.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let resolved = resolve_by_name(&self.worktree, repo)?;
+        let worktrees_dir = repo.worktrees_dir();
+        let context =
+            HookContext::for_worktree(repo.git(), &worktrees_dir, &resolved.name, &resolved.path);
+        // A failing hook fails the command, whatever `hooks.mode` says.
+        let runner = HookRunner::new(&repo.rsworktree_dir())
+            .with_repository(&repo.name())
+            .with_mode(HookFailureMode::FailFast);
+
+        if self.dry_run {
+            print_plan(&runner, self.hook, &context);
+            return Ok(());
+        }
+        if !runner.is_configured(self.hook) {
+            output::info(tr!("No {} hook is configured.", self.hook));
+            return Ok(());
+        }
+        if self.hook == HookName::PreCreate {
+            if let Some(name) = runner.run_pre_create(&context)? {
+                output::info(tr!(
+                    "The pre-create hook would rename `{}` to `{}`.",
+                    context.worktree_name,
+                    name
+                ));
+            }
+            return Ok(());
+        }
+        runner.run_hook(self.hook, &context)
+    }
+}
+
+fn print_plan(runner: &HookRunner, hook: HookName, context: &HookContext) {
+    if !runner.is_configured(hook) {
+        output::info(tr!("No {} hook is configured.", hook));
+        return;
+    }
+
+    output::heading(tr!(
+        "The {} hook for `{}` would run in `{}`:",
+        hook,
+        context.worktree_name,
+        context.worktree_path.display()
+    ));
+    if let Some(script) = runner.script_path(hook) {
+        if is_executable(&script) {
+            output::item(tr!("script `{}`", script.display()));
+        } else {
+            output::item(tr!(
+                "script `{}`, which is not executable and would be skipped",
+                script.display()
+            ));
+        }
+    }
+    for script in runner.directory_scripts(hook) {
+        output::item(tr!("script `{}`", script.display()));
+    }
+    for command in runner.inline_commands(hook) {
+        output::item(tr!("command `{}`", command));
+    }
+    for (name, command) in runner.tasks(hook) {
+        output::item(tr!("task {}: `{}`", name, command));
+    }
+
+    output::heading(tr!("With the environment:"));
+    for (key, value) in runner.env(context) {
+        let key = key.if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()));
+        output::item(format!("{key}={}", value.to_string_lossy()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{fs, process::Command};
+
+    use tempfile::TempDir;
+
+    use crate::commands::create::CreateCommand;
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_a_hook_for_an_existing_worktree() -> color_eyre::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new()?;
+        Command::new("git")
+            .args(["init", "--quiet"])
+            .current_dir(dir.path())
+            .status()?;
+        Command::new("git")
+            .args([
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--quiet",
+                "--allow-empty",
+                "-m",
+                "Initial",
+            ])
+            .current_dir(dir.path())
+            .status()?;
+        let repo = Repo::discover_from(dir.path())?;
+        CreateCommand::new("feature".into(), None).create_without_enter(&repo, true)?;
+
+        let hooks_dir = repo.rsworktree_dir().join("hooks");
+        fs::create_dir_all(&hooks_dir)?;
+        let hook = hooks_dir.join("post-create");
+        fs::write(
+            &hook,
+            "#!/bin/sh\necho \"$RSWORKTREE_NAME\" > \"$RSWORKTREE_PATH/ran\"\nexit 3\n",
+        )?;
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755))?;
+
+        HookRunCommand::new(HookName::PostCreate, "feature".into())
+            .with_dry_run(true)
+            .execute(&repo)?;
+        let marker = repo.worktrees_dir().join("feature/ran");
+        assert!(!marker.exists());
+
+        let error = HookRunCommand::new(HookName::PostCreate, "feature".into())
+            .execute(&repo)
+            .expect_err("the hook fails");
+        assert!(error.to_string().contains("exited with code 3"));
+        assert_eq!(fs::read_to_string(marker)?, "feature\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,560 @@
+mod pull_requests;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    GitProvider, Repo,
+    annotations::Annotations,
+    commands::{
+        notes::describe_created,
+        open::{multiplexer::window_name, resolve_by_name},
+    },
+    hooks::{HookRunRecord, HookRunner},
+    output,
+    process::{CommandRunner, SystemCommandRunner},
+    provider::{MergeRequestStatus, ProviderBackend, ProviderClient, RequestBackend},
+    worktree::{Worktree, WorktreeStatus},
+};
+
+pub(crate) use self::pull_requests::{CACHE_DIR, PullRequestCache, lookup_pull_requests};
+
+const RECENT_HOOK_RUNS: usize = 3;
+
+#[derive(Debug)]
+pub struct InfoCommand<R = SystemCommandRunner> {
+    name: String,
+    provider: GitProvider,
+    client: ProviderClient,
+    runner: R,
+}
+
+impl InfoCommand {
+    /// Runs the provider operations through the backend configured for `repo`.
+    pub fn new(name: String, provider: GitProvider, repo: &Repo) -> Self {
+        Self::with_runner(name, provider, SystemCommandRunner)
+            .with_backend(ProviderBackend::resolve(repo))
+    }
+}
+
+impl<R> InfoCommand<R>
+where
+    R: CommandRunner,
+{
+    pub fn with_runner(name: String, provider: GitProvider, runner: R) -> Self {
+        Self {
+            name,
+            provider,
+            client: provider.client(ProviderBackend::Cli),
+            runner,
+        }
+    }
+
+    /// Runs the provider operations through `backend` rather than the CLI.
+    pub fn with_backend(mut self, backend: ProviderBackend) -> Self {
+        self.client = self.provider.client(backend);
+        self
+    }
+
+    pub fn execute(&mut self, repo: &Repo) -> color_eyre::Result<()> {
+        let report = self.collect(repo)?;
+        print_report(&report, self.provider);
+        Ok(())
+    }
+
+    fn collect(&mut self, repo: &Repo) -> color_eyre::Result<WorktreeReport> {
+        let resolved = resolve_by_name(&self.name, repo)?;
+        let Worktree {
+            name,
+            path,
+            base,
+            status,
+            managed,
+        } = Worktree::load(repo, resolved.name, resolved.path)?;
+
+        let pull_request = match status.branch.as_deref() {
+            Some(branch) => lookup_pull_request(&mut self.runner, &mut self.client, &path, branch),
+            None => PullRequestLookup::Unavailable("worktree HEAD is detached".into()),
+        };
+
+        let hook_runs = HookRunner::new(&repo.rsworktree_dir())
+            .recent_runs(&name)
+            .unwrap_or_default()
+            .into_iter()
+            .take(RECENT_HOOK_RUNS)
+            .collect();
+
+        let session_name = window_name(repo, &name, &path);
+        let tmux = self.lookup_tmux_session(&path, session_name);
+
+        let annotations = status
+            .branch
+            .as_deref()
+            .filter(|_| managed)
+            .and_then(|branch| Annotations::load(repo, &name, branch).ok())
+            .unwrap_or_default();
+
+        Ok(WorktreeReport {
+            name,
+            disk_usage: disk_usage(&path),
+            path,
+            status,
+            base,
+            pull_request,
+            hook_runs,
+            tmux,
+            annotations,
+        })
+    }
+
+    fn lookup_tmux_session(&mut self, worktree_path: &Path, session_name: String) -> TmuxLookup {
+        let args = vec![
+            "list-sessions".to_owned(),
+            "-F".to_owned(),
+            "#{session_name}".to_owned(),
+        ];
+        match self.runner.run("tmux", worktree_path, &args) {
+            Ok(output) if output.success => {
+                if output.stdout.lines().any(|line| line.trim() == session_name) {
+                    TmuxLookup::Session(session_name)
+                } else {
+                    TmuxLookup::NoSession(session_name)
+                }
+            }
+            _ => TmuxLookup::Unavailable,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct WorktreeReport {
+    name: String,
+    path: PathBuf,
+    status: WorktreeStatus,
+    base: Option<String>,
+    pull_request: PullRequestLookup,
+    hook_runs: Vec<HookRunRecord>,
+    disk_usage: u64,
+    tmux: TmuxLookup,
+    /// Note and metadata recorded for the worktree; empty when detached or unmanaged.
+    annotations: Annotations,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PullRequestLookup {
+    Found(MergeRequestStatus),
+    None,
+    Unavailable(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum TmuxLookup {
+    Session(String),
+    NoSession(String),
+    Unavailable,
+}
+
+/// Total size in bytes of the files below `path`, without following symlinks.
+pub(crate) fn disk_usage(path: &Path) -> u64 {
+    let mut total = 0;
+    let mut pending = vec![path.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                total += metadata.len();
+            }
+        }
+    }
+
+    total
+}
+
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+pub(crate) fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..60 => "just now".to_owned(),
+        60..3_600 => format!("{}m ago", seconds / 60),
+        3_600..86_400 => format!("{}h ago", seconds / 3_600),
+        _ => format!("{}d ago", seconds / 86_400),
+    }
+}
+
+/// Looks up the open pull/merge request for `branch` through `client`.
+pub(crate) fn lookup_pull_request<R: CommandRunner>(
+    runner: &mut R,
+    client: &mut ProviderClient,
+    worktree_path: &Path,
+    branch: &str,
+) -> PullRequestLookup {
+    match client.view(runner, worktree_path, branch) {
+        Ok(Some(status)) => PullRequestLookup::Found(status),
+        Ok(None) => PullRequestLookup::None,
+        Err(error) => PullRequestLookup::Unavailable(error.to_string()),
+    }
+}
+
+fn print_report(report: &WorktreeReport, provider: GitProvider) {
+    let name_label = format_with_color(&report.name, |text| format!("{}", text.cyan().bold()));
+    output::heading(tr!("Worktree `{}`", name_label));
+
+    output::field(
+        &tr!("Path"),
+        format_with_color(&report.path.display().to_string(), |text| {
+            format!("{}", text.blue())
+        }),
+    );
+
+    let branch = match (&report.status.branch, &report.status.head) {
+        (Some(branch), _) => {
+            format_with_color(branch, |text| format!("{}", text.magenta().bold()))
+        }
+        (None, Some(head)) => tr!("(detached at {})", head),
+        (None, None) => tr!("(unborn)"),
+    };
+    output::field(&tr!("Branch"), branch);
+    output::field(
+        &tr!("Base"),
+        report.base.clone().unwrap_or_else(|| dimmed(&tr!("unknown"))),
+    );
+
+    let upstream = match &report.status.upstream {
+        Some(upstream) => tr!(
+            "{} ({} ahead, {} behind)",
+            upstream.name,
+            upstream.ahead,
+            upstream.behind
+        ),
+        None => dimmed(&tr!("none")),
+    };
+    output::field(&tr!("Upstream"), upstream);
+
+    let changes = report.status.changes.describe();
+    let changes = if report.status.changes.is_clean() {
+        format_with_color(&changes, |text| format!("{}", text.green()))
+    } else {
+        format_with_color(&changes, |text| format!("{}", text.yellow()))
+    };
+    output::field(&tr!("Changes"), changes);
+
+    let pull_request = match &report.pull_request {
+        PullRequestLookup::Found(status) => {
+            let mut value = format!(
+                "{} #{} ({})",
+                provider.merge_request_short(),
+                status.number,
+                status.state
+            );
+            if let Some(url) = &status.url {
+                value.push(' ');
+                value.push_str(url);
+            }
+            value
+        }
+        PullRequestLookup::None => dimmed(&tr!("none")),
+        PullRequestLookup::Unavailable(reason) => dimmed(&tr!("unavailable ({})", reason)),
+    };
+    output::field(provider.merge_request_short(), pull_request);
+
+    if report.hook_runs.is_empty() {
+        output::field(&tr!("Hook runs"), dimmed(&tr!("none recorded")));
+    } else {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        for (index, run) in report.hook_runs.iter().enumerate() {
+            let outcome = match run.exit_code {
+                Some(0) => format_with_color("ok", |text| format!("{}", text.green())),
+                Some(code) => {
+                    format_with_color(&format!("exit {code}"), |text| format!("{}", text.red()))
+                }
+                None => format_with_color("killed", |text| format!("{}", text.red())),
+            };
+            let value = format!(
+                "{} {} ({})",
+                run.hook,
+                outcome,
+                format_age(now.saturating_sub(run.finished_at))
+            );
+            let label = if index == 0 || output::is_plain() {
+                tr!("Hook runs")
+            } else {
+                String::new()
+            };
+            output::field(&label, value);
+        }
+    }
+
+    output::field(&tr!("Disk usage"), format_size(report.disk_usage));
+
+    let tmux = match &report.tmux {
+        TmuxLookup::Session(name) => tr!("session `{}`", name),
+        TmuxLookup::NoSession(name) => dimmed(&tr!("no session `{}`", name)),
+        TmuxLookup::Unavailable => dimmed(&tr!("not running")),
+    };
+    output::field(&tr!("Tmux"), tmux);
+
+    let annotations = &report.annotations;
+    output::field(
+        &tr!("Note"),
+        annotations
+            .note
+            .clone()
+            .unwrap_or_else(|| dimmed(&tr!("none"))),
+    );
+    let metadata = [
+        (tr!("PR link"), annotations.pr.clone()),
+        (tr!("Issue"), annotations.issue.clone()),
+        (tr!("Template"), annotations.template.clone()),
+        (tr!("Created"), describe_created(annotations)),
+    ];
+    for (label, value) in metadata {
+        if let Some(value) = value {
+            output::field(&label, value);
+        }
+    }
+}
+
+fn dimmed(value: &str) -> String {
+    format_with_color(value, |text| format!("{}", text.dimmed()))
+}
+
+fn format_with_color(value: &str, paint: impl Fn(&str) -> String) -> String {
+    value
+        .if_supports_color(Stream::Stdout, |text| paint(text))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    use color_eyre::eyre::{self, WrapErr};
+    use tempfile::TempDir;
+
+    use crate::{
+        annotations,
+        commands::create::CreateCommand,
+        process::{CommandOutput, ScriptedRunner},
+    };
+
+    fn init_git_repo(dir: &TempDir) -> color_eyre::Result<()> {
+        run(dir, ["git", "init"])?;
+        fs::write(dir.path().join("README.md"), "test")?;
+        run(dir, ["git", "add", "README.md"])?;
+        run(
+            dir,
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "Initial commit",
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn run(dir: &TempDir, cmd: impl IntoIterator<Item = &'static str>) -> color_eyre::Result<()> {
+        let mut iter = cmd.into_iter();
+        let program = iter.next().expect("command must not be empty");
+        let status = Command::new(program)
+            .current_dir(dir.path())
+            .args(iter)
+            .status()
+            .wrap_err_with(|| eyre::eyre!("failed to run `{program}`"))?;
+
+        if !status.success() {
+            return Err(eyre::eyre!("`{program}` exited with status {status}"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn collect_gathers_pull_request_base_and_tmux_session() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        CreateCommand::new("feature/info".into(), Some("HEAD".into()))
+            .create_without_enter(&repo, true)?;
+        let session = window_name(
+            &repo,
+            "feature/info",
+            &repo.worktrees_dir().join("feature/info"),
+        );
+
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::success(
+                r#"{"number":5,"url":"https://example.com/pull/5","state":"OPEN"}"#,
+            ))
+            .push(CommandOutput::success(format!("other\n{session}\n")));
+
+        let mut command =
+            InfoCommand::with_runner("info".into(), GitProvider::GitHub, runner.clone());
+        let report = command.collect(&repo)?;
+
+        assert_eq!(report.name, "feature/info");
+        assert_eq!(report.base.as_deref(), Some("HEAD"));
+        assert_eq!(
+            report.pull_request,
+            PullRequestLookup::Found(MergeRequestStatus {
+                number: 5,
+                url: Some("https://example.com/pull/5".into()),
+                state: "open".into(),
+            })
+        );
+        assert_eq!(report.tmux, TmuxLookup::Session(session));
+        assert!(report.disk_usage > 0);
+        let call = &runner.calls()[0];
+        assert_eq!(call.program, "gh");
+        assert_eq!(
+            call.args,
+            GitProvider::GitHub.build_view_args("feature/info")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn collect_degrades_when_provider_and_tmux_are_unavailable() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        CreateCommand::new("feature/info".into(), None).create_without_enter(&repo, true)?;
+
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::failure(
+                1,
+                "no pull requests found for branch \"feature/info\"",
+            ))
+            .push(CommandOutput::failure(
+                1,
+                "no server running on /tmp/tmux-0/default",
+            ));
+        let mut command =
+            InfoCommand::with_runner("feature/info".into(), GitProvider::GitHub, runner);
+        let report = command.collect(&repo)?;
+        assert_eq!(report.pull_request, PullRequestLookup::None);
+        assert_eq!(report.tmux, TmuxLookup::Unavailable);
+
+        let mut command = InfoCommand::with_runner(
+            "feature/info".into(),
+            GitProvider::GitLab,
+            ScriptedRunner::new(),
+        );
+        let report = command.collect(&repo)?;
+        assert_eq!(
+            report.pull_request,
+            PullRequestLookup::Unavailable("`glab` is not available".into())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn collect_loads_the_note_and_metadata_of_the_worktree() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        CreateCommand::new("feature/info".into(), None).create_without_enter(&repo, true)?;
+        annotations::record(&repo, "feature/info", "feature/info", |annotations| {
+            annotations.note = Some("try the new parser".into());
+            annotations.pr = Some("https://example.com/pull/9".into());
+        })?;
+
+        let mut command = InfoCommand::with_runner(
+            "feature/info".into(),
+            GitProvider::GitHub,
+            ScriptedRunner::new(),
+        );
+        let report = command.collect(&repo)?;
+        assert_eq!(
+            report.annotations.note.as_deref(),
+            Some("try the new parser")
+        );
+        assert_eq!(
+            report.annotations.pr.as_deref(),
+            Some("https://example.com/pull/9")
+        );
+        assert!(report.annotations.created_at.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn collect_errors_for_unknown_worktree() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        let mut command =
+            InfoCommand::with_runner("missing".into(), GitProvider::GitHub, ScriptedRunner::new());
+        let err = command.collect(&repo).unwrap_err();
+        assert!(err.to_string().contains("not found"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn disk_usage_sums_nested_files() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        fs::create_dir_all(dir.path().join("a/b"))?;
+        fs::write(dir.path().join("a/one"), vec![0u8; 100])?;
+        fs::write(dir.path().join("a/b/two"), vec![0u8; 28])?;
+
+        assert_eq!(disk_usage(dir.path()), 128);
+
+        Ok(())
+    }
+
+    #[test]
+    fn format_size_uses_binary_units() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn format_age_picks_largest_unit() {
+        assert_eq!(format_age(5), "just now");
+        assert_eq!(format_age(120), "2m ago");
+        assert_eq!(format_age(7_200), "2h ago");
+        assert_eq!(format_age(3 * 86_400), "3d ago");
+    }
+}
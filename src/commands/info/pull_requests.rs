@@ -0,0 +1,343 @@
+//! Cached, concurrent pull/merge request lookups for listings such as
+//! `ls --remote` and `status`.
+//!
+//! Results are kept in `.rsworktree/cache/pr-status.json` for `prCache.ttl`
+//! seconds (five minutes by default) so repeated listings skip the provider
+//! CLI. Failed lookups are never cached.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GitProvider,
+    editor::CONFIG_FILE_NAME,
+    process::CommandRunner,
+    provider::{MergeRequestStatus, ProviderClient},
+    telemetry::{Event, EventStore, elapsed_ms},
+};
+
+use super::{PullRequestLookup, lookup_pull_request};
+
+pub(crate) const CACHE_DIR: &str = "cache";
+const CACHE_FILE: &str = "pr-status.json";
+const DEFAULT_TTL_SECS: u64 = 300;
+/// Upper bound for concurrent provider lookups.
+const MAX_WORKERS: usize = 8;
+
+#[derive(Debug, Deserialize)]
+struct FileFormat {
+    #[serde(default, rename = "prCache")]
+    pr_cache: Option<CacheSettings>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CacheSettings {
+    /// Seconds a lookup stays valid; `0` disables the cache.
+    ttl: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    /// Seconds since the Unix epoch.
+    fetched_at: u64,
+    /// `None` when the branch had no open pull/merge request.
+    pull_request: Option<CachedStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedStatus {
+    number: u64,
+    url: Option<String>,
+    state: String,
+}
+
+/// Pull/merge request states by provider and branch.
+#[derive(Debug)]
+pub(crate) struct PullRequestCache {
+    path: PathBuf,
+    ttl: u64,
+    refresh: bool,
+    entries: BTreeMap<String, Entry>,
+    events: EventStore,
+}
+
+impl PullRequestCache {
+    /// Reads the cache of `rsworktree_dir`; a missing or corrupt file starts empty.
+    pub(crate) fn load(rsworktree_dir: &Path) -> Self {
+        let ttl = fs::read_to_string(rsworktree_dir.join(CONFIG_FILE_NAME))
+            .ok()
+            .and_then(|text| serde_json::from_str::<FileFormat>(&text).ok())
+            .and_then(|parsed| parsed.pr_cache)
+            .map_or(DEFAULT_TTL_SECS, |settings| settings.ttl);
+        let path = rsworktree_dir.join(CACHE_DIR).join(CACHE_FILE);
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            ttl,
+            refresh: false,
+            entries,
+            events: EventStore::new(rsworktree_dir),
+        }
+    }
+
+    /// Ignore cached entries and query the provider for every branch.
+    pub(crate) fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    fn get(&self, provider: GitProvider, branch: &str, now: u64) -> Option<PullRequestLookup> {
+        if self.refresh {
+            return None;
+        }
+        let entry = self.entries.get(&key(provider, branch))?;
+        if now.saturating_sub(entry.fetched_at) >= self.ttl {
+            return None;
+        }
+        Some(match &entry.pull_request {
+            Some(cached) => PullRequestLookup::Found(MergeRequestStatus {
+                number: cached.number,
+                url: cached.url.clone(),
+                state: cached.state.clone(),
+            }),
+            None => PullRequestLookup::None,
+        })
+    }
+
+    fn insert(
+        &mut self,
+        provider: GitProvider,
+        branch: &str,
+        lookup: &PullRequestLookup,
+        now: u64,
+    ) {
+        let pull_request = match lookup {
+            PullRequestLookup::Found(status) => Some(CachedStatus {
+                number: status.number,
+                url: status.url.clone(),
+                state: status.state.clone(),
+            }),
+            PullRequestLookup::None => None,
+            PullRequestLookup::Unavailable(_) => return,
+        };
+        self.entries.insert(
+            key(provider, branch),
+            Entry {
+                fetched_at: now,
+                pull_request,
+            },
+        );
+    }
+
+    /// Writes the cache, dropping expired entries. Failures are ignored, as
+    /// the cache only saves time.
+    fn save(&mut self, now: u64) {
+        if self.ttl == 0 {
+            return;
+        }
+        let ttl = self.ttl;
+        self.entries
+            .retain(|_, entry| now.saturating_sub(entry.fetched_at) < ttl);
+        let Ok(contents) = serde_json::to_string_pretty(&self.entries) else {
+            return;
+        };
+        if let Some(parent) = self.path.parent()
+            && fs::create_dir_all(parent).is_ok()
+        {
+            let _ = fs::write(&self.path, contents);
+        }
+    }
+}
+
+fn key(provider: GitProvider, branch: &str) -> String {
+    format!("{}:{branch}", provider.cli_program())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Looks up the pull/merge request of every `(worktree path, branch)`,
+/// answering from `cache` where possible and querying the provider for the
+/// rest on a few threads. Results are in the order of `branches`.
+pub(crate) fn lookup_pull_requests<R>(
+    runner: &R,
+    client: &ProviderClient,
+    cache: &mut PullRequestCache,
+    branches: &[(PathBuf, String)],
+) -> Vec<PullRequestLookup>
+where
+    R: CommandRunner + Clone + Send,
+{
+    let provider = client.provider();
+    let now = now();
+    let mut results = branches
+        .iter()
+        .map(|(_, branch)| cache.get(provider, branch, now))
+        .collect::<Vec<_>>();
+    let missing = results
+        .iter()
+        .enumerate()
+        .filter(|(_, result)| result.is_none())
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return results.into_iter().flatten().collect();
+    }
+
+    let workers = thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1)
+        .clamp(1, MAX_WORKERS);
+    let chunk_size = missing.len().div_ceil(workers);
+    let fetched = thread::scope(|scope| {
+        let handles = missing
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let mut runner = runner.clone();
+                let mut client = client.clone();
+                // Keeps the lookups below the command's span.
+                let span = tracing::Span::current();
+                scope.spawn(move || {
+                    let _span = span.entered();
+                    chunk
+                        .iter()
+                        .map(|&index| {
+                            let (path, branch) = &branches[index];
+                            let started = Instant::now();
+                            let lookup =
+                                lookup_pull_request(&mut runner, &mut client, path, branch);
+                            (index, lookup, elapsed_ms(started))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("pull request lookup panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    for (index, lookup, duration_ms) in fetched {
+        cache.events.record(Event::ProviderCall {
+            provider: provider.cli_program().to_owned(),
+            command: format!("{} view", provider.request_subcommand()),
+            success: !matches!(lookup, PullRequestLookup::Unavailable(_)),
+            duration_ms,
+        });
+        cache.insert(provider, &branches[index].1, &lookup, now);
+        results[index] = Some(lookup);
+    }
+    cache.save(now);
+    results.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use tempfile::TempDir;
+
+    use crate::{process::CommandOutput, provider::ProviderBackend};
+
+    /// Reports PR 7 for `feature/with-pr` and counts the calls made.
+    #[derive(Debug, Clone, Default)]
+    struct CountingRunner {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl CommandRunner for CountingRunner {
+        fn run(
+            &mut self,
+            _program: &str,
+            _current_dir: &Path,
+            args: &[String],
+        ) -> color_eyre::Result<CommandOutput> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let has_pr = args.iter().any(|arg| arg == "feature/with-pr");
+            Ok(CommandOutput {
+                stdout: if has_pr {
+                    r#"{"number":7,"state":"OPEN"}"#.to_owned()
+                } else {
+                    String::new()
+                },
+                stderr: if has_pr {
+                    String::new()
+                } else {
+                    "no pull requests found for branch".to_owned()
+                },
+                success: has_pr,
+                status_code: Some(if has_pr { 0 } else { 1 }),
+            })
+        }
+    }
+
+    fn branches() -> Vec<(PathBuf, String)> {
+        ["feature/with-pr", "feature/without"]
+            .into_iter()
+            .map(|branch| (PathBuf::from("."), branch.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn lookups_are_cached_until_refreshed() {
+        let dir = TempDir::new().expect("tempdir");
+        let runner = CountingRunner::default();
+        let client = GitProvider::GitHub.client(ProviderBackend::Cli);
+
+        let mut cache = PullRequestCache::load(dir.path());
+        let first = lookup_pull_requests(&runner, &client, &mut cache, &branches());
+        assert!(matches!(&first[0], PullRequestLookup::Found(status) if status.number == 7));
+        assert_eq!(first[1], PullRequestLookup::None);
+        assert_eq!(runner.calls.load(Ordering::SeqCst), 2);
+        assert!(dir.path().join(CACHE_DIR).join(CACHE_FILE).is_file());
+
+        let mut cache = PullRequestCache::load(dir.path());
+        let second = lookup_pull_requests(&runner, &client, &mut cache, &branches());
+        assert_eq!(second, first);
+        assert_eq!(runner.calls.load(Ordering::SeqCst), 2);
+
+        let mut cache = PullRequestCache::load(dir.path()).with_refresh(true);
+        lookup_pull_requests(&runner, &client, &mut cache, &branches());
+        assert_eq!(runner.calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn zero_ttl_disables_the_cache() {
+        let dir = TempDir::new().expect("tempdir");
+        fs::write(
+            dir.path().join(CONFIG_FILE_NAME),
+            r#"{"prCache": {"ttl": 0}}"#,
+        )
+        .expect("write preferences");
+        let runner = CountingRunner::default();
+        let client = GitProvider::GitHub.client(ProviderBackend::Cli);
+
+        for _ in 0..2 {
+            let mut cache = PullRequestCache::load(dir.path());
+            lookup_pull_requests(&runner, &client, &mut cache, &branches());
+        }
+
+        assert_eq!(runner.calls.load(Ordering::SeqCst), 4);
+        assert!(!dir.path().join(CACHE_DIR).exists());
+    }
+}
@@ -29,7 +29,7 @@ use super::{
 use crate::{
     commands::rm::{LocalBranchStatus, RemoveOutcome, branch_has_upstream},
     editor::LaunchOutcome,
-    telemetry::{EditorLaunchStatus, log_editor_launch_attempt},
+    telemetry::{self, EditorLaunchStatus},
 };
 
 #[allow(dead_code)]
@@ -95,13 +95,6 @@ impl ActionPanelState {
     }
 }
 
-struct EditorLaunchLog {
-    worktree: String,
-    path: PathBuf,
-    status: EditorLaunchStatus,
-    message: String,
-}
-
 pub struct InteractiveCommand<B, E>
 where
     B: Backend,
@@ -119,7 +112,6 @@ where
     pub(crate) default_branch: Option<String>,
     pub(crate) status: Option<StatusMessage>,
     pub(crate) dialog: Option<Dialog>,
-    editor_logs: Vec<EditorLaunchLog>,
 }
 
 impl<B, E> InteractiveCommand<B, E>
@@ -153,7 +145,6 @@ where
             default_branch,
             status: None,
             dialog: None,
-            editor_logs: Vec::new(),
         }
     }
 
@@ -168,6 +159,8 @@ where
         G: FnMut(&str, Option<&str>) -> Result<()>,
         H: FnMut(&str, &Path) -> color_eyre::Result<LaunchOutcome>,
     {
+        // Telemetry printed on stderr would draw over the interface.
+        let _stderr = telemetry::hold_stderr();
         self.terminal
             .hide_cursor()
             .wrap_err("failed to hide cursor")?;
@@ -181,10 +174,6 @@ where
             .show_cursor()
             .wrap_err("failed to show cursor")?;
 
-        for log in std::mem::take(&mut self.editor_logs) {
-            log_editor_launch_attempt(&log.worktree, &log.path, log.status, &log.message);
-        }
-
         result
     }
 
@@ -1071,36 +1060,20 @@ where
         self.restore_terminal()?;
 
         match result {
-            Ok(outcome) => {
-                match outcome.status {
-                    EditorLaunchStatus::Success => {
-                        self.status = Some(StatusMessage::info(outcome.message.clone()));
-                        self.dialog = None;
-                    }
-                    EditorLaunchStatus::PreferenceMissing => {
-                        self.show_info_popup(outcome.message.clone());
-                    }
-                    _ => {
-                        self.show_error_popup(outcome.message.clone());
-                    }
+            Ok(outcome) => match outcome.status {
+                EditorLaunchStatus::Success => {
+                    self.status = Some(StatusMessage::info(outcome.message));
+                    self.dialog = None;
                 }
-
-                self.editor_logs.push(EditorLaunchLog {
-                    worktree: name.to_string(),
-                    path: path.to_path_buf(),
-                    status: outcome.status,
-                    message: outcome.message,
-                });
-            }
+                EditorLaunchStatus::PreferenceMissing => {
+                    self.show_info_popup(outcome.message);
+                }
+                _ => {
+                    self.show_error_popup(outcome.message);
+                }
+            },
             Err(error) => {
-                let message = format!("Failed to open `{name}`: {error}");
-                self.show_error_popup(message.clone());
-                self.editor_logs.push(EditorLaunchLog {
-                    worktree: name.to_string(),
-                    path: path.to_path_buf(),
-                    status: EditorLaunchStatus::ConfigurationError,
-                    message,
-                });
+                self.show_error_popup(format!("Failed to open `{name}`: {error}"));
             }
         }
         Ok(())
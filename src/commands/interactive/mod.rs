@@ -7,6 +7,7 @@ mod view;
 pub use command::InteractiveCommand;
 #[allow(unused_imports)]
 pub use runtime::{CrosstermEvents, run};
+pub(crate) use view::plain_buffer;
 
 use std::path::PathBuf;
 
@@ -13,12 +13,12 @@ use crate::{
     commands::{
         cd::{CdCommand, shell_command},
         create::{CreateCommand, CreateOutcome},
-        list::{find_worktrees, format_worktree},
+        list::{find_managed_worktrees, format_worktree},
         merge::MergeCommand,
         review::{ReviewCommand, ReviewOptions},
         rm::RemoveCommand,
     },
-    editor::{launch_worktree, resolve_provider_preference},
+    editor::{LaunchWait, launch_worktree, resolve_provider_preference},
 };
 
 use super::{EventSource, Selection, WorktreeEntry, command::InteractiveCommand};
@@ -39,7 +39,7 @@ impl EventSource for CrosstermEvents {
 
 pub fn run(repo: &Repo) -> Result<()> {
     let worktrees_dir = repo.ensure_worktrees_dir()?;
-    let raw_entries = find_worktrees(&worktrees_dir)?;
+    let raw_entries = find_managed_worktrees(&worktrees_dir)?;
     let worktrees = raw_entries
         .into_iter()
         .map(|path| {
@@ -77,13 +77,12 @@ pub fn run(repo: &Repo) -> Result<()> {
             let command = CreateCommand::new(name.to_owned(), base.map(|b| b.to_owned()));
             match command.create_without_enter(repo, true)? {
                 CreateOutcome::Created => Ok(()),
-                CreateOutcome::AlreadyExists => Err(color_eyre::eyre::eyre!(
-                    "Worktree `{}` already exists.",
-                    name
-                )),
+                CreateOutcome::AlreadyExists | CreateOutcome::OpenedExisting => Err(
+                    color_eyre::eyre::eyre!("Worktree `{}` already exists.", name),
+                ),
             }
         },
-        |name, path| launch_worktree(repo, name, path, true),
+        |name, path| launch_worktree(repo, name, path, LaunchWait::Process),
     );
     let cleanup_result = cleanup_terminal();
 
@@ -120,7 +119,7 @@ pub fn run(repo: &Repo) -> Result<()> {
                     extra_args: Vec::new(),
                     provider,
                 };
-                let mut command = ReviewCommand::new(options);
+                let mut command = ReviewCommand::new(options, repo);
                 command.execute(repo)?;
             }
             Selection::MergePrGithub {
@@ -130,7 +129,7 @@ pub fn run(repo: &Repo) -> Result<()> {
                 remove_worktree,
             } => {
                 let provider = resolve_provider_preference(repo).unwrap_or(GitProvider::default());
-                let mut command = MergeCommand::new(name.clone(), provider);
+                let mut command = MergeCommand::new(name.clone(), provider, repo);
                 if !remove_local_branch {
                     command.disable_remove_local();
                 }
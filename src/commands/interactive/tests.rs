@@ -76,6 +76,7 @@ fn returns_first_worktree_when_enter_pressed_immediately() -> Result<()> {
             |_, _| {
                 Ok(RemoveOutcome {
                     local_branch: None,
+                    remote_branch: None,
                     repositioned: false,
                 })
             },
@@ -108,6 +109,7 @@ fn navigates_down_before_selecting() -> Result<()> {
             |_, _| {
                 Ok(RemoveOutcome {
                     local_branch: None,
+                    remote_branch: None,
                     repositioned: false,
                 })
             },
@@ -147,6 +149,7 @@ fn selecting_review_action_exits_with_review_variant() -> Result<()> {
             removed.push(name.to_owned());
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -184,6 +187,7 @@ fn action_panel_wraps_when_navigating_up() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -254,6 +258,7 @@ fn selecting_merge_action_collects_cleanup_choices() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -308,6 +313,7 @@ fn merge_dialog_allows_disabling_local_branch_removal() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -363,6 +369,7 @@ fn tabbing_to_actions_removes_selected_worktree() -> Result<()> {
             removed.push((name.to_owned(), remove_local_branch));
             Ok(RemoveOutcome {
                 local_branch: remove_local_branch.then_some(LocalBranchStatus::Deleted),
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -410,6 +417,7 @@ fn remove_dialog_allows_disabling_local_branch_removal() -> Result<()> {
             removed.push((name.to_owned(), remove_local_branch));
             Ok(RemoveOutcome {
                 local_branch: remove_local_branch.then_some(LocalBranchStatus::Deleted),
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -451,6 +459,7 @@ fn removing_current_worktree_requests_root_exit() -> Result<()> {
             removed.push((name.to_owned(), remove_local_branch));
             Ok(RemoveOutcome {
                 local_branch: remove_local_branch.then_some(LocalBranchStatus::Deleted),
+                remote_branch: None,
                 repositioned: true,
             })
         },
@@ -491,6 +500,7 @@ fn cancelling_remove_keeps_worktree() -> Result<()> {
             removed.push((name.to_owned(), remove_local_branch));
             Ok(RemoveOutcome {
                 local_branch: remove_local_branch.then_some(LocalBranchStatus::Deleted),
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -536,6 +546,7 @@ fn create_action_adds_new_worktree() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -581,6 +592,7 @@ fn cancelling_create_leaves_state_unchanged() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -613,6 +625,7 @@ fn cd_to_root_global_action_exits() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -645,6 +658,7 @@ fn up_from_top_moves_to_global_actions() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -682,6 +696,7 @@ fn up_from_top_after_tabbing_picks_last_global_action() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -714,6 +729,7 @@ fn up_with_no_worktrees_moves_to_global_actions() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -973,6 +989,7 @@ fn down_from_last_worktree_moves_to_global_actions() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -1011,6 +1028,7 @@ fn up_from_first_global_action_moves_to_last_worktree() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -1058,6 +1076,7 @@ fn down_navigates_within_global_actions() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -1102,6 +1121,7 @@ fn vim_keys_navigate_worktrees() -> Result<()> {
             |_, _| {
                 Ok(RemoveOutcome {
                     local_branch: None,
+                    remote_branch: None,
                     repositioned: false,
                 })
             },
@@ -1141,6 +1161,7 @@ fn left_right_navigate_actions_panel() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -1179,6 +1200,7 @@ fn q_key_exits_interactive_mode() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -1211,6 +1233,7 @@ fn e_key_opens_editor_directly() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
@@ -1254,6 +1277,7 @@ fn backtab_cycles_focus_backward() -> Result<()> {
         |_, _| {
             Ok(RemoveOutcome {
                 local_branch: None,
+                remote_branch: None,
                 repositioned: false,
             })
         },
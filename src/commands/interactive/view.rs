@@ -1,5 +1,6 @@
 use ratatui::{
     Frame,
+    buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
@@ -7,6 +8,7 @@ use ratatui::{
 };
 
 use super::command::ActionPanelState;
+use crate::output;
 use super::{
     Action, Focus, StatusMessage,
     dialog::{
@@ -99,6 +101,10 @@ impl Snapshot {
                 DialogView::Merge { name, dialog } => self.render_merge(frame, size, name, dialog),
             }
         }
+
+        if output::is_plain() {
+            plain_buffer(frame.buffer_mut());
+        }
     }
 
     fn render_list(&self, frame: &mut Frame, area: Rect, state: &mut ListState) {
@@ -620,6 +626,24 @@ impl Snapshot {
     }
 }
 
+/// Strips colors and replaces box-drawing and arrow glyphs with ASCII for plain output.
+pub(crate) fn plain_buffer(buffer: &mut Buffer) {
+    for cell in &mut buffer.content {
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+        let replacement = match cell.symbol() {
+            "─" | "━" | "═" => "-",
+            "│" | "┃" | "║" => "|",
+            "┌" | "┐" | "└" | "┘" | "╭" | "╮" | "╰" | "╯" | "┏" | "┓" | "┗" | "┛" => "+",
+            "▶" => ">",
+            "▲" => "^",
+            "▼" => "v",
+            _ => continue,
+        };
+        cell.set_symbol(replacement);
+    }
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
     let horizontal = Layout::default()
         .direction(Direction::Horizontal)
@@ -0,0 +1,337 @@
+//! Columns of the default `ls` table, selectable with `--columns`.
+
+use std::{fmt, str::FromStr};
+
+use owo_colors::OwoColorize;
+
+use crate::{
+    GitProvider,
+    annotations::Annotations,
+    commands::info::{PullRequestLookup, format_age},
+    output::Cell,
+    worktree::WorktreeStatus,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Name,
+    Branch,
+    /// Time since the last commit.
+    Age,
+    /// `clean` or `dirty`.
+    State,
+    /// Commits ahead of and behind the upstream.
+    Sync,
+    /// The open pull/merge request, looked up through the provider CLI.
+    Pr,
+    /// Time since `create` added the worktree.
+    Created,
+    /// Branch the worktree branch was created from.
+    Base,
+    /// First line of the note from `notes set` or `notes edit`.
+    Note,
+    /// Stash entries made on the worktree's branch.
+    Stashes,
+}
+
+impl Column {
+    pub const ALL: [Column; 10] = [
+        Column::Name,
+        Column::Branch,
+        Column::Age,
+        Column::State,
+        Column::Sync,
+        Column::Pr,
+        Column::Created,
+        Column::Base,
+        Column::Note,
+        Column::Stashes,
+    ];
+
+    /// Shown without `--columns`; `--remote` adds [`Column::Pr`] and
+    /// `--stashes` [`Column::Stashes`].
+    pub const DEFAULT: [Column; 5] = [
+        Column::Name,
+        Column::Branch,
+        Column::Age,
+        Column::State,
+        Column::Sync,
+    ];
+
+    /// Shown with `--long`: the default columns and the recorded annotations.
+    pub const LONG: [Column; 8] = [
+        Column::Name,
+        Column::Branch,
+        Column::Age,
+        Column::State,
+        Column::Sync,
+        Column::Created,
+        Column::Base,
+        Column::Note,
+    ];
+
+    /// Whether the column shows the annotations of the worktree's branch.
+    pub(crate) fn needs_annotations(&self) -> bool {
+        matches!(self, Column::Created | Column::Base | Column::Note)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Column::Name => "name",
+            Column::Branch => "branch",
+            Column::Age => "age",
+            Column::State => "state",
+            Column::Sync => "sync",
+            Column::Pr => "pr",
+            Column::Created => "created",
+            Column::Base => "base",
+            Column::Note => "note",
+            Column::Stashes => "stashes",
+        }
+    }
+
+    pub(crate) fn header(&self, provider: GitProvider) -> String {
+        match self {
+            Column::Name => tr!("NAME"),
+            Column::Branch => tr!("BRANCH"),
+            Column::Age => tr!("AGE"),
+            Column::State => tr!("STATE"),
+            Column::Sync => tr!("SYNC"),
+            Column::Pr => provider.merge_request_short().to_uppercase(),
+            Column::Created => tr!("CREATED"),
+            Column::Base => tr!("BASE"),
+            Column::Note => tr!("NOTE"),
+            Column::Stashes => tr!("STASHES"),
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        Column::ALL
+            .into_iter()
+            .find(|column| column.as_str() == name)
+            .ok_or_else(|| {
+                let available = Column::ALL.map(|column| column.as_str()).join(", ");
+                format!("unknown column '{s}', expected one of: {available}")
+            })
+    }
+}
+
+/// Everything known about one worktree row of the table.
+#[derive(Debug)]
+pub(crate) struct Row {
+    pub(crate) name: String,
+    /// `false` for a worktree outside the worktrees directory.
+    pub(crate) managed: bool,
+    /// `None` when the worktree could not be opened.
+    pub(crate) status: Option<WorktreeStatus>,
+    /// Commit time of HEAD in seconds since the Unix epoch.
+    pub(crate) last_commit_at: Option<i64>,
+    pub(crate) pull_request: PullRequestLookup,
+    /// Loaded only when a column shows them.
+    pub(crate) annotations: Option<Annotations>,
+    /// Stash entries made on the worktree's branch.
+    pub(crate) stashes: usize,
+}
+
+/// How cells are written; terminals get arrows and dashes, pipes plain words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Style {
+    Terminal,
+    Plain,
+}
+
+impl Row {
+    pub(crate) fn cell(
+        &self,
+        column: Column,
+        style: Style,
+        provider: GitProvider,
+        now: i64,
+    ) -> Cell {
+        let missing = || match style {
+            Style::Terminal => Cell::new("-").with_style(|text| format!("{}", text.dimmed())),
+            Style::Plain => Cell::new(""),
+        };
+        let Some(status) = &self.status else {
+            return match column {
+                Column::Name => self.name_cell(),
+                Column::State => {
+                    Cell::new(tr!("unreadable")).with_style(|text| format!("{}", text.red()))
+                }
+                _ => missing(),
+            };
+        };
+
+        match column {
+            Column::Name => self.name_cell(),
+            Column::Branch => {
+                let branch = match (&status.branch, &status.head) {
+                    (Some(branch), _) => branch.clone(),
+                    (None, Some(head)) => tr!("(detached at {})", head),
+                    (None, None) => tr!("(unborn)"),
+                };
+                Cell::new(branch).with_style(|text| format!("{}", text.magenta()))
+            }
+            Column::Age => match self.last_commit_at {
+                Some(time) => Cell::new(format_age(u64::try_from(now - time).unwrap_or_default())),
+                None => missing(),
+            },
+            Column::State if status.changes.is_clean() => {
+                Cell::new(tr!("clean")).with_style(|text| format!("{}", text.green()))
+            }
+            Column::State => {
+                Cell::new(tr!("dirty")).with_style(|text| format!("{}", text.yellow()))
+            }
+            Column::Sync => match (&status.upstream, style) {
+                (Some(upstream), Style::Terminal) => {
+                    Cell::new(format!("↑{} ↓{}", upstream.ahead, upstream.behind))
+                }
+                (Some(upstream), Style::Plain) => {
+                    Cell::new(tr!("{} ahead, {} behind", upstream.ahead, upstream.behind))
+                }
+                (None, Style::Terminal) => missing(),
+                (None, Style::Plain) => Cell::new(tr!("no upstream")),
+            },
+            Column::Pr => match &self.pull_request {
+                PullRequestLookup::Found(found) => Cell::new(format!(
+                    "{}{} ({})",
+                    provider.request_prefix(),
+                    found.number,
+                    found.state
+                ))
+                .with_style(|text| format!("{}", text.blue())),
+                PullRequestLookup::None => missing(),
+                PullRequestLookup::Unavailable(_) => Cell::new(tr!("unavailable")),
+            },
+            Column::Created => match self.annotations.as_ref().and_then(|a| a.created_at) {
+                Some(time) => Cell::new(format_age(
+                    u64::try_from(now).unwrap_or_default().saturating_sub(time),
+                )),
+                None => missing(),
+            },
+            Column::Base => match self.annotations.as_ref().and_then(|a| a.base.clone()) {
+                Some(base) => Cell::new(base).with_style(|text| format!("{}", text.magenta())),
+                None => missing(),
+            },
+            Column::Note => match self
+                .annotations
+                .as_ref()
+                .and_then(|a| a.note.as_deref())
+                .and_then(|note| note.lines().next())
+            {
+                Some(line) if style == Style::Terminal => Cell::new(truncate(line)),
+                Some(line) => Cell::new(line.to_owned()),
+                None => missing(),
+            },
+            Column::Stashes if self.stashes == 0 => missing(),
+            Column::Stashes => Cell::new(tr!("{} stashed", self.stashes))
+                .with_style(|text| format!("{}", text.yellow())),
+        }
+    }
+
+    /// The name, marked when the worktree was added outside the worktrees directory.
+    fn name_cell(&self) -> Cell {
+        if self.managed {
+            Cell::new(self.name.clone()).with_style(|text| format!("{}", text.cyan().bold()))
+        } else {
+            Cell::new(tr!("{} (unmanaged)", self.name))
+                .with_style(|text| format!("{}", text.cyan()))
+        }
+    }
+}
+
+/// Notes longer than this are cut on a terminal to keep the table readable.
+const NOTE_WIDTH: usize = 40;
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= NOTE_WIDTH {
+        return text.to_owned();
+    }
+    let mut cut = text.chars().take(NOTE_WIDTH - 1).collect::<String>();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::worktree::UpstreamStatus;
+
+    #[test]
+    fn parses_columns_case_insensitively() {
+        assert_eq!(" Age".parse::<Column>(), Ok(Column::Age));
+        assert_eq!("pr".parse::<Column>(), Ok(Column::Pr));
+        assert_eq!(
+            "size".parse::<Column>(),
+            Err(
+                "unknown column 'size', expected one of: name, branch, age, state, sync, pr, created, base, note, stashes"
+                    .to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn cells_degrade_to_words_without_a_terminal() {
+        let row = Row {
+            name: "feature/login".into(),
+            managed: true,
+            status: Some(WorktreeStatus {
+                branch: Some("feature/login".into()),
+                upstream: Some(UpstreamStatus {
+                    name: "origin/feature/login".into(),
+                    ahead: 2,
+                    behind: 1,
+                }),
+                ..WorktreeStatus::default()
+            }),
+            last_commit_at: Some(1_000),
+            pull_request: PullRequestLookup::None,
+            annotations: Some(Annotations {
+                branch: "feature/login".into(),
+                note: Some(
+                    "Rework the login form so that it validates while typing\nDetails".into(),
+                ),
+                base: Some("main".into()),
+                created_at: Some(1_000),
+                ..Annotations::default()
+            }),
+            stashes: 2,
+        };
+        let text = |column, style| {
+            let cell = row.cell(column, style, GitProvider::default(), 1_000 + 7_200);
+            let mut table = crate::output::Table::new();
+            table.push_row(vec![cell]);
+            table.render().join("")
+        };
+
+        assert_eq!(text(Column::Age, Style::Plain), "2h ago");
+        assert_eq!(text(Column::State, Style::Plain), "clean");
+        assert_eq!(text(Column::Sync, Style::Terminal), "↑2 ↓1");
+        assert_eq!(text(Column::Sync, Style::Plain), "2 ahead, 1 behind");
+        assert_eq!(text(Column::Pr, Style::Terminal), "-");
+        assert_eq!(text(Column::Pr, Style::Plain), "");
+        assert_eq!(text(Column::Created, Style::Plain), "2h ago");
+        assert_eq!(text(Column::Base, Style::Plain), "main");
+        assert_eq!(
+            text(Column::Note, Style::Plain),
+            "Rework the login form so that it validates while typing"
+        );
+        assert_eq!(
+            text(Column::Note, Style::Terminal),
+            "Rework the login form so that it valida…"
+        );
+        assert_eq!(text(Column::Stashes, Style::Plain), "2 stashed");
+    }
+}
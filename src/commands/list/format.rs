@@ -0,0 +1,241 @@
+//! `ls --format` templates.
+//!
+//! A template is plain text with `{placeholder}` fields, e.g.
+//! `{name}\t{branch}\t{ahead}/{behind}\t{pr.state}`. `\t`, `\n` and `\\` are
+//! unescaped so the template can be passed in single quotes, and `{{`/`}}`
+//! produce literal braces. Values that are not known render as an empty string.
+
+use color_eyre::eyre;
+
+use crate::{provider::MergeRequestStatus, worktree::Worktree};
+
+/// Every placeholder accepted by [`FormatTemplate::parse`], with a short description.
+pub(crate) const PLACEHOLDERS: &[(&str, &str)] = &[
+    ("name", "worktree name relative to `.rsworktree`"),
+    ("path", "absolute worktree path"),
+    ("branch", "checked out branch"),
+    ("head", "abbreviated HEAD commit"),
+    ("base", "branch the worktree was created from"),
+    ("upstream", "upstream tracking branch"),
+    ("ahead", "commits ahead of the upstream"),
+    ("behind", "commits behind the upstream"),
+    ("state", "`clean` or `dirty`"),
+    ("changes", "summary such as `1 modified, 2 untracked`"),
+    ("staged", "number of staged files"),
+    ("modified", "number of modified files"),
+    ("untracked", "number of untracked files"),
+    ("conflicted", "number of conflicted files"),
+    ("pr.number", "number of the open pull/merge request"),
+    ("pr.state", "state of the pull/merge request"),
+    ("pr.url", "URL of the pull/merge request"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Placeholder(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FormatTemplate {
+    segments: Vec<Segment>,
+}
+
+impl FormatTemplate {
+    pub(crate) fn parse(template: &str) -> color_eyre::Result<Self> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' => match chars.next() {
+                    Some('t') => literal.push('\t'),
+                    Some('n') => literal.push('\n'),
+                    Some('\\') => literal.push('\\'),
+                    Some(other) => {
+                        literal.push('\\');
+                        literal.push(other);
+                    }
+                    None => literal.push('\\'),
+                },
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for inner in chars.by_ref() {
+                        if inner == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(inner);
+                    }
+                    if !closed {
+                        return Err(eyre::eyre!("unclosed `{{` in format template"));
+                    }
+                    let Some((known, _)) = PLACEHOLDERS
+                        .iter()
+                        .find(|(placeholder, _)| *placeholder == name.trim())
+                    else {
+                        let available = PLACEHOLDERS
+                            .iter()
+                            .map(|(placeholder, _)| format!("{{{placeholder}}}"))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        return Err(eyre::eyre!(
+                            "unknown placeholder `{{{name}}}` in format template; available: {available}"
+                        ));
+                    };
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Placeholder(known));
+                }
+                other => literal.push(other),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Whether rendering needs the pull/merge request, which costs a provider call.
+    pub(crate) fn needs_pull_request(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Placeholder(name) if name.starts_with("pr.")))
+    }
+
+    pub(crate) fn render(
+        &self,
+        worktree: &Worktree,
+        pull_request: Option<&MergeRequestStatus>,
+    ) -> String {
+        let mut output = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => output.push_str(text),
+                Segment::Placeholder(name) => {
+                    output.push_str(&value(name, worktree, pull_request));
+                }
+            }
+        }
+        output
+    }
+}
+
+fn value(name: &str, worktree: &Worktree, pull_request: Option<&MergeRequestStatus>) -> String {
+    let status = &worktree.status;
+    let changes = &status.changes;
+    match name {
+        "name" => worktree.name.clone(),
+        "path" => worktree.path.display().to_string(),
+        "branch" => status.branch.clone().unwrap_or_default(),
+        "head" => status.head.clone().unwrap_or_default(),
+        "base" => worktree.base.clone().unwrap_or_default(),
+        "upstream" => status
+            .upstream
+            .as_ref()
+            .map(|upstream| upstream.name.clone())
+            .unwrap_or_default(),
+        "ahead" => optional(worktree.ahead()),
+        "behind" => optional(worktree.behind()),
+        "state" => if changes.is_clean() { "clean" } else { "dirty" }.to_owned(),
+        "changes" => changes.describe(),
+        "staged" => changes.staged.to_string(),
+        "modified" => changes.modified.to_string(),
+        "untracked" => changes.untracked.to_string(),
+        "conflicted" => changes.conflicted.to_string(),
+        "pr.number" => optional(pull_request.map(|status| status.number)),
+        "pr.state" => pull_request
+            .map(|status| status.state.clone())
+            .unwrap_or_default(),
+        "pr.url" => pull_request
+            .and_then(|status| status.url.clone())
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+fn optional(value: Option<impl ToString>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::worktree::{ChangeSummary, UpstreamStatus, WorktreeStatus};
+
+    fn worktree() -> Worktree {
+        Worktree {
+            name: "feature/test".into(),
+            path: PathBuf::from("/repo/.rsworktree/feature/test"),
+            managed: true,
+            base: Some("main".into()),
+            status: WorktreeStatus {
+                branch: Some("feature/test".into()),
+                head: Some("abc1234".into()),
+                upstream: Some(UpstreamStatus {
+                    name: "origin/feature/test".into(),
+                    ahead: 2,
+                    behind: 1,
+                }),
+                changes: ChangeSummary {
+                    modified: 1,
+                    ..ChangeSummary::default()
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn render_substitutes_placeholders_and_escapes() -> color_eyre::Result<()> {
+        let template = FormatTemplate::parse(r"{name}\t{branch}\t{ahead}/{behind}\t{state} {{x}}")?;
+
+        assert_eq!(
+            template.render(&worktree(), None),
+            "feature/test\tfeature/test\t2/1\tdirty {x}"
+        );
+        assert!(!template.needs_pull_request());
+        Ok(())
+    }
+
+    #[test]
+    fn render_uses_pull_request_when_requested() -> color_eyre::Result<()> {
+        let template = FormatTemplate::parse("{name} #{pr.number} {pr.state}")?;
+        let status = MergeRequestStatus {
+            number: 42,
+            url: None,
+            state: "open".into(),
+        };
+
+        assert!(template.needs_pull_request());
+        assert_eq!(
+            template.render(&worktree(), Some(&status)),
+            "feature/test #42 open"
+        );
+        assert_eq!(template.render(&worktree(), None), "feature/test # ");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_rejects_unknown_and_unclosed_placeholders() {
+        let error = FormatTemplate::parse("{nmae}").expect_err("unknown placeholder");
+        assert!(error.to_string().contains("unknown placeholder `{nmae}`"));
+        assert!(error.to_string().contains("{name}"));
+
+        assert!(FormatTemplate::parse("{name").is_err());
+    }
+}
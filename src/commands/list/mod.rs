@@ -1,21 +1,165 @@
+mod columns;
+mod format;
+
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     fs,
+    io::{self, IsTerminal},
     path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use color_eyre::eyre::{self, WrapErr};
 use owo_colors::{OwoColorize, Stream};
+use serde::Serialize;
+
+use crate::{
+    GitProvider, Repo,
+    annotations::Annotations,
+    commands::{
+        info::{PullRequestCache, PullRequestLookup, lookup_pull_requests},
+        stash::stash_counts,
+    },
+    output::{self, Table},
+    process::{CommandRunner, SystemCommandRunner},
+    provider::{ProviderBackend, ProviderClient},
+    worktree::{self, inspect_worktree},
+};
 
-use crate::Repo;
+pub use self::columns::Column;
+use self::{
+    columns::{Row, Style},
+    format::FormatTemplate,
+};
 
 #[derive(Debug, Default)]
-pub struct ListCommand;
+pub struct ListCommand<R = SystemCommandRunner> {
+    format: Option<String>,
+    json: bool,
+    porcelain: bool,
+    columns: Vec<Column>,
+    long: bool,
+    remote: bool,
+    stashes: bool,
+    refresh: bool,
+    provider: GitProvider,
+    client: ProviderClient,
+    runner: R,
+}
 
 impl ListCommand {
-    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+    /// Runs the provider operations through the backend configured for `repo`.
+    pub fn new(repo: &Repo, provider: GitProvider) -> Self {
+        Self::with_runner(SystemCommandRunner)
+            .with_provider(provider)
+            .with_backend(ProviderBackend::resolve(repo))
+    }
+}
+
+impl<R> ListCommand<R>
+where
+    R: CommandRunner + Clone + Send,
+{
+    pub fn with_runner(runner: R) -> Self {
+        Self {
+            format: None,
+            json: false,
+            porcelain: false,
+            columns: Vec::new(),
+            long: false,
+            remote: false,
+            stashes: false,
+            refresh: false,
+            provider: GitProvider::default(),
+            client: ProviderClient::default(),
+            runner,
+        }
+    }
+
+    /// Print one line per worktree rendered from `template` instead of the default listing.
+    pub fn with_format(mut self, template: Option<String>) -> Self {
+        self.format = template;
+        self
+    }
+
+    /// Print the worktrees as a JSON array instead of the default listing.
+    pub fn with_json(mut self, json: bool) -> Self {
+        self.json = json;
+        self
+    }
+
+    /// Print one tab-separated `worktree` record per worktree, see [`output::porcelain`].
+    pub fn with_porcelain(mut self, porcelain: bool) -> Self {
+        self.porcelain = porcelain;
+        self
+    }
+
+    /// Columns of the table, in order; empty shows [`Column::DEFAULT`].
+    pub fn with_columns(mut self, columns: Vec<Column>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Show [`Column::LONG`] instead of [`Column::DEFAULT`] without `--columns`.
+    pub fn with_long(mut self, long: bool) -> Self {
+        self.long = long;
+        self
+    }
+
+    /// Add the pull/merge request column, which queries the provider for every worktree.
+    pub fn with_remote(mut self, remote: bool) -> Self {
+        self.remote = remote;
+        self
+    }
+
+    /// Add the column counting the stash entries made on every worktree's branch.
+    pub fn with_stashes(mut self, stashes: bool) -> Self {
+        self.stashes = stashes;
+        self
+    }
+
+    /// Query the provider even for pull/merge request states cached recently.
+    pub fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Provider queried for `{pr.*}` placeholders and the PR column.
+    pub fn with_provider(mut self, provider: GitProvider) -> Self {
+        self.provider = provider;
+        self.client = provider.client(self.client.backend());
+        self
+    }
+
+    /// Runs the provider operations through `backend` rather than the CLI.
+    pub fn with_backend(mut self, backend: ProviderBackend) -> Self {
+        self.client = self.provider.client(backend);
+        self
+    }
+
+    pub fn execute(&mut self, repo: &Repo) -> color_eyre::Result<()> {
+        if self.json {
+            let entries = json_entries(repo)?;
+            let json = serde_json::to_string_pretty(&entries)
+                .wrap_err("failed to serialize worktree list")?;
+            println!("{json}");
+            return Ok(());
+        }
+
+        if self.porcelain {
+            output::porcelain::print(porcelain_records(repo)?);
+            return Ok(());
+        }
+
+        if let Some(template) = self.format.clone() {
+            for line in self.render_lines(repo, &template)? {
+                println!("{line}");
+            }
+            return Ok(());
+        }
+
         let worktrees_dir = repo.ensure_worktrees_dir()?;
-        let worktrees = find_worktrees(&worktrees_dir)?;
+        let worktrees = find_worktrees(repo)?;
 
         let header_path_raw = format!("{}", worktrees_dir.display());
         let header_path = format!(
@@ -24,39 +168,303 @@ impl ListCommand {
                 .as_str()
                 .if_supports_color(Stream::Stdout, |text| { format!("{}", text.blue().bold()) })
         );
-        let header_raw = format!("Worktrees under `{}`:", header_path);
-        let header = format!(
-            "{}",
-            header_raw
-                .as_str()
-                .if_supports_color(Stream::Stdout, |text| format!("{}", text.bold()))
-        );
-        println!("{}", header);
+        output::heading(tr!("Worktrees under `{}`:", header_path));
 
         if worktrees.is_empty() {
             let message = format!(
                 "{}",
-                "(none)".if_supports_color(Stream::Stdout, |text| { format!("{}", text.dimmed()) })
+                tr!("(none)").if_supports_color(Stream::Stdout, |text| { format!("{}", text.dimmed()) })
             );
-            println!("{}", message);
+            output::info(message);
         } else {
-            for worktree in worktrees {
-                let entry_raw = format_worktree(&worktree);
-                let entry = format!(
-                    "{}",
-                    entry_raw
-                        .as_str()
-                        .if_supports_color(Stream::Stdout, |text| { format!("{}", text.green()) })
-                );
-                println!("- {}", entry);
+            let style = if io::stdout().is_terminal() && !output::is_plain() {
+                Style::Terminal
+            } else {
+                Style::Plain
+            };
+            for line in self.render_table(repo, &worktrees_dir, &worktrees, style) {
+                println!("{line}");
             }
         }
 
         Ok(())
     }
+
+    /// The selected columns, with the PR column appended for `--remote` and
+    /// the stash column for `--stashes`.
+    fn columns(&self) -> Vec<Column> {
+        let mut columns = if !self.columns.is_empty() {
+            self.columns.clone()
+        } else if self.long {
+            Column::LONG.to_vec()
+        } else {
+            Column::DEFAULT.to_vec()
+        };
+        if self.remote && !columns.contains(&Column::Pr) {
+            columns.push(Column::Pr);
+        }
+        if self.stashes && !columns.contains(&Column::Stashes) {
+            columns.push(Column::Stashes);
+        }
+        columns
+    }
+
+    fn render_table(
+        &mut self,
+        repo: &Repo,
+        worktrees_dir: &Path,
+        worktrees: &[PathBuf],
+        style: Style,
+    ) -> Vec<String> {
+        let columns = self.columns();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| {
+                i64::try_from(elapsed.as_secs()).unwrap_or(i64::MAX)
+            });
+
+        let mut table = Table::new().with_header(
+            columns
+                .iter()
+                .map(|column| column.header(self.provider))
+                .collect(),
+        );
+        let statuses = worktrees
+            .iter()
+            .map(|relative| inspect_worktree(&worktrees_dir.join(relative)).ok())
+            .collect::<Vec<_>>();
+        let mut pull_requests = if columns.contains(&Column::Pr) {
+            let branches = worktrees
+                .iter()
+                .zip(&statuses)
+                .map(|(relative, status)| {
+                    let branch = status.as_ref().and_then(|status| status.branch.clone());
+                    (worktrees_dir.join(relative), branch)
+                })
+                .collect::<Vec<_>>();
+            self.pull_requests(&repo.rsworktree_dir(), &branches)
+                .into_iter()
+        } else {
+            Vec::new().into_iter()
+        };
+
+        let stash_counts = if columns.contains(&Column::Stashes) {
+            stash_counts(repo)
+        } else {
+            HashMap::new()
+        };
+
+        let annotated = columns.iter().any(Column::needs_annotations);
+        for (relative, status) in worktrees.iter().zip(statuses) {
+            let path = worktrees_dir.join(relative);
+            let name = format_worktree(relative);
+            let managed = is_managed(worktrees_dir, relative);
+            let annotations = status
+                .as_ref()
+                .and_then(|status| status.branch.as_deref())
+                .filter(|_| annotated && managed)
+                .and_then(|branch| Annotations::load(repo, &name, branch).ok());
+            let stashes = status
+                .as_ref()
+                .and_then(|status| status.branch.as_ref())
+                .and_then(|branch| stash_counts.get(branch))
+                .copied()
+                .unwrap_or_default();
+            let row = Row {
+                name,
+                managed,
+                last_commit_at: status.as_ref().and_then(|_| last_commit_at(&path)),
+                status,
+                pull_request: pull_requests.next().unwrap_or(PullRequestLookup::None),
+                annotations,
+                stashes,
+            };
+            table.push_row(
+                columns
+                    .iter()
+                    .map(|column| row.cell(*column, style, self.provider, now))
+                    .collect(),
+            );
+        }
+        table.render()
+    }
+
+    fn render_lines(&mut self, repo: &Repo, template: &str) -> color_eyre::Result<Vec<String>> {
+        let template = FormatTemplate::parse(template)?;
+        let worktrees = worktree::load_all(repo)?;
+
+        let mut pull_requests = if template.needs_pull_request() {
+            let branches = worktrees
+                .iter()
+                .map(|worktree| (worktree.path.clone(), worktree.status.branch.clone()))
+                .collect::<Vec<_>>();
+            self.pull_requests(&repo.rsworktree_dir(), &branches)
+                .into_iter()
+        } else {
+            Vec::new().into_iter()
+        };
+
+        let mut lines = Vec::with_capacity(worktrees.len());
+        for worktree in &worktrees {
+            let pull_request = pull_requests.next().unwrap_or(PullRequestLookup::None);
+            let status = match &pull_request {
+                PullRequestLookup::Found(status) => Some(status),
+                _ => None,
+            };
+            lines.push(template.render(worktree, status));
+        }
+        Ok(lines)
+    }
+
+    /// The pull/merge request of every `(path, branch)`, in order; worktrees
+    /// without a branch have none.
+    fn pull_requests(
+        &self,
+        rsworktree_dir: &Path,
+        worktrees: &[(PathBuf, Option<String>)],
+    ) -> Vec<PullRequestLookup> {
+        let branches = worktrees
+            .iter()
+            .filter_map(|(path, branch)| Some((path.clone(), branch.clone()?)))
+            .collect::<Vec<_>>();
+        let mut cache = PullRequestCache::load(rsworktree_dir).with_refresh(self.refresh);
+        let mut found =
+            lookup_pull_requests(&self.runner, &self.client, &mut cache, &branches).into_iter();
+        worktrees
+            .iter()
+            .map(|(_, branch)| match branch {
+                Some(_) => found.next().unwrap_or(PullRequestLookup::None),
+                None => PullRequestLookup::None,
+            })
+            .collect()
+    }
+}
+
+/// One worktree as printed by `ls --json`.
+#[derive(Debug, Serialize)]
+struct JsonEntry {
+    name: String,
+    path: PathBuf,
+    /// `false` for a worktree outside the worktrees directory.
+    managed: bool,
+    branch: Option<String>,
+    /// Full SHA of the checked out commit.
+    head: Option<String>,
+    dirty: bool,
+    /// Commit time of `head` in seconds since the Unix epoch.
+    last_commit_at: Option<i64>,
+}
+
+fn last_commit_at(path: &Path) -> Option<i64> {
+    let git = git2::Repository::open(path).ok()?;
+    let commit = git.head().and_then(|head| head.peel_to_commit()).ok()?;
+    Some(commit.time().seconds())
+}
+
+fn json_entries(repo: &Repo) -> color_eyre::Result<Vec<JsonEntry>> {
+    worktree::load_all(repo)?
+        .into_iter()
+        .map(|worktree| {
+            let git = git2::Repository::open(&worktree.path).wrap_err_with(|| {
+                eyre::eyre!("failed to open worktree at `{}`", worktree.path.display())
+            })?;
+            let commit = git.head().and_then(|head| head.peel_to_commit()).ok();
+            Ok(JsonEntry {
+                path: fs::canonicalize(&worktree.path).unwrap_or(worktree.path),
+                name: worktree.name,
+                managed: worktree.managed,
+                branch: worktree.status.branch,
+                head: commit.as_ref().map(|commit| commit.id().to_string()),
+                dirty: !worktree.status.changes.is_clean(),
+                last_commit_at: commit.map(|commit| commit.time().seconds()),
+            })
+        })
+        .collect()
+}
+
+/// `worktree <name> <path> <branch> <head> <clean|dirty> <upstream> <ahead>
+/// <behind> <managed|unmanaged>`, with the full HEAD commit and empty fields
+/// for a detached HEAD or a branch without upstream.
+fn porcelain_records(repo: &Repo) -> color_eyre::Result<Vec<String>> {
+    worktree::load_all(repo)?
+        .into_iter()
+        .map(|worktree| {
+            let git = git2::Repository::open(&worktree.path).wrap_err_with(|| {
+                eyre::eyre!("failed to open worktree at `{}`", worktree.path.display())
+            })?;
+            let head = git
+                .head()
+                .and_then(|head| head.peel_to_commit())
+                .map(|commit| commit.id().to_string())
+                .unwrap_or_default();
+            let path = fs::canonicalize(&worktree.path).unwrap_or(worktree.path);
+            let status = worktree.status;
+            let (upstream, ahead, behind) = match status.upstream {
+                Some(upstream) => (
+                    upstream.name,
+                    upstream.ahead.to_string(),
+                    upstream.behind.to_string(),
+                ),
+                None => Default::default(),
+            };
+            let state = if status.changes.is_clean() {
+                "clean"
+            } else {
+                "dirty"
+            };
+            let managed = if worktree.managed {
+                "managed"
+            } else {
+                "unmanaged"
+            };
+            Ok(output::porcelain::record(
+                "worktree",
+                [
+                    worktree.name,
+                    path.display().to_string(),
+                    status.branch.unwrap_or_default(),
+                    head,
+                    state.to_owned(),
+                    upstream,
+                    ahead,
+                    behind,
+                    managed.to_owned(),
+                ],
+            ))
+        })
+        .collect()
 }
 
-pub(crate) fn find_worktrees(base: &Path) -> color_eyre::Result<Vec<PathBuf>> {
+/// Every worktree of `repo` except the main working tree: the checkouts found
+/// under the worktrees directory, relative to it, followed by the other
+/// worktrees `git worktree list --porcelain` reports, such as ones added with
+/// `git worktree add` elsewhere. Those are unmanaged and returned as absolute
+/// paths, which joining onto the worktrees directory leaves unchanged.
+pub(crate) fn find_worktrees(repo: &Repo) -> color_eyre::Result<Vec<PathBuf>> {
+    let worktrees_dir = repo.worktrees_dir();
+    let mut results = if worktrees_dir.is_dir() {
+        find_managed_worktrees(&worktrees_dir)?
+    } else {
+        Vec::new()
+    };
+
+    // Without a `git` executable only the worktrees directory is scanned.
+    let mut unmanaged = repo
+        .git_worktrees()
+        .unwrap_or_default()
+        .into_iter()
+        // The main working tree, or the repository itself when it is bare.
+        .skip(1)
+        .map(|worktree| worktree.path)
+        .filter(|path| path.is_dir() && !is_managed(&worktrees_dir, path))
+        .collect::<Vec<_>>();
+    unmanaged.sort();
+    results.extend(unmanaged);
+    Ok(results)
+}
+
+/// The checkouts under `base`, relative to it, in alphabetical order.
+pub(crate) fn find_managed_worktrees(base: &Path) -> color_eyre::Result<Vec<PathBuf>> {
     let mut results = Vec::new();
     let mut queue = VecDeque::new();
     queue.push_back(base.to_path_buf());
@@ -91,7 +499,21 @@ pub(crate) fn find_worktrees(base: &Path) -> color_eyre::Result<Vec<PathBuf>> {
     Ok(results)
 }
 
+/// Whether `path` lies in the worktrees directory `worktrees_dir`.
+pub(crate) fn is_managed(worktrees_dir: &Path, path: &Path) -> bool {
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    canonical(&worktrees_dir.join(path)).starts_with(canonical(worktrees_dir))
+}
+
+/// The name of a worktree found by [`find_worktrees`]; an unmanaged worktree
+/// goes by the name of its directory.
 pub(crate) fn format_worktree(path: &Path) -> String {
+    if path.is_absolute() {
+        return path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+    }
     path.components()
         .map(|component| component.as_os_str().to_string_lossy().into_owned())
         .collect::<Vec<_>>()
@@ -105,7 +527,11 @@ mod tests {
 
     use tempfile::TempDir;
 
-    use crate::Repo;
+    use crate::{
+        Repo,
+        commands::{create::CreateCommand, open::find_by_name},
+        process::{CommandOutput, ScriptedRunner},
+    };
 
     fn init_git_repo(dir: &TempDir) -> color_eyre::Result<()> {
         run(dir, ["git", "init"])
@@ -142,7 +568,7 @@ mod tests {
         fs::create_dir_all(&worktree_b)?;
         fs::write(worktree_b.join(".git"), "gitdir: ../..")?;
 
-        let found = find_worktrees(&worktrees_dir)?;
+        let found = find_managed_worktrees(&worktrees_dir)?;
         let labels: Vec<String> = found.iter().map(|path| format_worktree(path)).collect();
 
         assert_eq!(labels, vec!["bugfix/squash", "feature/test"]);
@@ -157,7 +583,7 @@ mod tests {
         let repo = Repo::discover_from(repo_dir.path())?;
         let worktrees_dir = repo.ensure_worktrees_dir()?;
 
-        let found = find_worktrees(&worktrees_dir)?;
+        let found = find_managed_worktrees(&worktrees_dir)?;
         assert!(found.is_empty());
 
         Ok(())
@@ -186,7 +612,7 @@ mod tests {
         fs::create_dir_all(&worktree)?;
         fs::write(worktree.join(".git"), "gitdir: ..")?;
 
-        let cmd = ListCommand;
+        let mut cmd = ListCommand::new(&repo, GitProvider::default());
         // Just verify it doesn't error - output goes to stdout
         cmd.execute(&repo)?;
 
@@ -200,9 +626,193 @@ mod tests {
         let repo = Repo::discover_from(repo_dir.path())?;
         let _worktrees_dir = repo.ensure_worktrees_dir()?;
 
-        let cmd = ListCommand;
+        let mut cmd = ListCommand::new(&repo, GitProvider::default());
         cmd.execute(&repo)?;
 
         Ok(())
     }
+
+    #[test]
+    fn render_lines_formats_each_worktree() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        init_git_repo(&repo_dir)?;
+        fs::write(repo_dir.path().join("README.md"), "test")?;
+        run(&repo_dir, ["git", "add", "README.md"])?;
+        run(
+            &repo_dir,
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "Initial commit",
+            ],
+        )?;
+        let repo = Repo::discover_from(repo_dir.path())?;
+        CreateCommand::new("feature/format".into(), None).create_without_enter(&repo, true)?;
+
+        let runner = ScriptedRunner::new();
+        let mut command = ListCommand::with_runner(runner.clone());
+        let lines = command.render_lines(&repo, r"{name}\t{branch}\t{state}")?;
+        assert_eq!(lines, vec!["feature/format\tfeature/format\tclean"]);
+        assert!(
+            runner.calls().is_empty(),
+            "no provider call without pr placeholders"
+        );
+
+        runner.push(CommandOutput::success(
+            r#"{"number": 7, "url": "https://example.test/7", "state": "OPEN"}"#,
+        ));
+        let lines = command.render_lines(&repo, "{name} {pr.number} {pr.state}")?;
+        assert_eq!(lines, vec!["feature/format 7 open"]);
+        assert_eq!(runner.calls()[0].program, "gh");
+
+        Ok(())
+    }
+
+    #[test]
+    fn find_worktrees_adds_unmanaged_worktrees() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        init_git_repo(&repo_dir)?;
+        run(
+            &repo_dir,
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--allow-empty",
+                "-m",
+                "Initial commit",
+            ],
+        )?;
+        let repo = Repo::discover_from(repo_dir.path())?;
+        CreateCommand::new("feature/managed".into(), None).create_without_enter(&repo, true)?;
+        let outside = TempDir::new()?;
+        let unmanaged = outside.path().join("hotfix");
+        let status = Command::new("git")
+            .current_dir(repo_dir.path())
+            .args(["worktree", "add", "--quiet", "-b", "hotfix"])
+            .arg(&unmanaged)
+            .status()?;
+        assert!(status.success());
+
+        let found = find_worktrees(&repo)?;
+        let labels = found
+            .iter()
+            .map(|path| format_worktree(path))
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["feature/managed", "hotfix"]);
+        assert!(is_managed(&repo.worktrees_dir(), &found[0]));
+        assert!(!is_managed(&repo.worktrees_dir(), &found[1]));
+
+        let worktrees = worktree::load_all(&repo)?;
+        assert!(worktrees[0].managed);
+        assert!(!worktrees[1].managed);
+        assert_eq!(worktrees[1].status.branch.as_deref(), Some("hotfix"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn unmanaged_worktree_sharing_a_name_is_told_apart_by_path() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        init_git_repo(&repo_dir)?;
+        run(
+            &repo_dir,
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--allow-empty",
+                "-m",
+                "Initial commit",
+            ],
+        )?;
+        let repo = Repo::discover_from(repo_dir.path())?;
+        CreateCommand::new("hotfix".into(), None).create_without_enter(&repo, true)?;
+        let outside = TempDir::new()?;
+        let unmanaged = outside.path().join("hotfix");
+        let status = Command::new("git")
+            .current_dir(repo_dir.path())
+            .args(["worktree", "add", "--quiet", "-b", "external"])
+            .arg(&unmanaged)
+            .status()?;
+        assert!(status.success());
+
+        let error = find_by_name("hotfix", &repo).expect_err("two worktrees are named `hotfix`");
+        assert!(
+            error.to_string().contains(&unmanaged.display().to_string()),
+            "{error}"
+        );
+
+        let resolved = find_by_name(&unmanaged.display().to_string(), &repo)?
+            .expect("the path names the unmanaged worktree");
+        assert_eq!(resolved.path, unmanaged.canonicalize()?);
+        let managed = repo.worktrees_dir().join("hotfix");
+        let resolved = find_by_name(&managed.display().to_string(), &repo)?
+            .expect("the path names the managed worktree");
+        assert_eq!(resolved.path, managed.canonicalize()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn porcelain_records_describe_every_worktree() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        init_git_repo(&repo_dir)?;
+        run(
+            &repo_dir,
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--allow-empty",
+                "-m",
+                "Initial commit",
+            ],
+        )?;
+        let repo = Repo::discover_from(repo_dir.path())?;
+        CreateCommand::new("feature/porcelain".into(), None).create_without_enter(&repo, true)?;
+        let worktree = repo.worktrees_dir().join("feature/porcelain");
+        fs::write(worktree.join("scratch.txt"), "work in progress")?;
+
+        let records = porcelain_records(&repo)?;
+        assert_eq!(records.len(), 1);
+        let fields = records[0].split('\t').collect::<Vec<_>>();
+        assert_eq!(fields.len(), 10);
+        assert_eq!(fields[..2], ["worktree", "feature/porcelain"]);
+        assert_eq!(
+            fields[2],
+            fs::canonicalize(&worktree)?.display().to_string()
+        );
+        assert_eq!(fields[3], "feature/porcelain");
+        assert_eq!(fields[4].len(), 40);
+        assert_eq!(fields[5..], ["dirty", "", "", "", "managed"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_lines_rejects_unknown_placeholders() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        init_git_repo(&repo_dir)?;
+        let repo = Repo::discover_from(repo_dir.path())?;
+
+        let mut command = ListCommand::new(&repo, GitProvider::default());
+        assert!(command.render_lines(&repo, "{bogus}").is_err());
+
+        Ok(())
+    }
 }
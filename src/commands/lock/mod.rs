@@ -0,0 +1,189 @@
+use color_eyre::eyre::{self, WrapErr};
+use git2::WorktreeLockStatus;
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    Repo,
+    commands::{open::resolve_by_name, rm::find_worktree_name},
+    output,
+};
+
+/// Locks a worktree with `git worktree lock`, so `rm` and `prune` leave it
+/// alone unless forced.
+#[derive(Debug)]
+pub struct LockCommand {
+    name: String,
+    reason: Option<String>,
+}
+
+impl LockCommand {
+    pub fn new(name: String) -> Self {
+        Self { name, reason: None }
+    }
+
+    /// Why the worktree is locked, e.g. "on a removable drive".
+    pub fn with_reason(mut self, reason: Option<String>) -> Self {
+        self.reason = reason.filter(|reason| !reason.trim().is_empty());
+        self
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let (name, worktree) = find_git_worktree(repo, &self.name)?;
+        if let WorktreeLockStatus::Locked(reason) = lock_status(&worktree) {
+            return Err(eyre::eyre!(
+                "worktree `{name}` is already locked{}",
+                describe_reason(reason.as_deref())
+            ));
+        }
+        worktree
+            .lock(self.reason.as_deref())
+            .wrap_err_with(|| eyre::eyre!("failed to lock worktree `{name}`"))?;
+
+        let label = format!(
+            "{}",
+            name.as_str()
+                .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
+        );
+        output::success(tr!("Locked worktree `{}`.", label));
+        Ok(())
+    }
+}
+
+/// Lifts a lock set by [`LockCommand`] or `git worktree lock`.
+#[derive(Debug)]
+pub struct UnlockCommand {
+    name: String,
+}
+
+impl UnlockCommand {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let (name, worktree) = find_git_worktree(repo, &self.name)?;
+        let label = format!(
+            "{}",
+            name.as_str()
+                .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
+        );
+        if lock_status(&worktree) == WorktreeLockStatus::Unlocked {
+            output::info(tr!("Worktree `{}` is not locked.", label));
+            return Ok(());
+        }
+        worktree
+            .unlock()
+            .wrap_err_with(|| eyre::eyre!("failed to unlock worktree `{name}`"))?;
+        output::success(tr!("Unlocked worktree `{}`.", label));
+        Ok(())
+    }
+}
+
+/// Whether `worktree` is locked; unreadable lock files count as unlocked.
+pub(crate) fn lock_status(worktree: &git2::Worktree) -> WorktreeLockStatus {
+    worktree.is_locked().unwrap_or(WorktreeLockStatus::Unlocked)
+}
+
+/// ` (reason)` for messages about a locked worktree, or nothing.
+pub(crate) fn describe_reason(reason: Option<&str>) -> String {
+    reason
+        .map(str::trim)
+        .filter(|reason| !reason.is_empty())
+        .map(|reason| format!(" ({reason})"))
+        .unwrap_or_default()
+}
+
+fn find_git_worktree(repo: &Repo, name: &str) -> color_eyre::Result<(String, git2::Worktree)> {
+    let resolved = resolve_by_name(name, repo)?;
+    let git_repo = repo.git();
+    let git_name = find_worktree_name(git_repo, &resolved.path)?.ok_or_else(|| {
+        eyre::eyre!(
+            "`{}` is not a registered git worktree; run `rsworktree prune` to clean it up",
+            resolved.path.display()
+        )
+    })?;
+    let worktree = git_repo.find_worktree(&git_name).wrap_err_with(|| {
+        eyre::eyre!(
+            "failed to load git worktree metadata for `{}`",
+            resolved.name
+        )
+    })?;
+    Ok((resolved.name, worktree))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{fs, path::Path, process::Command};
+
+    use tempfile::TempDir;
+
+    use crate::commands::{create::CreateCommand, rm::RemoveCommand};
+
+    fn run(dir: &Path, args: &[&str]) -> color_eyre::Result<String> {
+        let output = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()?;
+        if !output.status.success() {
+            return Err(eyre::eyre!(
+                "git {:?} failed: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn init_repo(dir: &Path) -> color_eyre::Result<Repo> {
+        run(dir, &["init"])?;
+        fs::write(dir.join("README.md"), "test")?;
+        run(dir, &["add", "README.md"])?;
+        run(dir, &["commit", "-m", "Initial commit"])?;
+        Repo::discover_from(dir)
+    }
+
+    #[test]
+    fn locked_worktrees_survive_rm_until_unlocked() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let repo = init_repo(dir.path())?;
+        CreateCommand::new("usb".into(), None).create_without_enter(&repo, true)?;
+        let worktree_path = repo.worktrees_dir().join("usb");
+
+        LockCommand::new("usb".into())
+            .with_reason(Some("on a removable drive".into()))
+            .execute(&repo)?;
+        let porcelain = run(dir.path(), &["worktree", "list", "--porcelain"])?;
+        assert!(
+            porcelain.contains("locked on a removable drive"),
+            "{porcelain}"
+        );
+        assert!(LockCommand::new("usb".into()).execute(&repo).is_err());
+
+        let error = RemoveCommand::new("usb".into(), false)
+            .with_quiet(true)
+            .with_spawn_shell(false)
+            .execute(&repo)
+            .expect_err("locked worktree must not be removed");
+        assert!(error.to_string().contains("is locked"), "{error}");
+        assert!(worktree_path.exists());
+
+        UnlockCommand::new("usb".into()).execute(&repo)?;
+        UnlockCommand::new("usb".into()).execute(&repo)?;
+        RemoveCommand::new("usb".into(), false)
+            .with_quiet(true)
+            .with_spawn_shell(false)
+            .execute(&repo)?;
+        assert!(!worktree_path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn describe_reason_skips_blank_reasons() {
+        assert_eq!(describe_reason(Some("usb drive")), " (usb drive)");
+        assert_eq!(describe_reason(Some("  ")), "");
+        assert_eq!(describe_reason(None), "");
+    }
+}
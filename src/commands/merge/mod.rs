@@ -2,11 +2,21 @@ use std::path::{Path, PathBuf};
 
 use color_eyre::eyre::{self, WrapErr};
 use owo_colors::{OwoColorize, Stream};
-use serde::Deserialize;
 
 use crate::{
     GitProvider, Repo,
-    commands::review::{CommandOutput, CommandRunner, SystemCommandRunner},
+    commands::{
+        create::base_config_key,
+        rm::{RemoveCommand, remote_default_branch, upstream_remote},
+    },
+    config::Config,
+    output,
+    process::{CommandOutput, CommandRunner, SystemCommandRunner},
+    provider::{
+        MergeOutcome, MergeStrategy, ProviderBackend, ProviderClient, RequestBackend,
+        gerrit::{self, SshTarget},
+    },
+    webhook::{WebhookEvent, WebhookNotifier},
 };
 
 #[derive(Debug)]
@@ -14,13 +24,31 @@ pub struct MergeCommand<R = SystemCommandRunner> {
     name: String,
     remove_local_branch: bool,
     remove_remote_branch: bool,
+    cleanup: bool,
+    /// Stash uncommitted changes of a checked-out base branch around its pull.
+    autostash: bool,
+    /// `merge_strategy` from `config.toml` when not set.
+    strategy: Option<MergeStrategy>,
     provider: GitProvider,
+    client: ProviderClient,
+    /// The SSH daemon of the Gerrit server, found once the branch is known.
+    gerrit: Option<SshTarget>,
     runner: R,
 }
 
+/// The branch a merged worktree branch was created from and the remote it
+/// is pulled from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BaseBranch {
+    remote: String,
+    name: String,
+}
+
 impl MergeCommand {
-    pub fn new(name: String, provider: GitProvider) -> Self {
+    /// Runs the provider operations through the backend configured for `repo`.
+    pub fn new(name: String, provider: GitProvider, repo: &Repo) -> Self {
         Self::with_runner(name, provider, SystemCommandRunner)
+            .with_backend(ProviderBackend::resolve(repo))
     }
 }
 
@@ -33,11 +61,22 @@ where
             name,
             remove_local_branch: true,
             remove_remote_branch: false,
+            cleanup: false,
+            autostash: false,
+            strategy: None,
             provider,
+            client: provider.client(ProviderBackend::Cli),
+            gerrit: None,
             runner,
         }
     }
 
+    /// Runs the provider operations through `backend` rather than the CLI.
+    pub fn with_backend(mut self, backend: ProviderBackend) -> Self {
+        self.client = self.provider.client(backend);
+        self
+    }
+
     pub fn disable_remove_local(&mut self) {
         self.remove_local_branch = false;
     }
@@ -46,32 +85,94 @@ where
         self.remove_remote_branch = true;
     }
 
+    /// After merging, updates the base branch and removes the worktree
+    /// together with its local branch.
+    pub fn enable_cleanup(&mut self) {
+        self.cleanup = true;
+    }
+
+    /// With [`Self::enable_cleanup`], pulls a checked-out base branch with
+    /// `--autostash` instead of leaving it alone when it has uncommitted changes.
+    pub fn enable_autostash(&mut self) {
+        self.autostash = true;
+    }
+
+    /// Merges with `strategy` instead of the configured one.
+    pub fn set_strategy(&mut self, strategy: MergeStrategy) {
+        self.strategy = Some(strategy);
+    }
+
     pub fn execute(&mut self, repo: &Repo) -> color_eyre::Result<()> {
         let worktree_path = self.ensure_worktree_path(repo)?;
         let branch = self.determine_branch(&worktree_path)?;
         let repo_root = repo.root().to_path_buf();
+        if self.provider == GitProvider::Gerrit {
+            let remote = upstream_remote(repo.git(), &branch);
+            let target = SshTarget::from_remote(repo.git(), &remote).ok_or_else(|| {
+                eyre::eyre!(
+                    "remote `{}` does not point to a Gerrit server over SSH or HTTP",
+                    remote
+                )
+            })?;
+            self.gerrit = Some(target);
+        }
+        let config = repo.config()?;
+        let strategy = match self.strategy {
+            Some(strategy) => strategy,
+            None => config.merge_strategy.unwrap_or_default(),
+        };
+        if !self.provider.supports_merge_strategy(strategy) {
+            return Err(eyre::eyre!(
+                "{} cannot merge with the `{}` strategy",
+                self.provider,
+                strategy
+            ));
+        }
+        self.strategy = Some(strategy);
+        warn_unexpected_target(&config, repo.git(), &branch);
 
         let branch_label = format_with_color(&branch, |text| format!("{}", text.magenta().bold()));
         let path_label = format_with_color(&worktree_path.display().to_string(), |text| {
             format!("{}", text.blue())
         });
-        println!(
+        output::info(tr!(
             "Looking for open {} for `{}` from `{}`...",
             self.provider.merge_request_short(),
             branch_label,
             path_label
-        );
+        ));
+
+        // Read before merging: deleting the branch drops its config section.
+        let base = self
+            .cleanup
+            .then(|| find_base_branch(repo.git(), &branch))
+            .flatten();
 
         match self.find_pull_request(&repo_root, &branch)? {
             Some(pr_number) => {
-                self.merge_pull_request(&repo_root, &branch, &worktree_path, pr_number)
+                self.merge_pull_request(&repo_root, &branch, &worktree_path, pr_number)?;
+                WebhookNotifier::new(&repo.rsworktree_dir(), &repo.name()).notify(
+                    WebhookEvent::Merge,
+                    &self.name,
+                    Some(&branch),
+                    tr!(
+                        "Merged {} #{} for branch `{}`.",
+                        self.provider.merge_request_short(),
+                        pr_number,
+                        branch
+                    ),
+                );
+                if self.cleanup {
+                    self.clean_up(repo, &branch, base.as_ref())?;
+                }
+                Ok(())
             }
             None => {
-                println!(
+                output::info(tr!(
                     "No open {} found for branch `{}`.",
                     self.provider.merge_request_term(),
                     branch_label
-                );
+                ));
                 Ok(())
             }
         }
@@ -118,27 +219,7 @@ where
         repo_path: &Path,
         branch: &str,
     ) -> color_eyre::Result<Option<u64>> {
-        let args = self.provider.build_list_args(branch);
-        let cli_program = self.provider.cli_program();
-
-        let output = self
-            .runner
-            .run(cli_program, repo_path, &args)
-            .wrap_err_with(|| format!("failed to run `{} {} list`", cli_program, if self.provider == GitProvider::GitHub { "pr" } else { "mr" }))?;
-
-        if !output.success {
-            return Err(command_failure(cli_program, &args, &output));
-        }
-
-        let stdout = output.stdout.trim();
-        if stdout.is_empty() {
-            return Ok(None);
-        }
-
-        let prs: Vec<MergeRequestInfo> = serde_json::from_str(stdout)
-            .wrap_err_with(|| format!("failed to parse `{} {} list` output as JSON", cli_program, if self.provider == GitProvider::GitHub { "pr" } else { "mr" }))?;
-
-        Ok(prs.into_iter().next().map(|pr| pr.number()))
+        self.client.list(&mut self.runner, repo_path, branch)
     }
 
     fn merge_pull_request(
@@ -148,49 +229,64 @@ where
         worktree_path: &Path,
         pr_number: u64,
     ) -> color_eyre::Result<()> {
+        // Gerrit submits the patch set pushed from the worktree's `HEAD`.
+        let submit_args = match self.gerrit.clone() {
+            Some(target) => {
+                Some(target.command(gerrit::submit_args(&self.head_revision(worktree_path)?)))
+            }
+            None => None,
+        };
+
         let mut detached_for_deletion = false;
         if self.remove_local_branch {
             self.detach_worktree_head(worktree_path)?;
             detached_for_deletion = true;
         }
-
-        let args = self.provider.build_merge_args(pr_number, self.remove_local_branch);
         let cli_program = self.provider.cli_program();
 
-        let output = self
-            .runner
-            .run(cli_program, repo_path, &args)
-            .wrap_err_with(|| format!("failed to run `{} {} merge`", cli_program, if self.provider == GitProvider::GitHub { "pr" } else { "mr" }))?;
-
-        let branch_delete_failed = self.remove_local_branch && self.provider.is_branch_delete_failure(&output.stderr);
-
-        if !output.success && !branch_delete_failed {
-            if detached_for_deletion {
-                let _ = self.restore_worktree_branch(worktree_path, branch);
+        let merged = match submit_args {
+            Some(args) => self.submit_change(repo_path, &args),
+            None => self.client.merge(
+                &mut self.runner,
+                repo_path,
+                pr_number,
+                self.remove_local_branch,
+                self.strategy.unwrap_or_default(),
+            ),
+        };
+        let outcome = match merged {
+            Ok(outcome) => outcome,
+            Err(error) => {
+                if detached_for_deletion {
+                    let _ = self.restore_worktree_branch(worktree_path, branch);
+                }
+                return Err(error);
             }
-            return Err(command_failure(cli_program, &args, &output));
-        }
+        };
+        let branch_delete_failed = outcome == MergeOutcome::BranchKept;
 
-        let mr_prefix = if self.provider == GitProvider::GitHub { "#" } else { "!" };
+        let mr_prefix = self.provider.request_prefix();
         let pr_label = format_with_color(&format!("{}{}", mr_prefix, pr_number), |text| {
             format!("{}", text.green().bold())
         });
         let branch_label = format_with_color(branch, |text| format!("{}", text.magenta().bold()));
 
+        if self.gerrit.is_some() && self.remove_local_branch {
+            // Gerrit leaves branches alone; the worktree is detached from it.
+            self.delete_local_branch(repo_path, branch)?;
+        }
+
         if branch_delete_failed {
-            let warning = format!(
+            let warning = tr!(
                 "{} {} merged but `{}` could not delete branch `{}`. Leaving the branch intact.",
                 self.provider.merge_request_short(),
                 pr_label,
                 cli_program,
                 branch_label
             );
-            println!(
-                "{}",
-                warning.if_supports_color(Stream::Stdout, |text| format!("{}", text.yellow()))
-            );
+            output::warn(warning);
         } else if self.remove_local_branch {
-            println!("Deleted local branch `{}`.", branch_label);
+            output::success(tr!("Deleted local branch `{}`.", branch_label));
         }
 
         if !self.remove_local_branch || branch_delete_failed {
@@ -200,12 +296,68 @@ where
         if self.remove_remote_branch {
             self.delete_remote_branch(repo_path, branch)?;
         }
-        println!(
+        if self.provider == GitProvider::AzureDevOps {
+            // `az` enables auto-complete; Azure DevOps merges once the policies pass.
+            output::success(tr!(
+                "Set {} {} for branch `{}` to complete automatically.",
+                self.provider.merge_request_short(),
+                pr_label,
+                branch_label
+            ));
+            return Ok(());
+        }
+        output::success(tr!(
             "Merged {} {} for branch `{}`.",
             self.provider.merge_request_short(),
             pr_label,
             branch_label
-        );
+        ));
+        Ok(())
+    }
+
+    /// Submits the Gerrit change over SSH with `args`.
+    fn submit_change(
+        &mut self,
+        repo_path: &Path,
+        args: &[String],
+    ) -> color_eyre::Result<MergeOutcome> {
+        let output = self
+            .runner
+            .run("ssh", repo_path, args)
+            .wrap_err("failed to run `ssh gerrit review --submit`")?;
+
+        if !output.success {
+            return Err(command_failure("ssh", args, &output));
+        }
+
+        Ok(MergeOutcome::Merged)
+    }
+
+    fn head_revision(&mut self, worktree_path: &Path) -> color_eyre::Result<String> {
+        let args = vec!["rev-parse".to_owned(), "HEAD".to_owned()];
+        let output = self
+            .runner
+            .run("git", worktree_path, &args)
+            .wrap_err("failed to determine the worktree commit with `git rev-parse`")?;
+
+        if !output.success {
+            return Err(command_failure("git", &args, &output));
+        }
+
+        Ok(output.stdout.trim().to_owned())
+    }
+
+    fn delete_local_branch(&mut self, repo_path: &Path, branch: &str) -> color_eyre::Result<()> {
+        let args = vec!["branch".to_owned(), "-D".to_owned(), branch.to_owned()];
+        let output = self
+            .runner
+            .run("git", repo_path, &args)
+            .wrap_err("failed to delete local branch with `git branch`")?;
+
+        if !output.success {
+            return Err(command_failure("git", &args, &output));
+        }
+
         Ok(())
     }
 
@@ -253,24 +405,173 @@ where
             branch.to_owned(),
         ];
 
-        let output = self
-            .runner
-            .run("git", repo_path, &args)
-            .wrap_err("failed to delete remote branch with `git push`")?;
+        let runner = &mut self.runner;
+        let output = output::step(tr!("Deleting remote branch `{}`...", branch), || {
+            runner.run("git", repo_path, &args)
+        })
+        .wrap_err("failed to delete remote branch with `git push`")?;
 
         let branch_label = format_with_color(branch, |text| format!("{}", text.magenta().bold()));
 
         if !output.success {
             if remote_branch_already_gone(&output) {
-                println!("Remote branch `{}` was already removed.", branch_label);
+                output::info(tr!("Remote branch `{}` was already removed.", branch_label));
                 return Ok(());
             }
             return Err(command_failure("git", &args, &output));
         }
 
-        println!("Removed remote branch `{}`.", branch_label);
+        output::success(tr!("Removed remote branch `{}`.", branch_label));
         Ok(())
     }
+
+    /// Updates the base branch first, as removing the worktree the caller is
+    /// in ends in a subshell at the repository root.
+    fn clean_up(
+        &mut self,
+        repo: &Repo,
+        branch: &str,
+        base: Option<&BaseBranch>,
+    ) -> color_eyre::Result<()> {
+        match base {
+            Some(base) => self.update_base_branch(repo, base),
+            None => {
+                let branch_label =
+                    format_with_color(branch, |text| format!("{}", text.magenta().bold()));
+                output::info(tr!(
+                    "Base branch of `{}` is unknown; not updating it.",
+                    branch_label
+                ));
+            }
+        }
+
+        RemoveCommand::new(self.name.clone(), false)
+            .with_remove_local_branch(true)
+            .execute(repo)?;
+        Ok(())
+    }
+
+    /// Fast-forwards the base branch from its remote. A failure only warns,
+    /// since the merge itself already went through.
+    ///
+    /// A checked-out base branch with uncommitted changes is left alone, as
+    /// pulling would carry them into the update, unless `--autostash` is given.
+    fn update_base_branch(&mut self, repo: &Repo, base: &BaseBranch) {
+        let checked_out = repo
+            .git()
+            .head()
+            .ok()
+            .and_then(|head| head.shorthand().map(str::to_owned));
+        let base_label = format_with_color(&base.name, |text| format!("{}", text.magenta().bold()));
+        let pulls = checked_out.as_deref() == Some(base.name.as_str());
+        if pulls && !self.autostash {
+            let changed = uncommitted_files(repo.git());
+            if !changed.is_empty() {
+                output::warn(tr!(
+                    "Not updating base branch `{}`: `{}` has uncommitted changes. Commit or stash them, or pass `--autostash`:",
+                    base_label,
+                    repo.root().display()
+                ));
+                for path in changed {
+                    output::item(path);
+                }
+                return;
+            }
+        }
+        let args = base_update_args(checked_out.as_deref(), base, self.autostash);
+
+        let runner = &mut self.runner;
+        let result = output::step(tr!("Updating base branch `{}`...", base.name), || {
+            runner.run("git", repo.root(), &args)
+        })
+        .and_then(|output| {
+            if output.success {
+                Ok(())
+            } else {
+                Err(command_failure("git", &args, &output))
+            }
+        });
+        match result {
+            Ok(()) => output::success(tr!(
+                "Updated base branch `{}` from `{}`.",
+                base_label,
+                base.remote
+            )),
+            Err(err) => output::warn(tr!(
+                "Could not update base branch `{}`: {}",
+                base_label,
+                err
+            )),
+        }
+    }
+}
+
+fn find_base_branch(repo: &git2::Repository, branch: &str) -> Option<BaseBranch> {
+    let remote = upstream_remote(repo, branch);
+    let name = repo
+        .config()
+        .and_then(|config| config.get_string(&base_config_key(branch)))
+        .ok()
+        .or_else(|| remote_default_branch(repo, &remote))?;
+    Some(BaseBranch { remote, name })
+}
+
+/// With `protected_branches` configured, warns when `branch` is protected
+/// itself or was created from a branch that is not, as its pull/merge request
+/// then likely lands somewhere unexpected.
+fn warn_unexpected_target(config: &Config, repo: &git2::Repository, branch: &str) {
+    if config.protected_branches.is_empty() {
+        return;
+    }
+    if let Some(pattern) = config.protected_pattern(branch) {
+        output::warn(tr!(
+            "`{}` is a protected branch (`{}` in `protected_branches`); merging it lands it in another branch.",
+            branch,
+            pattern
+        ));
+    } else if let Some(base) = find_base_branch(repo, branch)
+        && config.protected_pattern(&base.name).is_none()
+    {
+        output::warn(tr!(
+            "`{}` was created from `{}`, which is not in `protected_branches`; make sure it merges into the branch you expect.",
+            branch,
+            base.name
+        ));
+    }
+}
+
+/// Pulls the base branch when the repository root has it checked out and
+/// fast-forwards it with a refspec fetch otherwise.
+fn base_update_args(checked_out: Option<&str>, base: &BaseBranch, autostash: bool) -> Vec<String> {
+    if checked_out == Some(base.name.as_str()) {
+        let mut args = vec!["pull".to_owned(), "--ff-only".to_owned()];
+        if autostash {
+            args.push("--autostash".to_owned());
+        }
+        args.push(base.remote.clone());
+        args.push(base.name.clone());
+        args
+    } else {
+        vec![
+            "fetch".to_owned(),
+            base.remote.clone(),
+            format!("{0}:{0}", base.name),
+        ]
+    }
+}
+
+/// Tracked files of the repository root with staged or unstaged changes.
+/// Untracked files are left out: a pull refuses to overwrite them anyway.
+fn uncommitted_files(repo: &git2::Repository) -> Vec<String> {
+    let mut options = git2::StatusOptions::new();
+    options.include_untracked(false).include_ignored(false);
+    let Ok(statuses) = repo.statuses(Some(&mut options)) else {
+        return Vec::new();
+    };
+    statuses
+        .iter()
+        .filter_map(|entry| entry.path().map(str::to_owned))
+        .collect()
 }
 
 fn remote_branch_already_gone(output: &CommandOutput) -> bool {
@@ -326,24 +627,6 @@ fn format_with_color(value: &str, paint: impl Fn(&str) -> String) -> String {
         .to_string()
 }
 
-/// Represents a pull/merge request from either GitHub or GitLab.
-/// GitHub returns `number`, GitLab returns `iid`.
-#[derive(Debug, Deserialize)]
-struct MergeRequestInfo {
-    /// GitHub uses `number`
-    #[serde(default)]
-    number: Option<u64>,
-    /// GitLab uses `iid`
-    #[serde(default)]
-    iid: Option<u64>,
-}
-
-impl MergeRequestInfo {
-    fn number(&self) -> u64 {
-        self.number.or(self.iid).unwrap_or(0)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -506,6 +789,74 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn merges_with_the_configured_strategy() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        init_git_repo(&repo_dir)?;
+        let repo = Repo::discover_from(repo_dir.path())?;
+        let worktree_path = repo.worktrees_dir().join("feature/test");
+        fs::create_dir_all(&worktree_path)?;
+        fs::write(
+            repo.rsworktree_dir().join("config.toml"),
+            "merge_strategy = \"squash\"\n",
+        )?;
+
+        let mut runner = MockCommandRunner::default();
+        runner.responses.extend([
+            Ok(CommandOutput {
+                stdout: "feature/test\n".into(),
+                stderr: String::new(),
+                success: true,
+                status_code: Some(0),
+            }),
+            Ok(CommandOutput {
+                stdout: "[{\"number\":42}]".into(),
+                stderr: String::new(),
+                success: true,
+                status_code: Some(0),
+            }),
+            Ok(CommandOutput {
+                stdout: String::new(),
+                stderr: String::new(),
+                success: true,
+                status_code: Some(0),
+            }),
+            Ok(CommandOutput {
+                stdout: String::new(),
+                stderr: String::new(),
+                success: true,
+                status_code: Some(0),
+            }),
+        ]);
+
+        let mut command = MergeCommand::with_runner("feature/test".into(), GitProvider::GitHub, runner);
+        command.execute(&repo)?;
+        assert_eq!(
+            command.runner.calls.last().map(|call| call.args.clone()),
+            Some(vec![
+                "pr".into(),
+                "merge".into(),
+                "42".into(),
+                "--squash".into(),
+                "--delete-branch".into()
+            ])
+        );
+
+        let mut runner = MockCommandRunner::default();
+        runner.responses.push_back(Ok(CommandOutput {
+            stdout: "feature/test\n".into(),
+            stderr: String::new(),
+            success: true,
+            status_code: Some(0),
+        }));
+        let mut command = MergeCommand::with_runner("feature/test".into(), GitProvider::Bitbucket, runner);
+        command.set_strategy(MergeStrategy::Rebase);
+        let error = command.execute(&repo).expect_err("bb cannot rebase");
+        assert!(error.to_string().contains("`rebase` strategy"), "{error}");
+        assert_eq!(command.runner.calls.len(), 1);
+        Ok(())
+    }
+
     #[test]
     fn determine_branch_surfaces_git_failures() -> color_eyre::Result<()> {
         let dir = TempDir::new()?;
@@ -1170,4 +1521,62 @@ mod tests {
         };
         assert!(!remote_branch_already_gone(&output_ok));
     }
+
+    #[test]
+    fn base_update_pulls_checked_out_base_and_fetches_otherwise() {
+        let base = BaseBranch {
+            remote: "origin".into(),
+            name: "main".into(),
+        };
+        assert_eq!(
+            base_update_args(Some("main"), &base, false),
+            vec!["pull", "--ff-only", "origin", "main"]
+        );
+        assert_eq!(
+            base_update_args(Some("main"), &base, true),
+            vec!["pull", "--ff-only", "--autostash", "origin", "main"]
+        );
+        assert_eq!(
+            base_update_args(Some("develop"), &base, true),
+            vec!["fetch", "origin", "main:main"]
+        );
+        assert_eq!(
+            base_update_args(None, &base, false),
+            vec!["fetch", "origin", "main:main"]
+        );
+    }
+
+    #[test]
+    fn lists_uncommitted_tracked_files() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        init_git_repo(&repo_dir)?;
+        let repo = Repo::discover_from(repo_dir.path())?;
+        fs::write(repo_dir.path().join("notes.txt"), "untracked")?;
+        assert!(uncommitted_files(repo.git()).is_empty());
+
+        fs::write(repo_dir.path().join("README.md"), "changed")?;
+        assert_eq!(uncommitted_files(repo.git()), ["README.md"]);
+        Ok(())
+    }
+
+    #[test]
+    fn finds_recorded_base_branch() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        init_git_repo(&repo_dir)?;
+        let repo = Repo::discover_from(repo_dir.path())?;
+        assert_eq!(find_base_branch(repo.git(), "feature/test"), None);
+
+        repo.git()
+            .config()?
+            .set_str(&base_config_key("feature/test"), "develop")?;
+        assert_eq!(
+            find_base_branch(repo.git(), "feature/test"),
+            Some(BaseBranch {
+                remote: "origin".into(),
+                name: "develop".into(),
+            })
+        );
+
+        Ok(())
+    }
 }
@@ -1,8 +1,34 @@
+pub mod archive;
 pub mod cd;
+pub mod ci;
+pub mod clean;
+pub mod completions;
+pub mod config;
 pub mod create;
+pub mod diff;
+pub mod doctor;
+pub mod du;
+pub mod editor;
+pub mod export;
+pub mod hooks;
+pub mod info;
 pub mod interactive;
 pub mod list;
+pub mod lock;
 pub mod merge;
+pub mod notes;
 pub mod open;
+pub mod pr;
+pub mod prune;
+pub mod recent;
+pub mod rename;
 pub mod review;
 pub mod rm;
+pub mod shell_init;
+pub mod stash;
+pub mod stats;
+pub mod status;
+pub mod switch;
+pub mod sync;
+pub mod ui;
+pub mod workspace;
@@ -0,0 +1,200 @@
+use std::{
+    env, fs,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    Repo,
+    annotations::{self, Annotations},
+    commands::{info::format_age, list::is_managed, open::resolve_by_name},
+    output,
+    worktree::inspect_worktree,
+};
+
+/// File in the worktrees directory the note is edited in.
+const NOTE_FILE: &str = "NOTE_EDITMSG";
+
+/// Changes requested by `rsworktree notes set`; an empty value clears the field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotesUpdate {
+    pub note: Option<String>,
+    pub pr: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct NotesCommand {
+    name: String,
+}
+
+impl NotesCommand {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+
+    pub fn show(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let (name, branch) = self.worktree(repo)?;
+        let annotations = Annotations::load(repo, &name, &branch)?;
+        print_annotations(&annotations);
+        Ok(())
+    }
+
+    pub fn set(&self, repo: &Repo, update: NotesUpdate) -> color_eyre::Result<()> {
+        if update == NotesUpdate::default() {
+            return Err(eyre::eyre!("nothing to set; pass `--note` and/or `--pr`"));
+        }
+
+        let (name, branch) = self.worktree(repo)?;
+        let annotations = annotations::record(repo, &name, &branch, |annotations| {
+            if let Some(note) = update.note {
+                annotations.note = Some(note).filter(|value| !value.trim().is_empty());
+            }
+            if let Some(pr) = update.pr {
+                annotations.pr = Some(pr).filter(|value| !value.trim().is_empty());
+            }
+        })?;
+        output::success(tr!(
+            "Updated annotations for `{}`.",
+            format_branch(&annotations.branch)
+        ));
+        Ok(())
+    }
+
+    /// Opens the note in `$VISUAL` or `$EDITOR` (`vi` without either) and
+    /// stores what was saved; an empty file clears it.
+    pub fn edit(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let (name, branch) = self.worktree(repo)?;
+        let current = Annotations::load(repo, &name, &branch)?.note;
+        let path = repo.ensure_rsworktree_dir()?.join(NOTE_FILE);
+        fs::write(&path, current.as_deref().unwrap_or_default())
+            .wrap_err_with(|| eyre::eyre!("failed to write `{}`", path.display()))?;
+
+        let editor = ["VISUAL", "EDITOR"]
+            .into_iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.trim().is_empty())
+            .unwrap_or_else(|| "vi".to_owned());
+        let mut parts = shell_words::split(&editor)
+            .wrap_err_with(|| eyre::eyre!("invalid editor command `{editor}`"))?
+            .into_iter();
+        let program = parts.next().unwrap_or_else(|| "vi".to_owned());
+        let status = Command::new(&program)
+            .args(parts)
+            .arg(&path)
+            .status()
+            .wrap_err_with(|| eyre::eyre!("failed to run `{program}`"))?;
+        let edited = fs::read_to_string(&path);
+        let _ = fs::remove_file(&path);
+        if !status.success() {
+            return Err(eyre::eyre!(
+                "`{program}` exited with {status}; note left unchanged"
+            ));
+        }
+        let edited = edited.wrap_err_with(|| eyre::eyre!("failed to read `{}`", path.display()))?;
+
+        let note = Some(edited.trim().to_owned()).filter(|note| !note.is_empty());
+        if note == current {
+            output::info(tr!("Note for `{}` unchanged.", format_branch(&branch)));
+            return Ok(());
+        }
+        annotations::record(repo, &name, &branch, |annotations| annotations.note = note)?;
+        output::success(tr!("Updated the note for `{}`.", format_branch(&branch)));
+        Ok(())
+    }
+
+    /// The name and branch of the worktree whose annotations are edited.
+    fn worktree(&self, repo: &Repo) -> color_eyre::Result<(String, String)> {
+        let resolved = resolve_by_name(&self.name, repo)?;
+        if !is_managed(&repo.worktrees_dir(), &resolved.path) {
+            return Err(eyre::eyre!(
+                "worktree `{}` is not under `.rsworktree`; annotations are kept for managed worktrees only",
+                resolved.name
+            ));
+        }
+        let branch = inspect_worktree(&resolved.path)?.branch.ok_or_else(|| {
+            eyre::eyre!(
+                "worktree `{}` has a detached HEAD; annotations are shared per branch",
+                resolved.name
+            )
+        })?;
+        Ok((resolved.name, branch))
+    }
+}
+
+#[derive(Debug)]
+pub struct NotesSyncCommand {
+    remote: String,
+}
+
+impl NotesSyncCommand {
+    pub fn new(remote: String) -> Self {
+        Self { remote }
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let summary = annotations::sync(repo, &self.remote)?;
+        if summary.total == 0 {
+            output::info(tr!("No annotations to sync."));
+            return Ok(());
+        }
+        output::success(tr!(
+            "Synced annotations for {} branch(es) with `{}`; {} updated locally.",
+            summary.total,
+            self.remote,
+            summary.updated
+        ));
+        Ok(())
+    }
+}
+
+fn print_annotations(annotations: &Annotations) {
+    output::heading(tr!(
+        "Annotations for `{}`",
+        format_branch(&annotations.branch)
+    ));
+    if annotations.is_empty() {
+        output::field("", tr!("none recorded"));
+        return;
+    }
+    let created = describe_created(annotations);
+    let fields = [
+        (tr!("Note"), &annotations.note),
+        (tr!("PR"), &annotations.pr),
+        (tr!("Template"), &annotations.template),
+        (tr!("Issue"), &annotations.issue),
+        (tr!("Base"), &annotations.base),
+        (tr!("Created"), &created),
+    ];
+    for (label, value) in fields {
+        if let Some(value) = value {
+            output::field(&label, value);
+        }
+    }
+}
+
+/// When and by whom the worktree was created, e.g. "3d ago by Ada".
+pub(crate) fn describe_created(annotations: &Annotations) -> Option<String> {
+    annotations.created_at.map(|created_at| {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let age = format_age(now.saturating_sub(created_at));
+        match &annotations.creator {
+            Some(creator) => tr!("{} by {}", age, creator),
+            None => age,
+        }
+    })
+}
+
+fn format_branch(branch: &str) -> String {
+    format!(
+        "{}",
+        branch.if_supports_color(Stream::Stdout, |text| {
+            format!("{}", text.magenta().bold())
+        })
+    )
+}
@@ -0,0 +1,192 @@
+//! Which worktrees were opened when, kept in
+//! `.rsworktree/state/open-history.json` for `open -`, `switch -` and
+//! `rsworktree recent`.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use serde::{Deserialize, Serialize};
+
+use super::{ResolvedWorktree, find_by_name, resolve_by_name};
+use crate::Repo;
+
+const STATE_DIR: &str = "state";
+const HISTORY_FILE: &str = "open-history.json";
+/// Opens kept; the oldest are dropped as new ones are recorded.
+const HISTORY_LIMIT: usize = 500;
+/// The name standing for the worktree opened before the last one, like `cd -`.
+pub(crate) const PREVIOUS: &str = "-";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenRecord {
+    worktree: String,
+    /// Seconds since the Unix epoch.
+    opened_at: u64,
+}
+
+/// A worktree from the history, with when it was last opened and how often.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RecentWorktree {
+    pub(crate) name: String,
+    pub(crate) last_opened: u64,
+    pub(crate) opens: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct OpenHistory {
+    path: PathBuf,
+    records: Vec<OpenRecord>,
+}
+
+impl OpenHistory {
+    /// Loads the history of the repository; a missing or unreadable file is
+    /// an empty history.
+    pub(crate) fn load(rsworktree_dir: &Path) -> Self {
+        let path = rsworktree_dir.join(STATE_DIR).join(HISTORY_FILE);
+        let records = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self { path, records }
+    }
+
+    /// Appends an open of `worktree` now and writes the history back.
+    pub(crate) fn record(&mut self, worktree: &str) -> color_eyre::Result<()> {
+        self.push(worktree, now());
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| eyre::eyre!("failed to create `{}`", parent.display()))?;
+        }
+        let contents = serde_json::to_string_pretty(&self.records)
+            .wrap_err("failed to serialize the open history")?;
+        fs::write(&self.path, contents)
+            .wrap_err_with(|| eyre::eyre!("failed to write `{}`", self.path.display()))
+    }
+
+    fn push(&mut self, worktree: &str, opened_at: u64) {
+        self.records.push(OpenRecord {
+            worktree: worktree.to_owned(),
+            opened_at,
+        });
+        let excess = self.records.len().saturating_sub(HISTORY_LIMIT);
+        self.records.drain(..excess);
+    }
+
+    /// Every worktree in the history once, most recently opened first.
+    pub(crate) fn recent(&self) -> Vec<RecentWorktree> {
+        let mut opens = HashMap::<&str, usize>::new();
+        for record in &self.records {
+            *opens.entry(record.worktree.as_str()).or_default() += 1;
+        }
+        let mut recent = Vec::<RecentWorktree>::new();
+        for record in self.records.iter().rev() {
+            if recent.iter().any(|entry| entry.name == record.worktree) {
+                continue;
+            }
+            recent.push(RecentWorktree {
+                name: record.worktree.clone(),
+                last_opened: record.opened_at,
+                opens: opens[record.worktree.as_str()],
+            });
+        }
+        recent
+    }
+
+    /// The worktrees opened before the last one, most recent first: where
+    /// `open -` goes, falling back to older ones that were removed since.
+    fn previous(&self) -> Vec<String> {
+        self.recent()
+            .into_iter()
+            .skip(1)
+            .map(|entry| entry.name)
+            .collect()
+    }
+}
+
+/// Resolves `-` to the worktree opened before the last one, and any other
+/// name as [`resolve_by_name`] does.
+pub(crate) fn resolve_with_history(
+    name: &str,
+    repo: &Repo,
+) -> color_eyre::Result<ResolvedWorktree> {
+    if name != PREVIOUS {
+        return resolve_by_name(name, repo);
+    }
+    for previous in OpenHistory::load(&repo.rsworktree_dir()).previous() {
+        if let Ok(Some(resolved)) = find_by_name(&previous, repo) {
+            return Ok(resolved);
+        }
+    }
+    Err(eyre::eyre!(
+        "no previously opened worktree to go back to; open one with `rsworktree worktree open <name>` first"
+    ))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn recent_lists_each_worktree_once_with_its_opens() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let mut history = OpenHistory::load(dir.path());
+        history.push("feature/a", 10);
+        history.push("feature/b", 20);
+        history.push("feature/a", 30);
+        history.push("feature/c", 40);
+
+        assert_eq!(
+            history.recent(),
+            [
+                RecentWorktree {
+                    name: "feature/c".into(),
+                    last_opened: 40,
+                    opens: 1,
+                },
+                RecentWorktree {
+                    name: "feature/a".into(),
+                    last_opened: 30,
+                    opens: 2,
+                },
+                RecentWorktree {
+                    name: "feature/b".into(),
+                    last_opened: 20,
+                    opens: 1,
+                },
+            ]
+        );
+        assert_eq!(history.previous(), ["feature/a", "feature/b"]);
+        Ok(())
+    }
+
+    #[test]
+    fn history_is_saved_and_capped() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let mut history = OpenHistory::load(dir.path());
+        for index in 0..HISTORY_LIMIT {
+            history.push(&format!("old/{index}"), index as u64);
+        }
+        history.record("feature/a")?;
+
+        let history = OpenHistory::load(dir.path());
+        assert_eq!(history.records.len(), HISTORY_LIMIT);
+        assert_eq!(history.records[0].worktree, "old/1");
+        assert_eq!(history.recent()[0].name, "feature/a");
+        Ok(())
+    }
+}
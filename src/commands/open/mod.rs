@@ -1,5 +1,9 @@
+pub(crate) mod history;
+pub(crate) mod multiplexer;
+mod picker;
+
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 
 use color_eyre::eyre::{self, WrapErr};
 use owo_colors::{OwoColorize, Stream};
@@ -7,330 +11,250 @@ use owo_colors::{OwoColorize, Stream};
 use crate::{
     Repo,
     commands::list::{find_worktrees, format_worktree},
-    editor::{launch_worktree, resolve_editor_preference, EditorPreferenceResolution},
-    telemetry::{EditorLaunchStatus, log_editor_launch_attempt},
+    editor::{
+        EditorKind, EditorPreferenceResolution, LaunchOutcome, LaunchWait, apply_devcontainer,
+        launch_worktree, record_launch, resolve_editor, resolve_worktree_editor_preference,
+        url_template, wait_flag,
+    },
+    output,
+    telemetry::EditorLaunchStatus,
 };
 
+use history::{OpenHistory, resolve_with_history};
+use multiplexer::{EditorInvocation, Multiplexer};
+
 pub struct OpenCommand {
     name: Option<String>,
     path: Option<PathBuf>,
+    interactive: bool,
+    shell: bool,
+    wait: bool,
 }
 
 impl OpenCommand {
     pub fn new(name: Option<String>, path: Option<PathBuf>) -> Self {
-        Self { name, path }
+        Self {
+            name,
+            path,
+            interactive: false,
+            shell: false,
+            wait: false,
+        }
+    }
+
+    /// Offer a fuzzy picker when neither a name nor a path is given and the
+    /// command runs in a terminal.
+    pub fn with_interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
+    }
+
+    /// Open a shell in the worktree instead of the editor, as the `terminal`
+    /// editor kind does.
+    pub fn with_shell(mut self, shell: bool) -> Self {
+        self.shell = shell;
+        self
+    }
+
+    /// Block until the editor is closed and fail when it exits with an
+    /// error. GUI editors get their wait flag, such as `code --wait`; in tmux
+    /// the editor pane is watched instead.
+    pub fn with_wait(mut self, wait: bool) -> Self {
+        self.wait = wait;
+        self
     }
 
     pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
-        let resolved = self.resolve_target(repo)?;
+        let Some(resolved) = self.resolve_target(repo)? else {
+            output::info(tr!("No worktree selected."));
+            return Ok(());
+        };
+        self.open(repo, &resolved)?;
+        // The history only serves `open -` and `recent`; the worktree is open either way.
+        let _ = OpenHistory::load(&repo.rsworktree_dir()).record(&resolved.name);
+        Ok(())
+    }
 
-        // Check if we're in a tmux session
-        if std::env::var("TMUX").is_ok() {
-            return self.execute_tmux(repo, &resolved);
+    fn open(&self, repo: &Repo, resolved: &ResolvedWorktree) -> color_eyre::Result<()> {
+        if self.wait && opens_url(repo, &resolved.name)? {
+            return Err(eyre::eyre!(
+                "the editor of `{}` opens a URL, which `--wait` cannot wait for",
+                resolved.name
+            ));
         }
 
-        self.execute_direct(repo, &resolved)
+        let terminal = terminal_preference(repo, &resolved.name)?;
+        if self.shell || terminal.is_some() {
+            return self.execute_shell(repo, resolved, terminal == Some(true));
+        }
+
+        // Inside tmux, Zellij, kitty or WezTerm, give the worktree its own
+        // session or tab; a URL editor opens outside the terminal anyway. Where
+        // the editor pane cannot be watched, `--wait` runs the editor right here.
+        if let Some(multiplexer) = multiplexer::detect()
+            && (!self.wait || multiplexer.can_wait())
+            && !opens_url(repo, &resolved.name)?
+        {
+            return self.execute_multiplexed(repo, resolved, multiplexer.as_ref());
+        }
+
+        self.execute_direct(repo, resolved)
     }
 
     fn execute_direct(&self, repo: &Repo, resolved: &ResolvedWorktree) -> color_eyre::Result<()> {
-        let outcome = match launch_worktree(repo, &resolved.name, &resolved.path, false) {
-            Ok(outcome) => {
-                log_editor_launch_attempt(
-                    &resolved.name,
-                    &resolved.path,
-                    outcome.status,
-                    &outcome.message,
-                );
-                outcome
-            }
-            Err(error) => {
-                log_editor_launch_attempt(
-                    &resolved.name,
-                    &resolved.path,
-                    EditorLaunchStatus::ConfigurationError,
-                    &error.to_string(),
-                );
-                return Err(error);
-            }
+        let wait = if self.wait {
+            LaunchWait::Editor
+        } else {
+            LaunchWait::Detached
         };
+        let outcome = launch_worktree(repo, &resolved.name, &resolved.path, wait)?;
 
         match outcome.status {
             EditorLaunchStatus::Success => {
-                println!(
+                output::success(tr!(
                     "Opened `{}` at `{}`.",
                     resolved.name,
                     resolved.path.display()
-                );
-                println!("{}", outcome.message);
+                ));
+                output::info(&outcome.message);
                 Ok(())
             }
             EditorLaunchStatus::PreferenceMissing => {
-                println!("{}", outcome.message);
+                output::info(&outcome.message);
                 Ok(())
             }
             _ => {
-                eprintln!("{}", outcome.message);
+                output::error(&outcome.message);
                 Err(eyre::eyre!(outcome.message))
             }
         }
     }
 
-    fn execute_tmux(&self, repo: &Repo, resolved: &ResolvedWorktree) -> color_eyre::Result<()> {
-        let project_name = repo
-            .root()
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown");
+    /// Opens a multiplexer window with a shell, else starts the configured
+    /// terminal emulator, else prints how to get there.
+    fn execute_shell(
+        &self,
+        repo: &Repo,
+        resolved: &ResolvedWorktree,
+        has_emulator: bool,
+    ) -> color_eyre::Result<()> {
+        if let Some(multiplexer) = multiplexer::detect() {
+            return multiplexer.open_shell(&window_name(repo, resolved), resolved);
+        }
+        if has_emulator {
+            return self.execute_direct(repo, resolved);
+        }
+        output::info(tr!(
+            "Run `cd {}` to enter `{}`.",
+            shell_words::quote(&resolved.path.display().to_string()),
+            resolved.name
+        ));
+        Ok(())
+    }
 
-        let session_name = format!("{}/{}", project_name, resolved.name);
+    fn execute_multiplexed(
+        &self,
+        repo: &Repo,
+        resolved: &ResolvedWorktree,
+        multiplexer: &dyn Multiplexer,
+    ) -> color_eyre::Result<()> {
+        let window_name = window_name(repo, resolved);
 
-        // Get the editor command
-        let editor_command = match resolve_editor_preference(repo)? {
-            EditorPreferenceResolution::Found(pref) => {
-                pref.command.to_string_lossy().into_owned()
-            }
+        let mut pref = match resolve_editor(repo, &resolved.name)? {
+            EditorPreferenceResolution::Found(pref) => pref,
             EditorPreferenceResolution::Missing(reason) => {
                 return Err(eyre::eyre!("No editor configured: {:?}", reason));
             }
         };
-
-        // Check if we're in the worktree session
-        let current_session = Command::new("tmux")
-            .args(["display-message", "-p", "#{session_name}"])
-            .output()
-            .wrap_err("failed to get current tmux session name")?;
-
-        let current_session_name = String::from_utf8_lossy(&current_session.stdout)
-            .trim()
-            .to_string();
-
-        if current_session_name == session_name {
-            // We're in the worktree session, check for editor pane
-            if let Some(pane_id) = self.find_editor_pane(&editor_command)? {
-                // Select the existing editor pane
-                let status = Command::new("tmux")
-                    .args(["select-pane", "-t", &pane_id])
-                    .status()
-                    .wrap_err("failed to select tmux pane")?;
-
-                if !status.success() {
-                    return Err(eyre::eyre!("failed to select editor pane"));
-                }
-
-                let pane_label = format_with_color(&pane_id, |text| {
-                    format!("{}", text.cyan().bold())
-                });
-                println!("Switched to editor pane `{}`", pane_label);
-                return Ok(());
-            }
-
-            // No editor pane found, create a new one
-            return self.create_editor_pane(repo, resolved, &editor_command);
+        apply_devcontainer(repo, &mut pref, &resolved.path);
+        let mut editor = EditorInvocation::new(&pref);
+        if self.wait
+            && let Some(flag) = wait_flag(&pref)
+        {
+            editor.args.push(flag.to_owned());
         }
 
-        // Check if the worktree session exists
-        let list_output = Command::new("tmux")
-            .args(["list-sessions", "-F", "#{session_name}"])
-            .output()
-            .wrap_err("failed to list tmux sessions")?;
-
-        let existing_sessions = String::from_utf8_lossy(&list_output.stdout);
-        let session_exists = existing_sessions
-            .lines()
-            .any(|line| line.trim() == session_name);
-
-        if session_exists {
-            // Switch to the session first
-            let status = Command::new("tmux")
-                .args(["switch-client", "-t", &session_name])
-                .status()
-                .wrap_err("failed to switch to tmux session")?;
-
-            if !status.success() {
-                return Err(eyre::eyre!("failed to switch to tmux session `{}`", session_name));
-            }
-
-            // Now check for editor pane in that session
-            if let Some(pane_id) = self.find_editor_pane_in_session(&session_name, &editor_command)? {
-                let status = Command::new("tmux")
-                    .args(["select-pane", "-t", &pane_id])
-                    .status()
-                    .wrap_err("failed to select tmux pane")?;
-
-                if !status.success() {
-                    return Err(eyre::eyre!("failed to select editor pane"));
+        let mut opened = Ok(());
+        record_launch(
+            repo,
+            &resolved.name,
+            &resolved.path,
+            &pref,
+            multiplexer.launch_method(),
+            || {
+                opened = if self.wait {
+                    multiplexer.open_and_wait(&window_name, resolved, &editor)
+                } else {
+                    multiplexer.open(&window_name, resolved, &editor)
+                };
+                match &opened {
+                    Ok(()) => LaunchOutcome {
+                        status: EditorLaunchStatus::Success,
+                        message: String::new(),
+                    },
+                    Err(error) => LaunchOutcome {
+                        status: EditorLaunchStatus::SpawnError,
+                        message: error.to_string(),
+                    },
                 }
+            },
+        );
+        opened
+    }
 
-                let session_label = format_with_color(&session_name, |text| {
-                    format!("{}", text.cyan().bold())
-                });
-                println!("Switched to editor in session `{}`", session_label);
-                return Ok(());
-            }
-
-            // No editor pane, create one
-            return self.create_editor_pane(repo, resolved, &editor_command);
+    fn resolve_target(&self, repo: &Repo) -> color_eyre::Result<Option<ResolvedWorktree>> {
+        if let Some(path) = &self.path {
+            return resolve_by_path(path, repo).map(Some);
         }
-
-        // Session doesn't exist, create it with editor
-        let editor_args = match resolve_editor_preference(repo)? {
-            EditorPreferenceResolution::Found(pref) => {
-                pref.args.iter()
-                    .map(|a| a.to_string_lossy().into_owned())
-                    .collect::<Vec<_>>()
-            }
-            _ => Vec::new(),
-        };
-
-        let mut cmd_parts = vec![editor_command.clone()];
-        cmd_parts.extend(editor_args);
-        cmd_parts.push(resolved.path.display().to_string());
-        let full_cmd = cmd_parts.join(" ");
-
-        // Create new session (detached) with editor
-        let status = Command::new("tmux")
-            .args([
-                "new-session",
-                "-d",
-                "-s",
-                &session_name,
-                "-c",
-                &resolved.path.display().to_string(),
-                &full_cmd,
-            ])
-            .status()
-            .wrap_err("failed to create tmux session with editor")?;
-
-        if !status.success() {
-            return Err(eyre::eyre!("failed to create tmux session `{}`", session_name));
+        if let Some(name) = &self.name {
+            return resolve_with_history(name, repo).map(Some);
         }
 
-        // Switch to the new session
-        let status = Command::new("tmux")
-            .args(["switch-client", "-t", &session_name])
-            .status()
-            .wrap_err("failed to switch to tmux session")?;
-
-        if !status.success() {
-            return Err(eyre::eyre!("failed to switch to tmux session `{}`", session_name));
+        let is_terminal = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+        if !self.interactive || !is_terminal {
+            return Err(eyre::eyre!("worktree name or --path must be provided"));
         }
-
-        let session_label = format_with_color(&session_name, |text| {
-            format!("{}", text.cyan().bold())
-        });
-        println!("Created session `{}` with editor", session_label);
-        Ok(())
-    }
-
-    fn find_editor_pane(&self, editor_command: &str) -> color_eyre::Result<Option<String>> {
-        // List panes in current session with their commands
-        let output = Command::new("tmux")
-            .args(["list-panes", "-s", "-F", "#{pane_id}:#{pane_current_command}"])
-            .output()
-            .wrap_err("failed to list tmux panes")?;
-
-        let panes = String::from_utf8_lossy(&output.stdout);
-        for line in panes.lines() {
-            if let Some((pane_id, cmd)) = line.split_once(':') {
-                if cmd.contains(editor_command) || self.is_editor_command(cmd) {
-                    return Ok(Some(pane_id.to_string()));
-                }
-            }
+        let worktrees_dir = repo.ensure_worktrees_dir()?;
+        let entries = find_worktrees(repo)?
+            .iter()
+            .map(|path| format_worktree(path))
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            return Err(eyre::eyre!(
+                "no worktrees found in `{}`; create one with `rsworktree create <name>`",
+                worktrees_dir.display()
+            ));
         }
-
-        Ok(None)
-    }
-
-    fn find_editor_pane_in_session(
-        &self,
-        session_name: &str,
-        editor_command: &str,
-    ) -> color_eyre::Result<Option<String>> {
-        let output = Command::new("tmux")
-            .args([
-                "list-panes",
-                "-s",
-                "-t",
-                session_name,
-                "-F",
-                "#{pane_id}:#{pane_current_command}",
-            ])
-            .output()
-            .wrap_err("failed to list tmux panes")?;
-
-        let panes = String::from_utf8_lossy(&output.stdout);
-        for line in panes.lines() {
-            if let Some((pane_id, cmd)) = line.split_once(':') {
-                if cmd.contains(editor_command) || self.is_editor_command(cmd) {
-                    return Ok(Some(pane_id.to_string()));
-                }
-            }
+        match picker::pick(entries)? {
+            Some(name) => resolve_by_name(&name, repo).map(Some),
+            None => Ok(None),
         }
-
-        Ok(None)
     }
+}
 
-    fn is_editor_command(&self, cmd: &str) -> bool {
-        let editors = ["vim", "nvim", "nano", "emacs", "code", "cursor", "webstorm", "rider", "idea"];
-        editors.iter().any(|e| cmd.contains(e))
-    }
-
-    fn create_editor_pane(
-        &self,
-        repo: &Repo,
-        resolved: &ResolvedWorktree,
-        editor_command: &str,
-    ) -> color_eyre::Result<()> {
-        // Get editor args if any
-        let editor_args = match resolve_editor_preference(repo)? {
-            EditorPreferenceResolution::Found(pref) => {
-                pref.args.iter()
-                    .map(|a| a.to_string_lossy().into_owned())
-                    .collect::<Vec<_>>()
-            }
-            _ => Vec::new(),
-        };
-
-        // Build the full command
-        let mut cmd_parts = vec![editor_command.to_string()];
-        cmd_parts.extend(editor_args);
-        cmd_parts.push(resolved.path.display().to_string());
-        let full_cmd = cmd_parts.join(" ");
-
-        // Create a new pane with the editor
-        let status = Command::new("tmux")
-            .args([
-                "split-window",
-                "-h",
-                "-c",
-                &resolved.path.display().to_string(),
-                &full_cmd,
-            ])
-            .status()
-            .wrap_err("failed to create tmux pane with editor")?;
-
-        if !status.success() {
-            return Err(eyre::eyre!("failed to create editor pane"));
-        }
-
-        let editor_label = format_with_color(editor_command, |text| {
-            format!("{}", text.cyan().bold())
-        });
-        println!("Opened `{}` in new pane", editor_label);
-        Ok(())
-    }
+/// Whether the editor of `worktree` is a URL template such as `vscode://file/{path}`.
+fn opens_url(repo: &Repo, worktree: &str) -> color_eyre::Result<bool> {
+    Ok(matches!(
+        resolve_worktree_editor_preference(repo, worktree)?,
+        EditorPreferenceResolution::Found(preference) if url_template(&preference.command).is_some()
+    ))
+}
 
-    fn resolve_target(&self, repo: &Repo) -> color_eyre::Result<ResolvedWorktree> {
-        if let Some(path) = &self.path {
-            return resolve_by_path(path, repo);
+/// Whether `worktree` opens a shell; `Some(true)` when a terminal emulator is set.
+fn terminal_preference(repo: &Repo, worktree: &str) -> color_eyre::Result<Option<bool>> {
+    Ok(match resolve_worktree_editor_preference(repo, worktree)? {
+        EditorPreferenceResolution::Found(preference)
+            if preference.kind == EditorKind::Terminal =>
+        {
+            Some(!preference.command.is_empty())
         }
+        _ => None,
+    })
+}
 
-        let name = self
-            .name
-            .as_ref()
-            .ok_or_else(|| eyre::eyre!("worktree name or --path must be provided"))?;
-        resolve_by_name(name, repo)
-    }
+/// The multiplexer window of a worktree, see [`multiplexer::window_name`].
+fn window_name(repo: &Repo, resolved: &ResolvedWorktree) -> String {
+    multiplexer::window_name(repo, &resolved.name, &resolved.path)
 }
 
 fn format_with_color(value: &str, paint: impl Fn(&str) -> String) -> String {
@@ -339,19 +263,43 @@ fn format_with_color(value: &str, paint: impl Fn(&str) -> String) -> String {
         .to_string()
 }
 
-struct ResolvedWorktree {
-    name: String,
-    path: PathBuf,
+#[derive(Debug)]
+pub(crate) struct ResolvedWorktree {
+    pub(crate) name: String,
+    pub(crate) path: PathBuf,
 }
 
-fn resolve_by_name(name: &str, repo: &Repo) -> color_eyre::Result<ResolvedWorktree> {
-    let worktrees_dir = repo.ensure_worktrees_dir()?;
-    let entries = find_worktrees(&worktrees_dir)?;
+pub(crate) fn resolve_by_name(name: &str, repo: &Repo) -> color_eyre::Result<ResolvedWorktree> {
+    repo.ensure_worktrees_dir()?;
+    find_by_name(name, repo)?.ok_or_else(|| {
+        eyre::eyre!(
+            "worktree `{}` not found. Run `rsworktree ls` to view available worktrees.",
+            name
+        )
+    })
+}
+
+/// Like [`resolve_by_name`], but returns `None` instead of an error when no
+/// worktree matches `name`. Ambiguous names are still an error.
+///
+/// Unmanaged worktrees are named after their directory, which a managed
+/// worktree may share; the path of a worktree always picks that one.
+pub(crate) fn find_by_name(
+    name: &str,
+    repo: &Repo,
+) -> color_eyre::Result<Option<ResolvedWorktree>> {
+    let worktrees_dir = repo.worktrees_dir();
+    let entries = find_worktrees(repo)?;
+    let by_path = Path::new(name).canonicalize().ok();
 
     let mut matches = Vec::new();
 
     for rel in entries {
         let display = format_worktree(&rel);
+        if by_path.is_some() && worktrees_dir.join(&rel).canonicalize().ok() == by_path {
+            matches = vec![(display, rel)];
+            break;
+        }
         let file_name = rel
             .file_name()
             .map(|component| component.to_string_lossy().into_owned());
@@ -366,20 +314,24 @@ fn resolve_by_name(name: &str, repo: &Repo) -> color_eyre::Result<ResolvedWorktr
     }
 
     if matches.is_empty() {
-        return Err(eyre::eyre!(
-            "worktree `{}` not found. Run `rsworktree ls` to view available worktrees.",
-            name
-        ));
+        return Ok(None);
     }
 
     if matches.len() > 1 {
+        // Unmanaged worktrees are listed by path, which tells them apart.
         let names = matches
             .iter()
-            .map(|(display, _)| display.as_str())
+            .map(|(display, rel)| {
+                if rel.is_absolute() {
+                    rel.display().to_string()
+                } else {
+                    display.clone()
+                }
+            })
             .collect::<Vec<_>>()
             .join(", ");
         return Err(eyre::eyre!(
-            "worktree identifier `{}` is ambiguous. Matches: {}",
+            "worktree identifier `{}` is ambiguous. Matches: {}; pass the full name or the path of the worktree",
             name,
             names
         ));
@@ -400,10 +352,10 @@ fn resolve_by_name(name: &str, repo: &Repo) -> color_eyre::Result<ResolvedWorktr
         .canonicalize()
         .wrap_err_with(|| eyre::eyre!("failed to resolve `{}`", absolute.display()))?;
 
-    Ok(ResolvedWorktree {
+    Ok(Some(ResolvedWorktree {
         name: display,
         path: canonical,
-    })
+    }))
 }
 
 fn resolve_by_path(path: &Path, repo: &Repo) -> color_eyre::Result<ResolvedWorktree> {
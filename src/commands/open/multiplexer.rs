@@ -0,0 +1,1212 @@
+//! Terminal multiplexers `open` can place the editor in: one tmux session or
+//! Zellij, kitty or WezTerm tab per worktree, reused on later `open` calls,
+//! or on Windows a new Windows Terminal tab.
+
+use std::{cell::RefCell, ffi::OsStr, path::Path, process::Command, thread, time::Duration};
+
+use color_eyre::eyre::{self, WrapErr};
+use owo_colors::OwoColorize;
+use serde_json::Value;
+
+use super::{ResolvedWorktree, format_with_color};
+use crate::{
+    Repo,
+    editor::{EditorKind, EditorPreference, devcontainer_args, resolve_program},
+    output,
+    process::{CommandOutput, CommandRunner, SystemCommandRunner, exit_status},
+    telemetry::EditorLaunchMethod,
+};
+
+/// How windows are named unless `window_name` is set in `config.toml`.
+pub const DEFAULT_WINDOW_NAME: &str = "{project}/{worktree}";
+
+/// How often `open --wait` checks whether the editor pane is still open.
+const PANE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Editor commands recognised when looking for a pane that already runs an editor.
+const KNOWN_EDITORS: [&str; 9] = [
+    "vim", "nvim", "nano", "emacs", "code", "cursor", "webstorm", "rider", "idea",
+];
+
+/// The configured editor and its arguments, without the worktree path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EditorInvocation {
+    pub(crate) command: String,
+    pub(crate) args: Vec<String>,
+    pub(crate) kind: EditorKind,
+}
+
+impl EditorInvocation {
+    pub(crate) fn new(preference: &EditorPreference) -> Self {
+        Self {
+            command: resolve_program(&preference.command)
+                .to_string_lossy()
+                .into_owned(),
+            args: preference
+                .args
+                .iter()
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect(),
+            kind: preference.kind,
+        }
+    }
+
+    /// Program and arguments opening `worktree`.
+    fn argv(&self, worktree: &ResolvedWorktree) -> Vec<String> {
+        let mut argv = vec![self.command.clone()];
+        argv.extend(self.args.iter().cloned());
+        match self.kind {
+            EditorKind::DevContainer => argv.extend(
+                devcontainer_args(OsStr::new(&self.command), &worktree.path)
+                    .iter()
+                    .map(|arg| arg.to_string_lossy().into_owned()),
+            ),
+            _ => argv.push(worktree.path.display().to_string()),
+        }
+        argv
+    }
+
+    /// The invocation as one shell command line.
+    pub(crate) fn command_line(&self, worktree: &ResolvedWorktree) -> String {
+        self.argv(worktree).join(" ")
+    }
+}
+
+/// The name of the tmux session, Zellij tab or Windows Terminal tab of the
+/// worktree `name` at `path`, from `window_name` in `config.toml`.
+pub(crate) fn window_name(repo: &Repo, name: &str, path: &Path) -> String {
+    let template = repo.config().ok().and_then(|config| config.window_name);
+    let project = repo
+        .root()
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown");
+    render_window_name(template.as_deref(), project, name, path)
+}
+
+/// Fills `{project}`, `{worktree}` and `{branch}` into `template`.
+///
+/// tmux turns `:` and `.` in session names into `_`, so they are replaced
+/// here as well to find the session again.
+pub(crate) fn render_window_name(
+    template: Option<&str>,
+    project: &str,
+    name: &str,
+    path: &Path,
+) -> String {
+    let template = template
+        .filter(|template| !template.trim().is_empty())
+        .unwrap_or(DEFAULT_WINDOW_NAME);
+    let mut rendered = template
+        .replace("{project}", project)
+        .replace("{worktree}", name);
+    if rendered.contains("{branch}") {
+        let branch = git2::Repository::open(path)
+            .ok()
+            .and_then(|repo| {
+                let head = repo.head().ok()?;
+                head.is_branch()
+                    .then(|| head.shorthand().map(str::to_owned))
+                    .flatten()
+            })
+            .unwrap_or_else(|| name.to_owned());
+        rendered = rendered.replace("{branch}", &branch);
+    }
+    rendered.replace([':', '.'], "_")
+}
+
+/// A terminal multiplexer that gives every worktree its own window.
+pub(crate) trait Multiplexer {
+    /// Shows the editor for `worktree` in the window named `window_name`,
+    /// creating the window or the editor when they do not exist yet.
+    fn open(
+        &self,
+        window_name: &str,
+        worktree: &ResolvedWorktree,
+        editor: &EditorInvocation,
+    ) -> color_eyre::Result<()>;
+
+    /// Whether [`Multiplexer::open_and_wait`] can tell when the editor closes.
+    fn can_wait(&self) -> bool {
+        false
+    }
+
+    /// Like [`Multiplexer::open`], but returns once the editor's pane has closed.
+    fn open_and_wait(
+        &self,
+        _window_name: &str,
+        _worktree: &ResolvedWorktree,
+        _editor: &EditorInvocation,
+    ) -> color_eyre::Result<()> {
+        Err(eyre::eyre!("this multiplexer cannot wait for the editor"))
+    }
+
+    /// Opens a new window named `window_name` running a shell in `worktree`.
+    fn open_shell(&self, window_name: &str, worktree: &ResolvedWorktree) -> color_eyre::Result<()>;
+
+    /// How an editor opened by [`Multiplexer::open`] is recorded.
+    fn launch_method(&self) -> EditorLaunchMethod;
+}
+
+/// The multiplexer `rsworktree` runs in, if any.
+pub(crate) fn detect() -> Option<Box<dyn Multiplexer>> {
+    detect_with(|name| std::env::var_os(name).is_some(), cfg!(windows))
+}
+
+fn detect_with(is_set: impl Fn(&str) -> bool, windows: bool) -> Option<Box<dyn Multiplexer>> {
+    if windows {
+        // tmux, Zellij and kitty do not run natively on Windows.
+        return if is_windows_terminal(&is_set) {
+            Some(Box::new(WindowsTerminal::new()))
+        } else if is_set("WEZTERM_PANE") {
+            Some(Box::new(WezTerm::new()))
+        } else {
+            None
+        };
+    }
+    // A multiplexer runs inside the terminal emulator, so it wins over it.
+    if is_set("TMUX") {
+        Some(Box::new(Tmux::new()))
+    } else if is_set("ZELLIJ") {
+        Some(Box::new(Zellij::new()))
+    } else if is_set("KITTY_WINDOW_ID") {
+        Some(Box::new(Kitty::new()))
+    } else if is_set("WEZTERM_PANE") {
+        Some(Box::new(WezTerm::new()))
+    } else {
+        None
+    }
+}
+
+/// Whether the process runs inside Windows Terminal, which sets `WT_SESSION`.
+pub(crate) fn is_windows_terminal(is_set: impl Fn(&str) -> bool) -> bool {
+    is_set("WT_SESSION")
+}
+
+fn is_editor_command(cmd: &str) -> bool {
+    KNOWN_EDITORS.iter().any(|editor| cmd.contains(editor))
+}
+
+/// One tmux session per worktree, with the editor in one of its panes.
+#[derive(Debug)]
+pub(crate) struct Tmux<R = SystemCommandRunner> {
+    runner: RefCell<R>,
+}
+
+impl Tmux {
+    pub(crate) fn new() -> Self {
+        Self::with_runner(SystemCommandRunner)
+    }
+}
+
+impl<R: CommandRunner> Multiplexer for Tmux<R> {
+    fn open(
+        &self,
+        session_name: &str,
+        worktree: &ResolvedWorktree,
+        editor: &EditorInvocation,
+    ) -> color_eyre::Result<()> {
+        self.open_editor(session_name, worktree, editor).map(|_| ())
+    }
+
+    fn can_wait(&self) -> bool {
+        true
+    }
+
+    fn open_and_wait(
+        &self,
+        session_name: &str,
+        worktree: &ResolvedWorktree,
+        editor: &EditorInvocation,
+    ) -> color_eyre::Result<()> {
+        let pane_id = self.open_editor(session_name, worktree, editor)?;
+        output::info(tr!("Waiting for pane `{}` to close...", pane_id));
+        while self.pane_exists(&pane_id) {
+            thread::sleep(PANE_POLL_INTERVAL);
+        }
+        Ok(())
+    }
+
+    fn open_shell(&self, window_name: &str, worktree: &ResolvedWorktree) -> color_eyre::Result<()> {
+        let created = self
+            .runner
+            .borrow_mut()
+            .run(
+                "tmux",
+                Path::new("."),
+                &shell_window_args(window_name, worktree),
+            )
+            .wrap_err("failed to create tmux window")?;
+        if !created.success {
+            return Err(eyre::eyre!(
+                "failed to create tmux window `{}`",
+                window_name
+            ));
+        }
+
+        let window_label = format_with_color(window_name, |text| format!("{}", text.cyan().bold()));
+        output::success(tr!("Opened a shell in window `{}`", window_label));
+        Ok(())
+    }
+
+    fn launch_method(&self) -> EditorLaunchMethod {
+        EditorLaunchMethod::Tmux
+    }
+}
+
+impl<R: CommandRunner> Tmux<R> {
+    pub(crate) fn with_runner(runner: R) -> Self {
+        Self {
+            runner: RefCell::new(runner),
+        }
+    }
+
+    /// Runs `tmux` with `args` in the current directory.
+    fn tmux(&self, args: &[&str]) -> color_eyre::Result<CommandOutput> {
+        let args = args.iter().map(|arg| (*arg).to_owned()).collect::<Vec<_>>();
+        self.runner.borrow_mut().run("tmux", Path::new("."), &args)
+    }
+
+    /// Shows the editor for `worktree` in the session `session_name` and
+    /// returns the id of its pane.
+    fn open_editor(
+        &self,
+        session_name: &str,
+        worktree: &ResolvedWorktree,
+        editor: &EditorInvocation,
+    ) -> color_eyre::Result<String> {
+        let editor_command = editor.command.as_str();
+
+        // Check if we're in the worktree session
+        let current_session = self
+            .tmux(&["display-message", "-p", "#{session_name}"])
+            .wrap_err("failed to get current tmux session name")?;
+
+        let current_session_name = current_session.stdout.trim();
+
+        if current_session_name == session_name {
+            // We're in the worktree session, check for editor pane
+            if let Some(pane_id) = self.find_editor_pane(None, editor_command)? {
+                self.select_pane(&pane_id)?;
+
+                let pane_label =
+                    format_with_color(&pane_id, |text| format!("{}", text.cyan().bold()));
+                output::success(tr!("Switched to editor pane `{}`", pane_label));
+                return Ok(pane_id);
+            }
+
+            // No editor pane found, create a new one
+            return self.create_editor_pane(worktree, editor);
+        }
+
+        // Check if the worktree session exists
+        let list_output = self
+            .tmux(&["list-sessions", "-F", "#{session_name}"])
+            .wrap_err("failed to list tmux sessions")?;
+
+        let session_exists = list_output
+            .stdout
+            .lines()
+            .any(|line| line.trim() == session_name);
+
+        if session_exists {
+            // Switch to the session first
+            self.switch_client(session_name)?;
+
+            // Now check for editor pane in that session
+            if let Some(pane_id) = self.find_editor_pane(Some(session_name), editor_command)? {
+                self.select_pane(&pane_id)?;
+
+                let session_label =
+                    format_with_color(session_name, |text| format!("{}", text.cyan().bold()));
+                output::success(tr!("Switched to editor in session `{}`", session_label));
+                return Ok(pane_id);
+            }
+
+            // No editor pane, create one
+            return self.create_editor_pane(worktree, editor);
+        }
+
+        // Create new session (detached) with editor
+        let created = self
+            .tmux(&[
+                "new-session",
+                "-d",
+                "-P",
+                "-F",
+                "#{pane_id}",
+                "-s",
+                session_name,
+                "-c",
+                &worktree.path.display().to_string(),
+                &editor.command_line(worktree),
+            ])
+            .wrap_err("failed to create tmux session with editor")?;
+
+        if !created.success {
+            return Err(eyre::eyre!(
+                "failed to create tmux session `{}`",
+                session_name
+            ));
+        }
+
+        // Switch to the new session
+        self.switch_client(session_name)?;
+
+        let session_label =
+            format_with_color(session_name, |text| format!("{}", text.cyan().bold()));
+        output::success(tr!("Created session `{}` with editor", session_label));
+        Ok(created.stdout.trim().to_owned())
+    }
+
+    /// Whether the pane `pane_id` is still open; it closes when its command exits.
+    fn pane_exists(&self, pane_id: &str) -> bool {
+        self.tmux(&["display-message", "-p", "-t", pane_id, "#{pane_id}"])
+            .is_ok_and(|output| output.success && output.stdout.trim() == pane_id)
+    }
+
+    /// Finds a pane running the editor in `session`, or in the current session.
+    fn find_editor_pane(
+        &self,
+        session: Option<&str>,
+        editor_command: &str,
+    ) -> color_eyre::Result<Option<String>> {
+        let mut args = vec!["list-panes", "-s"];
+        if let Some(session) = session {
+            args.extend(["-t", session]);
+        }
+        args.extend(["-F", "#{pane_id}:#{pane_current_command}"]);
+        let output = self.tmux(&args).wrap_err("failed to list tmux panes")?;
+
+        for line in output.stdout.lines() {
+            if let Some((pane_id, cmd)) = line.split_once(':')
+                && (cmd.contains(editor_command) || is_editor_command(cmd))
+            {
+                return Ok(Some(pane_id.to_string()));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn select_pane(&self, pane_id: &str) -> color_eyre::Result<()> {
+        let selected = self
+            .tmux(&["select-pane", "-t", pane_id])
+            .wrap_err("failed to select tmux pane")?;
+
+        if !selected.success {
+            return Err(eyre::eyre!("failed to select editor pane"));
+        }
+        Ok(())
+    }
+
+    fn switch_client(&self, session_name: &str) -> color_eyre::Result<()> {
+        let switched = self
+            .tmux(&["switch-client", "-t", session_name])
+            .wrap_err("failed to switch to tmux session")?;
+
+        if !switched.success {
+            return Err(eyre::eyre!(
+                "failed to switch to tmux session `{}`",
+                session_name
+            ));
+        }
+        Ok(())
+    }
+
+    fn create_editor_pane(
+        &self,
+        worktree: &ResolvedWorktree,
+        editor: &EditorInvocation,
+    ) -> color_eyre::Result<String> {
+        // Create a new pane with the editor
+        let created = self
+            .tmux(&[
+                "split-window",
+                "-h",
+                "-P",
+                "-F",
+                "#{pane_id}",
+                "-c",
+                &worktree.path.display().to_string(),
+                &editor.command_line(worktree),
+            ])
+            .wrap_err("failed to create tmux pane with editor")?;
+
+        if !created.success {
+            return Err(eyre::eyre!("failed to create editor pane"));
+        }
+
+        let editor_label =
+            format_with_color(&editor.command, |text| format!("{}", text.cyan().bold()));
+        output::success(tr!("Opened `{}` in new pane", editor_label));
+        Ok(created.stdout.trim().to_owned())
+    }
+}
+
+/// One Zellij tab per worktree in the current session, driven by `zellij action`.
+///
+/// Zellij cannot report which command a pane runs, so an existing tab is
+/// assumed to hold the editor and is only focused.
+#[derive(Debug)]
+pub(crate) struct Zellij<R = SystemCommandRunner> {
+    runner: RefCell<R>,
+}
+
+impl Zellij {
+    pub(crate) fn new() -> Self {
+        Self::with_runner(SystemCommandRunner)
+    }
+}
+
+impl<R: CommandRunner> Zellij<R> {
+    pub(crate) fn with_runner(runner: R) -> Self {
+        Self {
+            runner: RefCell::new(runner),
+        }
+    }
+
+    fn run(&self, args: &[String]) -> color_eyre::Result<()> {
+        let output = self
+            .runner
+            .borrow_mut()
+            .status(Command::new("zellij").args(args))
+            .wrap_err_with(|| eyre::eyre!("failed to run `zellij {}`", args[..2].join(" ")))?;
+        if !output.success {
+            return Err(eyre::eyre!(
+                "`zellij {}` exited with {}",
+                args[..2].join(" "),
+                exit_status(&output)
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<R: CommandRunner> Multiplexer for Zellij<R> {
+    fn open(
+        &self,
+        tab_name: &str,
+        worktree: &ResolvedWorktree,
+        editor: &EditorInvocation,
+    ) -> color_eyre::Result<()> {
+        let tab_label = format_with_color(tab_name, |text| format!("{}", text.cyan().bold()));
+
+        let tabs = self
+            .runner
+            .borrow_mut()
+            .run(
+                "zellij",
+                Path::new("."),
+                &["action", "query-tab-names"].map(String::from),
+            )
+            .wrap_err("failed to list zellij tabs")?;
+        if !tabs.success {
+            return Err(eyre::eyre!(
+                "failed to list zellij tabs: {}",
+                tabs.stderr.trim()
+            ));
+        }
+
+        if tabs.stdout.lines().any(|line| line.trim() == tab_name) {
+            self.run(&go_to_tab_args(tab_name))?;
+            output::success(tr!("Switched to editor in tab `{}`", tab_label));
+            return Ok(());
+        }
+
+        self.run(&new_tab_args(tab_name, worktree))?;
+        self.run(&editor_pane_args(worktree, editor))?;
+        output::success(tr!("Created tab `{}` with editor", tab_label));
+        Ok(())
+    }
+
+    /// A new tab starts with the default shell in its `--cwd`.
+    fn open_shell(&self, tab_name: &str, worktree: &ResolvedWorktree) -> color_eyre::Result<()> {
+        self.run(&new_tab_args(tab_name, worktree))?;
+        let tab_label = format_with_color(tab_name, |text| format!("{}", text.cyan().bold()));
+        output::success(tr!("Opened a shell in tab `{}`", tab_label));
+        Ok(())
+    }
+
+    fn launch_method(&self) -> EditorLaunchMethod {
+        EditorLaunchMethod::Zellij
+    }
+}
+
+/// A tmux window in the current session whose shell starts in the worktree.
+pub(crate) fn shell_window_args(window_name: &str, worktree: &ResolvedWorktree) -> Vec<String> {
+    vec![
+        "new-window".to_owned(),
+        "-n".to_owned(),
+        window_name.to_owned(),
+        "-c".to_owned(),
+        worktree.path.display().to_string(),
+    ]
+}
+
+fn go_to_tab_args(tab_name: &str) -> Vec<String> {
+    vec![
+        "action".to_owned(),
+        "go-to-tab-name".to_owned(),
+        tab_name.to_owned(),
+    ]
+}
+
+fn new_tab_args(tab_name: &str, worktree: &ResolvedWorktree) -> Vec<String> {
+    vec![
+        "action".to_owned(),
+        "new-tab".to_owned(),
+        "--name".to_owned(),
+        tab_name.to_owned(),
+        "--cwd".to_owned(),
+        worktree.path.display().to_string(),
+    ]
+}
+
+/// Runs the editor in a new pane of the focused tab; it closes with the editor.
+fn editor_pane_args(worktree: &ResolvedWorktree, editor: &EditorInvocation) -> Vec<String> {
+    let mut args = vec![
+        "run".to_owned(),
+        "--close-on-exit".to_owned(),
+        "--name".to_owned(),
+        editor.command.clone(),
+        "--cwd".to_owned(),
+        worktree.path.display().to_string(),
+        "--".to_owned(),
+    ];
+    args.extend(editor.argv(worktree));
+    args
+}
+
+/// One kitty tab per worktree, driven by `kitty @`, which needs
+/// `allow_remote_control` in `kitty.conf`.
+///
+/// Like with Zellij, an existing tab is assumed to hold the editor and is
+/// only focused.
+#[derive(Debug)]
+pub(crate) struct Kitty<R = SystemCommandRunner> {
+    runner: RefCell<R>,
+}
+
+impl Kitty {
+    pub(crate) fn new() -> Self {
+        Self::with_runner(SystemCommandRunner)
+    }
+}
+
+impl<R: CommandRunner> Kitty<R> {
+    pub(crate) fn with_runner(runner: R) -> Self {
+        Self {
+            runner: RefCell::new(runner),
+        }
+    }
+
+    fn run(&self, args: &[String]) -> color_eyre::Result<CommandOutput> {
+        self.runner
+            .borrow_mut()
+            .run("kitty", Path::new("."), args)
+            .wrap_err_with(|| eyre::eyre!("failed to run `kitty {}`", args[..2].join(" ")))
+    }
+
+    fn launch(&self, args: &[String]) -> color_eyre::Result<()> {
+        let output = self.run(args)?;
+        if !output.success {
+            return Err(eyre::eyre!(
+                "`kitty {}` failed; is `allow_remote_control` enabled in `kitty.conf`? {}",
+                args[..2].join(" "),
+                output.stderr.trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<R: CommandRunner> Multiplexer for Kitty<R> {
+    fn open(
+        &self,
+        tab_name: &str,
+        worktree: &ResolvedWorktree,
+        editor: &EditorInvocation,
+    ) -> color_eyre::Result<()> {
+        let tab_label = format_with_color(tab_name, |text| format!("{}", text.cyan().bold()));
+
+        // `focus-tab` fails when no tab matches.
+        let focused = self.run(&kitty_focus_tab_args(tab_name))?;
+        if focused.success {
+            output::success(tr!("Switched to editor in tab `{}`", tab_label));
+            return Ok(());
+        }
+
+        self.launch(&kitty_launch_args(
+            tab_name,
+            worktree,
+            &editor.argv(worktree),
+        ))?;
+        output::success(tr!("Created tab `{}` with editor", tab_label));
+        Ok(())
+    }
+
+    fn open_shell(&self, tab_name: &str, worktree: &ResolvedWorktree) -> color_eyre::Result<()> {
+        self.launch(&kitty_launch_args(tab_name, worktree, &[]))?;
+        let tab_label = format_with_color(tab_name, |text| format!("{}", text.cyan().bold()));
+        output::success(tr!("Opened a shell in tab `{}`", tab_label));
+        Ok(())
+    }
+
+    fn launch_method(&self) -> EditorLaunchMethod {
+        EditorLaunchMethod::Kitty
+    }
+}
+
+/// One WezTerm tab per worktree in the current window, driven by `wezterm cli`.
+///
+/// Tabs are found by the title set when they were spawned; an existing one
+/// is assumed to hold the editor and is only activated.
+#[derive(Debug)]
+pub(crate) struct WezTerm<R = SystemCommandRunner> {
+    runner: RefCell<R>,
+}
+
+impl WezTerm {
+    pub(crate) fn new() -> Self {
+        Self::with_runner(SystemCommandRunner)
+    }
+}
+
+impl<R: CommandRunner> WezTerm<R> {
+    pub(crate) fn with_runner(runner: R) -> Self {
+        Self {
+            runner: RefCell::new(runner),
+        }
+    }
+
+    /// Runs `wezterm cli <args>` and returns its standard output.
+    fn run(&self, args: &[String]) -> color_eyre::Result<String> {
+        let args = ["cli".to_owned()]
+            .into_iter()
+            .chain(args.iter().cloned())
+            .collect::<Vec<_>>();
+        let output = self
+            .runner
+            .borrow_mut()
+            .run("wezterm", Path::new("."), &args)
+            .wrap_err_with(|| eyre::eyre!("failed to run `wezterm cli {}`", args[1]))?;
+        if !output.success {
+            return Err(eyre::eyre!(
+                "`wezterm cli {}` failed: {}",
+                args[1],
+                output.stderr.trim()
+            ));
+        }
+        Ok(output.stdout)
+    }
+
+    /// Spawns a tab in the current WezTerm window and titles it `tab_name`.
+    fn spawn_tab(
+        &self,
+        tab_name: &str,
+        worktree: &ResolvedWorktree,
+        command: &[String],
+    ) -> color_eyre::Result<()> {
+        let pane_id = self.run(&wezterm_spawn_args(worktree, command))?;
+        self.run(&[
+            "set-tab-title".to_owned(),
+            "--pane-id".to_owned(),
+            pane_id.trim().to_owned(),
+            tab_name.to_owned(),
+        ])?;
+        Ok(())
+    }
+}
+
+impl<R: CommandRunner> Multiplexer for WezTerm<R> {
+    fn open(
+        &self,
+        tab_name: &str,
+        worktree: &ResolvedWorktree,
+        editor: &EditorInvocation,
+    ) -> color_eyre::Result<()> {
+        let tab_label = format_with_color(tab_name, |text| format!("{}", text.cyan().bold()));
+
+        let panes = self.run(&["list", "--format", "json"].map(String::from))?;
+        if let Some(tab_id) = find_wezterm_tab(&panes, tab_name) {
+            self.run(&["activate-tab".to_owned(), "--tab-id".to_owned(), tab_id])?;
+            output::success(tr!("Switched to editor in tab `{}`", tab_label));
+            return Ok(());
+        }
+
+        self.spawn_tab(tab_name, worktree, &editor.argv(worktree))?;
+        output::success(tr!("Created tab `{}` with editor", tab_label));
+        Ok(())
+    }
+
+    fn open_shell(&self, tab_name: &str, worktree: &ResolvedWorktree) -> color_eyre::Result<()> {
+        self.spawn_tab(tab_name, worktree, &[])?;
+        let tab_label = format_with_color(tab_name, |text| format!("{}", text.cyan().bold()));
+        output::success(tr!("Opened a shell in tab `{}`", tab_label));
+        Ok(())
+    }
+
+    fn launch_method(&self) -> EditorLaunchMethod {
+        EditorLaunchMethod::WezTerm
+    }
+}
+
+/// A new Windows Terminal tab per `open`, started through `wt.exe`.
+///
+/// Windows Terminal cannot list its tabs, so existing tabs are never reused.
+#[derive(Debug)]
+pub(crate) struct WindowsTerminal<R = SystemCommandRunner> {
+    runner: RefCell<R>,
+}
+
+impl WindowsTerminal {
+    pub(crate) fn new() -> Self {
+        Self::with_runner(SystemCommandRunner)
+    }
+}
+
+impl<R: CommandRunner> WindowsTerminal<R> {
+    pub(crate) fn with_runner(runner: R) -> Self {
+        Self {
+            runner: RefCell::new(runner),
+        }
+    }
+}
+
+impl<R: CommandRunner> Multiplexer for WindowsTerminal<R> {
+    fn open(
+        &self,
+        tab_name: &str,
+        worktree: &ResolvedWorktree,
+        editor: &EditorInvocation,
+    ) -> color_eyre::Result<()> {
+        open_windows_terminal_tab(
+            &mut *self.runner.borrow_mut(),
+            tab_name,
+            &worktree.path,
+            &editor.argv(worktree),
+        )?;
+        let tab_label = format_with_color(tab_name, |text| format!("{}", text.cyan().bold()));
+        output::success(tr!("Created tab `{}` with editor", tab_label));
+        Ok(())
+    }
+
+    fn open_shell(&self, tab_name: &str, worktree: &ResolvedWorktree) -> color_eyre::Result<()> {
+        open_windows_terminal_tab(
+            &mut *self.runner.borrow_mut(),
+            tab_name,
+            &worktree.path,
+            &[],
+        )?;
+        let tab_label = format_with_color(tab_name, |text| format!("{}", text.cyan().bold()));
+        output::success(tr!("Opened a shell in tab `{}`", tab_label));
+        Ok(())
+    }
+
+    fn launch_method(&self) -> EditorLaunchMethod {
+        EditorLaunchMethod::WindowsTerminal
+    }
+}
+
+/// Opens a tab titled `title` in the current Windows Terminal window, running
+/// `command` in `dir`, or the default profile's shell when `command` is empty.
+pub(crate) fn open_windows_terminal_tab(
+    runner: &mut impl CommandRunner,
+    title: &str,
+    dir: &Path,
+    command: &[String],
+) -> color_eyre::Result<()> {
+    let args = windows_terminal_tab_args(title, dir, command);
+    let output = runner
+        .status(Command::new("wt.exe").args(&args))
+        .wrap_err("failed to run `wt.exe new-tab`")?;
+    if !output.success {
+        return Err(eyre::eyre!(
+            "`wt.exe new-tab` exited with {}",
+            exit_status(&output)
+        ));
+    }
+    Ok(())
+}
+
+fn windows_terminal_tab_args(title: &str, dir: &Path, command: &[String]) -> Vec<String> {
+    // `wt` separates its own commands with `;`, so literal ones are escaped.
+    let escape = |arg: &str| arg.replace(';', "\\;");
+    let mut args = vec![
+        "--window".to_owned(),
+        "0".to_owned(),
+        "new-tab".to_owned(),
+        "--title".to_owned(),
+        escape(title),
+        "--startingDirectory".to_owned(),
+        escape(&dir.display().to_string()),
+    ];
+    args.extend(command.iter().map(|arg| escape(arg)));
+    args
+}
+
+/// Focuses the tab titled exactly `tab_name`; kitty matches titles as regular expressions.
+fn kitty_focus_tab_args(tab_name: &str) -> Vec<String> {
+    let escaped = tab_name
+        .chars()
+        .flat_map(|c| {
+            let escape = c.is_ascii_punctuation() && !matches!(c, '/' | '-' | '_');
+            escape.then_some('\\').into_iter().chain([c])
+        })
+        .collect::<String>();
+    vec![
+        "@".to_owned(),
+        "focus-tab".to_owned(),
+        "--match".to_owned(),
+        format!("title:^{escaped}$"),
+    ]
+}
+
+/// A new kitty tab running `command` in the worktree, or the shell when
+/// `command` is empty.
+fn kitty_launch_args(
+    tab_name: &str,
+    worktree: &ResolvedWorktree,
+    command: &[String],
+) -> Vec<String> {
+    let mut args = vec![
+        "@".to_owned(),
+        "launch".to_owned(),
+        "--type=tab".to_owned(),
+        "--tab-title".to_owned(),
+        tab_name.to_owned(),
+        "--cwd".to_owned(),
+        worktree.path.display().to_string(),
+    ];
+    args.extend(command.iter().cloned());
+    args
+}
+
+/// The id of the tab titled `tab_name` in the output of `wezterm cli list --format json`.
+fn find_wezterm_tab(panes: &str, tab_name: &str) -> Option<String> {
+    let panes: Value = serde_json::from_str(panes).ok()?;
+    panes
+        .as_array()?
+        .iter()
+        .find(|pane| pane["tab_title"].as_str() == Some(tab_name))
+        .and_then(|pane| pane["tab_id"].as_u64())
+        .map(|id| id.to_string())
+}
+
+fn wezterm_spawn_args(worktree: &ResolvedWorktree, command: &[String]) -> Vec<String> {
+    let mut args = vec![
+        "spawn".to_owned(),
+        "--cwd".to_owned(),
+        worktree.path.display().to_string(),
+    ];
+    if !command.is_empty() {
+        args.push("--".to_owned());
+        args.extend(command.iter().cloned());
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use crate::process::ScriptedRunner;
+
+    fn worktree() -> ResolvedWorktree {
+        ResolvedWorktree {
+            name: "feature/login".into(),
+            path: PathBuf::from("/repo/.rsworktree/feature/login"),
+        }
+    }
+
+    fn editor() -> EditorInvocation {
+        EditorInvocation {
+            command: "nvim".into(),
+            args: vec!["-O".into()],
+            kind: EditorKind::Editor,
+        }
+    }
+
+    #[test]
+    fn detects_multiplexer_from_environment() {
+        let detected = |vars: &'static [&'static str], windows: bool| {
+            detect_with(|name| vars.contains(&name), windows).is_some()
+        };
+        assert!(detected(&["TMUX"], false));
+        assert!(detected(&["ZELLIJ"], false));
+        assert!(detected(&["KITTY_WINDOW_ID"], false));
+        assert!(detected(&["WEZTERM_PANE"], false));
+        assert!(detected(&["WEZTERM_PANE"], true));
+        assert!(!detected(&["KITTY_WINDOW_ID"], true));
+        let launch_method = |vars: &'static [&'static str]| {
+            detect_with(|name| vars.contains(&name), false).map(|found| found.launch_method())
+        };
+        assert_eq!(
+            launch_method(&["KITTY_WINDOW_ID", "TMUX"]),
+            Some(EditorLaunchMethod::Tmux)
+        );
+        assert!(!detected(&["WT_SESSION"], false));
+        assert!(!detected(&[], false));
+        assert!(detected(&["WT_SESSION"], true));
+        assert!(!detected(&["TMUX"], true));
+    }
+
+    #[test]
+    fn window_names_follow_the_template() {
+        let path = Path::new("/nonexistent/feature/login");
+        assert_eq!(
+            render_window_name(None, "app", "feature/login", path),
+            "app/feature/login"
+        );
+        assert_eq!(
+            render_window_name(Some("{worktree}"), "app", "feature/login", path),
+            "feature/login"
+        );
+        assert_eq!(
+            render_window_name(Some("{project}:{branch}"), "my.app", "login", path),
+            "my_app_login"
+        );
+    }
+
+    #[test]
+    fn editor_invocation_appends_worktree_path() {
+        assert_eq!(
+            editor().command_line(&worktree()),
+            "nvim -O /repo/.rsworktree/feature/login"
+        );
+    }
+
+    #[test]
+    fn builds_tmux_shell_window_args() {
+        assert_eq!(
+            shell_window_args("app/feature/login", &worktree()),
+            vec![
+                "new-window",
+                "-n",
+                "app/feature/login",
+                "-c",
+                "/repo/.rsworktree/feature/login"
+            ]
+        );
+    }
+
+    #[test]
+    fn tmux_creates_a_session_or_reuses_its_editor_pane() -> color_eyre::Result<()> {
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::success("main\n"))
+            .push(CommandOutput::success("main\n"))
+            .push(CommandOutput::success("%7\n"))
+            .push(CommandOutput::success(""));
+        let tmux = Tmux::with_runner(runner.clone());
+        tmux.open("app/feature_login", &worktree(), &editor())?;
+        let subcommands = |runner: &ScriptedRunner| {
+            runner
+                .calls()
+                .into_iter()
+                .map(|call| call.args[0].clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            subcommands(&runner),
+            [
+                "display-message",
+                "list-sessions",
+                "new-session",
+                "switch-client"
+            ]
+        );
+        assert_eq!(
+            runner.calls()[2].args[8..],
+            [
+                "/repo/.rsworktree/feature/login",
+                "nvim -O /repo/.rsworktree/feature/login"
+            ]
+        );
+
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::success("app/feature_login\n"))
+            .push(CommandOutput::success("%1:zsh\n%2:nvim\n"))
+            .push(CommandOutput::success(""));
+        Tmux::with_runner(runner.clone()).open("app/feature_login", &worktree(), &editor())?;
+        assert_eq!(
+            subcommands(&runner),
+            ["display-message", "list-panes", "select-pane"]
+        );
+        assert_eq!(runner.calls()[2].args, ["select-pane", "-t", "%2"]);
+        Ok(())
+    }
+
+    #[test]
+    fn tab_multiplexers_reuse_a_tab_or_create_one() -> color_eyre::Result<()> {
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::success("Tab #1\napp/feature_login\n"))
+            .push(CommandOutput::success(""));
+        Zellij::with_runner(runner.clone()).open("app/feature_login", &worktree(), &editor())?;
+        let calls = runner.calls();
+        assert_eq!(calls[0].args, ["action", "query-tab-names"]);
+        assert_eq!(calls[1].program, "zellij");
+        assert_eq!(
+            calls[1].args,
+            ["action", "go-to-tab-name", "app/feature_login"]
+        );
+
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::failure(1, "no matching tabs"))
+            .push(CommandOutput::success(""));
+        Kitty::with_runner(runner.clone()).open("app/feature_login", &worktree(), &editor())?;
+        let calls = runner.calls();
+        assert_eq!(calls[0].args[1], "focus-tab");
+        assert_eq!(calls[1].args[..3], ["@", "launch", "--type=tab"]);
+        assert_eq!(
+            calls[1].args[7..],
+            ["nvim", "-O", "/repo/.rsworktree/feature/login"]
+        );
+
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::success("[]"))
+            .push(CommandOutput::success("12\n"))
+            .push(CommandOutput::success(""));
+        WezTerm::with_runner(runner.clone()).open("app/feature_login", &worktree(), &editor())?;
+        let calls = runner.calls();
+        assert_eq!(calls[0].args, ["cli", "list", "--format", "json"]);
+        assert_eq!(
+            calls[2].args,
+            [
+                "cli",
+                "set-tab-title",
+                "--pane-id",
+                "12",
+                "app/feature_login"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn builds_windows_terminal_tab_args() {
+        assert_eq!(
+            windows_terminal_tab_args(
+                "app/feature/login",
+                &worktree().path,
+                &editor().argv(&worktree())
+            ),
+            vec![
+                "--window",
+                "0",
+                "new-tab",
+                "--title",
+                "app/feature/login",
+                "--startingDirectory",
+                "/repo/.rsworktree/feature/login",
+                "nvim",
+                "-O",
+                "/repo/.rsworktree/feature/login"
+            ]
+        );
+        let args = windows_terminal_tab_args("a", Path::new("C:/a;b"), &["a;b".to_owned()]);
+        assert_eq!(args[6..], ["C:/a\\;b", "a\\;b"]);
+    }
+
+    #[test]
+    fn builds_zellij_tab_and_editor_pane_args() {
+        assert_eq!(
+            new_tab_args("app/feature/login", &worktree()),
+            vec![
+                "action",
+                "new-tab",
+                "--name",
+                "app/feature/login",
+                "--cwd",
+                "/repo/.rsworktree/feature/login"
+            ]
+        );
+        assert_eq!(
+            go_to_tab_args("app/feature/login"),
+            vec!["action", "go-to-tab-name", "app/feature/login"]
+        );
+        assert_eq!(
+            editor_pane_args(&worktree(), &editor()),
+            vec![
+                "run",
+                "--close-on-exit",
+                "--name",
+                "nvim",
+                "--cwd",
+                "/repo/.rsworktree/feature/login",
+                "--",
+                "nvim",
+                "-O",
+                "/repo/.rsworktree/feature/login"
+            ]
+        );
+    }
+    #[test]
+    fn builds_kitty_tab_args() {
+        assert_eq!(
+            kitty_focus_tab_args("my.app/feature(1)"),
+            vec![
+                "@",
+                "focus-tab",
+                "--match",
+                "title:^my\\.app/feature\\(1\\)$"
+            ]
+        );
+        assert_eq!(
+            kitty_launch_args(
+                "app/feature/login",
+                &worktree(),
+                &editor().argv(&worktree())
+            ),
+            vec![
+                "@",
+                "launch",
+                "--type=tab",
+                "--tab-title",
+                "app/feature/login",
+                "--cwd",
+                "/repo/.rsworktree/feature/login",
+                "nvim",
+                "-O",
+                "/repo/.rsworktree/feature/login"
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_wezterm_tabs_and_builds_spawn_args() {
+        let panes = r#"[
+            {"window_id": 0, "tab_id": 1, "pane_id": 1, "tab_title": ""},
+            {"window_id": 0, "tab_id": 4, "pane_id": 7, "tab_title": "app/feature/login"}
+        ]"#;
+        assert_eq!(
+            find_wezterm_tab(panes, "app/feature/login").as_deref(),
+            Some("4")
+        );
+        assert_eq!(find_wezterm_tab(panes, "app/main"), None);
+        assert_eq!(find_wezterm_tab("not json", "app/main"), None);
+        assert_eq!(
+            wezterm_spawn_args(&worktree(), &editor().argv(&worktree())),
+            vec![
+                "spawn",
+                "--cwd",
+                "/repo/.rsworktree/feature/login",
+                "--",
+                "nvim",
+                "-O",
+                "/repo/.rsworktree/feature/login"
+            ]
+        );
+        assert_eq!(
+            wezterm_spawn_args(&worktree(), &[]),
+            vec!["spawn", "--cwd", "/repo/.rsworktree/feature/login"]
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Fuzzy worktree picker used by `open` when no worktree is named.
+
+use std::io;
+
+use color_eyre::eyre::WrapErr;
+use crossterm::{
+    event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{
+    Frame, Terminal,
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use crate::commands::interactive::{CrosstermEvents, EventSource};
+
+/// Shows the picker on the terminal and returns the chosen entry, or `None` when cancelled.
+pub(crate) fn pick(entries: Vec<String>) -> color_eyre::Result<Option<String>> {
+    enable_raw_mode().wrap_err("failed to enable raw mode")?;
+    execute!(io::stdout(), EnterAlternateScreen).wrap_err("failed to enter alternate screen")?;
+
+    let result = Terminal::new(CrosstermBackend::new(io::stdout()))
+        .wrap_err("failed to initialize terminal")
+        .and_then(|mut terminal| run(&mut terminal, CrosstermEvents, Picker::new(entries)));
+
+    disable_raw_mode().wrap_err("failed to disable raw mode")?;
+    execute!(io::stdout(), LeaveAlternateScreen).wrap_err("failed to leave alternate screen")?;
+    result
+}
+
+fn run<B: Backend, E: EventSource>(
+    terminal: &mut Terminal<B>,
+    mut events: E,
+    mut picker: Picker,
+) -> color_eyre::Result<Option<String>> {
+    loop {
+        terminal
+            .draw(|frame| render(frame, &picker))
+            .wrap_err("failed to draw worktree picker")?;
+        let Event::Key(key) = events.next()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match picker.handle_key(key) {
+            PickerAction::Continue => {}
+            PickerAction::Select => return Ok(picker.selection().map(str::to_owned)),
+            PickerAction::Cancel => return Ok(None),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PickerAction {
+    Continue,
+    Select,
+    Cancel,
+}
+
+#[derive(Debug)]
+struct Picker {
+    entries: Vec<String>,
+    query: String,
+    /// Indices into `entries`, best match first.
+    matches: Vec<usize>,
+    selected: usize,
+}
+
+impl Picker {
+    fn new(entries: Vec<String>) -> Self {
+        let matches = (0..entries.len()).collect();
+        Self {
+            entries,
+            query: String::new(),
+            matches,
+            selected: 0,
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> PickerAction {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return PickerAction::Cancel,
+            KeyCode::Char('c') if control => return PickerAction::Cancel,
+            KeyCode::Enter if self.selection().is_some() => return PickerAction::Select,
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Char('p' | 'k') if control => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Char('n' | 'j') if control => self.move_selection(1),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.refresh();
+            }
+            KeyCode::Char(character) if !control => {
+                self.query.push(character);
+                self.refresh();
+            }
+            _ => {}
+        }
+        PickerAction::Continue
+    }
+
+    fn selection(&self) -> Option<&str> {
+        self.matches
+            .get(self.selected)
+            .map(|&index| self.entries[index].as_str())
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let last = self.matches.len() - 1;
+        self.selected = self.selected.saturating_add_signed(offset).min(last);
+    }
+
+    fn refresh(&mut self) {
+        let mut scored = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                fuzzy_score(&self.query, entry).map(|score| (index, score))
+            })
+            .collect::<Vec<_>>();
+        scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        self.matches = scored.into_iter().map(|(index, _)| index).collect();
+        self.selected = 0;
+    }
+}
+
+/// Scores `candidate` against `query` as a case-insensitive subsequence match.
+///
+/// Consecutive characters and matches at the start of a path segment or word
+/// score higher, gaps score lower. Returns `None` when `query` does not match.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate = candidate.chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    let mut next = 0;
+
+    for wanted in query.chars().filter(|character| !character.is_whitespace()) {
+        let position = (next..candidate.len())
+            .find(|&index| candidate[index].to_lowercase().eq(wanted.to_lowercase()))?;
+        next = position + 1;
+
+        score += 1;
+        if position == 0 || matches!(candidate[position - 1], '/' | '-' | '_' | '.' | ' ') {
+            score += 8;
+        }
+        match previous {
+            Some(previous) if previous + 1 == position => score += 8,
+            Some(previous) => score -= (position - previous - 1).min(5) as i64,
+            None => score -= position.min(3) as i64,
+        }
+        previous = Some(position);
+    }
+    Some(score)
+}
+
+fn render(frame: &mut Frame<'_>, picker: &Picker) {
+    let [input, list, help] = {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(frame.size());
+        [chunks[0], chunks[1], chunks[2]]
+    };
+
+    let prompt = Paragraph::new(Line::from(format!("> {}", picker.query))).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(tr!("Open worktree")),
+    );
+    frame.render_widget(prompt, input);
+    frame.set_cursor(
+        input.x + 3 + picker.query.chars().count() as u16,
+        input.y + 1,
+    );
+
+    let items = picker
+        .matches
+        .iter()
+        .map(|&index| ListItem::new(picker.entries[index].as_str()))
+        .collect::<Vec<_>>();
+    let title = tr!(
+        "{}/{} worktrees",
+        picker.matches.len(),
+        picker.entries.len()
+    );
+    let list_widget = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+    let mut state = ListState::default();
+    state.select((!picker.matches.is_empty()).then_some(picker.selected));
+    frame.render_stateful_widget(list_widget, list, &mut state);
+
+    let help_text = Paragraph::new(Line::from(tr!(
+        "Type to filter · ↑/↓ move · Enter open · Esc cancel"
+    )))
+    .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(help_text, help);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ratatui::backend::TestBackend;
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    struct StubEvents(Vec<Event>);
+
+    impl EventSource for StubEvents {
+        fn next(&mut self) -> color_eyre::Result<Event> {
+            Ok(self.0.remove(0))
+        }
+    }
+
+    fn entries() -> Vec<String> {
+        ["feature/login", "bugfix/logout", "feature/billing"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    #[test]
+    fn fuzzy_score_requires_subsequence_and_prefers_boundaries() {
+        assert!(fuzzy_score("xyz", "feature/login").is_none());
+        assert!(fuzzy_score("FLG", "feature/login").is_some());
+        assert!(fuzzy_score("log", "feature/login") > fuzzy_score("log", "feature/catalog"));
+        assert!(fuzzy_score("bill", "feature/billing") > fuzzy_score("bill", "bugfix/big-ll"));
+    }
+
+    #[test]
+    fn typing_filters_and_enter_selects_best_match() -> color_eyre::Result<()> {
+        let mut terminal = Terminal::new(TestBackend::new(50, 12))?;
+        let events = StubEvents(vec![
+            key(KeyCode::Char('b')),
+            key(KeyCode::Char('i')),
+            key(KeyCode::Char('l')),
+            key(KeyCode::Enter),
+        ]);
+
+        let selection = run(&mut terminal, events, Picker::new(entries()))?;
+
+        assert_eq!(selection.as_deref(), Some("feature/billing"));
+        Ok(())
+    }
+
+    #[test]
+    fn arrows_move_selection_and_escape_cancels() -> color_eyre::Result<()> {
+        let mut picker = Picker::new(entries());
+        picker.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        assert_eq!(picker.selection(), Some("bugfix/logout"));
+        picker.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        picker.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        assert_eq!(picker.selection(), Some("feature/billing"));
+
+        let mut terminal = Terminal::new(TestBackend::new(50, 12))?;
+        let selection = run(
+            &mut terminal,
+            StubEvents(vec![key(KeyCode::Esc)]),
+            Picker::new(entries()),
+        )?;
+        assert_eq!(selection, None);
+        Ok(())
+    }
+
+    #[test]
+    fn enter_without_matches_keeps_picker_open() {
+        let mut picker = Picker::new(entries());
+        for character in "zzz".chars() {
+            picker.handle_key(KeyEvent::new(KeyCode::Char(character), KeyModifiers::NONE));
+        }
+        assert_eq!(picker.selection(), None);
+        assert_eq!(
+            picker.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            PickerAction::Continue
+        );
+    }
+}
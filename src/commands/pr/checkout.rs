@@ -0,0 +1,290 @@
+//! `pr checkout`: review a pull/merge request in a worktree of its own.
+//!
+//! The worktree is added with a detached `HEAD` first and the provider
+//! checks the request out inside it, so requests from forks work as well and
+//! the main worktree is never switched.
+
+use std::path::Path;
+
+use color_eyre::eyre::{self, WrapErr};
+use owo_colors::OwoColorize;
+
+use super::{ensure_supported, format_with_color};
+use crate::{
+    GitProvider, Repo,
+    commands::open::OpenCommand,
+    hooks::{HookContext, HookName, HookRunner},
+    output,
+    process::{CommandOutput, CommandRunner, SystemCommandRunner, command_failure},
+    provider::{ProviderBackend, ProviderClient, RequestBackend},
+    repo::ensure_unreserved_name,
+    worktree,
+};
+
+#[derive(Debug)]
+pub struct PrCheckoutCommand<R = SystemCommandRunner> {
+    number: u64,
+    provider: GitProvider,
+    client: ProviderClient,
+    runner: R,
+    open: bool,
+}
+
+impl PrCheckoutCommand {
+    /// Runs the provider operations through the backend configured for `repo`.
+    pub fn new(number: u64, provider: GitProvider, repo: &Repo) -> Self {
+        Self::with_runner(number, provider, SystemCommandRunner)
+            .with_backend(ProviderBackend::resolve(repo))
+    }
+}
+
+impl<R> PrCheckoutCommand<R>
+where
+    R: CommandRunner,
+{
+    pub fn with_runner(number: u64, provider: GitProvider, runner: R) -> Self {
+        Self {
+            number,
+            provider,
+            client: provider.client(ProviderBackend::Cli),
+            runner,
+            open: true,
+        }
+    }
+
+    /// Runs the provider operations through `backend` rather than the CLI.
+    pub fn with_backend(mut self, backend: ProviderBackend) -> Self {
+        self.client = self.provider.client(backend);
+        self
+    }
+
+    /// Open the worktree in the configured editor afterwards (the default).
+    pub fn with_open(mut self, open: bool) -> Self {
+        self.open = open;
+        self
+    }
+
+    /// Creates a worktree named after the request's source branch, checks the
+    /// request out in it and opens it. An existing worktree is only opened.
+    pub fn execute(&mut self, repo: &Repo) -> color_eyre::Result<()> {
+        ensure_supported(self.provider, "checkout")?;
+        let branch = self.head_branch(repo.root())?;
+        ensure_unreserved_name(&branch)?;
+        let worktrees_dir = repo.ensure_worktrees_dir()?;
+        let worktree_path = worktrees_dir.join(&branch);
+        let name_label = format_with_color(&branch, |text| format!("{}", text.cyan().bold()));
+
+        if worktree_path.exists() {
+            output::info(tr!(
+                "Worktree `{}` already exists at `{}`.",
+                name_label,
+                worktree_path.display()
+            ));
+        } else {
+            self.check_out(repo, &worktrees_dir, &branch, &worktree_path)?;
+            let request_label = format_with_color(
+                &format!("{}{}", self.provider.request_prefix(), self.number),
+                |text| format!("{}", text.green().bold()),
+            );
+            output::success(tr!(
+                "Checked out {} {} into worktree `{}` at `{}`.",
+                self.provider.merge_request_short(),
+                request_label,
+                name_label,
+                worktree_path.display()
+            ));
+        }
+
+        if self.open {
+            OpenCommand::new(Some(branch), None).execute(repo)?;
+        }
+        Ok(())
+    }
+
+    fn head_branch(&mut self, repo_root: &Path) -> color_eyre::Result<String> {
+        let branch = self
+            .client
+            .head_branch(&mut self.runner, repo_root, self.number)?;
+        branch.ok_or_else(|| {
+            eyre::eyre!(
+                "no open {} {}{} found",
+                self.provider.merge_request_term(),
+                self.provider.request_prefix(),
+                self.number
+            )
+        })
+    }
+
+    fn check_out(
+        &mut self,
+        repo: &Repo,
+        worktrees_dir: &Path,
+        branch: &str,
+        worktree_path: &Path,
+    ) -> color_eyre::Result<()> {
+        let path = worktree_path.display().to_string();
+        self.run(
+            "git",
+            repo.root(),
+            &[
+                "worktree".to_owned(),
+                "add".to_owned(),
+                "--detach".to_owned(),
+                path.clone(),
+            ],
+        )?;
+
+        if let Err(error) = self
+            .client
+            .checkout(&mut self.runner, worktree_path, self.number)
+        {
+            let remove = [
+                "worktree".to_owned(),
+                "remove".to_owned(),
+                "--force".to_owned(),
+                path,
+            ];
+            if let Err(rollback) = self.run("git", repo.root(), &remove) {
+                output::warn(tr!("rollback incomplete: {}", rollback));
+            }
+            return Err(error);
+        }
+
+        worktree::config::apply_defaults(repo.git(), &repo.rsworktree_dir(), worktree_path)?;
+        let copy_patterns = worktree::config::copy_patterns(&repo.rsworktree_dir());
+        if !copy_patterns.is_empty() {
+            for path in worktree::copy::copy_matching(repo.root(), worktree_path, &copy_patterns)? {
+                output::info(tr!("Copied `{}` into the new worktree.", path.display()));
+            }
+        }
+
+        HookRunner::new(&repo.rsworktree_dir())
+            .with_repository(&repo.name())
+            .run_hook(
+            HookName::PostCreate,
+            &HookContext {
+                worktree_name: branch.to_owned(),
+                worktree_path: worktree_path.to_path_buf(),
+                branch: branch.to_owned(),
+                base_branch: None,
+                base_path: worktrees_dir.to_path_buf(),
+            },
+        )
+    }
+
+    fn run(
+        &mut self,
+        program: &str,
+        dir: &Path,
+        args: &[String],
+    ) -> color_eyre::Result<CommandOutput> {
+        let output = self
+            .runner
+            .run(program, dir, args)
+            .wrap_err_with(|| format!("failed to run `{program} {}`", args[..2].join(" ")))?;
+        if !output.success {
+            return Err(command_failure(program, args, &output));
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use crate::process::ScriptedRunner;
+
+    fn repo() -> color_eyre::Result<(TempDir, Repo)> {
+        let dir = TempDir::new()?;
+        git2::Repository::init(dir.path())?;
+        let repo = Repo::discover_from(dir.path())?;
+        Ok((dir, repo))
+    }
+
+    #[test]
+    fn checks_out_request_in_detached_worktree() -> color_eyre::Result<()> {
+        let (dir, repo) = repo()?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success(r#"{"headRefName":"fix/login"}"#));
+        runner.push(CommandOutput::success(""));
+        runner.push(CommandOutput::success(""));
+
+        let mut command = PrCheckoutCommand::with_runner(42, GitProvider::GitHub, runner.clone())
+            .with_open(false);
+        command.execute(&repo)?;
+
+        let worktree_path = dir.path().join(".rsworktree/fix/login");
+        assert_eq!(
+            runner
+                .calls()
+                .into_iter()
+                .map(|call| (call.program, call.args))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "gh".to_owned(),
+                    ["pr", "view", "42", "--json", "headRefName"]
+                        .map(String::from)
+                        .to_vec()
+                ),
+                (
+                    "git".to_owned(),
+                    vec![
+                        "worktree".to_owned(),
+                        "add".to_owned(),
+                        "--detach".to_owned(),
+                        worktree_path.display().to_string(),
+                    ]
+                ),
+                (
+                    "gh".to_owned(),
+                    ["pr", "checkout", "42"].map(String::from).to_vec()
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn failed_checkout_removes_worktree_again() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo()?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success(
+            r#"{"iid":7,"source_branch":"feature"}"#,
+        ));
+        runner.push(CommandOutput::success(""));
+        runner.push(CommandOutput::failure(1, "boom"));
+        runner.push(CommandOutput::success(""));
+
+        let mut command =
+            PrCheckoutCommand::with_runner(7, GitProvider::GitLab, runner.clone()).with_open(false);
+        assert!(command.execute(&repo).is_err());
+
+        let call = runner.calls().pop().expect("rollback call");
+        assert_eq!(call.program, "git");
+        assert_eq!(call.args[..3], ["worktree", "remove", "--force"]);
+        Ok(())
+    }
+
+    #[test]
+    fn existing_worktree_is_reused() -> color_eyre::Result<()> {
+        let (dir, repo) = repo()?;
+        fs::create_dir_all(dir.path().join(".rsworktree/feature"))?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success(
+            r#"{"iid":7,"source_branch":"feature"}"#,
+        ));
+
+        let mut command =
+            PrCheckoutCommand::with_runner(7, GitProvider::GitLab, runner.clone()).with_open(false);
+        command.execute(&repo)?;
+
+        assert_eq!(runner.calls().len(), 1);
+        Ok(())
+    }
+}
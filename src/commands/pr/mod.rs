@@ -0,0 +1,816 @@
+//! `pr` subcommands acting on the pull/merge request of a worktree's branch.
+
+mod checkout;
+
+use std::{
+    fs,
+    io::{self, IsTerminal, Read},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    GitProvider, Repo,
+    commands::rm::RemoveCommand,
+    output,
+    process::{CommandRunner, SystemCommandRunner, command_failure},
+    provider::{ProviderBackend, ProviderClient, RequestBackend, ReviewVerdict},
+    worktree::inspect_worktree,
+};
+
+pub use checkout::PrCheckoutCommand;
+
+/// File inside the git directory used to compose comments in an editor.
+const COMMENT_EDITMSG: &str = "RSWORKTREE_COMMENT_EDITMSG";
+
+/// Where the text of a comment comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageSource {
+    /// Text given on the command line.
+    Text(String),
+    /// A file to read; `-` reads standard input.
+    File(PathBuf),
+    /// Compose the text in the editor configured for git.
+    Editor,
+}
+
+/// What `pr close` cleans up besides closing the pull/merge request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CloseOptions {
+    /// Comment explaining why the pull/merge request is closed.
+    pub comment: Option<String>,
+    /// Delete the branch on its remote.
+    pub delete_remote_branch: bool,
+    /// Remove the worktree and its local branch.
+    pub remove_worktree: bool,
+    /// Remove the worktree even when it has uncommitted changes.
+    pub force: bool,
+    /// Skip the confirmation prompt.
+    pub assume_yes: bool,
+}
+
+#[derive(Debug)]
+pub struct PrCommand<R = SystemCommandRunner> {
+    name: String,
+    provider: GitProvider,
+    client: ProviderClient,
+    runner: R,
+}
+
+impl PrCommand {
+    /// Runs the provider operations through the backend configured for `repo`.
+    pub fn new(name: String, provider: GitProvider, repo: &Repo) -> Self {
+        Self::with_runner(name, provider, SystemCommandRunner)
+            .with_backend(ProviderBackend::resolve(repo))
+    }
+}
+
+impl<R> PrCommand<R>
+where
+    R: CommandRunner,
+{
+    pub fn with_runner(name: String, provider: GitProvider, runner: R) -> Self {
+        Self {
+            name,
+            provider,
+            client: provider.client(ProviderBackend::Cli),
+            runner,
+        }
+    }
+
+    /// Runs the provider operations through `backend` rather than the CLI.
+    pub fn with_backend(mut self, backend: ProviderBackend) -> Self {
+        self.client = self.provider.client(backend);
+        self
+    }
+
+    /// Posts a comment on the pull/merge request of the worktree's branch.
+    pub fn comment(&mut self, repo: &Repo, source: MessageSource) -> color_eyre::Result<()> {
+        ensure_supported(self.provider, "comment")?;
+        if !self.provider.supports_comments() {
+            return Err(self.comments_unsupported());
+        }
+        let worktree_path = self.ensure_worktree_path(repo)?;
+        let branch = self.determine_branch(&worktree_path)?;
+
+        let template = tr!(
+            "# Write the comment for the {} of `{}`.\n# Lines starting with '#' are ignored; an empty message aborts.",
+            self.provider.merge_request_term(),
+            branch
+        );
+        let body = self.resolve_message(repo, &worktree_path, source, &template)?;
+        if body.is_empty() {
+            return Err(eyre::eyre!("aborting comment due to empty message"));
+        }
+
+        let target = self.request_target(&worktree_path, &branch)?;
+        let url = self
+            .client
+            .comment(&mut self.runner, &worktree_path, &target, &body)?;
+
+        let branch_label = format_with_color(&branch, |text| format!("{}", text.magenta().bold()));
+        output::success(tr!(
+            "Commented on the {} for `{}`.",
+            self.provider.merge_request_term(),
+            branch_label
+        ));
+        if let Some(url) = url {
+            output::field("URL", url);
+        }
+        Ok(())
+    }
+
+    /// Submits an approval or a change request on the pull/merge request of the worktree's branch.
+    ///
+    /// Requesting changes without a message opens the editor, as reviewers are
+    /// expected to explain what needs to change, unless the provider has no
+    /// way to deliver the message.
+    pub fn review(
+        &mut self,
+        repo: &Repo,
+        verdict: ReviewVerdict,
+        source: Option<MessageSource>,
+    ) -> color_eyre::Result<()> {
+        ensure_supported(self.provider, "review")?;
+        let worktree_path = self.ensure_worktree_path(repo)?;
+        let branch = self.determine_branch(&worktree_path)?;
+
+        let delivers_message =
+            self.provider.review_accepts_message() || self.provider.supports_comments();
+        if source.is_some() && !delivers_message {
+            return Err(self.comments_unsupported());
+        }
+        let source = match (source, verdict) {
+            (Some(source), _) => Some(source),
+            (None, ReviewVerdict::RequestChanges) if delivers_message => {
+                Some(MessageSource::Editor)
+            }
+            (None, _) => None,
+        };
+        let body = match source {
+            Some(source) => {
+                let template = tr!(
+                    "# Describe the changes you request on the {} of `{}`.\n# Lines starting with '#' are ignored; an empty message aborts.",
+                    self.provider.merge_request_term(),
+                    branch
+                );
+                Some(self.resolve_message(repo, &worktree_path, source, &template)?)
+            }
+            None => None,
+        };
+        let body = body.filter(|body| !body.is_empty());
+        if verdict == ReviewVerdict::RequestChanges && body.is_none() && delivers_message {
+            return Err(eyre::eyre!(
+                "aborting review due to empty message; requesting changes needs an explanation"
+            ));
+        }
+
+        let target = self.request_target(&worktree_path, &branch)?;
+        let submitted = self.client.review(
+            &mut self.runner,
+            &worktree_path,
+            &target,
+            verdict,
+            body.as_deref(),
+        )?;
+        // Providers without message support for the verdict get the message as a comment.
+        if let Some(body) = &body
+            && !self.provider.review_accepts_message()
+        {
+            self.client
+                .comment(&mut self.runner, &worktree_path, &target, body)?;
+        }
+
+        let branch_label = format_with_color(&branch, |text| format!("{}", text.magenta().bold()));
+        match verdict {
+            ReviewVerdict::Approve => output::success(tr!(
+                "Approved the {} for `{}`.",
+                self.provider.merge_request_term(),
+                branch_label
+            )),
+            ReviewVerdict::RequestChanges if !submitted => {
+                output::success(tr!(
+                    "Commented on the {} for `{}`.",
+                    self.provider.merge_request_term(),
+                    branch_label
+                ));
+                output::note(tr!(
+                    "`{}` cannot request changes; the message was posted as a comment.",
+                    self.provider.cli_program()
+                ));
+            }
+            ReviewVerdict::RequestChanges => output::success(tr!(
+                "Requested changes on the {} for `{}`.",
+                self.provider.merge_request_term(),
+                branch_label
+            )),
+        }
+        Ok(())
+    }
+
+    /// Closes the pull/merge request of the worktree's branch without merging
+    /// it and, depending on `options`, deletes the remote branch and removes
+    /// the worktree with its local branch.
+    pub fn close(&mut self, repo: &Repo, options: CloseOptions) -> color_eyre::Result<()> {
+        ensure_supported(self.provider, "close")?;
+        let worktree_path = self.ensure_worktree_path(repo)?;
+        let branch = self.determine_branch(&worktree_path)?;
+        let remote = upstream_remote(repo, &branch);
+        if options.comment.is_some() && !self.provider.supports_comments() {
+            return Err(self.comments_unsupported());
+        }
+
+        if options.remove_worktree
+            && !options.force
+            && !inspect_worktree(&worktree_path)?.changes.is_clean()
+        {
+            return Err(eyre::eyre!(
+                "worktree `{}` has uncommitted changes; commit them or pass `--force` to discard them",
+                self.name
+            ));
+        }
+
+        let branch_label = format_with_color(&branch, |text| format!("{}", text.magenta().bold()));
+        if !options.assume_yes && !self.confirm_close(&branch_label, &remote, &options)? {
+            output::info(tr!("Aborted; nothing was changed."));
+            return Ok(());
+        }
+
+        let comment = options
+            .comment
+            .as_deref()
+            .map(str::trim)
+            .filter(|comment| !comment.is_empty());
+        let target = self.request_target(&worktree_path, &branch)?;
+        self.client
+            .close(&mut self.runner, &worktree_path, &target, comment)?;
+        output::success(tr!(
+            "Closed the {} for `{}`.",
+            self.provider.merge_request_term(),
+            branch_label
+        ));
+
+        if options.delete_remote_branch {
+            self.delete_remote_branch(repo, &remote, &branch)?;
+        }
+
+        if options.remove_worktree {
+            // Uncommitted changes were checked above, so removal may be forced.
+            RemoveCommand::new(self.name.clone(), true)
+                .with_remove_local_branch(true)
+                .execute(repo)?;
+        }
+
+        Ok(())
+    }
+
+    fn confirm_close(
+        &self,
+        branch_label: &str,
+        remote: &str,
+        options: &CloseOptions,
+    ) -> color_eyre::Result<bool> {
+        if !io::stdin().is_terminal() {
+            return Err(eyre::eyre!(
+                "`rsworktree pr close` needs confirmation; pass `--yes` when running non-interactively"
+            ));
+        }
+
+        output::heading(tr!("This will:"));
+        output::item(tr!(
+            "close the {} for `{}`",
+            self.provider.merge_request_term(),
+            branch_label
+        ));
+        if options.delete_remote_branch {
+            output::item(tr!("delete the branch `{}` on `{}`", branch_label, remote));
+        }
+        if options.remove_worktree {
+            output::item(tr!(
+                "remove the worktree `{}` and its local branch",
+                self.name
+            ));
+        }
+        output::prompt(tr!("Continue? [y/N] "))?;
+
+        let mut answer = String::new();
+        io::stdin()
+            .read_line(&mut answer)
+            .wrap_err("failed to read user input")?;
+        Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "Yes" | "YES"))
+    }
+
+    fn delete_remote_branch(
+        &mut self,
+        repo: &Repo,
+        remote: &str,
+        branch: &str,
+    ) -> color_eyre::Result<()> {
+        let args = vec![
+            "push".to_owned(),
+            remote.to_owned(),
+            "--delete".to_owned(),
+            branch.to_owned(),
+        ];
+        let output = self
+            .runner
+            .run("git", repo.root(), &args)
+            .wrap_err("failed to delete remote branch with `git push`")?;
+
+        let branch_label = format_with_color(branch, |text| format!("{}", text.magenta().bold()));
+        if !output.success {
+            let combined = format!("{}{}", output.stderr, output.stdout).to_lowercase();
+            if combined.contains("remote ref does not exist") {
+                output::info(tr!("Remote branch `{}` was already removed.", branch_label));
+                return Ok(());
+            }
+            return Err(command_failure("git", &args, &output));
+        }
+
+        output::success(tr!("Removed remote branch `{}`.", branch_label));
+        Ok(())
+    }
+
+    /// Returns the trimmed message text from `source`, composing it in the
+    /// editor with `template` when requested.
+    fn resolve_message(
+        &mut self,
+        repo: &Repo,
+        worktree_path: &Path,
+        source: MessageSource,
+        template: &str,
+    ) -> color_eyre::Result<String> {
+        let text = match source {
+            MessageSource::Text(text) => text,
+            MessageSource::File(path) => read_message_file(&path)?,
+            MessageSource::Editor => self.compose_in_editor(repo, worktree_path, template)?,
+        };
+        Ok(text.trim().to_owned())
+    }
+
+    /// How the provider addresses the request of `branch`: the branch
+    /// itself, or the request number where it needs one.
+    fn request_target(&mut self, worktree_path: &Path, branch: &str) -> color_eyre::Result<String> {
+        if self.provider.addresses_requests_by_branch() {
+            return Ok(branch.to_owned());
+        }
+
+        let number = self.client.list(&mut self.runner, worktree_path, branch)?;
+        number.map(|number| number.to_string()).ok_or_else(|| {
+            eyre::eyre!(
+                "no open {} found for branch `{branch}`",
+                self.provider.merge_request_term()
+            )
+        })
+    }
+
+    fn comments_unsupported(&self) -> eyre::Report {
+        eyre::eyre!(
+            "`{}` cannot comment on {}s, so messages are not supported on {}",
+            self.provider.cli_program(),
+            self.provider.merge_request_term(),
+            self.provider.display_name()
+        )
+    }
+
+    /// Opens the editor configured for git (`git var GIT_EDITOR`) on a message
+    /// file prefilled with `template` and returns the text without comment lines.
+    fn compose_in_editor(
+        &mut self,
+        repo: &Repo,
+        worktree_path: &Path,
+        template: &str,
+    ) -> color_eyre::Result<String> {
+        let args = vec!["var".to_owned(), "GIT_EDITOR".to_owned()];
+        let output = self
+            .runner
+            .run("git", worktree_path, &args)
+            .wrap_err("failed to determine the editor with `git var GIT_EDITOR`")?;
+        if !output.success {
+            return Err(command_failure("git", &args, &output));
+        }
+        let editor = output.stdout.trim();
+
+        let message_path = repo.git().path().join(COMMENT_EDITMSG);
+        fs::write(&message_path, format!("\n{template}\n"))
+            .wrap_err_with(|| eyre::eyre!("failed to write `{}`", message_path.display()))?;
+
+        // Like git, hand the editor command to the shell so it may contain arguments.
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(format!("{editor} \"$@\""))
+            .arg(editor)
+            .arg(&message_path)
+            .current_dir(worktree_path)
+            .status()
+            .wrap_err_with(|| eyre::eyre!("failed to launch editor `{editor}`"))?;
+        if !status.success() {
+            return Err(eyre::eyre!("editor `{editor}` exited with {status}"));
+        }
+
+        let text = fs::read_to_string(&message_path)
+            .wrap_err_with(|| eyre::eyre!("failed to read `{}`", message_path.display()))?;
+        Ok(strip_comment_lines(&text))
+    }
+
+    fn ensure_worktree_path(&self, repo: &Repo) -> color_eyre::Result<PathBuf> {
+        let worktrees_dir = repo.ensure_worktrees_dir()?;
+        let worktree_path = worktrees_dir.join(&self.name);
+        if !worktree_path.exists() {
+            return Err(eyre::eyre!(
+                "worktree `{}` does not exist under `{}`",
+                self.name,
+                worktrees_dir.display()
+            ));
+        }
+        Ok(worktree_path)
+    }
+
+    fn determine_branch(&mut self, worktree_path: &Path) -> color_eyre::Result<String> {
+        let args = vec![
+            "rev-parse".to_owned(),
+            "--abbrev-ref".to_owned(),
+            "HEAD".to_owned(),
+        ];
+        let output = self
+            .runner
+            .run("git", worktree_path, &args)
+            .wrap_err("failed to determine current branch with `git rev-parse`")?;
+
+        if !output.success {
+            return Err(command_failure("git", &args, &output));
+        }
+
+        let branch = output.stdout.trim();
+        if branch.is_empty() || branch == "HEAD" {
+            return Err(eyre::eyre!(
+                "worktree `{}` is not on a branch; cannot find its {}",
+                self.name,
+                self.provider.merge_request_term()
+            ));
+        }
+
+        Ok(branch.to_owned())
+    }
+}
+
+/// Gerrit reviews, comments on and abandons patch sets, which the `pr`
+/// commands cannot address; only `pr create` pushes a change there.
+fn ensure_supported(provider: GitProvider, command: &str) -> color_eyre::Result<()> {
+    if provider == GitProvider::Gerrit {
+        return Err(eyre::eyre!(
+            "`rsworktree pr {}` is not supported on Gerrit; use its web UI or `ssh <host> gerrit review`",
+            command
+        ));
+    }
+    Ok(())
+}
+
+/// The remote `branch` tracks, falling back to `origin`.
+fn upstream_remote(repo: &Repo, branch: &str) -> String {
+    repo.git()
+        .branch_upstream_remote(&format!("refs/heads/{branch}"))
+        .ok()
+        .and_then(|remote| remote.as_str().map(str::to_owned))
+        .unwrap_or_else(|| "origin".to_owned())
+}
+
+fn read_message_file(path: &Path) -> color_eyre::Result<String> {
+    if path == Path::new("-") {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .wrap_err("failed to read message from standard input")?;
+        return Ok(text);
+    }
+
+    fs::read_to_string(path)
+        .wrap_err_with(|| eyre::eyre!("failed to read message from `{}`", path.display()))
+}
+
+fn strip_comment_lines(text: &str) -> String {
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_with_color(value: &str, paint: impl Fn(&str) -> String) -> String {
+    value
+        .if_supports_color(Stream::Stdout, |text| paint(text))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    use crate::process::{CommandOutput, ScriptedRunner};
+
+    fn repo_with_worktree(name: &str) -> color_eyre::Result<(TempDir, Repo)> {
+        let dir = TempDir::new()?;
+        fs::create_dir_all(dir.path().join(".rsworktree").join(name))?;
+        git2::Repository::init(dir.path())?;
+        let repo = Repo::discover_from(dir.path())?;
+        Ok((dir, repo))
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| (*value).to_owned()).collect()
+    }
+
+    #[test]
+    fn comment_posts_text_on_branch_pull_request() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success(
+            "https://github.com/o/r/pull/1#issuecomment-1\n",
+        ));
+
+        let mut command =
+            PrCommand::with_runner("feature".into(), GitProvider::GitHub, runner.clone());
+        command.comment(&repo, MessageSource::Text("  Ready for review\n".into()))?;
+
+        let call = &runner.calls()[1];
+        assert_eq!(call.program, "gh");
+        assert_eq!(
+            call.args,
+            strings(&["pr", "comment", "feature", "--body", "Ready for review"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn comment_reads_message_file() -> color_eyre::Result<()> {
+        let (dir, repo) = repo_with_worktree("feature")?;
+        let message = dir.path().join("note.md");
+        fs::write(&message, "Deployed to staging\n")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success(""));
+
+        let mut command =
+            PrCommand::with_runner("feature".into(), GitProvider::GitLab, runner.clone());
+        command.comment(&repo, MessageSource::File(message))?;
+
+        let call = &runner.calls()[1];
+        assert_eq!(call.program, "glab");
+        assert_eq!(
+            call.args,
+            strings(&["mr", "note", "feature", "--message", "Deployed to staging"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn comment_composes_message_in_git_editor() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success("printf 'LGTM\\n# ignored\\n' >\n"));
+        runner.push(CommandOutput::success(""));
+
+        let mut command =
+            PrCommand::with_runner("feature".into(), GitProvider::GitHub, runner.clone());
+        command.comment(&repo, MessageSource::Editor)?;
+
+        let call = &runner.calls()[1];
+        assert_eq!(call.program, "git");
+        assert_eq!(call.args, strings(&["var", "GIT_EDITOR"]));
+        assert_eq!(runner.calls()[2].args[4], "LGTM");
+        Ok(())
+    }
+
+    #[test]
+    fn comment_rejects_empty_message() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+
+        let mut command =
+            PrCommand::with_runner("feature".into(), GitProvider::GitHub, runner.clone());
+        let error = command
+            .comment(&repo, MessageSource::Text("   ".into()))
+            .expect_err("empty message should abort");
+
+        assert!(error.to_string().contains("empty message"));
+        assert_eq!(runner.calls().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn review_approves_with_message_on_github() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success(""));
+
+        let mut command =
+            PrCommand::with_runner("feature".into(), GitProvider::GitHub, runner.clone());
+        command.review(
+            &repo,
+            ReviewVerdict::Approve,
+            Some(MessageSource::Text("LGTM".into())),
+        )?;
+
+        let call = &runner.calls()[1];
+        assert_eq!(call.program, "gh");
+        assert_eq!(
+            call.args,
+            strings(&["pr", "review", "feature", "--approve", "--body", "LGTM"])
+        );
+        assert_eq!(runner.calls().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn review_posts_gitlab_approval_message_as_note() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success(""));
+        runner.push(CommandOutput::success(""));
+
+        let mut command =
+            PrCommand::with_runner("feature".into(), GitProvider::GitLab, runner.clone());
+        command.review(
+            &repo,
+            ReviewVerdict::Approve,
+            Some(MessageSource::Text("LGTM".into())),
+        )?;
+
+        assert_eq!(
+            runner.calls()[1].args,
+            strings(&["mr", "approve", "feature"])
+        );
+        assert_eq!(
+            runner.calls()[2].args,
+            strings(&["mr", "note", "feature", "--message", "LGTM"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn review_looks_up_gitea_pull_request_number() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success(
+            r#"[{"index":"5","head":"feature","state":"open","url":""}]"#,
+        ));
+        runner.push(CommandOutput::success(""));
+
+        let mut command =
+            PrCommand::with_runner("feature".into(), GitProvider::Gitea, runner.clone());
+        command.review(
+            &repo,
+            ReviewVerdict::RequestChanges,
+            Some(MessageSource::Text("Add tests".into())),
+        )?;
+
+        assert_eq!(runner.calls()[1].args[..2], strings(&["pr", "list"]));
+        let call = &runner.calls()[2];
+        assert_eq!(call.program, "tea");
+        assert_eq!(call.args, strings(&["pr", "reject", "5", "Add tests"]));
+        assert_eq!(runner.calls().len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn review_votes_on_azure_devops_without_message() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success(
+            r#"[{"pullRequestId":8,"sourceRefName":"refs/heads/feature","status":"active"}]"#,
+        ));
+        runner.push(CommandOutput::success(""));
+
+        let mut command =
+            PrCommand::with_runner("feature".into(), GitProvider::AzureDevOps, runner.clone());
+        command.review(&repo, ReviewVerdict::RequestChanges, None)?;
+
+        let call = &runner.calls()[2];
+        assert_eq!(call.program, "az");
+        assert_eq!(
+            call.args,
+            strings(&[
+                "repos",
+                "pr",
+                "set-vote",
+                "--id",
+                "8",
+                "--vote",
+                "wait-for-author"
+            ])
+        );
+
+        let error = command
+            .comment(&repo, MessageSource::Text("LGTM".into()))
+            .expect_err("az cannot comment");
+        assert!(error.to_string().contains("cannot comment"));
+        assert_eq!(runner.calls().len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn review_request_changes_needs_message() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success("true\n"));
+
+        let mut command =
+            PrCommand::with_runner("feature".into(), GitProvider::GitHub, runner.clone());
+        let error = command
+            .review(&repo, ReviewVerdict::RequestChanges, None)
+            .expect_err("empty change request should abort");
+
+        assert!(error.to_string().contains("empty message"));
+        assert_eq!(
+            runner.calls()[1].args,
+            strings(&["var", "GIT_EDITOR"]),
+            "the editor should be opened for the explanation"
+        );
+        assert_eq!(runner.calls().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn close_posts_gitlab_comment_and_deletes_remote_branch() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success(""));
+        runner.push(CommandOutput::success(""));
+        runner.push(CommandOutput::success(""));
+
+        let mut command =
+            PrCommand::with_runner("feature".into(), GitProvider::GitLab, runner.clone());
+        command.close(
+            &repo,
+            CloseOptions {
+                comment: Some("Superseded by !12".into()),
+                delete_remote_branch: true,
+                assume_yes: true,
+                ..CloseOptions::default()
+            },
+        )?;
+
+        let calls = runner.calls();
+        assert_eq!(
+            calls[1].args,
+            strings(&["mr", "note", "feature", "--message", "Superseded by !12"])
+        );
+        assert_eq!(calls[2].args, strings(&["mr", "close", "feature"]));
+        assert_eq!(
+            calls[3].args,
+            strings(&["push", "origin", "--delete", "feature"])
+        );
+        assert!(
+            repo.worktrees_dir().join("feature").exists(),
+            "the worktree is kept without `remove_worktree`"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn close_tolerates_missing_remote_branch() -> color_eyre::Result<()> {
+        let (_dir, repo) = repo_with_worktree("feature")?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("feature\n"));
+        runner.push(CommandOutput::success(""));
+        runner.push(CommandOutput::failure(
+            1,
+            "error: unable to delete 'feature': remote ref does not exist",
+        ));
+
+        let mut command =
+            PrCommand::with_runner("feature".into(), GitProvider::GitHub, runner.clone());
+        command.close(
+            &repo,
+            CloseOptions {
+                delete_remote_branch: true,
+                assume_yes: true,
+                ..CloseOptions::default()
+            },
+        )?;
+
+        assert_eq!(runner.calls()[1].args, strings(&["pr", "close", "feature"]));
+        Ok(())
+    }
+
+    #[test]
+    fn strip_comment_lines_drops_hash_lines() {
+        assert_eq!(
+            strip_comment_lines("first\n# hint\nsecond\n"),
+            "first\nsecond"
+        );
+    }
+}
@@ -0,0 +1,524 @@
+use std::{
+    fs,
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use git2::{BranchType, ErrorCode, WorktreeLockStatus, WorktreePruneOptions};
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    Repo,
+    commands::{
+        create::base_config_key,
+        list::{find_managed_worktrees, format_worktree},
+        lock::lock_status,
+        rm::{RemoveCommand, find_worktree_name, remote_default_branch, upstream_remote},
+    },
+    output,
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Why a worktree is offered for pruning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PruneReason {
+    /// Git still tracks the worktree but its directory is gone.
+    MissingDirectory,
+    /// A directory under `.rsworktree` that git no longer knows as a worktree.
+    Orphaned,
+    /// The branch tracked a remote branch that has since been deleted.
+    UpstreamGone,
+    /// The pushed branch is fully merged into its base branch.
+    Merged { base: String },
+    /// No commit for at least the configured number of days.
+    Stale { days: u64 },
+}
+
+impl PruneReason {
+    fn describe(&self) -> String {
+        match self {
+            PruneReason::MissingDirectory => tr!("directory is missing"),
+            PruneReason::Orphaned => tr!("not a registered git worktree"),
+            PruneReason::UpstreamGone => tr!("upstream branch was deleted"),
+            PruneReason::Merged { base } => tr!("merged into `{}`", base),
+            PruneReason::Stale { days } => tr!("no commit for {} days", days),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PruneCandidate {
+    /// Worktree name under `.rsworktree`, or the path for worktrees outside it.
+    pub name: String,
+    pub path: PathBuf,
+    pub reason: PruneReason,
+    /// Git's own worktree name, used to prune the registration.
+    git_name: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct PruneCommand {
+    yes: bool,
+    dry_run: bool,
+    stale_days: Option<u64>,
+    force: bool,
+    no_wait: bool,
+}
+
+impl PruneCommand {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove the candidates without asking for confirmation.
+    pub fn with_yes(mut self, yes: bool) -> Self {
+        self.yes = yes;
+        self
+    }
+
+    /// Only report the candidates.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Also offer worktrees whose last commit is at least `days` old.
+    pub fn with_stale_days(mut self, days: Option<u64>) -> Self {
+        self.stale_days = days;
+        self
+    }
+
+    /// Also prune worktrees locked with `rsworktree lock` or `git worktree lock`.
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Fail instead of waiting when another command holds the repository lock.
+    pub fn with_no_wait(mut self, no_wait: bool) -> Self {
+        self.no_wait = no_wait;
+        self
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<Vec<PruneCandidate>> {
+        let candidates = self.find_candidates(repo)?;
+        if candidates.is_empty() {
+            output::success(tr!("Nothing to prune."));
+            return Ok(Vec::new());
+        }
+
+        output::heading(tr!("Worktrees that can be pruned:"));
+        for candidate in &candidates {
+            let name = format!(
+                "{}",
+                candidate
+                    .name
+                    .as_str()
+                    .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
+            );
+            output::item(format!("{name} ({})", candidate.reason.describe()));
+        }
+
+        if self.dry_run {
+            return Ok(Vec::new());
+        }
+        if !self.yes && !confirm(candidates.len())? {
+            return Ok(Vec::new());
+        }
+
+        let _lock = repo.lock(!self.no_wait)?;
+        let mut pruned = Vec::new();
+        for candidate in candidates {
+            match self.prune(repo, &candidate) {
+                Ok(()) => {
+                    output::success(tr!("Pruned `{}`.", candidate.name));
+                    pruned.push(candidate);
+                }
+                Err(error) => {
+                    output::warn(tr!("could not prune `{}`: {}", candidate.name, error));
+                }
+            }
+        }
+        Ok(pruned)
+    }
+
+    pub fn find_candidates(&self, repo: &Repo) -> color_eyre::Result<Vec<PruneCandidate>> {
+        let git_repo = repo.git();
+        let mut candidates = Vec::new();
+
+        // Registrations whose directory is gone, as `git worktree prune` would remove.
+        let names = git_repo
+            .worktrees()
+            .wrap_err("failed to list repository worktrees")?;
+        for name in names.iter().flatten() {
+            let worktree = match git_repo.find_worktree(name) {
+                Ok(worktree) => worktree,
+                Err(error) if error.code() == ErrorCode::NotFound => continue,
+                Err(error) => {
+                    return Err(eyre::eyre!("failed to open git worktree `{name}`: {error}"));
+                }
+            };
+            let unlocked = lock_status(&worktree) == WorktreeLockStatus::Unlocked;
+            if (unlocked || self.force) && !worktree.path().exists() {
+                let path = worktree.path().to_path_buf();
+                candidates.push(PruneCandidate {
+                    name: path
+                        .strip_prefix(repo.worktrees_dir())
+                        .map(format_worktree)
+                        .unwrap_or_else(|_| path.display().to_string()),
+                    path,
+                    reason: PruneReason::MissingDirectory,
+                    git_name: Some(name.to_owned()),
+                });
+            }
+        }
+
+        let worktrees_dir = repo.worktrees_dir();
+        if !worktrees_dir.exists() {
+            return Ok(candidates);
+        }
+
+        for relative in find_managed_worktrees(&worktrees_dir)? {
+            let name = format_worktree(&relative);
+            let path = worktrees_dir.join(&relative);
+            let reason = match find_worktree_name(git_repo, &path)? {
+                None => Some(PruneReason::Orphaned),
+                Some(git_name) if !self.force && is_locked(git_repo, &git_name) => None,
+                Some(_) => self.branch_reason(git_repo, &path),
+            };
+            if let Some(reason) = reason {
+                candidates.push(PruneCandidate {
+                    name,
+                    path,
+                    reason,
+                    git_name: None,
+                });
+            }
+        }
+
+        Ok(candidates)
+    }
+
+    fn branch_reason(&self, repo: &git2::Repository, path: &Path) -> Option<PruneReason> {
+        let worktree = git2::Repository::open(path).ok()?;
+        let head = worktree.head().ok()?;
+        let tip = head.target()?;
+
+        if let Some(branch) = head.is_branch().then(|| head.shorthand()).flatten() {
+            let tracks_remote = repo
+                .config()
+                .and_then(|config| config.get_string(&format!("branch.{branch}.merge")))
+                .is_ok();
+            if tracks_remote {
+                if repo
+                    .find_branch(branch, BranchType::Local)
+                    .and_then(|branch| branch.upstream())
+                    .is_err()
+                {
+                    return Some(PruneReason::UpstreamGone);
+                }
+                if let Some(base) = merged_into(repo, branch, tip) {
+                    return Some(PruneReason::Merged { base });
+                }
+            }
+        }
+
+        let days = self.stale_days?;
+        let committed_at = u64::try_from(worktree.find_commit(tip).ok()?.time().seconds()).ok()?;
+        let age_days = now().saturating_sub(committed_at) / SECONDS_PER_DAY;
+        (age_days >= days).then_some(PruneReason::Stale { days: age_days })
+    }
+
+    fn prune(&self, repo: &Repo, candidate: &PruneCandidate) -> color_eyre::Result<()> {
+        match candidate.reason {
+            PruneReason::MissingDirectory => {
+                let git_name = candidate.git_name.as_deref().unwrap_or(&candidate.name);
+                let worktree = repo
+                    .git()
+                    .find_worktree(git_name)
+                    .wrap_err("failed to load git worktree metadata")?;
+                worktree
+                    .prune(Some(WorktreePruneOptions::new().locked(self.force)))
+                    .wrap_err("failed to prune worktree metadata")
+            }
+            PruneReason::Orphaned => {
+                fs::remove_dir_all(&candidate.path).wrap_err_with(|| {
+                    eyre::eyre!("failed to remove `{}`", candidate.path.display())
+                })?;
+                remove_empty_parents(&repo.worktrees_dir(), &candidate.path);
+                Ok(())
+            }
+            _ => {
+                // A forced prune only overrides the lock; uncommitted changes still
+                // stop the removal, and then the lock is restored.
+                let git_repo = repo.git();
+                let locked = find_worktree_name(git_repo, &candidate.path)?
+                    .and_then(|git_name| git_repo.find_worktree(&git_name).ok())
+                    .and_then(|worktree| match lock_status(&worktree) {
+                        WorktreeLockStatus::Locked(reason) => Some((worktree, reason)),
+                        WorktreeLockStatus::Unlocked => None,
+                    });
+                if let Some((worktree, _)) = &locked {
+                    worktree
+                        .unlock()
+                        .wrap_err("failed to unlock the worktree")?;
+                }
+                let removed = RemoveCommand::new(candidate.name.clone(), false)
+                    .with_quiet(true)
+                    .with_spawn_shell(false)
+                    .execute(repo);
+                if removed.is_err()
+                    && let Some((worktree, reason)) = &locked
+                {
+                    let _ = worktree.lock(reason.as_deref());
+                }
+                removed?;
+                remove_empty_parents(&repo.worktrees_dir(), &candidate.path);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn is_locked(repo: &git2::Repository, git_name: &str) -> bool {
+    repo.find_worktree(git_name)
+        .is_ok_and(|worktree| lock_status(&worktree) != WorktreeLockStatus::Unlocked)
+}
+
+/// The base branch `branch` is merged into, if any.
+fn merged_into(repo: &git2::Repository, branch: &str, tip: git2::Oid) -> Option<String> {
+    let remote = upstream_remote(repo, branch);
+    let base = repo
+        .config()
+        .and_then(|config| config.get_string(&base_config_key(branch)))
+        .ok()
+        .or_else(|| remote_default_branch(repo, &remote))?;
+    let target = repo
+        .refname_to_id(&format!("refs/remotes/{remote}/{base}"))
+        .or_else(|_| repo.refname_to_id(&format!("refs/heads/{base}")))
+        .ok()?;
+    let merged = tip == target || repo.graph_descendant_of(target, tip).unwrap_or(false);
+    merged.then_some(base)
+}
+
+/// Removes the directories left empty between `path` and `worktrees_dir`, e.g. `feature/`.
+fn remove_empty_parents(worktrees_dir: &Path, path: &Path) {
+    let mut current = path.parent();
+    while let Some(dir) = current {
+        if dir == worktrees_dir || !dir.starts_with(worktrees_dir) || fs::remove_dir(dir).is_err() {
+            break;
+        }
+        current = dir.parent();
+    }
+}
+
+fn confirm(count: usize) -> color_eyre::Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        output::hint(tr!("pass `--yes` to prune without confirmation"));
+        return Ok(false);
+    }
+
+    output::prompt(tr!("Prune {} worktrees? [y/N] ", count)).ok();
+    let mut answer = String::new();
+    std::io::stdin()
+        .read_line(&mut answer)
+        .wrap_err("failed to read user input")?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "Yes" | "YES"))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    use tempfile::TempDir;
+
+    use crate::commands::create::CreateCommand;
+
+    fn init_git_repo(dir: &TempDir) -> color_eyre::Result<()> {
+        run_in(dir.path(), ["git", "init"])?;
+        fs::write(dir.path().join("README.md"), "test")?;
+        run_in(dir.path(), ["git", "add", "README.md"])?;
+        run_in(
+            dir.path(),
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "Initial commit",
+            ],
+        )
+    }
+
+    fn run_in(path: &Path, cmd: impl IntoIterator<Item = &'static str>) -> color_eyre::Result<()> {
+        let mut iter = cmd.into_iter();
+        let program = iter.next().expect("command must not be empty");
+        let status = Command::new(program)
+            .current_dir(path)
+            .args(iter)
+            .status()
+            .wrap_err_with(|| eyre::eyre!("failed to run `{program}`"))?;
+
+        if !status.success() {
+            return Err(eyre::eyre!("`{program}` exited with status {status}"));
+        }
+
+        Ok(())
+    }
+
+    fn init_remote(dir: &TempDir) -> color_eyre::Result<TempDir> {
+        let remote = TempDir::new()?;
+        run_in(remote.path(), ["git", "init", "--bare"])?;
+        let url = remote.path().to_string_lossy().into_owned();
+        let status = Command::new("git")
+            .current_dir(dir.path())
+            .args(["remote", "add", "origin", &url])
+            .status()?;
+        assert!(status.success(), "failed to add remote");
+        Ok(remote)
+    }
+
+    fn create(repo: &Repo, name: &str) -> color_eyre::Result<PathBuf> {
+        CreateCommand::new(name.into(), None).create_without_enter(repo, true)?;
+        Ok(repo.worktrees_dir().join(name))
+    }
+
+    fn reasons(candidates: &[PruneCandidate]) -> Vec<(&str, &PruneReason)> {
+        candidates
+            .iter()
+            .map(|candidate| (candidate.name.as_str(), &candidate.reason))
+            .collect()
+    }
+
+    #[test]
+    fn finds_merged_gone_missing_and_orphaned_worktrees() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let _remote = init_remote(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        create(&repo, "feature/active")?;
+        let merged = create(&repo, "feature/merged")?;
+        run_in(
+            &merged,
+            ["git", "push", "--set-upstream", "origin", "feature/merged"],
+        )?;
+        let gone = create(&repo, "feature/gone")?;
+        run_in(
+            &gone,
+            ["git", "push", "--set-upstream", "origin", "feature/gone"],
+        )?;
+        run_in(&gone, ["git", "push", "origin", "--delete", "feature/gone"])?;
+        let missing = create(&repo, "feature/missing")?;
+        fs::remove_dir_all(&missing)?;
+        let orphan = repo.worktrees_dir().join("old/orphan");
+        fs::create_dir_all(&orphan)?;
+        fs::write(orphan.join(".git"), "gitdir: /nonexistent")?;
+
+        let candidates = PruneCommand::new().find_candidates(&repo)?;
+
+        assert_eq!(
+            reasons(&candidates),
+            vec![
+                ("feature/missing", &PruneReason::MissingDirectory),
+                ("feature/gone", &PruneReason::UpstreamGone),
+                (
+                    "feature/merged",
+                    &PruneReason::Merged {
+                        base: repo
+                            .git()
+                            .head()?
+                            .shorthand()
+                            .unwrap_or_default()
+                            .to_owned()
+                    }
+                ),
+                ("old/orphan", &PruneReason::Orphaned),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn stale_worktrees_are_only_offered_with_a_threshold() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        create(&repo, "feature/quiet")?;
+
+        assert!(PruneCommand::new().find_candidates(&repo)?.is_empty());
+        assert!(
+            PruneCommand::new()
+                .with_stale_days(Some(30))
+                .find_candidates(&repo)?
+                .is_empty()
+        );
+        assert_eq!(
+            reasons(
+                &PruneCommand::new()
+                    .with_stale_days(Some(0))
+                    .find_candidates(&repo)?
+            ),
+            vec![("feature/quiet", &PruneReason::Stale { days: 0 })]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn prunes_candidates_and_empty_parent_directories() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let stale = create(&repo, "feature/stale")?;
+        let orphan = repo.worktrees_dir().join("old/orphan");
+        fs::create_dir_all(&orphan)?;
+        fs::write(orphan.join(".git"), "gitdir: /nonexistent")?;
+
+        let pruned = PruneCommand::new()
+            .with_yes(true)
+            .with_stale_days(Some(0))
+            .execute(&repo)?;
+
+        assert_eq!(pruned.len(), 2);
+        assert!(!stale.exists());
+        assert!(!repo.worktrees_dir().join("feature").exists());
+        assert!(!repo.worktrees_dir().join("old").exists());
+        assert!(PruneCommand::new().find_candidates(&repo)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn locked_worktrees_are_only_pruned_with_force() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let usb = create(&repo, "feature/usb")?;
+        run_in(dir.path(), ["git", "worktree", "lock", "feature/usb"])?;
+
+        let stale = PruneCommand::new().with_stale_days(Some(0));
+        assert!(stale.find_candidates(&repo)?.is_empty());
+
+        let pruned = stale.with_force(true).with_yes(true).execute(&repo)?;
+        assert_eq!(pruned.len(), 1);
+        assert!(!usb.exists());
+
+        Ok(())
+    }
+}
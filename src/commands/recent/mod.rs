@@ -0,0 +1,230 @@
+//! `recent`: the worktrees opened last with `worktree open` or `switch`,
+//! numbered so that `recent <number>` opens one of them again.
+
+use std::{
+    cmp::Reverse,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre;
+use owo_colors::OwoColorize;
+
+use crate::{
+    Repo,
+    commands::{
+        info::format_age,
+        list::{find_worktrees, format_worktree},
+        open::{
+            OpenCommand,
+            history::{OpenHistory, RecentWorktree},
+        },
+    },
+    output::{self, Cell, Table},
+};
+
+/// Worktrees listed without `--limit`.
+pub const DEFAULT_LIMIT: usize = 10;
+
+#[derive(Debug)]
+pub struct RecentCommand {
+    limit: usize,
+    most_used: bool,
+    pick: Option<usize>,
+}
+
+impl Default for RecentCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RecentCommand {
+    pub fn new() -> Self {
+        Self {
+            limit: DEFAULT_LIMIT,
+            most_used: false,
+            pick: None,
+        }
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Order by how often the worktrees were opened instead of when.
+    pub fn with_most_used(mut self, most_used: bool) -> Self {
+        self.most_used = most_used;
+        self
+    }
+
+    /// Open the worktree with this number in the listing instead of printing it.
+    pub fn with_pick(mut self, pick: Option<usize>) -> Self {
+        self.pick = pick;
+        self
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let entries = self.entries(repo)?;
+        if let Some(number) = self.pick {
+            let Some(entry) = number.checked_sub(1).and_then(|index| entries.get(index)) else {
+                return Err(eyre::eyre!(
+                    "there is no recent worktree number {number}; run `rsworktree recent` to list them"
+                ));
+            };
+            return OpenCommand::new(Some(entry.name.clone()), None).execute(repo);
+        }
+
+        if entries.is_empty() {
+            output::info(tr!(
+                "No worktree has been opened yet; `worktree open` and `switch` are remembered."
+            ));
+            return Ok(());
+        }
+        output::heading(tr!("Recently opened worktrees:"));
+        for line in render(&entries, now()) {
+            println!("{line}");
+        }
+        output::hint(tr!("Open one with `rsworktree recent <number>`."));
+        Ok(())
+    }
+
+    /// The worktrees from the history that still exist, in listing order.
+    fn entries(&self, repo: &Repo) -> color_eyre::Result<Vec<RecentWorktree>> {
+        let existing = find_worktrees(repo)?
+            .iter()
+            .map(|path| format_worktree(path))
+            .collect::<Vec<_>>();
+        let mut entries = OpenHistory::load(&repo.rsworktree_dir())
+            .recent()
+            .into_iter()
+            .filter(|entry| existing.contains(&entry.name))
+            .collect::<Vec<_>>();
+        if self.most_used {
+            // Stable, so equally used worktrees stay in recency order.
+            entries.sort_by_key(|entry| Reverse(entry.opens));
+        }
+        entries.truncate(self.limit);
+        Ok(entries)
+    }
+}
+
+fn render(entries: &[RecentWorktree], now: u64) -> Vec<String> {
+    let mut table = Table::new();
+    for (index, entry) in entries.iter().enumerate() {
+        table.push_row(vec![
+            Cell::new(format!("{}", index + 1)).with_style(bold),
+            Cell::new(entry.name.clone()).with_style(cyan),
+            Cell::new(format_age(now.saturating_sub(entry.last_opened))),
+            Cell::new(tr!("opened {} time(s)", entry.opens)).with_style(dimmed),
+        ]);
+    }
+    table.render()
+}
+
+fn bold(text: &str) -> String {
+    format!("{}", text.bold())
+}
+
+fn cyan(text: &str) -> String {
+    format!("{}", text.cyan())
+}
+
+fn dimmed(text: &str) -> String {
+    format!("{}", text.dimmed())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::process::Command;
+
+    use tempfile::TempDir;
+
+    use crate::commands::create::CreateCommand;
+
+    #[test]
+    fn lists_existing_worktrees_by_recency_or_use() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        Command::new("git")
+            .args(["init", "--quiet"])
+            .current_dir(dir.path())
+            .status()?;
+        Command::new("git")
+            .args([
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--quiet",
+                "--allow-empty",
+                "-m",
+                "Initial",
+            ])
+            .current_dir(dir.path())
+            .status()?;
+        let repo = Repo::discover_from(dir.path())?;
+        CreateCommand::new("feature/a".into(), None).create_without_enter(&repo, true)?;
+        CreateCommand::new("feature/b".into(), None).create_without_enter(&repo, true)?;
+        let mut history = OpenHistory::load(&repo.rsworktree_dir());
+        for name in ["feature/a", "feature/a", "feature/gone", "feature/b"] {
+            history.record(name)?;
+        }
+
+        let names = |command: &RecentCommand| -> color_eyre::Result<Vec<String>> {
+            Ok(command
+                .entries(&repo)?
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect())
+        };
+        assert_eq!(names(&RecentCommand::new())?, ["feature/b", "feature/a"]);
+        assert_eq!(
+            names(&RecentCommand::new().with_most_used(true))?,
+            ["feature/a", "feature/b"]
+        );
+        assert_eq!(names(&RecentCommand::new().with_limit(1))?, ["feature/b"]);
+
+        let error = RecentCommand::new()
+            .with_pick(Some(3))
+            .execute(&repo)
+            .expect_err("there are only two");
+        assert!(error.to_string().contains("no recent worktree number 3"));
+        Ok(())
+    }
+
+    #[test]
+    fn render_numbers_the_worktrees() {
+        let lines = render(
+            &[
+                RecentWorktree {
+                    name: "feature/login".into(),
+                    last_opened: 0,
+                    opens: 3,
+                },
+                RecentWorktree {
+                    name: "hotfix".into(),
+                    last_opened: 7_000,
+                    opens: 1,
+                },
+            ],
+            7_200,
+        );
+        assert_eq!(
+            lines,
+            [
+                "1  feature/login  2h ago  opened 3 time(s)",
+                "2  hotfix         3m ago  opened 1 time(s)",
+            ]
+        );
+    }
+}
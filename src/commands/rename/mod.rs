@@ -0,0 +1,287 @@
+use std::{
+    cell::RefCell,
+    env, fs,
+    path::{Component, Path},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    Repo, annotations,
+    commands::open::{multiplexer::window_name, resolve_by_name},
+    output,
+    process::{CommandRunner, SystemCommandRunner, command_failure},
+    repo::ensure_unreserved_name,
+    worktree::{inspect_worktree, ports::PortRegistry},
+};
+
+#[derive(Debug)]
+pub struct RenameCommand<R = SystemCommandRunner> {
+    name: String,
+    new_name: String,
+    rename_branch: bool,
+    no_wait: bool,
+    runner: RefCell<R>,
+}
+
+impl RenameCommand {
+    pub fn new(name: String, new_name: String) -> Self {
+        Self::with_runner(name, new_name, SystemCommandRunner)
+    }
+}
+
+impl<R> RenameCommand<R>
+where
+    R: CommandRunner,
+{
+    /// Runs git and `tmux` through `runner`.
+    pub fn with_runner(name: String, new_name: String, runner: R) -> Self {
+        Self {
+            name,
+            new_name,
+            rename_branch: false,
+            no_wait: false,
+            runner: RefCell::new(runner),
+        }
+    }
+
+    /// Also rename the branch checked out in the worktree to the new name.
+    pub fn with_rename_branch(mut self, rename: bool) -> Self {
+        self.rename_branch = rename;
+        self
+    }
+
+    /// Fail instead of waiting when another command holds the repository lock.
+    pub fn with_no_wait(mut self, no_wait: bool) -> Self {
+        self.no_wait = no_wait;
+        self
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let resolved = resolve_by_name(&self.name, repo)?;
+        let _lock = repo.lock(!self.no_wait)?;
+        validate_name(&self.new_name)?;
+        if resolved.name == self.new_name {
+            return Err(eyre::eyre!(
+                "worktree `{}` already has that name",
+                resolved.name
+            ));
+        }
+
+        let worktrees_dir = repo.worktrees_dir();
+        let target = worktrees_dir.join(&self.new_name);
+        if target.exists() {
+            return Err(eyre::eyre!(
+                "`{}` already exists; choose another name",
+                target.display()
+            ));
+        }
+        let branch = if self.rename_branch {
+            let branch = inspect_worktree(&resolved.path)?.branch.ok_or_else(|| {
+                eyre::eyre!(
+                    "worktree `{}` has a detached HEAD; there is no branch to rename",
+                    resolved.name
+                )
+            })?;
+            if !git2::Branch::name_is_valid(&self.new_name)? {
+                return Err(eyre::eyre!(
+                    "`{}` is not a valid branch name",
+                    self.new_name
+                ));
+            }
+            if branch != self.new_name
+                && repo
+                    .git()
+                    .find_branch(&self.new_name, git2::BranchType::Local)
+                    .is_ok()
+            {
+                return Err(eyre::eyre!(
+                    "branch `{}` already exists; rename without `-m` or choose another name",
+                    self.new_name
+                ));
+            }
+            Some(branch)
+        } else {
+            None
+        };
+
+        // Named before the move, while `{branch}` can still be read from the old path.
+        let old_window_name = window_name(repo, &resolved.name, &resolved.path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| eyre::eyre!("failed to create `{}`", parent.display()))?;
+        }
+        // `git worktree move` rewrites the `.git` file and the admin directory's `gitdir`.
+        self.run_git(
+            repo.root(),
+            &["worktree", "move"],
+            &[&resolved.path, &target],
+        )?;
+        self.run_git(repo.root(), &["worktree", "repair"], &[&target])?;
+        remove_empty_parents(&worktrees_dir.join(&resolved.name), &worktrees_dir);
+        let rsworktree_dir = repo.rsworktree_dir();
+        if let Err(error) = PortRegistry::new(&rsworktree_dir).rename(&resolved.name, &self.new_name)
+        {
+            output::warn(tr!(
+                "Could not move the ports of `{}`: {}",
+                resolved.name,
+                error
+            ));
+        }
+        if let Err(error) = annotations::rename(&rsworktree_dir, &resolved.name, &self.new_name) {
+            output::warn(tr!(
+                "Could not move the metadata of `{}`: {}",
+                resolved.name,
+                error
+            ));
+        }
+
+        output::success(tr!(
+            "Renamed worktree `{}` to `{}`.",
+            format_name(&resolved.name),
+            format_name(&self.new_name)
+        ));
+
+        if let Some(branch) = branch.filter(|branch| *branch != self.new_name) {
+            // Unlike libgit2, `git branch -m` also moves the `branch.<name>.*` settings.
+            self.run_git(&target, &["branch", "-m", &branch, &self.new_name], &[])?;
+            output::success(tr!(
+                "Renamed branch `{}` to `{}`.",
+                format_name(&branch),
+                format_name(&self.new_name)
+            ));
+        }
+
+        if env::var("TMUX").is_ok() {
+            self.rename_tmux_session(
+                &old_window_name,
+                &window_name(repo, &self.new_name, &target),
+            );
+        }
+
+        let cwd = env::current_dir().ok();
+        if cwd.is_some_and(|cwd| cwd.starts_with(&resolved.path)) {
+            output::hint(tr!(
+                "Your shell is still in the old directory; run `rsworktree switch {}`.",
+                self.new_name
+            ));
+        }
+        Ok(())
+    }
+
+    fn run_git(&self, dir: &Path, args: &[&str], paths: &[&Path]) -> color_eyre::Result<()> {
+        let args = args
+            .iter()
+            .map(|arg| (*arg).to_owned())
+            .chain(paths.iter().map(|path| path.display().to_string()))
+            .collect::<Vec<_>>();
+        let output = self.runner.borrow_mut().run("git", dir, &args)?;
+        if !output.success {
+            return Err(command_failure("git", &args, &output));
+        }
+        Ok(())
+    }
+
+    fn rename_tmux_session(&self, old: &str, new: &str) {
+        let tmux = |args: &[&str]| {
+            let args = args.iter().map(|arg| (*arg).to_owned()).collect::<Vec<_>>();
+            self.runner.borrow_mut().run("tmux", Path::new("."), &args)
+        };
+        let exists = tmux(&["list-sessions", "-F", "#{session_name}"])
+            .is_ok_and(|output| output.stdout.lines().any(|line| line.trim() == old));
+        if !exists {
+            return;
+        }
+
+        let renamed = tmux(&["rename-session", "-t", old, new]).is_ok_and(|output| output.success);
+        if renamed {
+            output::success(tr!(
+                "Renamed tmux session `{}` to `{}`.",
+                format_session(old),
+                format_session(new)
+            ));
+        } else {
+            output::warn(tr!("failed to rename tmux session `{}`", old));
+        }
+    }
+}
+
+/// Rejects names that would place the worktree outside `.rsworktree` or on
+/// top of its configuration and state.
+fn validate_name(name: &str) -> color_eyre::Result<()> {
+    let path = Path::new(name);
+    let inside = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if name.trim().is_empty() || !inside {
+        return Err(eyre::eyre!(
+            "`{}` is not a valid worktree name; use a relative path such as `feature/login`",
+            name
+        ));
+    }
+    ensure_unreserved_name(name)
+}
+
+/// Removes directories left empty by moving `path`, such as `.rsworktree/feature`.
+pub(crate) fn remove_empty_parents(path: &Path, worktrees_dir: &Path) {
+    for dir in path.ancestors().skip(1) {
+        // `remove_dir` refuses directories that still have entries.
+        if dir == worktrees_dir || !dir.starts_with(worktrees_dir) || fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+}
+
+fn format_name(name: &str) -> String {
+    format!(
+        "{}",
+        name.if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan().bold()))
+    )
+}
+
+fn format_session(name: &str) -> String {
+    format!(
+        "{}",
+        name.if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::{CommandOutput, ScriptedRunner};
+
+    #[test]
+    fn validate_name_rejects_paths_leaving_the_worktrees_dir() {
+        assert!(validate_name("feature/login").is_ok());
+        assert!(validate_name("../outside").is_err());
+        assert!(validate_name("feature/../../outside").is_err());
+        assert!(validate_name("/tmp/outside").is_err());
+        assert!(validate_name("./login").is_err());
+        assert!(validate_name(" ").is_err());
+        assert!(validate_name("state").is_err());
+        assert!(validate_name("logs/x").is_err());
+        assert!(validate_name("feature/state").is_ok());
+    }
+
+    #[test]
+    fn renames_the_tmux_session_only_when_it_exists() {
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::success("app/feature\nmain\n"))
+            .push(CommandOutput::success(""))
+            .push(CommandOutput::success("main\n"));
+        let command = RenameCommand::with_runner("feature".into(), "login".into(), runner.clone());
+
+        command.rename_tmux_session("app/feature", "app/login");
+        command.rename_tmux_session("app/feature", "app/login");
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0].args, ["list-sessions", "-F", "#{session_name}"]);
+        assert_eq!(
+            calls[1].args,
+            ["rename-session", "-t", "app/feature", "app/login"]
+        );
+    }
+}
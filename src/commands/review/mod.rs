@@ -1,13 +1,21 @@
+mod reviewers;
+
 use std::{
-    fmt,
     path::{Path, PathBuf},
-    process::Command,
+    time::Instant,
 };
 
 use color_eyre::eyre::{self, WrapErr};
 use owo_colors::{OwoColorize, Stream};
 
-use crate::{GitProvider, Repo};
+use crate::{
+    GitProvider, Repo, annotations,
+    commands::{create::base_config_key, rm::remote_default_branch},
+    output,
+    process::{CommandRunner, SystemCommandRunner, command_failure},
+    provider::{NewMergeRequest, ProviderBackend, ProviderClient, RequestBackend, gerrit},
+    telemetry::{Event, EventStore, elapsed_ms},
+};
 
 #[derive(Debug)]
 pub struct ReviewOptions {
@@ -33,12 +41,14 @@ pub struct ReviewCommand<R = SystemCommandRunner> {
     reviewers: Vec<String>,
     extra_args: Vec<String>,
     provider: GitProvider,
+    client: ProviderClient,
     runner: R,
 }
 
 impl ReviewCommand {
-    pub fn new(options: ReviewOptions) -> Self {
-        Self::with_runner(options, SystemCommandRunner)
+    /// Runs the provider operations through the backend configured for `repo`.
+    pub fn new(options: ReviewOptions, repo: &Repo) -> Self {
+        Self::with_runner(options, SystemCommandRunner).with_backend(ProviderBackend::resolve(repo))
     }
 }
 
@@ -68,10 +78,17 @@ where
             reviewers,
             extra_args,
             provider,
+            client: provider.client(ProviderBackend::Cli),
             runner,
         }
     }
 
+    /// Runs the provider operations through `backend` rather than the CLI.
+    pub fn with_backend(mut self, backend: ProviderBackend) -> Self {
+        self.client = self.provider.client(backend);
+        self
+    }
+
     pub fn execute(&mut self, repo: &Repo) -> color_eyre::Result<()> {
         let worktree_path = self.ensure_worktree_path(repo)?;
         let branch = self.determine_branch(&worktree_path)?;
@@ -80,27 +97,49 @@ where
         let path_label = format_with_color(&worktree_path.display().to_string(), |text| {
             format!("{}", text.blue())
         });
-        println!(
+        output::info(tr!(
             "Preparing {} {} for `{}` from `{}`...",
             self.provider.display_name(),
             self.provider.merge_request_short(),
             branch_label,
             path_label
-        );
+        ));
+
+        if self.provider == GitProvider::Gerrit {
+            self.resolve_auto_reviewers(repo, &worktree_path, &branch)?;
+            return self.push_change(repo, &worktree_path, &branch);
+        }
 
         self.ensure_pr_metadata_options()?;
+        self.resolve_auto_reviewers(repo, &worktree_path, &branch)?;
+        let title = self.issue_title(repo, &worktree_path, &branch);
 
         if self.push {
             self.push_branch(&worktree_path, &branch)?;
         } else {
-            let message = format!("Skipping push for `{}` (push disabled).", branch_label);
-            println!(
-                "{}",
-                message.if_supports_color(Stream::Stdout, |text| { format!("{}", text.dimmed()) })
+            let message = tr!("Skipping push for `{}` (push disabled).", branch_label);
+            output::info(
+                message.if_supports_color(Stream::Stdout, |text| format!("{}", text.dimmed())),
             );
         }
 
-        self.create_pull_request(&worktree_path, &branch)
+        let started = Instant::now();
+        let created = self.create_pull_request(&worktree_path, &branch, title);
+        EventStore::new(&repo.rsworktree_dir()).record(Event::ProviderCall {
+            provider: self.provider.cli_program().to_owned(),
+            command: format!("{} create", self.provider.request_subcommand()),
+            success: created.is_ok(),
+            duration_ms: elapsed_ms(started),
+        });
+        if let Some(link) = created?
+            && link.starts_with("http")
+            && let Err(error) = annotations::record(repo, &self.name, &branch, |annotations| {
+                annotations.pr = Some(link)
+            })
+        {
+            output::warn(tr!("failed to record the PR link: {}", error));
+        }
+        Ok(())
     }
 
     fn ensure_worktree_path(&self, repo: &Repo) -> color_eyre::Result<PathBuf> {
@@ -146,10 +185,11 @@ where
             self.remote.clone(),
             branch.to_owned(),
         ];
-        let output = self
-            .runner
-            .run("git", worktree_path, &args)
-            .wrap_err("failed to run `git push`")?;
+        let runner = &mut self.runner;
+        let output = output::step(tr!("Pushing `{}` to `{}`...", branch, self.remote), || {
+            runner.run("git", worktree_path, &args)
+        })
+        .wrap_err("failed to run `git push`")?;
 
         if !output.success {
             return Err(command_failure("git", &args, &output));
@@ -157,7 +197,11 @@ where
 
         let remote_label = format_with_color(&self.remote, |text| format!("{}", text.green()));
         let branch_label = format_with_color(branch, |text| format!("{}", text.magenta().bold()));
-        println!("Pushed `{}` to remote `{}`.", branch_label, remote_label);
+        output::success(tr!(
+            "Pushed `{}` to remote `{}`.",
+            branch_label,
+            remote_label
+        ));
 
         Ok(())
     }
@@ -166,44 +210,161 @@ where
         &mut self,
         worktree_path: &Path,
         branch: &str,
-    ) -> color_eyre::Result<()> {
-        let args = self.provider.build_create_args(
-            branch,
-            self.draft,
-            self.fill,
-            self.web,
-            &self.reviewers,
-            &self.extra_args,
-        );
-
-        let cli_program = self.provider.cli_program();
-        let output = self
-            .runner
-            .run(cli_program, worktree_path, &args)
-            .wrap_err_with(|| format!("failed to run `{} {} create`", cli_program, if self.provider == GitProvider::GitHub { "pr" } else { "mr" }))?;
-
-        if !output.success {
-            return Err(command_failure(cli_program, &args, &output));
-        }
+        title: Option<String>,
+    ) -> color_eyre::Result<Option<String>> {
+        let request = NewMergeRequest {
+            branch: branch.to_owned(),
+            title,
+            draft: self.draft,
+            fill: self.fill,
+            web: self.web,
+            reviewers: self.reviewers.clone(),
+            extra_args: self.extra_args.clone(),
+        };
+        let pr_link = self
+            .client
+            .create(&mut self.runner, worktree_path, &request)?;
 
         let branch_label = format_with_color(branch, |text| format!("{}", text.magenta().bold()));
-        println!(
+        output::success(tr!(
             "{} {} created for `{}`.",
             self.provider.display_name(),
             self.provider.merge_request_term(),
             branch_label
-        );
-        if let Some(pr_link) = output
-            .stdout
-            .lines()
-            .map(str::trim)
-            .find(|line| !line.is_empty())
+        ));
+        if let Some(pr_link) = &pr_link {
+            output::info(pr_link);
+        }
+        Ok(pr_link)
+    }
+
+    /// Pushes `HEAD` to `refs/for/<base>` on Gerrit, which creates the change
+    /// of the worktree or adds a patch set to it.
+    fn push_change(
+        &mut self,
+        repo: &Repo,
+        worktree_path: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<()> {
+        let base = repo
+            .git()
+            .config()
+            .and_then(|config| config.get_string(&base_config_key(branch)))
+            .ok()
+            .or_else(|| remote_default_branch(repo.git(), &self.remote))
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "cannot tell which branch `{}` is for; pass `--base` to `create` or run `git remote set-head {} --auto`",
+                    branch,
+                    self.remote
+                )
+            })?;
+        let mut args =
+            gerrit::push_args(&self.remote, &base, &self.name, self.draft, &self.reviewers);
+        args.extend(self.extra_args.iter().cloned());
+
+        let started = Instant::now();
+        let runner = &mut self.runner;
+        let output = output::step(tr!("Pushing `{}` for review...", branch), || {
+            runner.run("git", worktree_path, &args)
+        })
+        .wrap_err("failed to run `git push`");
+        EventStore::new(&repo.rsworktree_dir()).record(Event::ProviderCall {
+            provider: "git".to_owned(),
+            command: "push refs/for".to_owned(),
+            success: output.as_ref().is_ok_and(|output| output.success),
+            duration_ms: elapsed_ms(started),
+        });
+        let output = output?;
+        if !output.success {
+            return Err(command_failure("git", &args, &output));
+        }
+
+        let branch_label = format_with_color(branch, |text| format!("{}", text.magenta().bold()));
+        // Gerrit reports the change on stderr, prefixed with `remote:`.
+        let Some((number, url)) =
+            gerrit::parse_push_output(&format!("{}\n{}", output.stderr, output.stdout))
+        else {
+            output::success(tr!(
+                "Pushed `{}` for review to `refs/for/{}`.",
+                branch_label,
+                base
+            ));
+            return Ok(());
+        };
+        output::success(tr!(
+            "Pushed `{}` to Gerrit change {}.",
+            branch_label,
+            number
+        ));
+        output::info(&url);
+        if let Err(error) = annotations::record(repo, &self.name, branch, |annotations| {
+            annotations.pr = Some(url)
+        }) {
+            output::warn(tr!("failed to record the PR link: {}", error));
+        }
+        Ok(())
+    }
+
+    /// Replaces `--reviewer auto` with the owners of the changed files.
+    fn resolve_auto_reviewers(
+        &mut self,
+        repo: &Repo,
+        worktree_path: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<()> {
+        if !self
+            .reviewers
+            .iter()
+            .any(|reviewer| reviewer == reviewers::AUTO)
         {
-            println!("{}", pr_link);
+            return Ok(());
+        }
+        let found = reviewers::auto_reviewers(repo, worktree_path, branch)?;
+        if found.is_empty() {
+            output::note(tr!(
+                "No reviewers found in CODEOWNERS or `reviewers` in config.toml."
+            ));
+        } else {
+            output::info(tr!("Requesting reviews from {}.", found.join(", ")));
+        }
+
+        let explicit = std::mem::take(&mut self.reviewers)
+            .into_iter()
+            .filter(|reviewer| reviewer != reviewers::AUTO);
+        for reviewer in explicit.chain(found) {
+            if !self.reviewers.contains(&reviewer) {
+                self.reviewers.push(reviewer);
+            }
         }
         Ok(())
     }
 
+    /// `<issue key>: <summary>` for a branch created with `create --issue`,
+    /// unless a title was passed or the provider CLI takes none. The summary
+    /// is the recorded note, falling back to the head commit subject.
+    fn issue_title(&self, repo: &Repo, worktree_path: &Path, branch: &str) -> Option<String> {
+        if !self.provider.supports_title() || self.extra_args.iter().any(|arg| is_title_flag(arg)) {
+            return None;
+        }
+        let annotations = annotations::Annotations::load(repo, &self.name, branch).ok()?;
+        let issue = annotations.issue?;
+        let summary = annotations.note.or_else(|| {
+            git2::Repository::open(worktree_path)
+                .ok()?
+                .head()
+                .ok()?
+                .peel_to_commit()
+                .ok()?
+                .summary()
+                .map(str::to_owned)
+        })?;
+        if summary.starts_with(&issue) {
+            return Some(summary);
+        }
+        Some(format!("{issue}: {summary}"))
+    }
+
     fn ensure_pr_metadata_options(&mut self) -> color_eyre::Result<()> {
         if self.fill || self.web {
             return Ok(());
@@ -217,44 +378,21 @@ where
             return Ok(());
         }
 
-        let note = "No PR metadata flags provided; defaulting to `--fill`.";
-        let message = note.if_supports_color(Stream::Stdout, |text| format!("{}", text.yellow()));
-        println!("{}", message);
+        output::note(tr!("No PR metadata flags provided; defaulting to `--fill`."));
         self.fill = true;
         Ok(())
     }
 }
 
-fn command_failure(program: &str, args: &[String], output: &CommandOutput) -> color_eyre::Report {
-    let command_line = format_command(program, args);
-    let status = match output.status_code {
-        Some(code) => format!("exit status {code}"),
-        None => "termination by signal".to_owned(),
-    };
-
-    let mut message = format!("`{command_line}` failed with {status}");
-    let stderr = output.stderr.trim();
-    if !stderr.is_empty() {
-        message.push('\n');
-        message.push_str(stderr);
-    }
-
-    eyre::eyre!(message)
-}
-
 fn format_with_color(value: &str, paint: impl Fn(&str) -> String) -> String {
     value
         .if_supports_color(Stream::Stdout, |text| paint(text))
         .to_string()
 }
 
-fn format_command(program: &str, args: &[String]) -> String {
-    let mut parts = Vec::with_capacity(1 + args.len());
-    parts.push(quote_arg(program));
-    for arg in args {
-        parts.push(quote_arg(arg));
-    }
-    parts.join(" ")
+fn is_title_flag(arg: &str) -> bool {
+    let cleaned = arg.trim();
+    matches!(cleaned, "--title" | "-t") || cleaned.starts_with("--title=")
 }
 
 fn metadata_flag_allows_noninteractive(arg: &str) -> bool {
@@ -282,76 +420,12 @@ fn metadata_flag_allows_noninteractive(arg: &str) -> bool {
         || cleaned.starts_with("--body-file=")
 }
 
-fn quote_arg(value: &str) -> String {
-    if value
-        .chars()
-        .all(|c| matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' | '/' | '='))
-    {
-        value.to_owned()
-    } else {
-        let escaped = value.replace('\'', "'\\''");
-        format!("'{escaped}'")
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct CommandOutput {
-    pub stdout: String,
-    pub stderr: String,
-    pub success: bool,
-    pub status_code: Option<i32>,
-}
-
-pub trait CommandRunner {
-    fn run(
-        &mut self,
-        program: &str,
-        current_dir: &Path,
-        args: &[String],
-    ) -> color_eyre::Result<CommandOutput>;
-}
-
-#[derive(Debug, Clone, Default)]
-pub struct SystemCommandRunner;
-
-impl CommandRunner for SystemCommandRunner {
-    fn run(
-        &mut self,
-        program: &str,
-        current_dir: &Path,
-        args: &[String],
-    ) -> color_eyre::Result<CommandOutput> {
-        let output = Command::new(program)
-            .current_dir(current_dir)
-            .args(args)
-            .output()
-            .wrap_err_with(|| {
-                eyre::eyre!("failed to execute `{}`", format_command(program, args))
-            })?;
-
-        Ok(CommandOutput {
-            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
-            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
-            success: output.status.success(),
-            status_code: output.status.code(),
-        })
-    }
-}
-
-impl fmt::Display for CommandOutput {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "success: {}, status_code: {:?}, stdout: {:?}, stderr: {:?}",
-            self.success, self.status_code, self.stdout, self.stderr
-        )
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{collections::VecDeque, fs};
+    use std::{collections::VecDeque, fs, process::Command};
+
+    use crate::process::CommandOutput;
 
     use tempfile::TempDir;
 
@@ -388,20 +462,6 @@ mod tests {
         }
     }
 
-    #[test]
-    fn format_command_quotes_arguments_with_special_characters() {
-        let command = format_command(
-            "gh",
-            &[
-                "pr".into(),
-                "create".into(),
-                "--title".into(),
-                "Ready for review".into(),
-            ],
-        );
-        assert_eq!(command, "gh pr create --title 'Ready for review'");
-    }
-
     #[derive(Debug, Default)]
     struct MockCommandRunner {
         responses: VecDeque<color_eyre::Result<CommandOutput>>,
@@ -677,6 +737,101 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn records_created_pr_link_as_annotation() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        init_git_repo(&repo_dir)?;
+        let repo = Repo::discover_from(repo_dir.path())?;
+        fs::create_dir_all(repo.worktrees_dir().join("feature/test"))?;
+
+        let mut runner = MockCommandRunner::default();
+        runner.responses.extend([
+            Ok(CommandOutput {
+                stdout: "feature/test\n".into(),
+                stderr: String::new(),
+                success: true,
+                status_code: Some(0),
+            }),
+            Ok(CommandOutput {
+                stdout: "https://github.com/acme/app/pull/42\n".into(),
+                stderr: String::new(),
+                success: true,
+                status_code: Some(0),
+            }),
+        ]);
+
+        let options = ReviewOptions {
+            name: "feature/test".into(),
+            push: false,
+            draft: false,
+            fill: true,
+            web: false,
+            remote: "origin".into(),
+            reviewers: Vec::new(),
+            extra_args: Vec::new(),
+            provider: GitProvider::GitHub,
+        };
+        ReviewCommand::with_runner(options, runner).execute(&repo)?;
+
+        let recorded = annotations::Annotations::load(&repo, "feature/test", "feature/test")?;
+        assert_eq!(
+            recorded.pr.as_deref(),
+            Some("https://github.com/acme/app/pull/42")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn prefixes_pr_title_with_recorded_issue_key() -> color_eyre::Result<()> {
+        let repo_dir = TempDir::new()?;
+        init_git_repo(&repo_dir)?;
+        run(&repo_dir, ["git", "branch", "PROJ-7-fix-login"])?;
+        let repo = Repo::discover_from(repo_dir.path())?;
+        fs::create_dir_all(repo.worktrees_dir().join("PROJ-7-fix-login"))?;
+        annotations::record(
+            &repo,
+            "PROJ-7-fix-login",
+            "PROJ-7-fix-login",
+            |annotations| {
+                annotations.issue = Some("PROJ-7".into());
+                annotations.note = Some("Fix login".into());
+            },
+        )?;
+
+        let mut runner = MockCommandRunner::default();
+        for stdout in ["PROJ-7-fix-login\n", ""] {
+            runner.responses.push_back(Ok(CommandOutput {
+                stdout: stdout.into(),
+                stderr: String::new(),
+                success: true,
+                status_code: Some(0),
+            }));
+        }
+
+        let options = ReviewOptions {
+            name: "PROJ-7-fix-login".into(),
+            push: false,
+            draft: false,
+            fill: false,
+            web: false,
+            remote: "origin".into(),
+            reviewers: Vec::new(),
+            extra_args: Vec::new(),
+            provider: GitProvider::GitHub,
+        };
+        let mut command = ReviewCommand::with_runner(options, runner);
+        command.execute(&repo)?;
+
+        let create = &command.runner.calls[1];
+        assert_eq!(
+            create.args[create.args.len() - 3..],
+            ["--fill", "--title", "PROJ-7: Fix login"]
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn errors_when_worktree_missing() -> color_eyre::Result<()> {
         let repo_dir = TempDir::new()?;
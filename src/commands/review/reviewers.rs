@@ -0,0 +1,254 @@
+//! Picks reviewers for `review --reviewer auto`.
+//!
+//! The owners of the files changed since the base branch are read from the
+//! worktree's CODEOWNERS file. Patterns follow GitHub's rules: the last
+//! matching line wins, a pattern without a `/` in the middle matches at any
+//! depth and a directory pattern covers everything below it. Only `@user`
+//! and `@org/team` owners are used; e-mail owners cannot be requested as
+//! reviewers. When nobody is found, `reviewers` from `config.toml` is used.
+
+use std::{fs, io::ErrorKind, path::Path};
+
+use color_eyre::eyre::{self, WrapErr};
+use git2::Repository;
+
+use crate::{
+    Repo,
+    commands::{
+        create::base_config_key,
+        rm::{remote_default_branch, upstream_remote},
+    },
+    worktree::copy::matches_components,
+};
+
+/// The `--reviewer` value that asks for automatic reviewers.
+pub(crate) const AUTO: &str = "auto";
+
+/// Where GitHub and GitLab look for the file, in their order of precedence.
+const CODEOWNERS_PATHS: [&str; 4] = [
+    ".github/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+];
+
+/// Reviewers for the changes of `branch`, checked out at `worktree_path`.
+pub(crate) fn auto_reviewers(
+    repo: &Repo,
+    worktree_path: &Path,
+    branch: &str,
+) -> color_eyre::Result<Vec<String>> {
+    let mut reviewers = Vec::new();
+    if let Some(owners) = CodeOwners::find(worktree_path)? {
+        for file in changed_files(worktree_path, branch)? {
+            for owner in owners.owners(&file) {
+                if !reviewers.contains(owner) {
+                    reviewers.push(owner.clone());
+                }
+            }
+        }
+    }
+    if reviewers.is_empty() {
+        reviewers = repo.config()?.reviewers;
+    }
+    Ok(reviewers)
+}
+
+/// Files changed between the merge base with the base branch and `HEAD`.
+///
+/// Without a recorded base branch or a remote default branch nothing counts
+/// as changed.
+fn changed_files(worktree_path: &Path, branch: &str) -> color_eyre::Result<Vec<String>> {
+    let git = Repository::open(worktree_path)
+        .wrap_err_with(|| eyre::eyre!("failed to open `{}`", worktree_path.display()))?;
+    let remote = upstream_remote(&git, branch);
+    let Some(base) = git
+        .config()
+        .and_then(|config| config.get_string(&base_config_key(branch)))
+        .ok()
+        .or_else(|| remote_default_branch(&git, &remote))
+    else {
+        return Ok(Vec::new());
+    };
+    let Ok(target) = git
+        .refname_to_id(&format!("refs/remotes/{remote}/{base}"))
+        .or_else(|_| git.refname_to_id(&format!("refs/heads/{base}")))
+    else {
+        return Ok(Vec::new());
+    };
+
+    let head = git.head()?.peel_to_commit()?;
+    let merge_base = git
+        .merge_base(head.id(), target)
+        .wrap_err_with(|| eyre::eyre!("`{branch}` shares no history with `{base}`"))?;
+    let base_tree = git.find_commit(merge_base)?.tree()?;
+    let diff = git.diff_tree_to_tree(Some(&base_tree), Some(&head.tree()?), None)?;
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+        .map(|path| path.to_string_lossy().replace('\\', "/"))
+        .collect())
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    /// Path components as understood by [`matches_components`].
+    pattern: Vec<String>,
+    owners: Vec<String>,
+}
+
+impl CodeOwners {
+    /// Reads the first CODEOWNERS file found in the worktree.
+    pub(crate) fn find(worktree_path: &Path) -> color_eyre::Result<Option<Self>> {
+        for relative in CODEOWNERS_PATHS {
+            let path = worktree_path.join(relative);
+            match fs::read_to_string(&path) {
+                Ok(text) => return Ok(Some(Self::parse(&text))),
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => {
+                    return Err(err)
+                        .wrap_err_with(|| eyre::eyre!("failed to read `{}`", path.display()));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Parses CODEOWNERS lines; GitLab section headers are skipped.
+    pub(crate) fn parse(text: &str) -> Self {
+        let rules = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with(['#', '[', '^']))
+            .filter_map(|line| {
+                let mut words = line
+                    .split_whitespace()
+                    .take_while(|word| !word.starts_with('#'));
+                let pattern = compile(words.next()?);
+                let owners = words
+                    .filter_map(|owner| owner.strip_prefix('@'))
+                    .filter(|owner| !owner.is_empty())
+                    .map(str::to_owned)
+                    .collect();
+                Some(Rule { pattern, owners })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Owners of `path`, relative to the repository root with `/` separators.
+    pub(crate) fn owners(&self, path: &str) -> &[String] {
+        let path = path.split('/').map(str::to_owned).collect::<Vec<_>>();
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                let pattern = rule.pattern.iter().map(String::as_str).collect::<Vec<_>>();
+                matches_components(&pattern, &path, false)
+            })
+            .map_or(&[], |rule| rule.owners.as_slice())
+    }
+}
+
+fn compile(pattern: &str) -> Vec<String> {
+    let directory = pattern.ends_with('/');
+    let body = pattern.trim_matches('/');
+    let anchored = pattern.starts_with('/') || body.contains('/');
+
+    let mut components = Vec::new();
+    if !anchored {
+        components.push("**".to_owned());
+    }
+    components.extend(body.split('/').map(str::to_owned));
+    if directory {
+        components.push("*".to_owned());
+        components.push("**".to_owned());
+    } else if !body.rsplit('/').next().unwrap_or_default().contains('*') {
+        // A file or a directory: `docs` also owns `docs/guide.md`.
+        components.push("**".to_owned());
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::process::Command;
+
+    use tempfile::TempDir;
+
+    const CODEOWNERS: &str = "\
+# Default owners
+*               @acme/core
+*.js            @alice
+/docs/          @bob  # documentation
+apps/*          @carol
+[Backend]
+src/**/db       @dave mail@example.com
+/vendor/
+";
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let owners = CodeOwners::parse(CODEOWNERS);
+
+        assert_eq!(owners.owners("README.md"), ["acme/core"]);
+        assert_eq!(owners.owners("web/index.js"), ["alice"]);
+        assert_eq!(owners.owners("docs/guide/intro.md"), ["bob"]);
+        assert_eq!(owners.owners("docs"), ["acme/core"]);
+        assert_eq!(owners.owners("apps/web.toml"), ["carol"]);
+        assert_eq!(owners.owners("apps/web/main.rs"), ["acme/core"]);
+        assert_eq!(owners.owners("src/a/b/db/schema.sql"), ["dave"]);
+        assert!(owners.owners("vendor/lib.rs").is_empty());
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> color_eyre::Result<()> {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()?;
+        assert!(status.success(), "git {args:?} failed");
+        Ok(())
+    }
+
+    #[test]
+    fn auto_reviewers_own_the_files_changed_since_the_base() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let root = dir.path();
+        git(root, &["init", "-b", "main"])?;
+        fs::create_dir_all(root.join(".github"))?;
+        fs::write(root.join(".github/CODEOWNERS"), CODEOWNERS)?;
+        fs::write(root.join("README.md"), "readme")?;
+        git(root, &["add", "."])?;
+        git(root, &["commit", "-m", "init"])?;
+        git(root, &["checkout", "-b", "feature"])?;
+        fs::create_dir_all(root.join("docs"))?;
+        fs::write(root.join("docs/guide.md"), "guide")?;
+        fs::write(root.join("app.js"), "app")?;
+        git(root, &["add", "."])?;
+        git(root, &["commit", "-m", "docs"])?;
+        git(root, &["config", &base_config_key("feature"), "main"])?;
+        let repo = Repo::discover_from(root)?;
+
+        let mut reviewers = auto_reviewers(&repo, root, "feature")?;
+        reviewers.sort();
+        assert_eq!(reviewers, ["alice", "bob"]);
+
+        repo.ensure_rsworktree_dir()?;
+        fs::write(
+            repo.rsworktree_dir().join("config.toml"),
+            "reviewers = [\"erin\"]\n",
+        )?;
+        fs::write(root.join(".github/CODEOWNERS"), "/vendor/ @acme/vendor\n")?;
+        assert_eq!(auto_reviewers(&repo, root, "feature")?, ["erin"]);
+
+        Ok(())
+    }
+}
@@ -1,11 +1,30 @@
-use std::{fs, io::Write, io::IsTerminal, path::Path, process::Command};
+use std::{
+    fs,
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 use color_eyre::eyre::{self, Context};
 use owo_colors::{OwoColorize, Stream};
 
-use git2::{BranchType, ErrorCode, WorktreePruneOptions};
-
-use crate::{Repo, commands::cd::shell_command};
+use git2::{BranchType, ErrorCode, WorktreeLockStatus, WorktreePruneOptions};
+
+use crate::{
+    Repo, annotations,
+    commands::{
+        cd::shell_command,
+        create::{base_config_key, template_config_key},
+        list::is_managed,
+        lock::{describe_reason, lock_status},
+        open::{ResolvedWorktree, multiplexer::window_name},
+    },
+    hooks::{HookContext, HookFailureMode, HookName, HookRunner},
+    output,
+    templates::Template,
+    webhook::{WebhookEvent, WebhookNotifier},
+    worktree::{inspect_worktree, ports::PortRegistry},
+};
 
 #[cfg(test)]
 use crate::commands::cd::SHELL_OVERRIDE_ENV;
@@ -13,10 +32,14 @@ use crate::commands::cd::SHELL_OVERRIDE_ENV;
 #[derive(Debug)]
 pub struct RemoveCommand {
     name: String,
+    /// Set for a worktree outside the worktrees directory.
+    path: Option<PathBuf>,
     force: bool,
     quiet: bool,
     remove_local_branch: bool,
+    remove_remote_branch: bool,
     spawn_shell: bool,
+    no_wait: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -25,9 +48,16 @@ pub enum LocalBranchStatus {
     NotFound,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteBranchStatus {
+    Deleted,
+    NotFound,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RemoveOutcome {
     pub local_branch: Option<LocalBranchStatus>,
+    pub remote_branch: Option<RemoteBranchStatus>,
     pub repositioned: bool,
 }
 
@@ -35,10 +65,30 @@ impl RemoveCommand {
     pub fn new(name: String, force: bool) -> Self {
         Self {
             name,
+            path: None,
             force,
             quiet: false,
             remove_local_branch: false,
+            remove_remote_branch: false,
             spawn_shell: true,
+            no_wait: false,
+        }
+    }
+
+    /// Removes `resolved`, which may have been added with `git worktree add`
+    /// outside the worktrees directory. Such a worktree is removed at its path
+    /// and its branch is the one it has checked out.
+    pub(crate) fn for_resolved(repo: &Repo, resolved: ResolvedWorktree, force: bool) -> Self {
+        if is_managed(&repo.worktrees_dir(), &resolved.path) {
+            return Self::new(resolved.name, force);
+        }
+        let name = inspect_worktree(&resolved.path)
+            .ok()
+            .and_then(|status| status.branch)
+            .unwrap_or(resolved.name);
+        Self {
+            path: Some(resolved.path),
+            ..Self::new(name, force)
         }
     }
 
@@ -52,14 +102,41 @@ impl RemoveCommand {
         self
     }
 
+    /// Also deletes the branch on its remote, but only once it is merged into
+    /// its base branch unless the command is forced.
+    pub fn with_remove_remote_branch(mut self, remove: bool) -> Self {
+        self.remove_remote_branch = remove;
+        self
+    }
+
     pub fn with_spawn_shell(mut self, spawn: bool) -> Self {
         self.spawn_shell = spawn;
         self
     }
 
+    /// Fail instead of waiting when another command holds the repository lock.
+    pub fn with_no_wait(mut self, no_wait: bool) -> Self {
+        self.no_wait = no_wait;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Where the worktree to remove is checked out, whether or not it exists.
+    pub fn worktree_path(&self, repo: &Repo) -> PathBuf {
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => repo.worktrees_dir().join(&self.name),
+        };
+        fs::canonicalize(&path).unwrap_or(path)
+    }
+
     pub fn execute(&self, repo: &Repo) -> color_eyre::Result<RemoveOutcome> {
         let worktrees_dir = repo.worktrees_dir();
-        if !worktrees_dir.exists() {
+        let rsworktree_dir = repo.rsworktree_dir();
+        if self.path.is_none() && !worktrees_dir.exists() {
             let dir = format!("{}", worktrees_dir.display());
             let dir = format!(
                 "{}",
@@ -67,21 +144,22 @@ impl RemoveCommand {
                     .if_supports_color(Stream::Stdout, |text| format!("{}", text.blue()))
             );
             if !self.quiet {
-                println!(
+                output::info(tr!(
                     "No worktrees directory found at `{}`; nothing to remove.",
                     dir
-                );
+                ));
             }
             return Ok(RemoveOutcome {
                 local_branch: self
                     .remove_local_branch
                     .then_some(LocalBranchStatus::NotFound),
+                remote_branch: None,
                 repositioned: false,
             });
         }
 
-        let worktree_path = worktrees_dir.join(&self.name);
-        let worktree_path = fs::canonicalize(&worktree_path).unwrap_or(worktree_path);
+        let lock = repo.lock(!self.no_wait)?;
+        let worktree_path = self.worktree_path(repo);
 
         if !worktree_path.exists() {
             let name = format!(
@@ -91,16 +169,17 @@ impl RemoveCommand {
                     .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
             );
             if !self.quiet {
-                println!(
+                output::info(tr!(
                     "Worktree `{}` does not exist under `{}`.",
                     name,
                     worktrees_dir.display()
-                );
+                ));
             }
             return Ok(RemoveOutcome {
                 local_branch: self
                     .remove_local_branch
                     .then_some(LocalBranchStatus::NotFound),
+                remote_branch: None,
                 repositioned: false,
             });
         }
@@ -116,21 +195,45 @@ impl RemoveCommand {
                         .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
                 );
                 if !self.quiet {
-                    println!(
+                    output::info(tr!(
                         "Worktree `{}` does not exist under `{}`.",
                         name,
                         worktrees_dir.display()
-                    );
+                    ));
                 }
                 return Ok(RemoveOutcome {
                     local_branch: self
                         .remove_local_branch
                         .then_some(LocalBranchStatus::NotFound),
+                    remote_branch: None,
                     repositioned: false,
                 });
             }
         };
 
+        // Not even `--force` deletes a protected branch.
+        if (self.remove_local_branch || self.remove_remote_branch)
+            && let Some(pattern) = repo.config()?.protected_pattern(&self.name)
+        {
+            return Err(eyre::eyre!(
+                "refusing to delete `{}`, a protected branch (`{}` in `protected_branches`); remove the worktree without `--delete-branch` and `--remote`",
+                self.name,
+                pattern
+            ));
+        }
+
+        if !self.force
+            && let Ok(worktree) = git_repo.find_worktree(&worktree_name)
+            && let WorktreeLockStatus::Locked(reason) = lock_status(&worktree)
+        {
+            return Err(eyre::eyre!(
+                "worktree `{}` is locked{}; run `rsworktree unlock {}` or pass `--force` to remove it anyway",
+                self.name,
+                describe_reason(reason.as_deref()),
+                self.name
+            ));
+        }
+
         if !self.force
             && !self.quiet
             && std::io::stdin().is_terminal()
@@ -142,12 +245,11 @@ impl RemoveCommand {
                     .as_str()
                     .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
             );
-            println!(
-                "Warning: branch `{}` has not been pushed to any remote.",
+            output::warn(tr!(
+                "branch `{}` has not been pushed to any remote.",
                 branch_label
-            );
-            print!("Are you sure you want to remove this worktree? [y/N] ");
-            std::io::stdout().flush().ok();
+            ));
+            output::prompt(tr!("Are you sure you want to remove this worktree? [y/N] ")).ok();
 
             let mut answer = String::new();
             std::io::stdin()
@@ -157,11 +259,60 @@ impl RemoveCommand {
             if !matches!(answer.trim(), "y" | "Y" | "yes" | "Yes" | "YES") {
                 return Ok(RemoveOutcome {
                     local_branch: None,
+                    remote_branch: None,
                     repositioned: false,
                 });
             }
         }
 
+        if !self.force {
+            // A worktree that cannot be inspected may hold uncommitted work too.
+            let status = inspect_worktree(&worktree_path).wrap_err_with(|| {
+                eyre::eyre!(
+                    "cannot tell whether worktree `{}` has uncommitted changes; pass `--force` to remove it anyway",
+                    self.name
+                )
+            })?;
+            if !status.changes.is_clean() {
+                return Err(eyre::eyre!(
+                    "worktree `{}` has uncommitted changes ({}); commit them, park them with `rsworktree stash {}`, or pass `--force` to discard them",
+                    self.name,
+                    status.changes.describe(),
+                    self.name
+                ));
+            }
+        }
+
+        // Decide about the remote branch before anything is removed so a refusal leaves no trace.
+        let remote = if self.remove_remote_branch {
+            let remote = upstream_remote(git_repo, &self.name);
+            if !self.force {
+                self.ensure_merged(git_repo, &remote)?;
+            }
+            Some(remote)
+        } else {
+            None
+        };
+
+        let hook_context = self.hook_context(git_repo, &worktrees_dir, &worktree_path);
+        let hook_runner = self
+            .hook_runner(git_repo, &rsworktree_dir, &hook_context)
+            .with_repository(&repo.name());
+        let mode = hook_runner.mode();
+        let hook_runner = if hook_runner.pre_remove_aborts() {
+            hook_runner.with_mode(HookFailureMode::FailFast)
+        } else {
+            hook_runner
+        };
+        hook_runner
+            .run_hook(HookName::PreRemove, &hook_context)
+            .wrap_err_with(|| {
+                eyre::eyre!("`pre-remove` hook aborted the removal of `{}`", self.name)
+            })?;
+        let hook_runner = hook_runner.with_mode(mode);
+        // Named while `{branch}` can still be read from the worktree.
+        let session_name = window_name(repo, &self.name, &worktree_path);
+
         let worktree = git_repo.find_worktree(&worktree_name).wrap_err_with(|| {
             eyre::eyre!("failed to load git worktree metadata for `{}`", self.name)
         })?;
@@ -194,17 +345,47 @@ impl RemoveCommand {
                 .as_str()
                 .if_supports_color(Stream::Stdout, |text| format!("{}", text.red().bold()))
         );
+        let location = match &self.path {
+            Some(_) => worktree_path.parent().unwrap_or(&worktree_path),
+            None => &worktrees_dir,
+        };
+        if let Err(error) = PortRegistry::new(&rsworktree_dir).release(&self.name) {
+            output::warn(tr!(
+                "Could not release the ports of `{}`: {}",
+                self.name,
+                error
+            ));
+        }
+        if self.path.is_none()
+            && let Err(error) = annotations::remove(&rsworktree_dir, &self.name)
+        {
+            output::warn(tr!(
+                "Could not remove the metadata of `{}`: {}",
+                self.name,
+                error
+            ));
+        }
         if !self.quiet {
-            println!(
+            output::success(tr!(
                 "Removed worktree `{}` from `{}`.",
                 name,
-                worktrees_dir.display()
-            );
+                location.display()
+            ));
         }
+        WebhookNotifier::new(&rsworktree_dir, &repo.name()).notify(
+            WebhookEvent::Remove,
+            &self.name,
+            Some(&self.name),
+            tr!(
+                "Removed worktree `{}` from `{}`.",
+                self.name,
+                location.display()
+            ),
+        );
 
         // Close tmux session if it exists
         if std::env::var("TMUX").is_ok() {
-            self.close_tmux_session(repo);
+            self.close_tmux_session(&session_name);
         }
 
         let need_reposition = match std::env::current_dir() {
@@ -220,6 +401,14 @@ impl RemoveCommand {
         } else {
             None
         };
+        let remote_branch = match &remote {
+            Some(remote) => Some(self.delete_remote_branch(repo, remote)?),
+            None => None,
+        };
+
+        hook_runner.run_hook(HookName::PostRemove, &hook_context)?;
+        // The root shell below may stay open for a long time.
+        drop(lock);
 
         if need_reposition {
             std::env::set_current_dir(repo.root()).wrap_err_with(|| {
@@ -237,7 +426,7 @@ impl RemoveCommand {
                     .if_supports_color(Stream::Stdout, |text| format!("{}", text.blue().bold()))
             );
             if !self.quiet {
-                println!("Now in root `{}`.", root_display);
+                output::info(tr!("Now in root `{}`.", root_display));
             }
 
             if self.spawn_shell {
@@ -257,10 +446,43 @@ impl RemoveCommand {
 
         Ok(RemoveOutcome {
             local_branch,
+            remote_branch,
             repositioned: need_reposition,
         })
     }
 
+    fn hook_context(
+        &self,
+        repo: &git2::Repository,
+        worktrees_dir: &Path,
+        worktree_path: &Path,
+    ) -> HookContext {
+        HookContext::for_worktree(repo, worktrees_dir, &self.name, worktree_path)
+    }
+
+    /// The hook runner, extended by the template the branch was created with.
+    fn hook_runner(
+        &self,
+        repo: &git2::Repository,
+        rsworktree_dir: &Path,
+        context: &HookContext,
+    ) -> HookRunner {
+        let hook_runner = HookRunner::new(rsworktree_dir);
+        let Ok(name) = repo
+            .config()
+            .and_then(|config| config.get_string(&template_config_key(&context.branch)))
+        else {
+            return hook_runner;
+        };
+        match Template::load(rsworktree_dir, &name) {
+            Ok(template) => template.apply_to_hooks(hook_runner, context),
+            Err(error) => {
+                output::warn(tr!("Skipping the hooks of template `{}`: {}", name, error));
+                hook_runner
+            }
+        }
+    }
+
     fn delete_local_branch(&self, repo: &Repo) -> color_eyre::Result<LocalBranchStatus> {
         let git_repo = repo.git();
         match git_repo.find_branch(&self.name, BranchType::Local) {
@@ -295,7 +517,7 @@ impl RemoveCommand {
                                 format!("{}", text.magenta().bold())
                             })
                     );
-                    println!("Deleted local branch `{}`.", branch_label);
+                    output::success(tr!("Deleted local branch `{}`.", branch_label));
                 }
                 Ok(LocalBranchStatus::Deleted)
             }
@@ -309,10 +531,10 @@ impl RemoveCommand {
                                 format!("{}", text.magenta())
                             })
                     );
-                    println!(
+                    output::info(tr!(
                         "Local branch `{}` not found; skipping removal.",
                         branch_label
-                    );
+                    ));
                 }
                 Ok(LocalBranchStatus::NotFound)
             }
@@ -323,6 +545,105 @@ impl RemoveCommand {
         }
     }
 
+    /// Refuses to continue unless the branch, as last seen on `remote`, is
+    /// contained in the base branch it was created from.
+    fn ensure_merged(&self, repo: &git2::Repository, remote: &str) -> color_eyre::Result<()> {
+        let tip = repo
+            .refname_to_id(&format!("refs/remotes/{remote}/{}", self.name))
+            .or_else(|_| repo.refname_to_id(&format!("refs/heads/{}", self.name)));
+        let Ok(tip) = tip else {
+            return Ok(());
+        };
+
+        let base = repo
+            .config()
+            .and_then(|config| config.get_string(&base_config_key(&self.name)))
+            .ok()
+            .or_else(|| remote_default_branch(repo, remote));
+        let target = base.as_deref().and_then(|base| {
+            repo.refname_to_id(&format!("refs/remotes/{remote}/{base}"))
+                .or_else(|_| repo.refname_to_id(&format!("refs/heads/{base}")))
+                .ok()
+                .map(|id| (base, id))
+        });
+        let Some((base, target)) = target else {
+            return Err(eyre::eyre!(
+                "cannot tell whether `{}` is merged because its base branch is unknown; pass `--force` to delete the remote branch anyway",
+                self.name
+            ));
+        };
+
+        let merged = tip == target
+            || repo
+                .graph_descendant_of(target, tip)
+                .wrap_err_with(|| eyre::eyre!("failed to compare `{}` with `{base}`", self.name))?;
+        if !merged {
+            return Err(eyre::eyre!(
+                "branch `{}` is not merged into `{base}`; pass `--force` to delete the remote branch anyway",
+                self.name
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn delete_remote_branch(
+        &self,
+        repo: &Repo,
+        remote: &str,
+    ) -> color_eyre::Result<RemoteBranchStatus> {
+        let run = || {
+            Command::new("git")
+                .args(["push", remote, "--delete", &self.name])
+                .current_dir(repo.root())
+                .output()
+        };
+        let output = if self.quiet {
+            run()
+        } else {
+            output::step(
+                tr!("Deleting remote branch `{}` on `{}`...", self.name, remote),
+                run,
+            )
+        }
+        .wrap_err("failed to delete remote branch with `git push`")?;
+
+        let branch_label = format!(
+            "{}",
+            self.name
+                .as_str()
+                .if_supports_color(Stream::Stdout, |text| format!("{}", text.magenta().bold()))
+        );
+        if output.status.success() {
+            if !self.quiet {
+                output::success(tr!(
+                    "Deleted remote branch `{}` on `{}`.",
+                    branch_label,
+                    remote
+                ));
+            }
+            return Ok(RemoteBranchStatus::Deleted);
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.to_lowercase().contains("remote ref does not exist") {
+            if !self.quiet {
+                output::info(tr!(
+                    "Remote branch `{}` not found on `{}`; skipping removal.",
+                    branch_label,
+                    remote
+                ));
+            }
+            return Ok(RemoteBranchStatus::NotFound);
+        }
+
+        Err(eyre::eyre!(
+            "failed to delete remote branch `{}` on `{remote}`: {}",
+            self.name,
+            stderr.trim()
+        ))
+    }
+
     fn force_delete_reference(repo: &git2::Repository, name: &str) -> color_eyre::Result<()> {
         let full_ref = format!("refs/heads/{name}");
         match repo.find_reference(&full_ref) {
@@ -334,15 +655,7 @@ impl RemoveCommand {
         }
     }
 
-    fn close_tmux_session(&self, repo: &Repo) {
-        let project_name = repo
-            .root()
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown");
-
-        let session_name = format!("{}/{}", project_name, self.name);
-
+    fn close_tmux_session(&self, session_name: &str) {
         // Check if session exists
         let list_output = Command::new("tmux")
             .args(["list-sessions", "-F", "#{session_name}"])
@@ -359,19 +672,16 @@ impl RemoveCommand {
         if session_exists {
             // Kill the session
             let _ = Command::new("tmux")
-                .args(["kill-session", "-t", &session_name])
+                .args(["kill-session", "-t", session_name])
                 .status();
 
             if !self.quiet {
                 let session_label = format!(
                     "{}",
                     session_name
-                        .as_str()
-                        .if_supports_color(Stream::Stdout, |text| {
-                            format!("{}", text.cyan())
-                        })
+                        .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
                 );
-                println!("Closed tmux session `{}`.", session_label);
+                output::success(tr!("Closed tmux session `{}`.", session_label));
             }
         }
     }
@@ -385,7 +695,25 @@ pub(crate) fn branch_has_upstream(repo: &git2::Repository, name: &str) -> bool {
     }
 }
 
-fn find_worktree_name(
+/// The remote `branch` tracks, falling back to `origin`.
+pub(crate) fn upstream_remote(repo: &git2::Repository, branch: &str) -> String {
+    repo.branch_upstream_remote(&format!("refs/heads/{branch}"))
+        .ok()
+        .and_then(|remote| remote.as_str().map(str::to_owned))
+        .unwrap_or_else(|| "origin".to_owned())
+}
+
+/// The branch `refs/remotes/<remote>/HEAD` points to, e.g. `main`.
+pub(crate) fn remote_default_branch(repo: &git2::Repository, remote: &str) -> Option<String> {
+    let head = repo
+        .find_reference(&format!("refs/remotes/{remote}/HEAD"))
+        .ok()?;
+    head.symbolic_target()?
+        .strip_prefix(&format!("refs/remotes/{remote}/"))
+        .map(str::to_owned)
+}
+
+pub(crate) fn find_worktree_name(
     repo: &git2::Repository,
     worktree_path: &Path,
 ) -> color_eyre::Result<Option<String>> {
@@ -580,6 +908,66 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn refuses_to_delete_protected_branches() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        CreateCommand::new("hotfix/1".into(), None).create_without_enter(&repo, true)?;
+        fs::write(
+            repo.rsworktree_dir().join("config.toml"),
+            "protected_branches = [\"hotfix/*\"]\n",
+        )?;
+
+        let error = RemoveCommand::new("hotfix/1".into(), true)
+            .with_quiet(true)
+            .with_remove_local_branch(true)
+            .execute(&repo)
+            .expect_err("the branch is protected");
+        assert!(error.to_string().contains("protected branch"));
+        assert!(repo.worktrees_dir().join("hotfix/1").exists());
+
+        let outcome = RemoveCommand::new("hotfix/1".into(), false)
+            .with_quiet(true)
+            .execute(&repo)?;
+        assert_eq!(outcome.local_branch, None);
+        assert!(!repo.worktrees_dir().join("hotfix/1").exists());
+        assert!(
+            repo.git()
+                .find_branch("hotfix/1", BranchType::Local)
+                .is_ok()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn removes_unmanaged_worktree_at_its_path() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let outside = TempDir::new()?;
+        let worktree_path = outside.path().join("hotfix-checkout");
+        let status = Command::new("git")
+            .current_dir(dir.path())
+            .args(["worktree", "add", "--quiet", "-b", "hotfix"])
+            .arg(&worktree_path)
+            .status()?;
+        assert!(status.success());
+
+        let resolved = crate::commands::open::resolve_by_name("hotfix-checkout", &repo)?;
+        let command = RemoveCommand::for_resolved(&repo, resolved, false)
+            .with_quiet(true)
+            .with_remove_local_branch(true);
+        let outcome = command.execute(&repo)?;
+
+        assert_eq!(outcome.local_branch, Some(LocalBranchStatus::Deleted));
+        assert!(!worktree_path.exists());
+        assert!(repo.git().find_branch("hotfix", BranchType::Local).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn deletes_unmerged_local_branch_when_requested() -> color_eyre::Result<()> {
         let dir = TempDir::new()?;
@@ -622,6 +1010,113 @@ mod tests {
         Ok(())
     }
 
+    fn init_remote(dir: &TempDir) -> color_eyre::Result<TempDir> {
+        let remote = TempDir::new()?;
+        run(&remote, ["git", "init", "--bare"])?;
+        let url = remote.path().to_string_lossy().into_owned();
+        let status = Command::new("git")
+            .current_dir(dir.path())
+            .args(["remote", "add", "origin", &url])
+            .status()?;
+        assert!(status.success(), "failed to add remote");
+        Ok(remote)
+    }
+
+    fn commit_in(path: &Path, file: &str) -> color_eyre::Result<()> {
+        fs::write(path.join(file), file)?;
+        let status = Command::new("git")
+            .current_dir(path)
+            .args(["add", file])
+            .status()?;
+        assert!(status.success());
+        run_in(
+            path,
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "Change",
+            ],
+        )
+    }
+
+    fn remote_has_branch(remote: &TempDir, branch: &str) -> color_eyre::Result<bool> {
+        let remote = git2::Repository::open_bare(remote.path())?;
+        Ok(remote.find_branch(branch, BranchType::Local).is_ok())
+    }
+
+    #[test]
+    fn deletes_merged_remote_branch_when_requested() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let remote = init_remote(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        let create = CreateCommand::new("feature/done".into(), None);
+        create.create_without_enter(&repo, true)?;
+        let worktree_path = repo.worktrees_dir().join("feature/done");
+        run_in(
+            &worktree_path,
+            ["git", "push", "--set-upstream", "origin", "feature/done"],
+        )?;
+        assert!(remote_has_branch(&remote, "feature/done")?);
+
+        let command = RemoveCommand::new("feature/done".into(), false)
+            .with_quiet(true)
+            .with_remove_local_branch(true)
+            .with_remove_remote_branch(true);
+        let outcome = command.execute(&repo)?;
+
+        assert_eq!(outcome.local_branch, Some(LocalBranchStatus::Deleted));
+        assert_eq!(outcome.remote_branch, Some(RemoteBranchStatus::Deleted));
+        assert!(!remote_has_branch(&remote, "feature/done")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn refuses_to_delete_unmerged_remote_branch_without_force() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let remote = init_remote(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        let create = CreateCommand::new("feature/wip".into(), None);
+        create.create_without_enter(&repo, true)?;
+        let worktree_path = repo.worktrees_dir().join("feature/wip");
+        commit_in(&worktree_path, "wip.txt")?;
+        run_in(
+            &worktree_path,
+            ["git", "push", "--set-upstream", "origin", "feature/wip"],
+        )?;
+
+        let command = RemoveCommand::new("feature/wip".into(), false)
+            .with_quiet(true)
+            .with_remove_local_branch(true)
+            .with_remove_remote_branch(true);
+        let error = command
+            .execute(&repo)
+            .expect_err("unmerged branch should be refused");
+
+        assert!(error.to_string().contains("is not merged into"));
+        assert!(worktree_path.exists(), "nothing should be removed");
+        assert!(remote_has_branch(&remote, "feature/wip")?);
+
+        let forced = RemoveCommand::new("feature/wip".into(), true)
+            .with_quiet(true)
+            .with_remove_local_branch(true)
+            .with_remove_remote_branch(true);
+        let outcome = forced.execute(&repo)?;
+        assert_eq!(outcome.remote_branch, Some(RemoteBranchStatus::Deleted));
+        assert!(!remote_has_branch(&remote, "feature/wip")?);
+
+        Ok(())
+    }
+
     #[test]
     fn keeps_local_branch_when_not_requested() -> color_eyre::Result<()> {
         let dir = TempDir::new()?;
@@ -641,4 +1136,119 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn refuses_to_remove_worktree_with_uncommitted_changes() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        let create = CreateCommand::new("feature/dirty".into(), None);
+        create.create_without_enter(&repo, true)?;
+        let worktree_path = repo.worktrees_dir().join("feature/dirty");
+        fs::write(worktree_path.join("notes.txt"), "work in progress")?;
+
+        let error = RemoveCommand::new("feature/dirty".into(), false)
+            .with_quiet(true)
+            .execute(&repo)
+            .expect_err("dirty worktree should not be removed");
+        assert!(error.to_string().contains("uncommitted changes"));
+        assert!(worktree_path.join("notes.txt").exists());
+
+        RemoveCommand::new("feature/dirty".into(), true)
+            .with_quiet(true)
+            .execute(&repo)?;
+        assert!(!worktree_path.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn refuses_to_remove_worktree_it_cannot_inspect() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        let create = CreateCommand::new("feature/broken".into(), None);
+        create.create_without_enter(&repo, true)?;
+        let worktree_path = repo.worktrees_dir().join("feature/broken");
+        fs::write(worktree_path.join(".git"), "gitdir: /nonexistent\n")?;
+
+        let error = RemoveCommand::new("feature/broken".into(), false)
+            .with_quiet(true)
+            .execute(&repo)
+            .expect_err("a worktree that cannot be inspected should not be removed");
+        assert!(error.to_string().contains("pass `--force`"), "{error:?}");
+        assert!(worktree_path.exists());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_remove_hooks_and_lets_pre_remove_abort() -> color_eyre::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        let create = CreateCommand::new("feature/hooks".into(), None);
+        create.create_without_enter(&repo, true)?;
+        let worktrees_dir = repo.worktrees_dir();
+        let worktree_path = worktrees_dir.join("feature/hooks");
+
+        let hooks_dir = worktrees_dir.join("hooks");
+        fs::create_dir_all(&hooks_dir)?;
+        for (hook, script) in [
+            ("pre-remove", "#!/bin/sh\ntest -f keep && exit 1\nexit 0\n"),
+            (
+                "post-remove",
+                "#!/bin/sh\necho \"$RSWORKTREE_NAME\" > post-remove.log\n",
+            ),
+        ] {
+            let path = hooks_dir.join(hook);
+            fs::write(&path, script)?;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+        fs::write(
+            worktrees_dir.join(crate::editor::CONFIG_FILE_NAME),
+            r#"{"hooks": {"preRemoveAborts": true}}"#,
+        )?;
+        run_in(&worktree_path, ["touch", "keep"])?;
+        run_in(&worktree_path, ["git", "add", "keep"])?;
+        run_in(
+            &worktree_path,
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "Keep",
+            ],
+        )?;
+
+        let error = RemoveCommand::new("feature/hooks".into(), false)
+            .with_quiet(true)
+            .execute(&repo)
+            .expect_err("failing pre-remove hook should abort");
+        assert!(error.to_string().contains("pre-remove"));
+        assert!(worktree_path.exists());
+        assert!(!worktrees_dir.join("post-remove.log").exists());
+
+        fs::remove_file(worktrees_dir.join(crate::editor::CONFIG_FILE_NAME))?;
+        RemoveCommand::new("feature/hooks".into(), false)
+            .with_quiet(true)
+            .execute(&repo)?;
+        assert!(!worktree_path.exists());
+        assert_eq!(
+            fs::read_to_string(worktrees_dir.join("post-remove.log"))?.trim(),
+            "feature/hooks"
+        );
+
+        Ok(())
+    }
 }
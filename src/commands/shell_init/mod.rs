@@ -0,0 +1,141 @@
+//! Shell functions that let `rsworktree switch` change the calling shell's directory.
+//!
+//! A child process cannot change its parent's working directory, so `switch`
+//! only prints the worktree path and the generated `rsworktree` function `cd`s
+//! into it. Every other subcommand is passed through unchanged.
+
+use std::{fmt, str::FromStr};
+
+/// Set by the shell function so `switch` knows its output is being consumed.
+pub(crate) const INTEGRATION_ENV: &str = "RSWORKTREE_SHELL_INTEGRATION";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+impl Shell {
+    /// The shell function wrapping the `rsworktree` binary.
+    pub fn script(&self) -> &'static str {
+        match self {
+            Shell::Bash | Shell::Zsh => POSIX_SCRIPT,
+            Shell::Fish => FISH_SCRIPT,
+            Shell::Powershell => POWERSHELL_SCRIPT,
+        }
+    }
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Shell::Bash => write!(f, "bash"),
+            Shell::Zsh => write!(f, "zsh"),
+            Shell::Fish => write!(f, "fish"),
+            Shell::Powershell => write!(f, "powershell"),
+        }
+    }
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            "powershell" | "pwsh" => Ok(Shell::Powershell),
+            _ => Err(format!(
+                "unknown shell '{}', expected 'bash', 'zsh', 'fish' or 'powershell'",
+                s
+            )),
+        }
+    }
+}
+
+const POSIX_SCRIPT: &str = r#"rsworktree() {
+    if [ "$1" = "switch" ]; then
+        shift
+        local target
+        target="$(RSWORKTREE_SHELL_INTEGRATION=1 command rsworktree switch "$@")" || return
+        [ -n "$target" ] && cd -- "$target"
+    else
+        command rsworktree "$@"
+    fi
+}
+"#;
+
+const FISH_SCRIPT: &str = r#"function rsworktree --wraps rsworktree
+    if test (count $argv) -gt 0; and test "$argv[1]" = switch
+        set -l target (env RSWORKTREE_SHELL_INTEGRATION=1 rsworktree switch $argv[2..-1]); or return
+        test -n "$target"; and cd $target
+    else
+        command rsworktree $argv
+    end
+end
+"#;
+
+const POWERSHELL_SCRIPT: &str = r#"function rsworktree {
+    $binary = Get-Command rsworktree -CommandType Application | Select-Object -First 1
+    if ($args.Count -gt 0 -and $args[0] -eq 'switch') {
+        $env:RSWORKTREE_SHELL_INTEGRATION = '1'
+        try {
+            $target = & $binary @args
+        } finally {
+            Remove-Item Env:RSWORKTREE_SHELL_INTEGRATION
+        }
+        if ($LASTEXITCODE -eq 0 -and $target) {
+            Set-Location -LiteralPath $target
+        }
+    } else {
+        & $binary @args
+    }
+}
+"#;
+
+#[derive(Debug)]
+pub struct ShellInitCommand {
+    shell: Shell,
+}
+
+impl ShellInitCommand {
+    pub fn new(shell: Shell) -> Self {
+        Self { shell }
+    }
+
+    pub fn execute(&self) -> color_eyre::Result<()> {
+        print!("{}", self.shell.script());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_supported_shells() {
+        assert_eq!("bash".parse::<Shell>(), Ok(Shell::Bash));
+        assert_eq!("Zsh".parse::<Shell>(), Ok(Shell::Zsh));
+        assert_eq!("fish".parse::<Shell>(), Ok(Shell::Fish));
+        assert_eq!("pwsh".parse::<Shell>(), Ok(Shell::Powershell));
+        assert!("tcsh".parse::<Shell>().is_err());
+    }
+
+    #[test]
+    fn scripts_wrap_switch_and_set_integration_env() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = shell.script();
+            assert!(script.contains(INTEGRATION_ENV), "{shell}");
+            assert!(script.contains("rsworktree switch"), "{shell}");
+            assert!(script.contains("cd "), "{shell}");
+        }
+
+        let script = Shell::Powershell.script();
+        assert!(script.contains(INTEGRATION_ENV));
+        assert!(script.contains("Set-Location"));
+    }
+}
@@ -0,0 +1,274 @@
+//! `stash`: park the uncommitted changes of a worktree with `git stash`
+//! without entering it, and bring them back with `stash pop`.
+//!
+//! git keeps one stash list for all worktrees of a repository. The entries of
+//! a worktree are those made on its branch, which git names in every entry
+//! ("WIP on <branch>: ..." or "On <branch>: <message>").
+
+use std::{collections::HashMap, path::Path, process::Command};
+
+use color_eyre::eyre::{self, WrapErr};
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    Repo,
+    commands::open::{ResolvedWorktree, resolve_by_name},
+    output,
+    worktree::inspect_worktree,
+};
+
+/// What git calls the branch of a stash made on a detached HEAD.
+const DETACHED: &str = "(no branch)";
+
+#[derive(Debug)]
+pub struct StashCommand {
+    name: String,
+    message: Option<String>,
+    include_untracked: bool,
+}
+
+impl StashCommand {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            message: None,
+            include_untracked: false,
+        }
+    }
+
+    /// Describe the entry with `message` instead of the HEAD commit.
+    pub fn with_message(mut self, message: Option<String>) -> Self {
+        self.message = message.filter(|message| !message.trim().is_empty());
+        self
+    }
+
+    /// Stash untracked files too, as `git stash --include-untracked` does.
+    pub fn with_include_untracked(mut self, include_untracked: bool) -> Self {
+        self.include_untracked = include_untracked;
+        self
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let worktree = resolve_by_name(&self.name, repo)?;
+        let label = label(&worktree.name);
+        let changes = inspect_worktree(&worktree.path)?.changes;
+        let untracked_only = changes.staged + changes.modified + changes.conflicted == 0;
+        if changes.is_clean() || (untracked_only && !self.include_untracked) {
+            output::info(tr!("Worktree `{}` has no changes to stash.", label));
+            if changes.untracked > 0 {
+                output::hint(tr!(
+                    "pass `--include-untracked` to stash its {} untracked file(s)",
+                    changes.untracked
+                ));
+            }
+            return Ok(());
+        }
+
+        let mut args = vec!["stash", "push", "--quiet"];
+        if self.include_untracked {
+            args.push("--include-untracked");
+        }
+        if let Some(message) = &self.message {
+            args.extend(["--message", message]);
+        }
+        git(&worktree.path, &args)?;
+        output::success(tr!(
+            "Stashed the changes of `{}` ({}).",
+            label,
+            changes.describe()
+        ));
+        output::hint(tr!(
+            "Bring them back with `rsworktree stash pop {}`.",
+            worktree.name
+        ));
+        Ok(())
+    }
+}
+
+/// Applies and drops the newest stash entry made on a worktree's branch.
+#[derive(Debug)]
+pub struct StashPopCommand {
+    name: String,
+}
+
+impl StashPopCommand {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let worktree = resolve_by_name(&self.name, repo)?;
+        let branch = worktree_branch(&worktree)?;
+        let Some(index) = stash_entries(&worktree.path)?
+            .into_iter()
+            .find(|(_, entry_branch)| *entry_branch == branch)
+            .map(|(index, _)| index)
+        else {
+            return Err(eyre::eyre!(
+                "worktree `{}` has no stashed changes on `{branch}`; `git stash list` shows every stash of the repository",
+                worktree.name
+            ));
+        };
+
+        let entry = format!("stash@{{{index}}}");
+        git(&worktree.path, &["stash", "pop", "--quiet", &entry]).wrap_err_with(|| {
+            eyre::eyre!(
+                "`{entry}` was kept; resolve the conflicts in `{}`, then drop it with `git stash drop {entry}`",
+                worktree.path.display()
+            )
+        })?;
+        output::success(tr!(
+            "Restored the stashed changes of `{}`.",
+            label(&worktree.name)
+        ));
+        Ok(())
+    }
+}
+
+/// How many stash entries were made on each branch of the repository.
+pub(crate) fn stash_counts(repo: &Repo) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for (_, branch) in stash_entries(repo.root()).unwrap_or_default() {
+        *counts.entry(branch).or_default() += 1;
+    }
+    counts
+}
+
+/// The stash entries of the repository at `path`, newest first, with their
+/// `stash@{<index>}` index and the branch they were made on.
+fn stash_entries(path: &Path) -> color_eyre::Result<Vec<(usize, String)>> {
+    let mut git_repo = git2::Repository::open(path)
+        .wrap_err_with(|| eyre::eyre!("failed to open `{}`", path.display()))?;
+    let mut entries = Vec::new();
+    git_repo
+        .stash_foreach(|index, message, _| {
+            if let Some(branch) = stash_branch(message) {
+                entries.push((index, branch.to_owned()));
+            }
+            true
+        })
+        .wrap_err("failed to read the stash list")?;
+    Ok(entries)
+}
+
+/// The branch named in a stash message; branch names cannot contain `:`.
+fn stash_branch(message: &str) -> Option<&str> {
+    let rest = message
+        .strip_prefix("WIP on ")
+        .or_else(|| message.strip_prefix("On "))?;
+    rest.split_once(':').map(|(branch, _)| branch)
+}
+
+/// The branch of the worktree as git names it in stash messages.
+fn worktree_branch(worktree: &ResolvedWorktree) -> color_eyre::Result<String> {
+    let status = inspect_worktree(&worktree.path)?;
+    Ok(status.branch.unwrap_or_else(|| DETACHED.to_owned()))
+}
+
+fn git(worktree_path: &Path, args: &[&str]) -> color_eyre::Result<()> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(worktree_path)
+        .output()
+        .wrap_err_with(|| eyre::eyre!("failed to run `git {}`", args.join(" ")))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(eyre::eyre!(
+        "`git {}` failed in `{}`: {}",
+        args.join(" "),
+        worktree_path.display(),
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+fn label(name: &str) -> String {
+    format!(
+        "{}",
+        name.if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use crate::commands::create::CreateCommand;
+
+    fn run(dir: &Path, args: &[&str]) -> color_eyre::Result<String> {
+        let output = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()?;
+        assert!(output.status.success(), "git {args:?} failed");
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    #[test]
+    fn stash_branch_is_read_from_the_message() {
+        assert_eq!(
+            stash_branch("WIP on feature/a: 1234567 Add login"),
+            Some("feature/a")
+        );
+        assert_eq!(stash_branch("On main: before rebase"), Some("main"));
+        assert_eq!(
+            stash_branch("WIP on (no branch): 1234567 Detached"),
+            Some(DETACHED)
+        );
+        assert_eq!(stash_branch("autostash"), None);
+    }
+
+    #[test]
+    fn stashes_and_pops_the_changes_of_a_worktree() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        run(dir.path(), &["init", "--quiet"])?;
+        fs::write(dir.path().join("README.md"), "test\n")?;
+        run(dir.path(), &["add", "README.md"])?;
+        run(dir.path(), &["commit", "--quiet", "-m", "Initial"])?;
+        run(dir.path(), &["config", "user.name", "Test"])?;
+        run(dir.path(), &["config", "user.email", "test@example.com"])?;
+        let repo = Repo::discover_from(dir.path())?;
+        for name in ["feature/a", "feature/b"] {
+            CreateCommand::new(name.into(), None).create_without_enter(&repo, true)?;
+        }
+        let worktree_a = repo.worktrees_dir().join("feature/a");
+        let worktree_b = repo.worktrees_dir().join("feature/b");
+        fs::write(worktree_a.join("README.md"), "a\n")?;
+        fs::write(worktree_b.join("README.md"), "b\n")?;
+
+        StashCommand::new("feature/a".into()).execute(&repo)?;
+        StashCommand::new("feature/b".into())
+            .with_message(Some("parked".into()))
+            .execute(&repo)?;
+        assert_eq!(fs::read_to_string(worktree_a.join("README.md"))?, "test\n");
+        let counts = stash_counts(&repo);
+        assert_eq!(counts.get("feature/a"), Some(&1));
+        assert_eq!(counts.get("feature/b"), Some(&1));
+
+        // The newest entry is feature/b's; feature/a gets its own back.
+        StashPopCommand::new("feature/a".into()).execute(&repo)?;
+        assert_eq!(fs::read_to_string(worktree_a.join("README.md"))?, "a\n");
+        assert_eq!(fs::read_to_string(worktree_b.join("README.md"))?, "test\n");
+        assert_eq!(stash_counts(&repo).get("feature/a"), None);
+
+        let error = StashPopCommand::new("feature/a".into())
+            .execute(&repo)
+            .expect_err("nothing is stashed for feature/a anymore");
+        assert!(error.to_string().contains("no stashed changes"));
+
+        fs::write(worktree_a.join("new.txt"), "untracked\n")?;
+        run(&worktree_a, &["checkout", "--quiet", "README.md"])?;
+        StashCommand::new("feature/a".into()).execute(&repo)?;
+        assert!(worktree_a.join("new.txt").exists());
+        StashCommand::new("feature/a".into())
+            .with_include_untracked(true)
+            .execute(&repo)?;
+        assert!(!worktree_a.join("new.txt").exists());
+        Ok(())
+    }
+}
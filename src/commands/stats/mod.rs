@@ -0,0 +1,581 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    io,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre::WrapErr;
+use crossterm::{
+    event::{Event as TerminalEvent, KeyCode, KeyEventKind},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{
+    Frame, Terminal,
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{BarChart, Block, Borders, Paragraph, Row, Table},
+};
+
+use crate::{
+    Repo,
+    commands::interactive::{CrosstermEvents, EventSource},
+    output,
+    telemetry::{EditorLaunchStatus, Event, EventRecord, EventStore},
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// Days shown in the hook failure trend, today included.
+const TREND_DAYS: u64 = 7;
+const TOP_ENTRIES: usize = 5;
+
+/// Summarizes the local event store, as text or as a terminal dashboard.
+#[derive(Debug)]
+pub struct StatsCommand {
+    ui: bool,
+}
+
+impl StatsCommand {
+    pub fn new(ui: bool) -> Self {
+        Self { ui }
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let store = EventStore::new(&repo.rsworktree_dir());
+        let events = store.load()?;
+        if events.is_empty() {
+            output::info(tr!(
+                "No events recorded yet; they are collected in `{}` as you use rsworktree.",
+                store.path().display()
+            ));
+            return Ok(());
+        }
+
+        let stats = Stats::from_events(&events, now());
+        if self.ui {
+            return show_dashboard(&stats);
+        }
+        print_stats(&stats);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EditorStats {
+    editor: String,
+    launches: usize,
+    successes: usize,
+}
+
+impl EditorStats {
+    fn success_rate(&self) -> usize {
+        self.successes * 100 / self.launches.max(1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HookDay {
+    days_ago: u64,
+    runs: usize,
+    failures: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StepStats {
+    step: String,
+    runs: usize,
+    average_ms: u64,
+    max_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stats {
+    editors: Vec<EditorStats>,
+    /// One entry per day, oldest first.
+    hook_days: Vec<HookDay>,
+    slowest_steps: Vec<StepStats>,
+    worktrees: Vec<(String, usize)>,
+}
+
+impl Stats {
+    fn from_events(events: &[EventRecord], now: u64) -> Self {
+        let mut editors = BTreeMap::<&str, EditorStats>::new();
+        let mut hook_days = (0..TREND_DAYS)
+            .rev()
+            .map(|days_ago| HookDay {
+                days_ago,
+                runs: 0,
+                failures: 0,
+            })
+            .collect::<Vec<_>>();
+        let mut steps = BTreeMap::<&str, Vec<u64>>::new();
+        let mut worktrees = BTreeMap::<&str, usize>::new();
+
+        for record in events {
+            match &record.event {
+                Event::EditorLaunch {
+                    worktree,
+                    editor,
+                    status,
+                    ..
+                } => {
+                    let entry = editors.entry(editor).or_insert_with(|| EditorStats {
+                        editor: editor.clone(),
+                        launches: 0,
+                        successes: 0,
+                    });
+                    entry.launches += 1;
+                    if *status == EditorLaunchStatus::Success {
+                        entry.successes += 1;
+                    }
+                    *worktrees.entry(worktree).or_default() += 1;
+                }
+                Event::HookRun { success, .. } => {
+                    let days_ago = now.saturating_sub(record.at) / SECONDS_PER_DAY;
+                    if let Some(day) = hook_days.iter_mut().find(|day| day.days_ago == days_ago) {
+                        day.runs += 1;
+                        if !success {
+                            day.failures += 1;
+                        }
+                    }
+                }
+                Event::SetupStep {
+                    step, duration_ms, ..
+                } => steps.entry(step).or_default().push(*duration_ms),
+                Event::WorktreeUsed { worktree, .. } => {
+                    *worktrees.entry(worktree).or_default() += 1;
+                }
+                Event::WorktreeCreated { .. } | Event::ProviderCall { .. } => {}
+            }
+        }
+
+        let mut editors = editors.into_values().collect::<Vec<_>>();
+        editors.sort_by_key(|editor| Reverse(editor.launches));
+
+        let mut slowest_steps = steps
+            .into_iter()
+            .map(|(step, durations)| StepStats {
+                step: step.to_owned(),
+                runs: durations.len(),
+                average_ms: durations.iter().sum::<u64>() / durations.len() as u64,
+                max_ms: durations.iter().copied().max().unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+        slowest_steps.sort_by_key(|step| Reverse(step.average_ms));
+        slowest_steps.truncate(TOP_ENTRIES);
+
+        let mut worktrees = worktrees
+            .into_iter()
+            .map(|(name, uses)| (name.to_owned(), uses))
+            .collect::<Vec<_>>();
+        worktrees.sort_by_key(|(_, uses)| Reverse(*uses));
+        worktrees.truncate(TOP_ENTRIES);
+
+        Self {
+            editors,
+            hook_days,
+            slowest_steps,
+            worktrees,
+        }
+    }
+}
+
+fn print_stats(stats: &Stats) {
+    output::heading(tr!("Editor launches"));
+    if stats.editors.is_empty() {
+        output::field("", tr!("none recorded"));
+    }
+    for editor in &stats.editors {
+        output::field(
+            &editor.editor,
+            tr!(
+                "{}/{} succeeded ({}%)",
+                editor.successes,
+                editor.launches,
+                editor.success_rate()
+            ),
+        );
+    }
+
+    output::heading(tr!("Hook failures (last {} days)", TREND_DAYS));
+    for day in &stats.hook_days {
+        output::field(
+            &day_label(day.days_ago),
+            tr!("{} of {} failed", day.failures, day.runs),
+        );
+    }
+
+    output::heading(tr!("Slowest setup steps"));
+    if stats.slowest_steps.is_empty() {
+        output::field("", tr!("none recorded"));
+    }
+    for step in &stats.slowest_steps {
+        output::field(
+            &step.step,
+            tr!(
+                "avg {}, max {} over {} run(s)",
+                format_duration(step.average_ms),
+                format_duration(step.max_ms),
+                step.runs
+            ),
+        );
+    }
+
+    output::heading(tr!("Most used worktrees"));
+    if stats.worktrees.is_empty() {
+        output::field("", tr!("none recorded"));
+    }
+    for (name, uses) in &stats.worktrees {
+        output::field(name, tr!("{} use(s)", uses));
+    }
+}
+
+fn show_dashboard(stats: &Stats) -> color_eyre::Result<()> {
+    enable_raw_mode().wrap_err("failed to enable raw mode")?;
+    execute!(io::stdout(), EnterAlternateScreen).wrap_err("failed to enter alternate screen")?;
+
+    let result = Terminal::new(CrosstermBackend::new(io::stdout()))
+        .wrap_err("failed to initialize terminal")
+        .and_then(|mut terminal| run_dashboard(&mut terminal, CrosstermEvents, stats));
+
+    disable_raw_mode().wrap_err("failed to disable raw mode")?;
+    execute!(io::stdout(), LeaveAlternateScreen).wrap_err("failed to leave alternate screen")?;
+    result
+}
+
+/// Draws the dashboard until `q`, `Esc` or `Enter` is pressed.
+fn run_dashboard<B: Backend, E: EventSource>(
+    terminal: &mut Terminal<B>,
+    mut events: E,
+    stats: &Stats,
+) -> color_eyre::Result<()> {
+    loop {
+        terminal
+            .draw(|frame| render(frame, stats))
+            .wrap_err("failed to draw dashboard")?;
+        if let TerminalEvent::Key(key) = events.next()?
+            && key.kind == KeyEventKind::Press
+            && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter)
+        {
+            return Ok(());
+        }
+    }
+}
+
+fn render(frame: &mut Frame<'_>, stats: &Stats) {
+    let [body, footer] = split(
+        Direction::Vertical,
+        frame.size(),
+        [Constraint::Min(0), Constraint::Length(1)],
+    );
+    let [top, bottom] = split(
+        Direction::Vertical,
+        body,
+        [Constraint::Percentage(50), Constraint::Percentage(50)],
+    );
+    let [editors, hooks] = split(
+        Direction::Horizontal,
+        top,
+        [Constraint::Percentage(50), Constraint::Percentage(50)],
+    );
+    let [steps, worktrees] = split(
+        Direction::Horizontal,
+        bottom,
+        [Constraint::Percentage(50), Constraint::Percentage(50)],
+    );
+
+    let header = Style::default().add_modifier(Modifier::BOLD);
+
+    let rows = stats.editors.iter().map(|editor| {
+        Row::new(vec![
+            editor.editor.clone(),
+            editor.launches.to_string(),
+            format!("{}%", editor.success_rate()),
+        ])
+        .style(Style::default().fg(rate_color(editor.success_rate())))
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(10),
+            Constraint::Length(8),
+            Constraint::Length(8),
+        ],
+    )
+    .header(Row::new(vec![tr!("Editor"), tr!("Launches"), tr!("Success")]).style(header))
+    .block(panel(tr!("Editor launches")));
+    frame.render_widget(table, editors);
+
+    let labels = stats
+        .hook_days
+        .iter()
+        .map(|day| short_day_label(day.days_ago))
+        .collect::<Vec<_>>();
+    let data = stats
+        .hook_days
+        .iter()
+        .zip(&labels)
+        .map(|(day, label)| (label.as_str(), day.failures as u64))
+        .collect::<Vec<_>>();
+    let chart = BarChart::default()
+        .block(panel(tr!("Hook failures (last {} days)", TREND_DAYS)))
+        .bar_width(5)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::Red))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Red))
+        .data(&data);
+    frame.render_widget(chart, hooks);
+
+    let rows = stats.slowest_steps.iter().map(|step| {
+        Row::new(vec![
+            step.step.clone(),
+            format_duration(step.average_ms),
+            format_duration(step.max_ms),
+            step.runs.to_string(),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(16),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(5),
+        ],
+    )
+    .header(Row::new(vec![tr!("Step"), tr!("Avg"), tr!("Max"), tr!("Runs")]).style(header))
+    .block(panel(tr!("Slowest setup steps")));
+    frame.render_widget(table, steps);
+
+    let rows = stats
+        .worktrees
+        .iter()
+        .map(|(name, uses)| Row::new(vec![name.clone(), uses.to_string()]));
+    let table = Table::new(rows, [Constraint::Min(16), Constraint::Length(6)])
+        .header(Row::new(vec![tr!("Worktree"), tr!("Uses")]).style(header))
+        .block(panel(tr!("Most used worktrees")));
+    frame.render_widget(table, worktrees);
+
+    let help = Paragraph::new(Line::from(tr!("Press q or Esc to close")))
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(help, footer);
+}
+
+fn split<const N: usize>(
+    direction: Direction,
+    area: Rect,
+    constraints: [Constraint; N],
+) -> [Rect; N] {
+    let chunks = Layout::default()
+        .direction(direction)
+        .constraints(constraints)
+        .split(area);
+    std::array::from_fn(|index| chunks[index])
+}
+
+fn panel(title: String) -> Block<'static> {
+    Block::default().borders(Borders::ALL).title(title)
+}
+
+fn rate_color(rate: usize) -> Color {
+    match rate {
+        90.. => Color::Green,
+        60.. => Color::Yellow,
+        _ => Color::Red,
+    }
+}
+
+fn day_label(days_ago: u64) -> String {
+    match days_ago {
+        0 => tr!("today"),
+        1 => tr!("yesterday"),
+        days => tr!("{} days ago", days),
+    }
+}
+
+fn short_day_label(days_ago: u64) -> String {
+    match days_ago {
+        0 => tr!("today"),
+        days => format!("-{days}d"),
+    }
+}
+
+fn format_duration(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{ms} ms")
+    } else {
+        format!("{:.1} s", ms as f64 / 1000.0)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crossterm::event::{KeyEvent, KeyModifiers};
+    use ratatui::backend::TestBackend;
+
+    const NOW: u64 = 100 * SECONDS_PER_DAY;
+
+    fn record(seconds_ago: u64, event: Event) -> EventRecord {
+        EventRecord {
+            at: NOW - seconds_ago,
+            event,
+        }
+    }
+
+    fn launch(editor: &str, worktree: &str, status: EditorLaunchStatus) -> Event {
+        Event::EditorLaunch {
+            worktree: worktree.into(),
+            editor: editor.into(),
+            status,
+            path: None,
+            message: String::new(),
+            program: None,
+            version: None,
+            duration_ms: None,
+            method: None,
+        }
+    }
+
+    fn hook(success: bool) -> Event {
+        Event::HookRun {
+            worktree: "feature".into(),
+            hook: "post-create".into(),
+            success,
+            duration_ms: 10,
+            timed_out: false,
+        }
+    }
+
+    fn step(name: &str, duration_ms: u64) -> Event {
+        Event::SetupStep {
+            worktree: "feature".into(),
+            step: name.into(),
+            duration_ms,
+        }
+    }
+
+    fn sample_events() -> Vec<EventRecord> {
+        vec![
+            record(60, launch("code", "feature", EditorLaunchStatus::Success)),
+            record(
+                60,
+                launch("code", "feature", EditorLaunchStatus::SpawnError),
+            ),
+            record(60, launch("zed", "bugfix", EditorLaunchStatus::Success)),
+            record(
+                60,
+                Event::WorktreeUsed {
+                    worktree: "feature".into(),
+                    command: "cd".into(),
+                },
+            ),
+            record(60, hook(false)),
+            record(SECONDS_PER_DAY + 60, hook(true)),
+            record(SECONDS_PER_DAY + 60, hook(false)),
+            record(30 * SECONDS_PER_DAY, hook(false)),
+            record(60, step("worktree-add", 100)),
+            record(60, step("worktree-add", 300)),
+            record(60, step("post-create-hook", 4000)),
+        ]
+    }
+
+    struct StubEvents(Vec<TerminalEvent>);
+
+    impl EventSource for StubEvents {
+        fn next(&mut self) -> color_eyre::Result<TerminalEvent> {
+            Ok(self.0.remove(0))
+        }
+    }
+
+    #[test]
+    fn from_events_aggregates_each_panel() {
+        let stats = Stats::from_events(&sample_events(), NOW);
+
+        assert_eq!(
+            stats.editors,
+            vec![
+                EditorStats {
+                    editor: "code".into(),
+                    launches: 2,
+                    successes: 1,
+                },
+                EditorStats {
+                    editor: "zed".into(),
+                    launches: 1,
+                    successes: 1,
+                },
+            ]
+        );
+        assert_eq!(stats.editors[0].success_rate(), 50);
+
+        assert_eq!(stats.hook_days.len(), TREND_DAYS as usize);
+        let today = stats.hook_days.last().unwrap();
+        assert_eq!((today.days_ago, today.runs, today.failures), (0, 1, 1));
+        let yesterday = &stats.hook_days[stats.hook_days.len() - 2];
+        assert_eq!((yesterday.runs, yesterday.failures), (2, 1));
+
+        assert_eq!(stats.slowest_steps[0].step, "post-create-hook");
+        assert_eq!(
+            stats.slowest_steps[1],
+            StepStats {
+                step: "worktree-add".into(),
+                runs: 2,
+                average_ms: 200,
+                max_ms: 300,
+            }
+        );
+
+        assert_eq!(
+            stats.worktrees,
+            vec![("feature".into(), 3), ("bugfix".into(), 1)]
+        );
+    }
+
+    #[test]
+    fn dashboard_renders_panels_and_closes_on_q() -> color_eyre::Result<()> {
+        let stats = Stats::from_events(&sample_events(), NOW);
+        let mut terminal = Terminal::new(TestBackend::new(100, 30))?;
+        let events = StubEvents(vec![TerminalEvent::Key(KeyEvent::new(
+            KeyCode::Char('q'),
+            KeyModifiers::NONE,
+        ))]);
+
+        run_dashboard(&mut terminal, events, &stats)?;
+
+        let buffer = terminal.backend().buffer();
+        let screen = buffer
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        for title in [
+            "Editor launches",
+            "Hook failures",
+            "Slowest setup steps",
+            "Most used worktrees",
+            "post-create-hook",
+        ] {
+            assert!(screen.contains(title), "missing `{title}` in dashboard");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn format_duration_switches_to_seconds() {
+        assert_eq!(format_duration(850), "850 ms");
+        assert_eq!(format_duration(2400), "2.4 s");
+    }
+}
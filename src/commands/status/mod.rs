@@ -0,0 +1,528 @@
+use std::{path::Path, thread};
+
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    GitProvider, Repo,
+    annotations::Annotations,
+    commands::{
+        info::{PullRequestCache, PullRequestLookup, lookup_pull_requests},
+        list::{find_worktrees, format_worktree, is_managed},
+    },
+    output,
+    process::{CommandRunner, SystemCommandRunner},
+    provider::{ProviderBackend, ProviderClient},
+    worktree::{WorktreeStatus, inspect_worktree},
+};
+
+/// Upper bound for concurrent worktree inspections.
+const MAX_WORKERS: usize = 8;
+
+#[derive(Debug)]
+pub struct StatusCommand<R = SystemCommandRunner> {
+    provider: GitProvider,
+    client: ProviderClient,
+    runner: R,
+    refresh: bool,
+    porcelain: bool,
+}
+
+impl StatusCommand {
+    /// Runs the provider operations through the backend configured for `repo`.
+    pub fn new(provider: GitProvider, repo: &Repo) -> Self {
+        Self::with_runner(provider, SystemCommandRunner)
+            .with_backend(ProviderBackend::resolve(repo))
+    }
+}
+
+impl<R> StatusCommand<R>
+where
+    R: CommandRunner + Clone + Send,
+{
+    pub fn with_runner(provider: GitProvider, runner: R) -> Self {
+        Self {
+            provider,
+            client: provider.client(ProviderBackend::Cli),
+            runner,
+            refresh: false,
+            porcelain: false,
+        }
+    }
+
+    /// Runs the provider operations through `backend` rather than the CLI.
+    pub fn with_backend(mut self, backend: ProviderBackend) -> Self {
+        self.client = self.provider.client(backend);
+        self
+    }
+
+    /// Query the provider even for pull/merge request states cached recently.
+    pub fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Print one tab-separated `status` record per worktree, see [`output::porcelain`].
+    pub fn with_porcelain(mut self, porcelain: bool) -> Self {
+        self.porcelain = porcelain;
+        self
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let rows = self.collect(repo)?;
+        if self.porcelain {
+            output::porcelain::print(rows.iter().map(StatusRow::record));
+            return Ok(());
+        }
+
+        let header_path = format!("{}", repo.worktrees_dir().display());
+        let header_path = format!(
+            "{}",
+            header_path
+                .as_str()
+                .if_supports_color(Stream::Stdout, |text| format!("{}", text.blue().bold()))
+        );
+        output::heading(tr!("Worktree status under `{}`:", header_path));
+
+        if rows.is_empty() {
+            output::info(tr!("(none)"));
+            return Ok(());
+        }
+
+        let cells = rows
+            .iter()
+            .map(|row| row.cells(self.provider))
+            .collect::<Vec<_>>();
+        let widths: [usize; 5] = std::array::from_fn(|column| {
+            cells
+                .iter()
+                .map(|cells| cells[column].chars().count())
+                .max()
+                .unwrap_or_default()
+        });
+
+        // Pad before painting so escape codes do not skew the columns.
+        for (row, [name, branch, sync, changes, pull_request]) in rows.iter().zip(&cells) {
+            let clean = row
+                .status
+                .as_ref()
+                .is_some_and(|status| status.changes.is_clean());
+            let changes = pad(changes, widths[3]);
+            let changes = if clean {
+                paint(&changes, |text| format!("{}", text.green()))
+            } else {
+                paint(&changes, |text| format!("{}", text.yellow()))
+            };
+            let name = paint(&pad(name, widths[0]), |text| {
+                format!("{}", text.cyan().bold())
+            });
+            let branch = paint(&pad(branch, widths[1]), |text| {
+                format!("{}", text.magenta())
+            });
+            // The note's first line trails the row, after the padded PR column.
+            let line = match row.note.as_deref().and_then(|note| note.lines().next()) {
+                Some(note) => format!(
+                    "{}  {}",
+                    pad(pull_request, widths[4]),
+                    paint(note, |text| format!("{}", text.dimmed()))
+                ),
+                None => pull_request.clone(),
+            };
+            output::item(format!(
+                "{name}  {branch}  {}  {changes}  {line}",
+                pad(sync, widths[2])
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Inspects every worktree, spreading the git calls over a few threads,
+    /// then looks up the pull/merge requests of their branches.
+    fn collect(&self, repo: &Repo) -> color_eyre::Result<Vec<StatusRow>> {
+        let worktrees_dir = repo.ensure_worktrees_dir()?;
+        let worktrees = find_worktrees(repo)?
+            .into_iter()
+            .map(|relative| (format_worktree(&relative), worktrees_dir.join(relative)))
+            .collect::<Vec<_>>();
+        if worktrees.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let workers = thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1)
+            .clamp(1, MAX_WORKERS);
+        let chunk_size = worktrees.len().div_ceil(workers);
+
+        let mut rows = thread::scope(|scope| {
+            let handles = worktrees
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|(name, path)| inspect(name, path))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("status worker panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        let branches = worktrees
+            .iter()
+            .zip(&rows)
+            .filter_map(|((_, path), row)| {
+                let branch = row.status.as_ref()?.branch.clone()?;
+                Some((path.clone(), branch))
+            })
+            .collect::<Vec<_>>();
+        let mut cache = PullRequestCache::load(&repo.rsworktree_dir()).with_refresh(self.refresh);
+        let mut pull_requests =
+            lookup_pull_requests(&self.runner, &self.client, &mut cache, &branches).into_iter();
+        for row in &mut rows {
+            if row
+                .status
+                .as_ref()
+                .is_some_and(|status| status.branch.is_some())
+            {
+                row.pull_request = pull_requests.next().unwrap_or(PullRequestLookup::None);
+            }
+        }
+        for ((name, path), row) in worktrees.iter().zip(&mut rows) {
+            row.note = row
+                .status
+                .as_ref()
+                .and_then(|status| status.branch.as_deref())
+                .filter(|_| is_managed(&worktrees_dir, path))
+                .and_then(|branch| Annotations::load(repo, name, branch).ok())
+                .and_then(|annotations| annotations.note);
+        }
+
+        Ok(rows)
+    }
+}
+
+fn inspect(name: &str, path: &Path) -> StatusRow {
+    StatusRow {
+        name: name.to_owned(),
+        status: inspect_worktree(path).ok(),
+        pull_request: PullRequestLookup::None,
+        note: None,
+    }
+}
+
+#[derive(Debug)]
+struct StatusRow {
+    name: String,
+    /// `None` when the worktree could not be opened.
+    status: Option<WorktreeStatus>,
+    pull_request: PullRequestLookup,
+    /// The note from `notes set` or `notes edit`.
+    note: Option<String>,
+}
+
+impl StatusRow {
+    /// Plain text for the name, branch, upstream, changes and PR columns.
+    fn cells(&self, provider: GitProvider) -> [String; 5] {
+        let Some(status) = &self.status else {
+            return [
+                self.name.clone(),
+                String::new(),
+                String::new(),
+                tr!("unreadable"),
+                String::new(),
+            ];
+        };
+
+        let branch = match (&status.branch, &status.head) {
+            (Some(branch), _) => branch.clone(),
+            (None, Some(head)) => tr!("(detached at {})", head),
+            (None, None) => tr!("(unborn)"),
+        };
+        let sync = match &status.upstream {
+            Some(upstream) => tr!("{} ahead, {} behind", upstream.ahead, upstream.behind),
+            None => tr!("no upstream"),
+        };
+        let changes = if status.changes.is_clean() {
+            tr!("clean")
+        } else {
+            tr!("dirty")
+        };
+        let pull_request = match &self.pull_request {
+            PullRequestLookup::Found(found) => format!(
+                "{} {}{} ({})",
+                provider.merge_request_short(),
+                provider.request_prefix(),
+                found.number,
+                found.state
+            ),
+            PullRequestLookup::None => tr!("no {}", provider.merge_request_short()),
+            PullRequestLookup::Unavailable(_) => {
+                tr!("{} unavailable", provider.merge_request_short())
+            }
+        };
+
+        [self.name.clone(), branch, sync, changes, pull_request]
+    }
+
+    /// `status <name> <branch> <clean|dirty|unreadable> <ahead> <behind>
+    /// <pr number> <pr state>`, with empty fields for what is unknown and the
+    /// PR state `unavailable` when the provider could not be asked.
+    fn record(&self) -> String {
+        let (branch, state, ahead, behind) = match &self.status {
+            Some(status) => {
+                let (ahead, behind) = status
+                    .upstream
+                    .as_ref()
+                    .map(|upstream| (upstream.ahead.to_string(), upstream.behind.to_string()))
+                    .unwrap_or_default();
+                let state = if status.changes.is_clean() {
+                    "clean"
+                } else {
+                    "dirty"
+                };
+                (
+                    status.branch.clone().unwrap_or_default(),
+                    state,
+                    ahead,
+                    behind,
+                )
+            }
+            None => (String::new(), "unreadable", String::new(), String::new()),
+        };
+        let (number, pull_request_state) = match &self.pull_request {
+            PullRequestLookup::Found(found) => (found.number.to_string(), found.state.clone()),
+            PullRequestLookup::None => Default::default(),
+            PullRequestLookup::Unavailable(_) => (String::new(), "unavailable".to_owned()),
+        };
+        output::porcelain::record(
+            "status",
+            [
+                self.name.clone(),
+                branch,
+                state.to_owned(),
+                ahead,
+                behind,
+                number,
+                pull_request_state,
+            ],
+        )
+    }
+}
+
+fn pad(value: &str, width: usize) -> String {
+    format!("{value:<width$}")
+}
+
+fn paint(value: &str, style: impl Fn(&str) -> String) -> String {
+    value
+        .if_supports_color(Stream::Stdout, |text| style(text))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, process::Command};
+
+    use color_eyre::eyre::{self, WrapErr};
+    use tempfile::TempDir;
+
+    use crate::{
+        commands::create::CreateCommand, process::CommandOutput, provider::MergeRequestStatus,
+        worktree::UpstreamStatus,
+    };
+
+    /// Reports an open PR for worktrees whose path ends in `with-pr`.
+    #[derive(Debug, Clone)]
+    struct StubRunner;
+
+    impl CommandRunner for StubRunner {
+        fn run(
+            &mut self,
+            _program: &str,
+            current_dir: &Path,
+            _args: &[String],
+        ) -> color_eyre::Result<CommandOutput> {
+            let has_pr = current_dir.ends_with("with-pr");
+            Ok(CommandOutput {
+                stdout: if has_pr {
+                    r#"{"number":7,"state":"OPEN"}"#.to_owned()
+                } else {
+                    String::new()
+                },
+                stderr: if has_pr {
+                    String::new()
+                } else {
+                    "no pull requests found for branch".to_owned()
+                },
+                success: has_pr,
+                status_code: Some(if has_pr { 0 } else { 1 }),
+            })
+        }
+    }
+
+    fn init_git_repo(dir: &TempDir) -> color_eyre::Result<()> {
+        run(dir, ["git", "init"])?;
+        fs::write(dir.path().join("README.md"), "test")?;
+        run(dir, ["git", "add", "README.md"])?;
+        run(
+            dir,
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "Initial commit",
+            ],
+        )
+    }
+
+    fn run(dir: &TempDir, cmd: impl IntoIterator<Item = &'static str>) -> color_eyre::Result<()> {
+        let mut iter = cmd.into_iter();
+        let program = iter.next().expect("command must not be empty");
+        let status = Command::new(program)
+            .current_dir(dir.path())
+            .args(iter)
+            .status()
+            .wrap_err_with(|| eyre::eyre!("failed to run `{program}`"))?;
+
+        if !status.success() {
+            return Err(eyre::eyre!("`{program}` exited with status {status}"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn collects_every_worktree_in_order() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let names = (0..12)
+            .map(|index| format!("feature/{index:02}"))
+            .chain(["feature/with-pr".to_owned()])
+            .collect::<Vec<_>>();
+        for name in &names {
+            CreateCommand::new(name.clone(), None).create_without_enter(&repo, true)?;
+        }
+        fs::write(
+            repo.worktrees_dir().join("feature/03/scratch.txt"),
+            "work in progress",
+        )?;
+        crate::annotations::record(&repo, "feature/05", "feature/05", |annotations| {
+            annotations.note = Some("Spike the new parser".into());
+        })?;
+
+        let rows = StatusCommand::with_runner(GitProvider::GitHub, StubRunner).collect(&repo)?;
+
+        assert_eq!(
+            rows.iter().map(|row| row.name.as_str()).collect::<Vec<_>>(),
+            names.iter().map(String::as_str).collect::<Vec<_>>()
+        );
+        let dirty = rows
+            .iter()
+            .filter(|row| row.cells(GitProvider::GitHub)[3] == "dirty")
+            .map(|row| row.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(dirty, vec!["feature/03"]);
+
+        let with_pr = rows.last().expect("rows should not be empty");
+        assert!(matches!(
+            &with_pr.pull_request,
+            PullRequestLookup::Found(status) if status.number == 7
+        ));
+        assert!(matches!(rows[0].pull_request, PullRequestLookup::None));
+        assert_eq!(rows[5].note.as_deref(), Some("Spike the new parser"));
+        assert_eq!(rows[0].note, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cells_describe_branch_upstream_changes_and_pull_request() {
+        let row = StatusRow {
+            name: "feature/login".into(),
+            status: Some(WorktreeStatus {
+                branch: Some("feature/login".into()),
+                head: Some("abc1234".into()),
+                upstream: Some(UpstreamStatus {
+                    name: "origin/feature/login".into(),
+                    ahead: 2,
+                    behind: 1,
+                }),
+                changes: Default::default(),
+            }),
+            pull_request: PullRequestLookup::Found(MergeRequestStatus {
+                number: 12,
+                url: None,
+                state: "opened".into(),
+            }),
+            note: None,
+        };
+
+        assert_eq!(
+            row.cells(GitProvider::GitLab),
+            [
+                "feature/login".to_owned(),
+                "feature/login".to_owned(),
+                "2 ahead, 1 behind".to_owned(),
+                "clean".to_owned(),
+                "MR !12 (opened)".to_owned(),
+            ]
+        );
+
+        let unreadable = StatusRow {
+            name: "broken".into(),
+            status: None,
+            pull_request: PullRequestLookup::None,
+            note: None,
+        };
+        assert_eq!(unreadable.cells(GitProvider::GitHub)[3], "unreadable");
+    }
+
+    #[test]
+    fn records_leave_unknown_fields_empty() {
+        let row = StatusRow {
+            name: "feature/login".into(),
+            status: Some(WorktreeStatus {
+                branch: Some("feature/login".into()),
+                head: Some("abc1234".into()),
+                upstream: Some(UpstreamStatus {
+                    name: "origin/feature/login".into(),
+                    ahead: 2,
+                    behind: 0,
+                }),
+                changes: Default::default(),
+            }),
+            pull_request: PullRequestLookup::Found(MergeRequestStatus {
+                number: 12,
+                url: None,
+                state: "open".into(),
+            }),
+            note: Some("Spike\tthe parser".into()),
+        };
+        assert_eq!(
+            row.record(),
+            "status\tfeature/login\tfeature/login\tclean\t2\t0\t12\topen"
+        );
+
+        let unreadable = StatusRow {
+            name: "broken".into(),
+            status: None,
+            pull_request: PullRequestLookup::Unavailable("gh not found".into()),
+            note: None,
+        };
+        assert_eq!(
+            unreadable.record(),
+            "status\tbroken\t\tunreadable\t\t\t\tunavailable"
+        );
+    }
+}
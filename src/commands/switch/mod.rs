@@ -0,0 +1,154 @@
+use std::io::IsTerminal;
+
+use color_eyre::eyre::{self, WrapErr};
+
+use crate::{
+    Repo,
+    commands::{
+        open::history::{OpenHistory, resolve_with_history},
+        shell_init::INTEGRATION_ENV,
+    },
+    output,
+    telemetry::{Event, EventStore},
+};
+
+/// Resolves a worktree and prints its path for the `shell-init` wrapper to `cd` into.
+#[derive(Debug)]
+pub struct SwitchCommand {
+    name: String,
+}
+
+impl SwitchCommand {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+
+    pub fn execute(&self, repo: &Repo) -> color_eyre::Result<()> {
+        let resolved = resolve_with_history(&self.name, repo)?;
+        let canonical = resolved
+            .path
+            .canonicalize()
+            .wrap_err_with(|| eyre::eyre!("failed to resolve `{}`", resolved.path.display()))?;
+        let _ = OpenHistory::load(&repo.rsworktree_dir()).record(&resolved.name);
+        EventStore::new(&repo.rsworktree_dir()).record(Event::WorktreeUsed {
+            worktree: resolved.name,
+            command: String::from("switch"),
+        });
+
+        // The wrapper captures stdout, so the path is printed without decoration.
+        println!("{}", canonical.display());
+
+        if std::env::var_os(INTEGRATION_ENV).is_none() && std::io::stdout().is_terminal() {
+            output::hint(tr!(
+                "add `eval \"$(rsworktree shell-init bash)\"` (or zsh/fish) to your shell profile to let `switch` change the directory"
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, process::Command};
+
+    use tempfile::TempDir;
+
+    use crate::commands::create::CreateCommand;
+
+    fn init_git_repo(dir: &TempDir) -> color_eyre::Result<()> {
+        run(dir, ["git", "init"])?;
+        fs::write(dir.path().join("README.md"), "test")?;
+        run(dir, ["git", "add", "README.md"])?;
+        run(
+            dir,
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "Initial commit",
+            ],
+        )
+    }
+
+    fn run(dir: &TempDir, cmd: impl IntoIterator<Item = &'static str>) -> color_eyre::Result<()> {
+        let mut iter = cmd.into_iter();
+        let program = iter.next().expect("command must not be empty");
+        let status = Command::new(program)
+            .current_dir(dir.path())
+            .args(iter)
+            .status()
+            .wrap_err_with(|| eyre::eyre!("failed to run `{program}`"))?;
+
+        if !status.success() {
+            return Err(eyre::eyre!("`{program}` exited with status {status}"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn records_usage_of_resolved_worktree() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        CreateCommand::new("feature/switch".into(), None).create_without_enter(&repo, true)?;
+
+        SwitchCommand::new("switch".into()).execute(&repo)?;
+
+        let events = EventStore::new(&repo.rsworktree_dir()).load()?;
+        assert!(events.iter().any(|record| record.event
+            == Event::WorktreeUsed {
+                worktree: "feature/switch".into(),
+                command: "switch".into(),
+            }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn dash_switches_back_to_the_previous_worktree() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        let error = SwitchCommand::new("-".into())
+            .execute(&repo)
+            .expect_err("nothing was opened yet");
+        assert!(error.to_string().contains("no previously opened worktree"));
+
+        CreateCommand::new("feature/a".into(), None).create_without_enter(&repo, true)?;
+        CreateCommand::new("feature/b".into(), None).create_without_enter(&repo, true)?;
+        SwitchCommand::new("a".into()).execute(&repo)?;
+        SwitchCommand::new("b".into()).execute(&repo)?;
+
+        SwitchCommand::new("-".into()).execute(&repo)?;
+        let recent = OpenHistory::load(&repo.rsworktree_dir()).recent();
+        assert_eq!(recent[0].name, "feature/a");
+        assert_eq!(recent[0].opens, 2);
+
+        SwitchCommand::new("-".into()).execute(&repo)?;
+        let recent = OpenHistory::load(&repo.rsworktree_dir()).recent();
+        assert_eq!(recent[0].name, "feature/b");
+
+        Ok(())
+    }
+
+    #[test]
+    fn errors_for_unknown_worktree() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+
+        let error = SwitchCommand::new("missing".into())
+            .execute(&repo)
+            .expect_err("unknown worktree should not resolve");
+        assert!(error.to_string().contains("not found"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,354 @@
+//! `rsworktree sync`: fetches and fast-forwards every worktree whose branch
+//! fell behind its upstream.
+//!
+//! A worktree is only fast-forwarded when it has no staged, modified or
+//! conflicted files; untracked files do not count, since `git merge
+//! --ff-only` refuses to overwrite them anyway. Branches with commits of their
+//! own are reported as ahead or diverged and left alone. With `--watch`, the
+//! sync runs again after an interval, or as soon as the branch checked out in
+//! the main worktree moves, e.g. after a `git pull` there.
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    Repo, output,
+    process::{CommandRunner, SystemCommandRunner, command_failure},
+    worktree::{self, Worktree, WorktreeStatus},
+};
+
+/// How long `--watch` waits between two syncs unless `--interval` is given.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(300);
+/// How often `--watch` checks whether the main worktree's branch moved.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub struct SyncCommand<R = SystemCommandRunner> {
+    watch: bool,
+    interval: Duration,
+    no_wait: bool,
+    runner: R,
+}
+
+/// What a sync does with one worktree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SyncPlan {
+    /// Detached or without an upstream branch.
+    Untracked,
+    UpToDate,
+    FastForward {
+        behind: usize,
+    },
+    /// Behind, but with local changes a merge could clash with.
+    Dirty {
+        behind: usize,
+    },
+    Ahead {
+        ahead: usize,
+    },
+    Diverged {
+        ahead: usize,
+        behind: usize,
+    },
+}
+
+/// How many worktrees a sync fast-forwarded and found up to date.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct SyncSummary {
+    fast_forwarded: usize,
+    up_to_date: usize,
+}
+
+impl Default for SyncCommand {
+    fn default() -> Self {
+        Self::with_runner(SystemCommandRunner)
+    }
+}
+
+impl SyncCommand {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<R> SyncCommand<R>
+where
+    R: CommandRunner,
+{
+    pub fn with_runner(runner: R) -> Self {
+        Self {
+            watch: false,
+            interval: DEFAULT_INTERVAL,
+            no_wait: false,
+            runner,
+        }
+    }
+
+    /// Keep syncing until interrupted.
+    pub fn with_watch(mut self, watch: bool) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    /// How long `--watch` waits between two syncs.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Fail instead of waiting when another command holds the repository lock.
+    pub fn with_no_wait(mut self, no_wait: bool) -> Self {
+        self.no_wait = no_wait;
+        self
+    }
+
+    pub fn execute(&mut self, repo: &Repo) -> color_eyre::Result<()> {
+        if !self.watch {
+            return self.sync(repo).map(|_| ());
+        }
+        loop {
+            // A failed pass, e.g. while offline, is retried on the next one.
+            if let Err(error) = self.sync(repo) {
+                output::warn(tr!("Sync failed: {}", error));
+            }
+            output::note(tr!(
+                "Syncing again in {} or when the main worktree's branch moves; press Ctrl-C to stop.",
+                format!("{}s", self.interval.as_secs())
+            ));
+            wait_for_change(repo, self.interval);
+        }
+    }
+
+    fn sync(&mut self, repo: &Repo) -> color_eyre::Result<SyncSummary> {
+        let _lock = repo.lock(!self.no_wait)?;
+        let args = ["fetch", "--all", "--prune", "--quiet"].map(String::from);
+        let runner = &mut self.runner;
+        let fetched = output::step(tr!("Fetching remotes..."), || {
+            runner.run("git", repo.root(), &args)
+        })?;
+        if !fetched.success {
+            output::warn(tr!(
+                "Could not fetch; using the last fetched state: {}",
+                command_failure("git", &args, &fetched)
+            ));
+        }
+
+        let mut summary = SyncSummary::default();
+        for worktree in worktree::load_all(repo)? {
+            match plan(&worktree.status) {
+                SyncPlan::Untracked => {}
+                SyncPlan::UpToDate => summary.up_to_date += 1,
+                SyncPlan::FastForward { behind } => {
+                    if self.fast_forward(&worktree, behind) {
+                        summary.fast_forwarded += 1;
+                    }
+                }
+                SyncPlan::Dirty { behind } => output::warn(tr!(
+                    "`{}` has local changes; not fast-forwarding {} commit(s) from `{}`.",
+                    format_name(&worktree.name),
+                    behind,
+                    upstream_name(&worktree)
+                )),
+                SyncPlan::Ahead { ahead } => output::info(tr!(
+                    "`{}` is {} commit(s) ahead of `{}`.",
+                    format_name(&worktree.name),
+                    ahead,
+                    upstream_name(&worktree)
+                )),
+                SyncPlan::Diverged { ahead, behind } => output::warn(tr!(
+                    "`{}` has diverged from `{}` ({} ahead, {} behind); merge or rebase it yourself.",
+                    format_name(&worktree.name),
+                    upstream_name(&worktree),
+                    ahead,
+                    behind
+                )),
+            }
+        }
+        output::success(tr!(
+            "Fast-forwarded {} worktree(s); {} already up to date.",
+            summary.fast_forwarded,
+            summary.up_to_date
+        ));
+        Ok(summary)
+    }
+
+    /// Merges the upstream into `worktree`, returning whether it worked. A
+    /// failure only warns so the other worktrees are still synced.
+    fn fast_forward(&mut self, worktree: &Worktree, behind: usize) -> bool {
+        let args = ["merge", "--ff-only", "--quiet", "@{upstream}"].map(String::from);
+        let result = self
+            .runner
+            .run("git", &worktree.path, &args)
+            .and_then(|output| {
+                if output.success {
+                    Ok(())
+                } else {
+                    Err(command_failure("git", &args, &output))
+                }
+            });
+        match result {
+            Ok(()) => {
+                output::success(tr!(
+                    "Fast-forwarded `{}` by {} commit(s) from `{}`.",
+                    format_name(&worktree.name),
+                    behind,
+                    upstream_name(worktree)
+                ));
+                true
+            }
+            Err(error) => {
+                output::warn(tr!(
+                    "Could not fast-forward `{}`: {}",
+                    format_name(&worktree.name),
+                    error
+                ));
+                false
+            }
+        }
+    }
+}
+
+fn plan(status: &WorktreeStatus) -> SyncPlan {
+    let Some(upstream) = status.upstream.as_ref().filter(|_| status.branch.is_some()) else {
+        return SyncPlan::Untracked;
+    };
+    let changes = &status.changes;
+    let dirty = changes.staged + changes.modified + changes.conflicted > 0;
+    match (upstream.ahead, upstream.behind) {
+        (0, 0) => SyncPlan::UpToDate,
+        (0, behind) if dirty => SyncPlan::Dirty { behind },
+        (0, behind) => SyncPlan::FastForward { behind },
+        (ahead, 0) => SyncPlan::Ahead { ahead },
+        (ahead, behind) => SyncPlan::Diverged { ahead, behind },
+    }
+}
+
+/// Sleeps until `interval` passed or the main worktree's HEAD points to
+/// another commit.
+fn wait_for_change(repo: &Repo, interval: Duration) {
+    let head = || repo.git().head().ok().and_then(|head| head.target());
+    let started = head();
+    let deadline = Instant::now() + interval;
+    while Instant::now() < deadline {
+        thread::sleep(POLL_INTERVAL.min(interval));
+        if head() != started {
+            return;
+        }
+    }
+}
+
+fn upstream_name(worktree: &Worktree) -> &str {
+    worktree
+        .status
+        .upstream
+        .as_ref()
+        .map_or("", |upstream| upstream.name.as_str())
+}
+
+fn format_name(name: &str) -> String {
+    name.if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan().bold()))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{fs, path::Path, process::Command};
+
+    use color_eyre::eyre;
+    use tempfile::TempDir;
+
+    use crate::{
+        commands::create::CreateCommand,
+        worktree::{ChangeSummary, UpstreamStatus},
+    };
+
+    fn git(dir: &Path, args: &[&str]) -> color_eyre::Result<String> {
+        let output = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()?;
+        if !output.status.success() {
+            return Err(eyre::eyre!(
+                "git {args:?} failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    }
+
+    fn status(ahead: usize, behind: usize, modified: usize) -> WorktreeStatus {
+        WorktreeStatus {
+            branch: Some("feature".into()),
+            head: None,
+            upstream: Some(UpstreamStatus {
+                name: "origin/feature".into(),
+                ahead,
+                behind,
+            }),
+            changes: ChangeSummary {
+                modified,
+                ..ChangeSummary::default()
+            },
+        }
+    }
+
+    #[test]
+    fn plan_only_fast_forwards_clean_worktrees_that_are_behind() {
+        assert_eq!(plan(&status(0, 0, 0)), SyncPlan::UpToDate);
+        assert_eq!(plan(&status(0, 2, 0)), SyncPlan::FastForward { behind: 2 });
+        assert_eq!(plan(&status(0, 2, 1)), SyncPlan::Dirty { behind: 2 });
+        assert_eq!(plan(&status(1, 0, 0)), SyncPlan::Ahead { ahead: 1 });
+        assert_eq!(
+            plan(&status(1, 2, 0)),
+            SyncPlan::Diverged {
+                ahead: 1,
+                behind: 2
+            }
+        );
+        assert_eq!(plan(&WorktreeStatus::default()), SyncPlan::Untracked);
+    }
+
+    #[test]
+    fn sync_fast_forwards_a_worktree_behind_its_upstream() -> color_eyre::Result<()> {
+        let remote = TempDir::new()?;
+        git(remote.path(), &["init", "--bare", "--quiet"])?;
+        let upstream = TempDir::new()?;
+        git(upstream.path(), &["init", "--quiet"])?;
+        fs::write(upstream.path().join("README.md"), "one")?;
+        git(upstream.path(), &["add", "README.md"])?;
+        git(upstream.path(), &["commit", "--quiet", "-m", "One"])?;
+        git(upstream.path(), &["checkout", "--quiet", "-b", "feature"])?;
+        let remote_url = remote.path().to_string_lossy().into_owned();
+        git(
+            upstream.path(),
+            &["push", "--quiet", &remote_url, "feature"],
+        )?;
+
+        let dir = TempDir::new()?;
+        git(dir.path(), &["clone", "--quiet", &remote_url, "."])?;
+        let repo = Repo::discover_from(dir.path())?;
+        CreateCommand::new("feature".into(), None).create_without_enter(&repo, true)?;
+
+        fs::write(upstream.path().join("README.md"), "two")?;
+        git(upstream.path(), &["commit", "--quiet", "-am", "Two"])?;
+        git(
+            upstream.path(),
+            &["push", "--quiet", &remote_url, "feature"],
+        )?;
+        let tip = git(upstream.path(), &["rev-parse", "HEAD"])?;
+
+        let summary = SyncCommand::new().sync(&repo)?;
+        assert_eq!(summary.fast_forwarded, 1);
+        let worktree = repo.worktrees_dir().join("feature");
+        assert_eq!(git(&worktree, &["rev-parse", "HEAD"])?, tip);
+        assert_eq!(fs::read_to_string(worktree.join("README.md"))?, "two");
+        Ok(())
+    }
+}
@@ -0,0 +1,440 @@
+//! `rsworktree ui`: a terminal interface over [`WorktreeManager`].
+
+mod view;
+
+use std::{io, time::Duration};
+
+use color_eyre::eyre::WrapErr;
+use crossterm::{
+    event::{Event, KeyCode, KeyEvent, KeyEventKind},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{
+    Terminal,
+    backend::{Backend, CrosstermBackend},
+};
+
+use crate::{
+    CreateOptions, OpenOptions, RemoveOptions, WorktreeManager,
+    commands::interactive::{EventSource, StatusMessage},
+    telemetry::{self, EditorLaunchStatus},
+    worktree::Worktree,
+};
+
+/// How long the worktree list may go without reloading its git status.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+/// Commits shown by the log view.
+const LOG_LIMIT: usize = 50;
+const PAGE: u16 = 10;
+
+pub fn run(manager: &WorktreeManager) -> color_eyre::Result<()> {
+    enable_raw_mode().wrap_err("failed to enable raw mode")?;
+    execute!(io::stdout(), EnterAlternateScreen).wrap_err("failed to enter alternate screen")?;
+
+    let result = {
+        // Telemetry printed on stderr would draw over the interface.
+        let _stderr = telemetry::hold_stderr();
+        Terminal::new(CrosstermBackend::new(io::stdout()))
+            .wrap_err("failed to initialize terminal")
+            .and_then(|mut terminal| {
+                run_app(&mut terminal, RefreshingEvents(REFRESH_INTERVAL), manager)
+            })
+    };
+
+    disable_raw_mode().wrap_err("failed to disable raw mode")?;
+    execute!(io::stdout(), LeaveAlternateScreen).wrap_err("failed to leave alternate screen")?;
+    result
+}
+
+/// Terminal events, with a `FocusGained` standing in for a refresh tick when
+/// no input arrives within the interval.
+struct RefreshingEvents(Duration);
+
+impl EventSource for RefreshingEvents {
+    fn next(&mut self) -> color_eyre::Result<Event> {
+        if crossterm::event::poll(self.0).wrap_err("failed to poll terminal events")? {
+            crossterm::event::read().wrap_err("failed to read terminal event")
+        } else {
+            Ok(Event::FocusGained)
+        }
+    }
+}
+
+/// Draws the interface until `q` or `Esc` is pressed in the worktree list.
+fn run_app<B: Backend, E: EventSource>(
+    terminal: &mut Terminal<B>,
+    mut events: E,
+    manager: &WorktreeManager,
+) -> color_eyre::Result<()> {
+    let mut app = App::load(manager)?;
+    loop {
+        terminal
+            .draw(|frame| view::render(frame, &app))
+            .wrap_err("failed to draw interface")?;
+        match events.next()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                match app.handle_key(key, manager) {
+                    Control::Continue => {}
+                    // Hooks and editors write to the terminal behind our back.
+                    Control::Redraw => terminal.clear().wrap_err("failed to clear terminal")?,
+                    Control::Quit => return Ok(()),
+                }
+            }
+            Event::Key(_) => {}
+            _ => app.refresh(manager),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Control {
+    Continue,
+    Redraw,
+    Quit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Mode {
+    Browse,
+    Create {
+        input: String,
+    },
+    ConfirmRemove {
+        name: String,
+    },
+    Output {
+        title: String,
+        text: String,
+        scroll: u16,
+    },
+}
+
+#[derive(Debug)]
+struct App {
+    worktrees: Vec<Worktree>,
+    selected: usize,
+    mode: Mode,
+    status: Option<StatusMessage>,
+}
+
+impl App {
+    fn load(manager: &WorktreeManager) -> color_eyre::Result<Self> {
+        Ok(Self {
+            worktrees: manager.list()?,
+            selected: 0,
+            mode: Mode::Browse,
+            status: None,
+        })
+    }
+
+    fn selected(&self) -> Option<&Worktree> {
+        self.worktrees.get(self.selected)
+    }
+
+    /// Reloads the list and its git status, keeping the selected worktree selected.
+    fn refresh(&mut self, manager: &WorktreeManager) {
+        let current = self.selected().map(|worktree| worktree.name.clone());
+        match manager.list() {
+            Ok(worktrees) => {
+                self.worktrees = worktrees;
+                self.select(current.as_deref());
+            }
+            Err(err) => {
+                self.status = Some(StatusMessage::error(tr!(
+                    "Failed to load worktrees: {}",
+                    err
+                )));
+            }
+        }
+    }
+
+    fn select(&mut self, name: Option<&str>) {
+        self.selected = name
+            .and_then(|name| self.worktrees.iter().position(|w| w.name == name))
+            .unwrap_or(self.selected)
+            .min(self.worktrees.len().saturating_sub(1));
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, manager: &WorktreeManager) -> Control {
+        match &mut self.mode {
+            Mode::Browse => return self.handle_browse_key(key, manager),
+            Mode::Create { input } => match key.code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Esc => self.mode = Mode::Browse,
+                KeyCode::Enter => {
+                    let name = input.trim().to_owned();
+                    self.mode = Mode::Browse;
+                    return self.create(&name, manager);
+                }
+                _ => {}
+            },
+            Mode::ConfirmRemove { name } => match key.code {
+                KeyCode::Char('y') => {
+                    let name = std::mem::take(name);
+                    self.mode = Mode::Browse;
+                    return self.remove(&name, manager);
+                }
+                KeyCode::Char('n') | KeyCode::Esc => self.mode = Mode::Browse,
+                _ => {}
+            },
+            Mode::Output { scroll, .. } => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => self.mode = Mode::Browse,
+                KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => *scroll = scroll.saturating_add(1),
+                KeyCode::PageUp => *scroll = scroll.saturating_sub(PAGE),
+                KeyCode::PageDown => *scroll = scroll.saturating_add(PAGE),
+                _ => {}
+            },
+        }
+        Control::Continue
+    }
+
+    fn handle_browse_key(&mut self, key: KeyEvent, manager: &WorktreeManager) -> Control {
+        self.status = None;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Control::Quit,
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.worktrees.len().saturating_sub(1));
+            }
+            KeyCode::Char('r') => self.refresh(manager),
+            KeyCode::Char('c') => {
+                self.mode = Mode::Create {
+                    input: String::new(),
+                };
+            }
+            KeyCode::Char(key @ ('o' | 'd' | 'v' | 'l')) => {
+                let Some(name) = self.selected().map(|worktree| worktree.name.clone()) else {
+                    self.status = Some(StatusMessage::info(tr!("No worktree selected.")));
+                    return Control::Continue;
+                };
+                match key {
+                    'o' => return self.open(&name, manager),
+                    'd' => self.mode = Mode::ConfirmRemove { name },
+                    'v' => self.show_output(
+                        tr!("Diff of `{}`", name),
+                        manager.diff(&name),
+                        tr!("No uncommitted changes."),
+                    ),
+                    _ => self.show_output(
+                        tr!("Log of `{}`", name),
+                        manager.log(&name, LOG_LIMIT),
+                        tr!("No commits yet."),
+                    ),
+                }
+            }
+            _ => {}
+        }
+        Control::Continue
+    }
+
+    fn show_output(&mut self, title: String, text: color_eyre::Result<String>, empty: String) {
+        match text {
+            Ok(text) => {
+                let text = if text.trim().is_empty() { empty } else { text };
+                self.mode = Mode::Output {
+                    title,
+                    text,
+                    scroll: 0,
+                };
+            }
+            Err(err) => self.status = Some(StatusMessage::error(format!("{err:#}"))),
+        }
+    }
+
+    fn open(&mut self, name: &str, manager: &WorktreeManager) -> Control {
+        self.status = Some(match manager.open(OpenOptions::new(name)) {
+            Ok(opened) if opened.launch.status == EditorLaunchStatus::Success => {
+                StatusMessage::info(opened.launch.message)
+            }
+            Ok(opened) => StatusMessage::error(opened.launch.message),
+            Err(err) => StatusMessage::error(format!("{err:#}")),
+        });
+        Control::Redraw
+    }
+
+    fn create(&mut self, name: &str, manager: &WorktreeManager) -> Control {
+        if name.is_empty() {
+            self.status = Some(StatusMessage::error(tr!("Worktree name cannot be empty.")));
+            return Control::Continue;
+        }
+        match manager.create(CreateOptions::new(name)) {
+            Ok(created) => {
+                self.refresh(manager);
+                self.select(Some(&created.worktree.name));
+                self.status = Some(StatusMessage::info(tr!(
+                    "Created `{}`.",
+                    created.worktree.name
+                )));
+            }
+            Err(err) => {
+                self.status = Some(StatusMessage::error(tr!(
+                    "Failed to create `{}`: {}",
+                    name,
+                    err
+                )));
+            }
+        }
+        Control::Redraw
+    }
+
+    fn remove(&mut self, name: &str, manager: &WorktreeManager) -> Control {
+        match manager.remove(RemoveOptions::new(name)) {
+            Ok(_) => {
+                self.refresh(manager);
+                self.status = Some(StatusMessage::info(tr!("Removed `{}`.", name)));
+            }
+            Err(err) => {
+                self.status = Some(StatusMessage::error(tr!(
+                    "Failed to remove `{}`: {}",
+                    name,
+                    err
+                )));
+            }
+        }
+        Control::Redraw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{fs, process::Command};
+
+    use crossterm::event::KeyModifiers;
+    use ratatui::backend::TestBackend;
+    use tempfile::TempDir;
+
+    use crate::Repo;
+
+    struct StubEvents(Vec<Event>);
+
+    impl EventSource for StubEvents {
+        fn next(&mut self) -> color_eyre::Result<Event> {
+            Ok(self.0.remove(0))
+        }
+    }
+
+    fn keys(codes: &[KeyCode]) -> StubEvents {
+        StubEvents(
+            codes
+                .iter()
+                .map(|code| Event::Key(KeyEvent::new(*code, KeyModifiers::NONE)))
+                .collect(),
+        )
+    }
+
+    fn init_repo() -> color_eyre::Result<(TempDir, WorktreeManager)> {
+        let dir = TempDir::new()?;
+        for args in [
+            &["init"][..],
+            &["commit", "--allow-empty", "-m", "init"][..],
+        ] {
+            let status = Command::new("git")
+                .current_dir(dir.path())
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .status()?;
+            assert!(status.success());
+        }
+        let manager = WorktreeManager::new(Repo::discover_from(dir.path())?);
+        Ok((dir, manager))
+    }
+
+    fn screen(terminal: &Terminal<TestBackend>) -> String {
+        terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn lists_worktrees_with_their_status() -> color_eyre::Result<()> {
+        let (_dir, manager) = init_repo()?;
+        manager.create(CreateOptions::new("feature/api"))?;
+        let dirty = manager.create(CreateOptions::new("feature/web"))?;
+        fs::write(dirty.worktree.path.join("notes.txt"), "wip")?;
+        let mut terminal = Terminal::new(TestBackend::new(100, 20))?;
+
+        run_app(&mut terminal, keys(&[KeyCode::Char('q')]), &manager)?;
+
+        let screen = screen(&terminal);
+        for text in [
+            "feature/api",
+            "feature/web",
+            "clean",
+            "1 untracked",
+            "Branch",
+        ] {
+            assert!(screen.contains(text), "missing `{text}` in interface");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn creates_and_removes_worktrees_from_keys() -> color_eyre::Result<()> {
+        let (_dir, manager) = init_repo()?;
+        let mut terminal = Terminal::new(TestBackend::new(100, 20))?;
+        let events = keys(&[
+            KeyCode::Char('c'),
+            KeyCode::Char('n'),
+            KeyCode::Char('e'),
+            KeyCode::Char('w'),
+            KeyCode::Enter,
+            KeyCode::Char('q'),
+        ]);
+
+        run_app(&mut terminal, events, &manager)?;
+
+        let path = manager.get("new")?.path;
+        assert!(screen(&terminal).contains("Created `new`."));
+
+        let events = keys(&[KeyCode::Char('d'), KeyCode::Char('y'), KeyCode::Char('q')]);
+        run_app(&mut terminal, events, &manager)?;
+
+        assert!(!path.exists());
+        assert!(manager.list()?.is_empty());
+        assert!(screen(&terminal).contains("Removed `new`."));
+
+        Ok(())
+    }
+
+    #[test]
+    fn shows_the_log_and_diff_of_the_selected_worktree() -> color_eyre::Result<()> {
+        let (_dir, manager) = init_repo()?;
+        let created = manager.create(CreateOptions::new("feature"))?;
+        let mut terminal = Terminal::new(TestBackend::new(100, 20))?;
+        let mut app = App::load(&manager)?;
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        app.handle_key(press(KeyCode::Char('l')), &manager);
+        terminal.draw(|frame| view::render(frame, &app))?;
+        let screen_text = screen(&terminal);
+        assert!(screen_text.contains("Log of `feature`"));
+        assert!(screen_text.contains("init"));
+
+        app.handle_key(press(KeyCode::Esc), &manager);
+        assert_eq!(app.mode, Mode::Browse);
+
+        fs::write(created.worktree.path.join("a.txt"), "added\n")?;
+        let status = Command::new("git")
+            .current_dir(&created.worktree.path)
+            .args(["add", "a.txt"])
+            .status()?;
+        assert!(status.success());
+        app.handle_key(press(KeyCode::Char('v')), &manager);
+        terminal.draw(|frame| view::render(frame, &app))?;
+        assert!(screen(&terminal).contains("+added"));
+
+        Ok(())
+    }
+}
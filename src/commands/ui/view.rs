@@ -0,0 +1,147 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use crate::{commands::interactive::plain_buffer, output, worktree::Worktree};
+
+use super::{App, Mode};
+
+pub(super) fn render(frame: &mut Frame<'_>, app: &App) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(frame.size());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(layout[0]);
+
+    render_list(frame, columns[0], app);
+    match &app.mode {
+        Mode::Output {
+            title,
+            text,
+            scroll,
+        } => {
+            let output = Paragraph::new(text.as_str())
+                .block(panel(title.clone()))
+                .scroll((*scroll, 0));
+            frame.render_widget(output, columns[1]);
+        }
+        _ => render_details(frame, columns[1], app.selected()),
+    }
+    frame.render_widget(footer(app), layout[1]);
+
+    if output::is_plain() {
+        plain_buffer(frame.buffer_mut());
+    }
+}
+
+fn render_list(frame: &mut Frame<'_>, area: Rect, app: &App) {
+    let items = if app.worktrees.is_empty() {
+        vec![ListItem::new(tr!(
+            "(no worktrees, press `c` to create one)"
+        ))]
+    } else {
+        app.worktrees.iter().map(list_item).collect()
+    };
+    let list = List::new(items)
+        .block(panel(tr!("Worktrees")))
+        .highlight_symbol("▶ ")
+        .highlight_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    let mut state = ListState::default();
+    if !app.worktrees.is_empty() {
+        state.select(Some(app.selected));
+    }
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn list_item(worktree: &Worktree) -> ListItem<'static> {
+    let changes = &worktree.status.changes;
+    let color = if changes.is_clean() {
+        Color::Green
+    } else {
+        Color::Yellow
+    };
+    let mut spans = vec![
+        Span::raw(worktree.name.clone()),
+        Span::raw("  "),
+        Span::styled(changes.describe(), Style::default().fg(color)),
+    ];
+    if let Some(upstream) = &worktree.status.upstream {
+        spans.push(Span::raw(format!(
+            "  ↑{} ↓{}",
+            upstream.ahead, upstream.behind
+        )));
+    }
+    ListItem::new(Line::from(spans))
+}
+
+fn render_details(frame: &mut Frame<'_>, area: Rect, worktree: Option<&Worktree>) {
+    let lines = match worktree {
+        Some(worktree) => {
+            let status = &worktree.status;
+            let none = || tr!("none");
+            let upstream = status.upstream.as_ref().map_or_else(none, |upstream| {
+                format!(
+                    "{} (↑{} ↓{})",
+                    upstream.name, upstream.ahead, upstream.behind
+                )
+            });
+            [
+                (tr!("Path"), worktree.path.display().to_string()),
+                (tr!("Branch"), status.branch.clone().unwrap_or_else(none)),
+                (tr!("Base"), worktree.base.clone().unwrap_or_else(none)),
+                (tr!("HEAD"), status.head.clone().unwrap_or_else(none)),
+                (tr!("Upstream"), upstream),
+                (tr!("Changes"), status.changes.describe()),
+            ]
+            .into_iter()
+            .map(|(label, value)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{label}: "),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(value),
+                ])
+            })
+            .collect()
+        }
+        None => Vec::new(),
+    };
+    let details = Paragraph::new(lines)
+        .block(panel(tr!("Details")))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(details, area);
+}
+
+fn footer(app: &App) -> Paragraph<'static> {
+    match &app.mode {
+        Mode::Create { input } => Paragraph::new(tr!("New worktree name: {}_", input)),
+        Mode::ConfirmRemove { name } => Paragraph::new(tr!("Remove `{}`? (y/n)", name))
+            .style(Style::default().fg(Color::Yellow)),
+        Mode::Output { .. } => Paragraph::new(tr!("↑/↓ scroll  PgUp/PgDn page  q back"))
+            .style(Style::default().fg(Color::Gray)),
+        Mode::Browse => match &app.status {
+            Some(status) => Paragraph::new(status.text.clone()).style(status.style()),
+            None => Paragraph::new(tr!(
+                "o open  d remove  c create  v diff  l log  r refresh  q quit"
+            ))
+            .style(Style::default().fg(Color::Gray)),
+        },
+    }
+}
+
+fn panel(title: String) -> Block<'static> {
+    Block::default().title(title).borders(Borders::ALL)
+}
@@ -0,0 +1,521 @@
+//! Workspaces: named sets of repositories, configured as
+//! `[workspaces.<name>]` in the per-user `config.toml`, for features that
+//! span several of them.
+//!
+//! `ws create payments-fix` creates the worktree and branch `payments-fix` in
+//! every member repository. `ws open payments-fix` opens them side by side:
+//! one tmux window per repository inside tmux, else one VS Code or Cursor
+//! window with a multi-root workspace, else each in its own editor.
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    env, fs,
+    path::{Component, Path, PathBuf},
+    process::Command,
+};
+
+use color_eyre::eyre::{self, WrapErr};
+
+use crate::{
+    Repo,
+    commands::{
+        create::CreateCommand,
+        open::{
+            ResolvedWorktree, find_by_name,
+            multiplexer::{EditorInvocation, render_window_name, shell_window_args},
+        },
+    },
+    config::{self, Config, WorkspaceConfig},
+    editor::{
+        EditorKind, EditorPreference, EditorPreferenceResolution, LaunchWait, launch_worktree_with,
+        resolve_editor, resolve_program, url_template,
+    },
+    output,
+    process::{CommandRunner, SystemCommandRunner, command_failure},
+    telemetry::EditorLaunchStatus,
+};
+
+/// Editors that open a `.code-workspace` file as one window.
+const MULTI_ROOT_EDITORS: [&str; 5] = ["code", "code-insiders", "codium", "cursor", "windsurf"];
+
+/// Creates or opens the worktree `name` in every repository of a workspace.
+#[derive(Debug)]
+pub struct WorkspaceCommand<R = SystemCommandRunner> {
+    name: String,
+    workspace: Option<String>,
+    base: Option<String>,
+    runner: RefCell<R>,
+}
+
+/// A repository of the workspace.
+struct Member {
+    /// The directory name of the repository, such as `api`.
+    label: String,
+    repo: Repo,
+}
+
+impl WorkspaceCommand {
+    pub fn new(name: String) -> Self {
+        Self::with_runner(name, SystemCommandRunner)
+    }
+}
+
+impl<R> WorkspaceCommand<R>
+where
+    R: CommandRunner,
+{
+    /// Runs `tmux` and the editor through `runner`.
+    pub fn with_runner(name: String, runner: R) -> Self {
+        Self {
+            name,
+            workspace: None,
+            base: None,
+            runner: RefCell::new(runner),
+        }
+    }
+
+    /// The workspace to use. Defaults to the one containing the current
+    /// repository, or else the only one configured.
+    pub fn with_workspace(mut self, workspace: Option<String>) -> Self {
+        self.workspace = workspace;
+        self
+    }
+
+    /// Start every branch from `base` instead of each repository's `HEAD`.
+    pub fn with_base(mut self, base: Option<String>) -> Self {
+        self.base = base;
+        self
+    }
+
+    /// Creates the worktree in every member repository, keeping those that
+    /// already exist so an interrupted run can be repeated.
+    pub fn create(&self, current: Option<&Repo>) -> color_eyre::Result<()> {
+        let (workspace, members) = self.members(current)?;
+        for member in &members {
+            output::heading(&member.label);
+            CreateCommand::new(self.name.clone(), self.base.clone())
+                .with_exists_ok(true)
+                .create_without_enter(&member.repo, false)
+                .wrap_err_with(|| {
+                    eyre::eyre!(
+                        "failed to create `{}` in `{}`",
+                        self.name,
+                        member.repo.root().display()
+                    )
+                })?;
+        }
+        output::success(tr!(
+            "Created `{}` in {} repositories of workspace `{}`.",
+            self.name,
+            members.len(),
+            workspace
+        ));
+        Ok(())
+    }
+
+    /// Opens the worktree of every member repository that has one.
+    pub fn open(&self, current: Option<&Repo>) -> color_eyre::Result<()> {
+        let (workspace, members) = self.members(current)?;
+        let mut worktrees = Vec::new();
+        for member in members {
+            match find_by_name(&self.name, &member.repo)? {
+                Some(worktree) => worktrees.push((member, worktree)),
+                None => output::warn(tr!(
+                    "`{}` has no worktree `{}`; run `rsworktree ws create {}`.",
+                    member.label,
+                    self.name,
+                    self.name
+                )),
+            }
+        }
+        let Some((first, first_worktree)) = worktrees.first() else {
+            return Err(eyre::eyre!(
+                "no repository of workspace `{}` has a worktree `{}`",
+                workspace,
+                self.name
+            ));
+        };
+
+        if env::var_os("TMUX").is_some() {
+            return self.open_tmux_windows(&worktrees);
+        }
+        match resolve_editor(&first.repo, &first_worktree.name)? {
+            EditorPreferenceResolution::Found(preference) if is_multi_root(&preference) => {
+                self.open_code_workspace(&workspace, &preference, &worktrees)
+            }
+            _ => {
+                self.open_each(&worktrees);
+                Ok(())
+            }
+        }
+    }
+
+    /// Loads the selected workspace and discovers its repositories.
+    fn members(&self, current: Option<&Repo>) -> color_eyre::Result<(String, Vec<Member>)> {
+        let path = config::global_path().ok_or_else(|| {
+            eyre::eyre!(
+                "cannot locate the per-user `config.toml`; set {}",
+                config::GLOBAL_CONFIG_ENV
+            )
+        })?;
+        let workspaces = Config::read(&path)?.workspaces;
+        let (name, workspace) = select_workspace(
+            &workspaces,
+            self.workspace.as_deref(),
+            current.map(Repo::root),
+            &path,
+        )?;
+        if workspace.repos.is_empty() {
+            return Err(eyre::eyre!(
+                "workspace `{}` in `{}` lists no `repos`",
+                name,
+                path.display()
+            ));
+        }
+
+        let members = workspace
+            .repos
+            .iter()
+            .map(|repo| {
+                let repo = expand_home(repo);
+                let repo = Repo::discover_from(&repo).wrap_err_with(|| {
+                    eyre::eyre!(
+                        "repository `{}` of workspace `{}` not found",
+                        repo.display(),
+                        name
+                    )
+                })?;
+                let label = repo
+                    .root()
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| repo.root().display().to_string());
+                Ok(Member { label, repo })
+            })
+            .collect::<color_eyre::Result<Vec<_>>>()?;
+        Ok((name.to_owned(), members))
+    }
+
+    /// Writes `<workspace>-<name>.code-workspace` next to the per-user config
+    /// and opens it in `preference`.
+    fn open_code_workspace(
+        &self,
+        workspace: &str,
+        preference: &EditorPreference,
+        worktrees: &[(Member, ResolvedWorktree)],
+    ) -> color_eyre::Result<()> {
+        let dir = config::global_path()
+            .and_then(|path| path.parent().map(|dir| dir.join("workspaces")))
+            .unwrap_or_else(env::temp_dir);
+        fs::create_dir_all(&dir)
+            .wrap_err_with(|| eyre::eyre!("failed to create `{}`", dir.display()))?;
+        let file = dir.join(format!(
+            "{}-{}.code-workspace",
+            workspace,
+            self.name.replace('/', "-")
+        ));
+        fs::write(&file, code_workspace(worktrees))
+            .wrap_err_with(|| eyre::eyre!("failed to write `{}`", file.display()))?;
+
+        let program = resolve_program(&preference.command);
+        let mut command = Command::new(&program);
+        command.args(&preference.args).arg(&file);
+        self.runner
+            .borrow_mut()
+            .spawn(&mut command)
+            .wrap_err_with(|| {
+                eyre::eyre!(
+                    "failed to launch `{}`",
+                    preference.command.to_string_lossy()
+                )
+            })?;
+        output::success(tr!(
+            "Opened `{}` in {} repositories with `{}`.",
+            self.name,
+            worktrees.len(),
+            file.display()
+        ));
+        Ok(())
+    }
+
+    /// Opens every worktree in its own editor, like `worktree open`.
+    fn open_each(&self, worktrees: &[(Member, ResolvedWorktree)]) {
+        for (member, worktree) in worktrees {
+            match launch_worktree_with(
+                &member.repo,
+                &worktree.name,
+                &worktree.path,
+                LaunchWait::Detached,
+                &mut *self.runner.borrow_mut(),
+            ) {
+                Ok(outcome) if outcome.status == EditorLaunchStatus::Success => {
+                    output::success(tr!(
+                        "Opened `{}` at `{}`.",
+                        worktree.name,
+                        worktree.path.display()
+                    ));
+                }
+                Ok(outcome) => output::warn(outcome.message),
+                Err(error) => output::error(format!("{}: {}", member.label, error)),
+            }
+        }
+    }
+
+    /// Opens one tmux window per repository, named `<repository>/<worktree>` or
+    /// after the repository's `window_name`, running its editor, or a shell when
+    /// it has none.
+    fn open_tmux_windows(
+        &self,
+        worktrees: &[(Member, ResolvedWorktree)],
+    ) -> color_eyre::Result<()> {
+        for (member, worktree) in worktrees {
+            let template = member
+                .repo
+                .config()
+                .ok()
+                .and_then(|config| config.window_name);
+            let window_name = render_window_name(
+                template.as_deref(),
+                &member.label,
+                &worktree.name,
+                &worktree.path,
+            );
+            let mut args = shell_window_args(&window_name, worktree);
+            if let EditorPreferenceResolution::Found(preference) =
+                resolve_editor(&member.repo, &worktree.name)?
+                && preference.kind == EditorKind::Editor
+                && url_template(&preference.command).is_none()
+            {
+                args.push(EditorInvocation::new(&preference).command_line(worktree));
+            }
+            let output = self
+                .runner
+                .borrow_mut()
+                .run("tmux", Path::new("."), &args)
+                .wrap_err("failed to create tmux window")?;
+            if !output.success {
+                return Err(command_failure("tmux", &args, &output)
+                    .wrap_err(format!("failed to create tmux window `{window_name}`")));
+            }
+            output::success(tr!(
+                "Opened `{}` in window `{}`",
+                worktree.name,
+                window_name
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Picks `requested`, else the workspace listing the repository at
+/// `current`, else the only one configured in `path`.
+fn select_workspace<'a>(
+    workspaces: &'a BTreeMap<String, WorkspaceConfig>,
+    requested: Option<&str>,
+    current: Option<&Path>,
+    path: &Path,
+) -> color_eyre::Result<(&'a str, &'a WorkspaceConfig)> {
+    let names = || {
+        workspaces
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if workspaces.is_empty() {
+        return Err(eyre::eyre!(
+            "no workspaces configured; add `[workspaces.<name>]` with `repos = [...]` to `{}`",
+            path.display()
+        ));
+    }
+    if let Some(requested) = requested {
+        return workspaces
+            .get_key_value(requested)
+            .map(|(name, workspace)| (name.as_str(), workspace))
+            .ok_or_else(|| {
+                eyre::eyre!("unknown workspace `{}`; configured: {}", requested, names())
+            });
+    }
+
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if let Some(current) = current.map(canonical) {
+        let mut containing = workspaces.iter().filter(|(_, workspace)| {
+            workspace
+                .repos
+                .iter()
+                .any(|repo| canonical(&expand_home(repo)) == current)
+        });
+        if let (Some((name, workspace)), None) = (containing.next(), containing.next()) {
+            return Ok((name.as_str(), workspace));
+        }
+    }
+    match workspaces.iter().next() {
+        Some((name, workspace)) if workspaces.len() == 1 => Ok((name.as_str(), workspace)),
+        _ => Err(eyre::eyre!(
+            "several workspaces are configured ({}); pick one with `--workspace`",
+            names()
+        )),
+    }
+}
+
+/// `path` with a leading `~` replaced by the home directory.
+fn expand_home(path: &Path) -> PathBuf {
+    let mut components = path.components();
+    match components.next() {
+        Some(Component::Normal(first)) if first == "~" => {
+            match env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+                Some(home) => PathBuf::from(home).join(components.as_path()),
+                None => path.to_path_buf(),
+            }
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+fn is_multi_root(preference: &EditorPreference) -> bool {
+    preference.kind == EditorKind::Editor
+        && Path::new(&preference.command)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| MULTI_ROOT_EDITORS.contains(&stem))
+}
+
+/// A `.code-workspace` file with one folder per worktree, named after its repository.
+fn code_workspace(worktrees: &[(Member, ResolvedWorktree)]) -> String {
+    folders_json(
+        worktrees
+            .iter()
+            .map(|(member, worktree)| (member.label.as_str(), worktree.path.as_path())),
+    )
+}
+
+fn folders_json<'a>(folders: impl Iterator<Item = (&'a str, &'a Path)>) -> String {
+    let folders = folders
+        .map(|(name, path)| serde_json::json!({ "name": name, "path": path }))
+        .collect::<Vec<_>>();
+    let mut json = serde_json::to_string_pretty(&serde_json::json!({ "folders": folders }))
+        .unwrap_or_default();
+    json.push('\n');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    use crate::process::{CommandOutput, ScriptedRunner};
+
+    fn workspace(repos: &[&Path]) -> WorkspaceConfig {
+        WorkspaceConfig {
+            repos: repos.iter().map(|repo| repo.to_path_buf()).collect(),
+        }
+    }
+
+    #[test]
+    fn select_workspace_prefers_the_one_of_the_current_repository() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let (api, web, docs) = (
+            dir.path().join("api"),
+            dir.path().join("web"),
+            dir.path().join("docs"),
+        );
+        let config = dir.path().join("config.toml");
+        let workspaces = BTreeMap::from([
+            ("payments".to_owned(), workspace(&[&api, &web])),
+            ("site".to_owned(), workspace(&[&web, &docs])),
+        ]);
+
+        let select = |requested, current| {
+            select_workspace(&workspaces, requested, current, &config)
+                .map(|(name, _)| name.to_owned())
+        };
+        assert_eq!(select(Some("site"), Some(api.as_path()))?, "site");
+        assert_eq!(select(None, Some(api.as_path()))?, "payments");
+        assert!(select(None, Some(web.as_path())).is_err());
+        assert!(select(None, None).is_err());
+        assert!(select(Some("billing"), None).is_err());
+
+        let single = BTreeMap::from([("payments".to_owned(), workspace(&[&api]))]);
+        let (name, _) = select_workspace(&single, None, None, &config)?;
+        assert_eq!(name, "payments");
+        assert!(select_workspace(&BTreeMap::new(), None, None, &config).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn tmux_opens_one_window_per_repository() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let mut worktrees = Vec::new();
+        for label in ["api", "web"] {
+            git2::Repository::init(dir.path().join(label))?;
+            let repo = Repo::discover_from(dir.path().join(label))?;
+            let worktree = ResolvedWorktree {
+                name: "payments-fix".into(),
+                path: repo.worktrees_dir().join("payments-fix"),
+            };
+            let member = Member {
+                label: label.into(),
+                repo,
+            };
+            worktrees.push((member, worktree));
+        }
+
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::success(""))
+            .push(CommandOutput::failure(1, "no server running"));
+        let error = WorkspaceCommand::with_runner("payments-fix".into(), runner.clone())
+            .open_tmux_windows(&worktrees)
+            .expect_err("the second window failed");
+        assert!(
+            format!("{error:?}").contains("no server running"),
+            "{error:?}"
+        );
+
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 2);
+        assert!(calls.iter().all(|call| call.program == "tmux"));
+        assert_eq!(
+            calls[0].args[..5],
+            shell_window_args("api/payments-fix", &worktrees[0].1)
+        );
+        assert_eq!(calls[1].args[2], "web/payments-fix");
+        Ok(())
+    }
+
+    #[test]
+    fn expand_home_replaces_a_leading_tilde() {
+        let Some(home) = env::var_os("HOME") else {
+            return;
+        };
+        assert_eq!(
+            expand_home(Path::new("~/src/api")),
+            PathBuf::from(home).join("src/api")
+        );
+        assert_eq!(
+            expand_home(Path::new("/srv/~/api")),
+            PathBuf::from("/srv/~/api")
+        );
+    }
+
+    #[test]
+    fn code_workspace_lists_one_folder_per_repository() -> color_eyre::Result<()> {
+        let json = folders_json(
+            [
+                ("api", Path::new("/src/api/.rsworktree/payments-fix")),
+                ("web", Path::new("/src/web/.rsworktree/payments-fix")),
+            ]
+            .into_iter(),
+        );
+        let value: serde_json::Value = serde_json::from_str(&json)?;
+        assert_eq!(value["folders"][1]["name"], "web");
+        assert_eq!(
+            value["folders"][0]["path"],
+            "/src/api/.rsworktree/payments-fix"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,553 @@
+//! Layered `config.toml` settings: per-user defaults from
+//! `~/.config/rsworktree/config.toml`, overridden key by key by the
+//! repository's `.rsworktree/config.toml`. Settings in `preferences.json`
+//! still win over both.
+//!
+//! ```toml
+//! layout = "sibling"
+//! editor = "code --wait"
+//! editors = ["cursor", "code", "nvim"]
+//! provider = "gitlab"
+//! provider_backend = "api"
+//! merge_strategy = "squash"
+//! protected_branches = ["main", "release/*"]
+//! reviewers = ["alice", "acme/backend"]
+//! artifacts = ["target", "**/node_modules"]
+//! branch_pattern = "{user}/{type}/{slug}"
+//! window_name = "{worktree}"
+//! devcontainer = false
+//!
+//! [telemetry]
+//! sinks = ["jsonl", "stderr"]
+//!
+//! [env]
+//! PORT = "3000"
+//!
+//! [hooks]
+//! post-create = ["pnpm install"]
+//!
+//! [templates]
+//! files = [".env.template"]
+//! ports = 5
+//!
+//! [workspaces.payments]
+//! repos = ["~/src/api", "~/src/web"]
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    env, fmt, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    GitProvider,
+    hooks::InlineHooks,
+    provider::{MergeStrategy, ProviderBackend},
+    telemetry::SinkKind,
+    worktree::copy::matches_components,
+};
+
+pub const CONFIG_FILE: &str = "config.toml";
+/// Points to the per-user config file instead of `~/.config/rsworktree/config.toml`.
+pub const GLOBAL_CONFIG_ENV: &str = "RSWORKTREE_CONFIG";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Config {
+    /// Where the worktrees directory lives, see [`Layout`].
+    pub layout: Option<Layout>,
+    pub editor: Option<EditorConfig>,
+    /// Editors tried in order when `editor` is not set: the first one
+    /// installed that starts is used.
+    #[serde(default)]
+    pub editors: Vec<EditorConfig>,
+    pub provider: Option<GitProvider>,
+    /// Whether provider operations run the provider CLI or call its API.
+    pub provider_backend: Option<ProviderBackend>,
+    /// How `merge` lands pull/merge requests when `--strategy` is not given.
+    pub merge_strategy: Option<MergeStrategy>,
+    /// Branches `create` does not create worktrees for and `rm` does not
+    /// delete, as names or globs such as `release/*`.
+    #[serde(default)]
+    pub protected_branches: Vec<String>,
+    /// Whether and where events are recorded, see [`crate::telemetry`].
+    pub telemetry: Option<TelemetryConfig>,
+    /// Variables exported to hooks and the editor, see [`crate::hooks::ProjectEnv`].
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Commands run after each hook's scripts, see [`crate::hooks`].
+    #[serde(default)]
+    pub hooks: InlineHooks,
+    /// Reviewers for `review --reviewer auto` when CODEOWNERS names nobody.
+    #[serde(default)]
+    pub reviewers: Vec<String>,
+    /// Directories `clean` removes, instead of the ones detected per project.
+    #[serde(default)]
+    pub artifacts: Vec<String>,
+    /// How `create` turns its name into a branch, e.g. `{user}/{type}/{slug}`.
+    pub branch_pattern: Option<String>,
+    /// How tmux sessions and multiplexer tabs are named, e.g. `{project}:{branch}`.
+    pub window_name: Option<String>,
+    /// Whether worktrees with a dev container are opened inside it, see
+    /// [`crate::editor`].
+    pub devcontainer: Option<bool>,
+    /// Sets of repositories `ws` creates and opens worktrees in together.
+    #[serde(default)]
+    pub workspaces: BTreeMap<String, WorkspaceConfig>,
+    /// Files `create` renders into new worktrees, see [`crate::worktree::render`].
+    pub templates: Option<TemplatesConfig>,
+}
+
+/// The `layout` setting: where a repository keeps its worktrees. The
+/// configuration and recorded state stay in `.rsworktree` either way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Layout {
+    /// `.rsworktree` in the repository root.
+    #[default]
+    Inside,
+    /// `../<repo>-worktrees`, next to the repository.
+    Sibling,
+    /// `~/worktrees/<repo>`.
+    FlatGlobal,
+}
+
+/// A `[workspaces.<name>]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct WorkspaceConfig {
+    /// The member repositories; a leading `~/` stands for the home directory.
+    pub repos: Vec<PathBuf>,
+}
+
+/// The `[templates]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct TemplatesConfig {
+    /// Files ending in `.template`, relative to the repository root, rendered
+    /// into the worktree without the suffix.
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// How many consecutive ports each worktree gets for `{{port}}`.
+    pub ports: Option<u16>,
+    /// The lowest port handed out.
+    pub first_port: Option<u16>,
+}
+
+/// The `editor` setting, either a command line or `{ command, args }`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "EditorFormat")]
+pub struct EditorConfig {
+    pub command: String,
+    pub args: Vec<String>,
+    /// The file the setting was read from.
+    pub source: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EditorFormat {
+    CommandLine(String),
+    Table {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+impl TryFrom<EditorFormat> for EditorConfig {
+    type Error = String;
+
+    fn try_from(format: EditorFormat) -> Result<Self, Self::Error> {
+        let (command, args) = match format {
+            EditorFormat::CommandLine(line) => {
+                let mut parts = shell_words::split(&line)
+                    .map_err(|error| format!("invalid `editor`: {error}"))?
+                    .into_iter();
+                (parts.next().unwrap_or_default(), parts.collect())
+            }
+            EditorFormat::Table { command, args } => (command, args),
+        };
+        if command.trim().is_empty() {
+            return Err("`editor` must not be empty".to_owned());
+        }
+        Ok(Self {
+            command,
+            args,
+            source: PathBuf::new(),
+        })
+    }
+}
+
+/// The `telemetry` setting, either a boolean or `{ enabled, sinks, endpoint }`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "TelemetryFormat")]
+pub struct TelemetryConfig {
+    pub enabled: bool,
+    pub sinks: Vec<SinkKind>,
+    /// Base URL of the OTLP/HTTP collector for the `otlp` sink.
+    pub endpoint: Option<String>,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sinks: vec![SinkKind::Jsonl],
+            endpoint: None,
+        }
+    }
+}
+
+impl From<bool> for TelemetryConfig {
+    fn from(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TelemetryFormat {
+    Enabled(bool),
+    Table {
+        enabled: Option<bool>,
+        sinks: Option<Vec<SinkKind>>,
+        endpoint: Option<String>,
+    },
+}
+
+impl From<TelemetryFormat> for TelemetryConfig {
+    fn from(format: TelemetryFormat) -> Self {
+        match format {
+            TelemetryFormat::Enabled(enabled) => Self::from(enabled),
+            TelemetryFormat::Table {
+                enabled,
+                sinks,
+                endpoint,
+            } => {
+                let defaults = Self::default();
+                Self {
+                    enabled: enabled.unwrap_or(defaults.enabled),
+                    sinks: sinks.unwrap_or(defaults.sinks),
+                    endpoint,
+                }
+            }
+        }
+    }
+}
+
+/// A config file that could not be read or parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to load `{}`: {}",
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Loads the per-user file and the one in `rsworktree_dir`; missing files
+    /// count as empty.
+    pub fn load(rsworktree_dir: &Path) -> Result<Self, ConfigError> {
+        let global = match global_path() {
+            Some(path) => Self::read(&path)?,
+            None => Self::default(),
+        };
+        let local = Self::read(&rsworktree_dir.join(CONFIG_FILE))?;
+        Ok(local.or(global))
+    }
+
+    /// Reads a single config file.
+    pub fn read(path: &Path) -> Result<Self, ConfigError> {
+        let error = |message: String| ConfigError {
+            path: path.to_path_buf(),
+            message,
+        };
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(error(err.to_string())),
+        };
+        let mut config: Self = toml::from_str(&text).map_err(|err| error(err.to_string()))?;
+        for editor in config.editor.iter_mut().chain(&mut config.editors) {
+            editor.source = path.to_path_buf();
+        }
+        Ok(config)
+    }
+
+    /// Fills every setting missing here from `fallback`.
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            layout: self.layout.or(fallback.layout),
+            editor: self.editor.or(fallback.editor),
+            editors: if self.editors.is_empty() {
+                fallback.editors
+            } else {
+                self.editors
+            },
+            provider: self.provider.or(fallback.provider),
+            provider_backend: self.provider_backend.or(fallback.provider_backend),
+            merge_strategy: self.merge_strategy.or(fallback.merge_strategy),
+            protected_branches: if self.protected_branches.is_empty() {
+                fallback.protected_branches
+            } else {
+                self.protected_branches
+            },
+            telemetry: self.telemetry.or(fallback.telemetry),
+            env: fallback.env.into_iter().chain(self.env).collect(),
+            hooks: self.hooks.or(fallback.hooks),
+            reviewers: if self.reviewers.is_empty() {
+                fallback.reviewers
+            } else {
+                self.reviewers
+            },
+            artifacts: if self.artifacts.is_empty() {
+                fallback.artifacts
+            } else {
+                self.artifacts
+            },
+            branch_pattern: self.branch_pattern.or(fallback.branch_pattern),
+            window_name: self.window_name.or(fallback.window_name),
+            devcontainer: self.devcontainer.or(fallback.devcontainer),
+            workspaces: fallback
+                .workspaces
+                .into_iter()
+                .chain(self.workspaces)
+                .collect(),
+            templates: self.templates.or(fallback.templates),
+        }
+    }
+
+    /// The entry of `protected_branches` that `branch` matches, if any. `*`
+    /// and `?` stay within one `/`-separated part of the name, `**` spans
+    /// several.
+    pub fn protected_pattern(&self, branch: &str) -> Option<&str> {
+        let branch = branch.split('/').map(str::to_owned).collect::<Vec<_>>();
+        self.protected_branches
+            .iter()
+            .find(|pattern| {
+                let pattern = pattern.split('/').collect::<Vec<_>>();
+                matches_components(&pattern, &branch, false)
+            })
+            .map(String::as_str)
+    }
+
+    pub fn telemetry_enabled(&self) -> bool {
+        self.telemetry
+            .as_ref()
+            .is_none_or(|telemetry| telemetry.enabled)
+    }
+}
+
+/// `$RSWORKTREE_CONFIG`, else `config.toml` in `$XDG_CONFIG_HOME/rsworktree`
+/// or `~/.config/rsworktree`.
+pub fn global_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(GLOBAL_CONFIG_ENV).filter(|value| !value.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME")
+                .or_else(|| env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(config_home.join("rsworktree").join(CONFIG_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks::HookName;
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn read_accepts_editor_as_command_line_or_table() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let line = dir.path().join("line.toml");
+        fs::write(
+            &line,
+            "editor = \"code --wait\"\nprovider = \"gitlab\"\nprovider_backend = \"api\"\nlayout = \"flat-global\"\nmerge_strategy = \"squash\"\n",
+        )?;
+        let table = dir.path().join("table.toml");
+        fs::write(&table, "[editor]\ncommand = \"vim\"\nargs = [\"-p\"]\n")?;
+
+        let config = Config::read(&line)?;
+        assert_eq!(config.provider, Some(GitProvider::GitLab));
+        assert_eq!(config.provider_backend, Some(ProviderBackend::Api));
+        assert_eq!(config.layout, Some(Layout::FlatGlobal));
+        assert_eq!(config.merge_strategy, Some(MergeStrategy::Squash));
+        let editor = config.editor.expect("editor");
+        assert_eq!(editor.command, "code");
+        assert_eq!(editor.args, ["--wait"]);
+        assert_eq!(editor.source, line);
+
+        let editor = Config::read(&table)?.editor.expect("editor");
+        assert_eq!(
+            (editor.command.as_str(), editor.args),
+            ("vim", vec!["-p".to_owned()])
+        );
+        assert_eq!(
+            Config::read(&dir.path().join("missing.toml"))?,
+            Config::default()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn read_accepts_telemetry_as_boolean_or_table() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let flag = dir.path().join("flag.toml");
+        fs::write(&flag, "telemetry = false\n")?;
+        let table = dir.path().join("table.toml");
+        fs::write(
+            &table,
+            "[telemetry]\nsinks = [\"stderr\", \"otlp\"]\nendpoint = \"http://localhost:4318\"\n",
+        )?;
+
+        assert!(!Config::read(&flag)?.telemetry_enabled());
+        let telemetry = Config::read(&table)?.telemetry.expect("telemetry");
+        assert!(telemetry.enabled);
+        assert_eq!(telemetry.sinks, [SinkKind::Stderr, SinkKind::Otlp]);
+        assert_eq!(telemetry.endpoint.as_deref(), Some("http://localhost:4318"));
+        assert_eq!(TelemetryConfig::default().sinks, [SinkKind::Jsonl]);
+        Ok(())
+    }
+
+    #[test]
+    fn read_accepts_workspace_tables() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join(CONFIG_FILE);
+        fs::write(
+            &path,
+            "[workspaces.payments]\nrepos = [\"~/src/api\", \"/srv/web\"]\n",
+        )?;
+
+        let workspaces = Config::read(&path)?.workspaces;
+        assert_eq!(
+            workspaces["payments"].repos,
+            [PathBuf::from("~/src/api"), PathBuf::from("/srv/web")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn read_accepts_the_templates_table() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join(CONFIG_FILE);
+        fs::write(
+            &path,
+            "[templates]\nfiles = [\".env.template\"]\nports = 3\nfirst_port = 4000\n",
+        )?;
+
+        let templates = Config::read(&path)?.templates.expect("templates");
+        assert_eq!(templates.files, [".env.template"]);
+        assert_eq!(templates.ports, Some(3));
+        assert_eq!(templates.first_port, Some(4000));
+        Ok(())
+    }
+
+    #[test]
+    fn read_accepts_the_hooks_table() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join(CONFIG_FILE);
+        fs::write(
+            &path,
+            "[hooks]\npost-create = [\"pnpm install\", \"cp ../.env .\"]\npre-remove = [\"docker compose down\"]\n",
+        )?;
+        let user = Config::read(&path)?;
+        assert_eq!(
+            user.hooks.commands(HookName::PostCreate),
+            ["pnpm install", "cp ../.env ."]
+        );
+
+        fs::write(&path, "[hooks]\npost-create = [\"make setup\"]\n")?;
+        let config = Config::read(&path)?.or(user);
+        assert_eq!(config.hooks.commands(HookName::PostCreate), ["make setup"]);
+        assert_eq!(
+            config.hooks.commands(HookName::PreRemove),
+            ["docker compose down"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn read_accepts_multi_line_strings() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join(CONFIG_FILE);
+        fs::write(
+            &path,
+            "branch_pattern = \"\"\"\n{user}/{slug}\"\"\"\nwindow_name = '''{project}:{branch}'''\n",
+        )?;
+
+        let config = Config::read(&path)?;
+        assert_eq!(config.branch_pattern.as_deref(), Some("{user}/{slug}"));
+        assert_eq!(config.window_name.as_deref(), Some("{project}:{branch}"));
+        Ok(())
+    }
+
+    #[test]
+    fn read_reports_the_invalid_file() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join(CONFIG_FILE);
+        fs::write(&path, "editor = \"\"\n")?;
+
+        let error = Config::read(&path).expect_err("empty editor");
+        assert_eq!(error.path, path);
+        assert!(
+            error.message.contains("`editor` must not be empty"),
+            "{error}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn repository_settings_override_user_settings() {
+        let user = Config {
+            provider: Some(GitProvider::GitLab),
+            telemetry: Some(false.into()),
+            reviewers: vec!["alice".into()],
+            ..Config::default()
+        };
+        let repo = Config {
+            provider: Some(GitProvider::Gitea),
+            ..Config::default()
+        };
+
+        let config = repo.or(user);
+        assert_eq!(config.provider, Some(GitProvider::Gitea));
+        assert!(!config.telemetry_enabled());
+        assert_eq!(config.reviewers, ["alice"]);
+        assert!(Config::default().telemetry_enabled());
+    }
+
+    #[test]
+    fn protected_branches_match_names_and_globs() {
+        let config = Config {
+            protected_branches: vec!["main".into(), "release/*".into()],
+            ..Config::default()
+        };
+        assert_eq!(config.protected_pattern("main"), Some("main"));
+        assert_eq!(config.protected_pattern("release/1.2"), Some("release/*"));
+        assert_eq!(config.protected_pattern("release/1.2/hotfix"), None);
+        assert_eq!(config.protected_pattern("feature/main"), None);
+        assert_eq!(Config::default().protected_pattern("main"), None);
+    }
+}
@@ -0,0 +1,128 @@
+//! Falls back to an editor installed on `PATH` when neither `preferences.json`
+//! nor `$EDITOR`/`$VISUAL` names one.
+
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::{EditorKind, EditorPreference, EditorPreferenceSource};
+
+/// Editor commands looked up on `PATH`, most preferred first.
+const DETECTED_EDITORS: [&str; 10] = [
+    "code", "cursor", "nvim", "zed", "subl", "hx", "vim", "webstorm", "rider", "nano",
+];
+
+/// The first editor from the list of known editors that is installed on `PATH`.
+pub fn detect_editor() -> Option<EditorPreference> {
+    let path = env::var_os("PATH")?;
+    detect_in(&env::split_paths(&path).collect::<Vec<_>>())
+}
+
+/// The first of `commands` that is installed on `PATH`.
+pub(crate) fn find_on_path<'a>(commands: &[&'a str]) -> Option<&'a str> {
+    let path = env::var_os("PATH")?;
+    find_in(&env::split_paths(&path).collect::<Vec<_>>(), commands)
+}
+
+/// Where `command` is installed on `PATH`; a command naming a path is kept.
+pub(crate) fn locate(command: &OsStr) -> Option<PathBuf> {
+    let path = Path::new(command);
+    if path.components().count() > 1 {
+        return is_executable_file(path).then(|| path.to_path_buf());
+    }
+    let dirs = env::var_os("PATH")?;
+    locate_in(
+        &env::split_paths(&dirs).collect::<Vec<_>>(),
+        command.to_str()?,
+    )
+}
+
+fn locate_in(dirs: &[PathBuf], command: &str) -> Option<PathBuf> {
+    dirs.iter()
+        .flat_map(|dir| executable_names(command).map(move |name| dir.join(name)))
+        .find(|path| is_executable_file(path))
+}
+
+fn find_in<'a>(dirs: &[PathBuf], commands: &[&'a str]) -> Option<&'a str> {
+    commands
+        .iter()
+        .copied()
+        .find(|command| locate_in(dirs, command).is_some())
+}
+
+fn detect_in(dirs: &[PathBuf]) -> Option<EditorPreference> {
+    let command = find_in(dirs, &DETECTED_EDITORS)?;
+    Some(EditorPreference {
+        command: OsString::from(command),
+        args: Vec::new(),
+        kind: EditorKind::Editor,
+        source: EditorPreferenceSource::Detected,
+        fallbacks: Vec::new(),
+    })
+}
+
+#[cfg(unix)]
+fn executable_names(command: &str) -> impl Iterator<Item = String> {
+    std::iter::once(command.to_owned())
+}
+
+#[cfg(not(unix))]
+fn executable_names(command: &str) -> impl Iterator<Item = String> {
+    ["exe", "cmd", "bat"]
+        .into_iter()
+        .map(move |extension| format!("{command}.{extension}"))
+}
+
+#[cfg(unix)]
+fn is_executable_file(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable_file(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    fn install(dir: &Path, command: &str) -> color_eyre::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join(command);
+        fs::write(&path, "#!/bin/sh\n")?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn detects_most_preferred_editor_on_path() -> color_eyre::Result<()> {
+        let first = TempDir::new()?;
+        let second = TempDir::new()?;
+        install(first.path(), "vim")?;
+        install(second.path(), "nvim")?;
+        fs::write(second.path().join("code"), "not executable")?;
+        let dirs = [first.path().to_path_buf(), second.path().to_path_buf()];
+
+        let preference = detect_in(&dirs).expect("an editor should be detected");
+        assert_eq!(preference.command, OsString::from("nvim"));
+        assert_eq!(preference.source, EditorPreferenceSource::Detected);
+        assert_eq!(locate_in(&dirs, "nvim"), Some(second.path().join("nvim")));
+        assert_eq!(locate_in(&dirs, "code"), None);
+
+        assert_eq!(detect_in(&[TempDir::new()?.path().to_path_buf()]), None);
+
+        Ok(())
+    }
+}
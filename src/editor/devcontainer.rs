@@ -0,0 +1,241 @@
+//! Dev containers: a worktree with a `.devcontainer/devcontainer.json` is
+//! opened inside its container. VS Code and its forks get a `--folder-uri`
+//! pointing into the container, the `devcontainer` CLI runs `devcontainer open`.
+//!
+//! Set `devcontainer = false` in `config.toml` or `.rsworktree/editor.toml`
+//! to open such worktrees on the host.
+
+use std::{
+    ffi::{OsStr, OsString},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+use super::{
+    EditorKind, EditorPreference, find_on_path, preference::load_project_file, url_template,
+};
+use crate::{Repo, output};
+
+/// Where a project keeps its dev container configuration.
+const CONFIG_PATHS: [&str; 2] = [".devcontainer/devcontainer.json", ".devcontainer.json"];
+/// Editors that open folders inside a container through a `vscode-remote://` URI.
+const REMOTE_EDITORS: [&str; 4] = ["code", "code-insiders", "codium", "cursor"];
+
+/// Switches `preference` to [`EditorKind::DevContainer`] when `worktree_path`
+/// has a dev container configuration and the editor can open it, or else
+/// points to the `devcontainer` CLI.
+pub(crate) fn apply(repo: &Repo, preference: &mut EditorPreference, worktree_path: &Path) {
+    if preference.kind != EditorKind::Editor
+        || url_template(&preference.command).is_some()
+        || find_config(worktree_path).is_none()
+        || !enabled(repo)
+    {
+        return;
+    }
+    if is_remote_editor(&preference.command) {
+        output::info(tr!(
+            "Opening `{}` in its dev container; set `devcontainer = false` in `config.toml` to open it on the host.",
+            worktree_path.display()
+        ));
+        preference.kind = EditorKind::DevContainer;
+    } else if let Some(cli) = find_on_path(&["devcontainer"]) {
+        output::note(tr!(
+            "`{}` has a dev container; run `{} open {}` to open it there.",
+            worktree_path.display(),
+            cli,
+            shell_words::quote(&worktree_path.display().to_string())
+        ));
+    }
+}
+
+/// The arguments `command` needs to open `worktree_path` in its container:
+/// `open <path>` for the `devcontainer` CLI, `--folder-uri <uri>` for VS Code
+/// and its forks, and the plain path for any other editor.
+pub(crate) fn open_args(command: &OsStr, worktree_path: &Path) -> Vec<OsString> {
+    let name = Path::new(command)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_ascii_lowercase());
+    match name.as_deref() {
+        Some("devcontainer") => vec!["open".into(), worktree_path.into()],
+        _ if is_remote_editor(command) => {
+            vec!["--folder-uri".into(), folder_uri(worktree_path).into()]
+        }
+        _ => vec![worktree_path.into()],
+    }
+}
+
+/// The dev container configuration of the project at `path`, if it has one.
+fn find_config(path: &Path) -> Option<PathBuf> {
+    CONFIG_PATHS
+        .iter()
+        .map(|config| path.join(config))
+        .find(|config| config.is_file())
+}
+
+/// Whether worktrees are opened in their container: `devcontainer` in
+/// `config.toml` wins over the one in `.rsworktree/editor.toml`.
+fn enabled(repo: &Repo) -> bool {
+    repo.config()
+        .ok()
+        .and_then(|config| config.devcontainer)
+        .or_else(|| {
+            load_project_file(repo)
+                .ok()
+                .flatten()
+                .and_then(|file| file.devcontainer)
+        })
+        .unwrap_or(true)
+}
+
+fn is_remote_editor(command: &OsStr) -> bool {
+    Path::new(command)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| REMOTE_EDITORS.contains(&stem.to_ascii_lowercase().as_str()))
+}
+
+/// `vscode-remote://dev-container+<hex of the host path><folder in the container>`.
+fn folder_uri(worktree_path: &Path) -> String {
+    let host = worktree_path.display().to_string();
+    let hex = host
+        .bytes()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!(
+        "vscode-remote://dev-container+{hex}{}",
+        workspace_folder(worktree_path)
+    )
+}
+
+/// `workspaceFolder` from the configuration, `/workspaces/<folder name>` by default.
+fn workspace_folder(worktree_path: &Path) -> String {
+    let basename = worktree_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    find_config(worktree_path)
+        .and_then(|config| fs::read_to_string(config).ok())
+        .and_then(|text| serde_json::from_str::<Value>(&strip_jsonc(&text)).ok())
+        .and_then(|config| config["workspaceFolder"].as_str().map(str::to_owned))
+        .map(|folder| folder.replace("${localWorkspaceFolderBasename}", &basename))
+        .unwrap_or_else(|| format!("/workspaces/{basename}"))
+}
+
+/// Drops the comments and trailing commas `devcontainer.json` allows but JSON does not.
+fn strip_jsonc(text: &str) -> String {
+    let mut json = String::with_capacity(text.len());
+    // Where the last comma outside a string was written, until more than
+    // whitespace follows it.
+    let mut comma = None;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('"', _) => {
+                comma = None;
+                json.push(ch);
+                while let Some(ch) = chars.next() {
+                    json.push(ch);
+                    match ch {
+                        '\\' => json.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            ('/', Some('/')) => while chars.next_if(|&next| next != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            ('}' | ']', _) => {
+                if let Some(position) = comma.take() {
+                    json.remove(position);
+                }
+                json.push(ch);
+            }
+            (',', _) => {
+                comma = Some(json.len());
+                json.push(ch);
+            }
+            _ => {
+                if !ch.is_whitespace() {
+                    comma = None;
+                }
+                json.push(ch);
+            }
+        }
+    }
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn strips_comments_and_trailing_commas() {
+        let text = r#"{
+            // The image
+            "image": "rust:1", /* "old": 1, */
+            "url": "http://example.com",
+            "features": { "a": 1, },
+        }"#;
+        let value: Value = serde_json::from_str(&strip_jsonc(text)).expect("valid JSON");
+        assert_eq!(value["image"], "rust:1");
+        assert_eq!(value["url"], "http://example.com");
+        assert_eq!(value["features"]["a"], 1);
+    }
+
+    #[test]
+    fn opens_the_workspace_folder_inside_the_container() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let worktree = dir.path().join("feature");
+        fs::create_dir_all(worktree.join(".devcontainer"))?;
+        let hex = worktree
+            .display()
+            .to_string()
+            .bytes()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+
+        fs::write(
+            worktree.join(".devcontainer/devcontainer.json"),
+            "{ \"image\": \"rust:1\" }",
+        )?;
+        assert_eq!(
+            open_args(OsStr::new("/usr/bin/code"), &worktree),
+            [
+                OsString::from("--folder-uri"),
+                format!("vscode-remote://dev-container+{hex}/workspaces/feature").into()
+            ]
+        );
+
+        fs::write(
+            worktree.join(".devcontainer/devcontainer.json"),
+            "{ // JSONC\n \"workspaceFolder\": \"/src/${localWorkspaceFolderBasename}\", }",
+        )?;
+        assert_eq!(
+            folder_uri(&worktree),
+            format!("vscode-remote://dev-container+{hex}/src/feature")
+        );
+        assert_eq!(
+            open_args(OsStr::new("devcontainer"), &worktree),
+            [OsString::from("open"), worktree.clone().into_os_string()]
+        );
+        assert_eq!(
+            open_args(OsStr::new("vim"), &worktree),
+            [worktree.into_os_string()]
+        );
+        Ok(())
+    }
+}
@@ -1,14 +1,36 @@
-use std::{ffi::OsStr, io, path::Path, process::{Command, Stdio}};
+use std::{
+    ffi::{OsStr, OsString},
+    io,
+    path::Path,
+    process::Command,
+};
 
-use crate::telemetry::EditorLaunchStatus;
+use crate::{
+    process::{CommandRunner, SystemCommandRunner, exit_status},
+    telemetry::{EditorLaunchMethod, EditorLaunchStatus},
+};
 
-use super::EditorPreference;
+use super::{EditorKind, EditorPreference, devcontainer};
 
 pub struct LaunchRequest<'a> {
     pub preference: &'a EditorPreference,
     pub worktree_name: &'a str,
     pub worktree_path: &'a Path,
-    pub wait_for_completion: bool,
+    pub wait: LaunchWait,
+    /// Extra variables for the editor process, such as the project environment.
+    pub env: &'a [(String, String)],
+}
+
+/// When [`launch_editor`] returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchWait {
+    /// Right after starting the editor, detached from the terminal.
+    Detached,
+    /// Once the editor process exits, as for terminal editors in interactive mode.
+    Process,
+    /// Once the editor is closed: GUI editors also get their wait flag, such
+    /// as `code --wait`, so their process does not return right away.
+    Editor,
 }
 
 #[derive(Debug, Clone)]
@@ -18,10 +40,24 @@ pub struct LaunchOutcome {
 }
 
 pub fn launch_editor(request: LaunchRequest<'_>) -> LaunchOutcome {
+    launch_editor_with(request, &mut SystemCommandRunner)
+}
+
+/// Like [`launch_editor`], but starts the editor through `runner`.
+pub fn launch_editor_with(
+    request: LaunchRequest<'_>,
+    runner: &mut impl CommandRunner,
+) -> LaunchOutcome {
+    let _span = tracing::info_span!(
+        "editor",
+        command = %request.preference.command.to_string_lossy(),
+        worktree = request.worktree_name
+    )
+    .entered();
     if !request.worktree_path.exists() {
         return LaunchOutcome {
             status: EditorLaunchStatus::InvalidWorktreePath,
-            message: format!(
+            message: tr!(
                 "Worktree `{}` no longer exists at `{}`. Run `rsworktree worktree ls` or restart interactive mode to refresh the list.",
                 request.worktree_name,
                 request.worktree_path.display()
@@ -29,89 +65,227 @@ pub fn launch_editor(request: LaunchRequest<'_>) -> LaunchOutcome {
         };
     }
 
-    let mut command = Command::new(&request.preference.command);
-    command.args(&request.preference.args);
-    command.arg(request.worktree_path);
+    if request.preference.kind == EditorKind::Terminal && request.preference.command.is_empty() {
+        return LaunchOutcome {
+            status: EditorLaunchStatus::Success,
+            message: tr!(
+                "Run `cd {}` to enter `{}`.",
+                shell_words::quote(&request.worktree_path.display().to_string()),
+                request.worktree_name
+            ),
+        };
+    }
 
-    if request.wait_for_completion {
-        // For interactive mode: wait for editor to complete
-        match command.status() {
-            Ok(status) => {
-                if status.success() {
-                    LaunchOutcome {
-                        status: EditorLaunchStatus::Success,
-                        message: format!(
-                            "Launched `{}` using `{}`",
-                            request.worktree_name,
-                            format_command(&request.preference.command)
-                        ),
-                    }
-                } else {
-                    LaunchOutcome {
-                        status: EditorLaunchStatus::SpawnError,
-                        message: format!(
-                            "Editor `{}` exited with status: {}",
-                            format_command(&request.preference.command),
-                            status
-                        ),
-                    }
+    let template = url_template(&request.preference.command)
+        .filter(|_| request.preference.kind == EditorKind::Editor);
+    let (mut command, program) = match template {
+        // Remote setups such as VS Code Server only register URL handlers,
+        // so the URL is handed to the OS opener.
+        Some(template) => {
+            let (opener, opener_args) = url_opener();
+            let mut command = Command::new(opener);
+            command.args(opener_args).arg(expand_url(
+                template,
+                request.worktree_name,
+                request.worktree_path,
+            ));
+            (command, OsStr::new(opener))
+        }
+        None => {
+            let mut command = Command::new(resolve_program(&request.preference.command));
+            command.args(&request.preference.args);
+            if request.wait == LaunchWait::Editor
+                && let Some(flag) = wait_flag(request.preference)
+            {
+                command.arg(flag);
+            }
+            match request.preference.kind {
+                EditorKind::Editor => {
+                    command.arg(request.worktree_path);
+                }
+                // Terminal emulators start their shell in the directory they are started from.
+                EditorKind::Terminal => {
+                    command.current_dir(request.worktree_path);
+                }
+                EditorKind::DevContainer => {
+                    command.args(devcontainer::open_args(
+                        &request.preference.command,
+                        request.worktree_path,
+                    ));
                 }
             }
-            Err(error) => match error.kind() {
-                io::ErrorKind::NotFound => LaunchOutcome {
-                    status: EditorLaunchStatus::EditorMissing,
-                    message: format!(
-                        "Editor command `{}` was not found on PATH. Install the editor or update the configured command.",
-                        format_command(&request.preference.command)
-                    ),
-                },
-                _ => LaunchOutcome {
-                    status: EditorLaunchStatus::SpawnError,
-                    message: format!(
-                        "Failed to launch `{}` via `{}`: {}",
-                        request.worktree_name,
-                        format_command(&request.preference.command),
-                        error
-                    ),
-                },
-            },
+            (command, request.preference.command.as_os_str())
         }
+    };
+    command.envs(request.env.iter().map(|(key, value)| (key, value)));
+
+    let launched = if request.wait != LaunchWait::Detached {
+        // For interactive mode: wait for editor to complete
+        runner.status(&mut command).map(Some)
     } else {
         // For non-interactive mode: spawn in background
-        // Detach stdio to prevent blocking parent process
-        command.stdin(Stdio::null());
-        command.stdout(Stdio::null());
-        command.stderr(Stdio::null());
-
-        match command.spawn() {
-            Ok(_) => LaunchOutcome {
-                status: EditorLaunchStatus::Success,
-                message: format!(
-                    "Launched `{}` using `{}`",
-                    request.worktree_name,
-                    format_command(&request.preference.command)
+        runner.spawn(&mut command).map(|()| None)
+    };
+    match launched {
+        Ok(Some(output)) if !output.success => LaunchOutcome {
+            status: EditorLaunchStatus::SpawnError,
+            message: tr!(
+                "Editor `{}` exited with status: {}",
+                format_command(program),
+                exit_status(&output)
+            ),
+        },
+        Ok(_) => LaunchOutcome {
+            status: EditorLaunchStatus::Success,
+            message: tr!(
+                "Launched `{}` using `{}`",
+                request.worktree_name,
+                format_command(program)
+            ),
+        },
+        Err(error) => match error.kind() {
+            io::ErrorKind::NotFound => LaunchOutcome {
+                status: EditorLaunchStatus::EditorMissing,
+                message: tr!(
+                    "Editor command `{}` was not found on PATH. Install the editor or update the configured command.",
+                    format_command(program)
                 ),
             },
-            Err(error) => match error.kind() {
-                io::ErrorKind::NotFound => LaunchOutcome {
-                    status: EditorLaunchStatus::EditorMissing,
-                    message: format!(
-                        "Editor command `{}` was not found on PATH. Install the editor or update the configured command.",
-                        format_command(&request.preference.command)
-                    ),
-                },
-                _ => LaunchOutcome {
-                    status: EditorLaunchStatus::SpawnError,
-                    message: format!(
-                        "Failed to launch `{}` via `{}`: {}",
-                        request.worktree_name,
-                        format_command(&request.preference.command),
-                        error
-                    ),
-                },
+            _ => LaunchOutcome {
+                status: EditorLaunchStatus::SpawnError,
+                message: tr!(
+                    "Failed to launch `{}` via `{}`: {}",
+                    request.worktree_name,
+                    format_command(program),
+                    error
+                ),
             },
+        },
+    }
+}
+
+/// How [`launch_editor`] starts `preference`, and the program it runs.
+pub(crate) fn launch_program(preference: &EditorPreference) -> (EditorLaunchMethod, OsString) {
+    if preference.kind == EditorKind::Editor && url_template(&preference.command).is_some() {
+        (EditorLaunchMethod::Url, OsString::from(url_opener().0))
+    } else {
+        (EditorLaunchMethod::Direct, preference.command.clone())
+    }
+}
+
+/// The flag that makes a GUI editor block until its window is closed, unless
+/// the configured arguments already contain it. Terminal editors block anyway.
+pub(crate) fn wait_flag(preference: &EditorPreference) -> Option<&'static str> {
+    if preference.kind != EditorKind::Editor {
+        return None;
+    }
+    let name = Path::new(&preference.command).file_stem()?.to_str()?;
+    let flag = match name.to_ascii_lowercase().as_str() {
+        "code" | "code-insiders" | "codium" | "cursor" | "windsurf" | "zed" | "idea"
+        | "webstorm" | "rider" | "pycharm" | "goland" | "clion" | "phpstorm" | "rubymine" => {
+            "--wait"
         }
+        "subl" | "mate" => "-w",
+        _ => return None,
+    };
+    (!preference.args.iter().any(|arg| arg == flag)).then_some(flag)
+}
+
+/// The URL template of an editor `command` such as `vscode://file/{path}`.
+pub(crate) fn url_template(command: &OsStr) -> Option<&str> {
+    let command = command.to_str()?;
+    let (scheme, _) = command.split_once("://")?;
+    let mut chars = scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some(command)
+}
+
+/// Fills `{path}` with the percent-encoded worktree path and `{name}` with
+/// the worktree name. `vscode://file/{path}` does not double the leading `/`.
+fn expand_url(template: &str, worktree_name: &str, worktree_path: &Path) -> String {
+    let path = percent_encode(&worktree_path.to_string_lossy().replace('\\', "/"), "/:");
+    template
+        .replace("/{path}", &format!("/{}", path.trim_start_matches('/')))
+        .replace("{path}", &path)
+        .replace("{name}", &percent_encode(worktree_name, "/"))
+}
+
+/// Encodes every byte except unreserved URL characters and those in `keep`.
+fn percent_encode(text: &str, keep: &str) -> String {
+    text.bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric()
+                || b"-._~".contains(&byte)
+                || keep.as_bytes().contains(&byte)
+            {
+                char::from(byte).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect()
+}
+
+/// The program, and its leading arguments, that opens a URL with its
+/// registered handler.
+fn url_opener() -> (&'static str, &'static [&'static str]) {
+    if cfg!(target_os = "macos") {
+        ("open", &[])
+    } else if cfg!(windows) {
+        // Unlike `cmd /C start`, this leaves `&` in the URL alone.
+        ("rundll32", &["url.dll,FileProtocolHandler"])
+    } else {
+        ("xdg-open", &[])
+    }
+}
+
+/// Extensions of files Windows starts directly, in `PATHEXT` order.
+#[cfg(any(windows, test))]
+const WINDOWS_EXECUTABLE_EXTENSIONS: [&str; 4] = ["com", "exe", "bat", "cmd"];
+
+/// The program to start for the editor `command`.
+///
+/// On Windows, `Command` only appends `.exe` to bare names, so editors
+/// installed as `code.cmd` are resolved with `where` first.
+#[cfg(windows)]
+pub(crate) fn resolve_program(command: &OsStr) -> std::ffi::OsString {
+    let path = Path::new(command);
+    if path.extension().is_some() || path.components().count() > 1 {
+        return command.to_owned();
     }
+    Command::new("where")
+        .arg(command)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| first_executable_match(&String::from_utf8_lossy(&output.stdout)))
+        .map(std::ffi::OsString::from)
+        .unwrap_or_else(|| command.to_owned())
+}
+
+#[cfg(not(windows))]
+pub(crate) fn resolve_program(command: &OsStr) -> std::ffi::OsString {
+    command.to_owned()
+}
+
+/// The first path listed by `where` that Windows can start; `where` also
+/// lists extensionless shell scripts such as VS Code's `bin/code`.
+#[cfg(any(windows, test))]
+fn first_executable_match(stdout: &str) -> Option<std::path::PathBuf> {
+    stdout
+        .lines()
+        .map(|line| Path::new(line.trim()))
+        .find(|path| {
+            path.extension()
+                .and_then(OsStr::to_str)
+                .is_some_and(|extension| {
+                    WINDOWS_EXECUTABLE_EXTENSIONS
+                        .iter()
+                        .any(|known| extension.eq_ignore_ascii_case(known))
+                })
+        })
+        .map(Path::to_path_buf)
 }
 
 fn format_command(command: &OsStr) -> String {
@@ -124,7 +298,79 @@ mod tests {
     use std::ffi::OsString;
     use tempfile::TempDir;
 
-    use crate::editor::EditorPreference;
+    use crate::{
+        editor::EditorPreference,
+        process::{CommandOutput, ScriptedRunner},
+    };
+
+    #[test]
+    fn where_output_resolves_to_startable_file() {
+        let stdout = "C:\\VSCode\\bin\\code\r\nC:\\VSCode\\bin\\code.CMD\r\n";
+        assert_eq!(
+            first_executable_match(stdout),
+            Some(std::path::PathBuf::from("C:\\VSCode\\bin\\code.CMD"))
+        );
+        assert_eq!(first_executable_match("C:\\tools\\vim\n"), None);
+    }
+
+    #[test]
+    fn url_templates_are_recognized_by_their_scheme() {
+        assert_eq!(
+            url_template(OsStr::new("vscode://file/{path}")),
+            Some("vscode://file/{path}")
+        );
+        assert!(
+            url_template(OsStr::new(
+                "jetbrains://idea/navigate/reference?path={path}"
+            ))
+            .is_some()
+        );
+        assert_eq!(url_template(OsStr::new("code")), None);
+        assert_eq!(url_template(OsStr::new("C:\\Tools\\vim.exe")), None);
+        assert_eq!(url_template(OsStr::new("://missing-scheme")), None);
+    }
+
+    #[test]
+    fn expand_url_encodes_the_worktree_path() {
+        let path = Path::new("/home/me/my repo/.rsworktree/feature");
+        assert_eq!(
+            expand_url("vscode://file/{path}", "feature", path),
+            "vscode://file/home/me/my%20repo/.rsworktree/feature"
+        );
+        assert_eq!(
+            expand_url(
+                "vscode://vscode-remote/ssh-remote+devbox{path}?windowId=_blank",
+                "feature",
+                path
+            ),
+            "vscode://vscode-remote/ssh-remote+devbox/home/me/my%20repo/.rsworktree/feature?windowId=_blank"
+        );
+        assert_eq!(
+            expand_url(
+                "jetbrains://idea/navigate/reference?project={name}",
+                "feature/login",
+                path
+            ),
+            "jetbrains://idea/navigate/reference?project=feature/login"
+        );
+    }
+
+    #[test]
+    fn wait_flag_depends_on_the_editor() {
+        let editor = |command: &str, args: &[&str]| EditorPreference {
+            command: OsString::from(command),
+            args: args.iter().map(OsString::from).collect(),
+            kind: crate::editor::EditorKind::Editor,
+            source: crate::editor::EditorPreferenceSource::Environment {
+                variable: crate::editor::EditorEnvVar::Editor,
+            },
+            fallbacks: Vec::new(),
+        };
+        assert_eq!(wait_flag(&editor("code", &[])), Some("--wait"));
+        assert_eq!(wait_flag(&editor("/usr/local/bin/subl", &[])), Some("-w"));
+        assert_eq!(wait_flag(&editor("code", &["--wait"])), None);
+        assert_eq!(wait_flag(&editor("nvim", &[])), None);
+    }
 
     #[test]
     fn reports_missing_worktree_path() {
@@ -132,13 +378,16 @@ mod tests {
             preference: &EditorPreference {
                 command: OsString::from("vim"),
                 args: Vec::new(),
+                kind: crate::editor::EditorKind::Editor,
                 source: crate::editor::EditorPreferenceSource::Environment {
                     variable: crate::editor::EditorEnvVar::Editor,
                 },
+                fallbacks: Vec::new(),
             },
             worktree_name: "feature",
             worktree_path: Path::new("/nonexistent/path"),
-            wait_for_completion: false,
+            wait: LaunchWait::Detached,
+            env: &[],
         };
 
         let outcome = launch_editor(request);
@@ -153,13 +402,16 @@ mod tests {
             preference: &EditorPreference {
                 command: OsString::from("unlikely-editor-command"),
                 args: Vec::new(),
+                kind: crate::editor::EditorKind::Editor,
                 source: crate::editor::EditorPreferenceSource::Environment {
                     variable: crate::editor::EditorEnvVar::Editor,
                 },
+                fallbacks: Vec::new(),
             },
             worktree_name: "feature",
             worktree_path,
-            wait_for_completion: false,
+            wait: LaunchWait::Detached,
+            env: &[],
         };
 
         let outcome = launch_editor(request);
@@ -174,13 +426,16 @@ mod tests {
             preference: &EditorPreference {
                 command: OsString::from("true"),
                 args: Vec::new(),
+                kind: crate::editor::EditorKind::Editor,
                 source: crate::editor::EditorPreferenceSource::Environment {
                     variable: crate::editor::EditorEnvVar::Editor,
                 },
+                fallbacks: Vec::new(),
             },
             worktree_name: "feature",
             worktree_path,
-            wait_for_completion: true,
+            wait: LaunchWait::Process,
+            env: &[],
         };
 
         let outcome = launch_editor(request);
@@ -195,13 +450,16 @@ mod tests {
             preference: &EditorPreference {
                 command: OsString::from("false"),
                 args: Vec::new(),
+                kind: crate::editor::EditorKind::Editor,
                 source: crate::editor::EditorPreferenceSource::Environment {
                     variable: crate::editor::EditorEnvVar::Editor,
                 },
+                fallbacks: Vec::new(),
             },
             worktree_name: "feature",
             worktree_path,
-            wait_for_completion: true,
+            wait: LaunchWait::Process,
+            env: &[],
         };
 
         let outcome = launch_editor(request);
@@ -209,6 +467,47 @@ mod tests {
         assert!(outcome.message.contains("exited with status"));
     }
 
+    #[test]
+    fn launches_through_the_runner() {
+        let dir = TempDir::new().expect("tempdir");
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::failure(2, ""))
+            .push(CommandOutput::success(""));
+        let preference = EditorPreference {
+            command: OsString::from("code"),
+            args: vec![OsString::from("--new-window")],
+            kind: crate::editor::EditorKind::Editor,
+            source: crate::editor::EditorPreferenceSource::Environment {
+                variable: crate::editor::EditorEnvVar::Editor,
+            },
+            fallbacks: Vec::new(),
+        };
+        let request = |wait| LaunchRequest {
+            preference: &preference,
+            worktree_name: "feature",
+            worktree_path: dir.path(),
+            wait,
+            env: &[],
+        };
+
+        let outcome = launch_editor_with(request(LaunchWait::Editor), &mut runner.clone());
+        assert_eq!(outcome.status, EditorLaunchStatus::SpawnError);
+        assert!(
+            outcome.message.contains("exit status 2"),
+            "{}",
+            outcome.message
+        );
+        let outcome = launch_editor_with(request(LaunchWait::Detached), &mut runner.clone());
+        assert_eq!(outcome.status, EditorLaunchStatus::Success);
+
+        let path = dir.path().display().to_string();
+        let calls = runner.calls();
+        assert_eq!(calls[0].program, "code");
+        assert_eq!(calls[0].args, ["--new-window", "--wait", path.as_str()]);
+        assert_eq!(calls[1].args, ["--new-window", path.as_str()]);
+    }
+
     #[test]
     fn wait_for_completion_reports_missing_command() {
         let dir = TempDir::new().expect("tempdir");
@@ -217,13 +516,16 @@ mod tests {
             preference: &EditorPreference {
                 command: OsString::from("unlikely-editor-command"),
                 args: Vec::new(),
+                kind: crate::editor::EditorKind::Editor,
                 source: crate::editor::EditorPreferenceSource::Environment {
                     variable: crate::editor::EditorEnvVar::Editor,
                 },
+                fallbacks: Vec::new(),
             },
             worktree_name: "feature",
             worktree_path,
-            wait_for_completion: true,
+            wait: LaunchWait::Process,
+            env: &[],
         };
 
         let outcome = launch_editor(request);
@@ -1,40 +1,260 @@
+mod detect;
+mod devcontainer;
 mod launch;
 mod preference;
 mod support;
+mod version;
 
-use std::path::Path;
+use std::{
+    io::{self, IsTerminal},
+    mem,
+    path::Path,
+    time::Instant,
+};
+
+use color_eyre::eyre::WrapErr;
+
+use crate::{
+    Repo,
+    hooks::{HookContext, ProjectEnv},
+    output,
+    process::{CommandRunner, SystemCommandRunner},
+    telemetry::{EditorLaunchMethod, EditorLaunchStatus, Event, EventStore, elapsed_ms},
+};
 
-use crate::{Repo, telemetry::EditorLaunchStatus};
+pub use detect::detect_editor;
+pub(crate) use detect::{find_on_path, locate};
+pub(crate) use devcontainer::{apply as apply_devcontainer, open_args as devcontainer_args};
+pub(crate) use launch::{launch_program, resolve_program, url_template, wait_flag};
+pub(crate) use preference::save_editor_preference;
 
-pub use launch::{LaunchOutcome, LaunchRequest, launch_editor};
+pub use launch::{LaunchOutcome, LaunchRequest, LaunchWait, launch_editor, launch_editor_with};
 pub use preference::{
-    CONFIG_FILE_NAME, EditorEnvVar, EditorPreference, EditorPreferenceResolution,
-    EditorPreferenceSource, PreferenceMissingReason, resolve_editor_preference,
-    resolve_provider_preference,
+    CONFIG_FILE_NAME, EditorEnvVar, EditorKind, EditorPreference, EditorPreferenceResolution,
+    EditorPreferenceSource, PROJECT_FILE_NAME, PreferenceMissingReason, resolve_editor_preference,
+    resolve_provider_preference, resolve_worktree_editor_preference,
 };
 
 pub use support::{SupportedEditor, supported_editor_commands};
 
+/// Resolves the editor for `worktree` like [`resolve_worktree_editor_preference`],
+/// falling back to an editor found on `PATH` when none is configured. In a
+/// terminal, the user is offered to save the detected editor to `preferences.json`.
+pub fn resolve_editor(
+    repo: &Repo,
+    worktree: &str,
+) -> color_eyre::Result<EditorPreferenceResolution> {
+    let resolution = resolve_worktree_editor_preference(repo, worktree)?;
+    if resolution != EditorPreferenceResolution::Missing(PreferenceMissingReason::NotConfigured) {
+        return Ok(resolution);
+    }
+    let Some(preference) = detect_editor() else {
+        return Ok(resolution);
+    };
+
+    output::info(tr!(
+        "No editor configured; using `{}` found on PATH.",
+        preference.command.to_string_lossy()
+    ));
+    if io::stdin().is_terminal() && io::stdout().is_terminal() {
+        offer_to_save(repo, &preference)?;
+    }
+    Ok(EditorPreferenceResolution::Found(preference))
+}
+
+fn offer_to_save(repo: &Repo, preference: &EditorPreference) -> color_eyre::Result<()> {
+    let command = preference.command.to_string_lossy();
+    output::prompt(tr!(
+        "Save `{}` as the editor in `.rsworktree/{}`? [y/N] ",
+        command,
+        CONFIG_FILE_NAME
+    ))?;
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .wrap_err("failed to read user input")?;
+    if !matches!(answer.trim(), "y" | "Y" | "yes" | "Yes" | "YES") {
+        return Ok(());
+    }
+
+    let path = save_editor_preference(&repo.ensure_rsworktree_dir()?, None, preference)?;
+    output::success(tr!(
+        "Saved `{}` as the editor in `{}`.",
+        command,
+        path.display()
+    ));
+    Ok(())
+}
+
 pub fn launch_worktree(
     repo: &Repo,
     worktree_name: &str,
     worktree_path: &Path,
-    wait_for_completion: bool,
+    wait: LaunchWait,
 ) -> color_eyre::Result<LaunchOutcome> {
-    let resolution = resolve_editor_preference(repo)?;
-    let outcome = match resolution {
-        EditorPreferenceResolution::Found(preference) => launch_editor(LaunchRequest {
-            preference: &preference,
+    launch_worktree_with(
+        repo,
+        worktree_name,
+        worktree_path,
+        wait,
+        &mut SystemCommandRunner,
+    )
+}
+
+/// Like [`launch_worktree`], but starts the editor through `runner`.
+pub fn launch_worktree_with(
+    repo: &Repo,
+    worktree_name: &str,
+    worktree_path: &Path,
+    wait: LaunchWait,
+    runner: &mut impl CommandRunner,
+) -> color_eyre::Result<LaunchOutcome> {
+    let rsworktree_dir = repo.rsworktree_dir();
+    let events = EventStore::new(&rsworktree_dir);
+    let resolution = match resolve_editor(repo, worktree_name) {
+        Ok(resolution) => resolution,
+        Err(error) => {
+            events.record(Event::EditorLaunch {
+                worktree: worktree_name.to_owned(),
+                editor: String::from("none"),
+                status: EditorLaunchStatus::ConfigurationError,
+                path: Some(worktree_path.to_path_buf()),
+                message: error.to_string(),
+                program: None,
+                version: None,
+                duration_ms: None,
+                method: None,
+            });
+            return Err(error);
+        }
+    };
+    let project_env = ProjectEnv::load(&rsworktree_dir);
+    let env = if project_env.is_empty() {
+        Vec::new()
+    } else {
+        let context = HookContext::for_worktree(
+            repo.git(),
+            &repo.worktrees_dir(),
             worktree_name,
             worktree_path,
-            wait_for_completion,
-        }),
-        EditorPreferenceResolution::Missing(reason) => missing_preference_outcome(reason),
+        );
+        project_env.resolve(&context)
+    };
+    let mut preference = match resolution {
+        EditorPreferenceResolution::Found(preference) => preference,
+        EditorPreferenceResolution::Missing(reason) => {
+            let outcome = missing_preference_outcome(reason);
+            events.record(Event::EditorLaunch {
+                worktree: worktree_name.to_owned(),
+                editor: String::from("none"),
+                status: outcome.status,
+                path: Some(worktree_path.to_path_buf()),
+                message: outcome.message.clone(),
+                program: None,
+                version: None,
+                duration_ms: None,
+                method: None,
+            });
+            return Ok(outcome);
+        }
     };
 
+    let fallbacks = mem::take(&mut preference.fallbacks);
+    let mut launch = |mut preference: EditorPreference| {
+        devcontainer::apply(repo, &mut preference, worktree_path);
+        let (method, _) = launch_program(&preference);
+        record_launch(
+            repo,
+            worktree_name,
+            worktree_path,
+            &preference,
+            method,
+            || {
+                launch_editor_with(
+                    LaunchRequest {
+                        preference: &preference,
+                        worktree_name,
+                        worktree_path,
+                        wait,
+                        env: &env,
+                    },
+                    runner,
+                )
+            },
+        )
+    };
+
+    // Each attempt is recorded, so the events show which editor opened it.
+    let mut outcome = launch(preference);
+    for fallback in fallbacks {
+        if !matches!(
+            outcome.status,
+            EditorLaunchStatus::EditorMissing | EditorLaunchStatus::SpawnError
+        ) {
+            break;
+        }
+        output::warn(&outcome.message);
+        output::info(tr!(
+            "Trying `{}`, the next editor in `editors`.",
+            fallback.command.to_string_lossy()
+        ));
+        outcome = launch(fallback);
+    }
     Ok(outcome)
 }
 
+/// Runs `launch`, which starts `preference` by `method`, and records the
+/// outcome together with the program, its version and how long it took.
+pub(crate) fn record_launch(
+    repo: &Repo,
+    worktree_name: &str,
+    worktree_path: &Path,
+    preference: &EditorPreference,
+    method: EditorLaunchMethod,
+    launch: impl FnOnce() -> LaunchOutcome,
+) -> LaunchOutcome {
+    let events = EventStore::new(&repo.rsworktree_dir());
+    if !events.is_enabled() {
+        return launch();
+    }
+
+    let program = match method {
+        EditorLaunchMethod::Url => launch_program(preference).1,
+        _ => preference.command.clone(),
+    };
+    let program = detect::locate(&program);
+    let version = program.clone().and_then(version::probe_version);
+    let started = Instant::now();
+    let outcome = launch();
+    let duration_ms = elapsed_ms(started);
+
+    events.record(Event::EditorLaunch {
+        worktree: worktree_name.to_owned(),
+        editor: editor_label(&preference.command),
+        status: outcome.status,
+        path: Some(worktree_path.to_path_buf()),
+        message: outcome.message.clone(),
+        program,
+        version: version.and_then(|probe| probe.join().ok().flatten()),
+        duration_ms: Some(duration_ms),
+        method: Some(method),
+    });
+    outcome
+}
+
+/// Editor name for stats: the command's file name without any directory, or
+/// the scheme of a URL template.
+fn editor_label(command: &std::ffi::OsStr) -> String {
+    if let Some((scheme, _)) = url_template(command).and_then(|url| url.split_once("://")) {
+        return scheme.to_owned();
+    }
+    Path::new(command)
+        .file_name()
+        .unwrap_or(command)
+        .to_string_lossy()
+        .into_owned()
+}
+
 fn missing_preference_outcome(reason: PreferenceMissingReason) -> LaunchOutcome {
     match reason {
         PreferenceMissingReason::NotConfigured => {
@@ -44,7 +264,7 @@ fn missing_preference_outcome(reason: PreferenceMissingReason) -> LaunchOutcome
                 .join(", ");
             LaunchOutcome {
                 status: EditorLaunchStatus::PreferenceMissing,
-                message: format!(
+                message: tr!(
                     "No editor configured. Set one in `.rsworktree/{}` or export $EDITOR/$VISUAL. Supported commands: {}",
                     CONFIG_FILE_NAME, supported
                 ),
@@ -52,7 +272,7 @@ fn missing_preference_outcome(reason: PreferenceMissingReason) -> LaunchOutcome
         }
         PreferenceMissingReason::ConfigInvalid { path, error } => LaunchOutcome {
             status: EditorLaunchStatus::ConfigurationError,
-            message: format!(
+            message: tr!(
                 "Editor configuration `{}` is invalid: {}",
                 path.display(),
                 error
@@ -60,7 +280,7 @@ fn missing_preference_outcome(reason: PreferenceMissingReason) -> LaunchOutcome
         },
         PreferenceMissingReason::EnvInvalid { variable, error } => LaunchOutcome {
             status: EditorLaunchStatus::ConfigurationError,
-            message: format!(
+            message: tr!(
                 "Environment variable ${} could not be parsed: {}",
                 variable.name(),
                 error
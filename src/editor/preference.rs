@@ -1,15 +1,24 @@
 use std::{
+    collections::BTreeMap,
     env,
     ffi::OsString,
     fs,
     path::{Path, PathBuf},
 };
 
+use color_eyre::eyre::{self, WrapErr};
 use serde::Deserialize;
+use serde_json::Value;
 
-use crate::{GitProvider, Repo};
+use super::{detect::locate, url_template};
+use crate::{
+    GitProvider, Repo,
+    config::{Config, EditorConfig},
+};
 
 pub const CONFIG_FILE_NAME: &str = "preferences.json";
+/// The project's editor settings in `.rsworktree`, meant to be checked in.
+pub const PROJECT_FILE_NAME: &str = "editor.toml";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EditorPreferenceResolution {
@@ -19,15 +28,39 @@ pub enum EditorPreferenceResolution {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EditorPreference {
+    /// The editor, or for [`EditorKind::Terminal`] the terminal emulator,
+    /// which may be empty.
     pub command: OsString,
     pub args: Vec<OsString>,
+    pub kind: EditorKind,
     pub source: EditorPreferenceSource,
+    /// Editors tried in order when this one fails to start, from an
+    /// `editors` list.
+    pub fallbacks: Vec<EditorPreference>,
+}
+
+/// What opening a worktree starts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EditorKind {
+    /// The command, with the worktree path appended.
+    #[default]
+    Editor,
+    /// A shell in the worktree: a new multiplexer window, the configured
+    /// terminal emulator started in the worktree, or else a `cd` hint.
+    Terminal,
+    /// The worktree opened inside its dev container, see [`super::devcontainer`].
+    DevContainer,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EditorPreferenceSource {
     ConfigFile(PathBuf),
-    Environment { variable: EditorEnvVar },
+    Environment {
+        variable: EditorEnvVar,
+    },
+    /// Found on `PATH` because nothing was configured.
+    Detected,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -62,19 +95,45 @@ pub enum PreferenceMissingReason {
 struct FileFormat {
     #[serde(default)]
     editor: Option<FileEditorPreference>,
+    /// Editors for single worktrees, keyed by worktree name.
+    #[serde(default)]
+    editors: BTreeMap<String, FileEditorPreference>,
     #[serde(default)]
     provider: Option<GitProvider>,
 }
 
-#[derive(Debug, Deserialize)]
+/// `.rsworktree/editor.toml`, shared by everyone working on the project:
+///
+/// ```toml
+/// command = "code"
+/// args = ["--new-window"]
+/// devcontainer = false
+/// ```
+///
+/// or, for teams using different editors, `editors = ["cursor", "code", "nvim"]`.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ProjectFile {
+    #[serde(flatten)]
+    editor: FileEditorPreference,
+    /// Editors tried in order when `command` is not set.
+    #[serde(default)]
+    editors: Vec<EditorConfig>,
+    /// Whether worktrees with a dev container are opened inside it.
+    pub(crate) devcontainer: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
 struct FileEditorPreference {
+    #[serde(default)]
     command: String,
     #[serde(default)]
     args: Vec<String>,
+    #[serde(default)]
+    kind: EditorKind,
 }
 
 pub fn resolve_editor_preference(repo: &Repo) -> color_eyre::Result<EditorPreferenceResolution> {
-    let config_path = repo.worktrees_dir().join(CONFIG_FILE_NAME);
+    let config_path = repo.rsworktree_dir().join(CONFIG_FILE_NAME);
 
     if config_path.exists() {
         match load_from_config(&config_path) {
@@ -82,7 +141,7 @@ pub fn resolve_editor_preference(repo: &Repo) -> color_eyre::Result<EditorPrefer
                 return Ok(EditorPreferenceResolution::Found(preference));
             }
             Ok(None) => {
-                // Continue to environment fallback.
+                // Continue to the `config.toml` files.
             }
             Err(reason) => {
                 return Ok(EditorPreferenceResolution::Missing(reason));
@@ -90,6 +149,55 @@ pub fn resolve_editor_preference(repo: &Repo) -> color_eyre::Result<EditorPrefer
         }
     }
 
+    match repo.config() {
+        Ok(config) => {
+            if let Some(editor) = config.editor {
+                return Ok(EditorPreferenceResolution::Found(config_preference(editor)));
+            }
+            if let Some(preference) = chain_preference(config.editors) {
+                return Ok(EditorPreferenceResolution::Found(preference));
+            }
+        }
+        Err(error) => {
+            return Ok(EditorPreferenceResolution::Missing(
+                PreferenceMissingReason::ConfigInvalid {
+                    path: error.path,
+                    error: error.message,
+                },
+            ));
+        }
+    }
+
+    match load_project_file(repo) {
+        Ok(Some(file))
+            if !file.editor.command.trim().is_empty() || file.editor.kind != EditorKind::Editor =>
+        {
+            let path = project_file_path(repo);
+            return Ok(match to_preference(file.editor, &path) {
+                Ok(preference) => EditorPreferenceResolution::Found(preference),
+                Err(reason) => EditorPreferenceResolution::Missing(reason),
+            });
+        }
+        Ok(Some(file)) if !file.editors.is_empty() => {
+            let path = project_file_path(repo);
+            let editors = file
+                .editors
+                .into_iter()
+                .map(|editor| EditorConfig {
+                    source: path.clone(),
+                    ..editor
+                })
+                .collect();
+            if let Some(preference) = chain_preference(editors) {
+                return Ok(EditorPreferenceResolution::Found(preference));
+            }
+        }
+        Ok(_) => {
+            // The project sets no editor; continue to the environment.
+        }
+        Err(reason) => return Ok(EditorPreferenceResolution::Missing(reason)),
+    }
+
     for variable in [EditorEnvVar::Editor, EditorEnvVar::Visual] {
         match load_from_env(variable) {
             Ok(Some(preference)) => {
@@ -109,14 +217,94 @@ pub fn resolve_editor_preference(repo: &Repo) -> color_eyre::Result<EditorPrefer
     ))
 }
 
+/// Resolves the editor for `worktree`: its entry in `editors` wins over the
+/// repository-wide preference.
+pub fn resolve_worktree_editor_preference(
+    repo: &Repo,
+    worktree: &str,
+) -> color_eyre::Result<EditorPreferenceResolution> {
+    let config_path = repo.rsworktree_dir().join(CONFIG_FILE_NAME);
+
+    if config_path.exists() {
+        match parse_config(&config_path) {
+            Ok(mut parsed) => {
+                if let Some(editor) = parsed.editors.remove(worktree) {
+                    return Ok(match to_preference(editor, &config_path) {
+                        Ok(preference) => EditorPreferenceResolution::Found(preference),
+                        Err(reason) => EditorPreferenceResolution::Missing(reason),
+                    });
+                }
+            }
+            Err(reason) => return Ok(EditorPreferenceResolution::Missing(reason)),
+        }
+    }
+
+    resolve_editor_preference(repo)
+}
+
+/// Stores `preference` in `preferences.json`, as `editor` or, for a single
+/// worktree, under `editors`, keeping all other settings. Returns the file's path.
+pub(crate) fn save_editor_preference(
+    rsworktree_dir: &Path,
+    worktree: Option<&str>,
+    preference: &EditorPreference,
+) -> color_eyre::Result<PathBuf> {
+    let path = rsworktree_dir.join(CONFIG_FILE_NAME);
+    let mut preferences = match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str::<Value>(&contents)
+            .wrap_err_with(|| eyre::eyre!("failed to parse `{}`", path.display()))?,
+        Err(_) => Value::Object(Default::default()),
+    };
+    let Some(root) = preferences.as_object_mut() else {
+        return Err(eyre::eyre!("`{}` is not a JSON object", path.display()));
+    };
+    let mut editor = serde_json::json!({
+        "command": preference.command.to_string_lossy(),
+        "args": preference
+            .args
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>(),
+    });
+    match preference.kind {
+        EditorKind::Editor => {}
+        EditorKind::Terminal => editor["kind"] = Value::from("terminal"),
+        EditorKind::DevContainer => editor["kind"] = Value::from("devcontainer"),
+    }
+    match worktree {
+        Some(worktree) => {
+            let editors = root
+                .entry("editors")
+                .or_insert_with(|| Value::Object(Default::default()));
+            let Some(editors) = editors.as_object_mut() else {
+                return Err(eyre::eyre!(
+                    "`editors` in `{}` is not a JSON object",
+                    path.display()
+                ));
+            };
+            editors.insert(worktree.to_owned(), editor);
+        }
+        None => {
+            root.insert("editor".into(), editor);
+        }
+    }
+
+    let contents =
+        serde_json::to_string_pretty(&preferences).wrap_err("failed to serialize preferences")?;
+    fs::write(&path, contents + "\n")
+        .wrap_err_with(|| eyre::eyre!("failed to write `{}`", path.display()))?;
+    Ok(path)
+}
+
 /// Resolve the git provider preference.
 ///
 /// Resolution order:
 /// 1. Config file (`preferences.json`)
-/// 2. Environment variable (`RSWORKTREE_PROVIDER`)
-/// 3. Default (GitHub)
+/// 2. `config.toml` of the repository, then of the user
+/// 3. Environment variable (`RSWORKTREE_PROVIDER`)
+/// 4. Default (GitHub)
 pub fn resolve_provider_preference(repo: &Repo) -> color_eyre::Result<GitProvider> {
-    let config_path = repo.worktrees_dir().join(CONFIG_FILE_NAME);
+    let config_path = repo.rsworktree_dir().join(CONFIG_FILE_NAME);
 
     // Try config file first
     if config_path.exists() {
@@ -127,6 +315,15 @@ pub fn resolve_provider_preference(repo: &Repo) -> color_eyre::Result<GitProvide
         }
     }
 
+    // Then the layered `config.toml` files
+    if let Ok(Config {
+        provider: Some(provider),
+        ..
+    }) = repo.config()
+    {
+        return Ok(provider);
+    }
+
     // Try environment variable
     if let Ok(value) = env::var("RSWORKTREE_PROVIDER") {
         if let Ok(provider) = value.parse::<GitProvider>() {
@@ -144,7 +341,71 @@ fn load_provider_from_config(path: &Path) -> color_eyre::Result<Option<GitProvid
     Ok(parsed.provider)
 }
 
+/// Reads `.rsworktree/editor.toml` in the repository root, if it exists.
+pub(crate) fn load_project_file(
+    repo: &Repo,
+) -> Result<Option<ProjectFile>, PreferenceMissingReason> {
+    let path = project_file_path(repo);
+    let invalid = |error: String| PreferenceMissingReason::ConfigInvalid {
+        path: path.clone(),
+        error,
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(invalid(error.to_string())),
+    };
+    toml::from_str(&text)
+        .map(Some)
+        .map_err(|error| invalid(error.to_string()))
+}
+
+/// `.rsworktree/editor.toml`, which can be checked in.
+fn project_file_path(repo: &Repo) -> PathBuf {
+    repo.rsworktree_dir().join(PROJECT_FILE_NAME)
+}
+
+fn config_preference(editor: EditorConfig) -> EditorPreference {
+    EditorPreference {
+        command: OsString::from(editor.command),
+        args: editor.args.into_iter().map(OsString::from).collect(),
+        kind: EditorKind::Editor,
+        source: EditorPreferenceSource::ConfigFile(editor.source),
+        fallbacks: Vec::new(),
+    }
+}
+
+/// The first of `editors` that is installed, with the installed ones after
+/// it as its fallbacks. When none is installed, the first one, so that
+/// launching it reports it missing.
+fn chain_preference(editors: Vec<EditorConfig>) -> Option<EditorPreference> {
+    let installed = |preference: &EditorPreference| {
+        url_template(&preference.command).is_some() || locate(&preference.command).is_some()
+    };
+    let mut candidates = editors
+        .into_iter()
+        .map(config_preference)
+        .collect::<Vec<_>>();
+    let first = candidates.iter().position(installed).unwrap_or_default();
+    let mut candidates = candidates.split_off(first).into_iter();
+    let mut preference = candidates.next()?;
+    preference.fallbacks = candidates
+        .filter(|candidate| installed(candidate))
+        .collect();
+    Some(preference)
+}
+
 fn load_from_config(path: &Path) -> Result<Option<EditorPreference>, PreferenceMissingReason> {
+    let parsed = parse_config(path)?;
+
+    let Some(editor) = parsed.editor else {
+        return Ok(None);
+    };
+
+    to_preference(editor, path).map(Some)
+}
+
+fn parse_config(path: &Path) -> Result<FileFormat, PreferenceMissingReason> {
     let text = match fs::read_to_string(path) {
         Ok(text) => text,
         Err(error) => {
@@ -155,17 +416,17 @@ fn load_from_config(path: &Path) -> Result<Option<EditorPreference>, PreferenceM
         }
     };
 
-    let parsed: FileFormat =
-        serde_json::from_str(&text).map_err(|error| PreferenceMissingReason::ConfigInvalid {
-            path: path.to_path_buf(),
-            error: error.to_string(),
-        })?;
-
-    let Some(editor) = parsed.editor else {
-        return Ok(None);
-    };
+    serde_json::from_str(&text).map_err(|error| PreferenceMissingReason::ConfigInvalid {
+        path: path.to_path_buf(),
+        error: error.to_string(),
+    })
+}
 
-    if editor.command.trim().is_empty() {
+fn to_preference(
+    editor: FileEditorPreference,
+    path: &Path,
+) -> Result<EditorPreference, PreferenceMissingReason> {
+    if editor.command.trim().is_empty() && editor.kind == EditorKind::Editor {
         return Err(PreferenceMissingReason::ConfigInvalid {
             path: path.to_path_buf(),
             error: "`editor.command` must not be empty".to_string(),
@@ -177,11 +438,13 @@ fn load_from_config(path: &Path) -> Result<Option<EditorPreference>, PreferenceM
         args.push(OsString::from(arg));
     }
 
-    Ok(Some(EditorPreference {
-        command: OsString::from(editor.command),
+    Ok(EditorPreference {
+        command: OsString::from(editor.command.trim()),
         args,
+        kind: editor.kind,
         source: EditorPreferenceSource::ConfigFile(path.to_path_buf()),
-    }))
+        fallbacks: Vec::new(),
+    })
 }
 
 fn load_from_env(
@@ -219,7 +482,9 @@ fn load_from_env(
     Ok(Some(EditorPreference {
         command: OsString::from(command),
         args,
+        kind: EditorKind::Editor,
         source: EditorPreferenceSource::Environment { variable },
+        fallbacks: Vec::new(),
     }))
 }
 
@@ -239,8 +504,8 @@ mod tests {
     fn resolves_preference_from_config_file() {
         let dir = TempDir::new().expect("tempdir");
         let repo = init_repo(&dir);
-        let worktrees_dir = repo.ensure_worktrees_dir().expect("worktrees dir");
-        let config_path = worktrees_dir.join(CONFIG_FILE_NAME);
+        let rsworktree_dir = repo.ensure_rsworktree_dir().expect("rsworktree dir");
+        let config_path = rsworktree_dir.join(CONFIG_FILE_NAME);
 
         let json = serde_json::json!({
             "editor": {
@@ -287,8 +552,8 @@ mod tests {
     fn config_invalid_when_json_malformed() {
         let dir = TempDir::new().expect("tempdir");
         let repo = init_repo(&dir);
-        let worktrees_dir = repo.ensure_worktrees_dir().expect("worktrees dir");
-        let config_path = worktrees_dir.join(CONFIG_FILE_NAME);
+        let rsworktree_dir = repo.ensure_rsworktree_dir().expect("rsworktree dir");
+        let config_path = rsworktree_dir.join(CONFIG_FILE_NAME);
 
         fs::write(&config_path, "{ invalid json }").expect("write config");
 
@@ -308,8 +573,8 @@ mod tests {
     fn config_invalid_when_command_empty() {
         let dir = TempDir::new().expect("tempdir");
         let repo = init_repo(&dir);
-        let worktrees_dir = repo.ensure_worktrees_dir().expect("worktrees dir");
-        let config_path = worktrees_dir.join(CONFIG_FILE_NAME);
+        let rsworktree_dir = repo.ensure_rsworktree_dir().expect("rsworktree dir");
+        let config_path = rsworktree_dir.join(CONFIG_FILE_NAME);
 
         let json = serde_json::json!({
             "editor": {
@@ -330,12 +595,126 @@ mod tests {
         }
     }
 
+    #[test]
+    fn resolves_preference_from_config_toml() {
+        let dir = TempDir::new().expect("tempdir");
+        let repo = init_repo(&dir);
+        let rsworktree_dir = repo.ensure_rsworktree_dir().expect("rsworktree dir");
+        let toml_path = rsworktree_dir.join(crate::config::CONFIG_FILE);
+        fs::write(&toml_path, "editor = \"hx --vsplit\"\n").expect("write config.toml");
+
+        match resolve_editor_preference(&repo).expect("resolution") {
+            EditorPreferenceResolution::Found(preference) => {
+                assert_eq!(preference.command, OsString::from("hx"));
+                assert_eq!(preference.args, vec![OsString::from("--vsplit")]);
+                assert_eq!(
+                    preference.source,
+                    EditorPreferenceSource::ConfigFile(toml_path)
+                );
+            }
+            other => panic!("expected preference, got: {other:?}"),
+        }
+
+        let json = serde_json::json!({ "editor": { "command": "vim" } });
+        fs::write(
+            rsworktree_dir.join(CONFIG_FILE_NAME),
+            serde_json::to_vec(&json).unwrap(),
+        )
+        .expect("write config");
+        match resolve_editor_preference(&repo).expect("resolution") {
+            EditorPreferenceResolution::Found(preference) => {
+                assert_eq!(preference.command, OsString::from("vim"));
+            }
+            other => panic!("expected preference, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn resolves_preference_from_project_editor_toml() {
+        let dir = TempDir::new().expect("tempdir");
+        let repo = init_repo(&dir);
+        let rsworktree_dir = repo.ensure_rsworktree_dir().expect("rsworktree dir");
+        let project_path = rsworktree_dir.join(PROJECT_FILE_NAME);
+
+        fs::write(&project_path, "devcontainer = false\n").expect("write editor.toml");
+        let file = load_project_file(&repo)
+            .expect("valid file")
+            .expect("file exists");
+        assert_eq!(file.devcontainer, Some(false));
+
+        fs::write(
+            &project_path,
+            "command = \"code\"\nargs = [\"--new-window\"]\nkind = \"devcontainer\"\n",
+        )
+        .expect("write editor.toml");
+        match resolve_editor_preference(&repo).expect("resolution") {
+            EditorPreferenceResolution::Found(preference) => {
+                assert_eq!(preference.command, OsString::from("code"));
+                assert_eq!(preference.args, vec![OsString::from("--new-window")]);
+                assert_eq!(preference.kind, EditorKind::DevContainer);
+                assert_eq!(
+                    preference.source,
+                    EditorPreferenceSource::ConfigFile(project_path)
+                );
+            }
+            other => panic!("expected preference, got: {other:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolves_the_first_installed_editor_of_editors() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().expect("tempdir");
+        let repo = init_repo(&dir);
+        let rsworktree_dir = repo.ensure_rsworktree_dir().expect("rsworktree dir");
+        let bin = TempDir::new().expect("tempdir");
+        for command in ["code", "nvim"] {
+            let path = bin.path().join(command);
+            fs::write(&path, "#!/bin/sh\n").expect("write command");
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+                .expect("make command executable");
+        }
+        let command = |name: &str| bin.path().join(name).display().to_string();
+        let toml_path = rsworktree_dir.join(crate::config::CONFIG_FILE);
+        fs::write(
+            &toml_path,
+            format!(
+                "editors = [{:?}, \"{} --new-window\", {:?}, {:?}]\n",
+                command("cursor"),
+                command("code"),
+                command("zed"),
+                command("nvim")
+            ),
+        )
+        .expect("write config.toml");
+
+        match resolve_editor_preference(&repo).expect("resolution") {
+            EditorPreferenceResolution::Found(preference) => {
+                assert_eq!(preference.command, OsString::from(command("code")));
+                assert_eq!(preference.args, vec![OsString::from("--new-window")]);
+                assert_eq!(
+                    preference.source,
+                    EditorPreferenceSource::ConfigFile(toml_path)
+                );
+                let fallbacks = preference
+                    .fallbacks
+                    .iter()
+                    .map(|fallback| fallback.command.clone())
+                    .collect::<Vec<_>>();
+                assert_eq!(fallbacks, vec![OsString::from(command("nvim"))]);
+            }
+            other => panic!("expected preference, got: {other:?}"),
+        }
+    }
+
     #[test]
     fn config_with_no_editor_key_falls_through() {
         let dir = TempDir::new().expect("tempdir");
         let repo = init_repo(&dir);
-        let worktrees_dir = repo.ensure_worktrees_dir().expect("worktrees dir");
-        let config_path = worktrees_dir.join(CONFIG_FILE_NAME);
+        let rsworktree_dir = repo.ensure_rsworktree_dir().expect("rsworktree dir");
+        let config_path = rsworktree_dir.join(CONFIG_FILE_NAME);
 
         let json = serde_json::json!({
             "other_setting": true
@@ -435,7 +814,9 @@ mod tests {
         Ok(Some(EditorPreference {
             command: OsString::from(command),
             args,
+            kind: EditorKind::Editor,
             source: EditorPreferenceSource::Environment { variable },
+            fallbacks: Vec::new(),
         }))
     }
 
@@ -443,8 +824,8 @@ mod tests {
     fn resolves_provider_from_config_file() {
         let dir = TempDir::new().expect("tempdir");
         let repo = init_repo(&dir);
-        let worktrees_dir = repo.ensure_worktrees_dir().expect("worktrees dir");
-        let config_path = worktrees_dir.join(CONFIG_FILE_NAME);
+        let rsworktree_dir = repo.ensure_rsworktree_dir().expect("rsworktree dir");
+        let config_path = rsworktree_dir.join(CONFIG_FILE_NAME);
 
         let json = serde_json::json!({
             "provider": "gitlab"
@@ -455,6 +836,21 @@ mod tests {
         assert_eq!(provider, GitProvider::GitLab);
     }
 
+    #[test]
+    fn resolves_provider_from_config_toml() {
+        let dir = TempDir::new().expect("tempdir");
+        let repo = init_repo(&dir);
+        let rsworktree_dir = repo.ensure_rsworktree_dir().expect("rsworktree dir");
+        fs::write(
+            rsworktree_dir.join(crate::config::CONFIG_FILE),
+            "provider = \"bitbucket\"\n",
+        )
+        .expect("write config.toml");
+
+        let provider = resolve_provider_preference(&repo).expect("resolution");
+        assert_eq!(provider, GitProvider::Bitbucket);
+    }
+
     #[test]
     fn resolves_provider_defaults_to_github() {
         let dir = TempDir::new().expect("tempdir");
@@ -468,8 +864,8 @@ mod tests {
     fn resolves_provider_with_empty_config() {
         let dir = TempDir::new().expect("tempdir");
         let repo = init_repo(&dir);
-        let worktrees_dir = repo.ensure_worktrees_dir().expect("worktrees dir");
-        let config_path = worktrees_dir.join(CONFIG_FILE_NAME);
+        let rsworktree_dir = repo.ensure_rsworktree_dir().expect("rsworktree dir");
+        let config_path = rsworktree_dir.join(CONFIG_FILE_NAME);
 
         let json = serde_json::json!({
             "editor": { "command": "vim" }
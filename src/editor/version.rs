@@ -0,0 +1,99 @@
+//! Looks up the version of a launched editor for the `editor-launch` event.
+
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Editors that print their version and exit on `--version`; others, such as
+/// terminal emulators, might open a window instead.
+const VERSIONED_EDITORS: [&str; 12] = [
+    "code",
+    "code-insiders",
+    "codium",
+    "cursor",
+    "emacs",
+    "hx",
+    "micro",
+    "nano",
+    "nvim",
+    "subl",
+    "vim",
+    "zed",
+];
+
+/// A slower editor is recorded without a version.
+const VERSION_TIMEOUT: Duration = Duration::from_millis(500);
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Starts `program --version` in the background when `program` is a known
+/// editor, so the launch does not wait for it.
+pub(crate) fn probe_version(program: PathBuf) -> Option<JoinHandle<Option<String>>> {
+    let name = program.file_stem()?.to_str()?;
+    if !VERSIONED_EDITORS.contains(&name) {
+        return None;
+    }
+    Some(thread::spawn(move || read_version(&program)))
+}
+
+fn read_version(program: &Path) -> Option<String> {
+    let mut child = Command::new(program)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let deadline = Instant::now() + VERSION_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => break,
+            Ok(None) if Instant::now() < deadline => thread::sleep(POLL_INTERVAL),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+    let mut stdout = String::new();
+    child.stdout.take()?.read_to_string(&mut stdout).ok()?;
+    first_line(&stdout)
+}
+
+/// The first non-empty line, such as `1.96.2` for VS Code or
+/// `NVIM v0.10.2` for Neovim.
+fn first_line(stdout: &str) -> Option<String> {
+    stdout
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(|line| line.chars().take(80).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_is_the_first_line_printed() {
+        assert_eq!(
+            first_line("1.96.2\nfabdb6a30b49f79a7aba0f2ad9df9b399473380f\nx64\n"),
+            Some("1.96.2".into())
+        );
+        assert_eq!(
+            first_line("\nNVIM v0.10.2\nBuild type: Release\n"),
+            Some("NVIM v0.10.2".into())
+        );
+        assert_eq!(first_line("  \n"), None);
+    }
+
+    #[test]
+    fn only_known_editors_are_probed() {
+        assert!(probe_version(PathBuf::from("/usr/bin/wezterm")).is_none());
+        assert!(probe_version(PathBuf::from("/usr/bin")).is_none());
+    }
+}
@@ -0,0 +1,226 @@
+//! Project environment: variables from the `env` table of `config.toml` and
+//! from `.rsworktree/env`, exported to hooks and the launched editor.
+//!
+//! ```sh
+//! # .rsworktree/env
+//! DATABASE_URL=postgres://localhost/app_${WORKTREE_SLUG}
+//! export PORT=3000
+//! ```
+//!
+//! Values may refer to `${WORKTREE_NAME}`, `${WORKTREE_SLUG}`,
+//! `${WORKTREE_PATH}`, `${BRANCH}` and `${BASE_BRANCH}`, to variables defined
+//! earlier, and to the environment rsworktree runs in. Unknown names expand
+//! to an empty string.
+
+use std::{env, fs, path::Path};
+
+use crate::config::Config;
+
+use super::HookContext;
+
+/// Name of the env file inside `.rsworktree`.
+pub const ENV_FILE: &str = "env";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectEnv {
+    entries: Vec<(String, String)>,
+}
+
+impl ProjectEnv {
+    /// Reads the `env` table of the config files followed by the env file,
+    /// whose entries win. Unreadable files contribute nothing.
+    pub fn load(rsworktree_dir: &Path) -> Self {
+        let mut env = Self::default();
+        if let Ok(config) = Config::load(rsworktree_dir) {
+            for (key, value) in config.env {
+                env.set(key, value);
+            }
+        }
+        if let Ok(text) = fs::read_to_string(rsworktree_dir.join(ENV_FILE)) {
+            for (key, value) in parse(&text) {
+                env.set(key, value);
+            }
+        }
+        env
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn set(&mut self, key: String, value: String) {
+        self.entries.retain(|(existing, _)| *existing != key);
+        self.entries.push((key, value));
+    }
+
+    /// The variables with their placeholders filled in for the worktree in `context`.
+    pub fn resolve(&self, context: &HookContext) -> Vec<(String, String)> {
+        let mut resolved: Vec<(String, String)> = Vec::with_capacity(self.entries.len());
+        for (key, value) in &self.entries {
+            let value = interpolate(value, |name| {
+                builtin(name, context)
+                    .or_else(|| {
+                        resolved
+                            .iter()
+                            .rev()
+                            .find(|(key, _)| key == name)
+                            .map(|(_, value)| value.clone())
+                    })
+                    .or_else(|| env::var(name).ok())
+            });
+            resolved.push((key.clone(), value));
+        }
+        resolved
+    }
+}
+
+fn builtin(name: &str, context: &HookContext) -> Option<String> {
+    Some(match name {
+        "WORKTREE_NAME" => context.worktree_name.clone(),
+        "WORKTREE_SLUG" => slug(&context.worktree_name),
+        "WORKTREE_PATH" => context.worktree_path.display().to_string(),
+        "BRANCH" => context.branch.clone(),
+        "BASE_BRANCH" => context.base_branch.clone().unwrap_or_default(),
+        _ => return None,
+    })
+}
+
+/// `name` with everything but ASCII letters and digits replaced by `_`,
+/// usable in database names and the like.
+fn slug(name: &str) -> String {
+    name.chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+        .collect()
+}
+
+/// Parses `KEY=value` lines; blank lines, `#` comments and lines without a
+/// valid key are skipped, and an `export` prefix and surrounding quotes are dropped.
+fn parse(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            let valid = !key.is_empty()
+                && !key.starts_with(|ch: char| ch.is_ascii_digit())
+                && key
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+            valid.then(|| (key.to_owned(), unquote(value.trim()).to_owned()))
+        })
+        .collect()
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+/// Replaces every `${NAME}` in `value` with `lookup(NAME)`.
+fn interpolate(value: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(length) = rest[start + 2..].find('}') else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        let name = &rest[start + 2..start + 2 + length];
+        result.push_str(&lookup(name).unwrap_or_default());
+        rest = &rest[start + 3 + length..];
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use tempfile::TempDir;
+
+    fn context() -> HookContext {
+        HookContext {
+            worktree_name: "feature/login".into(),
+            worktree_path: PathBuf::from("/repo/.rsworktree/feature/login"),
+            branch: "feature/login".into(),
+            base_branch: Some("main".into()),
+            base_path: PathBuf::from("/repo/.rsworktree"),
+        }
+    }
+
+    #[test]
+    fn parse_skips_comments_and_strips_quotes() {
+        let entries = parse(
+            "# comment\n\nexport PORT=3000\nNAME = \"my app\"\nQUOTED='${BRANCH}'\nnot a line\n1BAD=x\n",
+        );
+
+        assert_eq!(
+            entries,
+            [
+                ("PORT".to_owned(), "3000".to_owned()),
+                ("NAME".to_owned(), "my app".to_owned()),
+                ("QUOTED".to_owned(), "${BRANCH}".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_fills_in_worktree_and_earlier_variables() {
+        let env = ProjectEnv {
+            entries: vec![
+                ("DB".into(), "app_${WORKTREE_SLUG}".into()),
+                (
+                    "DATABASE_URL".into(),
+                    "postgres://localhost/${DB}?base=${BASE_BRANCH}".into(),
+                ),
+                ("UNKNOWN".into(), "[${RSWORKTREE_SURELY_UNSET}]".into()),
+                ("OPEN".into(), "${WORKTREE_NAME".into()),
+            ],
+        };
+
+        assert_eq!(
+            env.resolve(&context()),
+            [
+                ("DB".to_owned(), "app_feature_login".to_owned()),
+                (
+                    "DATABASE_URL".to_owned(),
+                    "postgres://localhost/app_feature_login?base=main".to_owned()
+                ),
+                ("UNKNOWN".to_owned(), "[]".to_owned()),
+                ("OPEN".to_owned(), "${WORKTREE_NAME".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn load_lets_the_env_file_override_config() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        fs::write(
+            dir.path().join(crate::config::CONFIG_FILE),
+            "[env]\nPORT = \"3000\"\nHOST = \"localhost\"\n",
+        )?;
+        fs::write(dir.path().join(ENV_FILE), "PORT=4000\n")?;
+
+        let env = ProjectEnv::load(dir.path());
+        assert_eq!(
+            env.resolve(&context()),
+            [
+                ("HOST".to_owned(), "localhost".to_owned()),
+                ("PORT".to_owned(), "4000".to_owned()),
+            ]
+        );
+        Ok(())
+    }
+}
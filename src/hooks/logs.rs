@@ -0,0 +1,270 @@
+//! Captured hook output. With `hooks.captureOutput` in `preferences.json`,
+//! hook scripts, inline commands and tasks write their stdout and stderr to
+//! `.rsworktree/logs/<hook>-<timestamp>.log` instead of the terminal, and only
+//! the last lines of a failing one are printed.
+//!
+//! The first line of a log names the hook, worktree and command, the last one
+//! how it ended:
+//!
+//! ```text
+//! # post-create for `feature/login`: npm install
+//! ...
+//! # exited with code 1
+//! ```
+
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+
+use crate::output;
+
+pub const LOGS_DIR: &str = "logs";
+const LOG_EXTENSION: &str = "log";
+/// Logs kept per repository; the oldest are deleted as new ones are written.
+const LOGS_KEPT: usize = 100;
+/// Lines of a failed hook's log printed to the terminal.
+const TAIL_LINES: usize = 20;
+
+/// The log a single hook run writes to.
+#[derive(Debug)]
+pub(crate) struct LogFile {
+    pub(crate) path: PathBuf,
+    file: File,
+}
+
+impl LogFile {
+    /// Creates `<label>-<timestamp>.log` in the logs directory, e.g. for the
+    /// label `post-create` or, for a task, `post-create:npm`, headed by
+    /// `worktree` and the `source` that runs.
+    pub(crate) fn create(
+        rsworktree_dir: &Path,
+        label: &str,
+        worktree: &str,
+        source: &str,
+    ) -> color_eyre::Result<Self> {
+        let dir = logs_dir(rsworktree_dir);
+        fs::create_dir_all(&dir)
+            .wrap_err_with(|| eyre::eyre!("failed to create `{}`", dir.display()))?;
+        prune(&dir);
+
+        // Task names may contain characters that do not belong in a file name.
+        let file_label = label.replace(
+            |c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')),
+            "-",
+        );
+        let stem = format!("{file_label}-{}", format_timestamp(now()));
+        let (path, mut file) = (1..)
+            .map(|attempt| match attempt {
+                1 => dir.join(format!("{stem}.{LOG_EXTENSION}")),
+                _ => dir.join(format!("{stem}-{attempt}.{LOG_EXTENSION}")),
+            })
+            .find_map(
+                |path| match File::options().write(true).create_new(true).open(&path) {
+                    Err(error) if error.kind() == io::ErrorKind::AlreadyExists => None,
+                    result => Some(result.map(|file| (path, file))),
+                },
+            )
+            .expect("an unused log file name exists")
+            .wrap_err_with(|| eyre::eyre!("failed to create a log in `{}`", dir.display()))?;
+        writeln!(file, "# {label} for `{worktree}`: {source}")
+            .wrap_err_with(|| eyre::eyre!("failed to write `{}`", path.display()))?;
+        Ok(Self { path, file })
+    }
+
+    /// Handles for the stdout and stderr of the process writing to the log.
+    pub(crate) fn stdio(&self) -> io::Result<(Stdio, Stdio)> {
+        Ok((self.file.try_clone()?.into(), self.file.try_clone()?.into()))
+    }
+
+    /// Appends how the run ended, e.g. `exited with code 1`.
+    pub(crate) fn finish(mut self, outcome: &str) -> PathBuf {
+        // The log is only a convenience; a failed write must not fail the hook.
+        let _ = writeln!(self.file, "# {outcome}");
+        self.path
+    }
+}
+
+/// A log in the logs directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    /// File name without the `.log` extension.
+    pub name: String,
+    pub path: PathBuf,
+    /// Seconds since the Unix epoch at which the log was last written.
+    pub modified: u64,
+}
+
+impl LogEntry {
+    /// The first and last line of the log: which hook ran and how it ended.
+    pub fn summary(&self) -> (Option<String>, Option<String>) {
+        let Ok(contents) = fs::read_to_string(&self.path) else {
+            return (None, None);
+        };
+        let mut lines = contents.lines();
+        let header = lines
+            .next()
+            .and_then(|line| line.strip_prefix("# "))
+            .map(str::to_owned);
+        let footer = lines
+            .next_back()
+            .and_then(|line| line.strip_prefix("# "))
+            .map(str::to_owned);
+        (header, footer)
+    }
+}
+
+pub(crate) fn logs_dir(rsworktree_dir: &Path) -> PathBuf {
+    rsworktree_dir.join(LOGS_DIR)
+}
+
+/// Every log, most recent first.
+pub(crate) fn list(rsworktree_dir: &Path) -> color_eyre::Result<Vec<LogEntry>> {
+    let dir = logs_dir(rsworktree_dir);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(eyre::eyre!("failed to read `{}`: {error}", dir.display()));
+        }
+    };
+    let mut logs = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some(LOG_EXTENSION) {
+                return None;
+            }
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            let modified = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default();
+            Some(LogEntry {
+                name,
+                path,
+                modified,
+            })
+        })
+        .collect::<Vec<_>>();
+    logs.sort_by(|a, b| {
+        b.modified
+            .cmp(&a.modified)
+            .then_with(|| b.name.cmp(&a.name))
+    });
+    Ok(logs)
+}
+
+/// Prints the last lines a failed hook wrote to the log at `path`.
+pub(crate) fn print_tail(path: &Path) {
+    let contents = fs::read(path)
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .unwrap_or_default();
+    // Skip the header, and the footer `finish` wrote.
+    let mut lines = contents.lines().skip(1).collect::<Vec<_>>();
+    if lines.last().is_some_and(|line| line.starts_with("# ")) {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        output::note(tr!("No output; see `{}`.", path.display()));
+        return;
+    }
+    let tail = &lines[lines.len().saturating_sub(TAIL_LINES)..];
+    output::note(tr!(
+        "Last {} line(s) of output, full log in `{}`:",
+        tail.len(),
+        path.display()
+    ));
+    for line in tail {
+        println!("  {line}");
+    }
+}
+
+/// Deletes all but the most recent logs, leaving room for a new one.
+fn prune(dir: &Path) {
+    let Ok(logs) = list(dir.parent().unwrap_or(dir)) else {
+        return;
+    };
+    for log in logs.iter().skip(LOGS_KEPT - 1) {
+        let _ = fs::remove_file(&log.path);
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// `seconds` since the Unix epoch as `YYYYMMDD-HHMMSS` in UTC.
+fn format_timestamp(seconds: u64) -> String {
+    // Howard Hinnant's `civil_from_days`.
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    let time = seconds % 86_400;
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}",
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn timestamps_are_utc_calendar_dates() {
+        assert_eq!(format_timestamp(0), "19700101-000000");
+        assert_eq!(format_timestamp(951_782_400), "20000229-000000");
+        assert_eq!(format_timestamp(1_700_000_000), "20231114-221320");
+    }
+
+    #[test]
+    fn logs_get_unique_names_and_a_summary() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let first = LogFile::create(dir.path(), "post-create", "feature", "npm install")?;
+        let second = LogFile::create(dir.path(), "post-create", "feature", "npm install")?;
+        assert_ne!(first.path, second.path);
+
+        let path = first.finish("exited with code 1");
+        let logs = list(dir.path())?;
+        assert_eq!(logs.len(), 2);
+        let log = logs
+            .iter()
+            .find(|log| log.path == path)
+            .expect("log is listed");
+        assert_eq!(
+            log.summary(),
+            (
+                Some("post-create for `feature`: npm install".into()),
+                Some("exited with code 1".into())
+            )
+        );
+        Ok(())
+    }
+}
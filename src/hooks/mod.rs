@@ -1,22 +1,77 @@
+mod env;
+mod logs;
+mod tasks;
+
 use std::{
+    ffi::{OsStr, OsString},
+    fs,
+    io::{self, Read},
     path::{Path, PathBuf},
-    process::Command,
+    process::{Child, Command, ExitStatus, Stdio},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use color_eyre::eyre::{self, Context};
 use owo_colors::{OwoColorize, Stream};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commands::create::base_config_key,
+    config::Config,
+    editor::CONFIG_FILE_NAME,
+    output,
+    repo::RepoLock,
+    telemetry::{Event, EventStore, elapsed_ms},
+    webhook::{WebhookEvent, WebhookNotifier},
+    worktree::inspect_worktree,
+};
 
-const HOOKS_DIR: &str = "hooks";
+pub use self::env::{ENV_FILE, ProjectEnv};
+pub use self::logs::{LOGS_DIR, LogEntry};
+use self::{
+    logs::LogFile,
+    tasks::{HookTask, TaskResult, TaskState},
+};
+
+pub(crate) const HOOKS_DIR: &str = "hooks";
+/// Extensions tried after the bare hook name, as Windows picks the
+/// interpreter from the extension rather than from a shebang line.
+#[cfg(windows)]
+const SCRIPT_EXTENSIONS: &[&str] = &["exe", "cmd", "bat", "ps1"];
+#[cfg(not(windows))]
+const SCRIPT_EXTENSIONS: &[&str] = &[];
+const STATE_DIR: &str = "state";
+const HOOK_RUNS_FILE: &str = "hook-runs.json";
+const HOOK_RUNS_PER_WORKTREE: usize = 10;
+/// How often a hook running under a timeout is checked for completion.
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Prefix of the `pre-create` output line that replaces the proposed name.
+pub const RENAME_PREFIX: &str = "RSWORKTREE_NAME=";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HookName {
+    /// Runs before anything is created; see [`HookRunner::run_pre_create`].
+    PreCreate,
     PostCreate,
+    PreRemove,
+    PostRemove,
 }
 
 impl HookName {
+    pub const ALL: [HookName; 4] = [
+        HookName::PreCreate,
+        HookName::PostCreate,
+        HookName::PreRemove,
+        HookName::PostRemove,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
+            HookName::PreCreate => "pre-create",
             HookName::PostCreate => "post-create",
+            HookName::PreRemove => "pre-remove",
+            HookName::PostRemove => "post-remove",
         }
     }
 }
@@ -27,6 +82,21 @@ impl std::fmt::Display for HookName {
     }
 }
 
+impl std::str::FromStr for HookName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HookName::ALL
+            .into_iter()
+            .find(|hook| hook.as_str() == s.trim())
+            .ok_or_else(|| {
+                format!(
+                    "unknown hook '{s}', expected one of: pre-create, post-create, pre-remove, post-remove"
+                )
+            })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct HookContext {
     pub worktree_name: String,
@@ -36,17 +106,238 @@ pub struct HookContext {
     pub base_path: PathBuf,
 }
 
+impl HookContext {
+    /// The context of the existing worktree `name`: its checked-out branch,
+    /// falling back to `name`, and the base branch recorded by `create`.
+    pub(crate) fn for_worktree(
+        repo: &git2::Repository,
+        worktrees_dir: &Path,
+        name: &str,
+        path: &Path,
+    ) -> Self {
+        let branch = inspect_worktree(path)
+            .ok()
+            .and_then(|status| status.branch)
+            .unwrap_or_else(|| name.to_owned());
+        let base_branch = repo
+            .config()
+            .and_then(|config| config.get_string(&base_config_key(&branch)))
+            .ok();
+        Self {
+            worktree_name: name.to_owned(),
+            worktree_path: path.to_path_buf(),
+            branch,
+            base_branch,
+            base_path: worktrees_dir.to_path_buf(),
+        }
+    }
+}
+
+/// How a failing hook affects the command that triggered it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookFailureMode {
+    /// Print a warning and carry on (the default).
+    #[default]
+    Warn,
+    /// Abort the triggering command, which rolls back its changes.
+    FailFast,
+}
+
+impl HookFailureMode {
+    /// The value used for `hooks.mode` in `preferences.json`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookFailureMode::Warn => "warn",
+            HookFailureMode::FailFast => "fail-fast",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FileFormat {
+    #[serde(default)]
+    hooks: HookSettings,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct HookSettings {
+    #[serde(default)]
+    mode: HookFailureMode,
+    /// Lets a failing `pre-remove` hook abort the removal even in `warn` mode.
+    #[serde(default, rename = "preRemoveAborts")]
+    pre_remove_aborts: bool,
+    /// Seconds after which a hook is killed; `0` or absent waits forever.
+    #[serde(default)]
+    timeout: u64,
+    /// Commands run concurrently after the `post-create` hook.
+    #[serde(default)]
+    tasks: Vec<HookTask>,
+    /// How many tasks run at once; `0` or absent uses one per CPU.
+    #[serde(default)]
+    jobs: usize,
+    /// Writes hook output to `logs/` instead of the terminal.
+    #[serde(default, rename = "captureOutput")]
+    capture_output: bool,
+}
+
+/// The `[hooks]` table of `config.toml`: shell commands per hook, run after
+/// the hook scripts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct InlineHooks {
+    #[serde(default)]
+    pre_create: Vec<String>,
+    #[serde(default)]
+    post_create: Vec<String>,
+    #[serde(default)]
+    pre_remove: Vec<String>,
+    #[serde(default)]
+    post_remove: Vec<String>,
+}
+
+impl InlineHooks {
+    pub(crate) fn commands(&self, hook: HookName) -> &[String] {
+        match hook {
+            HookName::PreCreate => &self.pre_create,
+            HookName::PostCreate => &self.post_create,
+            HookName::PreRemove => &self.pre_remove,
+            HookName::PostRemove => &self.post_remove,
+        }
+    }
+
+    /// The commands of `self`, falling back to `fallback` per hook.
+    pub(crate) fn or(self, fallback: Self) -> Self {
+        let or = |commands: Vec<String>, fallback: Vec<String>| {
+            if commands.is_empty() {
+                fallback
+            } else {
+                commands
+            }
+        };
+        Self {
+            pre_create: or(self.pre_create, fallback.pre_create),
+            post_create: or(self.post_create, fallback.post_create),
+            pre_remove: or(self.pre_remove, fallback.pre_remove),
+            post_remove: or(self.post_remove, fallback.post_remove),
+        }
+    }
+
+    fn extend(&mut self, other: InlineHooks) {
+        self.pre_create.extend(other.pre_create);
+        self.post_create.extend(other.post_create);
+        self.pre_remove.extend(other.pre_remove);
+        self.post_remove.extend(other.post_remove);
+    }
+}
+
+/// A finished hook execution, persisted so `rsworktree info` can show it later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HookRunRecord {
+    pub worktree: String,
+    pub hook: String,
+    /// Exit code of the hook, `None` when it was terminated by a signal.
+    pub exit_code: Option<i32>,
+    /// Seconds since the Unix epoch at which the hook finished.
+    pub finished_at: u64,
+}
+
+impl HookRunRecord {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
 pub struct HookRunner {
     rsworktree_dir: PathBuf,
+    /// The repository name sent with `hook-failure` webhooks.
+    repository: String,
+    mode: HookFailureMode,
+    pre_remove_aborts: bool,
+    timeout: Option<Duration>,
+    inline: InlineHooks,
+    tasks: Vec<HookTask>,
+    jobs: usize,
+    capture_output: bool,
+    project_env: ProjectEnv,
+    env: Vec<(String, String)>,
 }
 
 impl HookRunner {
+    /// Creates a runner for `rsworktree_dir`, reading the failure mode from
+    /// `preferences.json` and the inline commands from `config.toml`.
     pub fn new(rsworktree_dir: &Path) -> Self {
+        let settings = load_settings(&rsworktree_dir.join(CONFIG_FILE_NAME));
+        // A broken `config.toml` is reported by the commands that read it.
+        let inline = Config::load(rsworktree_dir)
+            .map(|config| config.hooks)
+            .unwrap_or_default();
         Self {
             rsworktree_dir: rsworktree_dir.to_path_buf(),
+            repository: String::new(),
+            mode: settings.mode,
+            pre_remove_aborts: settings.pre_remove_aborts,
+            timeout: Some(Duration::from_secs(settings.timeout)).filter(|t| !t.is_zero()),
+            inline,
+            tasks: settings.tasks,
+            jobs: settings.jobs,
+            capture_output: settings.capture_output,
+            project_env: ProjectEnv::load(rsworktree_dir),
+            env: Vec::new(),
         }
     }
 
+    /// Names the repository in the `hook-failure` webhooks.
+    pub fn with_repository(mut self, repository: &str) -> Self {
+        self.repository = repository.to_owned();
+        self
+    }
+
+    pub fn with_mode(mut self, mode: HookFailureMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Kills hooks still running after `timeout`, replacing `hooks.timeout`;
+    /// a zero duration lets hooks run as long as they need.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout).filter(|timeout| !timeout.is_zero());
+        self
+    }
+
+    /// Writes the output of hooks to `logs/`, printing only the end of a
+    /// failing hook's log, replacing `hooks.captureOutput`.
+    pub fn with_capture_output(mut self, capture_output: bool) -> Self {
+        self.capture_output = capture_output;
+        self
+    }
+
+    /// Appends `inline` to the commands configured in `config.toml`.
+    pub(crate) fn with_inline_hooks(mut self, inline: InlineHooks) -> Self {
+        self.inline.extend(inline);
+        self
+    }
+
+    /// Exports `env` to every hook in addition to the `RSWORKTREE_*` variables
+    /// and the [`ProjectEnv`], overriding the latter.
+    pub fn with_env(mut self, env: Vec<(String, String)>) -> Self {
+        self.env = env;
+        self
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn mode(&self) -> HookFailureMode {
+        self.mode
+    }
+
+    /// Whether a failing `pre-remove` hook aborts the removal (`hooks.preRemoveAborts`).
+    pub fn pre_remove_aborts(&self) -> bool {
+        self.pre_remove_aborts
+    }
+
     pub fn hooks_dir(&self) -> PathBuf {
         self.rsworktree_dir.join(HOOKS_DIR)
     }
@@ -55,85 +346,730 @@ impl HookRunner {
         self.hooks_dir().join(hook.as_str())
     }
 
+    /// The script run for `hook`: `hook_path`, or on Windows the first of
+    /// `post-create.exe`, `.cmd`, `.bat` and `.ps1` that exists.
+    pub fn script_path(&self, hook: HookName) -> Option<PathBuf> {
+        find_script(&self.hooks_dir(), hook.as_str(), SCRIPT_EXTENSIONS)
+    }
+
+    pub fn hook_runs_path(&self) -> PathBuf {
+        self.rsworktree_dir.join(STATE_DIR).join(HOOK_RUNS_FILE)
+    }
+
+    /// Where hook output is captured with `hooks.captureOutput`.
+    pub fn logs_dir(&self) -> PathBuf {
+        logs::logs_dir(&self.rsworktree_dir)
+    }
+
+    /// The `hooks/<hook>.d/` directory whose scripts run after the `hook` script.
+    pub fn scripts_dir(&self, hook: HookName) -> PathBuf {
+        self.hooks_dir().join(format!("{}.d", hook.as_str()))
+    }
+
+    /// The executable scripts in [`HookRunner::scripts_dir`], in lexical
+    /// order; hidden and non-executable files, such as a README, are skipped.
+    pub fn directory_scripts(&self, hook: HookName) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(self.scripts_dir(hook)) else {
+            return Vec::new();
+        };
+        let mut scripts = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(OsStr::to_str)
+                    .is_some_and(|name| !name.starts_with('.'))
+                    && path.is_file()
+                    && is_executable(path)
+            })
+            .collect::<Vec<_>>();
+        scripts.sort();
+        scripts
+    }
+
+    /// The shell commands from `preferences.json` run after the `hook` script.
+    pub fn inline_commands(&self, hook: HookName) -> &[String] {
+        self.inline.commands(hook)
+    }
+
+    /// The names and commands of the tasks run after `hook`; only
+    /// `post-create` has tasks.
+    pub fn tasks(&self, hook: HookName) -> Vec<(&str, &str)> {
+        if hook != HookName::PostCreate {
+            return Vec::new();
+        }
+        self.tasks
+            .iter()
+            .map(|task| (task.name.as_str(), task.command.as_str()))
+            .collect()
+    }
+
+    /// Whether `hook` has scripts, inline commands or tasks to run.
+    pub fn is_configured(&self, hook: HookName) -> bool {
+        self.script_path(hook).is_some()
+            || !self.directory_scripts(hook).is_empty()
+            || !self.inline_commands(hook).is_empty()
+            || !self.tasks(hook).is_empty()
+    }
+
+    /// The variables hooks for `context` run with: the `RSWORKTREE_*` ones,
+    /// the [`ProjectEnv`] and those from [`HookRunner::with_env`].
+    pub fn env(&self, context: &HookContext) -> Vec<(String, OsString)> {
+        let base_branch = context.base_branch.as_deref().unwrap_or("");
+        [
+            ("RSWORKTREE_NAME", OsStr::new(&context.worktree_name)),
+            ("RSWORKTREE_PATH", context.worktree_path.as_os_str()),
+            ("RSWORKTREE_BRANCH", OsStr::new(&context.branch)),
+            ("RSWORKTREE_BASE_BRANCH", OsStr::new(base_branch)),
+            ("RSWORKTREE_BASE_PATH", context.base_path.as_os_str()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .chain(
+            self.project_env
+                .resolve(context)
+                .into_iter()
+                .chain(self.env.iter().cloned())
+                .map(|(key, value)| (key, OsString::from(value))),
+        )
+        .collect()
+    }
+
+    /// Returns the captured hook logs, most recent first.
+    pub fn logs(&self) -> color_eyre::Result<Vec<LogEntry>> {
+        logs::list(&self.rsworktree_dir)
+    }
+
+    /// Returns the recorded hook runs for `worktree`, most recent first.
+    pub fn recent_runs(&self, worktree: &str) -> color_eyre::Result<Vec<HookRunRecord>> {
+        let mut runs = self
+            .load_runs()?
+            .into_iter()
+            .filter(|run| run.worktree == worktree)
+            .collect::<Vec<_>>();
+        runs.reverse();
+        Ok(runs)
+    }
+
+    fn load_runs(&self) -> color_eyre::Result<Vec<HookRunRecord>> {
+        let path = self.hook_runs_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let contents = fs::read_to_string(&path)
+            .wrap_err_with(|| eyre::eyre!("failed to read `{}`", path.display()))?;
+        serde_json::from_str(&contents)
+            .wrap_err_with(|| eyre::eyre!("failed to parse `{}`", path.display()))
+    }
+
+    fn record_run(&self, record: HookRunRecord) -> color_eyre::Result<()> {
+        let mut runs = self.load_runs().unwrap_or_default();
+        runs.push(record.clone());
+
+        let kept = runs
+            .iter()
+            .filter(|run| run.worktree == record.worktree)
+            .count();
+        if kept > HOOK_RUNS_PER_WORKTREE {
+            let mut excess = kept - HOOK_RUNS_PER_WORKTREE;
+            runs.retain(|run| {
+                if excess > 0 && run.worktree == record.worktree {
+                    excess -= 1;
+                    return false;
+                }
+                true
+            });
+        }
+
+        let path = self.hook_runs_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| eyre::eyre!("failed to create `{}`", parent.display()))?;
+        }
+        let contents =
+            serde_json::to_string_pretty(&runs).wrap_err("failed to serialize hook runs")?;
+        fs::write(&path, contents)
+            .wrap_err_with(|| eyre::eyre!("failed to write `{}`", path.display()))
+    }
+
+    /// Runs the `hooks/` script for `hook`, if any, then the scripts of
+    /// `hooks/<hook>.d/` in lexical order, followed by its inline commands
+    /// and, for `post-create`, the tasks from `hooks.tasks`.
+    ///
+    /// Each script is reported and recorded on its own, the ones from the
+    /// directory as `<hook>.d/<file name>`.
     pub fn run_hook(&self, hook: HookName, context: &HookContext) -> color_eyre::Result<()> {
-        let hook_path = self.hook_path(hook);
+        if let Some(hook_path) = self.script_path(hook) {
+            self.run_script(hook.as_str(), &hook_path, context)?;
+        }
+        for script in self.directory_scripts(hook) {
+            self.run_script(&script_label(hook, &script), &script, context)?;
+        }
 
-        if !hook_path.exists() {
-            return Ok(());
+        for command in self.inline.commands(hook) {
+            let hook_name = format!(
+                "{}",
+                hook.as_str()
+                    .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
+            );
+            output::info(tr!("Running {} hook: {}", hook_name, command));
+
+            let (program, flag) = command_shell();
+            let mut process = Command::new(program);
+            process.arg(flag).arg(command);
+            self.run_process(hook.as_str(), process, command, context)?;
         }
 
-        if !is_executable(&hook_path) {
-            let path_display = hook_path.display();
-            let hint = format!(
+        if hook == HookName::PostCreate {
+            self.run_tasks(hook, context)?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs the `pre-create` scripts and inline commands for the proposed
+    /// worktree in `context`, returning the name that replaces it, if any.
+    ///
+    /// A hook exiting non-zero or timing out vetoes the creation, whatever the
+    /// failure mode. A hook opts into renaming by printing a line
+    /// `RSWORKTREE_NAME=<name>`, which becomes the worktree and branch name
+    /// seen by later hooks; its other output is passed through.
+    pub fn run_pre_create(&self, context: &HookContext) -> color_eyre::Result<Option<String>> {
+        let hook = HookName::PreCreate;
+        let mut processes = Vec::new();
+        if let Some(hook_path) = self.script_path(hook) {
+            if !is_executable(&hook_path) {
+                return Err(eyre::eyre!(
+                    "hook `{}` exists but is not executable",
+                    hook_path.display()
+                ));
+            }
+            processes.push((
+                hook.as_str().to_owned(),
+                hook_path.display().to_string(),
+                script_command(&hook_path),
+            ));
+        }
+        for script in self.directory_scripts(hook) {
+            processes.push((
+                script_label(hook, &script),
+                script.display().to_string(),
+                script_command(&script),
+            ));
+        }
+        for command in self.inline.commands(hook) {
+            let (program, flag) = command_shell();
+            let mut process = Command::new(program);
+            process.arg(flag).arg(command);
+            processes.push((hook.as_str().to_owned(), command.clone(), process));
+        }
+
+        let mut current = context.clone();
+        let mut renamed = None;
+        for (label, source, mut process) in processes {
+            let hook_name = format!(
                 "{}",
-                "hint: make the hook executable with `chmod +x`"
-                    .if_supports_color(Stream::Stderr, |text| format!("{}", text.dimmed()))
+                label
+                    .as_str()
+                    .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
             );
-            eprintln!(
-                "Warning: hook `{}` exists but is not executable.\n{hint}",
-                path_display
+            output::info(tr!("Running {} hook: {}", hook_name, source));
+
+            let started = Instant::now();
+            self.prepare(&mut process, &current);
+            let mut child = process
+                .stdout(Stdio::piped())
+                .spawn()
+                .wrap_err_with(|| eyre::eyre!("failed to execute hook `{source}`"))?;
+            // Drain stdout while waiting so a chatty hook cannot block on a full pipe.
+            let mut stdout = child.stdout.take().expect("stdout is piped");
+            let reader = thread::spawn(move || {
+                let mut text = String::new();
+                let _ = stdout.read_to_string(&mut text);
+                text
+            });
+            let status = match self.timeout {
+                Some(timeout) => wait_with_timeout(&mut child, timeout),
+                None => child.wait().map(Some),
+            }
+            .wrap_err_with(|| eyre::eyre!("failed to wait for hook `{source}`"))?;
+            let text = reader.join().unwrap_or_default();
+            self.record(&label, &current, status, elapsed_ms(started));
+
+            let mut replacement = None;
+            for line in text.lines() {
+                match line.trim().strip_prefix(RENAME_PREFIX) {
+                    Some(name) => replacement = Some(name.trim().to_owned()),
+                    None => println!("{line}"),
+                }
+            }
+
+            let Some(status) = status else {
+                return Err(eyre::eyre!(
+                    "hook `{}` timed out after {} seconds; `{}` was not created",
+                    label,
+                    self.timeout.unwrap_or_default().as_secs(),
+                    current.worktree_name
+                ));
+            };
+            if !status.success() {
+                return Err(eyre::eyre!(
+                    "hook `{}` rejected `{}` (exit code {})",
+                    label,
+                    current.worktree_name,
+                    status.code().unwrap_or(-1)
+                ));
+            }
+            if let Some(name) = replacement.filter(|name| !name.is_empty()) {
+                current.worktree_path = current.base_path.join(&name);
+                current.worktree_name = name.clone();
+                current.branch = name.clone();
+                renamed = Some(name);
+            }
+        }
+
+        Ok(renamed.filter(|name| *name != context.worktree_name))
+    }
+
+    /// Runs the tasks from `hooks.tasks` concurrently and prints a summary.
+    ///
+    /// Every task counts as a hook run named `<hook>:<task>`. In `fail-fast`
+    /// mode the first failing task stops the others.
+    fn run_tasks(&self, hook: HookName, context: &HookContext) -> color_eyre::Result<()> {
+        if self.tasks.is_empty() {
+            return Ok(());
+        }
+        let _span = tracing::info_span!(
+            "tasks",
+            hook = hook.as_str(),
+            worktree = %context.worktree_name,
+            count = self.tasks.len()
+        )
+        .entered();
+
+        let jobs = match self.jobs {
+            0 => thread::available_parallelism().map_or(1, usize::from),
+            jobs => jobs,
+        };
+        output::info(tr!(
+            "Running {} {} tasks ({} at a time)...",
+            self.tasks.len(),
+            hook.as_str(),
+            jobs.min(self.tasks.len())
+        ));
+        let commands = self
+            .tasks
+            .iter()
+            .map(|task| {
+                let (program, flag) = command_shell();
+                let mut process = Command::new(program);
+                process.arg(flag).arg(&task.command);
+                self.prepare(&mut process, context);
+                let label = format!("{}:{}", hook.as_str(), task.name);
+                let log = self.capture(&mut process, &label, &task.command, context);
+                (task.name.clone(), process, log)
+            })
+            .collect();
+        let results = tasks::run(
+            commands,
+            jobs,
+            self.timeout,
+            self.mode == HookFailureMode::FailFast,
+        );
+
+        for result in &results {
+            let status = match &result.state {
+                TaskState::Exited(status) => Some(*status),
+                TaskState::TimedOut => None,
+                _ => continue,
+            };
+            self.record(
+                &format!("{}:{}", hook.as_str(), result.name),
+                context,
+                status,
+                u64::try_from(result.duration.as_millis()).unwrap_or(u64::MAX),
             );
+        }
+        print_task_summary(&results);
+
+        let failed = results
+            .iter()
+            .filter(|result| result.failed())
+            .map(|result| result.name.as_str())
+            .collect::<Vec<_>>();
+        if failed.is_empty() {
+            return Ok(());
+        }
+        let message = tr!("{} tasks failed: {}", hook.as_str(), failed.join(", "));
+        WebhookNotifier::new(&self.rsworktree_dir, &self.repository).notify(
+            WebhookEvent::HookFailure,
+            &context.worktree_name,
+            Some(&context.branch),
+            message.clone(),
+        );
+        if self.mode == HookFailureMode::FailFast {
+            return Err(eyre::eyre!(
+                "{} tasks failed: {}",
+                hook.as_str(),
+                failed.join(", ")
+            ));
+        }
+        output::warn(message);
+        Ok(())
+    }
+
+    /// Runs the script at `hook_path`, reported and recorded as `label`.
+    fn run_script(
+        &self,
+        label: &str,
+        hook_path: &Path,
+        context: &HookContext,
+    ) -> color_eyre::Result<()> {
+        if !is_executable(hook_path) {
+            let path_display = hook_path.display();
+            if self.mode == HookFailureMode::FailFast {
+                return Err(eyre::eyre!(
+                    "hook `{}` exists but is not executable",
+                    path_display
+                ));
+            }
+            output::warn(tr!(
+                "hook `{}` exists but is not executable.",
+                path_display
+            ));
+            output::hint(tr!("make the hook executable with `chmod +x`"));
             return Ok(());
         }
 
         let hook_name = format!(
             "{}",
-            hook.as_str()
-                .if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
-        );
-        println!("Running {} hook...", hook_name);
-
-        let status = Command::new(&hook_path)
-            .current_dir(&context.worktree_path)
-            .env("RSWORKTREE_NAME", &context.worktree_name)
-            .env("RSWORKTREE_PATH", &context.worktree_path)
-            .env("RSWORKTREE_BRANCH", &context.branch)
-            .env(
-                "RSWORKTREE_BASE_BRANCH",
-                context.base_branch.as_deref().unwrap_or(""),
-            )
-            .env("RSWORKTREE_BASE_PATH", &context.base_path)
-            .status()
-            .wrap_err_with(|| {
-                eyre::eyre!("failed to execute hook `{}`", hook_path.display())
-            })?;
+            label.if_supports_color(Stream::Stdout, |text| format!("{}", text.cyan()))
+        );
+        output::info(tr!("Running {} hook...", hook_name));
+
+        let source = hook_path.display().to_string();
+        self.run_process(label, script_command(hook_path), &source, context)
+    }
+
+    /// Runs `process` with the hook environment and records and reports its
+    /// outcome as `label`, the hook name or `<hook>.d/<script>`.
+    ///
+    /// `source` names the script or inline command in spawn errors. A hook
+    /// killed by the timeout only warns, whatever the failure mode, so the
+    /// worktree it was preparing is kept.
+    fn run_process(
+        &self,
+        label: &str,
+        mut process: Command,
+        source: &str,
+        context: &HookContext,
+    ) -> color_eyre::Result<()> {
+        let _span = tracing::info_span!(
+            "hook",
+            hook = label,
+            worktree = %context.worktree_name,
+            source
+        )
+        .entered();
+        let started = Instant::now();
+        self.prepare(&mut process, context);
+        let log = self.capture(&mut process, label, source, context);
+        let mut child = process
+            .spawn()
+            .wrap_err_with(|| eyre::eyre!("failed to execute hook `{source}`"))?;
+        let status = match self.timeout {
+            Some(timeout) => wait_with_timeout(&mut child, timeout),
+            None => child.wait().map(Some),
+        }
+        .wrap_err_with(|| eyre::eyre!("failed to wait for hook `{source}`"))?;
+        self.record(label, context, status, elapsed_ms(started));
+
+        let Some(status) = status else {
+            let seconds = self.timeout.unwrap_or_default().as_secs();
+            if let Some(log) = log {
+                logs::print_tail(&log.finish(&format!("timed out after {seconds} seconds")));
+            }
+            WebhookNotifier::new(&self.rsworktree_dir, &self.repository).notify(
+                WebhookEvent::HookFailure,
+                &context.worktree_name,
+                Some(&context.branch),
+                tr!("hook `{}` timed out after {} seconds", label, seconds),
+            );
+            output::warn(tr!(
+                "hook `{}` timed out after {} seconds and was killed.",
+                label,
+                seconds
+            ));
+            return Ok(());
+        };
 
         if !status.success() {
             let code = status.code().unwrap_or(-1);
-            let warning = format!(
-                "{}",
-                format!("Warning: hook `{}` exited with code {code}", hook.as_str())
-                    .if_supports_color(Stream::Stderr, |text| format!("{}", text.yellow()))
+            if let Some(log) = log {
+                logs::print_tail(&log.finish(&format!("exited with code {code}")));
+            }
+            WebhookNotifier::new(&self.rsworktree_dir, &self.repository).notify(
+                WebhookEvent::HookFailure,
+                &context.worktree_name,
+                Some(&context.branch),
+                tr!("hook `{}` exited with code {}", label, code),
             );
-            eprintln!("{warning}");
+            if self.mode == HookFailureMode::FailFast {
+                return Err(eyre::eyre!("hook `{}` exited with code {code}", label));
+            }
+            output::warn(tr!("hook `{}` exited with code {}", label, code));
+            return Ok(());
         }
 
+        if let Some(log) = log {
+            log.finish("done");
+        }
+        output::success(tr!(
+            "{} hook done in {}",
+            label,
+            format!("{:.1}s", started.elapsed().as_secs_f64())
+        ));
         Ok(())
     }
+
+    /// With `hooks.captureOutput`, sends the output of `process` to a new log
+    /// named after `label` instead of the terminal. Should the log not be
+    /// created, the output is shown as usual.
+    fn capture(
+        &self,
+        process: &mut Command,
+        label: &str,
+        source: &str,
+        context: &HookContext,
+    ) -> Option<LogFile> {
+        if !self.capture_output {
+            return None;
+        }
+        let log = LogFile::create(&self.rsworktree_dir, label, &context.worktree_name, source)
+            .and_then(|log| {
+                let (stdout, stderr) = log.stdio()?;
+                process.stdin(Stdio::null()).stdout(stdout).stderr(stderr);
+                Ok(log)
+            });
+        match log {
+            Ok(log) => Some(log),
+            Err(error) => {
+                output::warn(tr!("Could not capture the hook output: {}", error));
+                None
+            }
+        }
+    }
+
+    /// Sets the working directory and hook environment of `process`.
+    fn prepare(&self, process: &mut Command, context: &HookContext) {
+        // `post-remove` runs once the worktree directory is gone.
+        let current_dir = if context.worktree_path.is_dir() {
+            &context.worktree_path
+        } else {
+            &context.base_path
+        };
+        process
+            .current_dir(current_dir)
+            .envs(RepoLock::env())
+            .envs(self.env(context));
+    }
+
+    /// Records a finished hook run for telemetry and `rsworktree info`;
+    /// a `None` status means the hook was killed by the timeout.
+    fn record(
+        &self,
+        hook: &str,
+        context: &HookContext,
+        status: Option<ExitStatus>,
+        duration_ms: u64,
+    ) {
+        EventStore::new(&self.rsworktree_dir).record(Event::HookRun {
+            worktree: context.worktree_name.clone(),
+            hook: hook.to_owned(),
+            success: status.is_some_and(|status| status.success()),
+            duration_ms,
+            timed_out: status.is_none(),
+        });
+
+        let record = HookRunRecord {
+            worktree: context.worktree_name.clone(),
+            hook: hook.to_owned(),
+            exit_code: status.and_then(|status| status.code()),
+            finished_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+        };
+        if let Err(error) = self.record_run(record) {
+            output::warn(tr!("failed to record hook run: {}", error));
+        }
+    }
+}
+
+/// Prints one line per task with how it ended and how long it took.
+fn print_task_summary(results: &[TaskResult]) {
+    let width = results
+        .iter()
+        .map(|result| result.name.chars().count())
+        .max()
+        .unwrap_or_default();
+    for result in results {
+        let name = format!("{:<width$}", result.name);
+        let seconds = format!("{:.1}s", result.duration.as_secs_f64());
+        match &result.state {
+            TaskState::Exited(status) if status.success() => {
+                output::success(tr!("{} done in {}", name, seconds));
+            }
+            TaskState::Exited(status) => {
+                output::error(tr!(
+                    "{} exited with code {} after {}",
+                    name,
+                    status.code().unwrap_or(-1),
+                    seconds
+                ));
+                if let Some(log) = &result.log {
+                    logs::print_tail(log);
+                }
+            }
+            TaskState::TimedOut => {
+                output::error(tr!("{} timed out after {}", name, seconds));
+                if let Some(log) = &result.log {
+                    logs::print_tail(log);
+                }
+            }
+            TaskState::Cancelled => output::note(tr!("{} cancelled after {}", name, seconds)),
+            TaskState::Skipped => output::note(tr!("{} skipped", name)),
+            TaskState::SpawnFailed(error) => {
+                output::error(tr!("{} could not be started: {}", name, error));
+            }
+        }
+    }
+}
+
+/// Waits for `child`, killing it once `timeout` has passed; `None` means it was killed.
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            // The hook may have exited since `try_wait`; reaping it is all that matters.
+            let _ = child.kill();
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(TIMEOUT_POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+fn load_settings(path: &Path) -> HookSettings {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str::<FileFormat>(&text).ok())
+        .map(|parsed| parsed.hooks)
+        .unwrap_or_default()
+}
+
+/// The user's shell and its flag for running a single command line.
+#[cfg(unix)]
+fn command_shell() -> (String, &'static str) {
+    let shell = std::env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.trim().is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_owned());
+    (shell, "-c")
+}
+
+#[cfg(not(unix))]
+fn command_shell() -> (String, &'static str) {
+    let shell = std::env::var("COMSPEC")
+        .ok()
+        .filter(|shell| !shell.trim().is_empty())
+        .unwrap_or_else(|| "cmd".to_owned());
+    (shell, "/C")
+}
+
+/// How a script of `hooks/<hook>.d/` is named in messages and hook runs.
+fn script_label(hook: HookName, script: &Path) -> String {
+    let name = script
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    format!("{}.d/{name}", hook.as_str())
+}
+
+fn find_script(hooks_dir: &Path, name: &str, extensions: &[&str]) -> Option<PathBuf> {
+    std::iter::once(hooks_dir.join(name))
+        .chain(
+            extensions
+                .iter()
+                .map(|extension| hooks_dir.join(format!("{name}.{extension}"))),
+        )
+        .find(|path| path.is_file())
+}
+
+/// The process running the hook script at `path`.
+///
+/// PowerShell scripts and batch files cannot be started directly, so they run
+/// through PowerShell and `cmd /C`. On Windows, scripts without an extension
+/// are assumed to be shell scripts and run with the `sh` of Git for Windows.
+fn script_command(path: &Path) -> Command {
+    let extension = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("ps1") => {
+            let mut command = Command::new(if cfg!(windows) { "powershell" } else { "pwsh" });
+            command
+                .args(["-NoProfile", "-ExecutionPolicy", "Bypass", "-File"])
+                .arg(path);
+            command
+        }
+        Some("cmd" | "bat") => {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(path);
+            command
+        }
+        None if cfg!(windows) => {
+            let mut command = Command::new("sh");
+            command.arg(path);
+            command
+        }
+        _ => Command::new(path),
+    }
 }
 
 #[cfg(unix)]
-fn is_executable(path: &Path) -> bool {
+pub(crate) fn is_executable(path: &Path) -> bool {
     use std::os::unix::fs::PermissionsExt;
     path.metadata()
         .map(|m| m.permissions().mode() & 0o111 != 0)
         .unwrap_or(false)
 }
 
+/// Windows has no executable bit; the extension decides how a script runs.
 #[cfg(not(unix))]
-fn is_executable(path: &Path) -> bool {
-    path.exists()
+pub(crate) fn is_executable(path: &Path) -> bool {
+    path.is_file()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::CONFIG_FILE;
     use std::fs;
     use tempfile::TempDir;
 
     #[test]
     fn hook_name_as_str() {
+        assert_eq!(HookName::PreCreate.as_str(), "pre-create");
         assert_eq!(HookName::PostCreate.as_str(), "post-create");
+        assert_eq!(HookName::PreRemove.as_str(), "pre-remove");
+        assert_eq!(HookName::PostRemove.as_str(), "post-remove");
     }
 
     #[test]
@@ -145,6 +1081,55 @@ mod tests {
         assert_eq!(runner.hook_path(HookName::PostCreate), expected);
     }
 
+    #[test]
+    fn find_script_tries_extensions_after_bare_name() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let extensions = ["exe", "cmd", "ps1"];
+        assert_eq!(find_script(dir.path(), "post-create", &extensions), None);
+
+        fs::write(dir.path().join("post-create.ps1"), "")?;
+        fs::write(dir.path().join("post-create.cmd"), "")?;
+        assert_eq!(
+            find_script(dir.path(), "post-create", &extensions),
+            Some(dir.path().join("post-create.cmd"))
+        );
+
+        fs::write(dir.path().join("post-create"), "")?;
+        assert_eq!(
+            find_script(dir.path(), "post-create", &extensions),
+            Some(dir.path().join("post-create"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn script_command_picks_interpreter_from_extension() {
+        let argv = |path: &str| {
+            let command = script_command(Path::new(path));
+            std::iter::once(command.get_program())
+                .chain(command.get_args())
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+
+        let powershell = argv("hooks/post-create.PS1");
+        assert_eq!(
+            powershell[1..],
+            [
+                "-NoProfile",
+                "-ExecutionPolicy",
+                "Bypass",
+                "-File",
+                "hooks/post-create.PS1"
+            ]
+        );
+        assert_eq!(
+            argv("hooks/post-create.cmd"),
+            ["cmd", "/C", "hooks/post-create.cmd"]
+        );
+        assert_eq!(argv("hooks/post-create.exe"), ["hooks/post-create.exe"]);
+    }
+
     #[test]
     fn run_hook_does_nothing_when_hook_missing() -> color_eyre::Result<()> {
         let dir = TempDir::new()?;
@@ -205,6 +1190,100 @@ mod tests {
         let content = fs::read_to_string(&marker_file)?;
         assert_eq!(content.trim(), "my-worktree");
 
+        let runs = runner.recent_runs("my-worktree")?;
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].hook, "post-create");
+        assert!(runs[0].succeeded());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_hook_runs_directory_scripts_in_lexical_order() -> color_eyre::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new()?;
+        let scripts_dir = dir.path().join("hooks/post-create.d");
+        fs::create_dir_all(&scripts_dir)?;
+        let marker_file = dir.path().join("order");
+        let write_script = |path: PathBuf, label: &str, code: i32| -> color_eyre::Result<()> {
+            fs::write(
+                &path,
+                format!("#!/bin/sh\necho {label} >> {marker_file:?}\nexit {code}\n"),
+            )?;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+            Ok(())
+        };
+        write_script(dir.path().join("hooks/post-create"), "main", 0)?;
+        write_script(scripts_dir.join("20-seed-db"), "seed", 1)?;
+        write_script(scripts_dir.join("10-install-deps"), "install", 0)?;
+        write_script(scripts_dir.join(".10-hidden"), "hidden", 0)?;
+        fs::write(scripts_dir.join("README"), "not a script")?;
+
+        let runner = HookRunner::new(dir.path());
+        assert_eq!(
+            runner.directory_scripts(HookName::PostCreate),
+            [
+                scripts_dir.join("10-install-deps"),
+                scripts_dir.join("20-seed-db")
+            ]
+        );
+        let context = HookContext {
+            worktree_name: "composed".into(),
+            worktree_path: dir.path().to_path_buf(),
+            branch: "composed".into(),
+            base_branch: None,
+            base_path: dir.path().to_path_buf(),
+        };
+
+        runner.run_hook(HookName::PostCreate, &context)?;
+
+        assert_eq!(fs::read_to_string(&marker_file)?, "main\ninstall\nseed\n");
+        let runs = runner.recent_runs("composed")?;
+        let statuses = runs
+            .iter()
+            .map(|run| (run.hook.as_str(), run.succeeded()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            [
+                ("post-create.d/20-seed-db", false),
+                ("post-create.d/10-install-deps", true),
+                ("post-create", true),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn record_run_keeps_latest_runs_per_worktree() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let runner = HookRunner::new(dir.path());
+
+        for index in 0..(HOOK_RUNS_PER_WORKTREE as u64 + 2) {
+            runner.record_run(HookRunRecord {
+                worktree: "busy".into(),
+                hook: "post-create".into(),
+                exit_code: Some(0),
+                finished_at: index,
+            })?;
+        }
+        runner.record_run(HookRunRecord {
+            worktree: "quiet".into(),
+            hook: "post-create".into(),
+            exit_code: Some(1),
+            finished_at: 0,
+        })?;
+
+        let busy = runner.recent_runs("busy")?;
+        assert_eq!(busy.len(), HOOK_RUNS_PER_WORKTREE);
+        assert_eq!(busy[0].finished_at, HOOK_RUNS_PER_WORKTREE as u64 + 1);
+
+        let quiet = runner.recent_runs("quiet")?;
+        assert_eq!(quiet.len(), 1);
+        assert!(!quiet[0].succeeded());
+
         Ok(())
     }
 
@@ -234,4 +1313,337 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn new_reads_failure_mode_from_preferences() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        assert_eq!(HookRunner::new(dir.path()).mode(), HookFailureMode::Warn);
+
+        fs::write(
+            dir.path().join(CONFIG_FILE_NAME),
+            r#"{"hooks": {"mode": "fail-fast"}}"#,
+        )?;
+        assert_eq!(HookRunner::new(dir.path()).mode(), HookFailureMode::FailFast);
+
+        Ok(())
+    }
+
+    #[test]
+    fn new_reads_pre_remove_abort_flag_from_preferences() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        assert!(!HookRunner::new(dir.path()).pre_remove_aborts());
+
+        fs::write(
+            dir.path().join(CONFIG_FILE_NAME),
+            r#"{"hooks": {"preRemoveAborts": true}}"#,
+        )?;
+        let runner = HookRunner::new(dir.path());
+        assert!(runner.pre_remove_aborts());
+        assert_eq!(runner.mode(), HookFailureMode::Warn);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_hook_fails_fast_on_non_zero_exit() -> color_eyre::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new()?;
+        let hooks_dir = dir.path().join("hooks");
+        fs::create_dir_all(&hooks_dir)?;
+        let hook_path = hooks_dir.join("post-create");
+        fs::write(&hook_path, "#!/bin/sh\nexit 3\n")?;
+        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))?;
+
+        let context = HookContext {
+            worktree_name: "test".into(),
+            worktree_path: dir.path().to_path_buf(),
+            branch: "feature/test".into(),
+            base_branch: None,
+            base_path: dir.path().to_path_buf(),
+        };
+
+        HookRunner::new(dir.path()).run_hook(HookName::PostCreate, &context)?;
+
+        let err = HookRunner::new(dir.path())
+            .with_mode(HookFailureMode::FailFast)
+            .run_hook(HookName::PostCreate, &context)
+            .unwrap_err();
+        assert!(err.to_string().contains("exited with code 3"));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_hook_runs_inline_commands_after_script() -> color_eyre::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new()?;
+        let hooks_dir = dir.path().join("hooks");
+        fs::create_dir_all(&hooks_dir)?;
+        let log = dir.path().join("hook.log");
+        let hook_path = hooks_dir.join("post-create");
+        fs::write(&hook_path, "#!/bin/sh\necho script >> hook.log\n")?;
+        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))?;
+        fs::write(
+            dir.path().join(CONFIG_FILE),
+            r#"[hooks]
+post-create = ['echo "inline $RSWORKTREE_NAME" >> hook.log', "echo second >> hook.log"]
+"#,
+        )?;
+
+        let context = HookContext {
+            worktree_name: "my-worktree".into(),
+            worktree_path: dir.path().to_path_buf(),
+            branch: "feature/test".into(),
+            base_branch: None,
+            base_path: dir.path().to_path_buf(),
+        };
+
+        let runner = HookRunner::new(dir.path());
+        runner.run_hook(HookName::PostCreate, &context)?;
+        runner.run_hook(HookName::PostRemove, &context)?;
+
+        assert_eq!(
+            fs::read_to_string(&log)?,
+            "script\ninline my-worktree\nsecond\n"
+        );
+        assert_eq!(runner.recent_runs("my-worktree")?.len(), 3);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_hook_exports_project_env() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        fs::write(
+            dir.path().join(ENV_FILE),
+            "DB=app_${WORKTREE_SLUG}\nPORT=3000\n",
+        )?;
+        fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[hooks]\npost-create = ['echo \"$DB:$PORT\" > env.log']\n",
+        )?;
+
+        let context = HookContext {
+            worktree_name: "feature/env".into(),
+            worktree_path: dir.path().to_path_buf(),
+            branch: "feature/env".into(),
+            base_branch: None,
+            base_path: dir.path().to_path_buf(),
+        };
+        HookRunner::new(dir.path())
+            .with_env(vec![("PORT".into(), "4000".into())])
+            .run_hook(HookName::PostCreate, &context)?;
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("env.log"))?,
+            "app_feature_env:4000\n"
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_pre_create_renames_or_vetoes() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let context = HookContext {
+            worktree_name: "login".into(),
+            worktree_path: dir.path().join("login"),
+            branch: "login".into(),
+            base_branch: None,
+            base_path: dir.path().to_path_buf(),
+        };
+
+        assert_eq!(HookRunner::new(dir.path()).run_pre_create(&context)?, None);
+
+        fs::write(
+            dir.path().join(CONFIG_FILE),
+            r#"[hooks]
+pre-create = [
+    "echo checking; echo RSWORKTREE_NAME=TICKET-1/$RSWORKTREE_NAME",
+    'echo "$RSWORKTREE_BRANCH" > seen',
+]
+"#,
+        )?;
+        let renamed = HookRunner::new(dir.path()).run_pre_create(&context)?;
+        assert_eq!(renamed.as_deref(), Some("TICKET-1/login"));
+        assert_eq!(
+            fs::read_to_string(dir.path().join("seen"))?,
+            "TICKET-1/login\n"
+        );
+
+        fs::write(
+            dir.path().join(CONFIG_FILE_NAME),
+            r#"{"hooks": {"mode": "warn"}}"#,
+        )?;
+        fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[hooks]\npre-create = [\"exit 3\"]\n",
+        )?;
+        let err = HookRunner::new(dir.path())
+            .run_pre_create(&context)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "hook `pre-create` rejected `login` (exit code 3)"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn run_hook_fails_fast_on_failing_inline_command() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        fs::write(
+            dir.path().join(CONFIG_FILE_NAME),
+            r#"{"hooks": {"mode": "fail-fast"}}"#,
+        )?;
+        fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[hooks]\npre-remove = [\"exit 4\", \"echo unreachable > marker\"]\n",
+        )?;
+
+        let context = HookContext {
+            worktree_name: "test".into(),
+            worktree_path: dir.path().to_path_buf(),
+            branch: "feature/test".into(),
+            base_branch: None,
+            base_path: dir.path().to_path_buf(),
+        };
+
+        let err = HookRunner::new(dir.path())
+            .run_hook(HookName::PreRemove, &context)
+            .unwrap_err();
+        assert!(err.to_string().contains("exited with code 4"));
+        assert!(!dir.path().join("marker").exists());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_hook_runs_post_create_tasks_and_records_them() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        fs::write(
+            dir.path().join(CONFIG_FILE_NAME),
+            r#"{"hooks": {"mode": "fail-fast", "jobs": 2, "tasks": [
+                {"name": "deps", "command": "echo \"$RSWORKTREE_NAME\" > deps"},
+                {"name": "broken", "command": "exit 2"}
+            ]}}"#,
+        )?;
+
+        let context = HookContext {
+            worktree_name: "test".into(),
+            worktree_path: dir.path().to_path_buf(),
+            branch: "feature/test".into(),
+            base_branch: None,
+            base_path: dir.path().to_path_buf(),
+        };
+        let runner = HookRunner::new(dir.path());
+
+        runner.run_hook(HookName::PreRemove, &context)?;
+        assert!(!dir.path().join("deps").exists());
+
+        let err = runner.run_hook(HookName::PostCreate, &context).unwrap_err();
+        assert_eq!(err.to_string(), "post-create tasks failed: broken");
+        let runs = runner.recent_runs("test")?;
+        assert!(
+            runs.iter()
+                .any(|run| run.hook == "post-create:broken" && run.exit_code == Some(2))
+        );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_hook_captures_output_in_logs() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        fs::write(
+            dir.path().join(CONFIG_FILE_NAME),
+            r#"{"hooks": {"captureOutput": true,
+                "tasks": [{"name": "npm ci", "command": "echo task output"}]
+            }}"#,
+        )?;
+        fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[hooks]\npost-create = [\"echo installing; echo oops >&2; exit 4\"]\n",
+        )?;
+        let context = HookContext {
+            worktree_name: "test".into(),
+            worktree_path: dir.path().to_path_buf(),
+            branch: "feature/test".into(),
+            base_branch: None,
+            base_path: dir.path().to_path_buf(),
+        };
+        let runner = HookRunner::new(dir.path());
+
+        runner.run_hook(HookName::PostCreate, &context)?;
+
+        let logs = runner.logs()?;
+        assert_eq!(logs.len(), 2, "{logs:?}");
+        let task = logs
+            .iter()
+            .find(|log| log.name.starts_with("post-create-npm-ci-"))
+            .expect("task log");
+        assert_eq!(
+            fs::read_to_string(&task.path)?,
+            "# post-create:npm ci for `test`: echo task output\ntask output\n# done\n"
+        );
+        let inline = logs.iter().find(|log| log != &task).expect("inline log");
+        let contents = fs::read_to_string(&inline.path)?;
+        assert!(contents.contains("installing\noops\n"), "{contents}");
+        assert_eq!(inline.summary().1.as_deref(), Some("exited with code 4"));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_hook_kills_hook_after_timeout() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        fs::write(
+            dir.path().join(CONFIG_FILE_NAME),
+            r#"{"hooks": {"mode": "fail-fast", "timeout": 30}}"#,
+        )?;
+        fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[hooks]\npost-create = [\"sleep 5\", \"echo after > marker\"]\n",
+        )?;
+        let runner = HookRunner::new(dir.path());
+        assert_eq!(runner.timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(runner.with_timeout(Duration::ZERO).timeout(), None);
+
+        let context = HookContext {
+            worktree_name: "slow".into(),
+            worktree_path: dir.path().to_path_buf(),
+            branch: "feature/slow".into(),
+            base_branch: None,
+            base_path: dir.path().to_path_buf(),
+        };
+
+        let started = Instant::now();
+        let runner = HookRunner::new(dir.path()).with_timeout(Duration::from_millis(200));
+        runner.run_hook(HookName::PostCreate, &context)?;
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(dir.path().join("marker").exists());
+
+        let runs = runner.recent_runs("slow")?;
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[1].exit_code, None);
+
+        let events = fs::read_to_string(EventStore::new(dir.path()).path())?;
+        assert!(
+            events
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .contains(r#""timed_out":true"#)
+        );
+
+        Ok(())
+    }
 }
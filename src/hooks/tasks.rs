@@ -0,0 +1,387 @@
+//! Post-create tasks: independent setup commands from `hooks.tasks` that run
+//! concurrently after the `post-create` hook, each line of their output
+//! prefixed with the task name, or written to a log of its own with
+//! `hooks.captureOutput`.
+//!
+//! ```json
+//! {
+//!   "hooks": {
+//!     "jobs": 4,
+//!     "tasks": [
+//!       { "name": "npm", "command": "npm install" },
+//!       { "name": "cargo", "command": "cargo fetch" }
+//!     ]
+//!   }
+//! }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Read},
+    path::PathBuf,
+    process::{Child, Command, ExitStatus, Stdio},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use owo_colors::{AnsiColors, OwoColorize, Stream};
+use serde::Deserialize;
+
+use super::logs::LogFile;
+
+/// How often running tasks are checked for completion.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Colors cycled through for the output prefixes of concurrent tasks.
+const PREFIX_COLORS: [AnsiColors; 5] = [
+    AnsiColors::Cyan,
+    AnsiColors::Magenta,
+    AnsiColors::Yellow,
+    AnsiColors::Green,
+    AnsiColors::Blue,
+];
+
+/// A named shell command from `hooks.tasks`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub(crate) struct HookTask {
+    pub(crate) name: String,
+    pub(crate) command: String,
+}
+
+/// How a task ended.
+#[derive(Debug)]
+pub(crate) enum TaskState {
+    Exited(ExitStatus),
+    /// Killed after running longer than the hook timeout.
+    TimedOut,
+    /// Killed because another task failed in `fail-fast` mode.
+    Cancelled,
+    /// Never started because another task failed in `fail-fast` mode.
+    Skipped,
+    SpawnFailed(String),
+}
+
+impl TaskState {
+    /// How the task ended, as written at the end of its log.
+    fn outcome(&self) -> String {
+        match self {
+            TaskState::Exited(status) if status.success() => "done".to_owned(),
+            TaskState::Exited(status) => {
+                format!("exited with code {}", status.code().unwrap_or(-1))
+            }
+            TaskState::TimedOut => "timed out".to_owned(),
+            TaskState::Cancelled => "cancelled".to_owned(),
+            TaskState::Skipped => "skipped".to_owned(),
+            TaskState::SpawnFailed(error) => format!("could not be started: {error}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct TaskResult {
+    pub(crate) name: String,
+    pub(crate) state: TaskState,
+    pub(crate) duration: Duration,
+    /// The log the output was captured in, if any.
+    pub(crate) log: Option<PathBuf>,
+}
+
+impl TaskResult {
+    /// Whether the task exited with an error or could not be started. Like
+    /// hooks, a task killed by the timeout does not count as failed.
+    pub(crate) fn failed(&self) -> bool {
+        match &self.state {
+            TaskState::Exited(status) => !status.success(),
+            TaskState::SpawnFailed(_) => true,
+            TaskState::TimedOut | TaskState::Cancelled | TaskState::Skipped => false,
+        }
+    }
+}
+
+struct Running {
+    index: usize,
+    name: String,
+    child: Child,
+    started: Instant,
+    readers: Vec<JoinHandle<()>>,
+    log: Option<LogFile>,
+}
+
+impl Running {
+    /// Kills the task. Its output readers are left behind, as processes it
+    /// started may keep the pipes open long after it is gone.
+    fn stop(mut self) -> (usize, TaskResult) {
+        // The task may have exited since it was last polled; reaping it is all that matters.
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        self.result(TaskState::Cancelled)
+    }
+
+    /// Waits for the output of the exited task to drain.
+    fn finish(mut self, status: ExitStatus) -> (usize, TaskResult) {
+        for reader in self.readers.drain(..) {
+            let _ = reader.join();
+        }
+        self.result(TaskState::Exited(status))
+    }
+
+    fn result(self, state: TaskState) -> (usize, TaskResult) {
+        let duration = self.started.elapsed();
+        (self.index, finished(self.name, state, duration, self.log))
+    }
+}
+
+fn finished(
+    name: String,
+    state: TaskState,
+    duration: Duration,
+    log: Option<LogFile>,
+) -> TaskResult {
+    let log = log.map(|log| log.finish(&state.outcome()));
+    TaskResult {
+        name,
+        state,
+        duration,
+        log,
+    }
+}
+
+/// Runs `tasks` with at most `jobs` of them at a time and returns their
+/// results in the order they were given. A task with a log has its output
+/// captured there rather than printed.
+///
+/// Every task is killed once it runs longer than `timeout`. With
+/// `stop_on_failure`, the first failing task kills the running ones and
+/// leaves the pending ones unstarted.
+pub(crate) fn run(
+    tasks: Vec<(String, Command, Option<LogFile>)>,
+    jobs: usize,
+    timeout: Option<Duration>,
+    stop_on_failure: bool,
+) -> Vec<TaskResult> {
+    let width = tasks
+        .iter()
+        .map(|(name, _, _)| name.chars().count())
+        .max()
+        .unwrap_or_default();
+    let jobs = jobs.max(1);
+    let mut results: Vec<Option<TaskResult>> = tasks.iter().map(|_| None).collect();
+    let mut pending: VecDeque<_> = tasks.into_iter().enumerate().collect();
+    let mut running: Vec<Running> = Vec::new();
+    let mut stopping = false;
+
+    loop {
+        while !stopping && running.len() < jobs {
+            let Some((index, (name, command, log))) = pending.pop_front() else {
+                break;
+            };
+            match spawn(index, name, command, log, width) {
+                Ok(task) => running.push(task),
+                Err((name, error, log)) => {
+                    results[index] = Some(finished(
+                        name,
+                        TaskState::SpawnFailed(error),
+                        Duration::ZERO,
+                        log,
+                    ));
+                    stopping = stop_on_failure;
+                }
+            }
+        }
+        if running.is_empty() {
+            break;
+        }
+
+        let mut still_running = Vec::with_capacity(running.len());
+        for mut task in running {
+            let (index, result) = match task.child.try_wait() {
+                Ok(None) if timeout.is_none_or(|timeout| task.started.elapsed() < timeout) => {
+                    still_running.push(task);
+                    continue;
+                }
+                Ok(None) => {
+                    let (index, mut result) = task.stop();
+                    result.state = TaskState::TimedOut;
+                    (index, result)
+                }
+                Ok(Some(status)) => task.finish(status),
+                Err(error) => {
+                    let (index, mut result) = task.stop();
+                    result.state = TaskState::SpawnFailed(error.to_string());
+                    (index, result)
+                }
+            };
+            stopping |= stop_on_failure && result.failed();
+            results[index] = Some(result);
+        }
+        running = still_running;
+
+        if stopping {
+            for task in running.drain(..) {
+                let (index, result) = task.stop();
+                results[index] = Some(result);
+            }
+        } else {
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    for (index, (name, _, log)) in pending {
+        results[index] = Some(finished(name, TaskState::Skipped, Duration::ZERO, log));
+    }
+    results.into_iter().flatten().collect()
+}
+
+/// Starts a task; its output goes to `log` or, without one, is printed with
+/// a prefix.
+fn spawn(
+    index: usize,
+    name: String,
+    mut command: Command,
+    log: Option<LogFile>,
+    width: usize,
+) -> Result<Running, (String, String, Option<LogFile>)> {
+    let started = Instant::now();
+    command.stdin(Stdio::null());
+    let stdio = match &log {
+        Some(log) => log.stdio(),
+        None => Ok((Stdio::piped(), Stdio::piped())),
+    };
+    let spawned = stdio.and_then(|(stdout, stderr)| command.stdout(stdout).stderr(stderr).spawn());
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(error) => return Err((name, error.to_string(), log)),
+    };
+
+    let color = PREFIX_COLORS[index % PREFIX_COLORS.len()];
+    let label = format!("{name:<width$} |");
+    let mut readers = Vec::with_capacity(2);
+    if let Some(stdout) = child.stdout.take() {
+        let prefix = paint_prefix(&label, color, Stream::Stdout);
+        readers.push(forward(stdout, move |line| println!("{prefix} {line}")));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        let prefix = paint_prefix(&label, color, Stream::Stderr);
+        readers.push(forward(stderr, move |line| eprintln!("{prefix} {line}")));
+    }
+
+    Ok(Running {
+        index,
+        name,
+        child,
+        started,
+        readers,
+        log,
+    })
+}
+
+fn paint_prefix(label: &str, color: AnsiColors, stream: Stream) -> String {
+    label
+        .if_supports_color(stream, |text| format!("{}", text.color(color)))
+        .to_string()
+}
+
+/// Prints every line read from `source` with `print` on a separate thread.
+fn forward(
+    source: impl Read + Send + 'static,
+    print: impl Fn(&str) + Send + 'static,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(source).lines().map_while(Result::ok) {
+            print(&line);
+        }
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    fn succeeded(result: &TaskResult) -> bool {
+        matches!(&result.state, TaskState::Exited(status) if status.success())
+    }
+
+    fn task(name: &str, script: &str) -> (String, Command, Option<LogFile>) {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        (name.to_owned(), command, None)
+    }
+
+    #[test]
+    fn run_starts_tasks_concurrently() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let flag = dir.path().join("flag");
+        let flag = flag.display();
+
+        // `wait` only finishes when `signal` runs at the same time.
+        let results = run(
+            vec![
+                task(
+                    "wait",
+                    &format!(
+                        "for _ in $(seq 100); do [ -f {flag} ] && exit 0; sleep 0.05; done; exit 1"
+                    ),
+                ),
+                task("signal", &format!("touch {flag}")),
+            ],
+            2,
+            Some(Duration::from_secs(10)),
+            false,
+        );
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result.name.as_str())
+                .collect::<Vec<_>>(),
+            ["wait", "signal"]
+        );
+        assert!(results.iter().all(succeeded), "{results:?}");
+        Ok(())
+    }
+
+    #[test]
+    fn run_stops_other_tasks_after_failure() {
+        let results = run(
+            vec![
+                task("slow", "sleep 10"),
+                task("broken", "exit 3"),
+                task("later", "true"),
+            ],
+            2,
+            None,
+            true,
+        );
+
+        assert!(
+            matches!(results[0].state, TaskState::Cancelled),
+            "{results:?}"
+        );
+        assert!(
+            matches!(&results[1].state, TaskState::Exited(status) if status.code() == Some(3)),
+            "{results:?}"
+        );
+        assert!(
+            matches!(results[2].state, TaskState::Skipped),
+            "{results:?}"
+        );
+        assert!(results[0].duration < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn run_kills_tasks_after_timeout() {
+        let results = run(
+            vec![task("hang", "sleep 10"), task("quick", "true")],
+            2,
+            Some(Duration::from_millis(200)),
+            false,
+        );
+
+        assert!(
+            matches!(results[0].state, TaskState::TimedOut),
+            "{results:?}"
+        );
+        assert!(succeeded(&results[1]));
+    }
+}
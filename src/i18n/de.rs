@@ -0,0 +1,1068 @@
+//! German (`de`) message catalog, keyed by the English template.
+
+pub(super) const MESSAGES: &[(&str, &str)] = &[
+    // output
+    ("Warning: {}", "Warnung: {}"),
+    ("hint: {}", "Hinweis: {}"),
+    // cd / open / interactive
+    (
+        "Switched to tmux session `{}`",
+        "Zur tmux-Sitzung `{}` gewechselt",
+    ),
+    ("Created tmux session `{}`", "tmux-Sitzung `{}` erstellt"),
+    ("Opened `{}` at `{}`.", "`{}` unter `{}` geöffnet."),
+    (
+        "Switched to editor pane `{}`",
+        "Zum Editor-Bereich `{}` gewechselt",
+    ),
+    (
+        "Switched to editor in session `{}`",
+        "Zum Editor in Sitzung `{}` gewechselt",
+    ),
+    (
+        "Created session `{}` with editor",
+        "Sitzung `{}` mit Editor erstellt",
+    ),
+    ("Opened `{}` in new pane", "`{}` in neuem Bereich geöffnet"),
+    (
+        "Waiting for pane `{}` to close...",
+        "Warte, bis der Bereich `{}` geschlossen wird...",
+    ),
+    // create
+    (
+        "Branch `{}` is already checked out at `{}`. Open it instead? [y/N] ",
+        "Branch `{}` ist bereits unter `{}` ausgecheckt. Stattdessen öffnen? [y/N] ",
+    ),
+    (
+        "Worktree `{}` already exists at `{}`.",
+        "Worktree `{}` existiert bereits unter `{}`.",
+    ),
+    ("rollback incomplete: {}", "Zurücksetzen unvollständig: {}"),
+    (
+        "Created worktree `{}` at `{}` from `{}`.",
+        "Worktree `{}` unter `{}` aus `{}` erstellt.",
+    ),
+    (
+        "Created worktree `{}` at `{}`.",
+        "Worktree `{}` unter `{}` erstellt.",
+    ),
+    // info
+    ("Worktree `{}`", "Worktree `{}`"),
+    ("Path", "Pfad"),
+    ("(detached at {})", "(losgelöst bei {})"),
+    ("(unborn)", "(ohne Commits)"),
+    ("Branch", "Branch"),
+    ("Base", "Basis"),
+    ("unknown", "unbekannt"),
+    ("{} ({} ahead, {} behind)", "{} ({} voraus, {} zurück)"),
+    ("none", "keine"),
+    ("Upstream", "Upstream"),
+    ("Changes", "Änderungen"),
+    ("unavailable ({})", "nicht verfügbar ({})"),
+    ("Hook runs", "Hook-Läufe"),
+    ("none recorded", "keine aufgezeichnet"),
+    ("Disk usage", "Speicher"),
+    ("session `{}`", "Sitzung `{}`"),
+    ("no session `{}`", "keine Sitzung `{}`"),
+    ("not running", "läuft nicht"),
+    ("Tmux", "Tmux"),
+    // ls
+    ("Worktrees under `{}`:", "Worktrees unter `{}`:"),
+    ("(none)", "(keine)"),
+    // merge
+    (
+        "Looking for open {} for `{}` from `{}`...",
+        "Suche offenen {} für `{}` aus `{}`...",
+    ),
+    (
+        "No open {} found for branch `{}`.",
+        "Kein offener {} für Branch `{}` gefunden.",
+    ),
+    (
+        "{} {} merged but `{}` could not delete branch `{}`. Leaving the branch intact.",
+        "{} {} zusammengeführt, aber `{}` konnte Branch `{}` nicht löschen. Der Branch bleibt erhalten.",
+    ),
+    (
+        "Deleted local branch `{}`.",
+        "Lokaler Branch `{}` gelöscht.",
+    ),
+    (
+        "Merged {} {} for branch `{}`.",
+        "{} {} für Branch `{}` zusammengeführt.",
+    ),
+    (
+        "Remote branch `{}` was already removed.",
+        "Entfernter Branch `{}` wurde bereits gelöscht.",
+    ),
+    (
+        "Removed remote branch `{}`.",
+        "Entfernten Branch `{}` gelöscht.",
+    ),
+    // review
+    (
+        "No reviewers found in CODEOWNERS or `reviewers` in config.toml.",
+        "Keine Reviewer in CODEOWNERS oder `reviewers` in config.toml gefunden.",
+    ),
+    ("Requesting reviews from {}.", "Fordere Reviews von {} an."),
+    (
+        "Preparing {} {} for `{}` from `{}`...",
+        "Bereite {} {} für `{}` aus `{}` vor...",
+    ),
+    (
+        "Skipping push for `{}` (push disabled).",
+        "Push für `{}` übersprungen (Push deaktiviert).",
+    ),
+    (
+        "Pushed `{}` to remote `{}`.",
+        "`{}` nach Remote `{}` gepusht.",
+    ),
+    ("{} {} created for `{}`.", "{} {} für `{}` erstellt."),
+    (
+        "Pushed `{}` for review to `refs/for/{}`.",
+        "`{}` zum Review nach `refs/for/{}` gepusht.",
+    ),
+    (
+        "Pushed `{}` to Gerrit change {}.",
+        "`{}` in Gerrit-Change {} gepusht.",
+    ),
+    (
+        "No PR metadata flags provided; defaulting to `--fill`.",
+        "Keine PR-Metadaten angegeben; verwende `--fill`.",
+    ),
+    // rm
+    (
+        "No worktrees directory found at `{}`; nothing to remove.",
+        "Kein Worktree-Verzeichnis unter `{}` gefunden; nichts zu entfernen.",
+    ),
+    (
+        "Worktree `{}` does not exist under `{}`.",
+        "Worktree `{}` existiert nicht unter `{}`.",
+    ),
+    (
+        "branch `{}` has not been pushed to any remote.",
+        "Branch `{}` wurde noch zu keinem Remote gepusht.",
+    ),
+    (
+        "Are you sure you want to remove this worktree? [y/N] ",
+        "Diesen Worktree wirklich entfernen? [y/N] ",
+    ),
+    (
+        "Removed worktree `{}` from `{}`.",
+        "Worktree `{}` aus `{}` entfernt.",
+    ),
+    ("Now in root `{}`.", "Jetzt im Hauptverzeichnis `{}`."),
+    (
+        "Local branch `{}` not found; skipping removal.",
+        "Lokaler Branch `{}` nicht gefunden; Löschen übersprungen.",
+    ),
+    (
+        "Closed tmux session `{}`.",
+        "tmux-Sitzung `{}` geschlossen.",
+    ),
+    // editor
+    (
+        "Worktree `{}` no longer exists at `{}`. Run `rsworktree worktree ls` or restart interactive mode to refresh the list.",
+        "Worktree `{}` existiert nicht mehr unter `{}`. Führe `rsworktree worktree ls` aus oder starte den interaktiven Modus neu, um die Liste zu aktualisieren.",
+    ),
+    ("Launched `{}` using `{}`", "`{}` mit `{}` gestartet"),
+    (
+        "Editor `{}` exited with status: {}",
+        "Editor `{}` wurde mit Status beendet: {}",
+    ),
+    (
+        "Editor command `{}` was not found on PATH. Install the editor or update the configured command.",
+        "Editor-Befehl `{}` wurde nicht im PATH gefunden. Installiere den Editor oder passe den konfigurierten Befehl an.",
+    ),
+    (
+        "Failed to launch `{}` via `{}`: {}",
+        "Start von `{}` über `{}` fehlgeschlagen: {}",
+    ),
+    (
+        "No editor configured. Set one in `.rsworktree/{}` or export $EDITOR/$VISUAL. Supported commands: {}",
+        "Kein Editor konfiguriert. Lege einen in `.rsworktree/{}` fest oder exportiere $EDITOR/$VISUAL. Unterstützte Befehle: {}",
+    ),
+    (
+        "Editor configuration `{}` is invalid: {}",
+        "Editor-Konfiguration `{}` ist ungültig: {}",
+    ),
+    (
+        "Environment variable ${} could not be parsed: {}",
+        "Umgebungsvariable ${} konnte nicht gelesen werden: {}",
+    ),
+    // hooks
+    (
+        "make the hook executable with `chmod +x`",
+        "mache den Hook mit `chmod +x` ausführbar",
+    ),
+    (
+        "hook `{}` exists but is not executable.",
+        "Hook `{}` existiert, ist aber nicht ausführbar.",
+    ),
+    ("Running {} hook...", "Führe {}-Hook aus..."),
+    (
+        "failed to record hook run: {}",
+        "Hook-Lauf konnte nicht gespeichert werden: {}",
+    ),
+    (
+        "hook `{}` exited with code {}",
+        "Hook `{}` wurde mit Code {} beendet",
+    ),
+    // webhook
+    (
+        "failed to send `{}` webhook: {}",
+        "`{}`-Webhook konnte nicht gesendet werden: {}",
+    ),
+    (
+        "Merged {} #{} for branch `{}`.",
+        "{} #{} für Branch `{}` zusammengeführt.",
+    ),
+    // ci
+    ("Starting {} CI for `{}`...", "Starte {}-CI für `{}`..."),
+    (
+        "Started CI for branch `{}`.",
+        "CI für Branch `{}` gestartet.",
+    ),
+    (
+        "follow it with `rsworktree ci status {} --watch`",
+        "verfolge sie mit `rsworktree ci status {} --watch`",
+    ),
+    (
+        "No CI runs found for branch `{}`.",
+        "Keine CI-Läufe für Branch `{}` gefunden.",
+    ),
+    ("CI {} for `{}`: {}", "CI {} für `{}`: {}"),
+    // pr
+    (
+        "# Write the comment for the {} of `{}`.\n# Lines starting with '#' are ignored; an empty message aborts.",
+        "# Schreibe den Kommentar für den {} von `{}`.\n# Zeilen mit '#' am Anfang werden ignoriert; eine leere Nachricht bricht ab.",
+    ),
+    ("Commented on the {} for `{}`.", "{} für `{}` kommentiert."),
+    (
+        "# Describe the changes you request on the {} of `{}`.\n# Lines starting with '#' are ignored; an empty message aborts.",
+        "# Beschreibe die gewünschten Änderungen am {} von `{}`.\n# Zeilen mit '#' am Anfang werden ignoriert; eine leere Nachricht bricht ab.",
+    ),
+    ("Approved the {} for `{}`.", "{} für `{}` genehmigt."),
+    (
+        "Requested changes on the {} for `{}`.",
+        "Änderungen am {} für `{}` angefordert.",
+    ),
+    (
+        "`{}` cannot request changes; the message was posted as a comment.",
+        "`{}` kann keine Änderungen anfordern; die Nachricht wurde als Kommentar gepostet.",
+    ),
+    ("Closed the {} for `{}`.", "{} für `{}` geschlossen."),
+    (
+        "Aborted; nothing was changed.",
+        "Abgebrochen; nichts wurde geändert.",
+    ),
+    ("This will:", "Folgendes wird ausgeführt:"),
+    ("close the {} for `{}`", "{} für `{}` schließen"),
+    (
+        "delete the branch `{}` on `{}`",
+        "Branch `{}` auf `{}` löschen",
+    ),
+    (
+        "remove the worktree `{}` and its local branch",
+        "Worktree `{}` und seinen lokalen Branch entfernen",
+    ),
+    ("Continue? [y/N] ", "Fortfahren? [y/N] "),
+    (
+        "Deleted remote branch `{}` on `{}`.",
+        "Remote-Branch `{}` auf `{}` gelöscht.",
+    ),
+    (
+        "Remote branch `{}` not found on `{}`; skipping removal.",
+        "Remote-Branch `{}` auf `{}` nicht gefunden; Entfernen übersprungen.",
+    ),
+    (
+        "Applied `{}` with `git am`.",
+        "`{}` mit `git am` angewendet.",
+    ),
+    (
+        "Applied `{}` to the working tree; the changes are left uncommitted for review.",
+        "`{}` auf das Arbeitsverzeichnis angewendet; die Änderungen bleiben zur Durchsicht uncommittet.",
+    ),
+    (
+        "Exported branch `{}` as a git bundle to `{}`.",
+        "Branch `{}` als Git-Bundle nach `{}` exportiert.",
+    ),
+    (
+        "Recreate it elsewhere with `rsworktree create {} --from-bundle {}`.",
+        "Anderswo mit `rsworktree create {} --from-bundle {}` wiederherstellen.",
+    ),
+    (
+        "Exported worktree `{}` as a tarball to `{}`.",
+        "Worktree `{}` als Tarball nach `{}` exportiert.",
+    ),
+    (
+        "Updated annotations for `{}`.",
+        "Anmerkungen für `{}` aktualisiert.",
+    ),
+    (
+        "No annotations to sync.",
+        "Keine Anmerkungen zum Synchronisieren.",
+    ),
+    (
+        "Synced annotations for {} branch(es) with `{}`; {} updated locally.",
+        "Anmerkungen für {} Branch(es) mit `{}` synchronisiert; {} lokal aktualisiert.",
+    ),
+    ("Annotations for `{}`", "Anmerkungen für `{}`"),
+    ("Note", "Notiz"),
+    ("Template", "Vorlage"),
+    (
+        "failed to record the PR link: {}",
+        "PR-Link konnte nicht gespeichert werden: {}",
+    ),
+    (
+        "Enabled `extensions.worktreeConfig` for this repository.",
+        "`extensions.worktreeConfig` für dieses Repository aktiviert.",
+    ),
+    (
+        "No per-worktree excludes for `{}`.",
+        "Keine worktree-spezifischen Ausschlüsse für `{}`.",
+    ),
+    (
+        "Per-worktree excludes for `{}`",
+        "Worktree-spezifische Ausschlüsse für `{}`",
+    ),
+    (
+        "All patterns are already excluded in `{}`.",
+        "Alle Muster sind in `{}` bereits ausgeschlossen.",
+    ),
+    ("Excluded {} in `{}`.", "{} in `{}` ausgeschlossen."),
+    (
+        "Installed {} hook(s) from `{}` (version {}).",
+        "{} Hook(s) aus `{}` installiert (Version {}).",
+    ),
+    (
+        "Hooks from `{}` are up to date (version {}).",
+        "Hooks aus `{}` sind aktuell (Version {}).",
+    ),
+    (
+        "Updated hooks from `{}` to version {} (was {}).",
+        "Hooks aus `{}` auf Version {} aktualisiert (vorher {}).",
+    ),
+    (
+        "No events recorded yet; they are collected in `{}` as you use rsworktree.",
+        "Noch keine Ereignisse aufgezeichnet; sie werden bei der Nutzung von rsworktree in `{}` gesammelt.",
+    ),
+    ("Editor launches", "Editor-Starts"),
+    ("{}/{} succeeded ({}%)", "{}/{} erfolgreich ({}%)"),
+    (
+        "Hook failures (last {} days)",
+        "Hook-Fehler (letzte {} Tage)",
+    ),
+    ("{} of {} failed", "{} von {} fehlgeschlagen"),
+    ("Slowest setup steps", "Langsamste Einrichtungsschritte"),
+    (
+        "avg {}, max {} over {} run(s)",
+        "Ø {}, max. {} bei {} Ausführung(en)",
+    ),
+    ("Most used worktrees", "Meistgenutzte Worktrees"),
+    ("{} use(s)", "{} Nutzung(en)"),
+    ("Editor", "Editor"),
+    ("Launches", "Starts"),
+    ("Success", "Erfolg"),
+    ("Step", "Schritt"),
+    ("Avg", "Ø"),
+    ("Max", "Max."),
+    ("Runs", "Läufe"),
+    ("Worktree", "Worktree"),
+    ("Uses", "Nutzungen"),
+    ("Press q or Esc to close", "Mit q oder Esc schließen"),
+    ("today", "heute"),
+    ("yesterday", "gestern"),
+    ("{} days ago", "vor {} Tagen"),
+    ("No worktree selected.", "Kein Worktree ausgewählt."),
+    ("Open worktree", "Worktree öffnen"),
+    ("{}/{} worktrees", "{}/{} Worktrees"),
+    (
+        "Type to filter · ↑/↓ move · Enter open · Esc cancel",
+        "Tippen zum Filtern · ↑/↓ bewegen · Enter öffnen · Esc abbrechen",
+    ),
+    (
+        "add `eval \"$(rsworktree shell-init bash)\"` (or zsh/fish) to your shell profile to let `switch` change the directory",
+        "füge `eval \"$(rsworktree shell-init bash)\"` (oder zsh/fish) zu deinem Shell-Profil hinzu, damit `switch` das Verzeichnis wechseln kann",
+    ),
+    ("Worktree status under `{}`:", "Worktree-Status unter `{}`:"),
+    ("unreadable", "nicht lesbar"),
+    ("{} (unmanaged)", "{} (nicht verwaltet)"),
+    ("{} ahead, {} behind", "{} voraus, {} zurück"),
+    ("no upstream", "kein Upstream"),
+    ("clean", "sauber"),
+    ("dirty", "geändert"),
+    ("no {}", "kein {}"),
+    ("{} unavailable", "{} nicht verfügbar"),
+    ("directory is missing", "Verzeichnis fehlt"),
+    (
+        "not a registered git worktree",
+        "kein registrierter Git-Worktree",
+    ),
+    (
+        "upstream branch was deleted",
+        "Upstream-Branch wurde gelöscht",
+    ),
+    ("merged into `{}`", "in `{}` gemergt"),
+    ("no commit for {} days", "seit {} Tagen kein Commit"),
+    ("Nothing to prune.", "Nichts aufzuräumen."),
+    (
+        "Worktrees that can be pruned:",
+        "Worktrees, die aufgeräumt werden können:",
+    ),
+    ("Pruned `{}`.", "`{}` aufgeräumt."),
+    (
+        "could not prune `{}`: {}",
+        "`{}` konnte nicht aufgeräumt werden: {}",
+    ),
+    (
+        "pass `--yes` to prune without confirmation",
+        "übergib `--yes`, um ohne Rückfrage aufzuräumen",
+    ),
+    (
+        "Prune {} worktrees? [y/N] ",
+        "{} Worktrees aufräumen? [y/N] ",
+    ),
+    ("Running {} hook: {}", "Führe {}-Hook aus: {}"),
+    (
+        "Copied `{}` into the new worktree.",
+        "`{}` in den neuen Worktree kopiert.",
+    ),
+    (
+        "Rendered `{}` from its template.",
+        "`{}` aus seiner Vorlage erzeugt.",
+    ),
+    (
+        "Template `{}` not found; skipped.",
+        "Vorlage `{}` nicht gefunden; übersprungen.",
+    ),
+    (
+        "Could not release the ports of `{}`: {}",
+        "Konnte die Ports von `{}` nicht freigeben: {}",
+    ),
+    (
+        "Could not move the ports of `{}`: {}",
+        "Konnte die Ports von `{}` nicht übertragen: {}",
+    ),
+    (
+        "Could not remove the metadata of `{}`: {}",
+        "Konnte die Metadaten von `{}` nicht entfernen: {}",
+    ),
+    (
+        "Could not move the metadata of `{}`: {}",
+        "Konnte die Metadaten von `{}` nicht übertragen: {}",
+    ),
+    (
+        "Switched to editor in tab `{}`",
+        "Zum Editor in Tab `{}` gewechselt",
+    ),
+    (
+        "Created tab `{}` with editor",
+        "Tab `{}` mit Editor erstellt",
+    ),
+    (
+        "Base branch of `{}` is unknown; not updating it.",
+        "Basis-Branch von `{}` ist unbekannt; er wird nicht aktualisiert.",
+    ),
+    (
+        "Updated base branch `{}` from `{}`.",
+        "Basis-Branch `{}` von `{}` aktualisiert.",
+    ),
+    (
+        "Could not update base branch `{}`: {}",
+        "Basis-Branch `{}` konnte nicht aktualisiert werden: {}",
+    ),
+    (
+        "hook `{}` timed out after {} seconds",
+        "Hook `{}` nach {} Sekunden abgebrochen",
+    ),
+    (
+        "hook `{}` timed out after {} seconds and was killed.",
+        "Hook `{}` hat das Zeitlimit von {} Sekunden überschritten und wurde beendet.",
+    ),
+    (
+        "No editor configured; using `{}` found on PATH.",
+        "Kein Editor konfiguriert; verwende `{}` aus PATH.",
+    ),
+    (
+        "Save `{}` as the editor in `.rsworktree/{}`? [y/N] ",
+        "`{}` als Editor in `.rsworktree/{}` speichern? [y/N] ",
+    ),
+    (
+        "Saved `{}` as the editor in `{}`.",
+        "`{}` als Editor in `{}` gespeichert.",
+    ),
+    (
+        "Worktree `{}` now opens with `{}`.",
+        "Worktree `{}` wird jetzt mit `{}` geöffnet.",
+    ),
+    (
+        "Worktrees now open with `{}`.",
+        "Worktrees werden jetzt mit `{}` geöffnet.",
+    ),
+    ("Saved to `{}`.", "In `{}` gespeichert."),
+    (
+        "Set {} {} for branch `{}` to complete automatically.",
+        "{} {} für Branch `{}` wird automatisch abgeschlossen.",
+    ),
+    (
+        "Could not fetch `{}`; using the last fetched state: {}",
+        "`{}` konnte nicht abgerufen werden; der zuletzt abgerufene Stand wird verwendet: {}",
+    ),
+    (
+        "Checked out {} {} into worktree `{}` at `{}`.",
+        "{} {} in Worktree `{}` unter `{}` ausgecheckt.",
+    ),
+    (
+        "Skipping the hooks of template `{}`: {}",
+        "Hooks der Vorlage `{}` werden übersprungen: {}",
+    ),
+    (
+        "Opened Windows Terminal tab `{}`",
+        "Windows-Terminal-Tab `{}` geöffnet",
+    ),
+    (
+        "Running {} {} tasks ({} at a time)...",
+        "{} {}-Aufgaben werden ausgeführt ({} gleichzeitig)...",
+    ),
+    ("{} tasks failed: {}", "{}-Aufgaben fehlgeschlagen: {}"),
+    ("{} done in {}", "{} fertig nach {}"),
+    (
+        "{} exited with code {} after {}",
+        "{0} nach {2} mit Code {1} beendet",
+    ),
+    (
+        "{} timed out after {}",
+        "{} nach {} wegen Zeitüberschreitung abgebrochen",
+    ),
+    ("{} cancelled after {}", "{} nach {} abgebrochen"),
+    ("{} skipped", "{} übersprungen"),
+    (
+        "{} could not be started: {}",
+        "{} konnte nicht gestartet werden: {}",
+    ),
+    ("AGE", "ALTER"),
+    ("STATE", "ZUSTAND"),
+    ("SYNC", "ABGLEICH"),
+    ("unavailable", "nicht verfügbar"),
+    (
+        "Renamed worktree `{}` to `{}`.",
+        "Worktree `{}` in `{}` umbenannt.",
+    ),
+    (
+        "Renamed branch `{}` to `{}`.",
+        "Branch `{}` in `{}` umbenannt.",
+    ),
+    (
+        "Renamed tmux session `{}` to `{}`.",
+        "tmux-Sitzung `{}` in `{}` umbenannt.",
+    ),
+    (
+        "failed to rename tmux session `{}`",
+        "tmux-Sitzung `{}` konnte nicht umbenannt werden",
+    ),
+    (
+        "Your shell is still in the old directory; run `rsworktree switch {}`.",
+        "Deine Shell ist noch im alten Verzeichnis; führe `rsworktree switch {}` aus.",
+    ),
+    (
+        "Worktree `{}` now opens a shell.",
+        "Worktree `{}` öffnet jetzt eine Shell.",
+    ),
+    (
+        "Worktrees now open a shell.",
+        "Worktrees öffnen jetzt eine Shell.",
+    ),
+    (
+        "Outside tmux, Zellij, kitty, WezTerm and Windows Terminal the shell starts in `{}`.",
+        "Außerhalb von tmux, Zellij, kitty, WezTerm und Windows Terminal startet die Shell in `{}`.",
+    ),
+    (
+        "Run `cd {}` to enter `{}`.",
+        "Führe `cd {}` aus, um `{}` zu betreten.",
+    ),
+    (
+        "Opened a shell in window `{}`",
+        "Shell in Fenster `{}` geöffnet",
+    ),
+    ("Opened a shell in tab `{}`", "Shell in Tab `{}` geöffnet"),
+    (
+        "The pre-create hook renamed `{}` to `{}`.",
+        "Der pre-create-Hook hat `{}` in `{}` umbenannt.",
+    ),
+    ("Issue", "Ticket"),
+    (
+        "Archived worktree `{}` to `{}`.",
+        "Worktree `{}` nach `{}` archiviert.",
+    ),
+    (
+        "Restore it with `rsworktree unarchive {}`.",
+        "Stelle ihn mit `rsworktree unarchive {}` wieder her.",
+    ),
+    ("No archived worktrees.", "Keine archivierten Worktrees."),
+    ("Archived worktrees", "Archivierte Worktrees"),
+    ("{} ({}, archived {})", "{} ({}, archiviert {})"),
+    (
+        "Restored worktree `{}` from the archive.",
+        "Worktree `{}` aus dem Archiv wiederhergestellt.",
+    ),
+    (
+        "Enter it with `rsworktree switch {}`.",
+        "Wechsle mit `rsworktree switch {}` hinein.",
+    ),
+    ("Locked worktree `{}`.", "Worktree `{}` gesperrt."),
+    (
+        "Worktree `{}` is not locked.",
+        "Worktree `{}` ist nicht gesperrt.",
+    ),
+    (
+        "Unlocked worktree `{}`.",
+        "Sperre von Worktree `{}` aufgehoben.",
+    ),
+    // ui
+    (
+        "Failed to load worktrees: {}",
+        "Worktrees konnten nicht geladen werden: {}",
+    ),
+    ("Diff of `{}`", "Diff von `{}`"),
+    ("Log of `{}`", "Log von `{}`"),
+    (
+        "No uncommitted changes.",
+        "Keine nicht committeten Änderungen.",
+    ),
+    ("No commits yet.", "Noch keine Commits."),
+    (
+        "Worktree name cannot be empty.",
+        "Der Worktree-Name darf nicht leer sein.",
+    ),
+    ("Created `{}`.", "`{}` erstellt."),
+    (
+        "Failed to create `{}`: {}",
+        "`{}` konnte nicht erstellt werden: {}",
+    ),
+    ("Removed `{}`.", "`{}` entfernt."),
+    (
+        "Failed to remove `{}`: {}",
+        "`{}` konnte nicht entfernt werden: {}",
+    ),
+    (
+        "(no worktrees, press `c` to create one)",
+        "(keine Worktrees, `c` legt einen an)",
+    ),
+    ("Worktrees", "Worktrees"),
+    ("Details", "Details"),
+    ("New worktree name: {}_", "Name des neuen Worktrees: {}_"),
+    ("Remove `{}`? (y/n)", "`{}` entfernen? (y/n)"),
+    (
+        "↑/↓ scroll  PgUp/PgDn page  q back",
+        "↑/↓ scrollen  PgUp/PgDn seitenweise  q zurück",
+    ),
+    (
+        "o open  d remove  c create  v diff  l log  r refresh  q quit",
+        "o öffnen  d entfernen  c erstellen  v Diff  l Log  r aktualisieren  q beenden",
+    ),
+    // du
+    ("SIZE", "GRÖSSE"),
+    ("WORKTREE", "WORKTREE"),
+    ("(shared object store)", "(gemeinsamer Objektspeicher)"),
+    ("(total)", "(gesamt)"),
+    // clean
+    ("No worktrees to clean.", "Keine Worktrees zum Aufräumen."),
+    ("Would free {} in total.", "Würde insgesamt {} freigeben."),
+    ("Freed {} in total.", "Insgesamt {} freigegeben."),
+    (
+        "Nothing to clean in `{}`.",
+        "In `{}` gibt es nichts aufzuräumen.",
+    ),
+    ("Artifacts in `{}`:", "Build-Artefakte in `{}`:"),
+    ("Would free {} in `{}`.", "Würde {} in `{}` freigeben."),
+    ("Freed {} in `{}`.", "{} in `{}` freigegeben."),
+    (
+        "could not remove `{}`: {}",
+        "`{}` konnte nicht entfernt werden: {}",
+    ),
+    // remote
+    (
+        "Worktree `{}` is at `{}` on `{}`. Connect with:",
+        "Worktree `{}` liegt unter `{}` auf `{}`. Verbinden mit:",
+    ),
+    (
+        "Opened `{}` on `{}` in `{}`.",
+        "`{}` auf `{}` in `{}` geöffnet.",
+    ),
+    // lock
+    (
+        "Waiting for another rsworktree command to finish...",
+        "Warte, bis ein anderer rsworktree-Befehl fertig ist...",
+    ),
+    // progress
+    ("Fetching `{}` from `{}`...", "Hole `{}` aus `{}`..."),
+    ("Fetching `{}`...", "Hole `{}`..."),
+    ("Checking out `{}`...", "Checke `{}` aus..."),
+    ("Pushing `{}` to `{}`...", "Pushe `{}` nach `{}`..."),
+    ("Pushing `{}` for review...", "Pushe `{}` zum Review..."),
+    (
+        "Updating base branch `{}`...",
+        "Aktualisiere Basis-Branch `{}`...",
+    ),
+    (
+        "Deleting remote branch `{}`...",
+        "Lösche Remote-Branch `{}`...",
+    ),
+    (
+        "Deleting remote branch `{}` on `{}`...",
+        "Lösche Remote-Branch `{}` auf `{}`...",
+    ),
+    ("{} hook done in {}", "{}-Hook fertig nach {}"),
+    // ws
+    (
+        "Created `{}` in {} repositories of workspace `{}`.",
+        "`{}` in {} Repositories des Workspace `{}` erstellt.",
+    ),
+    (
+        "`{}` has no worktree `{}`; run `rsworktree ws create {}`.",
+        "`{}` hat keinen Worktree `{}`; führe `rsworktree ws create {}` aus.",
+    ),
+    (
+        "Opened `{}` in {} repositories with `{}`.",
+        "`{}` in {} Repositories mit `{}` geöffnet.",
+    ),
+    (
+        "Opened `{}` in window `{}`",
+        "`{}` in Fenster `{}` geöffnet",
+    ),
+    (
+        "Checking out `{}` limited to {}...",
+        "Checke `{}` beschränkt auf {} aus...",
+    ),
+    // doctor
+    (
+        "Everything rsworktree needs is in place.",
+        "Alles, was rsworktree braucht, ist vorhanden.",
+    ),
+    (
+        "Everything rsworktree needs is in place; {} optional check(s) reported a problem.",
+        "Alles, was rsworktree braucht, ist vorhanden; {} optionale Prüfung(en) meldeten ein Problem.",
+    ),
+    (
+        "install git {}.{} or later from https://git-scm.com",
+        "installiere git {}.{} oder neuer von https://git-scm.com",
+    ),
+    (
+        "git is not installed or not on PATH.",
+        "git ist nicht installiert oder nicht im PATH.",
+    ),
+    ("git {} is installed.", "git {} ist installiert."),
+    (
+        "git {} is too old for worktrees as rsworktree uses them.",
+        "git {} ist zu alt für Worktrees, wie rsworktree sie nutzt.",
+    ),
+    (
+        "Could not read the git version from `{}`.",
+        "Konnte die git-Version nicht aus `{}` lesen.",
+    ),
+    (
+        "`git worktree list` failed: {}",
+        "`git worktree list` ist fehlgeschlagen: {}",
+    ),
+    (
+        "run `git worktree list` in the repository to see what git reports",
+        "führe `git worktree list` im Repository aus, um zu sehen, was git meldet",
+    ),
+    (
+        "{} worktree(s) registered with git no longer exist on disk.",
+        "{} bei git registrierte(r) Worktree(s) existieren nicht mehr auf der Festplatte.",
+    ),
+    (
+        "run `git worktree prune` to forget them",
+        "führe `git worktree prune` aus, um sie zu vergessen",
+    ),
+    ("git lists {} worktree(s).", "git listet {} Worktree(s)."),
+    (
+        "`config.toml` settings are valid.",
+        "Die Einstellungen in `config.toml` sind gültig.",
+    ),
+    ("`{}` is invalid: {}", "`{}` ist ungültig: {}"),
+    (
+        "fix or remove the setting, then run `rsworktree doctor` again",
+        "korrigiere oder entferne die Einstellung und führe `rsworktree doctor` erneut aus",
+    ),
+    (
+        "set one with `rsworktree editor set <command>` or `$EDITOR`",
+        "lege einen mit `rsworktree editor set <command>` oder `$EDITOR` fest",
+    ),
+    (
+        "Could not resolve the editor: {}",
+        "Konnte den Editor nicht ermitteln: {}",
+    ),
+    (
+        "Editor `{}` opens worktrees through a URL.",
+        "Editor `{}` öffnet Worktrees über eine URL.",
+    ),
+    ("Editor `{}` is installed.", "Editor `{}` ist installiert."),
+    (
+        "Editor `{}` is not on PATH.",
+        "Editor `{}` ist nicht im PATH.",
+    ),
+    (
+        "install it or change the editor with `rsworktree editor set <command>`",
+        "installiere ihn oder ändere den Editor mit `rsworktree editor set <command>`",
+    ),
+    (
+        "No editor configured; `{}` found on PATH will be used.",
+        "Kein Editor konfiguriert; `{}` aus dem PATH wird verwendet.",
+    ),
+    (
+        "No editor is configured or found on PATH.",
+        "Kein Editor konfiguriert oder im PATH gefunden.",
+    ),
+    (
+        "fix the file or set the editor again with `rsworktree editor set <command>`",
+        "korrigiere die Datei oder lege den Editor mit `rsworktree editor set <command>` neu fest",
+    ),
+    (
+        "`${}` cannot be used as the editor: {}",
+        "`${}` kann nicht als Editor verwendet werden: {}",
+    ),
+    ("fix `${}` or {}", "korrigiere `${}` oder {}"),
+    ("`{}` for {} is installed.", "`{}` für {} ist installiert."),
+    (
+        "`{}` for {} is not on PATH; `review`, `merge` and `pr` need it.",
+        "`{}` für {} ist nicht im PATH; `review`, `merge` und `pr` benötigen es.",
+    ),
+    (
+        "install `{}`, or select your provider with `--provider` or `provider` in `config.toml`",
+        "installiere `{}` oder wähle deinen Provider mit `--provider` oder `provider` in `config.toml`",
+    ),
+    ("{} is installed.", "{} ist installiert."),
+    ("tmux is not installed.", "tmux ist nicht installiert."),
+    (
+        "install tmux to open worktrees in tmux windows and sessions",
+        "installiere tmux, um Worktrees in tmux-Fenstern und -Sessions zu öffnen",
+    ),
+    (
+        "Hook `{}` is not executable.",
+        "Hook `{}` ist nicht ausführbar.",
+    ),
+    ("run `chmod +x {}`", "führe `chmod +x {}` aus"),
+    (
+        "{} hook script(s) found, all executable.",
+        "{} Hook-Skript(e) gefunden, alle ausführbar.",
+    ),
+    // sync
+    ("Fetching remotes...", "Hole Remotes..."),
+    (
+        "Could not fetch; using the last fetched state: {}",
+        "Holen fehlgeschlagen; verwende den zuletzt geholten Stand: {}",
+    ),
+    (
+        "`{}` has local changes; not fast-forwarding {} commit(s) from `{}`.",
+        "`{}` hat lokale Änderungen; {} Commit(s) von `{}` werden nicht vorgespult.",
+    ),
+    (
+        "`{}` is {} commit(s) ahead of `{}`.",
+        "`{}` ist {} Commit(s) vor `{}`.",
+    ),
+    (
+        "`{}` has diverged from `{}` ({} ahead, {} behind); merge or rebase it yourself.",
+        "`{}` ist von `{}` abgewichen ({} voraus, {} zurück); führe merge oder rebase selbst aus.",
+    ),
+    (
+        "Fast-forwarded {} worktree(s); {} already up to date.",
+        "{} Worktree(s) vorgespult; {} bereits aktuell.",
+    ),
+    (
+        "Fast-forwarded `{}` by {} commit(s) from `{}`.",
+        "`{}` um {} Commit(s) von `{}` vorgespult.",
+    ),
+    (
+        "Could not fast-forward `{}`: {}",
+        "`{}` konnte nicht vorgespult werden: {}",
+    ),
+    ("Sync failed: {}", "Synchronisierung fehlgeschlagen: {}"),
+    (
+        "Syncing again in {} or when the main worktree's branch moves; press Ctrl-C to stop.",
+        "Erneute Synchronisierung in {} oder sobald sich der Branch des Haupt-Worktrees bewegt; Strg-C beendet.",
+    ),
+    // hook logs
+    ("No output; see `{}`.", "Keine Ausgabe; siehe `{}`."),
+    (
+        "Last {} line(s) of output, full log in `{}`:",
+        "Letzte {} Zeile(n) der Ausgabe, vollständiges Log in `{}`:",
+    ),
+    (
+        "Could not capture the hook output: {}",
+        "Die Hook-Ausgabe konnte nicht aufgezeichnet werden: {}",
+    ),
+    (
+        "No hook logs in `{}`; set `hooks.captureOutput` to `true` in `preferences.json` to write them.",
+        "Keine Hook-Logs in `{}`; setze `hooks.captureOutput` in `preferences.json` auf `true`, um sie zu schreiben.",
+    ),
+    ("Hook logs in `{}`:", "Hook-Logs in `{}`:"),
+    ("unfinished", "nicht beendet"),
+    // dev containers
+    (
+        "Opening `{}` in its dev container; set `devcontainer = false` in `config.toml` to open it on the host.",
+        "`{}` wird in seinem Dev-Container geöffnet; setze `devcontainer = false` in `config.toml`, um es auf dem Host zu öffnen.",
+    ),
+    (
+        "`{}` has a dev container; run `{} open {}` to open it there.",
+        "`{}` hat einen Dev-Container; führe `{} open {}` aus, um es dort zu öffnen.",
+    ),
+    // alternates
+    (
+        "Sharing objects with `{}`; keep that clone in place.",
+        "Objekte werden mit `{}` geteilt; lass diesen Klon an seinem Platz.",
+    ),
+    (
+        "check the permissions of `.git/objects/info/alternates`",
+        "prüfe die Berechtigungen von `.git/objects/info/alternates`",
+    ),
+    (
+        "The clone objects are borrowed from is gone: `{}`.",
+        "Der Klon, von dem Objekte geliehen werden, fehlt: `{}`.",
+    ),
+    (
+        "restore that clone, or remove its line from `.git/objects/info/alternates` and fetch again",
+        "stelle diesen Klon wieder her oder entferne seine Zeile aus `.git/objects/info/alternates` und hole erneut",
+    ),
+    (
+        "Objects are shared with {} other clone(s).",
+        "Objekte werden mit {} anderen Klon(en) geteilt.",
+    ),
+    // editor fallbacks
+    (
+        "Trying `{}`, the next editor in `editors`.",
+        "Versuche `{}`, den nächsten Editor in `editors`.",
+    ),
+    // hooks run and list
+    ("No {} hook is configured.", "Kein {}-Hook eingerichtet."),
+    (
+        "The pre-create hook would rename `{}` to `{}`.",
+        "Der pre-create-Hook würde `{}` in `{}` umbenennen.",
+    ),
+    (
+        "The {} hook for `{}` would run in `{}`:",
+        "Der {}-Hook für `{}` würde in `{}` Folgendes ausführen:",
+    ),
+    ("script `{}`", "Skript `{}`"),
+    (
+        "script `{}`, which is not executable and would be skipped",
+        "Skript `{}`, das nicht ausführbar ist und übersprungen würde",
+    ),
+    ("command `{}`", "Befehl `{}`"),
+    ("task {}: `{}`", "Aufgabe {}: `{}`"),
+    ("With the environment:", "Mit der Umgebung:"),
+    ("Hooks in `{}`:", "Hooks in `{}`:"),
+    (
+        "script `{}` (not executable, run `chmod +x` on it)",
+        "Skript `{}` (nicht ausführbar, führe `chmod +x` darauf aus)",
+    ),
+    (
+        "{} command(s) from `preferences.json`",
+        "{} Befehl(e) aus `preferences.json`",
+    ),
+    ("{} task(s)", "{} Aufgabe(n)"),
+    // merge --autostash
+    (
+        "Not updating base branch `{}`: `{}` has uncommitted changes. Commit or stash them, or pass `--autostash`:",
+        "Basis-Branch `{}` wird nicht aktualisiert: `{}` hat nicht committete Änderungen. Committe oder stashe sie, oder übergib `--autostash`:",
+    ),
+    // worktree metadata
+    ("Note for `{}` unchanged.", "Notiz für `{}` unverändert."),
+    ("Updated the note for `{}`.", "Notiz für `{}` aktualisiert."),
+    ("{} by {}", "{} von {}"),
+    ("Created", "Erstellt"),
+    ("PR link", "PR-Link"),
+    ("CREATED", "ERSTELLT"),
+    ("BASE", "BASIS"),
+    ("NOTE", "NOTIZ"),
+    // open history
+    (
+        "No worktree has been opened yet; `worktree open` and `switch` are remembered.",
+        "Noch wurde kein Worktree geöffnet; `worktree open` und `switch` werden gemerkt.",
+    ),
+    ("Recently opened worktrees:", "Zuletzt geöffnete Worktrees:"),
+    (
+        "Open one with `rsworktree recent <number>`.",
+        "Öffne einen mit `rsworktree recent <Nummer>`.",
+    ),
+    ("opened {} time(s)", "{}-mal geöffnet"),
+    // protected branches
+    (
+        "`{}` is a protected branch (`{}` in `protected_branches`); merging it lands it in another branch.",
+        "`{}` ist ein geschützter Branch (`{}` in `protected_branches`); ein Merge überführt ihn in einen anderen Branch.",
+    ),
+    (
+        "`{}` was created from `{}`, which is not in `protected_branches`; make sure it merges into the branch you expect.",
+        "`{}` wurde von `{}` erstellt, der nicht in `protected_branches` steht; prüfe, ob er in den erwarteten Branch gemergt wird.",
+    ),
+    // hook directories
+    ("{} script(s) in `{}`", "{} Skript(e) in `{}`"),
+    // submodules
+    ("Initializing submodules...", "Submodule werden initialisiert..."),
+    (
+        "Could not initialize the submodules: {}",
+        "Die Submodule konnten nicht initialisiert werden: {}",
+    ),
+    (
+        "run `git submodule update --init --recursive` in the worktree",
+        "führe `git submodule update --init --recursive` im Worktree aus",
+    ),
+    // commit signing
+    (
+        "Commits are signed with {}, but `{}` is not installed.",
+        "Commits werden mit {} signiert, aber `{}` ist nicht installiert.",
+    ),
+    (
+        "install `{}`, or point `gpg.{}.program` at it",
+        "installiere `{}` oder setze `gpg.{}.program` darauf",
+    ),
+    (
+        "Commits are signed with ssh, but no signing key is set.",
+        "Commits werden mit ssh signiert, aber es ist kein Signierschlüssel gesetzt.",
+    ),
+    (
+        "run `git config --global user.signingkey ~/.ssh/id_ed25519.pub` with your public key",
+        "führe `git config --global user.signingkey ~/.ssh/id_ed25519.pub` mit deinem öffentlichen Schlüssel aus",
+    ),
+    (
+        "Commits are signed with {} using `{}`.",
+        "Commits werden mit {} über `{}` signiert.",
+    ),
+    // stash
+    (
+        "Worktree `{}` has no changes to stash.",
+        "Worktree `{}` hat keine Änderungen zum Stashen.",
+    ),
+    (
+        "pass `--include-untracked` to stash its {} untracked file(s)",
+        "übergib `--include-untracked`, um seine {} nicht verfolgte(n) Datei(en) zu stashen",
+    ),
+    (
+        "Stashed the changes of `{}` ({}).",
+        "Änderungen von `{}` gestasht ({}).",
+    ),
+    (
+        "Bring them back with `rsworktree stash pop {}`.",
+        "Hol sie mit `rsworktree stash pop {}` zurück.",
+    ),
+    (
+        "Restored the stashed changes of `{}`.",
+        "Gestashte Änderungen von `{}` wiederhergestellt.",
+    ),
+    ("STASHES", "STASHES"),
+    ("{} stashed", "{} gestasht"),
+    // provider API
+    (
+        "{} operations call its API.",
+        "{}-Operationen rufen dessen API auf.",
+    ),
+    (
+        "The {} API cannot be used: {}",
+        "Die {}-API kann nicht verwendet werden: {}",
+    ),
+    (
+        "fix the above, or set `provider_backend = \"cli\"` in `config.toml` to run `{}`",
+        "behebe das obige oder setze `provider_backend = \"cli\"` in `config.toml`, um `{}` auszuführen",
+    ),
+];
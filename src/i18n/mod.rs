@@ -0,0 +1,240 @@
+//! Message catalog for user-facing output.
+//!
+//! Messages are written in English at the call site via [`tr!`]; the English
+//! template doubles as the catalog key. Other locales map that key to a
+//! translated template and fall back to English when no entry exists.
+//! Templates use `{}` for the next argument, `{N}` for an explicit position
+//! (so translations may reorder arguments) and `{{`/`}}` for literal braces.
+
+mod de;
+
+use std::{collections::HashMap, env, fmt, fs, sync::OnceLock};
+
+use serde::Deserialize;
+
+use crate::{Repo, editor::CONFIG_FILE_NAME};
+
+/// Environment variable selecting the output language (e.g. `de`).
+pub const LOCALE_ENV: &str = "RSWORKTREE_LANG";
+
+/// Formats a catalog message: `tr!("Removed `{}`.", name)`.
+#[macro_export]
+macro_rules! tr {
+    ($template:literal $(,)?) => {
+        $crate::i18n::format_message($template, &[])
+    };
+    ($template:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format_message($template, &[$(&$arg as &dyn ::std::fmt::Display),+])
+    };
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    English,
+    German,
+}
+
+impl Locale {
+    /// Parses a language tag such as `de`, `de_DE.UTF-8` or `en-US`.
+    pub fn parse(tag: &str) -> Option<Self> {
+        let language = tag
+            .trim()
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match language.as_str() {
+            "en" | "c" | "posix" => Some(Locale::English),
+            "de" => Some(Locale::German),
+            _ => None,
+        }
+    }
+
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::German => "de",
+        }
+    }
+
+    fn catalog(self) -> Option<&'static HashMap<&'static str, &'static str>> {
+        static GERMAN: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+        match self {
+            Locale::English => None,
+            Locale::German => Some(GERMAN.get_or_init(|| de::MESSAGES.iter().copied().collect())),
+        }
+    }
+}
+
+static ACTIVE_LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Selects the locale for the rest of the process. Only the first call has an effect.
+pub fn init(locale: Locale) {
+    let _ = ACTIVE_LOCALE.set(locale);
+}
+
+/// Returns the active locale, falling back to `RSWORKTREE_LANG` when [`init`] was not called.
+pub fn current() -> Locale {
+    *ACTIVE_LOCALE.get_or_init(|| locale_from_env().unwrap_or_default())
+}
+
+#[derive(Debug, Deserialize)]
+struct FileFormat {
+    #[serde(default)]
+    locale: Option<String>,
+}
+
+/// Resolve the locale preference.
+///
+/// Resolution order:
+/// 1. Config file (`preferences.json`, key `locale`)
+/// 2. Environment variable (`RSWORKTREE_LANG`)
+/// 3. Default (English)
+pub fn resolve_locale_preference(repo: &Repo) -> Locale {
+    let config_path = repo.rsworktree_dir().join(CONFIG_FILE_NAME);
+    let configured = fs::read_to_string(config_path)
+        .ok()
+        .and_then(|text| serde_json::from_str::<FileFormat>(&text).ok())
+        .and_then(|parsed| parsed.locale)
+        .and_then(|tag| Locale::parse(&tag));
+
+    configured.or_else(locale_from_env).unwrap_or_default()
+}
+
+fn locale_from_env() -> Option<Locale> {
+    env::var(LOCALE_ENV)
+        .ok()
+        .and_then(|value| Locale::parse(&value))
+}
+
+/// Looks up `template` in the active locale's catalog.
+pub fn translate(template: &'static str) -> &'static str {
+    translate_for(current(), template)
+}
+
+fn translate_for(locale: Locale, template: &'static str) -> &'static str {
+    locale
+        .catalog()
+        .and_then(|catalog| catalog.get(template).copied())
+        .unwrap_or(template)
+}
+
+/// Translates `template` and substitutes `args`; used by [`tr!`].
+pub fn format_message(template: &'static str, args: &[&dyn fmt::Display]) -> String {
+    substitute(translate(template), args)
+}
+
+fn substitute(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut next_implicit = 0;
+    let mut chars = template.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                output.push('}');
+            }
+            '{' => {
+                let mut position = String::new();
+                for inner in chars.by_ref() {
+                    if inner == '}' {
+                        break;
+                    }
+                    position.push(inner);
+                }
+                let index = if position.is_empty() {
+                    next_implicit += 1;
+                    next_implicit - 1
+                } else {
+                    position.parse().unwrap_or(usize::MAX)
+                };
+                match args.get(index) {
+                    Some(arg) => output.push_str(&arg.to_string()),
+                    None => {
+                        output.push('{');
+                        output.push_str(&position);
+                        output.push('}');
+                    }
+                }
+            }
+            ch => output.push(ch),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholder_count(template: &str) -> usize {
+        substitute(template, &[])
+            .matches('{')
+            .count()
+            .saturating_sub(template.matches("{{").count())
+    }
+
+    #[test]
+    fn parse_accepts_common_tag_shapes() {
+        assert_eq!(Locale::parse("de"), Some(Locale::German));
+        assert_eq!(Locale::parse("de_DE.UTF-8"), Some(Locale::German));
+        assert_eq!(Locale::parse("en-US"), Some(Locale::English));
+        assert_eq!(Locale::parse("C"), Some(Locale::English));
+        assert_eq!(Locale::parse("xx"), None);
+    }
+
+    #[test]
+    fn substitute_handles_implicit_explicit_and_escaped_placeholders() {
+        assert_eq!(substitute("`{}` at `{}`", &[&"a", &"b"]), "`a` at `b`");
+        assert_eq!(substitute("{1} before {0}", &[&"a", &"b"]), "b before a");
+        assert_eq!(substitute("{{literal}} {}", &[&1]), "{literal} 1");
+        assert_eq!(substitute("missing {}", &[]), "missing {}");
+    }
+
+    #[test]
+    fn german_catalog_falls_back_to_english() {
+        assert_eq!(
+            translate_for(Locale::German, "Removed worktree `{}` from `{}`."),
+            "Worktree `{}` aus `{}` entfernt."
+        );
+        assert_eq!(
+            translate_for(Locale::German, "untranslated"),
+            "untranslated"
+        );
+        assert_eq!(
+            translate_for(Locale::English, "Removed worktree `{}` from `{}`."),
+            "Removed worktree `{}` from `{}`."
+        );
+    }
+
+    #[test]
+    fn german_catalog_keeps_placeholder_counts() {
+        for (english, german) in de::MESSAGES {
+            assert_eq!(
+                placeholder_count(english),
+                placeholder_count(german),
+                "placeholder mismatch for `{english}`"
+            );
+        }
+    }
+
+    #[test]
+    fn resolve_locale_preference_reads_config() -> color_eyre::Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        git2::Repository::init(dir.path())?;
+        let repo = Repo::discover_from(dir.path())?;
+        let config_dir = repo.ensure_rsworktree_dir()?;
+        fs::write(config_dir.join(CONFIG_FILE_NAME), r#"{"locale": "de_DE"}"#)?;
+
+        assert_eq!(resolve_locale_preference(&repo), Locale::German);
+
+        Ok(())
+    }
+}
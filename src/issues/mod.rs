@@ -0,0 +1,333 @@
+//! Issue tracker lookups for `create --issue`.
+//!
+//! Configured under `issues` in `preferences.json`. The issue title is fetched
+//! from Jira or Linear with `curl` and turned into a worktree name such as
+//! `PROJ-123-fix-login-redirect`; the key is recorded with the branch so
+//! `review` can put it into the pull request title.
+//!
+//! ```json
+//! {
+//!   "issues": {
+//!     "backend": "jira",
+//!     "url": "https://example.atlassian.net",
+//!     "email": "me@example.com"
+//!   }
+//! }
+//! ```
+
+use std::{
+    env, fs,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::{editor::CONFIG_FILE_NAME, webhook::CURL_ENV};
+
+const LINEAR_API_URL: &str = "https://api.linear.app/graphql";
+const REQUEST_TIMEOUT_SECS: &str = "10";
+const DEFAULT_BRANCH_FORMAT: &str = "{key}-{title}";
+/// Longest slug taken from an issue title, in characters.
+const MAX_SLUG_LEN: usize = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum IssueBackend {
+    Jira,
+    Linear,
+}
+
+impl IssueBackend {
+    fn default_token_env(self) -> &'static str {
+        match self {
+            IssueBackend::Jira => "JIRA_API_TOKEN",
+            IssueBackend::Linear => "LINEAR_API_KEY",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FileFormat {
+    #[serde(default)]
+    issues: Option<IssueConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IssueConfig {
+    backend: IssueBackend,
+    /// Base URL of the Jira site; unused for Linear.
+    #[serde(default)]
+    url: Option<String>,
+    /// Jira account for basic authentication; without it the token is sent
+    /// as a bearer token, as Jira Data Center expects.
+    #[serde(default)]
+    email: Option<String>,
+    /// Environment variable holding the API token.
+    #[serde(default)]
+    token_env: Option<String>,
+    /// Worktree name built from `{key}` and the slugified `{title}`.
+    #[serde(default)]
+    branch_format: Option<String>,
+}
+
+/// An issue fetched from the tracker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Issue {
+    pub(crate) key: String,
+    pub(crate) title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct IssueTracker {
+    config: IssueConfig,
+}
+
+impl IssueTracker {
+    /// Reads `issues` from the `preferences.json` of `rsworktree_dir`.
+    pub(crate) fn load(rsworktree_dir: &Path) -> color_eyre::Result<Self> {
+        let path = rsworktree_dir.join(CONFIG_FILE_NAME);
+        let config = fs::read_to_string(&path)
+            .ok()
+            .map(|text| serde_json::from_str::<FileFormat>(&text))
+            .transpose()
+            .wrap_err_with(|| eyre::eyre!("failed to parse `{}`", path.display()))?
+            .and_then(|parsed| parsed.issues)
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "no issue tracker configured; add an `issues` section to `{}`",
+                    path.display()
+                )
+            })?;
+        Ok(Self { config })
+    }
+
+    /// Fetches the title of the issue `key`.
+    pub(crate) fn fetch(&self, key: &str) -> color_eyre::Result<Issue> {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(eyre::eyre!("the issue key must not be empty"));
+        }
+        let token_env = self
+            .config
+            .token_env
+            .as_deref()
+            .unwrap_or(self.config.backend.default_token_env());
+        let token = env::var(token_env)
+            .ok()
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| {
+                eyre::eyre!("set `{token_env}` to the API token of the issue tracker")
+            })?;
+
+        let title = match self.config.backend {
+            IssueBackend::Jira => {
+                let url = self.config.url.as_deref().ok_or_else(|| {
+                    eyre::eyre!("set `issues.url` to the address of the Jira site")
+                })?;
+                let mut request = vec![
+                    config_line("url", &jira_issue_url(url, key)),
+                    config_line("header", "Accept: application/json"),
+                ];
+                request.push(match &self.config.email {
+                    Some(email) => config_line("user", &format!("{email}:{token}")),
+                    None => config_line("header", &format!("Authorization: Bearer {token}")),
+                });
+                let response = request_json(&request)?;
+                response["fields"]["summary"].as_str().map(str::to_owned)
+            }
+            IssueBackend::Linear => {
+                let body = json!({
+                    "query": "query Issue($id: String!) { issue(id: $id) { title } }",
+                    "variables": { "id": key },
+                });
+                let response = request_json(&[
+                    config_line("url", LINEAR_API_URL),
+                    config_line("header", "Content-Type: application/json"),
+                    config_line("header", &format!("Authorization: {token}")),
+                    config_line("data-binary", &body.to_string()),
+                ])?;
+                response["data"]["issue"]["title"]
+                    .as_str()
+                    .map(str::to_owned)
+            }
+        };
+        let title = title.ok_or_else(|| eyre::eyre!("issue `{key}` was not found"))?;
+        Ok(Issue {
+            key: key.to_owned(),
+            title,
+        })
+    }
+
+    /// The worktree name for `issue`, following `issues.branchFormat`.
+    pub(crate) fn worktree_name(&self, issue: &Issue) -> String {
+        let format = self
+            .config
+            .branch_format
+            .as_deref()
+            .unwrap_or(DEFAULT_BRANCH_FORMAT);
+        let title = slugify(&issue.title);
+        let name = format
+            .replace("{key}", &issue.key)
+            .replace("{title}", &title);
+        // An empty slug would leave a dangling separator such as `PROJ-1-`.
+        name.trim_end_matches(['-', '_', '/']).to_owned()
+    }
+}
+
+fn jira_issue_url(site: &str, key: &str) -> String {
+    format!(
+        "{}/rest/api/2/issue/{key}?fields=summary",
+        site.trim_end_matches('/')
+    )
+}
+
+/// `title` in lowercase with runs of anything but ASCII letters and digits
+/// replaced by a single `-`, cut at a word boundary.
+pub(crate) fn slugify(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+    for word in title
+        .split(|ch: char| !ch.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        if !slug.is_empty() && slug.len() + 1 + word.len() > MAX_SLUG_LEN {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&word.to_ascii_lowercase());
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    slug
+}
+
+/// A line of a curl config file; keeps the token out of the process list.
+pub(crate) fn config_line(option: &str, value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("{option} = \"{escaped}\"")
+}
+
+fn request_json(config: &[String]) -> color_eyre::Result<Value> {
+    let program = env::var(CURL_ENV).unwrap_or_else(|_| "curl".to_owned());
+    let mut child = Command::new(&program)
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--max-time",
+            REQUEST_TIMEOUT_SECS,
+            "--config",
+            "-",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .wrap_err_with(|| eyre::eyre!("failed to run `{program}`"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(config.join("\n").as_bytes())
+            .wrap_err("failed to write the issue tracker request")?;
+    }
+
+    let result = child
+        .wait_with_output()
+        .wrap_err_with(|| eyre::eyre!("failed to wait for `{program}`"))?;
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        return Err(eyre::eyre!(
+            "failed to fetch the issue: `{program}` exited with {}: {}",
+            result.status,
+            stderr.trim()
+        ));
+    }
+    serde_json::from_slice(&result.stdout).wrap_err("the issue tracker returned invalid JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    fn tracker(branch_format: Option<&str>) -> IssueTracker {
+        IssueTracker {
+            config: IssueConfig {
+                backend: IssueBackend::Jira,
+                url: Some("https://example.atlassian.net/".into()),
+                email: None,
+                token_env: None,
+                branch_format: branch_format.map(str::to_owned),
+            },
+        }
+    }
+
+    #[test]
+    fn slugify_keeps_ascii_words_and_stops_at_a_word_boundary() {
+        assert_eq!(
+            slugify("Fix: login redirect (Safari) fails!"),
+            "fix-login-redirect-safari-fails"
+        );
+        assert_eq!(slugify("Über größe"), "ber-gr-e");
+        assert_eq!(slugify("  --  "), "");
+
+        let long = slugify(&"word ".repeat(20));
+        assert!(long.len() <= MAX_SLUG_LEN, "{long}");
+        assert!(long.ends_with("word"), "{long}");
+    }
+
+    #[test]
+    fn worktree_name_follows_the_branch_format() {
+        let issue = Issue {
+            key: "PROJ-123".into(),
+            title: "Fix login redirect".into(),
+        };
+        assert_eq!(
+            tracker(None).worktree_name(&issue),
+            "PROJ-123-fix-login-redirect"
+        );
+        assert_eq!(
+            tracker(Some("feature/{key}/{title}")).worktree_name(&issue),
+            "feature/PROJ-123/fix-login-redirect"
+        );
+
+        let untitled = Issue {
+            key: "PROJ-7".into(),
+            title: "???".into(),
+        };
+        assert_eq!(tracker(None).worktree_name(&untitled), "PROJ-7");
+    }
+
+    #[test]
+    fn load_reads_the_issues_section() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        assert!(IssueTracker::load(dir.path()).is_err());
+
+        fs::write(
+            dir.path().join(CONFIG_FILE_NAME),
+            r#"{"issues": {"backend": "linear", "tokenEnv": "MY_LINEAR_KEY"}}"#,
+        )?;
+        let tracker = IssueTracker::load(dir.path())?;
+        assert_eq!(tracker.config.backend, IssueBackend::Linear);
+        assert_eq!(tracker.config.token_env.as_deref(), Some("MY_LINEAR_KEY"));
+        Ok(())
+    }
+
+    #[test]
+    fn request_config_escapes_values() {
+        assert_eq!(
+            config_line("data-binary", r#"{"id": "A\B"}"#),
+            r#"data-binary = "{\"id\": \"A\\B\"}""#
+        );
+        assert_eq!(
+            jira_issue_url("https://example.atlassian.net/", "PROJ-1"),
+            "https://example.atlassian.net/rest/api/2/issue/PROJ-1?fields=summary"
+        );
+    }
+}
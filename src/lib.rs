@@ -1,12 +1,29 @@
+#[macro_use]
+pub mod i18n;
+mod annotations;
 pub mod cli;
 mod commands;
+pub mod config;
 pub mod editor;
 pub mod hooks;
+mod issues;
+mod manager;
+pub mod output;
+pub mod process;
 pub mod provider;
 mod repo;
 pub mod telemetry;
+pub mod templates;
+pub mod webhook;
+mod worktree;
 
 pub use commands::create;
+pub use commands::create::{CreateOutcome, CreateReport};
+pub use commands::rm::{LocalBranchStatus, RemoteBranchStatus, RemoveOutcome};
 pub use hooks::{HookContext, HookName, HookRunner};
+pub use manager::{
+    CreateOptions, CreatedWorktree, OpenOptions, OpenedWorktree, RemoveOptions, WorktreeManager,
+};
 pub use provider::GitProvider;
 pub use repo::Repo;
+pub use worktree::{ChangeSummary, UpstreamStatus, Worktree, WorktreeStatus};
@@ -1,6 +1,5 @@
 use rsworktree::cli;
 
 fn main() -> color_eyre::Result<()> {
-    color_eyre::install()?;
     cli::run()
 }
@@ -0,0 +1,462 @@
+//! Library API for embedding rsworktree in other tools.
+//!
+//! [`WorktreeManager`] runs the same logic as the CLI commands but returns
+//! typed results and keeps the progress messages of [`crate::output`] quiet.
+//! Hook processes still write to the inherited stdout and stderr.
+//!
+//! ```no_run
+//! use rsworktree::{CreateOptions, RemoveOptions, WorktreeManager};
+//!
+//! # fn main() -> color_eyre::Result<()> {
+//! let manager = WorktreeManager::discover()?;
+//! let created = manager.create(CreateOptions::new("feature/login").with_base("main"))?;
+//! println!("{}", created.worktree.path.display());
+//!
+//! for worktree in manager.list()? {
+//!     println!("{} dirty={}", worktree.name, !worktree.status.changes.is_clean());
+//! }
+//!
+//! manager.remove(RemoveOptions::new("feature/login").with_delete_branch(true))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use color_eyre::eyre::{self, WrapErr};
+
+use crate::{
+    Repo,
+    commands::{
+        create::{CreateCommand, CreateOutcome},
+        open::resolve_by_name,
+        rm::{RemoveCommand, RemoveOutcome},
+    },
+    editor::{LaunchOutcome, LaunchWait, launch_worktree_with},
+    hooks::HookName,
+    output,
+    process::{CommandRunner, SystemCommandRunner},
+    templates::Template,
+    worktree::{self, Worktree},
+};
+
+/// Creates, lists, removes and opens the worktrees of one repository.
+#[derive(Debug)]
+pub struct WorktreeManager<R = SystemCommandRunner> {
+    repo: Repo,
+    /// Runs git for [`WorktreeManager::diff`] and [`WorktreeManager::log`],
+    /// and the editor for [`WorktreeManager::open`].
+    runner: RefCell<R>,
+}
+
+impl WorktreeManager {
+    pub fn new(repo: Repo) -> Self {
+        Self::with_runner(repo, SystemCommandRunner)
+    }
+
+    /// Manages the repository containing the current directory.
+    pub fn discover() -> color_eyre::Result<Self> {
+        Repo::discover().map(Self::new)
+    }
+}
+
+impl<R: CommandRunner> WorktreeManager<R> {
+    /// Manages `repo`, running the git commands through `runner`.
+    pub fn with_runner(repo: Repo, runner: R) -> Self {
+        Self {
+            repo,
+            runner: RefCell::new(runner),
+        }
+    }
+
+    pub fn repo(&self) -> &Repo {
+        &self.repo
+    }
+
+    /// Every worktree: those under `.rsworktree` sorted by name, then the
+    /// unmanaged ones `git worktree list` reports.
+    pub fn list(&self) -> color_eyre::Result<Vec<Worktree>> {
+        worktree::load_all(&self.repo)
+    }
+
+    /// Looks up a worktree by its full name or an unambiguous last segment.
+    pub fn get(&self, name: &str) -> color_eyre::Result<Worktree> {
+        let resolved = resolve_by_name(name, &self.repo)?;
+        Worktree::load(&self.repo, resolved.name, resolved.path)
+    }
+
+    /// Creates a worktree, running hooks and copying files like `rsworktree create`.
+    pub fn create(&self, options: CreateOptions) -> color_eyre::Result<CreatedWorktree> {
+        let template = options
+            .template
+            .as_deref()
+            .map(|name| Template::load(&self.repo.ensure_rsworktree_dir()?, name))
+            .transpose()?;
+        let command = CreateCommand::new(options.name, options.base)
+            .with_exists_ok(options.exists_ok)
+            .with_copy(options.copy)
+            .with_hook_timeout(options.hook_timeout)
+            .with_from_remote(options.from_remote)
+            .with_template(template);
+        let report = output::silenced(|| command.create_with_report(&self.repo, true))?;
+
+        let worktree = Worktree::load(&self.repo, report.name, report.path)?;
+        Ok(CreatedWorktree {
+            worktree,
+            outcome: report.outcome,
+            hooks: report.hooks,
+            duration: report.duration,
+        })
+    }
+
+    /// Removes a worktree, refusing uncommitted changes unless forced.
+    pub fn remove(&self, options: RemoveOptions) -> color_eyre::Result<RemoveOutcome> {
+        let resolved = resolve_by_name(&options.name, &self.repo)?;
+        let command = RemoveCommand::for_resolved(&self.repo, resolved, options.force)
+            .with_quiet(true)
+            .with_spawn_shell(false)
+            .with_remove_local_branch(options.delete_branch || options.delete_remote_branch)
+            .with_remove_remote_branch(options.delete_remote_branch);
+        output::silenced(|| command.execute(&self.repo))
+    }
+
+    /// Starts the configured editor for a worktree.
+    ///
+    /// Editor problems such as a missing command are reported through
+    /// [`LaunchOutcome::status`] rather than as an error.
+    pub fn open(&self, options: OpenOptions) -> color_eyre::Result<OpenedWorktree> {
+        let resolved = resolve_by_name(&options.name, &self.repo)?;
+        let wait = if options.wait {
+            LaunchWait::Process
+        } else {
+            LaunchWait::Detached
+        };
+        let launch = launch_worktree_with(
+            &self.repo,
+            &resolved.name,
+            &resolved.path,
+            wait,
+            &mut *self.runner.borrow_mut(),
+        )
+        .wrap_err_with(|| eyre::eyre!("failed to open worktree `{}`", resolved.name))?;
+        Ok(OpenedWorktree {
+            name: resolved.name,
+            path: resolved.path,
+            launch,
+        })
+    }
+
+    /// Uncommitted changes of a worktree against its `HEAD`, as `git diff` prints them.
+    pub fn diff(&self, name: &str) -> color_eyre::Result<String> {
+        let resolved = resolve_by_name(name, &self.repo)?;
+        self.git_output(&resolved.path, &["diff", "--no-color", "HEAD"])
+    }
+
+    /// The last `limit` commits of a worktree, one line each.
+    pub fn log(&self, name: &str, limit: usize) -> color_eyre::Result<String> {
+        let resolved = resolve_by_name(name, &self.repo)?;
+        let limit = format!("--max-count={limit}");
+        self.git_output(
+            &resolved.path,
+            &["log", "--no-color", "--oneline", "--decorate", &limit],
+        )
+    }
+
+    fn git_output(&self, dir: &Path, args: &[&str]) -> color_eyre::Result<String> {
+        let args = args.iter().map(|arg| (*arg).to_owned()).collect::<Vec<_>>();
+        let output = self
+            .runner
+            .borrow_mut()
+            .run("git", dir, &args)
+            .wrap_err_with(|| eyre::eyre!("failed to run `git {}`", args.join(" ")))?;
+        if !output.success {
+            return Err(eyre::eyre!(
+                "`git {}` failed: {}",
+                args.join(" "),
+                output.stderr.trim()
+            ));
+        }
+        Ok(output.stdout)
+    }
+}
+
+/// What [`WorktreeManager::create`] should create.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CreateOptions {
+    name: String,
+    base: Option<String>,
+    exists_ok: bool,
+    copy: Vec<String>,
+    hook_timeout: Option<Duration>,
+    from_remote: Option<String>,
+    template: Option<String>,
+}
+
+impl CreateOptions {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Branch to start from instead of the current `HEAD`.
+    pub fn with_base(mut self, base: impl Into<String>) -> Self {
+        self.base = Some(base.into());
+        self
+    }
+
+    /// Succeed with [`CreateOutcome::AlreadyExists`] when a matching worktree exists.
+    pub fn with_exists_ok(mut self, exists_ok: bool) -> Self {
+        self.exists_ok = exists_ok;
+        self
+    }
+
+    /// Globs of untracked files to copy from the main worktree.
+    pub fn with_copy(mut self, patterns: Vec<String>) -> Self {
+        self.copy = patterns;
+        self
+    }
+
+    /// Kill the `post-create` hook after this long instead of after `hooks.timeout`.
+    pub fn with_hook_timeout(mut self, timeout: Duration) -> Self {
+        self.hook_timeout = Some(timeout);
+        self
+    }
+
+    /// Check out a remote branch such as `origin/feature/x` as a tracking branch.
+    pub fn with_from_remote(mut self, branch: impl Into<String>) -> Self {
+        self.from_remote = Some(branch.into());
+        self
+    }
+
+    /// Apply `.rsworktree/templates/<name>.toml`.
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatedWorktree {
+    pub worktree: Worktree,
+    pub outcome: CreateOutcome,
+    /// The hooks that ran, in order.
+    pub hooks: Vec<HookName>,
+    /// How long the creation took, hooks included.
+    pub duration: Duration,
+}
+
+/// What [`WorktreeManager::remove`] should remove.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoveOptions {
+    name: String,
+    force: bool,
+    delete_branch: bool,
+    delete_remote_branch: bool,
+}
+
+impl RemoveOptions {
+    /// Removes the worktree called `name`, which may be shortened like in `open`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Discard uncommitted changes and skip the merge check for the remote branch.
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    pub fn with_delete_branch(mut self, delete: bool) -> Self {
+        self.delete_branch = delete;
+        self
+    }
+
+    /// Also delete the branch on its remote; implies deleting the local branch.
+    pub fn with_delete_remote_branch(mut self, delete: bool) -> Self {
+        self.delete_remote_branch = delete;
+        self
+    }
+}
+
+/// What [`WorktreeManager::open`] should open.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpenOptions {
+    name: String,
+    wait: bool,
+}
+
+impl OpenOptions {
+    /// Opens the worktree called `name`, which may be shortened like in `open`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            wait: false,
+        }
+    }
+
+    /// Block until the editor process exits.
+    pub fn with_wait(mut self, wait: bool) -> Self {
+        self.wait = wait;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenedWorktree {
+    pub name: String,
+    pub path: PathBuf,
+    pub launch: LaunchOutcome,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use crate::{
+        commands::rm::LocalBranchStatus,
+        process::{CommandOutput, ScriptedRunner},
+        telemetry::EditorLaunchStatus,
+    };
+
+    fn git(dir: &Path, args: &[&str]) -> color_eyre::Result<()> {
+        let args = ["-c", "user.name=Test", "-c", "user.email=test@example.com"]
+            .iter()
+            .chain(args)
+            .map(|arg| (*arg).to_owned())
+            .collect::<Vec<_>>();
+        let output = SystemCommandRunner.run("git", dir, &args)?;
+        assert!(output.success, "git {args:?} failed: {}", output.stderr);
+        Ok(())
+    }
+
+    fn init_repo() -> color_eyre::Result<(TempDir, WorktreeManager)> {
+        let dir = TempDir::new()?;
+        git(dir.path(), &["init"])?;
+        git(dir.path(), &["commit", "--allow-empty", "-m", "init"])?;
+        let manager = WorktreeManager::new(Repo::discover_from(dir.path())?);
+        Ok((dir, manager))
+    }
+
+    #[test]
+    fn create_list_and_remove_return_typed_results() -> color_eyre::Result<()> {
+        let (_dir, manager) = init_repo()?;
+
+        let created = manager.create(CreateOptions::new("feature/api"))?;
+        assert_eq!(created.outcome, CreateOutcome::Created);
+        assert!(created.hooks.is_empty());
+        assert_eq!(created.worktree.name, "feature/api");
+        assert_eq!(
+            created.worktree.status.branch.as_deref(),
+            Some("feature/api")
+        );
+
+        let again = manager.create(CreateOptions::new("feature/api").with_exists_ok(true))?;
+        assert_eq!(again.outcome, CreateOutcome::AlreadyExists);
+
+        fs::write(created.worktree.path.join("notes.txt"), "wip")?;
+        let listed = manager.list()?;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].status.changes.untracked, 1);
+        assert_eq!(manager.get("api")?.name, "feature/api");
+
+        assert!(manager.remove(RemoveOptions::new("api")).is_err());
+        let removed = manager.remove(
+            RemoveOptions::new("api")
+                .with_force(true)
+                .with_delete_branch(true),
+        )?;
+        assert_eq!(removed.local_branch, Some(LocalBranchStatus::Deleted));
+        assert!(manager.list()?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn diff_and_log_read_the_worktree_history() -> color_eyre::Result<()> {
+        let (_dir, manager) = init_repo()?;
+        let created = manager.create(CreateOptions::new("feature/log"))?;
+        fs::write(created.worktree.path.join("README.md"), "hello\n")?;
+        git(
+            &created.worktree.path,
+            &["commit", "--allow-empty", "-m", "second"],
+        )?;
+
+        let log = manager.log("log", 10)?;
+        assert_eq!(log.lines().count(), 2);
+        assert!(log.lines().next().unwrap().ends_with("second"));
+        assert_eq!(manager.log("log", 1)?.lines().count(), 1);
+
+        assert!(manager.diff("log")?.is_empty());
+        git(&created.worktree.path, &["add", "README.md"])?;
+        assert!(manager.diff("log")?.contains("+hello"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn diff_runs_git_through_the_runner() -> color_eyre::Result<()> {
+        let (dir, manager) = init_repo()?;
+        let created = manager.create(CreateOptions::new("feature/scripted"))?;
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::success("+scripted\n"))
+            .push(CommandOutput::failure(128, "fatal: bad revision"));
+        let manager =
+            WorktreeManager::with_runner(Repo::discover_from(dir.path())?, runner.clone());
+
+        assert_eq!(manager.diff("scripted")?, "+scripted\n");
+        let error = manager
+            .log("scripted", 5)
+            .expect_err("git log was scripted to fail");
+        assert!(error.to_string().contains("fatal: bad revision"));
+
+        let calls = runner.calls();
+        assert_eq!(calls[0].current_dir, created.worktree.path);
+        assert_eq!(calls[0].args, ["diff", "--no-color", "HEAD"]);
+        assert_eq!(calls[1].args[0], "log");
+        Ok(())
+    }
+
+    #[test]
+    fn open_starts_the_editor_through_the_runner() -> color_eyre::Result<()> {
+        let (dir, manager) = init_repo()?;
+        let created = manager.create(CreateOptions::new("feature/editor"))?;
+        let rsworktree_dir = manager.repo().ensure_rsworktree_dir()?;
+        fs::write(
+            rsworktree_dir.join(crate::config::CONFIG_FILE),
+            "editor = \"scripted-editor --new-window\"\n",
+        )?;
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success(""));
+        let manager =
+            WorktreeManager::with_runner(Repo::discover_from(dir.path())?, runner.clone());
+
+        let opened = manager.open(OpenOptions::new("editor").with_wait(true))?;
+
+        assert_eq!(opened.launch.status, EditorLaunchStatus::Success);
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].program, "scripted-editor");
+        assert_eq!(
+            calls[0].args,
+            [
+                "--new-window".to_owned(),
+                created.worktree.path.display().to_string()
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,348 @@
+//! Central writer for user-facing output.
+//!
+//! Commands report progress through the functions in this module instead of
+//! calling `println!` directly, so the presentation can be switched in one
+//! place. In [`OutputMode::Plain`] colors are disabled globally and every
+//! message is printed on its own line behind a textual label (`ok:`,
+//! `warning:`, ...), which reads well with screen readers and in dumb
+//! terminals.
+
+pub mod porcelain;
+mod progress;
+mod table;
+
+use std::{
+    cell::Cell as StdCell,
+    env,
+    fmt::Display,
+    io::{self, Write},
+    sync::OnceLock,
+};
+
+use owo_colors::{OwoColorize, Stream};
+
+pub use self::{
+    progress::{NO_PROGRESS_ENV, disable_progress, step},
+    table::{Cell, Table},
+};
+
+/// Environment variable forcing plain output when set to a truthy value.
+pub const PLAIN_ENV: &str = "RSWORKTREE_PLAIN";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    #[default]
+    Styled,
+    Plain,
+}
+
+impl OutputMode {
+    /// Picks the mode from the `--plain` flag and the environment.
+    ///
+    /// Plain output is used when the flag is given, when `RSWORKTREE_PLAIN`
+    /// is truthy, when `TERM` is `dumb`, or when the desktop reports an
+    /// active screen reader through `ACCESSIBILITY_ENABLED=1`.
+    pub fn detect(plain_flag: bool) -> Self {
+        let env_value = |key: &str| env::var(key).ok();
+        Self::detect_with(plain_flag, env_value)
+    }
+
+    fn detect_with(plain_flag: bool, env_value: impl Fn(&str) -> Option<String>) -> Self {
+        let truthy = |value: Option<String>| {
+            value.is_some_and(|value| {
+                matches!(
+                    value.trim().to_ascii_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+        };
+
+        if plain_flag
+            || truthy(env_value(PLAIN_ENV))
+            || truthy(env_value("ACCESSIBILITY_ENABLED"))
+            || env_value("TERM").is_some_and(|term| term == "dumb")
+        {
+            OutputMode::Plain
+        } else {
+            OutputMode::Styled
+        }
+    }
+}
+
+static ACTIVE_MODE: OnceLock<OutputMode> = OnceLock::new();
+
+thread_local! {
+    static SILENCED: StdCell<bool> = const { StdCell::new(false) };
+}
+
+/// Selects the output mode for the rest of the process. Only the first call has an effect.
+pub fn init(mode: OutputMode) {
+    if ACTIVE_MODE.set(mode).is_ok() && mode == OutputMode::Plain {
+        owo_colors::set_override(false);
+    }
+}
+
+/// Installs the error report hook matching the active mode.
+pub fn install_error_hook() -> color_eyre::Result<()> {
+    let builder = color_eyre::config::HookBuilder::default();
+    let builder = if is_plain() {
+        builder.theme(color_eyre::config::Theme::new())
+    } else {
+        builder
+    };
+    builder.install()
+}
+
+pub fn mode() -> OutputMode {
+    ACTIVE_MODE.get().copied().unwrap_or_default()
+}
+
+pub fn is_plain() -> bool {
+    mode() == OutputMode::Plain
+}
+
+/// Runs `f` with every message of this module suppressed on the current thread.
+///
+/// Library callers such as [`crate::WorktreeManager`] use this to reuse the
+/// commands without their progress output. Output of hook processes is not
+/// affected.
+pub fn silenced<T>(f: impl FnOnce() -> T) -> T {
+    let previous = SILENCED.with(|silenced| silenced.replace(true));
+    let result = f();
+    SILENCED.with(|silenced| silenced.set(previous));
+    result
+}
+
+fn is_silenced() -> bool {
+    SILENCED.with(StdCell::get)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Info,
+    Success,
+    Note,
+    Warning,
+    Hint,
+    Error,
+}
+
+impl Level {
+    fn label(self) -> &'static str {
+        match self {
+            Level::Info => "info",
+            Level::Success => "ok",
+            Level::Note => "note",
+            Level::Warning => "warning",
+            Level::Hint => "hint",
+            Level::Error => "error",
+        }
+    }
+
+    fn stream(self) -> Stream {
+        match self {
+            Level::Info | Level::Success | Level::Note => Stream::Stdout,
+            Level::Warning | Level::Hint | Level::Error => Stream::Stderr,
+        }
+    }
+}
+
+fn render(mode: OutputMode, level: Level, message: &str) -> String {
+    match mode {
+        OutputMode::Plain => format!("{}: {message}", level.label()),
+        OutputMode::Styled => match level {
+            Level::Info | Level::Success | Level::Error => message.to_owned(),
+            Level::Note => paint(level, message, |text| format!("{}", text.yellow())),
+            Level::Warning => paint(level, &tr!("Warning: {}", message), |text| {
+                format!("{}", text.yellow())
+            }),
+            Level::Hint => paint(level, &tr!("hint: {}", message), |text| {
+                format!("{}", text.dimmed())
+            }),
+        },
+    }
+}
+
+fn paint(level: Level, message: &str, style: impl Fn(&str) -> String) -> String {
+    message
+        .if_supports_color(level.stream(), |text| style(text))
+        .to_string()
+}
+
+fn emit(level: Level, message: impl Display) {
+    if is_silenced() {
+        return;
+    }
+    let line = render(mode(), level, &message.to_string());
+    progress::above_spinner(|| match level.stream() {
+        Stream::Stdout => println!("{line}"),
+        _ => eprintln!("{line}"),
+    });
+}
+
+/// Neutral progress or status message.
+pub fn info(message: impl Display) {
+    emit(Level::Info, message);
+}
+
+/// A completed action.
+pub fn success(message: impl Display) {
+    emit(Level::Success, message);
+}
+
+/// Something the user should notice but that does not indicate a problem.
+pub fn note(message: impl Display) {
+    emit(Level::Note, message);
+}
+
+/// A problem that did not stop the command; printed with a `Warning:` prefix.
+pub fn warn(message: impl Display) {
+    emit(Level::Warning, message);
+}
+
+/// Follow-up advice for a preceding warning or error; printed with a `hint:` prefix.
+pub fn hint(message: impl Display) {
+    emit(Level::Hint, message);
+}
+
+/// A failure reported before the command returns its error.
+pub fn error(message: impl Display) {
+    emit(Level::Error, message);
+}
+
+/// Prints a question without a trailing newline and flushes stdout.
+pub fn prompt(message: impl Display) -> io::Result<()> {
+    let message = message.to_string();
+    progress::above_spinner(|| {
+        match mode() {
+            OutputMode::Plain => print!("question: {message}"),
+            OutputMode::Styled => print!("{message}"),
+        }
+        io::stdout().flush()
+    })
+}
+
+/// A heading introducing the lines that follow.
+pub fn heading(message: impl Display) {
+    if is_silenced() {
+        return;
+    }
+    let message = message.to_string();
+    progress::above_spinner(|| match mode() {
+        OutputMode::Plain => println!("{message}"),
+        OutputMode::Styled => println!(
+            "{}",
+            message.if_supports_color(Stream::Stdout, |text| format!("{}", text.bold()))
+        ),
+    });
+}
+
+/// One entry of a list, rendered as a bullet unless output is plain.
+pub fn item(message: impl Display) {
+    if is_silenced() {
+        return;
+    }
+    progress::above_spinner(|| match mode() {
+        OutputMode::Plain => println!("{message}"),
+        OutputMode::Styled => println!("- {message}"),
+    });
+}
+
+/// A labelled value of a report, aligned in a column unless output is plain.
+pub fn field(label: &str, value: impl Display) {
+    if is_silenced() {
+        return;
+    }
+    let line = render_field(mode(), label, &value.to_string());
+    progress::above_spinner(|| println!("{line}"));
+}
+
+fn render_field(mode: OutputMode, label: &str, value: &str) -> String {
+    match mode {
+        OutputMode::Plain if label.is_empty() => value.to_owned(),
+        OutputMode::Plain => format!("{label}: {value}"),
+        OutputMode::Styled => {
+            let label = if label.is_empty() {
+                String::new()
+            } else {
+                format!("{label}:")
+            };
+            format!("  {label:<12}{value}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_from<'a>(pairs: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| {
+            pairs
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| (*value).to_owned())
+        }
+    }
+
+    #[test]
+    fn detect_honours_flag_and_environment() {
+        assert_eq!(
+            OutputMode::detect_with(false, env_from(&[])),
+            OutputMode::Styled
+        );
+        assert_eq!(
+            OutputMode::detect_with(true, env_from(&[])),
+            OutputMode::Plain
+        );
+        assert_eq!(
+            OutputMode::detect_with(false, env_from(&[(PLAIN_ENV, "1")])),
+            OutputMode::Plain
+        );
+        assert_eq!(
+            OutputMode::detect_with(false, env_from(&[(PLAIN_ENV, "0")])),
+            OutputMode::Styled
+        );
+        assert_eq!(
+            OutputMode::detect_with(false, env_from(&[("TERM", "dumb")])),
+            OutputMode::Plain
+        );
+        assert_eq!(
+            OutputMode::detect_with(false, env_from(&[("ACCESSIBILITY_ENABLED", "1")])),
+            OutputMode::Plain
+        );
+    }
+
+    #[test]
+    fn plain_messages_are_prefixed_with_their_level() {
+        assert_eq!(
+            render(OutputMode::Plain, Level::Success, "Created worktree `a`."),
+            "ok: Created worktree `a`."
+        );
+        assert_eq!(
+            render(OutputMode::Plain, Level::Warning, "rollback incomplete"),
+            "warning: rollback incomplete"
+        );
+        assert_eq!(
+            render(
+                OutputMode::Plain,
+                Level::Info,
+                "Running post-create hook..."
+            ),
+            "info: Running post-create hook..."
+        );
+    }
+
+    #[test]
+    fn plain_fields_are_not_padded() {
+        assert_eq!(
+            render_field(OutputMode::Plain, "Branch", "main"),
+            "Branch: main"
+        );
+        assert_eq!(
+            render_field(OutputMode::Styled, "Branch", "main"),
+            "  Branch:     main"
+        );
+        assert_eq!(render_field(OutputMode::Plain, "", "next"), "next");
+    }
+}
@@ -0,0 +1,61 @@
+//! `--porcelain` output for scripts and shell prompts: a versioned header,
+//! then one tab-separated record per line, starting with the kind of record
+//! (tabs shown as `\t`):
+//!
+//! ```text
+//! # rsworktree porcelain v1
+//! worktree\tfeature/login\t/repo/.rsworktree/feature/login\tfeature/login\t3f2c9a1...\tdirty\t\t\t\tmanaged
+//! ```
+//!
+//! Within a version, fields are only ever appended to a record; anything that
+//! would break a parser reading the existing fields bumps [`VERSION`].
+
+/// Version of the record layouts, printed in [`header`].
+pub const VERSION: u32 = 1;
+
+/// The line every porcelain output starts with.
+pub fn header() -> String {
+    format!("# rsworktree porcelain v{VERSION}")
+}
+
+/// `kind` and `fields` joined by tabs. Missing values are empty fields, and
+/// tabs or line breaks inside a field become spaces so a record stays on one
+/// line.
+pub fn record<I, S>(kind: &str, fields: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut line = kind.to_owned();
+    for field in fields {
+        line.push('\t');
+        line.extend(field.as_ref().chars().map(|ch| match ch {
+            '\t' | '\n' | '\r' => ' ',
+            ch => ch,
+        }));
+    }
+    line
+}
+
+/// Prints the header followed by `records`, bypassing the styled output.
+pub fn print(records: impl IntoIterator<Item = String>) {
+    println!("{}", header());
+    for record in records {
+        println!("{record}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_are_tab_separated_and_single_line() {
+        assert_eq!(header(), "# rsworktree porcelain v1");
+        assert_eq!(
+            record("created", ["feature/login", "", "a\tb\nc"]),
+            "created\tfeature/login\t\ta b c"
+        );
+        assert_eq!(record::<_, &str>("removed", []), "removed");
+    }
+}
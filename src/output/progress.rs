@@ -0,0 +1,140 @@
+//! Spinners shown while a long-running step, such as a fetch, is in progress.
+//!
+//! On an interactive stderr, [`step`] shows an `indicatif` spinner next to its
+//! message until the step returns and then clears the line. Messages printed
+//! in the meantime are written above the spinner. With `--no-progress`, in
+//! plain output, when stderr is not a terminal, when `RSWORKTREE_NO_PROGRESS`
+//! is truthy or when `CI` is set, the spinner is hidden and the message is
+//! printed once as an `info` line instead, which keeps CI logs readable.
+
+use std::{
+    env,
+    fmt::Display,
+    io::{self, IsTerminal},
+    sync::{
+        Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// Environment variable disabling spinners when set to a truthy value.
+pub const NO_PROGRESS_ENV: &str = "RSWORKTREE_NO_PROGRESS";
+
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// The spinner of the innermost running step.
+static ACTIVE: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+/// Turns spinners off for the rest of the process, for `--no-progress`.
+pub fn disable_progress() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Runs `f` while a spinner shows `message`, or prints `message` first when
+/// spinners are off.
+pub fn step<T>(message: impl Display, f: impl FnOnce() -> T) -> T {
+    if super::is_silenced() {
+        return f();
+    }
+    let spinner = ProgressBar::with_draw_target(None, draw_target());
+    if spinner.is_hidden() {
+        super::info(message);
+        return f();
+    }
+    spinner.set_style(ProgressStyle::default_spinner());
+    spinner.set_message(message.to_string());
+    spinner.enable_steady_tick(TICK_INTERVAL);
+
+    let outer = active().replace(spinner.clone());
+    let result = f();
+    *active() = outer;
+    spinner.finish_and_clear();
+    result
+}
+
+fn draw_target() -> ProgressDrawTarget {
+    if spinners_enabled() {
+        ProgressDrawTarget::stderr()
+    } else {
+        ProgressDrawTarget::hidden()
+    }
+}
+
+fn spinners_enabled() -> bool {
+    !DISABLED.load(Ordering::Relaxed)
+        && !super::is_plain()
+        && io::stderr().is_terminal()
+        && !disabled_by_env(|key| env::var(key).ok())
+}
+
+fn disabled_by_env(env_value: impl Fn(&str) -> Option<String>) -> bool {
+    let truthy = |value: Option<String>| {
+        value.is_some_and(|value| {
+            matches!(
+                value.trim().to_ascii_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            )
+        })
+    };
+    truthy(env_value(NO_PROGRESS_ENV))
+        || env_value("CI").is_some_and(|value| !matches!(value.trim(), "" | "0" | "false"))
+}
+
+fn active() -> MutexGuard<'static, Option<ProgressBar>> {
+    ACTIVE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Runs `print` with the spinner hidden, so its output does not run into the
+/// spinner.
+pub(super) fn above_spinner<T>(print: impl FnOnce() -> T) -> T {
+    let spinner = active().clone();
+    match spinner {
+        Some(spinner) => spinner.suspend(print),
+        None => print(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_from<'a>(pairs: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| {
+            pairs
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| (*value).to_owned())
+        }
+    }
+
+    #[test]
+    fn ci_and_no_progress_env_disable_spinners() {
+        assert!(!disabled_by_env(env_from(&[])));
+        assert!(disabled_by_env(env_from(&[(NO_PROGRESS_ENV, "1")])));
+        assert!(!disabled_by_env(env_from(&[(NO_PROGRESS_ENV, "0")])));
+        assert!(disabled_by_env(env_from(&[("CI", "true")])));
+        assert!(disabled_by_env(env_from(&[("CI", "woodpecker")])));
+        assert!(!disabled_by_env(env_from(&[("CI", "false")])));
+    }
+
+    #[test]
+    fn no_progress_hides_the_spinner() {
+        disable_progress();
+        assert!(draw_target().is_hidden());
+        assert_eq!(above_spinner(|| 7), 7);
+    }
+
+    #[test]
+    fn step_returns_the_result_of_the_step() {
+        assert_eq!(step("Counting...", || 42), 42);
+        assert_eq!(
+            super::super::silenced(|| step("Counting...", || "done")),
+            "done"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! A small column-aligned table for listings such as `ls`.
+//!
+//! Cells are padded before they are painted so escape codes never skew the
+//! columns, and colors are only applied when stdout supports them.
+
+use owo_colors::{OwoColorize, Stream};
+
+/// Gap between two columns.
+const COLUMN_GAP: &str = "  ";
+
+/// Styles the already padded text of a cell, e.g. `|text| text.green().to_string()`.
+pub type CellStyle = fn(&str) -> String;
+
+#[derive(Debug, Clone)]
+pub struct Cell {
+    text: String,
+    style: Option<CellStyle>,
+}
+
+impl Cell {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            style: None,
+        }
+    }
+
+    pub fn with_style(mut self, style: CellStyle) -> Self {
+        self.style = Some(style);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    header: Option<Vec<String>>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Column titles printed above the rows, painted bold.
+    pub fn with_header(mut self, header: Vec<String>) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    pub fn push_row(&mut self, row: Vec<Cell>) {
+        self.rows.push(row);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// The aligned lines of the table, without trailing whitespace.
+    pub fn render(&self) -> Vec<String> {
+        let header = self
+            .header
+            .as_ref()
+            .map(|header| header.iter().cloned().map(Cell::new).collect::<Vec<_>>());
+        let rows = header.iter().chain(&self.rows).collect::<Vec<_>>();
+        let columns = rows.iter().map(|row| row.len()).max().unwrap_or_default();
+        let widths = (0..columns)
+            .map(|column| {
+                rows.iter()
+                    .filter_map(|row| row.get(column))
+                    .map(|cell| cell.text.chars().count())
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        rows.iter()
+            .enumerate()
+            .map(|(index, row)| {
+                let is_header = index == 0 && header.is_some();
+                let last = row.len().saturating_sub(1);
+                let line = row
+                    .iter()
+                    .enumerate()
+                    .map(|(column, cell)| {
+                        // The last column is not padded, so lines carry no trailing spaces.
+                        let text = if column == last {
+                            cell.text.clone()
+                        } else {
+                            format!("{:<width$}", cell.text, width = widths[column])
+                        };
+                        let style = if is_header {
+                            Some(bold as CellStyle)
+                        } else {
+                            cell.style
+                        };
+                        match style {
+                            Some(style) => paint(&text, style),
+                            None => text,
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(COLUMN_GAP);
+                line.trim_end().to_owned()
+            })
+            .collect()
+    }
+}
+
+fn bold(text: &str) -> String {
+    format!("{}", text.bold())
+}
+
+fn paint(text: &str, style: CellStyle) -> String {
+    text.if_supports_color(Stream::Stdout, |text| style(text))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_aligns_columns_and_trims_lines() {
+        let mut table =
+            Table::new().with_header(vec!["NAME".into(), "BRANCH".into(), "AGE".into()]);
+        table.push_row(vec![
+            Cell::new("feature/login"),
+            Cell::new("login"),
+            Cell::new("2h ago"),
+        ]);
+        table.push_row(vec![Cell::new("hotfix"), Cell::new(""), Cell::new("")]);
+
+        assert_eq!(
+            table.render(),
+            [
+                "NAME           BRANCH  AGE",
+                "feature/login  login   2h ago",
+                "hotfix",
+            ]
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! Running external programs: git, the provider CLIs, tmux and editors.
+//!
+//! Code that runs them goes through a [`CommandRunner`], so tests and
+//! embedders can script or intercept every command instead of spawning it.
+
+use std::{
+    collections::VecDeque,
+    fmt, io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+
+/// What a command printed and how it exited.
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub success: bool,
+    pub status_code: Option<i32>,
+}
+
+impl CommandOutput {
+    /// A successful exit printing `stdout`.
+    pub fn success(stdout: impl Into<String>) -> Self {
+        Self {
+            stdout: stdout.into(),
+            stderr: String::new(),
+            success: true,
+            status_code: Some(0),
+        }
+    }
+
+    /// An exit with `code` printing `stderr`.
+    pub fn failure(code: i32, stderr: impl Into<String>) -> Self {
+        Self {
+            stdout: String::new(),
+            stderr: stderr.into(),
+            success: false,
+            status_code: Some(code),
+        }
+    }
+}
+
+impl fmt::Display for CommandOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "success: {}, status_code: {:?}, stdout: {:?}, stderr: {:?}",
+            self.success, self.status_code, self.stdout, self.stderr
+        )
+    }
+}
+
+pub trait CommandRunner {
+    /// Runs `program` with `args` in `current_dir` and captures its output.
+    fn run(
+        &mut self,
+        program: &str,
+        current_dir: &Path,
+        args: &[String],
+    ) -> color_eyre::Result<CommandOutput>;
+
+    /// Runs `command` on the terminal, as an editor in the foreground, and
+    /// waits for it to exit; nothing is captured.
+    ///
+    /// By default this goes through [`CommandRunner::run`] without the
+    /// environment set on `command`, which is what a runner recording calls
+    /// wants.
+    fn status(&mut self, command: &mut Command) -> io::Result<CommandOutput> {
+        let (program, current_dir, args) = parts(command);
+        self.run(&program, &current_dir, &args)
+            .map_err(|error| io::Error::other(error.to_string()))
+    }
+
+    /// Starts `command` detached from the terminal without waiting for it.
+    ///
+    /// By default this goes through [`CommandRunner::run`] like
+    /// [`CommandRunner::status`].
+    fn spawn(&mut self, command: &mut Command) -> io::Result<()> {
+        self.status(command).map(|_| ())
+    }
+}
+
+/// Runs commands for real.
+#[derive(Debug, Clone, Default)]
+pub struct SystemCommandRunner;
+
+impl CommandRunner for SystemCommandRunner {
+    fn run(
+        &mut self,
+        program: &str,
+        current_dir: &Path,
+        args: &[String],
+    ) -> color_eyre::Result<CommandOutput> {
+        let _span = tracing::info_span!("run", command = %format_command(program, args)).entered();
+        let output = Command::new(program)
+            .current_dir(current_dir)
+            .args(args)
+            .output()
+            .wrap_err_with(|| {
+                eyre::eyre!("failed to execute `{}`", format_command(program, args))
+            })?;
+
+        Ok(CommandOutput {
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            success: output.status.success(),
+            status_code: output.status.code(),
+        })
+    }
+
+    fn status(&mut self, command: &mut Command) -> io::Result<CommandOutput> {
+        let status = command.status()?;
+        Ok(CommandOutput {
+            stdout: String::new(),
+            stderr: String::new(),
+            success: status.success(),
+            status_code: status.code(),
+        })
+    }
+
+    fn spawn(&mut self, command: &mut Command) -> io::Result<()> {
+        // Detached stdio keeps the child from blocking or writing over us.
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map(|_| ())
+    }
+}
+
+/// A command [`ScriptedRunner`] was asked to run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedCommand {
+    pub program: String,
+    pub current_dir: PathBuf,
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Response {
+    Output(CommandOutput),
+    /// The program is not installed.
+    Missing,
+}
+
+#[derive(Debug, Default)]
+struct Script {
+    responses: VecDeque<Response>,
+    calls: Vec<RecordedCommand>,
+}
+
+/// A [`CommandRunner`] that runs nothing: it answers every command with the
+/// next scripted response and records it. A command beyond the script fails.
+///
+/// Clones share the script and the recorded commands, so a runner can be
+/// handed to a command and inspected afterwards.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedRunner {
+    script: Arc<Mutex<Script>>,
+}
+
+impl ScriptedRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers the next command with `output`.
+    pub fn push(&self, output: CommandOutput) -> &Self {
+        self.lock().responses.push_back(Response::Output(output));
+        self
+    }
+
+    /// Answers the next command as if its program was not installed.
+    pub fn push_missing(&self) -> &Self {
+        self.lock().responses.push_back(Response::Missing);
+        self
+    }
+
+    /// The commands run so far, in order.
+    pub fn calls(&self) -> Vec<RecordedCommand> {
+        self.lock().calls.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Script> {
+        self.script.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn respond(
+        &self,
+        program: &str,
+        current_dir: &Path,
+        args: &[String],
+    ) -> io::Result<CommandOutput> {
+        let mut script = self.lock();
+        script.calls.push(RecordedCommand {
+            program: program.to_owned(),
+            current_dir: current_dir.to_path_buf(),
+            args: args.to_vec(),
+        });
+        match script.responses.pop_front() {
+            Some(Response::Output(output)) => Ok(output),
+            Some(Response::Missing) => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("`{program}` is not installed"),
+            )),
+            None => Err(io::Error::other(format!(
+                "unexpected command `{}`",
+                format_command(program, args)
+            ))),
+        }
+    }
+}
+
+impl CommandRunner for ScriptedRunner {
+    fn run(
+        &mut self,
+        program: &str,
+        current_dir: &Path,
+        args: &[String],
+    ) -> color_eyre::Result<CommandOutput> {
+        self.respond(program, current_dir, args)
+            .wrap_err_with(|| eyre::eyre!("failed to execute `{}`", format_command(program, args)))
+    }
+
+    fn status(&mut self, command: &mut Command) -> io::Result<CommandOutput> {
+        let (program, current_dir, args) = parts(command);
+        self.respond(&program, &current_dir, &args)
+    }
+}
+
+/// The program, directory and arguments of `command`; a command without a
+/// directory runs in the current one.
+fn parts(command: &Command) -> (String, PathBuf, Vec<String>) {
+    (
+        command.get_program().to_string_lossy().into_owned(),
+        command
+            .get_current_dir()
+            .map_or_else(|| PathBuf::from("."), Path::to_path_buf),
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect(),
+    )
+}
+
+/// The error for a command that exited unsuccessfully, with its stderr.
+pub(crate) fn command_failure(
+    program: &str,
+    args: &[String],
+    output: &CommandOutput,
+) -> color_eyre::Report {
+    let command_line = format_command(program, args);
+    let mut message = format!("`{command_line}` failed with {}", exit_status(output));
+    let stderr = output.stderr.trim();
+    if !stderr.is_empty() {
+        message.push('\n');
+        message.push_str(stderr);
+    }
+
+    eyre::eyre!(message)
+}
+
+/// How the command exited, as in "failed with exit status 1".
+pub(crate) fn exit_status(output: &CommandOutput) -> String {
+    match output.status_code {
+        Some(code) => format!("exit status {code}"),
+        None => "termination by signal".to_owned(),
+    }
+}
+
+/// `program` and `args` as they would be typed in a shell.
+pub(crate) fn format_command(program: &str, args: &[String]) -> String {
+    let mut parts = Vec::with_capacity(1 + args.len());
+    parts.push(quote_arg(program));
+    for arg in args {
+        parts.push(quote_arg(arg));
+    }
+    parts.join(" ")
+}
+
+fn quote_arg(value: &str) -> String {
+    if value
+        .chars()
+        .all(|c| matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' | '/' | '='))
+    {
+        value.to_owned()
+    } else {
+        let escaped = value.replace('\'', "'\\''");
+        format!("'{escaped}'")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_command_quotes_arguments_with_special_characters() {
+        let command = format_command(
+            "gh",
+            &[
+                "pr".into(),
+                "create".into(),
+                "--title".into(),
+                "Ready for review".into(),
+            ],
+        );
+        assert_eq!(command, "gh pr create --title 'Ready for review'");
+    }
+
+    #[test]
+    fn scripted_runner_answers_and_records_in_order() {
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success("main\n")).push_missing();
+
+        let mut handle = runner.clone();
+        let output = handle
+            .run("git", Path::new("/repo"), &["branch".into()])
+            .expect("scripted");
+        assert_eq!(output.stdout, "main\n");
+
+        let mut editor = Command::new("code");
+        editor.arg("/repo/.rsworktree/feature").current_dir("/repo");
+        let error = handle.status(&mut editor).expect_err("scripted as missing");
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+        let error = handle
+            .run("tmux", Path::new("."), &["ls".into()])
+            .expect_err("the script is exhausted");
+        assert!(format!("{error:?}").contains("unexpected command `tmux ls`"));
+
+        assert_eq!(
+            runner.calls(),
+            [
+                RecordedCommand {
+                    program: "git".into(),
+                    current_dir: "/repo".into(),
+                    args: vec!["branch".into()],
+                },
+                RecordedCommand {
+                    program: "code".into(),
+                    current_dir: "/repo".into(),
+                    args: vec!["/repo/.rsworktree/feature".into()],
+                },
+                RecordedCommand {
+                    program: "tmux".into(),
+                    current_dir: ".".into(),
+                    args: vec!["ls".into()],
+                },
+            ]
+        );
+    }
+}
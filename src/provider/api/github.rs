@@ -0,0 +1,440 @@
+//! The operations of GitHub through its REST API.
+
+use std::path::Path;
+
+use color_eyre::eyre;
+use serde_json::{Value, json};
+
+use super::{Client, check_out, commit_message, encode, git};
+use crate::{
+    process::CommandRunner,
+    provider::{
+        MergeOutcome, MergeRequestStatus, MergeStrategy, NewMergeRequest, PipelineState,
+        PipelineStatus, ReviewVerdict,
+    },
+};
+
+pub(super) fn list(client: &Client, branch: &str) -> color_eyre::Result<Option<u64>> {
+    Ok(find(client, branch, "open")?.and_then(|pull| pull["number"].as_u64()))
+}
+
+/// The latest pull request of `branch`, open or not, as `gh pr view` shows it.
+pub(super) fn view(
+    client: &Client,
+    branch: &str,
+) -> color_eyre::Result<Option<MergeRequestStatus>> {
+    Ok(find(client, branch, "all")?.as_ref().map(status))
+}
+
+pub(super) fn head_branch(client: &Client, number: u64) -> color_eyre::Result<Option<String>> {
+    let pull = client.get(&format!("/repos/{}/pulls/{number}", client.repository()))?;
+    Ok(pull["head"]["ref"].as_str().map(str::to_owned))
+}
+
+/// Creates the pull request of `request.branch` against the default branch,
+/// titled and described after its last commit unless a title is given.
+pub(super) fn create(
+    client: &Client,
+    runner: &mut impl CommandRunner,
+    dir: &Path,
+    request: &NewMergeRequest,
+) -> color_eyre::Result<Option<String>> {
+    let (subject, body) = commit_message(runner, dir, &request.branch)?;
+    let pull = client.post(
+        &format!("/repos/{}/pulls", client.repository()),
+        json!({
+            "title": request.title.clone().unwrap_or(subject),
+            "head": request.branch,
+            "base": default_branch(client)?,
+            "body": body,
+            "draft": request.draft,
+        }),
+    )?;
+    if !request.reviewers.is_empty() {
+        // Teams are named `org/team`; the API wants the team alone.
+        let (teams, users): (Vec<&str>, Vec<&str>) = request
+            .reviewers
+            .iter()
+            .map(String::as_str)
+            .partition(|reviewer| reviewer.contains('/'));
+        let teams = teams
+            .iter()
+            .filter_map(|team| team.split_once('/').map(|(_, team)| team))
+            .collect::<Vec<_>>();
+        client.post(
+            &format!(
+                "/repos/{}/pulls/{}/requested_reviewers",
+                client.repository(),
+                pull["number"]
+            ),
+            json!({ "reviewers": users, "team_reviewers": teams }),
+        )?;
+    }
+    Ok(pull["html_url"].as_str().map(str::to_owned))
+}
+
+pub(super) fn merge(
+    client: &Client,
+    runner: &mut impl CommandRunner,
+    dir: &Path,
+    number: u64,
+    delete_branch: bool,
+    strategy: MergeStrategy,
+) -> color_eyre::Result<MergeOutcome> {
+    let pull = client.get(&format!("/repos/{}/pulls/{number}", client.repository()))?;
+    client.put(
+        &format!("/repos/{}/pulls/{number}/merge", client.repository()),
+        json!({ "merge_method": strategy.to_string() }),
+    )?;
+    if delete_branch {
+        let branch = pull["head"]["ref"].as_str().unwrap_or_default();
+        if remove_branch(client, runner, dir, &pull, branch).is_err() {
+            return Ok(MergeOutcome::BranchKept);
+        }
+    }
+    Ok(MergeOutcome::Merged)
+}
+
+/// Deletes the head branch of a merged pull request, on GitHub unless it
+/// lives in a fork, and locally if it exists, as `gh pr merge --delete-branch`.
+fn remove_branch(
+    client: &Client,
+    runner: &mut impl CommandRunner,
+    dir: &Path,
+    pull: &Value,
+    branch: &str,
+) -> color_eyre::Result<()> {
+    if pull["head"]["repo"]["full_name"].as_str() == Some(client.repository()) {
+        let path = branch.split('/').map(encode).collect::<Vec<_>>().join("/");
+        client.delete(&format!(
+            "/repos/{}/git/refs/heads/{path}",
+            client.repository()
+        ))?;
+    }
+    let local = format!("refs/heads/{branch}");
+    if git(runner, dir, &["show-ref", "--verify", "--quiet", &local]).is_ok() {
+        git(runner, dir, &["branch", "-D", branch])?;
+    }
+    Ok(())
+}
+
+pub(super) fn comment(
+    client: &Client,
+    target: &str,
+    body: &str,
+) -> color_eyre::Result<Option<String>> {
+    let comment = post_comment(client, number(client, target)?, body)?;
+    Ok(comment["html_url"].as_str().map(str::to_owned))
+}
+
+pub(super) fn close(
+    client: &Client,
+    target: &str,
+    comment: Option<&str>,
+) -> color_eyre::Result<()> {
+    let number = number(client, target)?;
+    if let Some(comment) = comment {
+        post_comment(client, number, comment)?;
+    }
+    client.patch(
+        &format!("/repos/{}/pulls/{number}", client.repository()),
+        json!({ "state": "closed" }),
+    )?;
+    Ok(())
+}
+
+pub(super) fn review(
+    client: &Client,
+    target: &str,
+    verdict: ReviewVerdict,
+    body: Option<&str>,
+) -> color_eyre::Result<bool> {
+    let number = number(client, target)?;
+    let event = match verdict {
+        ReviewVerdict::Approve => "APPROVE",
+        ReviewVerdict::RequestChanges => "REQUEST_CHANGES",
+    };
+    let mut review = json!({ "event": event });
+    if let Some(body) = body {
+        review["body"] = json!(body);
+    }
+    client.post(
+        &format!("/repos/{}/pulls/{number}/reviews", client.repository()),
+        review,
+    )?;
+    Ok(true)
+}
+
+pub(super) fn checkout(
+    client: &Client,
+    runner: &mut impl CommandRunner,
+    dir: &Path,
+    number: u64,
+) -> color_eyre::Result<()> {
+    let branch = head_branch(client, number)?
+        .ok_or_else(|| eyre::eyre!("pull request #{number} has no head branch"))?;
+    // GitHub keeps the head of every pull request, forks included, below `refs/pull`.
+    check_out(runner, dir, &format!("pull/{number}/head"), &branch)
+}
+
+/// Dispatches `workflow` on `branch`.
+pub(super) fn trigger_ci(client: &Client, branch: &str, workflow: &str) -> color_eyre::Result<()> {
+    client.post(
+        &format!(
+            "/repos/{}/actions/workflows/{}/dispatches",
+            client.repository(),
+            encode(workflow)
+        ),
+        json!({ "ref": branch }),
+    )?;
+    Ok(())
+}
+
+pub(super) fn ci_status(
+    client: &Client,
+    branch: &str,
+) -> color_eyre::Result<Option<PipelineStatus>> {
+    let runs = client.get(&format!(
+        "/repos/{}/actions/runs?per_page=1&branch={}",
+        client.repository(),
+        encode(branch)
+    ))?;
+    Ok(runs["workflow_runs"]
+        .as_array()
+        .and_then(|runs| runs.first())
+        .map(|run| PipelineStatus {
+            id: run["id"].as_u64().unwrap_or_default(),
+            name: run["name"]
+                .as_str()
+                .filter(|name| !name.is_empty())
+                .map(str::to_owned),
+            state: PipelineState::from_github(
+                run["status"].as_str().unwrap_or_default(),
+                run["conclusion"].as_str(),
+            ),
+            url: run["html_url"].as_str().map(str::to_owned),
+        }))
+}
+
+/// The latest pull request of `branch` in `state`.
+fn find(client: &Client, branch: &str, state: &str) -> color_eyre::Result<Option<Value>> {
+    let owner = client.repository().split('/').next().unwrap_or_default();
+    let pulls = client.get(&format!(
+        "/repos/{}/pulls?head={}&state={}&per_page=1",
+        client.repository(),
+        encode(&format!("{owner}:{branch}")),
+        encode(state)
+    ))?;
+    Ok(pulls.as_array().and_then(|pulls| pulls.first()).cloned())
+}
+
+/// The number of pull request `target`, given by number or by its open
+/// pull request's head branch.
+fn number(client: &Client, target: &str) -> color_eyre::Result<u64> {
+    if let Ok(number) = target.parse() {
+        return Ok(number);
+    }
+    list(client, target)?
+        .ok_or_else(|| eyre::eyre!("no open pull requests found for branch \"{target}\""))
+}
+
+fn default_branch(client: &Client) -> color_eyre::Result<String> {
+    let repository = client.get(&format!("/repos/{}", client.repository()))?;
+    repository["default_branch"]
+        .as_str()
+        .map(str::to_owned)
+        .ok_or_else(|| eyre::eyre!("`{}` has no default branch", client.repository()))
+}
+
+fn post_comment(client: &Client, number: u64, body: &str) -> color_eyre::Result<Value> {
+    client.post(
+        &format!("/repos/{}/issues/{number}/comments", client.repository()),
+        json!({ "body": body }),
+    )
+}
+
+fn status(pull: &Value) -> MergeRequestStatus {
+    let state = if !pull["merged_at"].is_null() {
+        "merged"
+    } else if pull["state"] == "closed" {
+        "closed"
+    } else {
+        "open"
+    };
+    MergeRequestStatus {
+        number: pull["number"].as_u64().unwrap_or_default(),
+        url: pull["html_url"].as_str().map(str::to_owned),
+        state: state.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use crate::{
+        process::{CommandOutput, ScriptedRunner},
+        provider::{
+            GitProvider, ProviderClient, RequestBackend,
+            api::tests::{ScriptedHttp, api},
+        },
+    };
+
+    const REMOTE: &str = "git@github.com:acme/app.git";
+    const API: &str = "https://api.github.com/repos/acme/app";
+
+    fn client(http: &Arc<ScriptedHttp>) -> (ProviderClient, ScriptedRunner) {
+        let (api, runner) = api(GitProvider::GitHub, REMOTE, http);
+        (ProviderClient::with_api(api), runner)
+    }
+
+    #[test]
+    fn lists_and_views_pull_requests() -> color_eyre::Result<()> {
+        let http = Arc::new(ScriptedHttp::default());
+        http.push(200, r#"[{"number":12,"html_url":"https://github.com/acme/app/pull/12","state":"open","merged_at":null,"head":{"ref":"feature/a"}}]"#)
+            .push(200, r#"[{"number":9,"html_url":"https://github.com/acme/app/pull/9","state":"closed","merged_at":"2025-01-01T00:00:00Z","head":{"ref":"feature/a"}}]"#)
+            .push(200, "[]");
+        let (mut client, mut runner) = client(&http);
+        let dir = Path::new("/repo");
+
+        assert_eq!(client.list(&mut runner, dir, "feature/a")?, Some(12));
+        let status = client
+            .view(&mut runner, dir, "feature/a")?
+            .expect("a pull request");
+        assert_eq!((status.number, status.state.as_str()), (9, "merged"));
+        assert_eq!(client.view(&mut runner, dir, "feature/b")?, None);
+
+        assert_eq!(
+            http.requests()[0].0,
+            format!("GET {API}/pulls?head=acme%3Afeature%2Fa&state=open&per_page=1")
+        );
+        assert_eq!(
+            http.requests()[1].0,
+            format!("GET {API}/pulls?head=acme%3Afeature%2Fa&state=all&per_page=1")
+        );
+        // The remote is read once.
+        assert_eq!(runner.calls().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn creates_a_pull_request_from_the_last_commit() -> color_eyre::Result<()> {
+        let http = Arc::new(ScriptedHttp::default());
+        http.push(200, r#"{"default_branch":"main"}"#)
+            .push(
+                201,
+                r#"{"number":7,"html_url":"https://github.com/acme/app/pull/7"}"#,
+            )
+            .push(201, "{}");
+        let (mut client, mut runner) = client(&http);
+        runner
+            .push(CommandOutput::success("Add login\n"))
+            .push(CommandOutput::success("Closes #3\n"));
+
+        let request = NewMergeRequest {
+            branch: "feature/a".into(),
+            draft: true,
+            fill: true,
+            reviewers: vec!["alice".into(), "acme/backend".into()],
+            ..NewMergeRequest::default()
+        };
+        let url = client.create(
+            &mut runner,
+            Path::new("/repo/.rsworktree/feature/a"),
+            &request,
+        )?;
+        assert_eq!(url.as_deref(), Some("https://github.com/acme/app/pull/7"));
+
+        let requests = http.requests();
+        assert_eq!(requests[0].0, format!("GET {API}"));
+        assert_eq!(requests[1].0, format!("POST {API}/pulls"));
+        assert_eq!(
+            requests[1].1,
+            Some(json!({
+                "title": "Add login",
+                "head": "feature/a",
+                "base": "main",
+                "body": "Closes #3",
+                "draft": true,
+            }))
+        );
+        assert_eq!(
+            requests[2].1,
+            Some(json!({ "reviewers": ["alice"], "team_reviewers": ["backend"] }))
+        );
+        let calls = runner.calls();
+        assert_eq!(calls[0].args, ["remote", "get-url", "origin"]);
+        assert_eq!(
+            calls[1].args,
+            ["log", "-1", "--format=%s", "feature/a", "--"]
+        );
+
+        let request = NewMergeRequest {
+            extra_args: vec!["--milestone".into(), "v1".into()],
+            ..request
+        };
+        let error = client
+            .create(&mut runner, Path::new("/repo"), &request)
+            .expect_err("extra arguments are for the CLI");
+        assert!(error.to_string().contains("`--milestone v1`"), "{error}");
+        assert_eq!(http.requests().len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn merge_deletes_the_branch_remotely_and_locally() -> color_eyre::Result<()> {
+        let http = Arc::new(ScriptedHttp::default());
+        http.push(
+            200,
+            r#"{"number":7,"head":{"ref":"feature/a","repo":{"full_name":"acme/app"}}}"#,
+        )
+        .push(200, r#"{"merged":true}"#)
+        .push(204, "");
+        let (mut client, mut runner) = client(&http);
+        runner
+            .push(CommandOutput::success(""))
+            .push(CommandOutput::failure(1, "error: branch not found"));
+
+        let outcome = client.merge(
+            &mut runner,
+            Path::new("/repo"),
+            7,
+            true,
+            MergeStrategy::Squash,
+        )?;
+        assert_eq!(outcome, MergeOutcome::BranchKept);
+
+        let requests = http.requests();
+        assert_eq!(requests[1].0, format!("PUT {API}/pulls/7/merge"));
+        assert_eq!(requests[1].1, Some(json!({ "merge_method": "squash" })));
+        assert_eq!(
+            requests[2].0,
+            format!("DELETE {API}/git/refs/heads/feature/a")
+        );
+        assert_eq!(runner.calls()[2].args, ["branch", "-D", "feature/a"]);
+        Ok(())
+    }
+
+    #[test]
+    fn reports_the_latest_workflow_run() -> color_eyre::Result<()> {
+        let http = Arc::new(ScriptedHttp::default());
+        http.push(200, r#"{"workflow_runs":[{"id":5,"name":"CI","status":"completed","conclusion":"failure","html_url":"https://github.com/acme/app/actions/runs/5"}]}"#)
+            .push(200, r#"{"workflow_runs":[]}"#);
+        let (mut client, mut runner) = client(&http);
+        let dir = Path::new("/repo");
+
+        let run = client
+            .ci_status(&mut runner, dir, "feature/a")?
+            .expect("a run");
+        assert_eq!((run.id, run.name.as_deref()), (5, Some("CI")));
+        assert_eq!(run.state, PipelineState::Failed);
+        assert_eq!(client.ci_status(&mut runner, dir, "feature/a")?, None);
+        assert_eq!(
+            http.requests()[0].0,
+            format!("GET {API}/actions/runs?per_page=1&branch=feature%2Fa")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,352 @@
+//! The operations of GitLab through its REST API.
+
+use std::path::Path;
+
+use color_eyre::eyre;
+use serde_json::{Value, json};
+
+use super::{Client, check_out, commit_message, encode};
+use crate::{
+    process::CommandRunner,
+    provider::{
+        MergeOutcome, MergeRequestStatus, MergeStrategy, NewMergeRequest, PipelineState,
+        PipelineStatus, ReviewVerdict,
+    },
+};
+
+pub(super) fn list(client: &Client, branch: &str) -> color_eyre::Result<Option<u64>> {
+    Ok(find(client, branch)?.and_then(|request| request["iid"].as_u64()))
+}
+
+pub(super) fn view(
+    client: &Client,
+    branch: &str,
+) -> color_eyre::Result<Option<MergeRequestStatus>> {
+    Ok(find(client, branch)?.map(|request| MergeRequestStatus {
+        number: request["iid"].as_u64().unwrap_or_default(),
+        url: request["web_url"].as_str().map(str::to_owned),
+        state: request["state"].as_str().unwrap_or_default().to_owned(),
+    }))
+}
+
+pub(super) fn head_branch(client: &Client, number: u64) -> color_eyre::Result<Option<String>> {
+    let request = client.get(&merge_request_path(client, number))?;
+    Ok(request["source_branch"].as_str().map(str::to_owned))
+}
+
+/// Creates the merge request of `request.branch` into the default branch,
+/// titled and described after its last commit unless a title is given.
+pub(super) fn create(
+    client: &Client,
+    runner: &mut impl CommandRunner,
+    dir: &Path,
+    request: &NewMergeRequest,
+) -> color_eyre::Result<Option<String>> {
+    let (subject, description) = commit_message(runner, dir, &request.branch)?;
+    let title = request.title.clone().unwrap_or(subject);
+    let title = if request.draft {
+        format!("Draft: {title}")
+    } else {
+        title
+    };
+    let project = client.get(&project_path(client))?;
+    let target = project["default_branch"]
+        .as_str()
+        .ok_or_else(|| eyre::eyre!("`{}` has no default branch", client.repository()))?;
+    let reviewer_ids = request
+        .reviewers
+        .iter()
+        .map(|username| user_id(client, username))
+        .collect::<color_eyre::Result<Vec<_>>>()?;
+
+    let mut body = json!({
+        "source_branch": request.branch,
+        "target_branch": target,
+        "title": title,
+        "description": description,
+    });
+    if !reviewer_ids.is_empty() {
+        body["reviewer_ids"] = json!(reviewer_ids);
+    }
+    let created = client.post(&format!("{}/merge_requests", project_path(client)), body)?;
+    Ok(created["web_url"].as_str().map(str::to_owned))
+}
+
+pub(super) fn merge(
+    client: &Client,
+    number: u64,
+    delete_branch: bool,
+    strategy: MergeStrategy,
+) -> color_eyre::Result<MergeOutcome> {
+    if strategy == MergeStrategy::Rebase {
+        return Err(eyre::eyre!(
+            "the API backend cannot rebase merge requests; use the `merge` or `squash` strategy, or set `provider_backend = \"cli\"` to run `glab`"
+        ));
+    }
+    client.put(
+        &format!("{}/merge", merge_request_path(client, number)),
+        json!({
+            "squash": strategy == MergeStrategy::Squash,
+            "should_remove_source_branch": delete_branch,
+        }),
+    )?;
+    Ok(MergeOutcome::Merged)
+}
+
+/// Adds a note to merge request `target`; GitLab reports no URL for it.
+pub(super) fn comment(
+    client: &Client,
+    target: &str,
+    body: &str,
+) -> color_eyre::Result<Option<String>> {
+    post_note(client, number(client, target)?, body)?;
+    Ok(None)
+}
+
+pub(super) fn close(
+    client: &Client,
+    target: &str,
+    comment: Option<&str>,
+) -> color_eyre::Result<()> {
+    let number = number(client, target)?;
+    if let Some(comment) = comment {
+        post_note(client, number, comment)?;
+    }
+    client.put(
+        &merge_request_path(client, number),
+        json!({ "state_event": "close" }),
+    )?;
+    Ok(())
+}
+
+/// Approves merge request `target`; GitLab has no verdict requesting changes.
+pub(super) fn review(
+    client: &Client,
+    target: &str,
+    verdict: ReviewVerdict,
+) -> color_eyre::Result<bool> {
+    if verdict == ReviewVerdict::RequestChanges {
+        return Ok(false);
+    }
+    let number = number(client, target)?;
+    client.post(
+        &format!("{}/approve", merge_request_path(client, number)),
+        json!({}),
+    )?;
+    Ok(true)
+}
+
+pub(super) fn checkout(
+    client: &Client,
+    runner: &mut impl CommandRunner,
+    dir: &Path,
+    number: u64,
+) -> color_eyre::Result<()> {
+    let branch = head_branch(client, number)?
+        .ok_or_else(|| eyre::eyre!("merge request !{number} has no source branch"))?;
+    // GitLab keeps the head of every merge request, forks included, below
+    // `refs/merge-requests`.
+    check_out(
+        runner,
+        dir,
+        &format!("merge-requests/{number}/head"),
+        &branch,
+    )
+}
+
+/// Runs the branch pipeline of `branch`.
+pub(super) fn trigger_ci(client: &Client, branch: &str) -> color_eyre::Result<()> {
+    client.post(
+        &format!("{}/pipeline?ref={}", project_path(client), encode(branch)),
+        json!({}),
+    )?;
+    Ok(())
+}
+
+pub(super) fn ci_status(
+    client: &Client,
+    branch: &str,
+) -> color_eyre::Result<Option<PipelineStatus>> {
+    let pipelines = client.get(&format!(
+        "{}/pipelines?ref={}&per_page=1",
+        project_path(client),
+        encode(branch)
+    ))?;
+    Ok(pipelines
+        .as_array()
+        .and_then(|pipelines| pipelines.first())
+        .map(|pipeline| PipelineStatus {
+            id: pipeline["id"].as_u64().unwrap_or_default(),
+            name: pipeline["name"]
+                .as_str()
+                .filter(|name| !name.is_empty())
+                .map(str::to_owned),
+            state: PipelineState::from_gitlab(pipeline["status"].as_str().unwrap_or_default()),
+            url: pipeline["web_url"].as_str().map(str::to_owned),
+        }))
+}
+
+/// The latest open merge request from `branch`.
+fn find(client: &Client, branch: &str) -> color_eyre::Result<Option<Value>> {
+    let requests = client.get(&format!(
+        "{}/merge_requests?source_branch={}&state=opened&per_page=1",
+        project_path(client),
+        encode(branch)
+    ))?;
+    Ok(requests
+        .as_array()
+        .and_then(|requests| requests.first())
+        .cloned())
+}
+
+/// The IID of merge request `target`, given by IID or by the source branch
+/// of an open merge request.
+fn number(client: &Client, target: &str) -> color_eyre::Result<u64> {
+    if let Ok(number) = target.parse() {
+        return Ok(number);
+    }
+    list(client, target)?
+        .ok_or_else(|| eyre::eyre!("no open merge request available for \"{target}\""))
+}
+
+fn post_note(client: &Client, number: u64, body: &str) -> color_eyre::Result<Value> {
+    client.post(
+        &format!("{}/notes", merge_request_path(client, number)),
+        json!({ "body": body }),
+    )
+}
+
+fn user_id(client: &Client, username: &str) -> color_eyre::Result<u64> {
+    let users = client.get(&format!("/users?username={}", encode(username)))?;
+    users
+        .as_array()
+        .and_then(|users| users.first())
+        .and_then(|user| user["id"].as_u64())
+        .ok_or_else(|| eyre::eyre!("GitLab user `{username}` was not found"))
+}
+
+fn project_path(client: &Client) -> String {
+    format!("/projects/{}", encode(client.repository()))
+}
+
+fn merge_request_path(client: &Client, number: u64) -> String {
+    format!("{}/merge_requests/{number}", project_path(client))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use crate::{
+        process::{CommandOutput, ScriptedRunner},
+        provider::{
+            GitProvider, ProviderClient, RequestBackend,
+            api::tests::{ScriptedHttp, api},
+        },
+    };
+
+    const API: &str = "https://gitlab.example.com/api/v4/projects/group%2Fapp";
+
+    fn client(http: &Arc<ScriptedHttp>) -> (ProviderClient, ScriptedRunner) {
+        let (api, runner) = api(
+            GitProvider::GitLab,
+            "git@gitlab.example.com:group/app.git",
+            http,
+        );
+        (ProviderClient::with_api(api), runner)
+    }
+
+    #[test]
+    fn views_merge_requests_and_pipelines() -> color_eyre::Result<()> {
+        let http = Arc::new(ScriptedHttp::default());
+        http.push(200, r#"[{"id":901,"iid":4,"web_url":"https://gitlab.example.com/group/app/-/merge_requests/4","state":"opened"}]"#)
+            .push(200, "[]")
+            .push(200, r#"[{"id":77,"status":"running","ref":"feature/a","web_url":"https://gitlab.example.com/group/app/-/pipelines/77"}]"#);
+        let (mut client, mut runner) = client(&http);
+        let dir = Path::new("/repo");
+
+        let status = client
+            .view(&mut runner, dir, "feature/a")?
+            .expect("a merge request");
+        assert_eq!((status.number, status.state.as_str()), (4, "opened"));
+        assert_eq!(client.view(&mut runner, dir, "feature/b")?, None);
+
+        let pipeline = client
+            .ci_status(&mut runner, dir, "feature/a")?
+            .expect("a pipeline");
+        assert_eq!(pipeline.id, 77);
+        assert!(!pipeline.state.is_finished());
+
+        assert_eq!(
+            http.requests()[0].0,
+            format!("GET {API}/merge_requests?source_branch=feature%2Fa&state=opened&per_page=1")
+        );
+        assert_eq!(
+            http.requests()[2].0,
+            format!("GET {API}/pipelines?ref=feature%2Fa&per_page=1")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn creates_and_merges_a_merge_request() -> color_eyre::Result<()> {
+        let http = Arc::new(ScriptedHttp::default());
+        http.push(200, r#"{"default_branch":"main"}"#)
+            .push(200, r#"[{"id":31,"username":"alice"}]"#)
+            .push(
+                201,
+                r#"{"iid":5,"web_url":"https://gitlab.example.com/group/app/-/merge_requests/5"}"#,
+            )
+            .push(200, r#"{"state":"merged"}"#);
+        let (mut client, mut runner) = client(&http);
+        runner
+            .push(CommandOutput::success("Add login\n"))
+            .push(CommandOutput::success("\n"));
+        let dir = Path::new("/repo");
+
+        let request = NewMergeRequest {
+            branch: "feature/a".into(),
+            title: Some("PROJ-1: Add login".into()),
+            draft: true,
+            reviewers: vec!["alice".into()],
+            ..NewMergeRequest::default()
+        };
+        assert_eq!(
+            client.create(&mut runner, dir, &request)?.as_deref(),
+            Some("https://gitlab.example.com/group/app/-/merge_requests/5")
+        );
+        assert_eq!(
+            client.merge(&mut runner, dir, 5, true, MergeStrategy::Squash)?,
+            MergeOutcome::Merged
+        );
+        let error = client
+            .merge(&mut runner, dir, 5, false, MergeStrategy::Rebase)
+            .expect_err("the API cannot rebase");
+        assert!(error.to_string().contains("cannot rebase"));
+
+        let requests = http.requests();
+        assert_eq!(
+            requests[1].0,
+            "GET https://gitlab.example.com/api/v4/users?username=alice"
+        );
+        assert_eq!(
+            requests[2].1,
+            Some(json!({
+                "source_branch": "feature/a",
+                "target_branch": "main",
+                "title": "Draft: PROJ-1: Add login",
+                "description": "",
+                "reviewer_ids": [31],
+            }))
+        );
+        assert_eq!(requests[3].0, format!("PUT {API}/merge_requests/5/merge"));
+        assert_eq!(
+            requests[3].1,
+            Some(json!({ "squash": true, "should_remove_source_branch": true }))
+        );
+        assert_eq!(requests.len(), 4);
+        Ok(())
+    }
+}
@@ -0,0 +1,724 @@
+//! The API backend of the `provider-api` feature: provider operations call
+//! the GitHub and GitLab REST APIs over HTTP instead of running `gh` and
+//! `glab`, for machines where the CLIs cannot be installed.
+//!
+//! Requests carry the token of `GH_TOKEN` (or `GITHUB_TOKEN`) or
+//! `GITLAB_TOKEN`; the API and the repository follow from the URL of the
+//! `origin` remote.
+
+mod github;
+mod gitlab;
+
+use std::{env, fmt, path::Path, sync::Arc, time::Duration};
+
+use color_eyre::eyre::{self, WrapErr};
+use serde_json::Value;
+
+use super::{
+    GitProvider, MergeOutcome, MergeRequestStatus, MergeStrategy, NewMergeRequest, PipelineStatus,
+    RequestBackend, ReviewVerdict,
+};
+use crate::process::{CommandRunner, command_failure};
+
+/// The remote whose URL names the repository on the provider.
+const REMOTE: &str = "origin";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sends requests to the provider API.
+pub(crate) trait Http: fmt::Debug + Send + Sync {
+    fn send(&self, request: &Request) -> color_eyre::Result<Response>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Request {
+    pub(crate) method: &'static str,
+    pub(crate) url: String,
+    pub(crate) headers: Vec<String>,
+    /// JSON.
+    pub(crate) body: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Response {
+    pub(crate) status: u16,
+    pub(crate) body: String,
+}
+
+/// Sends requests with a blocking HTTP client.
+#[derive(Debug)]
+struct Agent(ureq::Agent);
+
+impl Agent {
+    fn new() -> Self {
+        Self(ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build())
+    }
+}
+
+impl Http for Agent {
+    fn send(&self, request: &Request) -> color_eyre::Result<Response> {
+        let mut call = self.0.request(request.method, &request.url);
+        for header in &request.headers {
+            let (name, value) = header
+                .split_once(':')
+                .ok_or_else(|| eyre::eyre!("malformed header `{header}`"))?;
+            call = call.set(name.trim(), value.trim());
+        }
+        let result = match &request.body {
+            Some(body) => call
+                .set("Content-Type", "application/json")
+                .send_string(body),
+            None => call.call(),
+        };
+
+        // Error statuses are answers too; the caller reads their message.
+        let response = match result {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(ureq::Error::Transport(error)) => {
+                return Err(eyre::eyre!(
+                    "failed to send {} {}: {error}",
+                    request.method,
+                    request.url
+                ));
+            }
+        };
+        let status = response.status();
+        let body = response
+            .into_string()
+            .wrap_err_with(|| eyre::eyre!("failed to read the response to {}", request.url))?;
+        Ok(Response { status, body })
+    }
+}
+
+/// Runs the operations of GitHub or GitLab through its API.
+#[derive(Debug, Clone)]
+pub(crate) struct ApiBackend {
+    pub(super) provider: GitProvider,
+    token: String,
+    http: Arc<dyn Http>,
+    /// Read from the remote on the first command.
+    repository: Option<Repository>,
+}
+
+impl ApiBackend {
+    /// Fails for providers without API support and when the token is not set.
+    pub(crate) fn load(provider: GitProvider) -> color_eyre::Result<Self> {
+        let token_env: &[&str] = match provider {
+            GitProvider::GitHub => &["GH_TOKEN", "GITHUB_TOKEN"],
+            GitProvider::GitLab => &["GITLAB_TOKEN"],
+            _ => {
+                return Err(eyre::eyre!(
+                    "the API backend supports GitHub and GitLab; {} operations need `{}`",
+                    provider.display_name(),
+                    provider.cli_program()
+                ));
+            }
+        };
+        let token = token_env
+            .iter()
+            .find_map(|name| env::var(name).ok().filter(|token| !token.trim().is_empty()))
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "set `{}` to a {} access token to use its API",
+                    token_env[0],
+                    provider.display_name()
+                )
+            })?;
+        Ok(Self::with_http(provider, token, Arc::new(Agent::new())))
+    }
+
+    pub(crate) fn with_http(provider: GitProvider, token: String, http: Arc<dyn Http>) -> Self {
+        Self {
+            provider,
+            token,
+            http,
+            repository: None,
+        }
+    }
+
+    fn client(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        current_dir: &Path,
+    ) -> color_eyre::Result<Client> {
+        let repository = match &self.repository {
+            Some(repository) => repository.clone(),
+            None => {
+                let url = git(runner, current_dir, &["remote", "get-url", REMOTE])?;
+                let repository =
+                    Repository::from_remote_url(self.provider, &url).ok_or_else(|| {
+                        eyre::eyre!(
+                            "cannot tell the {} repository from the `{REMOTE}` remote `{url}`",
+                            self.provider.display_name()
+                        )
+                    })?;
+                self.repository.insert(repository).clone()
+            }
+        };
+        Ok(Client {
+            provider: self.provider,
+            token: self.token.clone(),
+            http: Arc::clone(&self.http),
+            repository,
+        })
+    }
+}
+
+impl RequestBackend for ApiBackend {
+    fn list(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<Option<u64>> {
+        let client = self.client(runner, dir)?;
+        match self.provider {
+            GitProvider::GitLab => gitlab::list(&client, branch),
+            _ => github::list(&client, branch),
+        }
+    }
+
+    fn view(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<Option<MergeRequestStatus>> {
+        let client = self.client(runner, dir)?;
+        match self.provider {
+            GitProvider::GitLab => gitlab::view(&client, branch),
+            _ => github::view(&client, branch),
+        }
+    }
+
+    fn head_branch(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        number: u64,
+    ) -> color_eyre::Result<Option<String>> {
+        let client = self.client(runner, dir)?;
+        match self.provider {
+            GitProvider::GitLab => gitlab::head_branch(&client, number),
+            _ => github::head_branch(&client, number),
+        }
+    }
+
+    fn create(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        request: &NewMergeRequest,
+    ) -> color_eyre::Result<Option<String>> {
+        refuse_cli_options(self.provider, request)?;
+        let client = self.client(runner, dir)?;
+        match self.provider {
+            GitProvider::GitLab => gitlab::create(&client, runner, dir, request),
+            _ => github::create(&client, runner, dir, request),
+        }
+    }
+
+    fn merge(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        number: u64,
+        delete_branch: bool,
+        strategy: MergeStrategy,
+    ) -> color_eyre::Result<MergeOutcome> {
+        let client = self.client(runner, dir)?;
+        match self.provider {
+            GitProvider::GitLab => gitlab::merge(&client, number, delete_branch, strategy),
+            _ => github::merge(&client, runner, dir, number, delete_branch, strategy),
+        }
+    }
+
+    fn comment(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        target: &str,
+        body: &str,
+    ) -> color_eyre::Result<Option<String>> {
+        let client = self.client(runner, dir)?;
+        match self.provider {
+            GitProvider::GitLab => gitlab::comment(&client, target, body),
+            _ => github::comment(&client, target, body),
+        }
+    }
+
+    fn close(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        target: &str,
+        comment: Option<&str>,
+    ) -> color_eyre::Result<()> {
+        let client = self.client(runner, dir)?;
+        match self.provider {
+            GitProvider::GitLab => gitlab::close(&client, target, comment),
+            _ => github::close(&client, target, comment),
+        }
+    }
+
+    fn review(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        target: &str,
+        verdict: ReviewVerdict,
+        body: Option<&str>,
+    ) -> color_eyre::Result<bool> {
+        let client = self.client(runner, dir)?;
+        match self.provider {
+            GitProvider::GitLab => gitlab::review(&client, target, verdict),
+            _ => github::review(&client, target, verdict, body),
+        }
+    }
+
+    fn checkout(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        number: u64,
+    ) -> color_eyre::Result<()> {
+        let client = self.client(runner, dir)?;
+        match self.provider {
+            GitProvider::GitLab => gitlab::checkout(&client, runner, dir, number),
+            _ => github::checkout(&client, runner, dir, number),
+        }
+    }
+
+    fn trigger_ci(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+        workflow: &str,
+    ) -> color_eyre::Result<()> {
+        let client = self.client(runner, dir)?;
+        match self.provider {
+            GitProvider::GitLab => gitlab::trigger_ci(&client, branch),
+            _ => github::trigger_ci(&client, branch, workflow),
+        }
+    }
+
+    fn ci_status(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<Option<PipelineStatus>> {
+        let client = self.client(runner, dir)?;
+        match self.provider {
+            GitProvider::GitLab => gitlab::ci_status(&client, branch),
+            _ => github::ci_status(&client, branch),
+        }
+    }
+}
+
+/// A repository on the provider and the API serving it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Repository {
+    api_url: String,
+    /// `owner/name` on GitHub, `group/subgroup/name` on GitLab.
+    path: String,
+}
+
+impl Repository {
+    /// The repository behind a remote URL such as
+    /// `git@github.com:acme/app.git` or `https://gitlab.example.com/group/app`.
+    /// GitHub Enterprise and self-managed GitLab are served from the host of
+    /// the remote.
+    fn from_remote_url(provider: GitProvider, url: &str) -> Option<Self> {
+        let (scheme, host, path) = match url.split_once("://") {
+            Some((scheme, rest)) => {
+                let (authority, path) = rest.split_once('/')?;
+                let host = authority.rsplit('@').next()?;
+                // The port of an SSH URL is not the one of the API.
+                let host = match scheme {
+                    "http" | "https" => host,
+                    _ => host.split(':').next()?,
+                };
+                (if scheme == "http" { "http" } else { "https" }, host, path)
+            }
+            None => {
+                let (destination, path) = url.split_once(':')?;
+                ("https", destination.rsplit('@').next()?, path)
+            }
+        };
+        let path = path.trim_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        if host.is_empty() || !path.contains('/') {
+            return None;
+        }
+        let api_url = match provider {
+            GitProvider::GitLab => format!("{scheme}://{host}/api/v4"),
+            _ if host == "github.com" => "https://api.github.com".to_owned(),
+            _ => format!("{scheme}://{host}/api/v3"),
+        };
+        Some(Self {
+            api_url,
+            path: path.to_owned(),
+        })
+    }
+}
+
+/// Sends the requests of one operation.
+#[derive(Debug)]
+pub(super) struct Client {
+    provider: GitProvider,
+    token: String,
+    http: Arc<dyn Http>,
+    repository: Repository,
+}
+
+impl Client {
+    /// The path of the repository on the provider.
+    pub(super) fn repository(&self) -> &str {
+        &self.repository.path
+    }
+
+    pub(super) fn get(&self, path: &str) -> color_eyre::Result<Value> {
+        self.send("GET", path, None)
+    }
+
+    pub(super) fn post(&self, path: &str, body: Value) -> color_eyre::Result<Value> {
+        self.send("POST", path, Some(body))
+    }
+
+    pub(super) fn put(&self, path: &str, body: Value) -> color_eyre::Result<Value> {
+        self.send("PUT", path, Some(body))
+    }
+
+    pub(super) fn patch(&self, path: &str, body: Value) -> color_eyre::Result<Value> {
+        self.send("PATCH", path, Some(body))
+    }
+
+    pub(super) fn delete(&self, path: &str) -> color_eyre::Result<Value> {
+        self.send("DELETE", path, None)
+    }
+
+    /// Sends a request to `path` below the API URL and returns the JSON it
+    /// answered with, `null` for an empty answer.
+    fn send(
+        &self,
+        method: &'static str,
+        path: &str,
+        body: Option<Value>,
+    ) -> color_eyre::Result<Value> {
+        let headers = match self.provider {
+            GitProvider::GitLab => vec![format!("PRIVATE-TOKEN: {}", self.token)],
+            _ => vec![
+                format!("Authorization: Bearer {}", self.token),
+                "Accept: application/vnd.github+json".to_owned(),
+                "X-GitHub-Api-Version: 2022-11-28".to_owned(),
+            ],
+        };
+        let _span = tracing::info_span!("api", request = %format!("{method} {path}")).entered();
+        let response = self.http.send(&Request {
+            method,
+            url: format!("{}{path}", self.repository.api_url),
+            headers,
+            body: body.map(|body| body.to_string()),
+        })?;
+        if !(200..300).contains(&response.status) {
+            return Err(eyre::eyre!(
+                "{method} {path} returned HTTP {}: {}",
+                response.status,
+                error_message(&response.body)
+            ));
+        }
+        if response.body.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_str(&response.body).wrap_err_with(|| {
+            eyre::eyre!(
+                "the {} API answered {method} {path} with invalid JSON",
+                self.provider.display_name()
+            )
+        })
+    }
+}
+
+/// The reason an API gives for an error: GitHub's `message` and the
+/// messages of its validation `errors`, GitLab's `message` or `error`.
+fn error_message(body: &str) -> String {
+    let Ok(value) = serde_json::from_str::<Value>(body) else {
+        return body.trim().to_owned();
+    };
+    let text = |value: &Value| match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    let mut message = value
+        .get("message")
+        .or_else(|| value.get("error"))
+        .map(text)
+        .unwrap_or_else(|| value.to_string());
+    if let Some(errors) = value.get("errors").and_then(Value::as_array) {
+        let details = errors
+            .iter()
+            .map(|error| error.get("message").map_or_else(|| text(error), text))
+            .collect::<Vec<_>>();
+        if !details.is_empty() {
+            message = format!("{message} ({})", details.join("; "));
+        }
+    }
+    message
+}
+
+/// Refuses what only the provider CLI can do with `request`: open it in the
+/// browser or take extra arguments.
+fn refuse_cli_options(provider: GitProvider, request: &NewMergeRequest) -> color_eyre::Result<()> {
+    let mut args = request.extra_args.clone();
+    if request.web {
+        args.insert(0, "--web".to_owned());
+    }
+    if args.is_empty() {
+        return Ok(());
+    }
+    Err(eyre::eyre!(
+        "the API backend cannot pass `{}` on; set `provider_backend = \"cli\"` to run `{}` with it",
+        args.join(" "),
+        provider.cli_program()
+    ))
+}
+
+/// `value` percent-encoded for a URL path segment or query value.
+pub(super) fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Runs git in `dir` and returns what it printed, trimmed.
+pub(super) fn git(
+    runner: &mut impl CommandRunner,
+    dir: &Path,
+    args: &[&str],
+) -> color_eyre::Result<String> {
+    let args = args.iter().map(|arg| (*arg).to_owned()).collect::<Vec<_>>();
+    let output = runner.run("git", dir, &args)?;
+    if !output.success {
+        return Err(command_failure("git", &args, &output));
+    }
+    Ok(output.stdout.trim().to_owned())
+}
+
+/// The subject and body of the last commit of `branch`, which become the
+/// title and description of a new request.
+pub(super) fn commit_message(
+    runner: &mut impl CommandRunner,
+    dir: &Path,
+    branch: &str,
+) -> color_eyre::Result<(String, String)> {
+    let subject = git(runner, dir, &["log", "-1", "--format=%s", branch, "--"])?;
+    let body = git(runner, dir, &["log", "-1", "--format=%b", branch, "--"])?;
+    Ok((subject, body))
+}
+
+/// Fetches `head_ref` and checks it out as `branch`, as `gh pr checkout`
+/// and `glab mr checkout` do.
+pub(super) fn check_out(
+    runner: &mut impl CommandRunner,
+    dir: &Path,
+    head_ref: &str,
+    branch: &str,
+) -> color_eyre::Result<()> {
+    git(runner, dir, &["fetch", "--quiet", REMOTE, head_ref])?;
+    git(
+        runner,
+        dir,
+        &["checkout", "--quiet", "-B", branch, "FETCH_HEAD"],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{
+        collections::VecDeque,
+        sync::{Mutex, PoisonError},
+    };
+
+    use crate::process::{CommandOutput, ScriptedRunner};
+
+    /// Answers requests from a script and records them.
+    #[derive(Debug, Default)]
+    pub(crate) struct ScriptedHttp {
+        responses: Mutex<VecDeque<Response>>,
+        requests: Mutex<Vec<Request>>,
+    }
+
+    impl ScriptedHttp {
+        pub(crate) fn push(&self, status: u16, body: &str) -> &Self {
+            self.responses
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push_back(Response {
+                    status,
+                    body: body.to_owned(),
+                });
+            self
+        }
+
+        /// The method and URL of every request, and its body.
+        pub(crate) fn requests(&self) -> Vec<(String, Option<Value>)> {
+            self.requests
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .map(|request| {
+                    (
+                        format!("{} {}", request.method, request.url),
+                        request
+                            .body
+                            .as_deref()
+                            .and_then(|body| serde_json::from_str(body).ok()),
+                    )
+                })
+                .collect()
+        }
+    }
+
+    impl Http for ScriptedHttp {
+        fn send(&self, request: &Request) -> color_eyre::Result<Response> {
+            self.requests
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(request.clone());
+            self.responses
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .pop_front()
+                .ok_or_else(|| eyre::eyre!("unexpected request {} {}", request.method, request.url))
+        }
+    }
+
+    /// An API backend for `provider` answering from `http`, and a runner
+    /// that expects `git remote get-url origin` to print `remote` first.
+    pub(crate) fn api(
+        provider: GitProvider,
+        remote: &str,
+        http: &Arc<ScriptedHttp>,
+    ) -> (ApiBackend, ScriptedRunner) {
+        let runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success(format!("{remote}\n")));
+        let http: Arc<dyn Http> = Arc::clone(http) as Arc<dyn Http>;
+        (
+            ApiBackend::with_http(provider, "secret".into(), http),
+            runner,
+        )
+    }
+
+    #[test]
+    fn repository_follows_the_remote_url() {
+        let repository = |provider, url| {
+            Repository::from_remote_url(provider, url)
+                .map(|repository| (repository.api_url, repository.path))
+        };
+        let github = |path: &str| Some(("https://api.github.com".to_owned(), path.to_owned()));
+        assert_eq!(
+            repository(GitProvider::GitHub, "git@github.com:acme/app.git"),
+            github("acme/app")
+        );
+        assert_eq!(
+            repository(GitProvider::GitHub, "https://token@github.com/acme/app"),
+            github("acme/app")
+        );
+        assert_eq!(
+            repository(
+                GitProvider::GitHub,
+                "ssh://git@ghe.example.com:2222/acme/app.git"
+            ),
+            Some((
+                "https://ghe.example.com/api/v3".to_owned(),
+                "acme/app".to_owned()
+            ))
+        );
+        assert_eq!(
+            repository(
+                GitProvider::GitLab,
+                "https://gitlab.example.com:8443/group/sub/app.git"
+            ),
+            Some((
+                "https://gitlab.example.com:8443/api/v4".to_owned(),
+                "group/sub/app".to_owned()
+            ))
+        );
+        assert_eq!(repository(GitProvider::GitHub, "/srv/git/app.git"), None);
+    }
+
+    #[test]
+    fn errors_carry_the_message_of_the_api() {
+        assert_eq!(
+            error_message(
+                r#"{"message":"Validation Failed","errors":[{"message":"A pull request already exists for acme:feature."}]}"#
+            ),
+            "Validation Failed (A pull request already exists for acme:feature.)"
+        );
+        assert_eq!(
+            error_message(r#"{"message":{"source_branch":["is invalid"]}}"#),
+            r#"{"source_branch":["is invalid"]}"#
+        );
+        assert_eq!(error_message("Bad Gateway\n"), "Bad Gateway");
+        assert_eq!(encode("group/app"), "group%2Fapp");
+        assert_eq!(encode("feature/a b"), "feature%2Fa%20b");
+    }
+
+    #[test]
+    fn agent_sends_the_headers_and_reads_error_statuses() -> color_eyre::Result<()> {
+        use std::{
+            io::{BufRead, BufReader, Write},
+            net::TcpListener,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}/repos/acme/app/pulls", listener.local_addr()?);
+        let server = std::thread::spawn(move || -> std::io::Result<Vec<String>> {
+            let (stream, _) = listener.accept()?;
+            let mut reader = BufReader::new(stream.try_clone()?);
+            let mut head = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line)?;
+                if line.trim().is_empty() {
+                    break;
+                }
+                head.push(line.trim().to_owned());
+            }
+            let body = r#"{"message":"Not Found"}"#;
+            write!(
+                &stream,
+                "HTTP/1.1 404 Not Found\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )?;
+            Ok(head)
+        });
+
+        let response = Agent::new().send(&Request {
+            method: "GET",
+            url,
+            headers: vec!["Authorization: Bearer secret".to_owned()],
+            body: None,
+        })?;
+
+        assert_eq!(response.status, 404);
+        assert_eq!(response.body, r#"{"message":"Not Found"}"#);
+        let head = server.join().expect("server thread")?;
+        assert_eq!(head[0], "GET /repos/acme/app/pulls HTTP/1.1");
+        assert!(
+            head.iter()
+                .any(|line| line == "Authorization: Bearer secret")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,434 @@
+//! How provider operations reach the provider: through its CLI, or with the
+//! `provider-api` feature through its REST API (`provider_backend = "api"`
+//! in `config.toml`).
+
+use std::{env, fmt, path::Path};
+
+use color_eyre::eyre;
+use serde::Deserialize;
+
+#[cfg(feature = "provider-api")]
+use super::api::ApiBackend;
+use super::{
+    CliBackend, GitProvider, MergeOutcome, MergeRequestStatus, MergeStrategy, NewMergeRequest,
+    PipelineStatus, ReviewVerdict,
+};
+use crate::{Repo, process::CommandRunner};
+
+/// Selects the backend when `config.toml` does not.
+pub const PROVIDER_BACKEND_ENV: &str = "RSWORKTREE_PROVIDER_BACKEND";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderBackend {
+    /// Run `gh`, `glab` and the other provider CLIs.
+    #[default]
+    Cli,
+    /// Call the GitHub or GitLab REST API with a token from the environment.
+    Api,
+}
+
+impl ProviderBackend {
+    /// The backend set by `provider_backend` in `config.toml`, then by
+    /// `RSWORKTREE_PROVIDER_BACKEND`; the CLI otherwise.
+    pub fn resolve(repo: &Repo) -> Self {
+        repo.config()
+            .ok()
+            .and_then(|config| config.provider_backend)
+            .or_else(|| {
+                env::var(PROVIDER_BACKEND_ENV)
+                    .ok()
+                    .and_then(|value| value.parse().ok())
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Cli => "cli",
+            Self::Api => "api",
+        }
+    }
+}
+
+impl fmt::Display for ProviderBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ProviderBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "cli" => Ok(Self::Cli),
+            "api" => Ok(Self::Api),
+            other => Err(format!(
+                "unknown provider backend `{other}`; expected `cli` or `api`"
+            )),
+        }
+    }
+}
+
+/// The pull/merge request and CI operations rsworktree runs on a provider.
+///
+/// [`CliBackend`] runs the provider CLI for them; with the `provider-api`
+/// feature, the API backend calls the GitHub or GitLab REST API instead.
+/// Requests are addressed by their source branch or by their number, see
+/// [`GitProvider::addresses_requests_by_branch`]; `runner` runs the CLI and
+/// the git commands an operation needs in `dir`.
+pub trait RequestBackend {
+    /// The number of the open request of `branch`.
+    fn list(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<Option<u64>>;
+
+    /// The request of `branch`, `None` when it has none. Errors are short
+    /// enough to be shown in place of the request.
+    fn view(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<Option<MergeRequestStatus>>;
+
+    /// The source branch of request `number`.
+    fn head_branch(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        number: u64,
+    ) -> color_eyre::Result<Option<String>>;
+
+    /// Creates `request` and returns its URL when the provider reports one.
+    fn create(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        request: &NewMergeRequest,
+    ) -> color_eyre::Result<Option<String>>;
+
+    /// Merges request `number` with `strategy`, deleting its branch with
+    /// `delete_branch`.
+    fn merge(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        number: u64,
+        delete_branch: bool,
+        strategy: MergeStrategy,
+    ) -> color_eyre::Result<MergeOutcome>;
+
+    /// Comments on request `target` and returns the URL of the comment when
+    /// the provider reports one.
+    fn comment(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        target: &str,
+        body: &str,
+    ) -> color_eyre::Result<Option<String>>;
+
+    /// Closes request `target` without merging it, after posting `comment`.
+    fn close(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        target: &str,
+        comment: Option<&str>,
+    ) -> color_eyre::Result<()>;
+
+    /// Submits `verdict` on request `target`, with `body` where
+    /// [`GitProvider::review_accepts_message`]. Returns `false` without
+    /// submitting anything when the provider has no such verdict.
+    fn review(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        target: &str,
+        verdict: ReviewVerdict,
+        body: Option<&str>,
+    ) -> color_eyre::Result<bool>;
+
+    /// Checks request `number` out in `dir`, fetching it from a fork where needed.
+    fn checkout(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        number: u64,
+    ) -> color_eyre::Result<()>;
+
+    /// Starts a CI run on `branch`; GitHub runs `workflow`.
+    fn trigger_ci(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+        workflow: &str,
+    ) -> color_eyre::Result<()>;
+
+    /// The latest CI run of `branch`, `None` when it has none yet.
+    fn ci_status(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<Option<PipelineStatus>>;
+}
+
+impl GitProvider {
+    /// The operations of this provider through `backend`.
+    pub fn client(self, backend: ProviderBackend) -> ProviderClient {
+        let selected = match backend {
+            ProviderBackend::Cli => Backend::Cli(CliBackend::new(self)),
+            #[cfg(feature = "provider-api")]
+            ProviderBackend::Api => match ApiBackend::load(self) {
+                Ok(api) => Backend::Api(api),
+                Err(error) => Backend::Unavailable(error.to_string()),
+            },
+            #[cfg(not(feature = "provider-api"))]
+            ProviderBackend::Api => Backend::Unavailable(format!(
+                "`provider_backend = \"api\"` needs rsworktree built with the `provider-api` feature (`cargo install rsworktree --features provider-api`); {} operations run `{}` otherwise",
+                self.display_name(),
+                self.cli_program()
+            )),
+        };
+        ProviderClient {
+            provider: self,
+            backend: selected,
+        }
+    }
+}
+
+/// The operations of a provider through the backend selected for it.
+#[derive(Debug, Clone, Default)]
+pub struct ProviderClient {
+    provider: GitProvider,
+    backend: Backend,
+}
+
+#[derive(Debug, Clone)]
+enum Backend {
+    Cli(CliBackend),
+    #[cfg(feature = "provider-api")]
+    Api(ApiBackend),
+    /// The API backend is selected but cannot be used; every operation
+    /// fails with this reason, so lookups degrade as they do when the CLI
+    /// is missing.
+    Unavailable(String),
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self::Cli(CliBackend::default())
+    }
+}
+
+impl ProviderClient {
+    #[cfg(all(test, feature = "provider-api"))]
+    pub(crate) fn with_api(api: ApiBackend) -> Self {
+        Self {
+            provider: api.provider,
+            backend: Backend::Api(api),
+        }
+    }
+
+    pub fn provider(&self) -> GitProvider {
+        self.provider
+    }
+
+    /// The selected backend, even when it cannot be used.
+    pub fn backend(&self) -> ProviderBackend {
+        match self.backend {
+            Backend::Cli(_) => ProviderBackend::Cli,
+            _ => ProviderBackend::Api,
+        }
+    }
+
+    /// Why the selected API backend cannot be used: rsworktree was built
+    /// without the `provider-api` feature, the provider has no API support
+    /// or its token is not set.
+    pub fn unavailable(&self) -> Option<&str> {
+        match &self.backend {
+            Backend::Unavailable(reason) => Some(reason),
+            _ => None,
+        }
+    }
+}
+
+/// Runs an operation on the selected backend.
+macro_rules! dispatch {
+    ($client:ident.$operation:ident($($arg:expr),*)) => {
+        match &mut $client.backend {
+            Backend::Cli(cli) => cli.$operation($($arg),*),
+            #[cfg(feature = "provider-api")]
+            Backend::Api(api) => api.$operation($($arg),*),
+            Backend::Unavailable(reason) => Err(eyre::eyre!("{reason}")),
+        }
+    };
+}
+
+impl RequestBackend for ProviderClient {
+    fn list(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<Option<u64>> {
+        dispatch!(self.list(runner, dir, branch))
+    }
+
+    fn view(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<Option<MergeRequestStatus>> {
+        dispatch!(self.view(runner, dir, branch))
+    }
+
+    fn head_branch(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        number: u64,
+    ) -> color_eyre::Result<Option<String>> {
+        dispatch!(self.head_branch(runner, dir, number))
+    }
+
+    fn create(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        request: &NewMergeRequest,
+    ) -> color_eyre::Result<Option<String>> {
+        dispatch!(self.create(runner, dir, request))
+    }
+
+    fn merge(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        number: u64,
+        delete_branch: bool,
+        strategy: MergeStrategy,
+    ) -> color_eyre::Result<MergeOutcome> {
+        dispatch!(self.merge(runner, dir, number, delete_branch, strategy))
+    }
+
+    fn comment(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        target: &str,
+        body: &str,
+    ) -> color_eyre::Result<Option<String>> {
+        dispatch!(self.comment(runner, dir, target, body))
+    }
+
+    fn close(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        target: &str,
+        comment: Option<&str>,
+    ) -> color_eyre::Result<()> {
+        dispatch!(self.close(runner, dir, target, comment))
+    }
+
+    fn review(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        target: &str,
+        verdict: ReviewVerdict,
+        body: Option<&str>,
+    ) -> color_eyre::Result<bool> {
+        dispatch!(self.review(runner, dir, target, verdict, body))
+    }
+
+    fn checkout(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        number: u64,
+    ) -> color_eyre::Result<()> {
+        dispatch!(self.checkout(runner, dir, number))
+    }
+
+    fn trigger_ci(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+        workflow: &str,
+    ) -> color_eyre::Result<()> {
+        dispatch!(self.trigger_ci(runner, dir, branch, workflow))
+    }
+
+    fn ci_status(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<Option<PipelineStatus>> {
+        dispatch!(self.ci_status(runner, dir, branch))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::process::{CommandOutput, ScriptedRunner};
+
+    #[test]
+    fn backend_parses_from_its_name() {
+        assert_eq!("api".parse(), Ok(ProviderBackend::Api));
+        assert_eq!(" CLI ".parse(), Ok(ProviderBackend::Cli));
+        assert!("rest".parse::<ProviderBackend>().is_err());
+        assert_eq!(ProviderBackend::default(), ProviderBackend::Cli);
+    }
+
+    #[test]
+    fn cli_backend_runs_the_provider_cli() -> color_eyre::Result<()> {
+        let mut runner = ScriptedRunner::new();
+        runner.push(CommandOutput::success(r#"[{"number":4}]"#));
+        let mut client = GitProvider::GitHub.client(ProviderBackend::Cli);
+        assert_eq!(client.backend(), ProviderBackend::Cli);
+
+        assert_eq!(
+            client.list(&mut runner, Path::new("/repo"), "feature")?,
+            Some(4)
+        );
+        assert_eq!(runner.calls()[0].program, "gh");
+        assert_eq!(
+            runner.calls()[0].args,
+            GitProvider::GitHub.build_list_args("feature")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unusable_api_backend_fails_every_operation() {
+        let mut runner = ScriptedRunner::new();
+        // Gitea has no API backend, and without `provider-api` none has.
+        let mut client = GitProvider::Gitea.client(ProviderBackend::Api);
+        assert_eq!(client.backend(), ProviderBackend::Api);
+        let reason = client
+            .unavailable()
+            .expect("the API cannot be used")
+            .to_owned();
+        #[cfg(not(feature = "provider-api"))]
+        assert!(reason.contains("--features provider-api"), "{reason}");
+
+        let error = client
+            .view(&mut runner, Path::new("/repo"), "feature")
+            .expect_err("the API cannot be used");
+        assert_eq!(error.to_string(), reason);
+        assert!(runner.calls().is_empty());
+    }
+}
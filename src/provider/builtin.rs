@@ -0,0 +1,770 @@
+//! The providers rsworktree ships with, each driving its own CLI.
+
+use super::{
+    BitbucketPipelinePayload, GITEA_LIST_LIMIT, GitHubRunPayload, GitLabPipelinePayload,
+    GiteaPullPayload, HeadBranchPayload, MergeRequestStatus, MergeStrategy, PipelineState,
+    PipelineStatus, Provider, ReviewVerdict, ViewPayload, azure_update_args, gerrit,
+    parse_azure_pulls, parse_gitea_pulls,
+};
+
+/// The built-in variants of [`super::GitProvider`], which
+/// [`super::GitProvider::provider`] hands out as their [`Provider`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum BuiltIn {
+    GitHub,
+    GitLab,
+    Bitbucket,
+    Gitea,
+    AzureDevOps,
+    Gerrit,
+}
+
+impl Provider for BuiltIn {
+    fn name(&self) -> &'static str {
+        match self {
+            BuiltIn::GitHub => "github",
+            BuiltIn::GitLab => "gitlab",
+            BuiltIn::Bitbucket => "bitbucket",
+            BuiltIn::Gitea => "gitea",
+            BuiltIn::AzureDevOps => "azuredevops",
+            BuiltIn::Gerrit => "gerrit",
+        }
+    }
+
+    fn cli_program(&self) -> &'static str {
+        match self {
+            BuiltIn::GitHub => "gh",
+            BuiltIn::GitLab => "glab",
+            BuiltIn::Bitbucket => "bb",
+            BuiltIn::Gitea => "tea",
+            BuiltIn::AzureDevOps => "az",
+            BuiltIn::Gerrit => "ssh",
+        }
+    }
+
+    fn display_name(&self) -> &'static str {
+        match self {
+            BuiltIn::GitHub => "GitHub",
+            BuiltIn::GitLab => "GitLab",
+            BuiltIn::Bitbucket => "Bitbucket",
+            BuiltIn::Gitea => "Gitea",
+            BuiltIn::AzureDevOps => "Azure DevOps",
+            BuiltIn::Gerrit => "Gerrit",
+        }
+    }
+
+    fn merge_request_term(&self) -> &'static str {
+        match self {
+            BuiltIn::GitHub | BuiltIn::Bitbucket | BuiltIn::Gitea | BuiltIn::AzureDevOps => {
+                "pull request"
+            }
+            BuiltIn::GitLab => "merge request",
+            BuiltIn::Gerrit => "change",
+        }
+    }
+
+    fn merge_request_short(&self) -> &'static str {
+        match self {
+            BuiltIn::GitHub | BuiltIn::Bitbucket | BuiltIn::Gitea | BuiltIn::AzureDevOps => "PR",
+            BuiltIn::GitLab => "MR",
+            BuiltIn::Gerrit => "change",
+        }
+    }
+
+    fn request_subcommand(&self) -> &'static str {
+        match self {
+            BuiltIn::GitHub | BuiltIn::Bitbucket | BuiltIn::Gitea => "pr",
+            BuiltIn::GitLab => "mr",
+            BuiltIn::AzureDevOps => "repos pr",
+            BuiltIn::Gerrit => "gerrit",
+        }
+    }
+
+    fn request_prefix(&self) -> &'static str {
+        match self {
+            BuiltIn::GitHub | BuiltIn::Bitbucket | BuiltIn::Gitea => "#",
+            BuiltIn::GitLab | BuiltIn::AzureDevOps => "!",
+            BuiltIn::Gerrit => "",
+        }
+    }
+
+    fn addresses_requests_by_branch(&self) -> bool {
+        !matches!(
+            self,
+            BuiltIn::Gitea | BuiltIn::AzureDevOps | BuiltIn::Gerrit
+        )
+    }
+
+    fn supports_title(&self) -> bool {
+        !matches!(self, BuiltIn::Bitbucket | BuiltIn::Gitea | BuiltIn::Gerrit)
+    }
+
+    fn supports_comments(&self) -> bool {
+        !matches!(self, BuiltIn::AzureDevOps | BuiltIn::Gerrit)
+    }
+
+    fn supports_merge_strategy(&self, strategy: MergeStrategy) -> bool {
+        match self {
+            BuiltIn::GitHub | BuiltIn::GitLab | BuiltIn::Gitea => true,
+            BuiltIn::AzureDevOps => strategy != MergeStrategy::Rebase,
+            _ => strategy == MergeStrategy::Merge,
+        }
+    }
+
+    fn review_accepts_message(&self) -> bool {
+        matches!(self, BuiltIn::GitHub | BuiltIn::Gitea)
+    }
+
+    fn build_create_args(
+        &self,
+        branch: &str,
+        draft: bool,
+        fill: bool,
+        web: bool,
+        reviewers: &[String],
+        extra_args: &[String],
+    ) -> Vec<String> {
+        let mut args: Vec<String> = self
+            .request_subcommand()
+            .split(' ')
+            .map(str::to_owned)
+            .collect();
+        args.push("create".to_owned());
+
+        // Branch specification differs between providers
+        match self {
+            BuiltIn::GitHub => {
+                args.push("--head".to_owned());
+                args.push(branch.to_owned());
+            }
+            BuiltIn::GitLab => {
+                args.push("--source-branch".to_owned());
+                args.push(branch.to_owned());
+            }
+            BuiltIn::Bitbucket => {
+                args.push("--source".to_owned());
+                args.push(branch.to_owned());
+            }
+            BuiltIn::Gitea => {
+                args.push("--head".to_owned());
+                args.push(branch.to_owned());
+            }
+            BuiltIn::AzureDevOps => {
+                args.push("--source-branch".to_owned());
+                args.push(branch.to_owned());
+            }
+            BuiltIn::Gerrit => return Vec::new(),
+        }
+
+        if draft {
+            match self {
+                BuiltIn::Gitea => {}
+                BuiltIn::AzureDevOps => {
+                    args.push("--draft".to_owned());
+                    args.push("true".to_owned());
+                }
+                _ => args.push("--draft".to_owned()),
+            }
+        }
+
+        if fill {
+            match self {
+                BuiltIn::GitHub => args.push("--fill".to_owned()),
+                BuiltIn::GitLab => args.push("--fill".to_owned()),
+                // `bb`, `tea` and `az` fill the title and description from the commits by default.
+                _ => {}
+            }
+        }
+
+        if web {
+            match self {
+                BuiltIn::GitHub => args.push("--web".to_owned()),
+                BuiltIn::GitLab => args.push("--web".to_owned()),
+                BuiltIn::Bitbucket => args.push("--web".to_owned()),
+                BuiltIn::AzureDevOps => args.push("--open".to_owned()),
+                _ => {}
+            }
+        }
+
+        // `az` takes all reviewers after a single `--reviewers` flag.
+        if *self == BuiltIn::AzureDevOps && !reviewers.is_empty() {
+            args.push("--reviewers".to_owned());
+            args.extend(reviewers.iter().cloned());
+        }
+
+        for reviewer in reviewers {
+            match self {
+                BuiltIn::GitHub => {
+                    args.push("--reviewer".to_owned());
+                    args.push(reviewer.clone());
+                }
+                BuiltIn::GitLab => {
+                    args.push("--reviewer".to_owned());
+                    args.push(reviewer.clone());
+                }
+                BuiltIn::Bitbucket => {
+                    args.push("--reviewer".to_owned());
+                    args.push(reviewer.clone());
+                }
+                _ => {}
+            }
+        }
+
+        args.extend(extra_args.iter().cloned());
+
+        args
+    }
+
+    fn build_list_args(&self, branch: &str) -> Vec<String> {
+        match self {
+            BuiltIn::GitHub => vec![
+                "pr".to_owned(),
+                "list".to_owned(),
+                "--head".to_owned(),
+                branch.to_owned(),
+                "--state".to_owned(),
+                "open".to_owned(),
+                "--json".to_owned(),
+                "number".to_owned(),
+                "--limit".to_owned(),
+                "1".to_owned(),
+            ],
+            BuiltIn::GitLab => vec![
+                "mr".to_owned(),
+                "list".to_owned(),
+                "--source-branch".to_owned(),
+                branch.to_owned(),
+                "--state".to_owned(),
+                "opened".to_owned(),
+                "--output".to_owned(),
+                "json".to_owned(),
+            ],
+            BuiltIn::Bitbucket => vec![
+                "pr".to_owned(),
+                "list".to_owned(),
+                "--source".to_owned(),
+                branch.to_owned(),
+                "--state".to_owned(),
+                "OPEN".to_owned(),
+                "--output".to_owned(),
+                "json".to_owned(),
+            ],
+            BuiltIn::Gitea => vec![
+                "pr".to_owned(),
+                "list".to_owned(),
+                "--state".to_owned(),
+                "open".to_owned(),
+                "--limit".to_owned(),
+                GITEA_LIST_LIMIT.to_string(),
+                "--fields".to_owned(),
+                "index,head,state,url".to_owned(),
+                "--output".to_owned(),
+                "json".to_owned(),
+            ],
+            BuiltIn::AzureDevOps => vec![
+                "repos".to_owned(),
+                "pr".to_owned(),
+                "list".to_owned(),
+                "--source-branch".to_owned(),
+                branch.to_owned(),
+                "--status".to_owned(),
+                "active".to_owned(),
+                "--output".to_owned(),
+                "json".to_owned(),
+            ],
+            BuiltIn::Gerrit => gerrit::query_args(branch),
+        }
+    }
+
+    fn parse_list_output(
+        &self,
+        branch: &str,
+        stdout: &str,
+    ) -> Result<Option<u64>, serde_json::Error> {
+        let stdout = stdout.trim();
+        if stdout.is_empty() {
+            return Ok(None);
+        }
+
+        match self {
+            BuiltIn::Gitea => {
+                Ok(parse_gitea_pulls(branch, stdout)?.and_then(|pull| pull.index.parse().ok()))
+            }
+            BuiltIn::AzureDevOps => {
+                Ok(parse_azure_pulls(branch, stdout)?.map(|pull| pull.pull_request_id))
+            }
+            BuiltIn::Gerrit => Ok(gerrit::parse_query_output(stdout)?.map(|change| change.number)),
+            _ => {
+                let requests: Vec<ViewPayload> = serde_json::from_str(stdout)?;
+                Ok(requests.into_iter().next().map(|request| request.number()))
+            }
+        }
+    }
+
+    fn build_view_args(&self, branch: &str) -> Vec<String> {
+        match self {
+            BuiltIn::Gitea | BuiltIn::AzureDevOps => self.build_list_args(branch),
+            BuiltIn::GitHub => vec![
+                "pr".to_owned(),
+                "view".to_owned(),
+                branch.to_owned(),
+                "--json".to_owned(),
+                "number,url,state".to_owned(),
+            ],
+            BuiltIn::GitLab | BuiltIn::Bitbucket => vec![
+                self.request_subcommand().to_owned(),
+                "view".to_owned(),
+                branch.to_owned(),
+                "--output".to_owned(),
+                "json".to_owned(),
+            ],
+            BuiltIn::Gerrit => gerrit::query_args(branch),
+        }
+    }
+
+    fn parse_view_output(
+        &self,
+        branch: &str,
+        stdout: &str,
+    ) -> Result<Option<MergeRequestStatus>, serde_json::Error> {
+        if *self == BuiltIn::Gerrit {
+            return Ok(gerrit::parse_query_output(stdout)?.map(gerrit::Change::into_status));
+        }
+
+        if *self == BuiltIn::Gitea {
+            return Ok(
+                parse_gitea_pulls(branch, stdout)?.map(|pull| MergeRequestStatus {
+                    number: pull.index.parse().unwrap_or(0),
+                    url: Some(pull.url).filter(|url| !url.is_empty()),
+                    state: pull.state.to_lowercase(),
+                }),
+            );
+        }
+
+        if *self == BuiltIn::AzureDevOps {
+            return Ok(
+                parse_azure_pulls(branch, stdout)?.map(|pull| MergeRequestStatus {
+                    number: pull.pull_request_id,
+                    url: pull.web_url(),
+                    state: pull.status.to_lowercase(),
+                }),
+            );
+        }
+
+        let payload: ViewPayload = serde_json::from_str(stdout)?;
+        Ok(Some(MergeRequestStatus {
+            number: payload.number(),
+            url: payload.url.or(payload.web_url).or(payload
+                .links
+                .and_then(|links| links.html)
+                .map(|html| html.href)),
+            state: payload.state.unwrap_or_default().to_lowercase(),
+        }))
+    }
+
+    fn build_head_branch_args(&self, number: u64) -> Vec<String> {
+        match self {
+            BuiltIn::GitHub => vec![
+                "pr".to_owned(),
+                "view".to_owned(),
+                number.to_string(),
+                "--json".to_owned(),
+                "headRefName".to_owned(),
+            ],
+            BuiltIn::GitLab | BuiltIn::Bitbucket => vec![
+                self.request_subcommand().to_owned(),
+                "view".to_owned(),
+                number.to_string(),
+                "--output".to_owned(),
+                "json".to_owned(),
+            ],
+            BuiltIn::Gitea => self.build_list_args(""),
+            BuiltIn::AzureDevOps => vec![
+                "repos".to_owned(),
+                "pr".to_owned(),
+                "show".to_owned(),
+                "--id".to_owned(),
+                number.to_string(),
+                "--output".to_owned(),
+                "json".to_owned(),
+            ],
+            BuiltIn::Gerrit => gerrit::change_query_args(number),
+        }
+    }
+
+    fn parse_head_branch(
+        &self,
+        number: u64,
+        stdout: &str,
+    ) -> Result<Option<String>, serde_json::Error> {
+        let stdout = stdout.trim();
+        if stdout.is_empty() {
+            return Ok(None);
+        }
+        if *self == BuiltIn::Gerrit {
+            return Ok(gerrit::parse_query_output(stdout)?.and_then(|change| change.topic));
+        }
+        if *self == BuiltIn::Gitea {
+            let pulls: Vec<GiteaPullPayload> = serde_json::from_str(stdout)?;
+            return Ok(pulls
+                .into_iter()
+                .find(|pull| pull.index == number.to_string())
+                .map(|pull| pull.head));
+        }
+
+        let payload: HeadBranchPayload = serde_json::from_str(stdout)?;
+        let branch = payload
+            .head_ref_name
+            .or(payload.source_branch)
+            .or(payload.source.map(|source| source.branch.name))
+            .or(payload.source_ref_name.map(|name| {
+                name.strip_prefix("refs/heads/")
+                    .map(str::to_owned)
+                    .unwrap_or(name)
+            }));
+        Ok(branch.filter(|branch| !branch.is_empty()))
+    }
+
+    fn build_checkout_args(&self, number: u64) -> Vec<String> {
+        match self {
+            BuiltIn::AzureDevOps => vec![
+                "repos".to_owned(),
+                "pr".to_owned(),
+                "checkout".to_owned(),
+                "--id".to_owned(),
+                number.to_string(),
+            ],
+            BuiltIn::Gerrit => Vec::new(),
+            _ => vec![
+                self.request_subcommand().to_owned(),
+                "checkout".to_owned(),
+                number.to_string(),
+            ],
+        }
+    }
+
+    fn build_merge_args(
+        &self,
+        mr_number: u64,
+        delete_branch: bool,
+        strategy: MergeStrategy,
+    ) -> Vec<String> {
+        match self {
+            BuiltIn::GitHub => {
+                let mut args = vec![
+                    "pr".to_owned(),
+                    "merge".to_owned(),
+                    mr_number.to_string(),
+                    format!("--{strategy}"),
+                ];
+                if delete_branch {
+                    args.push("--delete-branch".to_owned());
+                }
+                args
+            }
+            BuiltIn::GitLab => {
+                let mut args = vec!["mr".to_owned(), "merge".to_owned(), mr_number.to_string()];
+                // `glab` merges by default.
+                if strategy != MergeStrategy::Merge {
+                    args.push(format!("--{strategy}"));
+                }
+                if delete_branch {
+                    args.push("--remove-source-branch".to_owned());
+                }
+                args
+            }
+            BuiltIn::Bitbucket => {
+                let mut args = vec!["pr".to_owned(), "merge".to_owned(), mr_number.to_string()];
+                if delete_branch {
+                    args.push("--close-source-branch".to_owned());
+                }
+                args
+            }
+            BuiltIn::Gitea => vec![
+                "pr".to_owned(),
+                "merge".to_owned(),
+                mr_number.to_string(),
+                "--style".to_owned(),
+                strategy.to_string(),
+            ],
+            BuiltIn::AzureDevOps => {
+                let mut args = azure_update_args(&mr_number.to_string());
+                args.push("--auto-complete".to_owned());
+                args.push("true".to_owned());
+                if strategy == MergeStrategy::Squash {
+                    args.push("--squash".to_owned());
+                    args.push("true".to_owned());
+                }
+                if delete_branch {
+                    args.push("--delete-source-branch".to_owned());
+                    args.push("true".to_owned());
+                }
+                args
+            }
+            BuiltIn::Gerrit => Vec::new(),
+        }
+    }
+
+    fn build_comment_args(&self, branch: &str, body: &str) -> Option<Vec<String>> {
+        let args = match self {
+            BuiltIn::GitHub => vec![
+                "pr".to_owned(),
+                "comment".to_owned(),
+                branch.to_owned(),
+                "--body".to_owned(),
+                body.to_owned(),
+            ],
+            BuiltIn::GitLab => vec![
+                "mr".to_owned(),
+                "note".to_owned(),
+                branch.to_owned(),
+                "--message".to_owned(),
+                body.to_owned(),
+            ],
+            BuiltIn::Bitbucket => vec![
+                "pr".to_owned(),
+                "comment".to_owned(),
+                branch.to_owned(),
+                "--body".to_owned(),
+                body.to_owned(),
+            ],
+            BuiltIn::Gitea => vec!["comment".to_owned(), branch.to_owned(), body.to_owned()],
+            BuiltIn::AzureDevOps | BuiltIn::Gerrit => return None,
+        };
+        Some(args)
+    }
+
+    fn build_close_args(&self, branch: &str, comment: Option<&str>) -> Vec<String> {
+        match self {
+            BuiltIn::GitHub => {
+                let mut args = vec!["pr".to_owned(), "close".to_owned(), branch.to_owned()];
+                if let Some(comment) = comment {
+                    args.push("--comment".to_owned());
+                    args.push(comment.to_owned());
+                }
+                args
+            }
+            BuiltIn::GitLab => vec!["mr".to_owned(), "close".to_owned(), branch.to_owned()],
+            BuiltIn::Bitbucket => {
+                vec!["pr".to_owned(), "decline".to_owned(), branch.to_owned()]
+            }
+            BuiltIn::Gitea => vec!["pr".to_owned(), "close".to_owned(), branch.to_owned()],
+            BuiltIn::AzureDevOps => {
+                let mut args = azure_update_args(branch);
+                args.push("--status".to_owned());
+                args.push("abandoned".to_owned());
+                args
+            }
+            BuiltIn::Gerrit => Vec::new(),
+        }
+    }
+
+    fn build_review_args(
+        &self,
+        branch: &str,
+        verdict: ReviewVerdict,
+        body: Option<&str>,
+    ) -> Option<Vec<String>> {
+        match self {
+            BuiltIn::GitHub => {
+                let flag = match verdict {
+                    ReviewVerdict::Approve => "--approve",
+                    ReviewVerdict::RequestChanges => "--request-changes",
+                };
+                let mut args = vec![
+                    "pr".to_owned(),
+                    "review".to_owned(),
+                    branch.to_owned(),
+                    flag.to_owned(),
+                ];
+                if let Some(body) = body {
+                    args.push("--body".to_owned());
+                    args.push(body.to_owned());
+                }
+                Some(args)
+            }
+            BuiltIn::GitLab => match verdict {
+                ReviewVerdict::Approve => Some(vec![
+                    "mr".to_owned(),
+                    "approve".to_owned(),
+                    branch.to_owned(),
+                ]),
+                ReviewVerdict::RequestChanges => None,
+            },
+            BuiltIn::Bitbucket => {
+                let action = match verdict {
+                    ReviewVerdict::Approve => "approve",
+                    ReviewVerdict::RequestChanges => "request-changes",
+                };
+                Some(vec!["pr".to_owned(), action.to_owned(), branch.to_owned()])
+            }
+            BuiltIn::Gitea => {
+                let action = match verdict {
+                    ReviewVerdict::Approve => "approve",
+                    ReviewVerdict::RequestChanges => "reject",
+                };
+                let mut args = vec!["pr".to_owned(), action.to_owned(), branch.to_owned()];
+                args.extend(body.map(str::to_owned));
+                Some(args)
+            }
+            BuiltIn::AzureDevOps => {
+                let vote = match verdict {
+                    ReviewVerdict::Approve => "approve",
+                    ReviewVerdict::RequestChanges => "wait-for-author",
+                };
+                Some(vec![
+                    "repos".to_owned(),
+                    "pr".to_owned(),
+                    "set-vote".to_owned(),
+                    "--id".to_owned(),
+                    branch.to_owned(),
+                    "--vote".to_owned(),
+                    vote.to_owned(),
+                ])
+            }
+            BuiltIn::Gerrit => None,
+        }
+    }
+
+    fn build_ci_trigger_args(&self, branch: &str, workflow: &str) -> Option<Vec<String>> {
+        let args = match self {
+            BuiltIn::GitHub => vec![
+                "workflow".to_owned(),
+                "run".to_owned(),
+                workflow.to_owned(),
+                "--ref".to_owned(),
+                branch.to_owned(),
+            ],
+            BuiltIn::GitLab => vec![
+                "ci".to_owned(),
+                "run".to_owned(),
+                "--branch".to_owned(),
+                branch.to_owned(),
+            ],
+            BuiltIn::Bitbucket => vec![
+                "pipeline".to_owned(),
+                "run".to_owned(),
+                "--branch".to_owned(),
+                branch.to_owned(),
+            ],
+            BuiltIn::Gitea | BuiltIn::AzureDevOps | BuiltIn::Gerrit => return None,
+        };
+        Some(args)
+    }
+
+    fn build_ci_status_args(&self, branch: &str) -> Option<Vec<String>> {
+        let args = match self {
+            BuiltIn::GitHub => vec![
+                "run".to_owned(),
+                "list".to_owned(),
+                "--branch".to_owned(),
+                branch.to_owned(),
+                "--limit".to_owned(),
+                "1".to_owned(),
+                "--json".to_owned(),
+                "databaseId,workflowName,status,conclusion,url".to_owned(),
+            ],
+            BuiltIn::GitLab => vec![
+                "ci".to_owned(),
+                "get".to_owned(),
+                "--branch".to_owned(),
+                branch.to_owned(),
+                "--output".to_owned(),
+                "json".to_owned(),
+            ],
+            BuiltIn::Bitbucket => vec![
+                "pipeline".to_owned(),
+                "list".to_owned(),
+                "--branch".to_owned(),
+                branch.to_owned(),
+                "--limit".to_owned(),
+                "1".to_owned(),
+                "--output".to_owned(),
+                "json".to_owned(),
+            ],
+            BuiltIn::Gitea | BuiltIn::AzureDevOps | BuiltIn::Gerrit => return None,
+        };
+        Some(args)
+    }
+
+    fn parse_ci_status_output(
+        &self,
+        stdout: &str,
+    ) -> Result<Option<PipelineStatus>, serde_json::Error> {
+        let stdout = stdout.trim();
+        if stdout.is_empty() {
+            return Ok(None);
+        }
+
+        match self {
+            BuiltIn::GitHub => {
+                let runs: Vec<GitHubRunPayload> = serde_json::from_str(stdout)?;
+                Ok(runs.into_iter().next().map(|run| PipelineStatus {
+                    id: run.database_id,
+                    name: run.workflow_name.filter(|name| !name.is_empty()),
+                    state: PipelineState::from_github(&run.status, run.conclusion.as_deref()),
+                    url: run.url,
+                }))
+            }
+            BuiltIn::GitLab => {
+                let pipeline: Option<GitLabPipelinePayload> = serde_json::from_str(stdout)?;
+                Ok(pipeline.map(|pipeline| PipelineStatus {
+                    id: pipeline.id,
+                    name: pipeline.name.filter(|name| !name.is_empty()),
+                    state: PipelineState::from_gitlab(&pipeline.status),
+                    url: pipeline.web_url,
+                }))
+            }
+            BuiltIn::Bitbucket => {
+                let pipelines: Vec<BitbucketPipelinePayload> = serde_json::from_str(stdout)?;
+                Ok(pipelines.into_iter().next().map(|pipeline| PipelineStatus {
+                    id: pipeline.build_number,
+                    name: None,
+                    state: PipelineState::from_bitbucket(
+                        &pipeline.state.name,
+                        pipeline
+                            .state
+                            .result
+                            .as_ref()
+                            .map(|result| result.name.as_str()),
+                    ),
+                    url: pipeline
+                        .links
+                        .and_then(|links| links.html)
+                        .map(|html| html.href),
+                }))
+            }
+            BuiltIn::Gitea | BuiltIn::AzureDevOps | BuiltIn::Gerrit => Ok(None),
+        }
+    }
+
+    fn is_branch_delete_failure(&self, stderr: &str) -> bool {
+        let stderr_lower = stderr.to_lowercase();
+        match self {
+            BuiltIn::GitHub => {
+                stderr_lower.contains("failed to delete local branch")
+                    || stderr_lower.contains("cannot delete branch")
+            }
+            BuiltIn::GitLab => {
+                stderr_lower.contains("failed to delete")
+                    || stderr_lower.contains("could not remove")
+            }
+            BuiltIn::Bitbucket => {
+                stderr_lower.contains("failed to delete")
+                    || stderr_lower.contains("could not delete branch")
+                    || stderr_lower.contains("source branch could not be closed")
+            }
+            BuiltIn::Gitea => {
+                stderr_lower.contains("failed to delete")
+                    || stderr_lower.contains("could not delete branch")
+                    || stderr_lower.contains("branch deletion failed")
+            }
+            BuiltIn::AzureDevOps => {
+                stderr_lower.contains("failed to delete")
+                    || stderr_lower.contains("could not delete source branch")
+            }
+            // Gerrit never deletes the branch.
+            BuiltIn::Gerrit => false,
+        }
+    }
+}
@@ -0,0 +1,391 @@
+//! The CLI backend: provider operations run the provider CLI with the
+//! arguments [`GitProvider`] builds, and what it prints is parsed back.
+
+use std::path::Path;
+
+use color_eyre::eyre::{self, WrapErr};
+
+use super::{
+    GitProvider, MergeOutcome, MergeRequestStatus, MergeStrategy, NewMergeRequest, PipelineStatus,
+    RequestBackend, ReviewVerdict, gerrit::SshTarget,
+};
+use crate::{
+    commands::rm::upstream_remote,
+    process::{CommandOutput, CommandRunner, command_failure},
+};
+
+/// Runs the operations of a provider with its CLI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CliBackend {
+    provider: GitProvider,
+}
+
+impl CliBackend {
+    pub fn new(provider: GitProvider) -> Self {
+        Self { provider }
+    }
+
+    /// Runs the CLI with `args` in `dir` and fails unless it succeeds.
+    fn run(
+        &self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        args: &[String],
+    ) -> color_eyre::Result<CommandOutput> {
+        let program = self.provider.cli_program();
+        let output = runner
+            .run(program, dir, args)
+            .wrap_err_with(|| format!("failed to run `{program} {}`", command_name(args)))?;
+        if !output.success {
+            return Err(command_failure(program, args, &output));
+        }
+        Ok(output)
+    }
+
+    /// `args` behind the SSH destination of the Gerrit server `branch` is
+    /// pushed to; unchanged on the other providers.
+    fn on_server(
+        &self,
+        dir: &Path,
+        branch: &str,
+        args: Vec<String>,
+    ) -> color_eyre::Result<Vec<String>> {
+        if self.provider != GitProvider::Gerrit {
+            return Ok(args);
+        }
+        let repo = git2::Repository::open(dir)
+            .wrap_err_with(|| eyre::eyre!("failed to open `{}`", dir.display()))?;
+        let remote = upstream_remote(&repo, branch);
+        let target = SshTarget::from_remote(&repo, &remote).ok_or_else(|| {
+            eyre::eyre!("remote `{remote}` does not point to a Gerrit server over SSH or HTTP")
+        })?;
+        Ok(target.command(args))
+    }
+
+    fn parse_failure(&self, args: &[String]) -> String {
+        format!(
+            "failed to parse `{} {}` output as JSON",
+            self.provider.cli_program(),
+            command_name(args)
+        )
+    }
+
+    fn unsupported(&self, operation: &str) -> eyre::Report {
+        eyre::eyre!(
+            "`{}` cannot {operation}, so this is not supported on {}",
+            self.provider.cli_program(),
+            self.provider.display_name()
+        )
+    }
+}
+
+impl RequestBackend for CliBackend {
+    fn list(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<Option<u64>> {
+        let args = self.provider.build_list_args(branch);
+        let parse_failure = self.parse_failure(&args);
+        let output = self.run(runner, dir, &self.on_server(dir, branch, args)?)?;
+        self.provider
+            .parse_list_output(branch, &output.stdout)
+            .wrap_err(parse_failure)
+    }
+
+    fn view(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<Option<MergeRequestStatus>> {
+        let args = self.on_server(dir, branch, self.provider.build_view_args(branch))?;
+        let program = self.provider.cli_program();
+        let output = runner
+            .run(program, dir, &args)
+            .map_err(|_| eyre::eyre!("`{program}` is not available"))?;
+
+        if !output.success {
+            let stderr = output.stderr.trim();
+            let lowered = stderr.to_lowercase();
+            if lowered.contains("no pull requests found")
+                || lowered.contains("no open merge request")
+            {
+                return Ok(None);
+            }
+            let reason = stderr
+                .lines()
+                .next()
+                .filter(|line| !line.is_empty())
+                .unwrap_or("provider lookup failed");
+            return Err(eyre::eyre!("{reason}"));
+        }
+
+        self.provider
+            .parse_view_output(branch, output.stdout.trim())
+            .map_err(|error| eyre::eyre!("failed to parse `{program}` output: {error}"))
+    }
+
+    fn head_branch(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        number: u64,
+    ) -> color_eyre::Result<Option<String>> {
+        let args = self.provider.build_head_branch_args(number);
+        let output = self.run(runner, dir, &args)?;
+        self.provider
+            .parse_head_branch(number, &output.stdout)
+            .wrap_err_with(|| self.parse_failure(&args))
+    }
+
+    fn create(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        request: &NewMergeRequest,
+    ) -> color_eyre::Result<Option<String>> {
+        let mut extra_args = request.extra_args.clone();
+        if let Some(title) = &request.title {
+            extra_args.extend(["--title".to_owned(), title.clone()]);
+        }
+        let args = self.provider.build_create_args(
+            &request.branch,
+            request.draft,
+            request.fill,
+            request.web,
+            &request.reviewers,
+            &extra_args,
+        );
+        let output = self.run(runner, dir, &args)?;
+        Ok(pull_request_url(&output.stdout).or_else(|| {
+            output
+                .stdout
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(str::to_owned)
+        }))
+    }
+
+    fn merge(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        number: u64,
+        delete_branch: bool,
+        strategy: MergeStrategy,
+    ) -> color_eyre::Result<MergeOutcome> {
+        let args = self
+            .provider
+            .build_merge_args(number, delete_branch, strategy);
+        if args.is_empty() {
+            return Err(self.unsupported("merge changes by their number"));
+        }
+        let program = self.provider.cli_program();
+        let output = runner.run(program, dir, &args).wrap_err_with(|| {
+            format!(
+                "failed to run `{program} {} merge`",
+                self.provider.request_subcommand()
+            )
+        })?;
+
+        if delete_branch && self.provider.is_branch_delete_failure(&output.stderr) {
+            return Ok(MergeOutcome::BranchKept);
+        }
+        if !output.success {
+            return Err(command_failure(program, &args, &output));
+        }
+        Ok(MergeOutcome::Merged)
+    }
+
+    fn comment(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        target: &str,
+        body: &str,
+    ) -> color_eyre::Result<Option<String>> {
+        let args = self
+            .provider
+            .build_comment_args(target, body)
+            .ok_or_else(|| {
+                self.unsupported(&format!(
+                    "comment on {}s",
+                    self.provider.merge_request_term()
+                ))
+            })?;
+        let output = self.run(runner, dir, &args)?;
+        let url = output.stdout.trim();
+        Ok(url.starts_with("http").then(|| url.to_owned()))
+    }
+
+    fn close(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        target: &str,
+        comment: Option<&str>,
+    ) -> color_eyre::Result<()> {
+        // Only `gh` closes with a comment; the others get it posted first.
+        if let Some(comment) = comment
+            && self.provider != GitProvider::GitHub
+        {
+            self.comment(runner, dir, target, comment)?;
+        }
+        let args = self.provider.build_close_args(target, comment);
+        if args.is_empty() {
+            return Err(self.unsupported(&format!("close {}s", self.provider.merge_request_term())));
+        }
+        self.run(runner, dir, &args)?;
+        Ok(())
+    }
+
+    fn review(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        target: &str,
+        verdict: ReviewVerdict,
+        body: Option<&str>,
+    ) -> color_eyre::Result<bool> {
+        let Some(args) = self.provider.build_review_args(target, verdict, body) else {
+            return Ok(false);
+        };
+        self.run(runner, dir, &args)?;
+        Ok(true)
+    }
+
+    fn checkout(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        number: u64,
+    ) -> color_eyre::Result<()> {
+        let args = self.provider.build_checkout_args(number);
+        if args.is_empty() {
+            return Err(self.unsupported(&format!(
+                "check {}s out",
+                self.provider.merge_request_term()
+            )));
+        }
+        self.run(runner, dir, &args)?;
+        Ok(())
+    }
+
+    fn trigger_ci(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+        workflow: &str,
+    ) -> color_eyre::Result<()> {
+        let args = self
+            .provider
+            .build_ci_trigger_args(branch, workflow)
+            .ok_or_else(|| self.unsupported("run or list CI pipelines"))?;
+        self.run(runner, dir, &args)?;
+        Ok(())
+    }
+
+    fn ci_status(
+        &mut self,
+        runner: &mut impl CommandRunner,
+        dir: &Path,
+        branch: &str,
+    ) -> color_eyre::Result<Option<PipelineStatus>> {
+        let args = self
+            .provider
+            .build_ci_status_args(branch)
+            .ok_or_else(|| self.unsupported("run or list CI pipelines"))?;
+        let output = self.run(runner, dir, &args)?;
+        self.provider
+            .parse_ci_status_output(&output.stdout)
+            .wrap_err_with(|| self.parse_failure(&args))
+    }
+}
+
+/// The CLI command `args` run, such as `pr list`.
+fn command_name(args: &[String]) -> String {
+    args.iter().take(2).cloned().collect::<Vec<_>>().join(" ")
+}
+
+/// The first URL printed by the provider CLI; `glab`, for one, prints
+/// progress lines before it.
+fn pull_request_url(stdout: &str) -> Option<String> {
+    stdout
+        .split_whitespace()
+        .find(|word| word.starts_with("https://") || word.starts_with("http://"))
+        .map(|url| url.trim_end_matches([',', '.']).to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::process::ScriptedRunner;
+
+    #[test]
+    fn pull_request_url_skips_progress_output() {
+        let glab = "\nCreating merge request for feature into main in acme/app\n\n!7 Add login (feature)\n https://gitlab.com/acme/app/-/merge_requests/7\n";
+        assert_eq!(
+            pull_request_url(glab).as_deref(),
+            Some("https://gitlab.com/acme/app/-/merge_requests/7")
+        );
+        assert_eq!(
+            pull_request_url("Opening github.com/acme/app/pull/new in your browser."),
+            None
+        );
+    }
+
+    #[test]
+    fn merge_keeps_the_branch_the_cli_could_not_delete() -> color_eyre::Result<()> {
+        let mut runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::failure(
+                1,
+                "failed to delete local branch feature",
+            ))
+            .push(CommandOutput::failure(1, "merge conflict"));
+        let mut cli = CliBackend::new(GitProvider::GitHub);
+        let dir = Path::new("/repo");
+
+        assert_eq!(
+            cli.merge(&mut runner, dir, 7, true, MergeStrategy::Squash)?,
+            MergeOutcome::BranchKept
+        );
+        let error = cli
+            .merge(&mut runner, dir, 7, true, MergeStrategy::Squash)
+            .expect_err("the merge failed");
+        assert!(error.to_string().contains("merge conflict"), "{error}");
+        assert_eq!(
+            runner.calls()[0].args,
+            ["pr", "merge", "7", "--squash", "--delete-branch"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn close_posts_the_comment_first_where_the_cli_cannot_attach_it() -> color_eyre::Result<()> {
+        let mut runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::success(""))
+            .push(CommandOutput::success(""));
+        let mut cli = CliBackend::new(GitProvider::GitLab);
+
+        cli.close(
+            &mut runner,
+            Path::new("/repo"),
+            "feature",
+            Some("Superseded"),
+        )?;
+        let calls = runner.calls();
+        assert_eq!(
+            calls[0].args,
+            ["mr", "note", "feature", "--message", "Superseded"]
+        );
+        assert_eq!(calls[1].args, ["mr", "close", "feature"]);
+        Ok(())
+    }
+}
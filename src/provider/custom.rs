@@ -0,0 +1,195 @@
+//! Providers registered by crates embedding rsworktree.
+//!
+//! The built-in providers are the variants of [`GitProvider`], each
+//! implementing [`Provider`] for its CLI. Another crate can add its own, such
+//! as one for Review Board through `rbt`, by implementing [`Provider`] and
+//! calling [`register`] before [`crate::cli::run`].
+//! A registered provider is selected by its [`Provider::name`] like a built-in
+//! one: with `--provider`, in `preferences.json`, in `config.toml` or in
+//! `RSWORKTREE_PROVIDER`. Built-in names and their aliases take precedence.
+
+use std::{
+    fmt,
+    sync::{PoisonError, RwLock},
+};
+
+use super::{GitProvider, MergeRequestStatus, MergeStrategy, PipelineStatus, ReviewVerdict};
+
+/// The providers added with [`register`], most recent last.
+static REGISTRY: RwLock<Vec<&'static dyn Provider>> = RwLock::new(Vec::new());
+
+/// Builds the command lines of a provider CLI and parses what it prints.
+///
+/// The methods mirror those of [`GitProvider`], whose documentation describes
+/// what each command has to do. Capabilities a CLI lacks, such as comments,
+/// reviews or CI runs, default to unsupported.
+pub trait Provider: fmt::Debug + Send + Sync {
+    /// The lowercase name selecting the provider, such as `reviewboard`.
+    fn name(&self) -> &'static str;
+
+    /// A human-readable name, such as `Review Board`.
+    fn display_name(&self) -> &'static str;
+
+    /// The CLI program the arguments are passed to.
+    fn cli_program(&self) -> &'static str;
+
+    fn merge_request_term(&self) -> &'static str {
+        "pull request"
+    }
+
+    fn merge_request_short(&self) -> &'static str {
+        "PR"
+    }
+
+    /// The subcommand grouping request operations, shown in messages.
+    fn request_subcommand(&self) -> &'static str;
+
+    fn request_prefix(&self) -> &'static str {
+        "#"
+    }
+
+    fn addresses_requests_by_branch(&self) -> bool {
+        true
+    }
+
+    fn supports_title(&self) -> bool {
+        true
+    }
+
+    fn supports_comments(&self) -> bool {
+        false
+    }
+
+    fn supports_merge_strategy(&self, strategy: MergeStrategy) -> bool {
+        strategy == MergeStrategy::Merge
+    }
+
+    fn review_accepts_message(&self) -> bool {
+        false
+    }
+
+    fn build_create_args(
+        &self,
+        branch: &str,
+        draft: bool,
+        fill: bool,
+        web: bool,
+        reviewers: &[String],
+        extra_args: &[String],
+    ) -> Vec<String>;
+
+    fn build_list_args(&self, branch: &str) -> Vec<String>;
+
+    fn parse_list_output(
+        &self,
+        branch: &str,
+        stdout: &str,
+    ) -> Result<Option<u64>, serde_json::Error>;
+
+    fn build_view_args(&self, branch: &str) -> Vec<String>;
+
+    fn parse_view_output(
+        &self,
+        branch: &str,
+        stdout: &str,
+    ) -> Result<Option<MergeRequestStatus>, serde_json::Error>;
+
+    fn build_head_branch_args(&self, number: u64) -> Vec<String>;
+
+    fn parse_head_branch(
+        &self,
+        number: u64,
+        stdout: &str,
+    ) -> Result<Option<String>, serde_json::Error>;
+
+    fn build_checkout_args(&self, number: u64) -> Vec<String>;
+
+    fn build_merge_args(
+        &self,
+        mr_number: u64,
+        delete_branch: bool,
+        strategy: MergeStrategy,
+    ) -> Vec<String>;
+
+    fn build_comment_args(&self, _branch: &str, _body: &str) -> Option<Vec<String>> {
+        None
+    }
+
+    fn build_close_args(&self, branch: &str, comment: Option<&str>) -> Vec<String>;
+
+    fn build_review_args(
+        &self,
+        _branch: &str,
+        _verdict: ReviewVerdict,
+        _body: Option<&str>,
+    ) -> Option<Vec<String>> {
+        None
+    }
+
+    fn build_ci_trigger_args(&self, _branch: &str, _workflow: &str) -> Option<Vec<String>> {
+        None
+    }
+
+    fn build_ci_status_args(&self, _branch: &str) -> Option<Vec<String>> {
+        None
+    }
+
+    fn parse_ci_status_output(
+        &self,
+        _stdout: &str,
+    ) -> Result<Option<PipelineStatus>, serde_json::Error> {
+        Ok(None)
+    }
+
+    fn is_branch_delete_failure(&self, _stderr: &str) -> bool {
+        false
+    }
+}
+
+/// A provider added with [`register`], held by [`GitProvider::Custom`].
+#[derive(Debug, Clone, Copy)]
+pub struct CustomProvider(&'static dyn Provider);
+
+impl CustomProvider {
+    pub fn provider(&self) -> &'static dyn Provider {
+        self.0
+    }
+}
+
+impl PartialEq for CustomProvider {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name() == other.0.name()
+    }
+}
+
+impl Eq for CustomProvider {}
+
+/// Makes `provider` selectable by its name and returns it as a
+/// [`GitProvider`]. Registering another provider of the same name replaces it.
+pub fn register(provider: impl Provider + 'static) -> GitProvider {
+    let provider: &'static dyn Provider = Box::leak(Box::new(provider));
+    let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+    registry.retain(|registered| !registered.name().eq_ignore_ascii_case(provider.name()));
+    registry.push(provider);
+    GitProvider::Custom(CustomProvider(provider))
+}
+
+/// The registered provider called `name`, ignoring case.
+pub(super) fn lookup(name: &str) -> Option<GitProvider> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .find(|provider| provider.name().eq_ignore_ascii_case(name))
+        .map(|provider| GitProvider::Custom(CustomProvider(*provider)))
+}
+
+/// The names of the registered providers.
+pub(super) fn names() -> Vec<&'static str> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .map(|provider| provider.name())
+        .collect()
+}
@@ -1,50 +1,138 @@
+#[cfg(feature = "provider-api")]
+mod api;
+mod backend;
+mod builtin;
+mod cli;
+mod custom;
+pub(crate) mod gerrit;
+
 use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
+use self::builtin::BuiltIn;
+
+pub use self::{
+    backend::{PROVIDER_BACKEND_ENV, ProviderBackend, ProviderClient, RequestBackend},
+    cli::CliBackend,
+    custom::{CustomProvider, Provider, register},
+};
+
 /// Git hosting provider for merge/pull request operations.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[serde(try_from = "String", into = "String")]
 pub enum GitProvider {
     #[default]
     GitHub,
     GitLab,
+    Bitbucket,
+    /// Gitea and Forgejo, through the `tea` CLI.
+    Gitea,
+    /// Azure Repos, through the `az repos` commands of the Azure CLI.
+    AzureDevOps,
+    /// Gerrit, through `git push` to `refs/for/<base>` and the `gerrit`
+    /// commands of its SSH daemon. The arguments built for it run on the
+    /// daemon and need the host the remote points to in front of them.
+    Gerrit,
+    /// A provider added with [`register`].
+    Custom(CustomProvider),
 }
 
 impl GitProvider {
-    /// Returns the CLI program name for this provider.
-    pub fn cli_program(&self) -> &'static str {
+    /// The implementation of this provider, which the methods below call.
+    pub fn provider(&self) -> &'static dyn Provider {
         match self {
-            GitProvider::GitHub => "gh",
-            GitProvider::GitLab => "glab",
+            GitProvider::GitHub => &BuiltIn::GitHub,
+            GitProvider::GitLab => &BuiltIn::GitLab,
+            GitProvider::Bitbucket => &BuiltIn::Bitbucket,
+            GitProvider::Gitea => &BuiltIn::Gitea,
+            GitProvider::AzureDevOps => &BuiltIn::AzureDevOps,
+            GitProvider::Gerrit => &BuiltIn::Gerrit,
+            GitProvider::Custom(custom) => custom.provider(),
         }
     }
 
+    /// Returns the name selecting this provider in `--provider` and the configuration.
+    pub fn name(&self) -> &'static str {
+        self.provider().name()
+    }
+
+    /// Returns the CLI program name for this provider.
+    pub fn cli_program(&self) -> &'static str {
+        self.provider().cli_program()
+    }
+
     /// Returns a human-readable display name for the provider.
     pub fn display_name(&self) -> &'static str {
-        match self {
-            GitProvider::GitHub => "GitHub",
-            GitProvider::GitLab => "GitLab",
-        }
+        self.provider().display_name()
     }
 
     /// Returns the term for merge/pull requests on this provider.
     pub fn merge_request_term(&self) -> &'static str {
-        match self {
-            GitProvider::GitHub => "pull request",
-            GitProvider::GitLab => "merge request",
-        }
+        self.provider().merge_request_term()
     }
 
     /// Returns the short term for merge/pull requests (PR or MR).
     pub fn merge_request_short(&self) -> &'static str {
-        match self {
-            GitProvider::GitHub => "PR",
-            GitProvider::GitLab => "MR",
-        }
+        self.provider().merge_request_short()
+    }
+
+    /// Returns the CLI subcommand that groups pull/merge request operations.
+    ///
+    /// `az` nests it below `repos`, so the subcommand is two words there.
+    pub fn request_subcommand(&self) -> &'static str {
+        self.provider().request_subcommand()
+    }
+
+    /// Returns the prefix used when referring to a request by number (`#12`,
+    /// `!12`); Gerrit changes go by their bare number.
+    pub fn request_prefix(&self) -> &'static str {
+        self.provider().request_prefix()
+    }
+
+    /// Whether the CLI can address a pull/merge request by its source branch.
+    ///
+    /// `tea`, `az` and Gerrit only accept request numbers; look the number up
+    /// with [`Self::build_list_args`] and [`Self::parse_list_output`] and pass
+    /// it in place of the branch.
+    pub fn addresses_requests_by_branch(&self) -> bool {
+        self.provider().addresses_requests_by_branch()
+    }
+
+    /// Whether the create command of the CLI accepts `--title`.
+    ///
+    /// `bb`, `tea` and Gerrit always take the title from the commits.
+    pub fn supports_title(&self) -> bool {
+        self.provider().supports_title()
+    }
+
+    /// Whether the CLI can comment on a pull/merge request.
+    ///
+    /// `az repos pr` has no comment command, and Gerrit reviews address a
+    /// patch set rather than a change.
+    pub fn supports_comments(&self) -> bool {
+        self.provider().supports_comments()
+    }
+
+    /// Whether [`Self::build_merge_args`] can merge with `strategy`.
+    ///
+    /// `az` squashes but cannot rebase; `bb` and Gerrit always merge the way
+    /// the server is configured to, as do custom providers by default.
+    pub fn supports_merge_strategy(&self, strategy: MergeStrategy) -> bool {
+        self.provider().supports_merge_strategy(strategy)
+    }
+
+    /// Whether [`Self::build_review_args`] carries the review message itself.
+    pub fn review_accepts_message(&self) -> bool {
+        self.provider().review_accepts_message()
     }
 
     /// Build arguments for creating a pull/merge request.
+    ///
+    /// `tea` has no draft, fill, browser or reviewer options, so those are
+    /// ignored on Gitea; it takes the title from the head commit. `az` always
+    /// fills the title and description from the commits. Empty on Gerrit,
+    /// where pushing to `refs/for/<base>` creates the change.
     pub fn build_create_args(
         &self,
         branch: &str,
@@ -54,130 +142,517 @@ impl GitProvider {
         reviewers: &[String],
         extra_args: &[String],
     ) -> Vec<String> {
-        let mut args = match self {
-            GitProvider::GitHub => vec!["pr".to_owned(), "create".to_owned()],
-            GitProvider::GitLab => vec!["mr".to_owned(), "create".to_owned()],
-        };
-
-        // Branch specification differs between providers
-        match self {
-            GitProvider::GitHub => {
-                args.push("--head".to_owned());
-                args.push(branch.to_owned());
-            }
-            GitProvider::GitLab => {
-                args.push("--source-branch".to_owned());
-                args.push(branch.to_owned());
-            }
-        }
+        self.provider()
+            .build_create_args(branch, draft, fill, web, reviewers, extra_args)
+    }
 
-        if draft {
-            args.push("--draft".to_owned());
-        }
+    /// Build arguments for listing open pull/merge requests.
+    ///
+    /// `tea` cannot filter by branch; [`Self::parse_list_output`] picks the
+    /// request of the branch from the listing.
+    pub fn build_list_args(&self, branch: &str) -> Vec<String> {
+        self.provider().build_list_args(branch)
+    }
 
-        if fill {
-            match self {
-                GitProvider::GitHub => args.push("--fill".to_owned()),
-                GitProvider::GitLab => args.push("--fill".to_owned()),
-            }
-        }
+    /// Parse the JSON printed by the list command built with
+    /// [`Self::build_list_args`] into the number of the branch's request.
+    pub fn parse_list_output(
+        &self,
+        branch: &str,
+        stdout: &str,
+    ) -> Result<Option<u64>, serde_json::Error> {
+        self.provider().parse_list_output(branch, stdout)
+    }
 
-        if web {
-            match self {
-                GitProvider::GitHub => args.push("--web".to_owned()),
-                GitProvider::GitLab => args.push("--web".to_owned()),
-            }
-        }
+    /// Build arguments for viewing the pull/merge request of a branch as JSON.
+    ///
+    /// On Gitea and Azure DevOps this lists the open pull requests, see
+    /// [`Self::build_list_args`].
+    pub fn build_view_args(&self, branch: &str) -> Vec<String> {
+        self.provider().build_view_args(branch)
+    }
 
-        for reviewer in reviewers {
-            match self {
-                GitProvider::GitHub => {
-                    args.push("--reviewer".to_owned());
-                    args.push(reviewer.clone());
-                }
-                GitProvider::GitLab => {
-                    args.push("--reviewer".to_owned());
-                    args.push(reviewer.clone());
-                }
-            }
-        }
+    /// Parse the JSON printed by the view command built with [`Self::build_view_args`].
+    ///
+    /// Returns `None` when `branch` has no open request, which only Gitea,
+    /// Azure DevOps and Gerrit report through the output rather than a
+    /// failing command.
+    pub fn parse_view_output(
+        &self,
+        branch: &str,
+        stdout: &str,
+    ) -> Result<Option<MergeRequestStatus>, serde_json::Error> {
+        self.provider().parse_view_output(branch, stdout)
+    }
 
-        args.extend(extra_args.iter().cloned());
+    /// Build arguments for viewing pull/merge request `number` as JSON, to
+    /// learn its source branch with [`Self::parse_head_branch`].
+    ///
+    /// On Gitea this lists the open pull requests, see [`Self::build_list_args`].
+    pub fn build_head_branch_args(&self, number: u64) -> Vec<String> {
+        self.provider().build_head_branch_args(number)
+    }
 
-        args
+    /// Parse the source branch of request `number` from the JSON printed by
+    /// the command built with [`Self::build_head_branch_args`]; on Gerrit,
+    /// the topic of the change.
+    pub fn parse_head_branch(
+        &self,
+        number: u64,
+        stdout: &str,
+    ) -> Result<Option<String>, serde_json::Error> {
+        self.provider().parse_head_branch(number, stdout)
     }
 
-    /// Build arguments for listing open pull/merge requests.
-    pub fn build_list_args(&self, branch: &str) -> Vec<String> {
-        match self {
-            GitProvider::GitHub => vec![
-                "pr".to_owned(),
-                "list".to_owned(),
-                "--head".to_owned(),
-                branch.to_owned(),
-                "--state".to_owned(),
-                "open".to_owned(),
-                "--json".to_owned(),
-                "number".to_owned(),
-                "--limit".to_owned(),
-                "1".to_owned(),
-            ],
-            GitProvider::GitLab => vec![
-                "mr".to_owned(),
-                "list".to_owned(),
-                "--source-branch".to_owned(),
-                branch.to_owned(),
-                "--state".to_owned(),
-                "opened".to_owned(),
-                "--output".to_owned(),
-                "json".to_owned(),
-            ],
-        }
+    /// Build arguments for checking out pull/merge request `number` in the
+    /// current directory, fetching it from a fork where needed. Empty on
+    /// Gerrit, whose SSH daemon cannot check changes out.
+    pub fn build_checkout_args(&self, number: u64) -> Vec<String> {
+        self.provider().build_checkout_args(number)
     }
 
     /// Build arguments for merging a pull/merge request.
-    pub fn build_merge_args(&self, mr_number: u64, delete_branch: bool) -> Vec<String> {
-        match self {
-            GitProvider::GitHub => {
-                let mut args = vec![
-                    "pr".to_owned(),
-                    "merge".to_owned(),
-                    mr_number.to_string(),
-                    "--merge".to_owned(),
-                ];
-                if delete_branch {
-                    args.push("--delete-branch".to_owned());
-                }
-                args
-            }
-            GitProvider::GitLab => {
-                let mut args = vec![
-                    "mr".to_owned(),
-                    "merge".to_owned(),
-                    mr_number.to_string(),
-                ];
-                if delete_branch {
-                    args.push("--remove-source-branch".to_owned());
-                }
-                args
-            }
-        }
+    ///
+    /// `tea` cannot delete the head branch; Gitea does so when the repository
+    /// enables "Delete pull request branch after merge by default". `az`
+    /// enables auto-complete, so Azure DevOps completes the pull request once
+    /// its branch policies pass. Empty on Gerrit, which submits a patch set
+    /// by its revision rather than a change by its number.
+    ///
+    /// `strategy` is left out where [`Self::supports_merge_strategy`] says
+    /// the CLI cannot choose it.
+    pub fn build_merge_args(
+        &self,
+        mr_number: u64,
+        delete_branch: bool,
+        strategy: MergeStrategy,
+    ) -> Vec<String> {
+        self.provider()
+            .build_merge_args(mr_number, delete_branch, strategy)
+    }
+
+    /// Build arguments for commenting on the pull/merge request of `branch`.
+    ///
+    /// Pass the request number as `branch` on Gitea, see
+    /// [`Self::addresses_requests_by_branch`]. Returns `None` on Azure DevOps
+    /// and Gerrit, see [`Self::supports_comments`].
+    pub fn build_comment_args(&self, branch: &str, body: &str) -> Option<Vec<String>> {
+        self.provider().build_comment_args(branch, body)
+    }
+
+    /// Build arguments for closing the pull/merge request of `branch` without merging it.
+    ///
+    /// Only `gh` can attach a closing comment; post it with
+    /// [`Self::build_comment_args`] first on the other providers. Bitbucket
+    /// calls closing a pull request declining it, Azure DevOps abandoning it.
+    /// Empty on Gerrit, which abandons a patch set rather than a change.
+    pub fn build_close_args(&self, branch: &str, comment: Option<&str>) -> Vec<String> {
+        self.provider().build_close_args(branch, comment)
+    }
+
+    /// Build arguments for submitting a review verdict on the pull/merge request of `branch`.
+    ///
+    /// `glab` cannot request changes, so `None` is returned for that verdict on
+    /// GitLab. GitLab and Bitbucket verdicts carry no message either; post
+    /// `body` as a comment with [`Self::build_comment_args`] instead, see
+    /// [`Self::review_accepts_message`]. Azure DevOps votes "wait for author"
+    /// when changes are requested. Returns `None` on Gerrit, which reviews a
+    /// patch set rather than a change.
+    pub fn build_review_args(
+        &self,
+        branch: &str,
+        verdict: ReviewVerdict,
+        body: Option<&str>,
+    ) -> Option<Vec<String>> {
+        self.provider().build_review_args(branch, verdict, body)
+    }
+
+    /// Build arguments for starting a CI run on `branch`.
+    ///
+    /// GitHub Actions needs the workflow to dispatch; GitLab and Bitbucket
+    /// Pipelines run the branch pipeline and ignore `workflow`. Returns `None`
+    /// on Gitea, as `tea` cannot run Gitea Actions, on Azure DevOps, whose
+    /// pipelines live outside `az repos`, and on Gerrit.
+    pub fn build_ci_trigger_args(&self, branch: &str, workflow: &str) -> Option<Vec<String>> {
+        self.provider().build_ci_trigger_args(branch, workflow)
+    }
+
+    /// Build arguments for fetching the latest CI run of `branch` as JSON.
+    ///
+    /// Returns `None` on Gitea, as `tea` cannot list Gitea Actions runs, on
+    /// Azure DevOps and on Gerrit.
+    pub fn build_ci_status_args(&self, branch: &str) -> Option<Vec<String>> {
+        self.provider().build_ci_status_args(branch)
+    }
+
+    /// Parse the JSON printed by the command built with [`Self::build_ci_status_args`].
+    ///
+    /// Returns `None` when the branch has no CI runs yet.
+    pub fn parse_ci_status_output(
+        &self,
+        stdout: &str,
+    ) -> Result<Option<PipelineStatus>, serde_json::Error> {
+        self.provider().parse_ci_status_output(stdout)
     }
 
     /// Check if the command output indicates a branch delete failure.
     pub fn is_branch_delete_failure(&self, stderr: &str) -> bool {
-        let stderr_lower = stderr.to_lowercase();
+        self.provider().is_branch_delete_failure(stderr)
+    }
+}
+
+/// Summary of a pull/merge request as reported by the provider CLI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeRequestStatus {
+    pub number: u64,
+    pub url: Option<String>,
+    /// Lowercased provider state, e.g. `open`, `opened`, `merged` or `closed`.
+    pub state: String,
+}
+
+/// A pull/merge request to create from a branch.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NewMergeRequest {
+    pub branch: String,
+    /// The title; taken from the commits when not set.
+    pub title: Option<String>,
+    pub draft: bool,
+    /// Fill the title and description from the commits.
+    pub fill: bool,
+    /// Continue in the browser.
+    pub web: bool,
+    pub reviewers: Vec<String>,
+    /// Further arguments for the provider CLI.
+    pub extra_args: Vec<String>,
+}
+
+/// How merging a pull/merge request went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeOutcome {
+    Merged,
+    /// Merged, but its branch could not be deleted.
+    BranchKept,
+}
+
+/// Upper bound of open pull requests `tea` lists when looking for a branch.
+const GITEA_LIST_LIMIT: u32 = 100;
+
+/// GitHub reports `number`/`url`, GitLab reports `iid`/`web_url` and
+/// Bitbucket reports `id`/`links.html.href`.
+#[derive(Debug, Deserialize)]
+struct ViewPayload {
+    #[serde(default)]
+    number: Option<u64>,
+    #[serde(default)]
+    iid: Option<u64>,
+    #[serde(default)]
+    id: Option<u64>,
+    #[serde(default)]
+    links: Option<BitbucketLinks>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    web_url: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+}
+
+impl ViewPayload {
+    fn number(&self) -> u64 {
+        self.number.or(self.iid).or(self.id).unwrap_or(0)
+    }
+}
+
+/// GitHub reports `headRefName`, GitLab `source_branch`, Bitbucket
+/// `source.branch.name` and Azure DevOps the full `sourceRefName`.
+#[derive(Debug, Deserialize)]
+struct HeadBranchPayload {
+    #[serde(default, rename = "headRefName")]
+    head_ref_name: Option<String>,
+    #[serde(default)]
+    source_branch: Option<String>,
+    #[serde(default)]
+    source: Option<BitbucketSource>,
+    #[serde(default, rename = "sourceRefName")]
+    source_ref_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketSource {
+    branch: BitbucketBranch,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketBranch {
+    name: String,
+}
+
+/// A row of `tea pr list --output json`; `tea` prints every field as a string.
+#[derive(Debug, Deserialize)]
+struct GiteaPullPayload {
+    index: String,
+    head: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    url: String,
+}
+
+/// Picks the pull request of `branch` from `tea pr list` output.
+fn parse_gitea_pulls(
+    branch: &str,
+    stdout: &str,
+) -> Result<Option<GiteaPullPayload>, serde_json::Error> {
+    let stdout = stdout.trim();
+    if stdout.is_empty() {
+        return Ok(None);
+    }
+    let pulls: Vec<GiteaPullPayload> = serde_json::from_str(stdout)?;
+    Ok(pulls.into_iter().find(|pull| pull.head == branch))
+}
+
+/// Arguments of `az repos pr update` for the pull request `id`.
+fn azure_update_args(id: &str) -> Vec<String> {
+    vec![
+        "repos".to_owned(),
+        "pr".to_owned(),
+        "update".to_owned(),
+        "--id".to_owned(),
+        id.to_owned(),
+    ]
+}
+
+/// A row of `az repos pr list --output json`, which names the source branch
+/// by its full ref and only reports the REST API url of the pull request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AzurePullPayload {
+    pull_request_id: u64,
+    source_ref_name: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    repository: Option<AzureRepositoryPayload>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AzureRepositoryPayload {
+    #[serde(default)]
+    web_url: Option<String>,
+}
+
+impl AzurePullPayload {
+    /// The pull request page in the browser, built from the repository's web url.
+    fn web_url(&self) -> Option<String> {
+        self.repository
+            .as_ref()
+            .and_then(|repository| repository.web_url.as_deref())
+            .map(|web_url| {
+                format!(
+                    "{}/pullrequest/{}",
+                    web_url.trim_end_matches('/'),
+                    self.pull_request_id
+                )
+            })
+    }
+}
+
+/// Picks the pull request of `branch` from `az repos pr list` output.
+fn parse_azure_pulls(
+    branch: &str,
+    stdout: &str,
+) -> Result<Option<AzurePullPayload>, serde_json::Error> {
+    let stdout = stdout.trim();
+    if stdout.is_empty() {
+        return Ok(None);
+    }
+    let pulls: Vec<AzurePullPayload> = serde_json::from_str(stdout)?;
+    Ok(pulls.into_iter().find(|pull| {
+        pull.source_ref_name.strip_prefix("refs/heads/") == Some(branch)
+            || pull.source_ref_name == branch
+    }))
+}
+
+/// Verdict submitted by `pr review`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewVerdict {
+    Approve,
+    RequestChanges,
+}
+
+/// How `merge` lands a pull/merge request on its base branch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    /// A merge commit.
+    #[default]
+    Merge,
+    /// The commits squashed into one.
+    Squash,
+    /// The commits rebased onto the base branch.
+    Rebase,
+}
+
+impl fmt::Display for MergeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MergeStrategy::Merge => "merge",
+            MergeStrategy::Squash => "squash",
+            MergeStrategy::Rebase => "rebase",
+        })
+    }
+}
+
+impl std::str::FromStr for MergeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "merge" => Ok(MergeStrategy::Merge),
+            "squash" => Ok(MergeStrategy::Squash),
+            "rebase" => Ok(MergeStrategy::Rebase),
+            _ => Err(format!(
+                "unknown merge strategy '{s}', expected one of: merge, squash, rebase"
+            )),
+        }
+    }
+}
+
+/// The latest CI run (GitHub workflow run or GitLab pipeline) of a branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineStatus {
+    pub id: u64,
+    /// Workflow or pipeline name, when the provider reports one.
+    pub name: Option<String>,
+    pub state: PipelineState,
+    pub url: Option<String>,
+}
+
+/// Provider-neutral state of a CI run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineState {
+    Pending,
+    Running,
+    Success,
+    Failed,
+    Canceled,
+    Skipped,
+}
+
+impl PipelineState {
+    pub fn as_str(&self) -> &'static str {
         match self {
-            GitProvider::GitHub => {
-                stderr_lower.contains("failed to delete local branch")
-                    || stderr_lower.contains("cannot delete branch")
-            }
-            GitProvider::GitLab => {
-                stderr_lower.contains("failed to delete")
-                    || stderr_lower.contains("could not remove")
-            }
+            PipelineState::Pending => "pending",
+            PipelineState::Running => "running",
+            PipelineState::Success => "success",
+            PipelineState::Failed => "failed",
+            PipelineState::Canceled => "canceled",
+            PipelineState::Skipped => "skipped",
         }
     }
+
+    /// Whether the run has completed and its state will not change anymore.
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, PipelineState::Pending | PipelineState::Running)
+    }
+
+    fn from_github(status: &str, conclusion: Option<&str>) -> Self {
+        match status {
+            "completed" => match conclusion.unwrap_or_default() {
+                "success" => PipelineState::Success,
+                "cancelled" => PipelineState::Canceled,
+                "skipped" | "neutral" | "stale" => PipelineState::Skipped,
+                _ => PipelineState::Failed,
+            },
+            "in_progress" => PipelineState::Running,
+            _ => PipelineState::Pending,
+        }
+    }
+
+    fn from_bitbucket(state: &str, result: Option<&str>) -> Self {
+        match state {
+            "COMPLETED" => match result.unwrap_or_default() {
+                "SUCCESSFUL" => PipelineState::Success,
+                "STOPPED" => PipelineState::Canceled,
+                "SKIPPED" => PipelineState::Skipped,
+                _ => PipelineState::Failed,
+            },
+            "IN_PROGRESS" | "RUNNING" => PipelineState::Running,
+            _ => PipelineState::Pending,
+        }
+    }
+
+    fn from_gitlab(status: &str) -> Self {
+        match status {
+            "running" => PipelineState::Running,
+            "success" => PipelineState::Success,
+            "failed" => PipelineState::Failed,
+            "canceled" | "canceling" => PipelineState::Canceled,
+            "skipped" => PipelineState::Skipped,
+            _ => PipelineState::Pending,
+        }
+    }
+}
+
+impl fmt::Display for PipelineState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitHubRunPayload {
+    database_id: u64,
+    #[serde(default)]
+    workflow_name: Option<String>,
+    status: String,
+    #[serde(default)]
+    conclusion: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabPipelinePayload {
+    id: u64,
+    #[serde(default)]
+    name: Option<String>,
+    status: String,
+    #[serde(default)]
+    web_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketPipelinePayload {
+    build_number: u64,
+    state: BitbucketPipelineState,
+    #[serde(default)]
+    links: Option<BitbucketLinks>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketPipelineState {
+    name: String,
+    #[serde(default)]
+    result: Option<BitbucketPipelineResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketPipelineResult {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketLinks {
+    #[serde(default)]
+    html: Option<BitbucketLink>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketLink {
+    href: String,
 }
 
 impl fmt::Display for GitProvider {
@@ -193,14 +668,49 @@ impl std::str::FromStr for GitProvider {
         match s.to_lowercase().as_str() {
             "github" | "gh" => Ok(GitProvider::GitHub),
             "gitlab" | "glab" => Ok(GitProvider::GitLab),
-            _ => Err(format!(
-                "unknown provider '{}', expected 'github' or 'gitlab'",
-                s
-            )),
+            "bitbucket" | "bb" => Ok(GitProvider::Bitbucket),
+            "gitea" | "forgejo" | "tea" => Ok(GitProvider::Gitea),
+            "azuredevops" | "azure-devops" | "azure" | "ado" | "az" => Ok(GitProvider::AzureDevOps),
+            "gerrit" => Ok(GitProvider::Gerrit),
+            _ => custom::lookup(s).ok_or_else(|| {
+                let mut expected = [
+                    "github",
+                    "gitlab",
+                    "bitbucket",
+                    "gitea",
+                    "azuredevops",
+                    "gerrit",
+                ]
+                .into_iter()
+                .chain(custom::names())
+                .map(|name| format!("'{name}'"))
+                .collect::<Vec<_>>();
+                let last = expected.pop().unwrap_or_default();
+                format!(
+                    "unknown provider '{}', expected {} or {}",
+                    s,
+                    expected.join(", "),
+                    last
+                )
+            }),
         }
     }
 }
 
+impl TryFrom<String> for GitProvider {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<GitProvider> for String {
+    fn from(provider: GitProvider) -> Self {
+        provider.name().to_owned()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,26 +833,316 @@ mod tests {
 
     #[test]
     fn build_merge_args_github() {
-        let args = GitProvider::GitHub.build_merge_args(42, true);
+        let args = GitProvider::GitHub.build_merge_args(42, true, MergeStrategy::Merge);
         assert_eq!(
             args,
             vec!["pr", "merge", "42", "--merge", "--delete-branch"]
         );
 
-        let args_no_delete = GitProvider::GitHub.build_merge_args(42, false);
+        let args_no_delete = GitProvider::GitHub.build_merge_args(42, false, MergeStrategy::Merge);
         assert_eq!(args_no_delete, vec!["pr", "merge", "42", "--merge"]);
+
+        assert_eq!(
+            GitProvider::GitHub.build_merge_args(42, false, MergeStrategy::Squash),
+            vec!["pr", "merge", "42", "--squash"]
+        );
+        assert_eq!(
+            GitProvider::GitHub.build_merge_args(42, false, MergeStrategy::Rebase),
+            vec!["pr", "merge", "42", "--rebase"]
+        );
     }
 
     #[test]
     fn build_merge_args_gitlab() {
-        let args = GitProvider::GitLab.build_merge_args(42, true);
+        let args = GitProvider::GitLab.build_merge_args(42, true, MergeStrategy::Merge);
         assert_eq!(
             args,
             vec!["mr", "merge", "42", "--remove-source-branch"]
         );
 
-        let args_no_delete = GitProvider::GitLab.build_merge_args(42, false);
+        let args_no_delete = GitProvider::GitLab.build_merge_args(42, false, MergeStrategy::Merge);
         assert_eq!(args_no_delete, vec!["mr", "merge", "42"]);
+
+        assert_eq!(
+            GitProvider::GitLab.build_merge_args(42, true, MergeStrategy::Squash),
+            vec!["mr", "merge", "42", "--squash", "--remove-source-branch"]
+        );
+    }
+
+    #[test]
+    fn build_view_args_per_provider() {
+        assert_eq!(
+            GitProvider::GitHub.build_view_args("feature/test"),
+            vec!["pr", "view", "feature/test", "--json", "number,url,state"]
+        );
+        assert_eq!(
+            GitProvider::GitLab.build_view_args("feature/test"),
+            vec!["mr", "view", "feature/test", "--output", "json"]
+        );
+    }
+
+    #[test]
+    fn parse_view_output_handles_github_and_gitlab_payloads() {
+        let github = GitProvider::GitHub
+            .parse_view_output(
+                "feature",
+                r#"{"number":12,"url":"https://github.com/o/r/pull/12","state":"OPEN"}"#,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            github,
+            MergeRequestStatus {
+                number: 12,
+                url: Some("https://github.com/o/r/pull/12".into()),
+                state: "open".into(),
+            }
+        );
+
+        let gitlab = GitProvider::GitLab
+            .parse_view_output("feature", r#"{"iid":7,"web_url":"https://gitlab.com/o/r/-/merge_requests/7","state":"merged"}"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(gitlab.number, 7);
+        assert_eq!(
+            gitlab.url.as_deref(),
+            Some("https://gitlab.com/o/r/-/merge_requests/7")
+        );
+        assert_eq!(gitlab.state, "merged");
+
+        assert!(
+            GitProvider::GitHub
+                .parse_view_output("feature", "not json")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn head_branch_and_checkout_args_per_provider() {
+        assert_eq!(
+            GitProvider::GitHub.build_head_branch_args(12),
+            vec!["pr", "view", "12", "--json", "headRefName"]
+        );
+        assert_eq!(
+            GitProvider::GitLab.build_checkout_args(12),
+            vec!["mr", "checkout", "12"]
+        );
+        assert_eq!(
+            GitProvider::AzureDevOps.build_checkout_args(12),
+            vec!["repos", "pr", "checkout", "--id", "12"]
+        );
+
+        let branch =
+            |provider: GitProvider, stdout: &str| provider.parse_head_branch(12, stdout).unwrap();
+        assert_eq!(
+            branch(GitProvider::GitHub, r#"{"headRefName":"fix/login"}"#).as_deref(),
+            Some("fix/login")
+        );
+        assert_eq!(
+            branch(
+                GitProvider::GitLab,
+                r#"{"iid":12,"source_branch":"fix/login"}"#
+            )
+            .as_deref(),
+            Some("fix/login")
+        );
+        assert_eq!(
+            branch(
+                GitProvider::Bitbucket,
+                r#"{"id":12,"source":{"branch":{"name":"fix/login"}}}"#
+            )
+            .as_deref(),
+            Some("fix/login")
+        );
+        assert_eq!(
+            branch(
+                GitProvider::AzureDevOps,
+                r#"{"pullRequestId":12,"sourceRefName":"refs/heads/fix/login"}"#
+            )
+            .as_deref(),
+            Some("fix/login")
+        );
+        assert_eq!(
+            branch(
+                GitProvider::Gitea,
+                r#"[{"index":"3","head":"other"},{"index":"12","head":"fix/login"}]"#
+            )
+            .as_deref(),
+            Some("fix/login")
+        );
+        assert_eq!(branch(GitProvider::Gitea, "[]"), None);
+    }
+
+    #[test]
+    fn build_comment_args_per_provider() {
+        assert_eq!(
+            GitProvider::GitHub
+                .build_comment_args("feature/test", "Ready")
+                .unwrap(),
+            vec!["pr", "comment", "feature/test", "--body", "Ready"]
+        );
+        assert_eq!(
+            GitProvider::GitLab
+                .build_comment_args("feature/test", "Ready")
+                .unwrap(),
+            vec!["mr", "note", "feature/test", "--message", "Ready"]
+        );
+    }
+
+    #[test]
+    fn build_close_args_per_provider() {
+        assert_eq!(
+            GitProvider::GitHub.build_close_args("feature/test", Some("Superseded")),
+            vec!["pr", "close", "feature/test", "--comment", "Superseded"]
+        );
+        assert_eq!(
+            GitProvider::GitHub.build_close_args("feature/test", None),
+            vec!["pr", "close", "feature/test"]
+        );
+        assert_eq!(
+            GitProvider::GitLab.build_close_args("feature/test", Some("Superseded")),
+            vec!["mr", "close", "feature/test"]
+        );
+    }
+
+    #[test]
+    fn build_review_args_per_provider() {
+        assert_eq!(
+            GitProvider::GitHub.build_review_args(
+                "feature/test",
+                ReviewVerdict::RequestChanges,
+                Some("Needs tests")
+            ),
+            Some(vec![
+                "pr".to_owned(),
+                "review".to_owned(),
+                "feature/test".to_owned(),
+                "--request-changes".to_owned(),
+                "--body".to_owned(),
+                "Needs tests".to_owned(),
+            ])
+        );
+        assert_eq!(
+            GitProvider::GitLab.build_review_args(
+                "feature/test",
+                ReviewVerdict::Approve,
+                Some("LGTM")
+            ),
+            Some(vec![
+                "mr".to_owned(),
+                "approve".to_owned(),
+                "feature/test".to_owned()
+            ])
+        );
+        assert_eq!(
+            GitProvider::GitLab.build_review_args(
+                "feature/test",
+                ReviewVerdict::RequestChanges,
+                None
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn build_ci_args_per_provider() {
+        assert_eq!(
+            GitProvider::GitHub
+                .build_ci_trigger_args("feature/test", "ci.yml")
+                .unwrap(),
+            vec!["workflow", "run", "ci.yml", "--ref", "feature/test"]
+        );
+        assert_eq!(
+            GitProvider::GitLab
+                .build_ci_trigger_args("feature/test", "ci.yml")
+                .unwrap(),
+            vec!["ci", "run", "--branch", "feature/test"]
+        );
+        assert_eq!(
+            GitProvider::GitLab
+                .build_ci_status_args("feature/test")
+                .unwrap(),
+            vec!["ci", "get", "--branch", "feature/test", "--output", "json"]
+        );
+        assert!(
+            GitProvider::GitHub
+                .build_ci_status_args("feature/test")
+                .unwrap()
+                .starts_with(&["run".to_owned(), "list".to_owned()])
+        );
+        assert!(
+            GitProvider::Gitea
+                .build_ci_trigger_args("feature/test", "ci.yml")
+                .is_none()
+        );
+        assert!(
+            GitProvider::Gitea
+                .build_ci_status_args("feature/test")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn parse_ci_status_output_handles_github_and_gitlab_payloads() {
+        let github = GitProvider::GitHub
+            .parse_ci_status_output(r#"[{"databaseId":99,"workflowName":"CI","status":"completed","conclusion":"failure","url":"https://github.com/o/r/actions/runs/99"}]"#)
+            .unwrap()
+            .expect("run should be parsed");
+        assert_eq!(
+            github,
+            PipelineStatus {
+                id: 99,
+                name: Some("CI".into()),
+                state: PipelineState::Failed,
+                url: Some("https://github.com/o/r/actions/runs/99".into()),
+            }
+        );
+
+        let gitlab = GitProvider::GitLab
+            .parse_ci_status_output(
+                r#"{"id":5,"status":"running","web_url":"https://gitlab.com/o/r/-/pipelines/5"}"#,
+            )
+            .unwrap()
+            .expect("pipeline should be parsed");
+        assert_eq!(gitlab.id, 5);
+        assert_eq!(gitlab.state, PipelineState::Running);
+        assert!(!gitlab.state.is_finished());
+
+        assert_eq!(
+            GitProvider::GitHub.parse_ci_status_output("[]").unwrap(),
+            None
+        );
+        assert_eq!(
+            GitProvider::GitLab.parse_ci_status_output("").unwrap(),
+            None
+        );
+        assert!(
+            GitProvider::GitHub
+                .parse_ci_status_output("not json")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn pipeline_state_maps_provider_states() {
+        assert_eq!(
+            PipelineState::from_github("queued", None),
+            PipelineState::Pending
+        );
+        assert_eq!(
+            PipelineState::from_github("in_progress", None),
+            PipelineState::Running
+        );
+        assert_eq!(
+            PipelineState::from_github("completed", Some("cancelled")),
+            PipelineState::Canceled
+        );
+        assert_eq!(PipelineState::from_gitlab("manual"), PipelineState::Pending);
+        assert_eq!(
+            PipelineState::from_gitlab("success"),
+            PipelineState::Success
+        );
+        assert!(PipelineState::Success.is_finished());
     }
 
     #[test]
@@ -359,6 +1159,294 @@ mod tests {
         assert!(!GitProvider::GitLab.is_branch_delete_failure("success"));
     }
 
+    #[test]
+    fn is_branch_delete_failure_bitbucket() {
+        assert!(GitProvider::Bitbucket.is_branch_delete_failure("Failed to delete branch"));
+        assert!(GitProvider::Bitbucket.is_branch_delete_failure("could not delete branch"));
+        assert!(!GitProvider::Bitbucket.is_branch_delete_failure("success"));
+    }
+
+    #[test]
+    fn bitbucket_builds_pr_create_list_and_merge_args() {
+        let provider = GitProvider::Bitbucket;
+        assert_eq!(provider.cli_program(), "bb");
+        assert_eq!(provider.merge_request_term(), "pull request");
+
+        assert_eq!(
+            provider.build_create_args("feature", false, true, false, &["alice".to_owned()], &[]),
+            vec!["pr", "create", "--source", "feature", "--reviewer", "alice"]
+        );
+        assert_eq!(
+            provider.build_list_args("feature"),
+            vec![
+                "pr", "list", "--source", "feature", "--state", "OPEN", "--output", "json"
+            ]
+        );
+        assert_eq!(
+            provider.build_merge_args(7, true, MergeStrategy::Merge),
+            vec!["pr", "merge", "7", "--close-source-branch"]
+        );
+        assert_eq!(
+            provider.build_merge_args(7, false, MergeStrategy::Merge),
+            vec!["pr", "merge", "7"]
+        );
+        assert_eq!(
+            provider.build_close_args("feature", None),
+            vec!["pr", "decline", "feature"]
+        );
+    }
+
+    #[test]
+    fn bitbucket_parses_view_and_pipeline_payloads() {
+        let provider = GitProvider::Bitbucket;
+        let status = provider
+            .parse_view_output(
+                "feature",
+                r#"{"id":12,"state":"OPEN","links":{"html":{"href":"https://bitbucket.org/acme/app/pull-requests/12"}}}"#,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(status.number, 12);
+        assert_eq!(
+            status.url.as_deref(),
+            Some("https://bitbucket.org/acme/app/pull-requests/12")
+        );
+
+        let pipeline = provider
+            .parse_ci_status_output(
+                r#"[{"build_number":41,"state":{"name":"COMPLETED","result":{"name":"FAILED"}}}]"#,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(pipeline.id, 41);
+        assert_eq!(pipeline.state, PipelineState::Failed);
+        assert!(provider.parse_ci_status_output("[]").unwrap().is_none());
+    }
+
+    #[test]
+    fn gitea_builds_pr_create_list_and_merge_args() {
+        let provider = GitProvider::Gitea;
+        assert_eq!(provider.cli_program(), "tea");
+        assert_eq!(provider.merge_request_short(), "PR");
+        assert!(!provider.addresses_requests_by_branch());
+
+        assert_eq!(
+            provider.build_create_args("feature", true, true, true, &["alice".to_owned()], &[]),
+            vec!["pr", "create", "--head", "feature"]
+        );
+        assert_eq!(
+            provider.build_list_args("feature"),
+            vec![
+                "pr",
+                "list",
+                "--state",
+                "open",
+                "--limit",
+                "100",
+                "--fields",
+                "index,head,state,url",
+                "--output",
+                "json"
+            ]
+        );
+        assert_eq!(
+            provider.build_view_args("feature"),
+            provider.build_list_args("feature")
+        );
+        assert_eq!(
+            provider.build_merge_args(7, true, MergeStrategy::Merge),
+            vec!["pr", "merge", "7", "--style", "merge"]
+        );
+        assert_eq!(
+            provider.build_merge_args(7, true, MergeStrategy::Rebase),
+            vec!["pr", "merge", "7", "--style", "rebase"]
+        );
+        assert_eq!(
+            provider.build_comment_args("7", "Looks good").unwrap(),
+            vec!["comment", "7", "Looks good"]
+        );
+        assert_eq!(
+            provider.build_review_args("7", ReviewVerdict::RequestChanges, Some("Add tests")),
+            Some(vec![
+                "pr".to_owned(),
+                "reject".to_owned(),
+                "7".to_owned(),
+                "Add tests".to_owned()
+            ])
+        );
+    }
+
+    #[test]
+    fn gitea_picks_the_branch_from_pull_request_listing() {
+        let provider = GitProvider::Gitea;
+        let listing = r#"[
+            {"index":"3","head":"other","state":"open","url":"https://codeberg.org/o/r/pulls/3"},
+            {"index":"9","head":"feature","state":"open","url":"https://codeberg.org/o/r/pulls/9"}
+        ]"#;
+
+        assert_eq!(
+            provider.parse_list_output("feature", listing).unwrap(),
+            Some(9)
+        );
+        assert_eq!(
+            provider.parse_list_output("missing", listing).unwrap(),
+            None
+        );
+        assert_eq!(
+            provider.parse_view_output("feature", listing).unwrap(),
+            Some(MergeRequestStatus {
+                number: 9,
+                url: Some("https://codeberg.org/o/r/pulls/9".into()),
+                state: "open".into(),
+            })
+        );
+        assert_eq!(provider.parse_view_output("missing", "[]").unwrap(), None);
+        assert_eq!(
+            GitProvider::GitLab
+                .parse_list_output("feature", r#"[{"iid":4}]"#)
+                .unwrap(),
+            Some(4)
+        );
+    }
+
+    #[test]
+    fn is_branch_delete_failure_gitea() {
+        assert!(GitProvider::Gitea.is_branch_delete_failure("Branch deletion failed"));
+        assert!(!GitProvider::Gitea.is_branch_delete_failure("merged"));
+    }
+
+    #[test]
+    fn azure_devops_builds_az_repos_pr_args() {
+        let provider = GitProvider::AzureDevOps;
+        assert_eq!(provider.cli_program(), "az");
+        assert_eq!(provider.request_prefix(), "!");
+        assert!(!provider.addresses_requests_by_branch());
+        assert!(!provider.supports_comments());
+
+        assert_eq!(
+            provider.build_create_args(
+                "feature",
+                true,
+                true,
+                false,
+                &["alice".to_owned(), "bob".to_owned()],
+                &[]
+            ),
+            vec![
+                "repos",
+                "pr",
+                "create",
+                "--source-branch",
+                "feature",
+                "--draft",
+                "true",
+                "--reviewers",
+                "alice",
+                "bob"
+            ]
+        );
+        assert_eq!(
+            provider.build_list_args("feature"),
+            vec![
+                "repos",
+                "pr",
+                "list",
+                "--source-branch",
+                "feature",
+                "--status",
+                "active",
+                "--output",
+                "json"
+            ]
+        );
+        assert_eq!(
+            provider.build_merge_args(7, true, MergeStrategy::Merge),
+            vec![
+                "repos",
+                "pr",
+                "update",
+                "--id",
+                "7",
+                "--auto-complete",
+                "true",
+                "--delete-source-branch",
+                "true"
+            ]
+        );
+        assert_eq!(
+            provider.build_merge_args(7, false, MergeStrategy::Squash),
+            vec![
+                "repos",
+                "pr",
+                "update",
+                "--id",
+                "7",
+                "--auto-complete",
+                "true",
+                "--squash",
+                "true"
+            ]
+        );
+        assert!(!provider.supports_merge_strategy(MergeStrategy::Rebase));
+        assert_eq!(
+            provider.build_close_args("7", Some("Superseded")),
+            vec![
+                "repos",
+                "pr",
+                "update",
+                "--id",
+                "7",
+                "--status",
+                "abandoned"
+            ]
+        );
+        assert_eq!(
+            provider
+                .build_review_args("7", ReviewVerdict::RequestChanges, None)
+                .unwrap(),
+            vec![
+                "repos",
+                "pr",
+                "set-vote",
+                "--id",
+                "7",
+                "--vote",
+                "wait-for-author"
+            ]
+        );
+        assert_eq!(provider.build_comment_args("7", "Looks good"), None);
+        assert_eq!(provider.build_ci_status_args("feature"), None);
+    }
+
+    #[test]
+    fn azure_devops_parses_pull_request_listing() {
+        let provider = GitProvider::AzureDevOps;
+        let listing = r#"[
+            {
+                "pullRequestId": 31,
+                "sourceRefName": "refs/heads/feature",
+                "status": "active",
+                "url": "https://dev.azure.com/org/project/_apis/git/repositories/1/pullRequests/31",
+                "repository": {"webUrl": "https://dev.azure.com/org/project/_git/app"}
+            }
+        ]"#;
+
+        assert_eq!(
+            provider.parse_list_output("feature", listing).unwrap(),
+            Some(31)
+        );
+        assert_eq!(
+            provider.parse_view_output("feature", listing).unwrap(),
+            Some(MergeRequestStatus {
+                number: 31,
+                url: Some("https://dev.azure.com/org/project/_git/app/pullrequest/31".into()),
+                state: "active".into(),
+            })
+        );
+        assert_eq!(provider.parse_list_output("feat", listing).unwrap(), None);
+        assert_eq!(provider.parse_view_output("feature", "[]").unwrap(), None);
+    }
+
     #[test]
     fn from_str_parses_valid_providers() {
         assert_eq!("github".parse::<GitProvider>().unwrap(), GitProvider::GitHub);
@@ -367,6 +1455,21 @@ mod tests {
         assert_eq!("gitlab".parse::<GitProvider>().unwrap(), GitProvider::GitLab);
         assert_eq!("GitLab".parse::<GitProvider>().unwrap(), GitProvider::GitLab);
         assert_eq!("glab".parse::<GitProvider>().unwrap(), GitProvider::GitLab);
+        assert_eq!(
+            "bitbucket".parse::<GitProvider>().unwrap(),
+            GitProvider::Bitbucket
+        );
+        assert_eq!("bb".parse::<GitProvider>().unwrap(), GitProvider::Bitbucket);
+        assert_eq!("gitea".parse::<GitProvider>().unwrap(), GitProvider::Gitea);
+        assert_eq!("forgejo".parse::<GitProvider>().unwrap(), GitProvider::Gitea);
+        assert_eq!(
+            "azure-devops".parse::<GitProvider>().unwrap(),
+            GitProvider::AzureDevOps
+        );
+        assert_eq!(
+            "ado".parse::<GitProvider>().unwrap(),
+            GitProvider::AzureDevOps
+        );
     }
 
     #[test]
@@ -393,5 +1496,160 @@ mod tests {
         assert_eq!(json, "\"gitlab\"");
         let parsed: GitProvider = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed, GitProvider::GitLab);
+
+        let json = serde_json::to_string(&GitProvider::AzureDevOps).unwrap();
+        assert_eq!(json, "\"azuredevops\"");
+        let parsed: GitProvider = serde_json::from_str("\"azure\"").unwrap();
+        assert_eq!(parsed, GitProvider::AzureDevOps);
+    }
+
+    #[test]
+    fn built_in_providers_dispatch_through_the_trait() {
+        let providers: Vec<&dyn Provider> = [
+            GitProvider::GitHub,
+            GitProvider::GitLab,
+            GitProvider::Gitea,
+            GitProvider::Gerrit,
+        ]
+        .iter()
+        .map(GitProvider::provider)
+        .collect();
+        let names: Vec<_> = providers.iter().map(|provider| provider.name()).collect();
+        assert_eq!(names, ["github", "gitlab", "gitea", "gerrit"]);
+        assert_eq!(
+            providers[1].build_merge_args(3, true, MergeStrategy::Squash),
+            ["mr", "merge", "3", "--squash", "--remove-source-branch"]
+        );
+        assert!(!providers[2].addresses_requests_by_branch());
+        assert_eq!(providers[3].build_comment_args("feature", "hi"), None);
+    }
+
+    #[derive(Debug)]
+    struct ReviewBoard;
+
+    impl Provider for ReviewBoard {
+        fn name(&self) -> &'static str {
+            "reviewboard"
+        }
+
+        fn display_name(&self) -> &'static str {
+            "Review Board"
+        }
+
+        fn cli_program(&self) -> &'static str {
+            "rbt"
+        }
+
+        fn request_subcommand(&self) -> &'static str {
+            "request"
+        }
+
+        fn build_create_args(
+            &self,
+            branch: &str,
+            _draft: bool,
+            _fill: bool,
+            _web: bool,
+            _reviewers: &[String],
+            _extra_args: &[String],
+        ) -> Vec<String> {
+            vec!["request".into(), "push".into(), branch.into()]
+        }
+
+        fn build_list_args(&self, branch: &str) -> Vec<String> {
+            vec!["request".into(), "list".into(), branch.into()]
+        }
+
+        fn parse_list_output(
+            &self,
+            _branch: &str,
+            stdout: &str,
+        ) -> Result<Option<u64>, serde_json::Error> {
+            serde_json::from_str(stdout)
+        }
+
+        fn build_view_args(&self, branch: &str) -> Vec<String> {
+            self.build_list_args(branch)
+        }
+
+        fn parse_view_output(
+            &self,
+            _branch: &str,
+            stdout: &str,
+        ) -> Result<Option<MergeRequestStatus>, serde_json::Error> {
+            let number: Option<u64> = serde_json::from_str(stdout)?;
+            Ok(number.map(|number| MergeRequestStatus {
+                number,
+                url: None,
+                state: "new".into(),
+            }))
+        }
+
+        fn build_head_branch_args(&self, number: u64) -> Vec<String> {
+            vec!["request".into(), "view".into(), number.to_string()]
+        }
+
+        fn parse_head_branch(
+            &self,
+            _number: u64,
+            stdout: &str,
+        ) -> Result<Option<String>, serde_json::Error> {
+            Ok(Some(stdout.trim().to_owned()))
+        }
+
+        fn build_checkout_args(&self, number: u64) -> Vec<String> {
+            vec!["request".into(), "checkout".into(), number.to_string()]
+        }
+
+        fn build_merge_args(
+            &self,
+            mr_number: u64,
+            _delete_branch: bool,
+            _strategy: MergeStrategy,
+        ) -> Vec<String> {
+            vec!["request".into(), "submit".into(), mr_number.to_string()]
+        }
+
+        fn build_close_args(&self, branch: &str, _comment: Option<&str>) -> Vec<String> {
+            vec!["request".into(), "abandon".into(), branch.into()]
+        }
+    }
+
+    #[test]
+    fn registered_providers_are_selected_by_name() {
+        let reviewboard = register(ReviewBoard);
+        assert_eq!("ReviewBoard".parse::<GitProvider>(), Ok(reviewboard));
+        assert_eq!(
+            serde_json::to_string(&reviewboard).unwrap(),
+            "\"reviewboard\""
+        );
+        let parsed: GitProvider = serde_json::from_str("\"reviewboard\"").unwrap();
+        assert_eq!(parsed, reviewboard);
+        assert_eq!(reviewboard.to_string(), "Review Board");
+        assert!(
+            "reviewbord"
+                .parse::<GitProvider>()
+                .unwrap_err()
+                .ends_with("'gerrit' or 'reviewboard'")
+        );
+
+        assert_eq!(reviewboard.cli_program(), "rbt");
+        assert_eq!(reviewboard.request_prefix(), "#");
+        assert_eq!(
+            reviewboard.build_create_args("feature", true, true, false, &[], &[]),
+            ["request", "push", "feature"]
+        );
+        assert_eq!(
+            reviewboard.build_merge_args(7, true, MergeStrategy::Merge),
+            ["request", "submit", "7"]
+        );
+        assert!(!reviewboard.supports_merge_strategy(MergeStrategy::Squash));
+        assert_eq!(
+            reviewboard.parse_list_output("feature", "12").unwrap(),
+            Some(12)
+        );
+        assert!(!reviewboard.supports_comments());
+        assert_eq!(reviewboard.build_comment_args("feature", "hi"), None);
+        assert_eq!(reviewboard.build_ci_status_args("feature"), None);
     }
 }
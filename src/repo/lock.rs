@@ -0,0 +1,99 @@
+//! The advisory lock taken by commands that change the worktrees directory.
+//!
+//! `create`, `rm`, `rename` and `prune` hold `.rsworktree/lock` while they
+//! run, so two invocations cannot move or remove the same checkout at once.
+//! The lock is held by the process: commands calling each other, such as
+//! `prune` removing its candidates, take it once. Hooks inherit the path in
+//! `RSWORKTREE_LOCK`, so a hook running `rsworktree` again does not wait for
+//! the command that started it.
+
+use std::{
+    env,
+    fs::{File, OpenOptions, TryLockError},
+    io,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+
+use crate::output;
+
+/// Path of the lock held by the invoking command, exported to hooks.
+pub(crate) const LOCK_ENV: &str = "RSWORKTREE_LOCK";
+
+pub(super) const LOCK_FILE: &str = "lock";
+
+/// The locks this process holds.
+static HELD: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Releases the repository lock when dropped.
+#[derive(Debug)]
+pub struct RepoLock {
+    /// `None` when the lock was already held by this process or its parent.
+    file: Option<File>,
+    path: PathBuf,
+}
+
+impl RepoLock {
+    pub(super) fn acquire(path: PathBuf, wait: bool) -> color_eyre::Result<Self> {
+        let inherited = env::var_os(LOCK_ENV).is_some_and(|locked| Path::new(&locked) == path);
+        if inherited || held().contains(&path) {
+            return Ok(Self { file: None, path });
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .wrap_err_with(|| eyre::eyre!("failed to open `{}`", path.display()))?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) if wait => wait_for(&file)
+                .wrap_err_with(|| eyre::eyre!("failed to lock `{}`", path.display()))?,
+            Err(TryLockError::WouldBlock) => {
+                return Err(eyre::eyre!(
+                    "another rsworktree command holds `{}`; try again once it has finished",
+                    path.display()
+                ));
+            }
+            Err(TryLockError::Error(err)) => {
+                return Err(err)
+                    .wrap_err_with(|| eyre::eyre!("failed to lock `{}`", path.display()));
+            }
+        }
+
+        held().push(path.clone());
+        Ok(Self {
+            file: Some(file),
+            path,
+        })
+    }
+
+    /// The environment handed to hooks while this process holds a lock.
+    pub(crate) fn env() -> Option<(&'static str, PathBuf)> {
+        held().last().map(|path| (LOCK_ENV, path.clone()))
+    }
+}
+
+impl Drop for RepoLock {
+    fn drop(&mut self) {
+        if let Some(file) = self.file.take() {
+            let _ = file.unlock();
+            held().retain(|path| *path != self.path);
+        }
+    }
+}
+
+fn held() -> MutexGuard<'static, Vec<PathBuf>> {
+    HELD.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Blocks until `file` is locked, with a spinner on an interactive stderr.
+fn wait_for(file: &File) -> io::Result<()> {
+    output::step(
+        tr!("Waiting for another rsworktree command to finish..."),
+        || file.lock(),
+    )
+}
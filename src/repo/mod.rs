@@ -1,23 +1,82 @@
 use std::{
     fs::{self, OpenOptions},
     io::Write,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
 
 use color_eyre::eyre::{self, Context};
 use git2::Repository as GitRepository;
 
+use crate::{
+    annotations,
+    commands::{archive::ARCHIVE_DIR, info::CACHE_DIR},
+    config::{self, Config, ConfigError, Layout},
+    editor::{CONFIG_FILE_NAME, PROJECT_FILE_NAME},
+    hooks::{self, ENV_FILE, LOGS_DIR},
+    process::{CommandRunner, SystemCommandRunner},
+    telemetry::STATE_DIR,
+    templates::TEMPLATES_DIR,
+    worktree::ports::PORTS_FILE,
+};
+
+mod lock;
+pub(crate) mod signing;
+
+pub(crate) use self::lock::RepoLock;
+
 const WORKTREE_IGNORE_ENTRY: &str = ".rsworktree/";
 const WORKTREE_IGNORE_ALT_ENTRY: &str = ".rsworktree";
+pub(crate) const WORKTREES_DIR_NAME: &str = ".rsworktree";
+/// The entries of `.rsworktree` that hold configuration and state rather
+/// than a worktree.
+const RESERVED_NAMES: &[&str] = &[
+    config::CONFIG_FILE,
+    CONFIG_FILE_NAME,
+    PROJECT_FILE_NAME,
+    ENV_FILE,
+    lock::LOCK_FILE,
+    PORTS_FILE,
+    hooks::HOOKS_DIR,
+    LOGS_DIR,
+    TEMPLATES_DIR,
+    STATE_DIR,
+    annotations::META_DIR,
+    CACHE_DIR,
+    ARCHIVE_DIR,
+];
+/// Lists the object directories of other clones a repository borrows from,
+/// relative to the git common directory.
+const ALTERNATES_FILE: &str = "objects/info/alternates";
+
+/// An entry of `git worktree list --porcelain`, including checkouts outside
+/// `.rsworktree` and the main working tree.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct GitWorktree {
+    pub(crate) path: PathBuf,
+    pub(crate) head: Option<String>,
+    /// Short name of the checked out branch; `None` when detached or bare.
+    pub(crate) branch: Option<String>,
+    pub(crate) bare: bool,
+    pub(crate) locked: bool,
+    pub(crate) prunable: bool,
+}
 
 pub struct Repo {
     git: GitRepository,
     root: PathBuf,
+    /// `.rsworktree`, holding the configuration and recorded state.
+    rsworktree_dir: PathBuf,
+    /// Where the checkouts go, which `layout` may move out of `rsworktree_dir`.
+    worktrees_dir: PathBuf,
 }
 
 impl std::fmt::Debug for Repo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Repo").field("root", &self.root).finish()
+        f.debug_struct("Repo")
+            .field("root", &self.root)
+            .field("rsworktree_dir", &self.rsworktree_dir)
+            .field("worktrees_dir", &self.worktrees_dir)
+            .finish()
     }
 }
 
@@ -28,11 +87,12 @@ impl Repo {
     }
 
     pub fn discover_from<P: AsRef<Path>>(path: P) -> color_eyre::Result<Self> {
+        let _span = tracing::info_span!("discover", path = %path.as_ref().display()).entered();
         let discovered =
             GitRepository::discover(path.as_ref()).wrap_err("failed to discover git repository")?;
 
         let common_dir = discovered.commondir().to_path_buf();
-        let root = common_dir
+        let parent = common_dir
             .parent()
             .ok_or_else(|| {
                 eyre::eyre!(
@@ -42,6 +102,32 @@ impl Repo {
             })?
             .to_path_buf();
 
+        // A bare repository in `.git` (or behind a `.git` file, as with
+        // `git clone --bare <url> .bare`) keeps its worktrees next to it like a
+        // normal checkout. A bare directory on its own, such as `project.git`,
+        // gets a sibling `project.rsworktree` instead.
+        let bare = discovered.is_bare()
+            || (discovered.is_worktree()
+                && GitRepository::open(&common_dir).is_ok_and(|repo| repo.is_bare()));
+        let (root, rsworktree_dir) = if bare && !parent.join(".git").exists() {
+            let name = common_dir
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| String::from("repository"));
+            let rsworktree_dir = parent.join(format!("{name}{WORKTREES_DIR_NAME}"));
+            (common_dir.clone(), rsworktree_dir)
+        } else {
+            let rsworktree_dir = parent.join(WORKTREES_DIR_NAME);
+            (parent, rsworktree_dir)
+        };
+        // Only the checkouts move with `layout`; a broken file is reported by
+        // the commands that read it.
+        let layout = Config::load(&rsworktree_dir)
+            .ok()
+            .and_then(|config| config.layout)
+            .unwrap_or_default();
+        let worktrees_dir = layout_dir(layout, &root)?.unwrap_or_else(|| rsworktree_dir.clone());
+
         let git = if discovered.is_worktree() {
             GitRepository::open(&root)
                 .or_else(|_| GitRepository::open(common_dir.clone()))
@@ -50,30 +136,177 @@ impl Repo {
             discovered
         };
 
-        Ok(Self { git, root })
+        Ok(Self {
+            git,
+            root,
+            rsworktree_dir,
+            worktrees_dir,
+        })
     }
 
     pub fn root(&self) -> &Path {
         &self.root
     }
 
+    /// The name of the repository directory, without the `.git` of a bare clone.
+    pub fn name(&self) -> String {
+        repo_name(&self.root)
+    }
+
     pub fn git(&self) -> &GitRepository {
         &self.git
     }
 
+    /// The directory of `config.toml`, `preferences.json`, hooks, templates
+    /// and recorded state, which stays in the repository whatever the `layout`.
+    pub fn rsworktree_dir(&self) -> PathBuf {
+        self.rsworktree_dir.clone()
+    }
+
+    /// The directory the worktrees are checked out in.
     pub fn worktrees_dir(&self) -> PathBuf {
-        self.root.join(".rsworktree")
+        self.worktrees_dir.clone()
     }
 
-    pub fn ensure_worktrees_dir(&self) -> color_eyre::Result<PathBuf> {
+    /// Whether the repository has no primary working tree, as after
+    /// `git clone --bare`; every checkout is then a worktree.
+    pub fn is_bare(&self) -> bool {
+        self.git.is_bare()
+    }
+
+    /// The merged per-user and repository `config.toml` settings.
+    pub fn config(&self) -> Result<Config, ConfigError> {
+        Config::load(&self.rsworktree_dir)
+    }
+
+    pub fn ensure_rsworktree_dir(&self) -> color_eyre::Result<PathBuf> {
         self.ensure_gitignore_entry()?;
+        let dir = self.rsworktree_dir();
+        fs::create_dir_all(&dir)
+            .wrap_err_with(|| eyre::eyre!("failed to create `{}`", dir.display()))?;
+        Ok(dir)
+    }
+
+    pub fn ensure_worktrees_dir(&self) -> color_eyre::Result<PathBuf> {
+        self.ensure_rsworktree_dir()?;
         let dir = self.worktrees_dir();
         fs::create_dir_all(&dir)
             .wrap_err_with(|| eyre::eyre!("failed to create `{}`", dir.display()))?;
         Ok(dir)
     }
 
+    /// Takes the repository lock for a command that changes the worktrees
+    /// directory. Without `wait`, fails right away when another command holds it.
+    pub(crate) fn lock(&self, wait: bool) -> color_eyre::Result<RepoLock> {
+        RepoLock::acquire(self.ensure_rsworktree_dir()?.join(lock::LOCK_FILE), wait)
+    }
+
+    /// Every worktree git knows about, the main working tree first.
+    pub(crate) fn git_worktrees(&self) -> color_eyre::Result<Vec<GitWorktree>> {
+        self.git_worktrees_with(&mut SystemCommandRunner)
+    }
+
+    /// Like [`Repo::git_worktrees`], running `git worktree list` through `runner`.
+    pub(crate) fn git_worktrees_with(
+        &self,
+        runner: &mut impl CommandRunner,
+    ) -> color_eyre::Result<Vec<GitWorktree>> {
+        let args = ["worktree", "list", "--porcelain"].map(String::from);
+        let output = runner
+            .run("git", &self.root, &args)
+            .wrap_err("failed to run `git worktree list`")?;
+        if !output.success {
+            return Err(eyre::eyre!(
+                "`git worktree list` failed: {}",
+                output.stderr.trim()
+            ));
+        }
+        Ok(parse_worktree_list(&output.stdout))
+    }
+
+    /// The object directories of other clones this repository borrows objects
+    /// from, as listed in `.git/objects/info/alternates`.
+    pub fn alternates(&self) -> color_eyre::Result<Vec<PathBuf>> {
+        let path = self.git.commondir().join(ALTERNATES_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => {
+                return Err(eyre::eyre!("failed to read `{}`: {error}", path.display()));
+            }
+        };
+        let objects_dir = self.git.commondir().join("objects");
+        Ok(contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            // Relative entries are relative to the objects directory.
+            .map(|line| objects_dir.join(line))
+            .collect())
+    }
+
+    /// Lets this repository use the objects of the clone at `reference`, like
+    /// `git clone --reference`, so fetches and new worktrees only store the
+    /// objects that clone lacks. Returns the clone's object directory and
+    /// whether it was not attached before.
+    ///
+    /// The reference clone must stay in place: objects only it has are not
+    /// copied, unless `git repack -a -d` is run here first.
+    pub fn attach_alternate(&self, reference: &Path) -> color_eyre::Result<(PathBuf, bool)> {
+        let reference_repo = GitRepository::open(reference)
+            .wrap_err_with(|| eyre::eyre!("`{}` is not a git repository", reference.display()))?;
+        let objects_dir = std::path::absolute(reference_repo.commondir().join("objects"))
+            .wrap_err_with(|| eyre::eyre!("failed to resolve `{}`", reference.display()))?;
+        let own_objects_dir = self.git.commondir().join("objects");
+        if fs::canonicalize(&objects_dir).ok() == fs::canonicalize(&own_objects_dir).ok() {
+            return Err(eyre::eyre!(
+                "`{}` is this repository; pass another clone to share objects with",
+                reference.display()
+            ));
+        }
+        let attached = self.alternates()?.iter().any(|alternate| {
+            fs::canonicalize(alternate).ok() == fs::canonicalize(&objects_dir).ok()
+        });
+        if attached {
+            return Ok((objects_dir, false));
+        }
+
+        let path = self.git.commondir().join(ALTERNATES_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| eyre::eyre!("failed to create `{}`", parent.display()))?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| writeln!(file, "{}", objects_dir.display()))
+            .wrap_err_with(|| eyre::eyre!("failed to write `{}`", path.display()))?;
+        // The object database read the file when it was opened.
+        self.git
+            .odb()
+            .and_then(|odb| odb.add_disk_alternate(&objects_dir.to_string_lossy()))
+            .wrap_err("failed to load the objects of the reference clone")?;
+        Ok((objects_dir, true))
+    }
+
+    /// The worktree that has `branch` checked out, wherever it lives.
+    pub(crate) fn worktree_for_branch(
+        &self,
+        branch: &str,
+    ) -> color_eyre::Result<Option<GitWorktree>> {
+        Ok(self
+            .git_worktrees()?
+            .into_iter()
+            .find(|worktree| worktree.branch.as_deref() == Some(branch)))
+    }
+
     fn ensure_gitignore_entry(&self) -> color_eyre::Result<()> {
+        // Without a working tree there is nothing `.rsworktree` could be committed
+        // from, and a directory outside the repository needs no entry.
+        if self.is_bare() || self.rsworktree_dir != self.root.join(WORKTREES_DIR_NAME) {
+            return Ok(());
+        }
         let gitignore_path = self.root.join(".gitignore");
 
         if gitignore_path.exists() {
@@ -111,6 +344,85 @@ impl Repo {
     }
 }
 
+/// Rejects worktree names that would take one of the [`RESERVED_NAMES`] or
+/// nest below it, such as `state` or `logs/x`.
+pub(crate) fn ensure_unreserved_name(name: &str) -> color_eyre::Result<()> {
+    let first = Path::new(name).components().next();
+    if let Some(Component::Normal(first)) = first
+        && let Some(reserved) = RESERVED_NAMES.iter().find(|reserved| first == **reserved)
+    {
+        return Err(eyre::eyre!(
+            "`{}` is reserved for rsworktree's own data in `{}`; pick another name for the worktree",
+            reserved,
+            WORKTREES_DIR_NAME
+        ));
+    }
+    Ok(())
+}
+
+/// The worktrees directory `layout` places outside the repository at `root`,
+/// or `None` for the default one.
+fn layout_dir(layout: Layout, root: &Path) -> color_eyre::Result<Option<PathBuf>> {
+    let name = repo_name(root);
+    match layout {
+        Layout::Inside => Ok(None),
+        Layout::Sibling => {
+            let parent = root.parent().unwrap_or(root);
+            Ok(Some(parent.join(format!("{name}-worktrees"))))
+        }
+        Layout::FlatGlobal => {
+            let home = std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .filter(|home| !home.is_empty())
+                .ok_or_else(|| {
+                    eyre::eyre!("layout `flat-global` needs $HOME to locate `~/worktrees`")
+                })?;
+            Ok(Some(PathBuf::from(home).join("worktrees").join(name)))
+        }
+    }
+}
+
+fn repo_name(root: &Path) -> String {
+    root.file_name()
+        .map(|name| name.to_string_lossy())
+        .map(|name| name.strip_suffix(".git").unwrap_or(&name).to_owned())
+        .unwrap_or_else(|| String::from("repository"))
+}
+
+/// Parses `git worktree list --porcelain`: one block of `<label> [value]`
+/// lines per worktree, separated by blank lines.
+fn parse_worktree_list(text: &str) -> Vec<GitWorktree> {
+    let mut worktrees = Vec::new();
+    let mut current: Option<GitWorktree> = None;
+    for line in text.lines() {
+        let (label, value) = line.split_once(' ').unwrap_or((line, ""));
+        if label == "worktree" {
+            worktrees.extend(current.take());
+            current = Some(GitWorktree {
+                path: PathBuf::from(value),
+                ..GitWorktree::default()
+            });
+            continue;
+        }
+        let Some(worktree) = current.as_mut() else {
+            continue;
+        };
+        match label {
+            "HEAD" => worktree.head = Some(value.to_owned()),
+            "branch" => {
+                let branch = value.strip_prefix("refs/heads/").unwrap_or(value);
+                worktree.branch = Some(branch.to_owned());
+            }
+            "bare" => worktree.bare = true,
+            "locked" => worktree.locked = true,
+            "prunable" => worktree.prunable = true,
+            _ => {}
+        }
+    }
+    worktrees.extend(current);
+    worktrees
+}
+
 fn gitignore_has_entry(contents: &str) -> bool {
     contents
         .lines()
@@ -121,10 +433,12 @@ fn gitignore_has_entry(contents: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use std::{fs, process::Command};
 
     use tempfile::TempDir;
 
+    use crate::process::{CommandOutput, ScriptedRunner};
+
     fn init_repo(dir: &TempDir) -> color_eyre::Result<Repo> {
         git2::Repository::init(dir.path())?;
         Repo::discover_from(dir.path())
@@ -179,6 +493,86 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn lock_is_exclusive_between_processes_and_reentrant_within_one() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let repo = init_repo(&dir)?;
+
+        let lock = repo.lock(false)?;
+        drop(repo.lock(false)?);
+        // A second open file description stands in for another process.
+        let other = fs::File::open(repo.rsworktree_dir().join(lock::LOCK_FILE))?;
+        assert!(other.try_lock().is_err());
+        drop(lock);
+
+        other.try_lock()?;
+        let error = repo.lock(false).expect_err("lock should be held elsewhere");
+        assert!(error.to_string().contains("another rsworktree command"));
+        drop(other);
+        repo.lock(false)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn sibling_layout_keeps_worktrees_next_to_the_repository() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let root = dir.path().join("app");
+        git2::Repository::init(&root)?;
+        fs::create_dir_all(root.join(WORKTREES_DIR_NAME))?;
+        fs::write(
+            root.join(WORKTREES_DIR_NAME)
+                .join(crate::config::CONFIG_FILE),
+            "layout = \"sibling\"\n",
+        )?;
+
+        let repo = Repo::discover_from(&root)?;
+        let worktrees_dir = repo.ensure_worktrees_dir()?;
+
+        assert_eq!(worktrees_dir, dir.path().join("app-worktrees"));
+        assert!(worktrees_dir.is_dir());
+        assert_eq!(repo.rsworktree_dir(), root.join(WORKTREES_DIR_NAME));
+        assert_eq!(repo.config()?.layout, Some(Layout::Sibling));
+        drop(repo.lock(false)?);
+        assert!(root.join(WORKTREES_DIR_NAME).join(lock::LOCK_FILE).exists());
+        assert!(!worktrees_dir.join(lock::LOCK_FILE).exists());
+        assert_eq!(
+            layout_dir(Layout::Sibling, Path::new("/src/app.git"))?,
+            Some(PathBuf::from("/src/app-worktrees"))
+        );
+        assert_eq!(layout_dir(Layout::Inside, &root)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn attach_alternate_borrows_objects_from_another_clone() -> color_eyre::Result<()> {
+        let reference_dir = TempDir::new()?;
+        let reference = git2::Repository::init(reference_dir.path())?;
+        let signature = git2::Signature::now("Test", "test@example.com")?;
+        let tree = reference.find_tree(reference.index()?.write_tree()?)?;
+        let commit = reference.commit(None, &signature, &signature, "Shared", &tree, &[])?;
+
+        let dir = TempDir::new()?;
+        let repo = init_repo(&dir)?;
+        assert!(repo.git().find_commit(commit).is_err());
+        assert!(repo.attach_alternate(dir.path()).is_err());
+
+        let (objects_dir, attached) = repo.attach_alternate(reference_dir.path())?;
+        assert!(attached);
+        assert!(objects_dir.ends_with(".git/objects"));
+        assert!(repo.git().find_commit(commit).is_ok());
+        assert!(!repo.attach_alternate(reference_dir.path())?.1);
+        assert_eq!(repo.alternates()?, [objects_dir]);
+        // git itself finds the borrowed commit, too.
+        let output = Command::new("git")
+            .args(["cat-file", "-t", &commit.to_string()])
+            .current_dir(dir.path())
+            .output()?;
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "commit");
+        Ok(())
+    }
+
     #[test]
     fn gitignore_has_entry_detects_alternate_form() {
         assert!(gitignore_has_entry(".rsworktree\n"));
@@ -200,4 +594,92 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn parse_worktree_list_reads_each_block() {
+        let text = "\
+worktree /repo
+HEAD 1111111111111111111111111111111111111111
+branch refs/heads/main
+
+worktree /repo/.rsworktree/review
+HEAD 2222222222222222222222222222222222222222
+detached
+locked on a removable drive
+
+worktree /elsewhere/feature
+HEAD 3333333333333333333333333333333333333333
+branch refs/heads/feature/x
+prunable gitdir file points to non-existent location
+";
+        let worktrees = parse_worktree_list(text);
+
+        assert_eq!(worktrees.len(), 3);
+        assert_eq!(worktrees[0].path, PathBuf::from("/repo"));
+        assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+        assert_eq!(worktrees[1].branch, None);
+        assert!(worktrees[1].locked);
+        assert_eq!(
+            worktrees[2],
+            GitWorktree {
+                path: PathBuf::from("/elsewhere/feature"),
+                head: Some("3".repeat(40)),
+                branch: Some("feature/x".into()),
+                bare: false,
+                locked: false,
+                prunable: true,
+            }
+        );
+        assert!(parse_worktree_list("worktree /bare.git\nbare\n")[0].bare);
+    }
+
+    #[test]
+    fn git_worktrees_runs_git_in_the_root() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let repo = init_repo(&dir)?;
+        let runner = ScriptedRunner::new();
+        runner
+            .push(CommandOutput::success(
+                "worktree /repo\nHEAD 1111\nbranch refs/heads/main\n",
+            ))
+            .push(CommandOutput::failure(128, "fatal: not a git repository\n"));
+
+        let worktrees = repo.git_worktrees_with(&mut runner.clone())?;
+        assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+        let error = repo
+            .git_worktrees_with(&mut runner.clone())
+            .expect_err("scripted to fail");
+        assert!(error.to_string().contains("not a git repository"));
+
+        let call = &runner.calls()[0];
+        assert_eq!(call.current_dir, repo.root());
+        assert_eq!(call.args, ["worktree", "list", "--porcelain"]);
+        Ok(())
+    }
+
+    #[test]
+    fn bare_repositories_keep_worktrees_next_to_the_git_dir() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        git2::Repository::init_bare(dir.path().join("project/.git"))?;
+        git2::Repository::init_bare(dir.path().join("lone.git"))?;
+
+        let project = Repo::discover_from(dir.path().join("project"))?;
+        assert!(project.is_bare());
+        assert_eq!(project.root(), dir.path().join("project"));
+        assert_eq!(
+            project.worktrees_dir(),
+            dir.path().join("project/.rsworktree")
+        );
+        project.ensure_worktrees_dir()?;
+        assert!(!dir.path().join("project/.gitignore").exists());
+
+        let lone = Repo::discover_from(dir.path().join("lone.git"))?;
+        assert!(lone.is_bare());
+        assert_eq!(lone.worktrees_dir(), dir.path().join("lone.rsworktree"));
+        assert_eq!(lone.name(), "lone");
+        assert_eq!(project.name(), "project");
+        assert!(!init_repo(&TempDir::new()?)?.is_bare());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,158 @@
+//! Commit signing as git is told to do it with `commit.gpgsign`,
+//! `gpg.format` and `user.signingkey`, so that a commit git could not sign is
+//! reported with what to check rather than git's bare error.
+
+use git2::Config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SigningFormat {
+    OpenPgp,
+    Ssh,
+    X509,
+}
+
+impl SigningFormat {
+    /// The value of `gpg.format`.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::OpenPgp => "openpgp",
+            Self::Ssh => "ssh",
+            Self::X509 => "x509",
+        }
+    }
+
+    fn default_program(self) -> &'static str {
+        match self {
+            Self::OpenPgp => "gpg",
+            Self::Ssh => "ssh-keygen",
+            Self::X509 => "gpgsm",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Signing {
+    pub(crate) format: SigningFormat,
+    /// `user.signingkey`; git falls back to the committer identity for
+    /// OpenPGP and X.509, or to `gpg.ssh.defaultKeyCommand` for SSH.
+    pub(crate) key: Option<String>,
+    /// The program git runs to sign, `gpg.<format>.program` or its default.
+    pub(crate) program: String,
+}
+
+impl Signing {
+    /// The signing git applies to new commits, or `None` when
+    /// `commit.gpgsign` is off.
+    pub(crate) fn load(config: &Config) -> Option<Self> {
+        if !config.get_bool("commit.gpgsign").unwrap_or(false) {
+            return None;
+        }
+        let format = match config.get_string("gpg.format").ok().as_deref() {
+            Some("ssh") => SigningFormat::Ssh,
+            Some("x509") => SigningFormat::X509,
+            _ => SigningFormat::OpenPgp,
+        };
+        let program = config
+            .get_string(&format!("gpg.{}.program", format.as_str()))
+            .ok()
+            .or_else(|| {
+                // `gpg.program` predates the other formats and still names the OpenPGP program.
+                (format == SigningFormat::OpenPgp)
+                    .then(|| config.get_string("gpg.program").ok())
+                    .flatten()
+            })
+            .unwrap_or_else(|| format.default_program().to_owned());
+        Some(Self {
+            format,
+            key: config
+                .get_string("user.signingkey")
+                .ok()
+                .filter(|key| !key.trim().is_empty()),
+            program,
+        })
+    }
+
+    /// What to check when git could not sign a commit.
+    pub(crate) fn hint(&self) -> String {
+        match (self.format, &self.key) {
+            (SigningFormat::Ssh, None) => {
+                "set `user.signingkey` to your SSH public key, e.g. `git config --global user.signingkey ~/.ssh/id_ed25519.pub`".to_owned()
+            }
+            (SigningFormat::Ssh, Some(key)) => format!(
+                "check that `{key}` is readable and its key is loaded in your SSH agent (`ssh-add -l`)"
+            ),
+            (SigningFormat::OpenPgp, key) => format!(
+                "check that `echo test | {} --clear-sign{}` works; in a terminal, `export GPG_TTY=$(tty)` lets gpg ask for the passphrase",
+                self.program,
+                key.as_deref()
+                    .map(|key| format!(" --local-user {key}"))
+                    .unwrap_or_default()
+            ),
+            (SigningFormat::X509, _) => format!(
+                "check that `{} --list-secret-keys` lists the certificate of `user.signingkey`",
+                self.program
+            ),
+        }
+    }
+}
+
+/// Whether git's error output says a commit could not be signed.
+pub(crate) fn is_signing_failure(stderr: &str) -> bool {
+    let stderr = stderr.to_ascii_lowercase();
+    [
+        "failed to sign",
+        "signing failed",
+        "couldn't sign",
+        "failed to write commit object",
+    ]
+    .iter()
+    .any(|needle| stderr.contains(needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn loads_signing_from_git_config() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let repo = git2::Repository::init(dir.path())?;
+        let mut config = repo.config()?.open_level(git2::ConfigLevel::Local)?;
+        config.set_bool("commit.gpgsign", false)?;
+        assert_eq!(Signing::load(&repo.config()?.snapshot()?), None);
+
+        config.set_bool("commit.gpgsign", true)?;
+        config.set_str("gpg.format", "ssh")?;
+        let signing = Signing::load(&repo.config()?.snapshot()?).expect("signing is on");
+        assert_eq!(signing.format, SigningFormat::Ssh);
+        assert_eq!(signing.program, "ssh-keygen");
+        assert!(signing.hint().contains("user.signingkey"));
+
+        config.set_str("gpg.format", "openpgp")?;
+        config.set_str("gpg.program", "gpg2")?;
+        config.set_str("user.signingkey", "ABCD1234")?;
+        let signing = Signing::load(&repo.config()?.snapshot()?).expect("signing is on");
+        assert_eq!(signing.program, "gpg2");
+        assert!(
+            signing
+                .hint()
+                .contains("gpg2 --clear-sign --local-user ABCD1234")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn recognizes_signing_failures() {
+        assert!(is_signing_failure(
+            "error: gpg failed to sign the data\nfatal: failed to write commit object"
+        ));
+        assert!(is_signing_failure(
+            "error: Couldn't load public key ~/.ssh/id.pub: No such file or directory?\n\nfatal: failed to write commit object"
+        ));
+        assert!(!is_signing_failure(
+            "error: patch failed: README.md:1\nerror: README.md: patch does not apply"
+        ));
+    }
+}
@@ -0,0 +1,373 @@
+//! Events worth counting in `rsworktree stats`, dispatched to the sinks
+//! selected under `[telemetry]` in `config.toml`:
+//!
+//! ```toml
+//! [telemetry]
+//! sinks = ["jsonl", "stderr"]
+//! ```
+//!
+//! `jsonl`, the default, appends to `.rsworktree/state/events.jsonl`, which is
+//! what `stats` reads. `stderr` prints one line per event and `otlp` sends
+//! OpenTelemetry log records to `endpoint`; the latter needs the `otlp` cargo
+//! feature. Spans for debugging slow commands are separate, see `trace.rs`.
+
+#[cfg(feature = "otlp")]
+mod otlp;
+mod sinks;
+mod trace;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+#[cfg(feature = "otlp")]
+pub use otlp::OtlpSink;
+pub use sinks::{JsonlSink, StderrHold, StderrSink, TelemetrySink, hold_stderr};
+pub use trace::{TRACE_ENV, init as init_tracing};
+
+pub(crate) const STATE_DIR: &str = "state";
+const EVENTS_FILE: &str = "events.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EditorLaunchStatus {
+    Success,
+    EditorMissing,
+    InvalidWorktreePath,
+    SpawnError,
+    PreferenceMissing,
+    ConfigurationError,
+}
+
+/// How an editor was started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EditorLaunchMethod {
+    /// As a process of its own.
+    Direct,
+    /// Through the URL handler of the operating system.
+    Url,
+    Tmux,
+    Zellij,
+    WindowsTerminal,
+    Kitty,
+    #[serde(rename = "wezterm")]
+    WezTerm,
+}
+
+/// A destination for events, as named in `telemetry.sinks`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkKind {
+    Jsonl,
+    Stderr,
+    Otlp,
+}
+
+/// Something worth counting in `rsworktree stats`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Event {
+    WorktreeCreated {
+        worktree: String,
+        branch: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base: Option<String>,
+    },
+    EditorLaunch {
+        worktree: String,
+        editor: String,
+        status: EditorLaunchStatus,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<PathBuf>,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        message: String,
+        /// The program started, as found on `PATH`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        program: Option<PathBuf>,
+        /// The first line the program printed for `--version`, for editors
+        /// that answer quickly.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        version: Option<String>,
+        /// Until the editor was started, or until it exited when waited for.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        duration_ms: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        method: Option<EditorLaunchMethod>,
+    },
+    /// Kept as `hook` on the wire so older event logs still load.
+    #[serde(rename = "hook")]
+    HookRun {
+        worktree: String,
+        hook: String,
+        success: bool,
+        duration_ms: u64,
+        /// The hook was killed after exceeding `hooks.timeout`.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        timed_out: bool,
+    },
+    SetupStep {
+        worktree: String,
+        step: String,
+        duration_ms: u64,
+    },
+    WorktreeUsed {
+        worktree: String,
+        command: String,
+    },
+    /// A call to the hosting provider's CLI, such as `gh pr create`.
+    ProviderCall {
+        provider: String,
+        command: String,
+        success: bool,
+        duration_ms: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventRecord {
+    /// Seconds since the Unix epoch.
+    pub at: u64,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+/// Records events to the configured sinks; with the default `jsonl` sink
+/// nothing leaves the machine. `telemetry = false` in `config.toml` turns
+/// recording off.
+#[derive(Debug, Clone)]
+pub struct EventStore {
+    rsworktree_dir: PathBuf,
+}
+
+impl EventStore {
+    pub fn new(rsworktree_dir: &Path) -> Self {
+        Self {
+            rsworktree_dir: rsworktree_dir.to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.rsworktree_dir.join(STATE_DIR).join(EVENTS_FILE)
+    }
+
+    /// Hands `event` to every configured sink. Recording is best effort:
+    /// failures never affect the command.
+    pub fn record(&self, event: Event) {
+        let Some(config) = self.enabled_config() else {
+            return;
+        };
+        let record = EventRecord { at: now(), event };
+        for sink in self.sinks(&config) {
+            let _ = sink.emit(&record);
+        }
+    }
+
+    /// Whether [`EventStore::record`] records anything, so that costly
+    /// details can be skipped otherwise.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled_config().is_some()
+    }
+
+    fn enabled_config(&self) -> Option<Config> {
+        // Only record for repositories that already use rsworktree.
+        if !self.rsworktree_dir.is_dir() {
+            return None;
+        }
+        let config = Config::load(&self.rsworktree_dir).unwrap_or_default();
+        config.telemetry_enabled().then_some(config)
+    }
+
+    fn sinks(&self, config: &Config) -> Vec<Box<dyn TelemetrySink>> {
+        let telemetry = config.telemetry.clone().unwrap_or_default();
+        telemetry
+            .sinks
+            .iter()
+            .filter_map(|kind| -> Option<Box<dyn TelemetrySink>> {
+                match kind {
+                    SinkKind::Jsonl => Some(Box::new(JsonlSink::new(self.path()))),
+                    SinkKind::Stderr => Some(Box::new(StderrSink)),
+                    #[cfg(feature = "otlp")]
+                    SinkKind::Otlp => telemetry.endpoint.as_deref().map(|endpoint| {
+                        Box::new(OtlpSink::new(endpoint)) as Box<dyn TelemetrySink>
+                    }),
+                    #[cfg(not(feature = "otlp"))]
+                    SinkKind::Otlp => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Runs `step`, recording how long it took under `name`.
+    pub fn time_step<T>(
+        &self,
+        worktree: &str,
+        name: &str,
+        step: impl FnOnce() -> color_eyre::Result<T>,
+    ) -> color_eyre::Result<T> {
+        let _span = tracing::info_span!("step", worktree, step = name).entered();
+        let started = Instant::now();
+        let result = step();
+        self.record(Event::SetupStep {
+            worktree: worktree.to_owned(),
+            step: name.to_owned(),
+            duration_ms: elapsed_ms(started),
+        });
+        result
+    }
+
+    /// All events written by the `jsonl` sink, oldest first; unreadable lines
+    /// are skipped.
+    pub fn load(&self) -> color_eyre::Result<Vec<EventRecord>> {
+        let path = self.path();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => {
+                return Err(eyre::eyre!("failed to read `{}`: {error}", path.display()));
+            }
+        };
+        Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+pub fn elapsed_ms(started: Instant) -> u64 {
+    u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn record_appends_events_that_load_reads_back() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let store = EventStore::new(dir.path());
+
+        store.record(Event::WorktreeUsed {
+            worktree: "feature".into(),
+            command: "cd".into(),
+        });
+        store.time_step("feature", "worktree-add", || Ok(()))?;
+
+        let events = store.load()?;
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].event,
+            Event::WorktreeUsed {
+                worktree: "feature".into(),
+                command: "cd".into(),
+            }
+        );
+        assert!(matches!(
+            &events[1].event,
+            Event::SetupStep { step, .. } if step == "worktree-add"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn record_skips_repositories_without_rsworktree_dir() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let store = EventStore::new(&dir.path().join(".rsworktree"));
+
+        store.record(Event::WorktreeUsed {
+            worktree: "feature".into(),
+            command: "cd".into(),
+        });
+
+        assert!(!store.path().exists());
+        assert!(store.load()?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn record_skips_when_telemetry_is_disabled() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        fs::write(
+            dir.path().join(crate::config::CONFIG_FILE),
+            "telemetry = false\n",
+        )?;
+        let store = EventStore::new(dir.path());
+
+        store.record(Event::WorktreeUsed {
+            worktree: "feature".into(),
+            command: "cd".into(),
+        });
+
+        assert!(!store.path().exists());
+        assert!(!store.is_enabled());
+
+        Ok(())
+    }
+
+    #[test]
+    fn record_uses_the_configured_sinks() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        fs::write(
+            dir.path().join(crate::config::CONFIG_FILE),
+            "[telemetry]\nsinks = [\"stderr\"]\n",
+        )?;
+        let store = EventStore::new(dir.path());
+
+        store.record(Event::WorktreeCreated {
+            worktree: "feature".into(),
+            branch: "feature".into(),
+            base: None,
+        });
+
+        assert!(!store.path().exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn hook_runs_keep_the_wire_name_of_older_logs() -> color_eyre::Result<()> {
+        let line = r#"{"at":1,"kind":"hook","worktree":"feature","hook":"post-create","success":true,"duration_ms":5}"#;
+        let record: EventRecord = serde_json::from_str(line)?;
+        assert!(matches!(record.event, Event::HookRun { success: true, .. }));
+        assert_eq!(serde_json::to_string(&record)?, line);
+
+        Ok(())
+    }
+
+    #[test]
+    fn editor_launches_of_older_logs_load_without_launch_details() -> color_eyre::Result<()> {
+        let line = r#"{"at":1,"kind":"editor-launch","worktree":"feature","editor":"code","status":"success"}"#;
+        let record: EventRecord = serde_json::from_str(line)?;
+        assert!(matches!(
+            record.event,
+            Event::EditorLaunch {
+                program: None,
+                version: None,
+                duration_ms: None,
+                method: None,
+                ..
+            }
+        ));
+        assert_eq!(serde_json::to_string(&record)?, line);
+
+        Ok(())
+    }
+}
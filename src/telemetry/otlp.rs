@@ -0,0 +1,264 @@
+//! OTLP/HTTP export of events as OpenTelemetry log records and, with the
+//! `telemetry-otlp` feature, of spans as traces, sent with `curl` like the
+//! webhooks.
+
+#[cfg(feature = "telemetry-otlp")]
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, RandomState},
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use serde_json::{Map, Value, json};
+
+#[cfg(feature = "telemetry-otlp")]
+use super::trace::SpanRecord;
+use super::{EventRecord, TelemetrySink};
+use crate::webhook;
+
+const SERVICE_NAME: &str = "rsworktree";
+
+/// Posts every event to `{endpoint}/v1/logs`.
+#[derive(Debug, Clone)]
+pub struct OtlpSink {
+    endpoint: String,
+}
+
+impl OtlpSink {
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_owned(),
+        }
+    }
+
+    fn logs_url(&self) -> String {
+        format!("{}/v1/logs", self.endpoint)
+    }
+}
+
+impl TelemetrySink for OtlpSink {
+    fn emit(&self, record: &EventRecord) -> color_eyre::Result<()> {
+        webhook::deliver(&self.logs_url(), &payload(record)?.to_string())
+            .wrap_err("failed to export the event")
+    }
+}
+
+/// The OTLP/JSON `ExportLogsServiceRequest` for `record`.
+fn payload(record: &EventRecord) -> color_eyre::Result<Value> {
+    let Value::Object(mut fields) =
+        serde_json::to_value(record).wrap_err("failed to serialize event")?
+    else {
+        return Err(eyre::eyre!("events serialize to JSON objects"));
+    };
+    fields.remove("at");
+    let kind = fields
+        .remove("kind")
+        .and_then(|kind| kind.as_str().map(str::to_owned))
+        .unwrap_or_default();
+    let attributes = fields
+        .into_iter()
+        .map(|(key, value)| json!({ "key": key, "value": any_value(value) }))
+        .collect::<Vec<_>>();
+    let nanos = u128::from(record.at) * 1_000_000_000;
+
+    Ok(json!({
+        "resourceLogs": [{
+            "resource": {
+                "attributes": [
+                    { "key": "service.name", "value": { "stringValue": SERVICE_NAME } }
+                ]
+            },
+            "scopeLogs": [{
+                "scope": { "name": SERVICE_NAME },
+                "logRecords": [{
+                    "timeUnixNano": nanos.to_string(),
+                    "body": { "stringValue": kind },
+                    "attributes": attributes
+                }]
+            }]
+        }]
+    }))
+}
+
+/// Collects ended spans and posts them to `{endpoint}/v1/traces` in one request.
+#[cfg(feature = "telemetry-otlp")]
+#[derive(Debug)]
+pub(super) struct TraceExporter {
+    endpoint: String,
+    /// Random per process, so the spans of one command form one trace.
+    trace_id: String,
+    state: Mutex<TraceState>,
+}
+
+#[cfg(feature = "telemetry-otlp")]
+#[derive(Debug, Default)]
+struct TraceState {
+    spans: Vec<Value>,
+    /// OTLP span ids of the open spans, by `tracing` id. `tracing` reuses the
+    /// ids of ended spans, OTLP span ids must be unique within the trace.
+    ids: HashMap<u64, u64>,
+    next_id: u64,
+}
+
+#[cfg(feature = "telemetry-otlp")]
+impl TraceState {
+    fn span_id(&mut self, id: u64) -> u64 {
+        let next_id = &mut self.next_id;
+        *self.ids.entry(id).or_insert_with(|| {
+            *next_id += 1;
+            *next_id
+        })
+    }
+}
+
+#[cfg(feature = "telemetry-otlp")]
+impl TraceExporter {
+    pub(super) fn new(endpoint: &str) -> Self {
+        let random = RandomState::new();
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_owned(),
+            trace_id: format!("{:016x}{:016x}", random.hash_one(1), random.hash_one(2)),
+            state: Mutex::default(),
+        }
+    }
+
+    fn traces_url(&self) -> String {
+        format!("{}/v1/traces", self.endpoint)
+    }
+
+    /// Adds the span `id` that ran for `elapsed` below `parent`.
+    pub(super) fn push(&self, id: u64, parent: Option<u64>, record: SpanRecord, elapsed: Duration) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let span_id = state.span_id(id);
+        state.ids.remove(&id);
+        let mut span = json!({
+            "traceId": self.trace_id,
+            "spanId": format!("{span_id:016x}"),
+            "name": record.name,
+            // SPAN_KIND_INTERNAL
+            "kind": 1,
+            "startTimeUnixNano": record.start_time.to_string(),
+            "endTimeUnixNano": (record.start_time + elapsed.as_nanos()).to_string(),
+            "attributes": record
+                .fields
+                .into_iter()
+                .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+                .collect::<Vec<_>>(),
+        });
+        if let Some(parent) = parent {
+            span["parentSpanId"] = Value::from(format!("{:016x}", state.span_id(parent)));
+        }
+        state.spans.push(span);
+    }
+
+    /// Sends the collected spans. Export is best effort, like the event sinks.
+    pub(super) fn flush(&self) {
+        let spans = std::mem::take(
+            &mut self
+                .state
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .spans,
+        );
+        if spans.is_empty() {
+            return;
+        }
+        let _ = webhook::deliver(&self.traces_url(), &trace_payload(spans).to_string());
+    }
+}
+
+/// The OTLP/JSON `ExportTraceServiceRequest` for `spans`.
+#[cfg(feature = "telemetry-otlp")]
+fn trace_payload(spans: Vec<Value>) -> Value {
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [
+                    { "key": "service.name", "value": { "stringValue": SERVICE_NAME } }
+                ]
+            },
+            "scopeSpans": [{
+                "scope": { "name": SERVICE_NAME },
+                "spans": spans
+            }]
+        }]
+    })
+}
+
+fn any_value(value: Value) -> Value {
+    let mut any = Map::new();
+    match value {
+        Value::Bool(flag) => any.insert("boolValue".into(), Value::Bool(flag)),
+        // OTLP/JSON encodes 64-bit integers as strings.
+        Value::Number(number) if number.is_u64() || number.is_i64() => {
+            any.insert("intValue".into(), Value::String(number.to_string()))
+        }
+        Value::Number(number) => any.insert("doubleValue".into(), Value::Number(number)),
+        Value::String(text) => any.insert("stringValue".into(), Value::String(text)),
+        other => any.insert("stringValue".into(), Value::String(other.to_string())),
+    };
+    Value::Object(any)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::telemetry::Event;
+
+    #[test]
+    fn payload_is_an_otlp_log_record() -> color_eyre::Result<()> {
+        let record = EventRecord {
+            at: 2,
+            event: Event::ProviderCall {
+                provider: "gh".into(),
+                command: "pr create".into(),
+                success: true,
+                duration_ms: 120,
+            },
+        };
+
+        let payload = payload(&record)?;
+        let log = &payload["resourceLogs"][0]["scopeLogs"][0]["logRecords"][0];
+        assert_eq!(log["timeUnixNano"], "2000000000");
+        assert_eq!(log["body"]["stringValue"], "provider-call");
+        assert!(log["attributes"].as_array().is_some_and(|attributes| {
+            attributes.contains(&json!({ "key": "duration_ms", "value": { "intValue": "120" } }))
+                && attributes.contains(&json!({ "key": "success", "value": { "boolValue": true } }))
+        }));
+        assert_eq!(
+            OtlpSink::new("http://localhost:4318/").logs_url(),
+            "http://localhost:4318/v1/logs"
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "telemetry-otlp")]
+    #[test]
+    fn spans_get_unique_ids_and_their_parent() {
+        let exporter = TraceExporter::new("http://localhost:4318/");
+        let record = |name| SpanRecord {
+            name,
+            fields: vec![("command", "git fetch".into())],
+            started: std::time::Instant::now(),
+            start_time: 1_000,
+        };
+        exporter.push(2, Some(1), record("run"), Duration::from_nanos(5));
+        // `tracing` hands out the ended span's id again.
+        exporter.push(2, Some(1), record("run"), Duration::from_nanos(5));
+        exporter.push(1, None, record("rsworktree"), Duration::from_nanos(50));
+
+        let state = exporter.state.lock().expect("lock");
+        let [first, second, root] = state.spans.as_slice() else {
+            panic!("expected three spans, got {:?}", state.spans);
+        };
+        assert_ne!(first["spanId"], second["spanId"]);
+        assert_eq!(first["parentSpanId"], root["spanId"]);
+        assert!(root.get("parentSpanId").is_none());
+        assert_eq!(first["endTimeUnixNano"], "1005");
+        assert_eq!(first["traceId"].as_str().map(str::len), Some(32));
+        assert_eq!(exporter.traces_url(), "http://localhost:4318/v1/traces");
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use serde_json::Value;
+
+use super::EventRecord;
+
+/// Once the event log grows past this size, only the newer half is kept.
+const MAX_EVENTS_BYTES: u64 = 1024 * 1024;
+
+/// Lines held back by [`hold_stderr`]; `None` while nothing holds them.
+static HELD_LINES: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+/// Somewhere recorded events go.
+pub trait TelemetrySink {
+    fn emit(&self, record: &EventRecord) -> color_eyre::Result<()>;
+}
+
+/// Appends events as JSON lines, halving the file once it grows too large.
+#[derive(Debug, Clone)]
+pub struct JsonlSink {
+    path: PathBuf,
+}
+
+impl JsonlSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn truncate(&self) -> color_eyre::Result<()> {
+        let path = &self.path;
+        let contents = fs::read_to_string(path)
+            .wrap_err_with(|| eyre::eyre!("failed to read `{}`", path.display()))?;
+        let lines = contents.lines().collect::<Vec<_>>();
+        let kept = lines[lines.len() / 2..].join("\n") + "\n";
+        fs::write(path, kept).wrap_err_with(|| eyre::eyre!("failed to write `{}`", path.display()))
+    }
+}
+
+impl TelemetrySink for JsonlSink {
+    fn emit(&self, record: &EventRecord) -> color_eyre::Result<()> {
+        let path = &self.path;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| eyre::eyre!("failed to create `{}`", parent.display()))?;
+        }
+        if fs::metadata(path).is_ok_and(|metadata| metadata.len() > MAX_EVENTS_BYTES) {
+            self.truncate()?;
+        }
+
+        let mut line = serde_json::to_string(record).wrap_err("failed to serialize event")?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .wrap_err_with(|| eyre::eyre!("failed to write `{}`", path.display()))
+    }
+}
+
+/// Prints every event as `[kind] key=value ...` on stderr.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StderrSink;
+
+impl TelemetrySink for StderrSink {
+    fn emit(&self, record: &EventRecord) -> color_eyre::Result<()> {
+        print_line(format_line(record)?);
+        Ok(())
+    }
+}
+
+/// Prints `line` on stderr, unless [`hold_stderr`] holds it back.
+pub(super) fn print_line(line: String) {
+    let mut held = HELD_LINES.lock().unwrap_or_else(PoisonError::into_inner);
+    match held.as_mut() {
+        Some(lines) => lines.push(line),
+        None => eprintln!("{line}"),
+    }
+}
+
+/// Keeps [`StderrSink`] output back until dropped, so it does not draw over a
+/// full-screen interface.
+#[must_use]
+pub struct StderrHold {
+    _private: (),
+}
+
+pub fn hold_stderr() -> StderrHold {
+    HELD_LINES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get_or_insert_with(Vec::new);
+    StderrHold { _private: () }
+}
+
+impl Drop for StderrHold {
+    fn drop(&mut self) {
+        let lines = HELD_LINES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .unwrap_or_default();
+        for line in lines {
+            eprintln!("{line}");
+        }
+    }
+}
+
+fn format_line(record: &EventRecord) -> color_eyre::Result<String> {
+    let Value::Object(mut fields) =
+        serde_json::to_value(record).wrap_err("failed to serialize event")?
+    else {
+        return Err(eyre::eyre!("events serialize to JSON objects"));
+    };
+    fields.remove("at");
+    let kind = match fields.remove("kind") {
+        Some(Value::String(kind)) => kind,
+        _ => String::from("event"),
+    };
+    let pairs = fields
+        .into_iter()
+        .map(|(key, value)| match value {
+            Value::String(text) => format!("{key}={text}"),
+            other => format!("{key}={other}"),
+        })
+        .collect::<Vec<_>>();
+    Ok(format!("[{kind}] {}", pairs.join(" ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    use crate::telemetry::{EditorLaunchMethod, EditorLaunchStatus, Event};
+
+    #[test]
+    fn stderr_lines_list_the_event_fields() -> color_eyre::Result<()> {
+        let record = EventRecord {
+            at: 1,
+            event: Event::EditorLaunch {
+                worktree: "feature".into(),
+                editor: "code".into(),
+                status: EditorLaunchStatus::SpawnError,
+                path: None,
+                message: "not found".into(),
+                program: Some("/usr/bin/code".into()),
+                version: Some("1.96.2".into()),
+                duration_ms: Some(12),
+                method: Some(EditorLaunchMethod::Tmux),
+            },
+        };
+        assert_eq!(
+            format_line(&record)?,
+            "[editor-launch] duration_ms=12 editor=code message=not found method=tmux program=/usr/bin/code status=spawn-error version=1.96.2 worktree=feature"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn jsonl_sink_keeps_the_newer_half_of_a_full_log() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("state").join("events.jsonl");
+        let sink = JsonlSink::new(path.clone());
+        let record = EventRecord {
+            at: 1,
+            event: Event::WorktreeUsed {
+                worktree: "feature".into(),
+                command: "cd".into(),
+            },
+        };
+
+        sink.emit(&record)?;
+        let line = fs::read_to_string(&path)?;
+        let lines = usize::try_from(MAX_EVENTS_BYTES)? / line.len() + 1;
+        fs::write(&path, line.repeat(lines))?;
+        sink.emit(&record)?;
+
+        let kept = fs::read_to_string(&path)?.lines().count();
+        assert_eq!(kept, lines - lines / 2 + 1);
+        Ok(())
+    }
+}
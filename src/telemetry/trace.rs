@@ -0,0 +1,206 @@
+//! `tracing` spans around what makes a command slow: git and provider CLI
+//! calls, hooks and editor launches, nested in one span for the command.
+//!
+//! `RSWORKTREE_TRACE=1` prints every span with its duration on stderr once it
+//! ends, indented below the span it ran in:
+//!
+//! ```text
+//! [trace]     run command="git worktree add ..." 412ms
+//! [trace]   hook hook=post-create worktree=feature 88.2s
+//! [trace] rsworktree command=create 90.1s
+//! ```
+//!
+//! With the `telemetry-otlp` cargo feature, `OTEL_EXPORTER_OTLP_ENDPOINT`
+//! sends the spans to `{endpoint}/v1/traces` once the command has finished.
+
+use std::{
+    env,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use tracing::{
+    Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+};
+use tracing_error::ErrorLayer;
+use tracing_subscriber::{
+    Layer,
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+};
+
+#[cfg(feature = "telemetry-otlp")]
+use super::otlp::TraceExporter;
+use super::sinks::print_line;
+
+/// Prints spans on stderr when set to `1` or `true`.
+pub const TRACE_ENV: &str = "RSWORKTREE_TRACE";
+/// Base URL of the OTLP/HTTP collector spans are exported to.
+#[cfg(feature = "telemetry-otlp")]
+pub const OTLP_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Installs the span output selected by the environment. Without any, spans
+/// are never recorded.
+pub fn init() {
+    let pretty = env::var(TRACE_ENV).is_ok_and(|value| matches!(value.trim(), "1" | "true"));
+    let layer = TraceLayer {
+        pretty,
+        #[cfg(feature = "telemetry-otlp")]
+        exporter: env::var(OTLP_ENDPOINT_ENV)
+            .ok()
+            .filter(|endpoint| !endpoint.trim().is_empty())
+            .map(|endpoint| TraceExporter::new(&endpoint)),
+    };
+    if !layer.is_enabled() {
+        return;
+    }
+    // The error layer lets error reports list the spans they happened in.
+    let subscriber = tracing_subscriber::registry()
+        .with(layer)
+        .with(ErrorLayer::default());
+    // Fails only when a subscriber is installed already, e.g. by an embedder.
+    let _ = tracing::subscriber::set_global_default(subscriber);
+}
+
+/// What is kept of a span until it ends.
+#[derive(Debug)]
+pub(super) struct SpanRecord {
+    pub(super) name: &'static str,
+    pub(super) fields: Vec<(&'static str, String)>,
+    pub(super) started: Instant,
+    /// Nanoseconds since the Unix epoch, for the OTLP export.
+    #[cfg_attr(not(feature = "telemetry-otlp"), allow(dead_code))]
+    pub(super) start_time: u128,
+}
+
+impl SpanRecord {
+    fn new(attributes: &Attributes<'_>) -> Self {
+        let mut record = Self {
+            name: attributes.metadata().name(),
+            fields: Vec::new(),
+            started: Instant::now(),
+            start_time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_nanos())
+                .unwrap_or_default(),
+        };
+        attributes.record(&mut record);
+        record
+    }
+
+    /// `[trace] <indent><name> key=value ... <duration>`.
+    fn format_line(&self, depth: usize, elapsed: Duration) -> String {
+        let mut line = format!("[trace] {}{}", "  ".repeat(depth), self.name);
+        for (key, value) in &self.fields {
+            if value.contains(char::is_whitespace) {
+                line.push_str(&format!(" {key}={value:?}"));
+            } else {
+                line.push_str(&format!(" {key}={value}"));
+            }
+        }
+        line.push(' ');
+        line.push_str(&format_duration(elapsed));
+        line
+    }
+}
+
+impl Visit for SpanRecord {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields.push((field.name(), value.to_owned()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.fields.push((field.name(), format!("{value:?}")));
+    }
+}
+
+struct TraceLayer {
+    pretty: bool,
+    #[cfg(feature = "telemetry-otlp")]
+    exporter: Option<TraceExporter>,
+}
+
+impl TraceLayer {
+    fn is_enabled(&self) -> bool {
+        #[cfg(feature = "telemetry-otlp")]
+        if self.exporter.is_some() {
+            return true;
+        }
+        self.pretty
+    }
+}
+
+impl<S> Layer<S> for TraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attributes: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanRecord::new(attributes));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(record) = span.extensions_mut().get_mut::<SpanRecord>()
+        {
+            values.record(record);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(record) = span.extensions_mut().remove::<SpanRecord>() else {
+            return;
+        };
+        let elapsed = record.started.elapsed();
+        if self.pretty {
+            let depth = span.scope().skip(1).count();
+            print_line(record.format_line(depth, elapsed));
+        }
+        #[cfg(feature = "telemetry-otlp")]
+        if let Some(exporter) = &self.exporter {
+            let parent = span.parent().map(|parent| parent.id().into_u64());
+            exporter.push(id.into_u64(), parent, record, elapsed);
+            // The command's span ends last; send everything at once.
+            if parent.is_none() {
+                exporter.flush();
+            }
+        }
+    }
+}
+
+/// `412ms` below a second, `90.1s` above.
+fn format_duration(elapsed: Duration) -> String {
+    if elapsed < Duration::from_secs(1) {
+        format!("{}ms", elapsed.as_millis())
+    } else {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_show_depth_fields_and_duration() {
+        let record = SpanRecord {
+            name: "run",
+            fields: vec![
+                ("command", "git fetch --all".into()),
+                ("worktree", "feature".into()),
+            ],
+            started: Instant::now(),
+            start_time: 0,
+        };
+        assert_eq!(
+            record.format_line(2, Duration::from_millis(412)),
+            "[trace]     run command=\"git fetch --all\" worktree=feature 412ms"
+        );
+        assert_eq!(format_duration(Duration::from_millis(90_140)), "90.1s");
+    }
+}
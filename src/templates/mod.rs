@@ -0,0 +1,240 @@
+//! Worktree templates: `.rsworktree/templates/<name>.toml` files bundling the
+//! base branch, name prefix, files to copy, environment and hooks for a kind
+//! of worktree, applied with `rsworktree create --template <name>`.
+//!
+//! ```toml
+//! base = "release/2.x"
+//! prefix = "bugfix/"
+//! copy = [".env", "config/*.local.yml"]
+//!
+//! [env]
+//! DATABASE_URL = "postgres://localhost/app_{name}"
+//!
+//! [hooks]
+//! post-create = ["npm ci"]
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use serde::Deserialize;
+
+use crate::hooks::{HookContext, HookRunner, InlineHooks};
+
+pub const TEMPLATES_DIR: &str = "templates";
+const TEMPLATE_EXTENSION: &str = "toml";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    name: String,
+    settings: TemplateSettings,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateSettings {
+    /// Branch new worktrees start from unless `--base` is given.
+    #[serde(default)]
+    base: Option<String>,
+    /// Prepended to the worktree name, e.g. `bugfix/`.
+    #[serde(default)]
+    prefix: Option<String>,
+    /// Globs copied from the main worktree, like `worktree.copy`.
+    #[serde(default)]
+    copy: Vec<String>,
+    /// Variables exported to hooks; `{name}`, `{branch}` and `{base}` are substituted.
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    /// Commands run after the configured hooks.
+    #[serde(default)]
+    hooks: InlineHooks,
+}
+
+impl Template {
+    /// Reads `templates/<name>.toml` from `rsworktree_dir`.
+    pub fn load(rsworktree_dir: &Path, name: &str) -> color_eyre::Result<Self> {
+        let path = template_path(rsworktree_dir, name);
+        if !path.is_file() {
+            let available = available(rsworktree_dir);
+            return Err(if available.is_empty() {
+                eyre::eyre!(
+                    "template `{name}` not found; create `{}` to define it",
+                    path.display()
+                )
+            } else {
+                eyre::eyre!(
+                    "template `{name}` not found; available templates: {}",
+                    available.join(", ")
+                )
+            });
+        }
+
+        let contents = fs::read_to_string(&path)
+            .wrap_err_with(|| eyre::eyre!("failed to read `{}`", path.display()))?;
+        Self::parse(name, &contents)
+            .wrap_err_with(|| eyre::eyre!("failed to parse `{}`", path.display()))
+    }
+
+    fn parse(name: &str, contents: &str) -> color_eyre::Result<Self> {
+        let settings = toml::from_str(contents)?;
+        Ok(Self {
+            name: name.to_owned(),
+            settings,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn base(&self) -> Option<&str> {
+        self.settings.base.as_deref()
+    }
+
+    pub fn copy_patterns(&self) -> &[String] {
+        &self.settings.copy
+    }
+
+    /// `name` with the template prefix, unless it already starts with it.
+    pub fn worktree_name(&self, name: &str) -> String {
+        match self.settings.prefix.as_deref() {
+            Some(prefix) if !name.starts_with(prefix) => format!("{prefix}{name}"),
+            _ => name.to_owned(),
+        }
+    }
+
+    /// Adds the template's hook commands and environment to `runner`.
+    pub fn apply_to_hooks(&self, runner: HookRunner, context: &HookContext) -> HookRunner {
+        let env = self
+            .settings
+            .env
+            .iter()
+            .map(|(key, value)| (key.clone(), substitute(value, context)))
+            .collect();
+        runner
+            .with_inline_hooks(self.settings.hooks.clone())
+            .with_env(env)
+    }
+}
+
+fn template_path(rsworktree_dir: &Path, name: &str) -> PathBuf {
+    rsworktree_dir
+        .join(TEMPLATES_DIR)
+        .join(format!("{name}.{TEMPLATE_EXTENSION}"))
+}
+
+/// Names of the templates in `rsworktree_dir`, sorted.
+pub fn available(rsworktree_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(rsworktree_dir.join(TEMPLATES_DIR)) else {
+        return Vec::new();
+    };
+    let mut names = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == TEMPLATE_EXTENSION)
+        })
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_owned()))
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+fn substitute(value: &str, context: &HookContext) -> String {
+    value
+        .replace("{name}", &context.worktree_name)
+        .replace("{branch}", &context.branch)
+        .replace("{base}", context.base_branch.as_deref().unwrap_or(""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::hooks::HookName;
+
+    use tempfile::TempDir;
+
+    fn write_template(dir: &Path, name: &str, contents: &str) -> color_eyre::Result<()> {
+        let templates_dir = dir.join(TEMPLATES_DIR);
+        fs::create_dir_all(&templates_dir)?;
+        fs::write(templates_dir.join(format!("{name}.toml")), contents)?;
+        Ok(())
+    }
+
+    #[test]
+    fn load_reads_template_settings() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        write_template(
+            dir.path(),
+            "backend-bugfix",
+            r#"
+base = "release/2.x"
+prefix = "bugfix/"
+copy = [".env"]
+
+[env]
+DATABASE_URL = "postgres://localhost/app_{name}"
+
+[hooks]
+post-create = ["make setup"]
+"#,
+        )?;
+
+        let template = Template::load(dir.path(), "backend-bugfix")?;
+
+        assert_eq!(template.name(), "backend-bugfix");
+        assert_eq!(template.base(), Some("release/2.x"));
+        assert_eq!(template.copy_patterns(), [".env"]);
+        assert_eq!(template.worktree_name("login"), "bugfix/login");
+        assert_eq!(template.worktree_name("bugfix/login"), "bugfix/login");
+        assert_eq!(
+            template.settings.hooks.commands(HookName::PostCreate),
+            ["make setup"]
+        );
+
+        let context = HookContext {
+            worktree_name: "bugfix/login".into(),
+            worktree_path: dir.path().join("bugfix/login"),
+            branch: "bugfix/login".into(),
+            base_branch: Some("release/2.x".into()),
+            base_path: dir.path().to_path_buf(),
+        };
+        assert_eq!(
+            substitute(&template.settings.env["DATABASE_URL"], &context),
+            "postgres://localhost/app_bugfix/login"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn load_rejects_unknown_keys() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        write_template(dir.path(), "typo", "bsae = \"main\"\n")?;
+
+        let error = Template::load(dir.path(), "typo").unwrap_err();
+
+        assert!(format!("{error:#}").contains("unknown field `bsae`"));
+        Ok(())
+    }
+
+    #[test]
+    fn load_lists_available_templates_when_missing() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        write_template(dir.path(), "frontend", "")?;
+        write_template(dir.path(), "backend", "")?;
+
+        let error = Template::load(dir.path(), "mobile").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "template `mobile` not found; available templates: backend, frontend"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+//! HTTP notifications for worktree lifecycle events.
+//!
+//! Configured under `webhook` in `preferences.json`. Every selected event is
+//! POSTed as JSON to the configured URL with `curl`; delivery problems are
+//! reported as warnings and never fail the command that raised the event.
+
+use std::{
+    env, fs,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{editor::CONFIG_FILE_NAME, output};
+
+/// Environment variable overriding the `curl` executable used for delivery.
+pub const CURL_ENV: &str = "RSWORKTREE_CURL";
+
+const DELIVERY_TIMEOUT_SECS: &str = "10";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WebhookEvent {
+    Create,
+    Remove,
+    Merge,
+    HookFailure,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::Create => "create",
+            WebhookEvent::Remove => "remove",
+            WebhookEvent::Merge => "merge",
+            WebhookEvent::HookFailure => "hook-failure",
+        }
+    }
+}
+
+/// The JSON document sent for an event when no template is configured.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub repository: String,
+    pub worktree: String,
+    pub branch: Option<String>,
+    pub message: String,
+    /// Seconds since the Unix epoch at which the event happened.
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FileFormat {
+    #[serde(default)]
+    webhook: Option<WebhookConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct WebhookConfig {
+    url: String,
+    /// Events to send; all events when empty.
+    #[serde(default)]
+    events: Vec<WebhookEvent>,
+    /// JSON document sent instead of the default payload. `{event}`,
+    /// `{repository}`, `{worktree}`, `{branch}`, `{message}` and `{timestamp}`
+    /// are replaced inside its strings.
+    #[serde(default)]
+    template: Option<Value>,
+}
+
+pub struct WebhookNotifier {
+    config: Option<WebhookConfig>,
+    repository: String,
+}
+
+impl WebhookNotifier {
+    /// Creates a notifier for `rsworktree_dir`, reading `webhook` from
+    /// `preferences.json`. `repository` is the name sent in the payload.
+    pub fn new(rsworktree_dir: &Path, repository: &str) -> Self {
+        let config = fs::read_to_string(rsworktree_dir.join(CONFIG_FILE_NAME))
+            .ok()
+            .and_then(|text| serde_json::from_str::<FileFormat>(&text).ok())
+            .and_then(|parsed| parsed.webhook);
+
+        Self {
+            config,
+            repository: repository.to_owned(),
+        }
+    }
+
+    pub fn is_enabled_for(&self, event: WebhookEvent) -> bool {
+        self.config
+            .as_ref()
+            .is_some_and(|config| config.events.is_empty() || config.events.contains(&event))
+    }
+
+    /// Sends `event` if it is configured, warning instead of failing on delivery errors.
+    pub fn notify(
+        &self,
+        event: WebhookEvent,
+        worktree: &str,
+        branch: Option<&str>,
+        message: impl Into<String>,
+    ) {
+        if !self.is_enabled_for(event) {
+            return;
+        }
+        let Some(config) = &self.config else {
+            return;
+        };
+
+        let payload = WebhookPayload {
+            event,
+            repository: self.repository.clone(),
+            worktree: worktree.to_owned(),
+            branch: branch.map(str::to_owned),
+            message: message.into(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+        };
+
+        if let Err(error) = deliver(&config.url, &render_body(config, &payload)) {
+            output::warn(tr!(
+                "failed to send `{}` webhook: {}",
+                event.as_str(),
+                error
+            ));
+        }
+    }
+}
+
+fn render_body(config: &WebhookConfig, payload: &WebhookPayload) -> String {
+    let document = match &config.template {
+        Some(template) => fill_template(template, payload),
+        None => serde_json::to_value(payload).unwrap_or(Value::Null),
+    };
+    document.to_string()
+}
+
+fn fill_template(template: &Value, payload: &WebhookPayload) -> Value {
+    match template {
+        Value::String(text) => Value::String(
+            text.replace("{event}", payload.event.as_str())
+                .replace("{repository}", &payload.repository)
+                .replace("{worktree}", &payload.worktree)
+                .replace("{branch}", payload.branch.as_deref().unwrap_or(""))
+                .replace("{message}", &payload.message)
+                .replace("{timestamp}", &payload.timestamp.to_string()),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| fill_template(item, payload))
+                .collect(),
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), fill_template(value, payload)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// POSTs the JSON `body` to `url` with `curl`.
+pub(crate) fn deliver(url: &str, body: &str) -> color_eyre::Result<()> {
+    let program = env::var(CURL_ENV).unwrap_or_else(|_| "curl".to_owned());
+    let mut child = Command::new(&program)
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--max-time",
+            DELIVERY_TIMEOUT_SECS,
+            "--request",
+            "POST",
+            "--header",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+            url,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .wrap_err_with(|| eyre::eyre!("failed to run `{program}`"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(body.as_bytes())
+            .wrap_err("failed to write the request body")?;
+    }
+
+    let result = child
+        .wait_with_output()
+        .wrap_err_with(|| eyre::eyre!("failed to wait for `{program}`"))?;
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        return Err(eyre::eyre!(
+            "`{program}` exited with {}: {}",
+            result.status,
+            stderr.trim()
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn payload() -> WebhookPayload {
+        WebhookPayload {
+            event: WebhookEvent::Create,
+            repository: "project".into(),
+            worktree: "feature/x".into(),
+            branch: Some("feature/x".into()),
+            message: "Created \"feature/x\"".into(),
+            timestamp: 1_700_000_000,
+        }
+    }
+
+    fn config(events: Vec<WebhookEvent>, template: Option<Value>) -> WebhookConfig {
+        WebhookConfig {
+            url: "https://hooks.example.test".into(),
+            events,
+            template,
+        }
+    }
+
+    #[test]
+    fn default_body_is_the_serialized_payload() -> color_eyre::Result<()> {
+        let body = render_body(&config(Vec::new(), None), &payload());
+        let parsed: Value = serde_json::from_str(&body)?;
+
+        assert_eq!(parsed["event"], "create");
+        assert_eq!(parsed["repository"], "project");
+        assert_eq!(parsed["branch"], "feature/x");
+        assert_eq!(parsed["timestamp"], 1_700_000_000);
+        Ok(())
+    }
+
+    #[test]
+    fn template_strings_are_filled_and_stay_valid_json() -> color_eyre::Result<()> {
+        let template = json!({
+            "text": "{event} in {repository}: {message}",
+            "blocks": [{"type": "section", "id": "{worktree}"}],
+            "unfurl_links": false
+        });
+        let body = render_body(&config(Vec::new(), Some(template)), &payload());
+        let parsed: Value = serde_json::from_str(&body)?;
+
+        assert_eq!(parsed["text"], "create in project: Created \"feature/x\"");
+        assert_eq!(parsed["blocks"][0]["id"], "feature/x");
+        assert_eq!(parsed["unfurl_links"], false);
+        Ok(())
+    }
+
+    #[test]
+    fn notifier_reads_config_and_filters_events() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let rsworktree_dir = dir.path().join("project/.rsworktree");
+        fs::create_dir_all(&rsworktree_dir)?;
+
+        let notifier = WebhookNotifier::new(&rsworktree_dir, "project");
+        assert!(!notifier.is_enabled_for(WebhookEvent::Create));
+
+        fs::write(
+            rsworktree_dir.join(CONFIG_FILE_NAME),
+            r#"{"webhook": {"url": "https://hooks.example.test", "events": ["merge", "hook-failure"]}}"#,
+        )?;
+        let notifier = WebhookNotifier::new(&rsworktree_dir, "project");
+        assert_eq!(notifier.repository, "project");
+        assert!(notifier.is_enabled_for(WebhookEvent::Merge));
+        assert!(notifier.is_enabled_for(WebhookEvent::HookFailure));
+        assert!(!notifier.is_enabled_for(WebhookEvent::Create));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,360 @@
+//! Per-worktree git config (`extensions.worktreeConfig`) and excludes.
+//!
+//! Git reads `info/exclude` from the common directory, so it is shared by every
+//! worktree. rsworktree gives a worktree its own exclude file by pointing
+//! `core.excludesFile` at `<worktree git dir>/info/exclude` in the worktree's
+//! `config.worktree`. Because that replaces the user's global excludes file
+//! for the worktree, the global patterns are copied into the new file first.
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use git2::{ConfigLevel, Repository};
+use serde::Deserialize;
+
+use super::submodules::SubmoduleMode;
+use crate::editor::CONFIG_FILE_NAME;
+
+const EXTENSION_KEY: &str = "extensions.worktreeConfig";
+const EXCLUDES_MARKER: &str = "# rsworktree: per-worktree excludes";
+
+#[derive(Debug, Default, Deserialize)]
+struct FileFormat {
+    #[serde(default)]
+    worktree: Option<WorktreeDefaults>,
+}
+
+/// `worktree` in `preferences.json`: applied to every worktree `create` adds.
+#[derive(Debug, Default, Deserialize)]
+struct WorktreeDefaults {
+    /// Git config set with `git config --worktree`.
+    #[serde(default)]
+    config: BTreeMap<String, String>,
+    /// Patterns added to the worktree's own exclude file.
+    #[serde(default)]
+    excludes: Vec<String>,
+    /// Glob patterns of untracked files copied from the main worktree.
+    #[serde(default)]
+    copy: Vec<String>,
+    /// Directories a sparse checkout is limited to; empty checks out everything.
+    #[serde(default)]
+    sparse: Vec<String>,
+    /// How submodules are initialized.
+    #[serde(default)]
+    submodules: SubmoduleMode,
+}
+
+/// Whether `extensions.worktreeConfig` is enabled for the repository.
+pub(crate) fn extension_enabled(repo: &Repository) -> bool {
+    repo.config()
+        .and_then(|config| config.get_bool(EXTENSION_KEY))
+        .unwrap_or(false)
+}
+
+/// Enables `extensions.worktreeConfig`, returning `true` when it was off.
+///
+/// Repository extensions are only honoured from format version 1, so the
+/// version is raised as well when needed.
+pub(crate) fn ensure_extension(repo: &Repository) -> color_eyre::Result<bool> {
+    if extension_enabled(repo) {
+        return Ok(false);
+    }
+
+    let mut config = repo
+        .config()
+        .and_then(|config| config.open_level(ConfigLevel::Local))
+        .wrap_err("failed to open the repository config")?;
+    let version = config
+        .get_i32("core.repositoryformatversion")
+        .unwrap_or_default();
+    if version < 1 {
+        config
+            .set_i32("core.repositoryformatversion", 1)
+            .wrap_err("failed to raise `core.repositoryformatversion`")?;
+    }
+    config
+        .set_bool(EXTENSION_KEY, true)
+        .wrap_err_with(|| eyre::eyre!("failed to enable `{EXTENSION_KEY}`"))?;
+    Ok(true)
+}
+
+/// Sets `key` in the worktree's `config.worktree`.
+pub(crate) fn set_worktree_config(
+    worktree_path: &Path,
+    key: &str,
+    value: &str,
+) -> color_eyre::Result<()> {
+    let output = Command::new("git")
+        .args(["config", "--worktree", key, value])
+        .current_dir(worktree_path)
+        .output()
+        .wrap_err("failed to run `git config --worktree`")?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(eyre::eyre!(
+        "failed to set `{key}` for `{}`: {}",
+        worktree_path.display(),
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+/// Path of the worktree's own exclude file inside its git directory.
+pub(crate) fn exclude_file(worktree_path: &Path) -> color_eyre::Result<PathBuf> {
+    let worktree_repo = Repository::open(worktree_path).wrap_err_with(|| {
+        eyre::eyre!("failed to open worktree at `{}`", worktree_path.display())
+    })?;
+    Ok(worktree_repo.path().join("info").join("exclude"))
+}
+
+/// Patterns rsworktree added to the worktree's own exclude file.
+pub(crate) fn list_excludes(worktree_path: &Path) -> color_eyre::Result<Vec<String>> {
+    let path = exclude_file(worktree_path)?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(eyre::eyre!("failed to read `{}`: {error}", path.display()));
+        }
+    };
+    Ok(contents
+        .lines()
+        .skip_while(|line| *line != EXCLUDES_MARKER)
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+/// Adds `patterns` to the worktree's own exclude file and returns the ones
+/// that were not there yet.
+pub(crate) fn add_excludes(
+    repo: &Repository,
+    worktree_path: &Path,
+    patterns: &[String],
+) -> color_eyre::Result<Vec<String>> {
+    ensure_extension(repo)?;
+    let path = exclude_file(worktree_path)?;
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| eyre::eyre!("failed to create `{}`", parent.display()))?;
+        }
+        fs::write(&path, seed_contents(repo))
+            .wrap_err_with(|| eyre::eyre!("failed to write `{}`", path.display()))?;
+    }
+    set_worktree_config(worktree_path, "core.excludesFile", &path.to_string_lossy())?;
+
+    let existing = list_excludes(worktree_path)?;
+    let added: Vec<String> = patterns
+        .iter()
+        .map(|pattern| pattern.trim())
+        .filter(|pattern| !pattern.is_empty())
+        .filter(|pattern| !existing.iter().any(|known| known == pattern))
+        .map(String::from)
+        .collect();
+    if added.is_empty() {
+        return Ok(added);
+    }
+
+    let mut contents = fs::read_to_string(&path)
+        .wrap_err_with(|| eyre::eyre!("failed to read `{}`", path.display()))?;
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    for pattern in &added {
+        contents.push_str(pattern);
+        contents.push('\n');
+    }
+    fs::write(&path, contents)
+        .wrap_err_with(|| eyre::eyre!("failed to write `{}`", path.display()))?;
+    Ok(added)
+}
+
+/// Applies the `worktree` defaults from `preferences.json` to a new worktree.
+pub(crate) fn apply_defaults(
+    repo: &Repository,
+    rsworktree_dir: &Path,
+    worktree_path: &Path,
+) -> color_eyre::Result<()> {
+    let Some(defaults) = load_defaults(rsworktree_dir) else {
+        return Ok(());
+    };
+
+    if !defaults.config.is_empty() {
+        ensure_extension(repo)?;
+        for (key, value) in &defaults.config {
+            set_worktree_config(worktree_path, key, value)?;
+        }
+    }
+    if !defaults.excludes.is_empty() {
+        add_excludes(repo, worktree_path, &defaults.excludes)?;
+    }
+    Ok(())
+}
+
+/// Patterns from `worktree.copy` in `preferences.json`.
+pub(crate) fn copy_patterns(rsworktree_dir: &Path) -> Vec<String> {
+    load_defaults(rsworktree_dir)
+        .map(|defaults| defaults.copy)
+        .unwrap_or_default()
+}
+
+/// Directories from `worktree.sparse` in `preferences.json`.
+pub(crate) fn sparse_paths(rsworktree_dir: &Path) -> Vec<String> {
+    load_defaults(rsworktree_dir)
+        .map(|defaults| defaults.sparse)
+        .unwrap_or_default()
+}
+
+/// `worktree.submodules` in `preferences.json`.
+pub(crate) fn submodule_mode(rsworktree_dir: &Path) -> SubmoduleMode {
+    load_defaults(rsworktree_dir)
+        .map(|defaults| defaults.submodules)
+        .unwrap_or_default()
+}
+
+fn load_defaults(rsworktree_dir: &Path) -> Option<WorktreeDefaults> {
+    fs::read_to_string(rsworktree_dir.join(CONFIG_FILE_NAME))
+        .ok()
+        .and_then(|text| serde_json::from_str::<FileFormat>(&text).ok())
+        .and_then(|parsed| parsed.worktree)
+}
+
+/// Initial contents of a per-worktree exclude file: the user's global
+/// excludes, which `core.excludesFile` would otherwise hide, and the marker
+/// after which rsworktree appends its own patterns.
+fn seed_contents(repo: &Repository) -> String {
+    let mut contents = String::new();
+    if let Some(global) = global_excludes_file(repo)
+        && let Ok(patterns) = fs::read_to_string(&global)
+    {
+        contents.push_str(&format!("# copied from {}\n", global.display()));
+        contents.push_str(&patterns);
+        if !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+    }
+    contents.push_str(EXCLUDES_MARKER);
+    contents.push('\n');
+    contents
+}
+
+fn global_excludes_file(repo: &Repository) -> Option<PathBuf> {
+    if let Ok(path) = repo
+        .config()
+        .and_then(|config| config.get_path("core.excludesFile"))
+    {
+        return Some(path);
+    }
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("git").join("ignore"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    use crate::{Repo, commands::create::CreateCommand};
+
+    fn init_repo(dir: &Path) -> color_eyre::Result<Repo> {
+        let run = |args: &[&str]| -> color_eyre::Result<()> {
+            let status = Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(dir)
+                .status()?;
+            if !status.success() {
+                return Err(eyre::eyre!("git {:?} failed", args));
+            }
+            Ok(())
+        };
+        run(&["init", "--quiet"])?;
+        fs::write(dir.join("README.md"), "test")?;
+        run(&["add", "README.md"])?;
+        run(&["commit", "--quiet", "-m", "Initial commit"])?;
+        Repo::discover_from(dir)
+    }
+
+    fn git_stdout(dir: &Path, args: &[&str]) -> color_eyre::Result<String> {
+        let output = Command::new("git").args(args).current_dir(dir).output()?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    }
+
+    #[test]
+    fn ensure_extension_enables_worktree_config_once() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let repo = init_repo(dir.path())?;
+
+        assert!(!extension_enabled(repo.git()));
+        assert!(ensure_extension(repo.git())?);
+        assert!(!ensure_extension(repo.git())?);
+        assert_eq!(
+            git_stdout(
+                dir.path(),
+                &["config", "--get", "extensions.worktreeConfig"]
+            )?,
+            "true"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn excludes_only_apply_to_their_worktree() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let repo = init_repo(dir.path())?;
+        CreateCommand::new("feature/one".into(), None).create_without_enter(&repo, true)?;
+        CreateCommand::new("feature/two".into(), None).create_without_enter(&repo, true)?;
+        let one = repo.worktrees_dir().join("feature/one");
+        let two = repo.worktrees_dir().join("feature/two");
+        fs::write(one.join(".env.local"), "SECRET=1")?;
+        fs::write(two.join(".env.local"), "SECRET=2")?;
+
+        let added = add_excludes(repo.git(), &one, &[".env.local".into()])?;
+        assert_eq!(added, vec![".env.local".to_owned()]);
+        assert!(add_excludes(repo.git(), &one, &[".env.local".into()])?.is_empty());
+
+        assert_eq!(list_excludes(&one)?, vec![".env.local".to_owned()]);
+        assert_eq!(git_stdout(&one, &["status", "--porcelain"])?, "");
+        assert!(git_stdout(&two, &["status", "--porcelain"])?.contains(".env.local"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_applies_worktree_defaults_from_preferences() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let repo = init_repo(dir.path())?;
+        fs::write(
+            repo.ensure_rsworktree_dir()?.join(CONFIG_FILE_NAME),
+            r#"{ "worktree": { "config": { "rsworktree.role": "sandbox" }, "excludes": ["layout.kdl"] } }"#,
+        )?;
+
+        CreateCommand::new("feature/defaults".into(), None).create_without_enter(&repo, true)?;
+
+        let worktree_path = repo.worktrees_dir().join("feature/defaults");
+        assert_eq!(
+            git_stdout(&worktree_path, &["config", "--worktree", "rsworktree.role"])?,
+            "sandbox"
+        );
+        assert_eq!(git_stdout(dir.path(), &["config", "rsworktree.role"])?, "");
+        assert_eq!(
+            list_excludes(&worktree_path)?,
+            vec!["layout.kdl".to_owned()]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+//! Copies untracked files such as `.env` from the main worktree into a new one.
+//!
+//! Patterns are matched against paths relative to the repository root, with
+//! `/` as separator: `*` and `?` match within one path component and `**`
+//! matches any number of components. A matching directory is copied as a
+//! whole; symlinks are recreated rather than followed.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+
+/// Directories never searched for files to copy.
+const SKIPPED_DIRS: [&str; 2] = [".git", ".rsworktree"];
+
+/// Copies every entry below `source` matching one of `patterns` into `target`
+/// and returns the matched paths relative to `source`.
+///
+/// Entries that already exist in `target`, such as tracked files checked out
+/// with the worktree, are left untouched.
+pub(crate) fn copy_matching(
+    source: &Path,
+    target: &Path,
+    patterns: &[String],
+) -> color_eyre::Result<Vec<PathBuf>> {
+    let patterns = patterns
+        .iter()
+        .map(|pattern| pattern.trim().trim_start_matches("./").trim_matches('/'))
+        .filter(|pattern| !pattern.is_empty())
+        .map(|pattern| pattern.split('/').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut copied = Vec::new();
+    if !patterns.is_empty() {
+        collect(source, target, &mut Vec::new(), &patterns, &mut copied)?;
+    }
+    Ok(copied)
+}
+
+fn collect(
+    source: &Path,
+    target: &Path,
+    components: &mut Vec<String>,
+    patterns: &[Vec<&str>],
+    copied: &mut Vec<PathBuf>,
+) -> color_eyre::Result<()> {
+    let dir = components
+        .iter()
+        .fold(source.to_path_buf(), |path, name| path.join(name));
+    let mut entries = fs::read_dir(&dir)
+        .wrap_err_with(|| eyre::eyre!("failed to read `{}`", dir.display()))?
+        .collect::<Result<Vec<_>, _>>()
+        .wrap_err_with(|| eyre::eyre!("failed to read `{}`", dir.display()))?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+            continue;
+        };
+        if components.is_empty() && SKIPPED_DIRS.contains(&name.as_str()) {
+            continue;
+        }
+        components.push(name);
+        let path = components.iter().collect::<PathBuf>();
+        let file_type = entry.file_type()?;
+
+        if patterns
+            .iter()
+            .any(|pattern| matches_components(pattern, components, false))
+        {
+            let destination = target.join(&path);
+            if fs::symlink_metadata(&destination).is_err() {
+                copy_entry(&entry.path(), &destination).wrap_err_with(|| {
+                    eyre::eyre!("failed to copy `{}` into the new worktree", path.display())
+                })?;
+                copied.push(path);
+            }
+        } else if file_type.is_dir()
+            && patterns
+                .iter()
+                .any(|pattern| matches_components(pattern, components, true))
+        {
+            collect(source, target, components, patterns, copied)?;
+        }
+        components.pop();
+    }
+    Ok(())
+}
+
+fn copy_entry(source: &Path, destination: &Path) -> io::Result<()> {
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file_type = fs::symlink_metadata(source)?.file_type();
+    if file_type.is_symlink() {
+        copy_symlink(source, destination)
+    } else if file_type.is_dir() {
+        fs::create_dir(destination)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_entry(&entry.path(), &destination.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(source, destination).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, destination: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, destination)
+}
+
+#[cfg(windows)]
+fn copy_symlink(source: &Path, destination: &Path) -> io::Result<()> {
+    let link = fs::read_link(source)?;
+    if fs::metadata(source).is_ok_and(|metadata| metadata.is_dir()) {
+        std::os::windows::fs::symlink_dir(link, destination)
+    } else {
+        std::os::windows::fs::symlink_file(link, destination)
+    }
+}
+
+/// Matches path components against pattern components. With `prefix`, also
+/// accepts paths that only match the beginning of the pattern, i.e.
+/// directories that may contain a match.
+pub(crate) fn matches_components(pattern: &[&str], path: &[String], prefix: bool) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (None, None) => true,
+        (Some((&"**", rest)), None) => matches_components(rest, path, prefix),
+        (_, None) => prefix,
+        (None, Some(_)) => false,
+        (Some((&"**", rest)), Some((_, path_rest))) => {
+            matches_components(rest, path, prefix) || matches_components(pattern, path_rest, prefix)
+        }
+        (Some((segment, rest)), Some((name, path_rest))) => {
+            matches_segment(segment.as_bytes(), name.as_bytes())
+                && matches_components(rest, path_rest, prefix)
+        }
+    }
+}
+
+fn matches_segment(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            matches_segment(rest, name)
+                || name
+                    .split_first()
+                    .is_some_and(|(_, name_rest)| matches_segment(pattern, name_rest))
+        }
+        (Some((b'?', rest)), Some((_, name_rest))) => matches_segment(rest, name_rest),
+        (Some((expected, rest)), Some((actual, name_rest))) => {
+            expected == actual && matches_segment(rest, name_rest)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        let pattern = pattern.split('/').collect::<Vec<_>>();
+        let path = path.split('/').map(str::to_owned).collect::<Vec<_>>();
+        matches_components(&pattern, &path, false)
+    }
+
+    #[test]
+    fn glob_patterns_match_relative_paths() {
+        assert!(matches(".env", ".env"));
+        assert!(matches(".env.*", ".env.local"));
+        assert!(!matches(".env.*", ".env"));
+        assert!(!matches(".env", "apps/web/.env"));
+        assert!(matches("**/.env", ".env"));
+        assert!(matches("**/.env", "apps/web/.env"));
+        assert!(matches("apps/*/.env", "apps/web/.env"));
+        assert!(!matches("apps/*/.env", "apps/web/src/.env"));
+        assert!(matches("config/??.json", "config/de.json"));
+        assert!(matches("apps/**", "apps/web/.env"));
+    }
+
+    #[test]
+    fn copies_matching_entries_without_overwriting() -> color_eyre::Result<()> {
+        let source = TempDir::new()?;
+        let target = TempDir::new()?;
+        fs::write(source.path().join(".env"), "SECRET=1")?;
+        fs::write(source.path().join(".env.local"), "LOCAL=1")?;
+        fs::write(source.path().join("README.md"), "changed")?;
+        fs::create_dir_all(source.path().join("apps/web/node_modules/pkg"))?;
+        fs::write(source.path().join("apps/web/.env"), "WEB=1")?;
+        fs::write(source.path().join("apps/web/node_modules/pkg/index.js"), "")?;
+        fs::create_dir_all(source.path().join(".git"))?;
+        fs::write(source.path().join(".git/.env"), "")?;
+        fs::write(target.path().join("README.md"), "tracked")?;
+
+        let copied = copy_matching(
+            source.path(),
+            target.path(),
+            &[
+                "**/.env*".into(),
+                "README.md".into(),
+                "apps/*/node_modules".into(),
+            ],
+        )?;
+
+        assert_eq!(
+            copied,
+            [
+                ".env",
+                ".env.local",
+                "apps/web/.env",
+                "apps/web/node_modules"
+            ]
+            .map(PathBuf::from)
+            .to_vec()
+        );
+        assert_eq!(fs::read_to_string(target.path().join(".env"))?, "SECRET=1");
+        assert_eq!(
+            fs::read_to_string(target.path().join("README.md"))?,
+            "tracked"
+        );
+        assert!(
+            target
+                .path()
+                .join("apps/web/node_modules/pkg/index.js")
+                .exists()
+        );
+        assert!(!target.path().join(".git").exists());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn recreates_symlinks() -> color_eyre::Result<()> {
+        let source = TempDir::new()?;
+        let target = TempDir::new()?;
+        std::os::unix::fs::symlink("../shared/node_modules", source.path().join("node_modules"))?;
+
+        copy_matching(source.path(), target.path(), &["node_modules".into()])?;
+
+        assert_eq!(
+            fs::read_link(target.path().join("node_modules"))?,
+            PathBuf::from("../shared/node_modules")
+        );
+
+        Ok(())
+    }
+}
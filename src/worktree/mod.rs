@@ -0,0 +1,296 @@
+//! The rich view of a managed worktree shared by `info`, `ls` and friends.
+
+pub(crate) mod config;
+pub(crate) mod copy;
+pub(crate) mod ports;
+pub(crate) mod render;
+pub(crate) mod submodules;
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{self, WrapErr};
+use git2::{BranchType, Repository, Status, StatusOptions};
+
+use crate::{
+    Repo,
+    commands::{
+        create::base_config_key,
+        list::{find_worktrees, format_worktree, is_managed},
+    },
+};
+
+/// A worktree together with its branch and working tree state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Worktree {
+    pub name: String,
+    pub path: PathBuf,
+    /// Whether the worktree is under `.rsworktree` rather than added with
+    /// `git worktree add` elsewhere.
+    pub managed: bool,
+    /// Branch the worktree was created from, as recorded by `create`.
+    pub base: Option<String>,
+    pub status: WorktreeStatus,
+}
+
+impl Worktree {
+    pub(crate) fn load(repo: &Repo, name: String, path: PathBuf) -> color_eyre::Result<Self> {
+        let status = inspect_worktree(&path)?;
+        let base = status.branch.as_deref().and_then(|branch| {
+            repo.git()
+                .config()
+                .and_then(|config| config.get_string(&base_config_key(branch)))
+                .ok()
+        });
+
+        Ok(Self {
+            managed: is_managed(&repo.worktrees_dir(), &path),
+            name,
+            path,
+            base,
+            status,
+        })
+    }
+
+    pub fn ahead(&self) -> Option<usize> {
+        self.status.upstream.as_ref().map(|upstream| upstream.ahead)
+    }
+
+    pub fn behind(&self) -> Option<usize> {
+        self.status
+            .upstream
+            .as_ref()
+            .map(|upstream| upstream.behind)
+    }
+}
+
+/// Loads every worktree, those under `.rsworktree` first, sorted by name.
+pub(crate) fn load_all(repo: &Repo) -> color_eyre::Result<Vec<Worktree>> {
+    let worktrees_dir = repo.ensure_worktrees_dir()?;
+    find_worktrees(repo)?
+        .into_iter()
+        .map(|relative| {
+            let name = format_worktree(&relative);
+            Worktree::load(repo, name, worktrees_dir.join(relative))
+        })
+        .collect()
+}
+
+/// Branch, upstream and working tree state of a single worktree checkout.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorktreeStatus {
+    pub branch: Option<String>,
+    /// Abbreviated HEAD commit.
+    pub head: Option<String>,
+    pub upstream: Option<UpstreamStatus>,
+    pub changes: ChangeSummary,
+}
+
+/// The tracking branch of a worktree branch and how far the two diverged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpstreamStatus {
+    pub name: String,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// Number of files per `git status` state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChangeSummary {
+    pub staged: usize,
+    pub modified: usize,
+    pub untracked: usize,
+    pub conflicted: usize,
+}
+
+impl ChangeSummary {
+    pub fn is_clean(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn describe(&self) -> String {
+        if self.is_clean() {
+            return "clean".to_owned();
+        }
+
+        [
+            (self.staged, "staged"),
+            (self.modified, "modified"),
+            (self.untracked, "untracked"),
+            (self.conflicted, "conflicted"),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{count} {label}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+pub(crate) fn inspect_worktree(path: &Path) -> color_eyre::Result<WorktreeStatus> {
+    let repo = Repository::open(path)
+        .wrap_err_with(|| eyre::eyre!("failed to open worktree at `{}`", path.display()))?;
+
+    let mut status = WorktreeStatus::default();
+
+    if let Ok(head) = repo.head() {
+        status.head = head
+            .target()
+            .map(|oid| oid.to_string().chars().take(7).collect());
+
+        if head.is_branch() {
+            status.branch = head.shorthand().map(String::from);
+        }
+    }
+
+    if let Some(branch_name) = status.branch.as_deref()
+        && let Ok(branch) = repo.find_branch(branch_name, BranchType::Local)
+        && let Ok(upstream) = branch.upstream()
+    {
+        let name = upstream
+            .name()
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+            .to_owned();
+        let (ahead, behind) = match (branch.get().target(), upstream.get().target()) {
+            (Some(local), Some(remote)) => repo.graph_ahead_behind(local, remote).unwrap_or((0, 0)),
+            _ => (0, 0),
+        };
+        status.upstream = Some(UpstreamStatus {
+            name,
+            ahead,
+            behind,
+        });
+    }
+
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).include_ignored(false);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .wrap_err_with(|| eyre::eyre!("failed to read status of `{}`", path.display()))?;
+
+    for entry in statuses.iter() {
+        let flags = entry.status();
+        if flags.is_conflicted() {
+            status.changes.conflicted += 1;
+            continue;
+        }
+        if flags.intersects(
+            Status::INDEX_NEW
+                | Status::INDEX_MODIFIED
+                | Status::INDEX_DELETED
+                | Status::INDEX_RENAMED
+                | Status::INDEX_TYPECHANGE,
+        ) {
+            status.changes.staged += 1;
+        }
+        if flags.intersects(
+            Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED | Status::WT_TYPECHANGE,
+        ) {
+            status.changes.modified += 1;
+        }
+        if flags.contains(Status::WT_NEW) {
+            status.changes.untracked += 1;
+        }
+    }
+
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, process::Command};
+
+    use tempfile::TempDir;
+
+    use crate::commands::create::CreateCommand;
+
+    fn init_git_repo(dir: &TempDir) -> color_eyre::Result<()> {
+        run(dir, ["git", "init"])?;
+        fs::write(dir.path().join("README.md"), "test")?;
+        run(dir, ["git", "add", "README.md"])?;
+        run(
+            dir,
+            [
+                "git",
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "Initial commit",
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn run(dir: &TempDir, cmd: impl IntoIterator<Item = &'static str>) -> color_eyre::Result<()> {
+        let mut iter = cmd.into_iter();
+        let program = iter.next().expect("command must not be empty");
+        let status = Command::new(program)
+            .current_dir(dir.path())
+            .args(iter)
+            .status()
+            .wrap_err_with(|| eyre::eyre!("failed to run `{program}`"))?;
+
+        if !status.success() {
+            return Err(eyre::eyre!("`{program}` exited with status {status}"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn inspect_worktree_reports_branch_and_changes() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        CreateCommand::new("feature/info".into(), None).create_without_enter(&repo, true)?;
+
+        let worktree_path = repo.worktrees_dir().join("feature/info");
+        fs::write(worktree_path.join("notes.txt"), "draft")?;
+        fs::write(worktree_path.join("README.md"), "changed")?;
+
+        let status = inspect_worktree(&worktree_path)?;
+        assert_eq!(status.branch.as_deref(), Some("feature/info"));
+        assert_eq!(status.head.as_deref().map(str::len), Some(7));
+        assert!(status.upstream.is_none());
+        assert_eq!(
+            status.changes,
+            ChangeSummary {
+                staged: 0,
+                modified: 1,
+                untracked: 1,
+                conflicted: 0,
+            }
+        );
+        assert_eq!(status.changes.describe(), "1 modified, 1 untracked");
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_all_collects_status_and_base() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        init_git_repo(&dir)?;
+        let repo = Repo::discover_from(dir.path())?;
+        CreateCommand::new("feature/b".into(), Some("HEAD".into()))
+            .create_without_enter(&repo, true)?;
+        CreateCommand::new("feature/a".into(), None).create_without_enter(&repo, true)?;
+
+        let worktrees = load_all(&repo)?;
+
+        let names: Vec<&str> = worktrees
+            .iter()
+            .map(|worktree| worktree.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["feature/a", "feature/b"]);
+        assert_eq!(worktrees[1].status.branch.as_deref(), Some("feature/b"));
+        assert!(worktrees[1].base.is_some());
+        assert_eq!(worktrees[1].ahead(), None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+//! Port ranges handed out to worktrees, so dev servers started in different
+//! worktrees do not collide.
+//!
+//! Each worktree gets `ports` consecutive ports that are not assigned to
+//! another worktree and not in use when allocated. The assignments are kept in
+//! `.rsworktree/ports.json`, released by `rm` and moved by `rename`:
+//!
+//! ```json
+//! { "feature/login": { "start": 3000, "count": 10 } }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    net::TcpListener,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+use serde::{Deserialize, Serialize};
+
+pub const PORTS_FILE: &str = "ports.json";
+/// The lowest port handed out unless `templates.first_port` is set.
+pub const DEFAULT_FIRST_PORT: u16 = 3000;
+/// Ports per worktree unless `templates.ports` is set.
+pub const DEFAULT_PORT_COUNT: u16 = 10;
+
+/// Consecutive ports assigned to one worktree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortRange {
+    pub start: u16,
+    pub count: u16,
+}
+
+impl PortRange {
+    /// The `offset`th port of the range.
+    pub fn port(&self, offset: u16) -> Option<u16> {
+        (offset < self.count).then(|| self.start + offset)
+    }
+
+    fn end(&self) -> u32 {
+        u32::from(self.start) + u32::from(self.count)
+    }
+
+    fn overlaps(&self, other: &PortRange) -> bool {
+        u32::from(self.start) < other.end() && u32::from(other.start) < self.end()
+    }
+}
+
+/// The port assignments in `ports.json`.
+#[derive(Debug)]
+pub(crate) struct PortRegistry {
+    path: PathBuf,
+}
+
+impl PortRegistry {
+    pub(crate) fn new(rsworktree_dir: &Path) -> Self {
+        Self {
+            path: rsworktree_dir.join(PORTS_FILE),
+        }
+    }
+
+    /// The range of `worktree`, allocating the lowest free one from
+    /// `first_port` on when it has none yet.
+    pub(crate) fn allocate(
+        &self,
+        worktree: &str,
+        first_port: u16,
+        count: u16,
+    ) -> color_eyre::Result<PortRange> {
+        let mut ranges = self.load()?;
+        if let Some(range) = ranges.get(worktree) {
+            return Ok(*range);
+        }
+        let count = count.max(1);
+        let mut start = u32::from(first_port);
+        let range = loop {
+            let Ok(candidate_start) = u16::try_from(start) else {
+                return Err(eyre::eyre!(
+                    "no free range of {count} ports from {first_port} on"
+                ));
+            };
+            let candidate = PortRange {
+                start: candidate_start,
+                count,
+            };
+            if candidate.end() > u32::from(u16::MAX) + 1 {
+                return Err(eyre::eyre!(
+                    "no free range of {count} ports from {first_port} on"
+                ));
+            }
+            match ranges.values().find(|taken| taken.overlaps(&candidate)) {
+                Some(taken) => start = taken.end(),
+                None if all_free(&candidate) => break candidate,
+                None => start += u32::from(count),
+            }
+        };
+        ranges.insert(worktree.to_owned(), range);
+        self.save(&ranges)?;
+        Ok(range)
+    }
+
+    /// Forgets the range of `worktree`, returning whether it had one.
+    pub(crate) fn release(&self, worktree: &str) -> color_eyre::Result<bool> {
+        let mut ranges = self.load()?;
+        if ranges.remove(worktree).is_none() {
+            return Ok(false);
+        }
+        self.save(&ranges)?;
+        Ok(true)
+    }
+
+    /// Moves the range of `old` to `new`.
+    pub(crate) fn rename(&self, old: &str, new: &str) -> color_eyre::Result<()> {
+        let mut ranges = self.load()?;
+        if let Some(range) = ranges.remove(old) {
+            ranges.insert(new.to_owned(), range);
+            self.save(&ranges)?;
+        }
+        Ok(())
+    }
+
+    fn load(&self) -> color_eyre::Result<BTreeMap<String, PortRange>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(error) => {
+                return Err(eyre::eyre!(
+                    "failed to read `{}`: {error}",
+                    self.path.display()
+                ));
+            }
+        };
+        serde_json::from_str(&contents)
+            .wrap_err_with(|| eyre::eyre!("failed to parse `{}`", self.path.display()))
+    }
+
+    fn save(&self, ranges: &BTreeMap<String, PortRange>) -> color_eyre::Result<()> {
+        let contents = serde_json::to_string_pretty(ranges)?;
+        fs::write(&self.path, contents + "\n")
+            .wrap_err_with(|| eyre::eyre!("failed to write `{}`", self.path.display()))
+    }
+}
+
+/// Whether nothing listens on any port of `range` on the loopback interface.
+fn all_free(range: &PortRange) -> bool {
+    (0..range.count)
+        .filter_map(|offset| range.port(offset))
+        .all(|port| TcpListener::bind(("127.0.0.1", port)).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn worktrees_get_separate_ranges_until_released() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let registry = PortRegistry::new(dir.path());
+        // Keep a port busy so the allocation has to skip past it.
+        let busy = TcpListener::bind(("127.0.0.1", 0))?;
+        let busy_port = busy.local_addr()?.port();
+
+        let first = registry.allocate("feature/a", busy_port, 3)?;
+        assert!(first.start > busy_port);
+        assert_eq!(registry.allocate("feature/a", busy_port, 3)?, first);
+
+        let second = registry.allocate("feature/b", busy_port, 3)?;
+        assert!(!second.overlaps(&first));
+
+        registry.rename("feature/b", "feature/c")?;
+        assert_eq!(registry.allocate("feature/c", busy_port, 3)?, second);
+        assert!(registry.release("feature/a")?);
+        assert!(!registry.release("feature/a")?);
+        assert_eq!(registry.load()?.len(), 1);
+        Ok(())
+    }
+}
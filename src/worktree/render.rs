@@ -0,0 +1,218 @@
+//! Files rendered into a new worktree from the `templates.files` in
+//! `config.toml`, e.g. `.env.template` becomes `.env`.
+//!
+//! `{{worktree}}`, `{{branch}}`, `{{base}}` and `{{path}}` are replaced with
+//! the worktree's name, branch, base branch and path. `{{port}}` is the first
+//! port of the range allocated to the worktree in `ports.json`, and
+//! `{{port.1}}`, `{{port.2}}`, ... the following ones; the range is only
+//! allocated when a template uses it. Any other `{{...}}` is an error.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+
+use super::ports::{DEFAULT_FIRST_PORT, DEFAULT_PORT_COUNT, PortRange, PortRegistry};
+use crate::{config::TemplatesConfig, output};
+
+pub const TEMPLATE_SUFFIX: &str = ".template";
+
+/// What the placeholders of a template stand for.
+#[derive(Debug, Clone)]
+pub(crate) struct TemplateContext<'a> {
+    pub worktree: &'a str,
+    pub branch: &'a str,
+    pub base: Option<&'a str>,
+    pub path: &'a Path,
+}
+
+/// Renders each of `config.files` found in the new worktree or, for an
+/// untracked template, in `source`, and returns the written files.
+///
+/// Ports are allocated in `rsworktree_dir` on first use; `allocated` is set
+/// once they are, so a failed `create` can release them.
+pub(crate) fn render_templates(
+    config: &TemplatesConfig,
+    rsworktree_dir: &Path,
+    source: &Path,
+    context: &TemplateContext<'_>,
+    allocated: &mut bool,
+) -> color_eyre::Result<Vec<PathBuf>> {
+    let mut ports = None;
+    let mut written = Vec::new();
+    for file in &config.files {
+        let Some(target) = file.strip_suffix(TEMPLATE_SUFFIX) else {
+            return Err(eyre::eyre!(
+                "`templates.files` entry `{file}` must end in `{TEMPLATE_SUFFIX}`"
+            ));
+        };
+        let template = [context.path, source]
+            .iter()
+            .map(|dir| dir.join(file))
+            .find(|path| path.is_file());
+        let Some(template) = template else {
+            output::warn(tr!("Template `{}` not found; skipped.", file));
+            continue;
+        };
+        let text = fs::read_to_string(&template)
+            .wrap_err_with(|| eyre::eyre!("failed to read `{}`", template.display()))?;
+        let rendered = render(&text, |name| {
+            lookup(name, context, || {
+                if let Some(range) = ports {
+                    return Ok(range);
+                }
+                let range = PortRegistry::new(rsworktree_dir).allocate(
+                    context.worktree,
+                    config.first_port.unwrap_or(DEFAULT_FIRST_PORT),
+                    config.ports.unwrap_or(DEFAULT_PORT_COUNT),
+                )?;
+                *allocated = true;
+                Ok(*ports.insert(range))
+            })
+        })
+        .wrap_err_with(|| eyre::eyre!("failed to render `{}`", template.display()))?;
+
+        let target = context.path.join(target);
+        fs::write(&target, rendered)
+            .wrap_err_with(|| eyre::eyre!("failed to write `{}`", target.display()))?;
+        written.push(target);
+    }
+    Ok(written)
+}
+
+/// The value of the placeholder `name`.
+fn lookup(
+    name: &str,
+    context: &TemplateContext<'_>,
+    ports: impl FnOnce() -> color_eyre::Result<PortRange>,
+) -> color_eyre::Result<String> {
+    match name {
+        "worktree" => return Ok(context.worktree.to_owned()),
+        "branch" => return Ok(context.branch.to_owned()),
+        "base" => return Ok(context.base.unwrap_or_default().to_owned()),
+        "path" => return Ok(context.path.display().to_string()),
+        _ => {}
+    }
+    let offset = match name.strip_prefix("port") {
+        Some("") => 0,
+        Some(offset) => match offset.strip_prefix('.').map(str::parse::<u16>) {
+            Some(Ok(offset)) => offset,
+            _ => return Err(eyre::eyre!("unknown placeholder `{{{{{name}}}}}`")),
+        },
+        None => return Err(eyre::eyre!("unknown placeholder `{{{{{name}}}}}`")),
+    };
+    let range = ports()?;
+    range.port(offset).map(|port| port.to_string()).ok_or_else(|| {
+        eyre::eyre!(
+            "`{{{{{name}}}}}` is outside the {} ports allocated per worktree; raise `templates.ports`",
+            range.count
+        )
+    })
+}
+
+/// Replaces each `{{ name }}` in `text` with `value(name)`.
+fn render(
+    text: &str,
+    mut value: impl FnMut(&str) -> color_eyre::Result<String>,
+) -> color_eyre::Result<String> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(&value(rest[start + 2..start + end].trim())?);
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn placeholders_are_replaced_and_ports_allocated_once() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let source = dir.path().join("main");
+        let worktree = dir.path().join("feature");
+        fs::create_dir_all(source.join("web"))?;
+        fs::create_dir_all(worktree.join("web"))?;
+        fs::write(
+            source.join(".env.template"),
+            "NAME={{worktree}}\nPORT={{ port }}\nDB_PORT={{port.1}}\n",
+        )?;
+        fs::write(
+            worktree.join("web/.env.template"),
+            "API=http://localhost:{{port}}\n",
+        )?;
+        let config = TemplatesConfig {
+            files: vec![
+                ".env.template".into(),
+                "web/.env.template".into(),
+                "missing.template".into(),
+            ],
+            ports: Some(2),
+            first_port: Some(41000),
+        };
+        let context = TemplateContext {
+            worktree: "feature/login",
+            branch: "feature/login",
+            base: None,
+            path: &worktree,
+        };
+
+        let mut allocated = false;
+        let written = render_templates(&config, dir.path(), &source, &context, &mut allocated)?;
+        assert!(allocated);
+        assert_eq!(written, [worktree.join(".env"), worktree.join("web/.env")]);
+        let port = PortRegistry::new(dir.path()).allocate("feature/login", 41000, 2)?;
+        assert_eq!(
+            fs::read_to_string(worktree.join(".env"))?,
+            format!(
+                "NAME=feature/login\nPORT={}\nDB_PORT={}\n",
+                port.start,
+                port.start + 1
+            )
+        );
+        assert_eq!(
+            fs::read_to_string(worktree.join("web/.env"))?,
+            format!("API=http://localhost:{}\n", port.start)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_placeholders_and_ports_beyond_the_range_fail() {
+        let context = TemplateContext {
+            worktree: "x",
+            branch: "x",
+            base: Some("main"),
+            path: Path::new("/tmp/x"),
+        };
+        let ports = || {
+            Ok(PortRange {
+                start: 3000,
+                count: 2,
+            })
+        };
+        assert_eq!(
+            lookup("base", &context, ports).ok().as_deref(),
+            Some("main")
+        );
+        assert!(lookup("port.2", &context, ports).is_err());
+        assert!(lookup("user", &context, ports).is_err());
+        assert_eq!(
+            render("a {{ not closed", |_| Ok(String::new()))
+                .ok()
+                .as_deref(),
+            Some("a {{ not closed")
+        );
+    }
+}
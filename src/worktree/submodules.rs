@@ -0,0 +1,119 @@
+//! Submodules of a new worktree: `git worktree add` only checks out the
+//! superproject, leaving every submodule directory empty until they are
+//! initialized.
+
+use std::{path::Path, process::Command};
+
+use color_eyre::eyre::{self, WrapErr};
+use serde::Deserialize;
+
+/// How `create` initializes submodules (`worktree.submodules` in
+/// `preferences.json`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum SubmoduleMode {
+    /// Clone every submodule, recursively, with its full history.
+    #[default]
+    Full,
+    /// Only fetch the commit each submodule is pinned to (`--depth 1`).
+    Shallow,
+    /// Leave the submodules uninitialized.
+    Off,
+}
+
+/// Whether the worktree checked out a `.gitmodules` file.
+pub(crate) fn has_submodules(worktree_path: &Path) -> bool {
+    worktree_path.join(".gitmodules").is_file()
+}
+
+/// Runs `git submodule update --init --recursive` in the worktree.
+pub(crate) fn update(worktree_path: &Path, mode: SubmoduleMode) -> color_eyre::Result<()> {
+    run(update_command(worktree_path, mode), worktree_path)
+}
+
+fn update_command(worktree_path: &Path, mode: SubmoduleMode) -> Command {
+    let mut command = Command::new("git");
+    command
+        .args(["submodule", "update", "--init", "--recursive"])
+        .current_dir(worktree_path);
+    if mode == SubmoduleMode::Shallow {
+        command.args(["--depth", "1"]);
+    }
+    command
+}
+
+fn run(mut command: Command, worktree_path: &Path) -> color_eyre::Result<()> {
+    let output = command
+        .output()
+        .wrap_err("failed to run `git submodule update`")?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(eyre::eyre!(
+        "`git submodule update` failed in `{}`: {}",
+        worktree_path.display(),
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) -> color_eyre::Result<()> {
+        let status = Command::new("git")
+            .args([
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "-c",
+                "protocol.file.allow=always",
+            ])
+            .args(args)
+            .current_dir(dir)
+            .status()?;
+        assert!(status.success(), "git {args:?} failed");
+        Ok(())
+    }
+
+    #[test]
+    fn initializes_submodules_of_a_new_worktree() -> color_eyre::Result<()> {
+        let dir = TempDir::new()?;
+        let sub = dir.path().join("sub");
+        let superproject = dir.path().join("super");
+        let worktree = dir.path().join("worktree");
+        for repo in [&sub, &superproject] {
+            std::fs::create_dir(repo)?;
+            git(repo, &["init", "--quiet"])?;
+            git(
+                repo,
+                &["commit", "--quiet", "--allow-empty", "-m", "Initial"],
+            )?;
+        }
+        git(
+            &superproject,
+            &["submodule", "--quiet", "add", "../sub", "sub"],
+        )?;
+        git(&superproject, &["commit", "--quiet", "-m", "Add sub"])?;
+        let worktree_arg = worktree.to_str().expect("temporary paths are UTF-8");
+        git(
+            &superproject,
+            &["worktree", "add", "--quiet", "-b", "feature", worktree_arg],
+        )?;
+        assert!(has_submodules(&worktree));
+        assert!(!worktree.join("sub/.git").exists());
+
+        // git refuses local submodule URLs unless told otherwise.
+        let mut command = update_command(&worktree, SubmoduleMode::Shallow);
+        command
+            .env("GIT_CONFIG_COUNT", "1")
+            .env("GIT_CONFIG_KEY_0", "protocol.file.allow")
+            .env("GIT_CONFIG_VALUE_0", "always");
+        run(command, &worktree)?;
+        assert!(worktree.join("sub/.git").exists());
+        Ok(())
+    }
+}
@@ -1,14 +1,36 @@
+#[path = "commands/archive.rs"]
+mod archive;
 #[path = "commands/cd.rs"]
 mod cd;
+#[path = "commands/ci.rs"]
+mod ci;
+#[path = "commands/completions.rs"]
+mod completions;
+#[path = "commands/config.rs"]
+mod config;
 #[path = "commands/create.rs"]
 mod create;
+#[path = "commands/export.rs"]
+mod export;
+#[path = "commands/hooks.rs"]
+mod hooks;
+#[path = "commands/info.rs"]
+mod info;
 #[path = "commands/list.rs"]
 mod list;
 #[path = "commands/merge.rs"]
 mod merge;
 #[path = "commands/open.rs"]
 mod open;
+#[path = "commands/pr.rs"]
+mod pr;
+#[path = "commands/rename.rs"]
+mod rename;
 #[path = "commands/review.rs"]
 mod review;
 #[path = "commands/rm.rs"]
 mod rm;
+#[path = "commands/status.rs"]
+mod status;
+#[path = "commands/switch.rs"]
+mod switch;
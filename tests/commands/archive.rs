@@ -0,0 +1,90 @@
+use std::{error::Error, fs, path::Path, process::Command as StdCommand};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn init_git_repo(dir: &Path) -> Result<(), Box<dyn Error>> {
+    run(dir, ["git", "init"])?;
+    fs::write(dir.join("README.md"), "test")?;
+    run(dir, ["git", "add", "README.md"])?;
+    run(
+        dir,
+        [
+            "git",
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-m",
+            "Initial commit",
+        ],
+    )?;
+    Ok(())
+}
+
+fn run(dir: &Path, cmd: impl IntoIterator<Item = &'static str>) -> Result<(), Box<dyn Error>> {
+    let mut iter = cmd.into_iter();
+    let program = iter.next().expect("command must not be empty");
+    let status = StdCommand::new(program)
+        .current_dir(dir)
+        .args(iter)
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("`{program}` exited with status {status}").into());
+    }
+
+    Ok(())
+}
+
+fn rsworktree(dir: &Path) -> Result<Command, Box<dyn Error>> {
+    let mut command = Command::cargo_bin("rsworktree")?;
+    command
+        .current_dir(dir)
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env");
+    Ok(command)
+}
+
+#[test]
+fn archive_command_parks_and_restores_worktree() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    rsworktree(repo_dir.path())?
+        .args(["archive", "--list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No archived worktrees."));
+
+    rsworktree(repo_dir.path())?
+        .args(["create", "experiment"])
+        .assert()
+        .success();
+    let worktree = repo_dir.path().join(".rsworktree/experiment");
+    fs::write(worktree.join("idea.txt"), "untracked")?;
+
+    rsworktree(repo_dir.path())?
+        .args(["archive", "experiment"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Archived worktree"));
+    assert!(!worktree.exists());
+
+    rsworktree(repo_dir.path())?
+        .args(["archive", "--list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("experiment"));
+
+    rsworktree(repo_dir.path())?
+        .args(["unarchive", "experiment"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored worktree"));
+    assert_eq!(fs::read_to_string(worktree.join("idea.txt"))?, "untracked");
+
+    Ok(())
+}
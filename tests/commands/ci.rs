@@ -0,0 +1,153 @@
+#![cfg(unix)]
+
+use std::{
+    env, error::Error, ffi::OsString, fs, os::unix::fs::PermissionsExt, path::Path, path::PathBuf,
+    process::Command as StdCommand,
+};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+struct StubGh {
+    _dir: TempDir,
+    path_value: OsString,
+    log_path: PathBuf,
+}
+
+#[test]
+fn ci_trigger_dispatches_workflow_and_status_reports_run() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/test"])
+        .assert()
+        .success();
+
+    let stub = install_stub_gh()?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env("PATH", &stub.path_value)
+        .env("GH_LOG", &stub.log_path)
+        .args(["ci", "trigger", "feature/test", "--workflow", "ci.yml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Started CI for branch `feature/test`.",
+        ));
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env("PATH", &stub.path_value)
+        .env("GH_LOG", &stub.log_path)
+        .env(
+            "GH_RUN_LIST_RESPONSE",
+            r#"[{"databaseId": 7, "workflowName": "CI", "status": "completed", "conclusion": "success", "url": "https://example.test/runs/7"}]"#,
+        )
+        .args(["ci", "status", "feature/test", "--watch"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "CI CI #7 for `feature/test`: success",
+        ));
+
+    let log_contents = fs::read_to_string(&stub.log_path)?;
+    assert!(log_contents.contains("args:workflow run ci.yml --ref feature/test"));
+    assert!(log_contents.contains("args:run list --branch feature/test"));
+
+    Ok(())
+}
+
+#[test]
+fn ci_trigger_requires_workflow_for_github() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/test"])
+        .assert()
+        .success();
+
+    let stub = install_stub_gh()?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env("PATH", &stub.path_value)
+        .env("GH_LOG", &stub.log_path)
+        .args(["ci", "trigger", "feature/test"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--workflow"));
+
+    assert!(!stub.log_path.exists());
+
+    Ok(())
+}
+
+fn init_git_repo(dir: &Path) -> Result<(), Box<dyn Error>> {
+    run(dir, ["git", "init"])?;
+    fs::write(dir.join("README.md"), "test")?;
+    run(dir, ["git", "add", "README.md"])?;
+    run(
+        dir,
+        [
+            "git",
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-m",
+            "Initial commit",
+        ],
+    )?;
+    Ok(())
+}
+
+fn run(dir: &Path, cmd: impl IntoIterator<Item = &'static str>) -> Result<(), Box<dyn Error>> {
+    let mut iter = cmd.into_iter();
+    let program = iter.next().expect("command must not be empty");
+    let status = StdCommand::new(program)
+        .current_dir(dir)
+        .args(iter)
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("`{program}` exited with status {status}").into());
+    }
+
+    Ok(())
+}
+
+fn install_stub_gh() -> Result<StubGh, Box<dyn Error>> {
+    let stub_dir = TempDir::new()?;
+    let gh_log = stub_dir.path().join("gh.log");
+    let gh_path = stub_dir.path().join("gh");
+    fs::write(
+        &gh_path,
+        "#! /bin/sh\n\nlog() {\n  printf 'args:%s\\n' \"$*\" >> \"$GH_LOG\"\n}\n\ncase \"$1 $2\" in\n  'workflow run')\n    log \"$@\"\n    ;;\n  'run list')\n    log \"$@\"\n    printf '%s' \"${GH_RUN_LIST_RESPONSE:-[]}\"\n    ;;\n  *)\n    echo \"unexpected gh invocation: $*\" >&2\n    exit 1\n    ;;\nesac\n\nexit 0\n",
+    )?;
+    let mut perms = fs::metadata(&gh_path)?.permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(&gh_path, perms)?;
+
+    let mut path_value = OsString::from(stub_dir.path());
+    if let Some(existing) = env::var_os("PATH") {
+        path_value.push(":");
+        path_value.push(existing);
+    }
+
+    Ok(StubGh {
+        _dir: stub_dir,
+        path_value,
+        log_path: gh_log,
+    })
+}
@@ -0,0 +1,97 @@
+use std::{error::Error, fs, path::Path, process::Command as StdCommand};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn init_git_repo(dir: &Path) -> Result<(), Box<dyn Error>> {
+    run(dir, ["git", "init", "-b", "main"])?;
+    fs::write(dir.join("README.md"), "test")?;
+    run(dir, ["git", "add", "README.md"])?;
+    run(
+        dir,
+        [
+            "git",
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-m",
+            "Initial commit",
+        ],
+    )?;
+    Ok(())
+}
+
+fn run(dir: &Path, cmd: impl IntoIterator<Item = &'static str>) -> Result<(), Box<dyn Error>> {
+    let mut iter = cmd.into_iter();
+    let program = iter.next().expect("command must not be empty");
+    let status = StdCommand::new(program)
+        .current_dir(dir)
+        .args(iter)
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("`{program}` exited with status {status}").into());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn completions_command_prints_script_outside_repository() -> Result<(), Box<dyn Error>> {
+    let dir = TempDir::new()?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(dir.path())
+        .args(["completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "complete -F _rsworktree_names -o bashdefault -o default rsworktree",
+        ))
+        .stdout(predicate::str::contains(
+            "'rsworktree rm') kind=worktrees ;;",
+        ));
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(dir.path())
+        .args(["__complete", "worktrees"])
+        .assert()
+        .success()
+        .stdout("");
+
+    Ok(())
+}
+
+#[test]
+fn complete_command_lists_worktrees_and_branches() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    run(repo_dir.path(), ["git", "branch", "develop"])?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/login"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["__complete", "worktrees"])
+        .assert()
+        .success()
+        .stdout("feature/login\n");
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["__complete", "branches"])
+        .assert()
+        .success()
+        .stdout("develop\nfeature/login\nmain\n");
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use std::{error::Error, fs, path::Path, process::Command as StdCommand};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn init_git_repo(dir: &Path) -> Result<(), Box<dyn Error>> {
+    let init_with_main = StdCommand::new("git")
+        .current_dir(dir)
+        .args(["init", "-b", "main"])
+        .status()?;
+
+    if !init_with_main.success() {
+        run(dir, ["git", "init"])?;
+        run(dir, ["git", "branch", "-M", "main"])?;
+    }
+
+    fs::write(dir.join("README.md"), "test")?;
+    run(dir, ["git", "add", "README.md"])?;
+    run(
+        dir,
+        [
+            "git",
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-m",
+            "Initial commit",
+        ],
+    )?;
+    Ok(())
+}
+
+fn run(dir: &Path, cmd: impl IntoIterator<Item = &'static str>) -> Result<(), Box<dyn Error>> {
+    let mut iter = cmd.into_iter();
+    let program = iter.next().expect("command must not be empty");
+    let status = StdCommand::new(program)
+        .current_dir(dir)
+        .args(iter)
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("`{program}` exited with status {status}").into());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn config_and_exclude_apply_to_a_single_worktree() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    for name in ["feature/one", "feature/two"] {
+        Command::cargo_bin("rsworktree")?
+            .current_dir(repo_dir.path())
+            .env_remove("TMUX")
+            .env("RSWORKTREE_SHELL", "env")
+            .args(["create", name])
+            .assert()
+            .success();
+    }
+    let one = repo_dir.path().join(".rsworktree/feature/one");
+    let two = repo_dir.path().join(".rsworktree/feature/two");
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(&one)
+        .args(["config", "--worktree", "user.email", "one@example.com"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("extensions.worktreeConfig"));
+
+    let read_email = |dir: &Path| -> Result<String, Box<dyn Error>> {
+        let output = StdCommand::new("git")
+            .current_dir(dir)
+            .args(["config", "--worktree", "--get", "user.email"])
+            .output()?;
+        Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+    };
+    assert_eq!(read_email(&one)?, "one@example.com");
+    assert_eq!(read_email(&two)?, "");
+
+    fs::write(one.join(".env.local"), "PORT=3001")?;
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["exclude", "-n", "feature/one", ".env.local"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Excluded .env.local"));
+
+    let status = StdCommand::new("git")
+        .current_dir(&one)
+        .args(["status", "--porcelain"])
+        .output()?;
+    assert!(String::from_utf8(status.stdout)?.trim().is_empty());
+
+    Ok(())
+}
@@ -78,6 +78,40 @@ fn create_command_creates_worktree_and_updates_gitignore() -> Result<(), Box<dyn
     Ok(())
 }
 
+#[test]
+fn create_command_works_in_bare_clone() -> Result<(), Box<dyn Error>> {
+    let origin = TempDir::new()?;
+    init_git_repo(origin.path())?;
+    let dir = TempDir::new()?;
+    let origin_path = origin.path().to_string_lossy().into_owned();
+    let status = StdCommand::new("git")
+        .current_dir(dir.path())
+        .args(["clone", "--bare", &origin_path, "project/.git"])
+        .status()?;
+    assert!(status.success(), "git clone --bare should succeed");
+    let project = dir.path().join("project");
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(&project)
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/bare"])
+        .assert()
+        .success();
+
+    let worktree = project.join(".rsworktree/feature/bare");
+    assert!(worktree.join("README.md").is_file());
+    assert!(!project.join(".gitignore").exists());
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(&worktree)
+        .args(["ls"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("feature/bare"));
+
+    Ok(())
+}
 #[test]
 fn create_command_reuses_existing_branch() -> Result<(), Box<dyn Error>> {
     let repo_dir = TempDir::new()?;
@@ -215,3 +249,392 @@ fn create_command_handles_names_with_reserved_characters() -> Result<(), Box<dyn
 
     Ok(())
 }
+
+#[test]
+fn create_command_with_exists_ok_is_repeatable() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    for _ in 0..2 {
+        Command::cargo_bin("rsworktree")?
+            .current_dir(repo_dir.path())
+            .env_remove("TMUX")
+            .env("RSWORKTREE_SHELL", "env")
+            .args(["create", "feature/idempotent", "--base", "main", "--exists-ok"])
+            .assert()
+            .success();
+    }
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/idempotent", "--base", "missing", "--exists-ok"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed to resolve base reference `missing`"));
+
+    Ok(())
+}
+
+#[test]
+fn create_command_plain_output_labels_lines() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["--plain", "create", "feature/plain"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("ok: Created worktree `feature/plain`")
+                .and(predicate::str::contains("\u{1b}[").not()),
+        );
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_PLAIN", "1")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/plain"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "info: Worktree `feature/plain` already exists",
+        ));
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn create_command_posts_webhook_event() -> Result<(), Box<dyn Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    let scratch = TempDir::new()?;
+    let payload_path = scratch.path().join("payload.json");
+    let curl = scratch.path().join("fake-curl");
+    fs::write(
+        &curl,
+        format!("#!/bin/sh\ncat > '{}'\n", payload_path.display()),
+    )?;
+    fs::set_permissions(&curl, fs::Permissions::from_mode(0o755))?;
+
+    let config_dir = repo_dir.path().join(".rsworktree");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("preferences.json"),
+        r#"{"webhook": {"url": "https://hooks.example.test", "template": {"text": "{event}: {worktree}"}}}"#,
+    )?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .env("RSWORKTREE_CURL", &curl)
+        .args(["create", "feature/hooked"])
+        .assert()
+        .success();
+
+    let payload = fs::read_to_string(&payload_path)?;
+    assert_eq!(payload, r#"{"text":"create: feature/hooked"}"#);
+
+    Ok(())
+}
+
+#[test]
+fn create_command_applies_patch_from_stdin() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    let patch = "diff --git a/README.md b/README.md\n--- a/README.md\n+++ b/README.md\n@@ -1 +1 @@\n-test\n\\ No newline at end of file\n+patched\n";
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/patch", "--apply", "-"])
+        .write_stdin(patch)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("left uncommitted for review"));
+
+    let readme = repo_dir.path().join(".rsworktree/feature/patch/README.md");
+    assert_eq!(fs::read_to_string(readme)?, "patched\n");
+
+    Ok(())
+}
+
+#[test]
+fn create_command_copies_untracked_files() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    fs::write(repo_dir.path().join(".env"), "SECRET=1")?;
+    fs::write(repo_dir.path().join(".env.local"), "LOCAL=1")?;
+    fs::create_dir_all(repo_dir.path().join("apps/web"))?;
+    fs::write(repo_dir.path().join("apps/web/.env"), "WEB=1")?;
+    fs::create_dir_all(repo_dir.path().join(".rsworktree"))?;
+    fs::write(
+        repo_dir.path().join(".rsworktree/preferences.json"),
+        r#"{"worktree": {"copy": ["apps/*/.env"]}}"#,
+    )?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/copy", "--copy", ".env*"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Copied `.env.local`"));
+
+    let worktree = repo_dir.path().join(".rsworktree/feature/copy");
+    assert_eq!(fs::read_to_string(worktree.join(".env"))?, "SECRET=1");
+    assert_eq!(fs::read_to_string(worktree.join(".env.local"))?, "LOCAL=1");
+    assert_eq!(fs::read_to_string(worktree.join("apps/web/.env"))?, "WEB=1");
+
+    Ok(())
+}
+
+#[test]
+fn create_command_applies_template() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    run(repo_dir.path(), ["git", "branch", "release"])?;
+    fs::write(repo_dir.path().join(".env"), "SECRET=1")?;
+    fs::create_dir_all(repo_dir.path().join(".rsworktree/templates"))?;
+    fs::write(
+        repo_dir
+            .path()
+            .join(".rsworktree/templates/backend-bugfix.toml"),
+        r#"
+base = "release"
+prefix = "bugfix/"
+copy = [".env"]
+
+[env]
+DATABASE_NAME = "app_{branch}"
+
+[hooks]
+post-create = ["echo \"$DATABASE_NAME\" > database.txt"]
+"#,
+    )?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "--template", "backend-bugfix", "my-fix"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("from `release`"));
+
+    let worktree = repo_dir.path().join(".rsworktree/bugfix/my-fix");
+    assert_eq!(fs::read_to_string(worktree.join(".env"))?, "SECRET=1");
+    assert_eq!(
+        fs::read_to_string(worktree.join("database.txt"))?.trim(),
+        "app_bugfix/my-fix"
+    );
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["create", "--template", "frontend", "my-fix"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "template `frontend` not found; available templates: backend-bugfix",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn create_command_checks_out_remote_branch() -> Result<(), Box<dyn Error>> {
+    let remote_dir = TempDir::new()?;
+    init_git_repo(remote_dir.path())?;
+    let clone_parent = TempDir::new()?;
+    let remote_path = remote_dir.path().to_str().ok_or("non-UTF-8 path")?;
+    let status = StdCommand::new("git")
+        .current_dir(clone_parent.path())
+        .args(["clone", "--quiet", remote_path, "clone"])
+        .status()?;
+    assert!(status.success(), "git clone should succeed");
+    let repo_dir = clone_parent.path().join("clone");
+
+    // The colleague pushes the branch after the clone, so it is not fetched yet.
+    run(
+        remote_dir.path(),
+        ["git", "checkout", "-b", "feature/review"],
+    )?;
+    fs::write(remote_dir.path().join("review.txt"), "colleague's work")?;
+    run(remote_dir.path(), ["git", "add", "review.txt"])?;
+    run(
+        remote_dir.path(),
+        [
+            "git",
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-m",
+            "Colleague commit",
+        ],
+    )?;
+    run(remote_dir.path(), ["git", "checkout", "main"])?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(&repo_dir)
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "review", "--from-remote", "feature/review"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("origin/feature/review"));
+
+    let worktree = repo_dir.join(".rsworktree/review");
+    assert_eq!(
+        fs::read_to_string(worktree.join("review.txt"))?,
+        "colleague's work"
+    );
+    let upstream = StdCommand::new("git")
+        .current_dir(&worktree)
+        .args(["rev-parse", "--abbrev-ref", "@{upstream}"])
+        .output()?;
+    assert_eq!(
+        String::from_utf8_lossy(&upstream.stdout).trim(),
+        "origin/feature/review"
+    );
+
+    // Once fetched, the branch is picked up from `origin` without the flag.
+    run(
+        &repo_dir,
+        ["git", "worktree", "remove", ".rsworktree/review"],
+    )?;
+    run(&repo_dir, ["git", "branch", "-D", "review"])?;
+    Command::cargo_bin("rsworktree")?
+        .current_dir(&repo_dir)
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/review"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("origin/feature/review"));
+    assert!(
+        repo_dir
+            .join(".rsworktree/feature/review/review.txt")
+            .exists()
+    );
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn create_command_runs_pre_create_hook() -> Result<(), Box<dyn Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    let hooks_dir = repo_dir.path().join(".rsworktree/hooks");
+    fs::create_dir_all(&hooks_dir)?;
+    let hook = hooks_dir.join("pre-create");
+    fs::write(
+        &hook,
+        "#!/bin/sh\ncase \"$RSWORKTREE_NAME\" in\n  wip*) exit 1 ;;\nesac\necho \"RSWORKTREE_NAME=TICKET-1/$RSWORKTREE_NAME\"\n",
+    )?;
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755))?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "wip-login"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("rejected `wip-login`"));
+    assert!(!repo_dir.path().join(".rsworktree/wip-login").exists());
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "login"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("renamed"));
+
+    let worktree = repo_dir.path().join(".rsworktree/TICKET-1/login");
+    assert!(worktree.is_dir(), "renamed worktree should exist");
+    let branch = StdCommand::new("git")
+        .current_dir(&worktree)
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .output()?;
+    assert_eq!(
+        String::from_utf8_lossy(&branch.stdout).trim(),
+        "TICKET-1/login"
+    );
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn create_command_names_worktree_after_issue() -> Result<(), Box<dyn Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    let scratch = TempDir::new()?;
+    let request_path = scratch.path().join("request.txt");
+    let curl = scratch.path().join("fake-curl");
+    fs::write(
+        &curl,
+        format!(
+            "#!/bin/sh\ncat > '{}'\necho '{{\"fields\": {{\"summary\": \"Fix login redirect\"}}}}'\n",
+            request_path.display()
+        ),
+    )?;
+    fs::set_permissions(&curl, fs::Permissions::from_mode(0o755))?;
+
+    let config_dir = repo_dir.path().join(".rsworktree");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("preferences.json"),
+        r#"{"issues": {"backend": "jira", "url": "https://example.atlassian.net"}}"#,
+    )?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .env("RSWORKTREE_CURL", &curl)
+        .env("JIRA_API_TOKEN", "secret")
+        .args(["create", "--issue", "PROJ-123"])
+        .assert()
+        .success();
+
+    assert!(
+        repo_dir
+            .path()
+            .join(".rsworktree/PROJ-123-fix-login-redirect")
+            .is_dir()
+    );
+    let request = fs::read_to_string(&request_path)?;
+    assert!(request.contains("/rest/api/2/issue/PROJ-123?fields=summary"));
+    assert!(request.contains("Authorization: Bearer secret"));
+
+    let meta = fs::read_to_string(
+        repo_dir
+            .path()
+            .join(".rsworktree/meta/PROJ-123-fix-login-redirect.toml"),
+    )?;
+    assert!(meta.contains("issue = \"PROJ-123\"\n"));
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use std::{error::Error, fs, path::Path, process::Command as StdCommand};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn init_git_repo(dir: &Path) -> Result<(), Box<dyn Error>> {
+    let init_with_main = StdCommand::new("git")
+        .current_dir(dir)
+        .args(["init", "-b", "main"])
+        .status()?;
+
+    if !init_with_main.success() {
+        run(dir, ["git", "init"])?;
+        run(dir, ["git", "branch", "-M", "main"])?;
+    }
+
+    fs::write(dir.join("README.md"), "test")?;
+    run(dir, ["git", "add", "README.md"])?;
+    run(
+        dir,
+        [
+            "git",
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-m",
+            "Initial commit",
+        ],
+    )?;
+    Ok(())
+}
+
+fn run(dir: &Path, cmd: impl IntoIterator<Item = &'static str>) -> Result<(), Box<dyn Error>> {
+    let mut iter = cmd.into_iter();
+    let program = iter.next().expect("command must not be empty");
+    let status = StdCommand::new(program)
+        .current_dir(dir)
+        .args(iter)
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("`{program}` exited with status {status}").into());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn export_bundle_round_trips_through_create_from_bundle() -> Result<(), Box<dyn Error>> {
+    let source_dir = TempDir::new()?;
+    init_git_repo(source_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(source_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/share"])
+        .assert()
+        .success();
+
+    let bundle = source_dir.path().join("share.bundle");
+    Command::cargo_bin("rsworktree")?
+        .current_dir(source_dir.path())
+        .args(["export", "feature/share", "--bundle", "-o"])
+        .arg(&bundle)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("--from-bundle"));
+
+    let target_dir = TempDir::new()?;
+    init_git_repo(target_dir.path())?;
+    Command::cargo_bin("rsworktree")?
+        .current_dir(target_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/share", "--from-bundle"])
+        .arg(&bundle)
+        .assert()
+        .success();
+
+    assert!(
+        target_dir
+            .path()
+            .join(".rsworktree/feature/share/README.md")
+            .exists()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn export_tar_writes_archive_of_working_tree() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/tar"])
+        .assert()
+        .success();
+    fs::write(
+        repo_dir.path().join(".rsworktree/feature/tar/draft.txt"),
+        "draft",
+    )?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["export", "feature/tar", "--tar"])
+        .assert()
+        .success();
+
+    let listing = StdCommand::new("tar")
+        .current_dir(repo_dir.path())
+        .args(["-tzf", "feature-tar.tar.gz"])
+        .output()?;
+    let listing = String::from_utf8(listing.stdout)?;
+    assert!(listing.lines().any(|line| line == "draft.txt"));
+    assert!(listing.lines().any(|line| line == "README.md"));
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use std::{error::Error, fs, path::Path, process::Command as StdCommand};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn init_git_repo(dir: &Path) -> Result<(), Box<dyn Error>> {
+    let init_with_main = StdCommand::new("git")
+        .current_dir(dir)
+        .args(["init", "-b", "main"])
+        .status()?;
+
+    if !init_with_main.success() {
+        run(dir, &["git", "init"])?;
+        run(dir, &["git", "branch", "-M", "main"])?;
+    }
+
+    fs::write(dir.join("README.md"), "test")?;
+    run(dir, &["git", "add", "README.md"])?;
+    commit(dir, "Initial commit")
+}
+
+fn commit(dir: &Path, message: &str) -> Result<(), Box<dyn Error>> {
+    run(
+        dir,
+        &[
+            "git",
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "--quiet",
+            "-m",
+            message,
+        ],
+    )
+}
+
+fn run(dir: &Path, cmd: &[&str]) -> Result<(), Box<dyn Error>> {
+    let (program, args) = cmd.split_first().expect("command must not be empty");
+    let status = StdCommand::new(program)
+        .current_dir(dir)
+        .args(args)
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("`{program}` exited with status {status}").into());
+    }
+
+    Ok(())
+}
+
+fn hash_object(dir: &Path, file: &str) -> Result<String, Box<dyn Error>> {
+    let output = StdCommand::new("git")
+        .current_dir(dir)
+        .args(["hash-object", file])
+        .output()?;
+    Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+}
+
+fn publish_hooks(dir: &Path, version: &str, script: &str) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(dir.join("hooks"))?;
+    fs::write(dir.join("hooks/post-create"), script)?;
+    let checksum = hash_object(dir, "hooks/post-create")?;
+    fs::write(
+        dir.join("rsworktree-hooks.json"),
+        format!(
+            r#"{{ "version": "{version}", "hooks": {{ "post-create": {{ "path": "hooks/post-create", "checksum": "{checksum}" }} }} }}"#
+        ),
+    )?;
+    run(dir, &["git", "add", "."])?;
+    commit(dir, &format!("Release {version}"))
+}
+
+#[test]
+fn hooks_install_and_update_from_git_url() -> Result<(), Box<dyn Error>> {
+    let source_dir = TempDir::new()?;
+    init_git_repo(source_dir.path())?;
+    publish_hooks(source_dir.path(), "1.0.0", "#!/bin/sh\necho v1\n")?;
+    let source_url = format!("file://{}", source_dir.path().display());
+
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["hooks", "install", &source_url])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("post-create"));
+
+    let hook = repo_dir.path().join(".rsworktree/hooks/post-create");
+    assert_eq!(fs::read_to_string(&hook)?, "#!/bin/sh\necho v1\n");
+    assert!(
+        !repo_dir
+            .path()
+            .join(".rsworktree/state/hooks-source.checkout")
+            .exists()
+    );
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["hooks", "update"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("up to date"));
+
+    publish_hooks(source_dir.path(), "1.1.0", "#!/bin/sh\necho v2\n")?;
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["hooks", "update"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1.1.0"));
+    assert_eq!(fs::read_to_string(&hook)?, "#!/bin/sh\necho v2\n");
+
+    Ok(())
+}
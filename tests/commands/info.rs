@@ -0,0 +1,86 @@
+use std::{error::Error, fs, path::Path, process::Command as StdCommand};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn init_git_repo(dir: &Path) -> Result<(), Box<dyn Error>> {
+    run(dir, ["git", "init"])?;
+    fs::write(dir.join("README.md"), "test")?;
+    run(dir, ["git", "add", "README.md"])?;
+    run(
+        dir,
+        [
+            "git",
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-m",
+            "Initial commit",
+        ],
+    )?;
+    Ok(())
+}
+
+fn run(dir: &Path, cmd: impl IntoIterator<Item = &'static str>) -> Result<(), Box<dyn Error>> {
+    let mut iter = cmd.into_iter();
+    let program = iter.next().expect("command must not be empty");
+    let status = StdCommand::new(program)
+        .current_dir(dir)
+        .args(iter)
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("`{program}` exited with status {status}").into());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn info_command_reports_worktree_details() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/info"])
+        .assert()
+        .success();
+
+    fs::write(
+        repo_dir.path().join(".rsworktree/feature/info/scratch.txt"),
+        "wip",
+    )?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["info", "feature/info"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Worktree `feature/info`"))
+        .stdout(predicate::str::contains("Branch:"))
+        .stdout(predicate::str::contains("1 untracked"))
+        .stdout(predicate::str::contains("Disk usage:"));
+
+    Ok(())
+}
+
+#[test]
+fn info_command_fails_for_unknown_worktree() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["info", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
+
+    Ok(())
+}
@@ -91,7 +91,7 @@ fn ls_command_works_from_inside_worktree() -> Result<(), Box<dyn Error>> {
     Command::cargo_bin("rsworktree")?
         .current_dir(repo_dir.path())
         .env_remove("TMUX")
-            .env("RSWORKTREE_SHELL", "env")
+        .env("RSWORKTREE_SHELL", "env")
         .args(["create", "feature/nested"])
         .assert()
         .success();
@@ -107,3 +107,155 @@ fn ls_command_works_from_inside_worktree() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn ls_command_uses_locale_from_environment() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env("RSWORKTREE_LANG", "de")
+        .arg("ls")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Worktrees unter").and(predicate::str::contains("(keine)")),
+        );
+
+    Ok(())
+}
+
+#[test]
+fn configured_alias_expands_to_ls() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    let config_dir = repo_dir.path().join(".rsworktree");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("preferences.json"),
+        r#"{"aliases": {"l": "ls"}}"#,
+    )?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .arg("l")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Worktrees under"));
+
+    Ok(())
+}
+
+#[test]
+fn ls_command_renders_format_template() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/format"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["ls", "--format", r"{name}\t{branch}\t{state}"])
+        .assert()
+        .success()
+        .stdout("feature/format\tfeature/format\tclean\n");
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["ls", "--format", "{nope}"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown placeholder `{nope}`"));
+
+    Ok(())
+}
+
+#[test]
+fn ls_command_renders_selected_columns() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/table"])
+        .assert()
+        .success();
+    fs::write(
+        repo_dir.path().join(".rsworktree/feature/table/notes.txt"),
+        "wip",
+    )?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["ls", "--columns", "name,state,sync"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("NAME           STATE  SYNC\n").and(predicate::str::contains(
+                "feature/table  dirty  no upstream\n",
+            )),
+        );
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["ls", "--columns", "name,size"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown column 'size'"));
+
+    Ok(())
+}
+
+#[test]
+fn ls_command_prints_json() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .arg("ls")
+        .arg("--json")
+        .assert()
+        .success()
+        .stdout("[]\n");
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/json"])
+        .assert()
+        .success();
+    let worktree_path = repo_dir.path().join(".rsworktree/feature/json");
+    fs::write(worktree_path.join("scratch.txt"), "wip")?;
+
+    let output = Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["ls", "--json"])
+        .output()?;
+    assert!(output.status.success());
+
+    let entries: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let entry = &entries[0];
+    assert_eq!(entries.as_array().map(Vec::len), Some(1));
+    assert_eq!(entry["name"], "feature/json");
+    assert_eq!(entry["branch"], "feature/json");
+    assert_eq!(
+        entry["path"],
+        fs::canonicalize(&worktree_path)?.display().to_string()
+    );
+    assert_eq!(entry["head"].as_str().map(str::len), Some(40));
+    assert_eq!(entry["dirty"], true);
+    assert!(entry["last_commit_at"].as_i64().is_some());
+
+    Ok(())
+}
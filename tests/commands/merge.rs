@@ -59,6 +59,50 @@ fn merge_merges_open_pr_for_current_worktree() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn merge_cleanup_removes_worktree_and_local_branch() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/test"])
+        .assert()
+        .success();
+
+    let stub = install_stub_gh()?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .env("PATH", &stub.path_value)
+        .env("GH_LOG", &stub.log_path)
+        .env(
+            "GH_PR_LIST_RESPONSE",
+            r#"[{"number": 42, "state": "OPEN"}]"#,
+        )
+        .args(["merge", "feature/test", "--cleanup"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Merged PR #42 for branch `feature/test`.",
+        ))
+        .stdout(predicate::str::contains("Removed worktree `feature/test`"))
+        .stderr(predicate::str::contains("Could not update base branch"));
+
+    assert!(!repo_dir.path().join(".rsworktree/feature/test").exists());
+    let branches = StdCommand::new("git")
+        .current_dir(repo_dir.path())
+        .args(["branch", "--list", "feature/test"])
+        .output()?;
+    assert!(String::from_utf8(branches.stdout)?.trim().is_empty());
+
+    Ok(())
+}
+
 #[test]
 fn merge_reports_when_no_pr_found() -> Result<(), Box<dyn Error>> {
     let repo_dir = TempDir::new()?;
@@ -43,6 +43,7 @@ fn create_worktree(repo_dir: &Path, name: &str) -> Result<(), Box<dyn Error>> {
     Command::cargo_bin("rsworktree")?
         .current_dir(repo_dir)
         .env_remove("TMUX")
+        .env_remove("ZELLIJ")
         .env("RSWORKTREE_SHELL", "env")
         .args(["create", name])
         .assert()
@@ -62,6 +63,7 @@ fn open_editor_uses_env_editor_and_reports_success() -> Result<(), Box<dyn Error
     Command::cargo_bin("rsworktree")?
         .current_dir(repo_dir.path())
         .env_remove("TMUX")
+        .env_remove("ZELLIJ")
         .args(["worktree", "open", "feature/test"])
         .assert()
         .success()
@@ -83,10 +85,14 @@ fn open_editor_guidance_when_no_preference() -> Result<(), Box<dyn Error>> {
 
     let guard_editor = EnvGuard::remove("EDITOR");
     let guard_visual = EnvGuard::remove("VISUAL");
+    // No editor on PATH either, so none can be detected.
+    let empty_path = TempDir::new()?;
 
     Command::cargo_bin("rsworktree")?
         .current_dir(repo_dir.path())
         .env_remove("TMUX")
+        .env_remove("ZELLIJ")
+        .env("PATH", empty_path.path())
         .args(["worktree", "open", "feature/empty"])
         .assert()
         .success()
@@ -97,6 +103,41 @@ fn open_editor_guidance_when_no_preference() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[cfg(unix)]
+#[test]
+fn open_editor_detects_editor_on_path() -> Result<(), Box<dyn Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    create_worktree(repo_dir.path(), "feature/detected")?;
+
+    let bin_dir = TempDir::new()?;
+    let editor = bin_dir.path().join("nvim");
+    fs::write(&editor, "#!/bin/sh\nexit 0\n")?;
+    fs::set_permissions(&editor, fs::Permissions::from_mode(0o755))?;
+
+    let guard_editor = EnvGuard::remove("EDITOR");
+    let guard_visual = EnvGuard::remove("VISUAL");
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env_remove("ZELLIJ")
+        .env("PATH", bin_dir.path())
+        .args(["worktree", "open", "feature/detected"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("No editor configured; using `nvim` found on PATH.")
+                .and(predicate::str::contains("Opened `feature/detected`")),
+        );
+
+    drop(guard_visual);
+    drop(guard_editor);
+    Ok(())
+}
+
 #[test]
 fn open_editor_errors_when_worktree_missing() -> Result<(), Box<dyn Error>> {
     let repo_dir = TempDir::new()?;
@@ -105,6 +146,7 @@ fn open_editor_errors_when_worktree_missing() -> Result<(), Box<dyn Error>> {
     Command::cargo_bin("rsworktree")?
         .current_dir(repo_dir.path())
         .env_remove("TMUX")
+        .env_remove("ZELLIJ")
         .args(["worktree", "open", "missing"])
         .assert()
         .failure()
@@ -163,6 +205,7 @@ fn open_editor_with_path_flag() -> Result<(), Box<dyn Error>> {
     Command::cargo_bin("rsworktree")?
         .current_dir(repo_dir.path())
         .env_remove("TMUX")
+        .env_remove("ZELLIJ")
         .args([
             "worktree",
             "open",
@@ -185,6 +228,7 @@ fn open_editor_errors_when_path_does_not_exist() -> Result<(), Box<dyn Error>> {
     Command::cargo_bin("rsworktree")?
         .current_dir(repo_dir.path())
         .env_remove("TMUX")
+        .env_remove("ZELLIJ")
         .args(["worktree", "open", "--path", "/nonexistent/path"])
         .assert()
         .failure()
@@ -206,6 +250,7 @@ fn open_editor_matches_partial_name() -> Result<(), Box<dyn Error>> {
     Command::cargo_bin("rsworktree")?
         .current_dir(repo_dir.path())
         .env_remove("TMUX")
+        .env_remove("ZELLIJ")
         .args(["worktree", "open", "unique-name"])
         .assert()
         .success()
@@ -225,6 +270,7 @@ fn open_editor_errors_on_ambiguous_name() -> Result<(), Box<dyn Error>> {
     Command::cargo_bin("rsworktree")?
         .current_dir(repo_dir.path())
         .env_remove("TMUX")
+        .env_remove("ZELLIJ")
         .args(["worktree", "open", "shared"])
         .assert()
         .failure()
@@ -253,6 +299,7 @@ fn open_editor_uses_preferences_file() -> Result<(), Box<dyn Error>> {
     Command::cargo_bin("rsworktree")?
         .current_dir(repo_dir.path())
         .env_remove("TMUX")
+        .env_remove("ZELLIJ")
         .args(["worktree", "open", "feature/prefs"])
         .assert()
         .success()
@@ -262,3 +309,75 @@ fn open_editor_uses_preferences_file() -> Result<(), Box<dyn Error>> {
     drop(guard_editor);
     Ok(())
 }
+
+#[test]
+fn open_without_name_outside_terminal_keeps_error() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    create_worktree(repo_dir.path(), "feature/picker")?;
+
+    for args in [
+        vec!["worktree", "open"],
+        vec!["worktree", "open", "--no-interactive"],
+    ] {
+        Command::cargo_bin("rsworktree")?
+            .current_dir(repo_dir.path())
+            .env_remove("TMUX")
+            .env_remove("ZELLIJ")
+            .args(args)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "worktree name or --path must be provided",
+            ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn open_shell_prints_cd_hint_outside_multiplexer() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    create_worktree(repo_dir.path(), "feature/shell")?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env_remove("ZELLIJ")
+        .env("EDITOR", "/usr/bin/env false")
+        .args(["worktree", "open", "shell", "--shell"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Run `cd ")
+                .and(predicate::str::contains("to enter `feature/shell`"))
+                .and(predicate::str::contains("Launched").not()),
+        );
+
+    Ok(())
+}
+
+#[test]
+fn open_starts_configured_terminal_emulator() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    create_worktree(repo_dir.path(), "feature/terminal")?;
+    fs::write(
+        repo_dir.path().join(".rsworktree/preferences.json"),
+        r#"{"editor": {"kind": "terminal", "command": "/usr/bin/env", "args": ["true"]}}"#,
+    )?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env_remove("ZELLIJ")
+        .args(["worktree", "open", "feature/terminal"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Launched `feature/terminal` using `/usr/bin/env`",
+        ));
+
+    Ok(())
+}
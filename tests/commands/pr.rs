@@ -0,0 +1,209 @@
+#![cfg(unix)]
+
+use std::{
+    env, error::Error, ffi::OsString, fs, os::unix::fs::PermissionsExt, path::Path, path::PathBuf,
+    process::Command as StdCommand,
+};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+struct StubGh {
+    _dir: TempDir,
+    path_value: OsString,
+    log_path: PathBuf,
+}
+
+#[test]
+fn pr_comment_posts_message_from_stdin() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    create_worktree(repo_dir.path(), "feature/test")?;
+
+    let stub = install_stub_gh()?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env("PATH", &stub.path_value)
+        .env("GH_LOG", &stub.log_path)
+        .args(["pr", "comment", "feature/test", "--file", "-"])
+        .write_stdin("Ready for review\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Commented on the pull request for `feature/test`.",
+        ));
+
+    let log_contents = fs::read_to_string(&stub.log_path)?;
+    assert!(log_contents.contains("args:pr comment feature/test --body Ready for review"));
+
+    Ok(())
+}
+
+#[test]
+fn pr_review_requests_changes_with_message() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    create_worktree(repo_dir.path(), "feature/test")?;
+
+    let stub = install_stub_gh()?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env("PATH", &stub.path_value)
+        .env("GH_LOG", &stub.log_path)
+        .args([
+            "pr",
+            "review",
+            "feature/test",
+            "--request-changes",
+            "-m",
+            "Needs tests",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Requested changes on the pull request for `feature/test`.",
+        ));
+
+    let log_contents = fs::read_to_string(&stub.log_path)?;
+    assert!(
+        log_contents.contains("args:pr review feature/test --request-changes --body Needs tests")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn pr_close_removes_worktree_and_local_branch() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    create_worktree(repo_dir.path(), "feature/test")?;
+
+    let stub = install_stub_gh()?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("PATH", &stub.path_value)
+        .env("GH_LOG", &stub.log_path)
+        .args([
+            "pr",
+            "close",
+            "feature/test",
+            "--remove",
+            "--comment",
+            "Superseded",
+            "--yes",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Closed the pull request for `feature/test`.")
+                .and(predicate::str::contains("Removed worktree `feature/test`"))
+                .and(predicate::str::contains(
+                    "Deleted local branch `feature/test`.",
+                )),
+        );
+
+    let log_contents = fs::read_to_string(&stub.log_path)?;
+    assert!(log_contents.contains("args:pr close feature/test --comment Superseded"));
+    assert!(!repo_dir.path().join(".rsworktree/feature/test").exists());
+
+    Ok(())
+}
+
+#[test]
+fn pr_close_requires_confirmation_when_not_interactive() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    create_worktree(repo_dir.path(), "feature/test")?;
+
+    let stub = install_stub_gh()?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env("PATH", &stub.path_value)
+        .env("GH_LOG", &stub.log_path)
+        .args(["pr", "close", "feature/test", "--cleanup"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--yes"));
+
+    assert!(!stub.log_path.exists());
+    assert!(repo_dir.path().join(".rsworktree/feature/test").exists());
+
+    Ok(())
+}
+
+fn create_worktree(repo: &Path, name: &str) -> Result<(), Box<dyn Error>> {
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo)
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", name])
+        .assert()
+        .success();
+    Ok(())
+}
+
+fn init_git_repo(dir: &Path) -> Result<(), Box<dyn Error>> {
+    run(dir, ["git", "init"])?;
+    fs::write(dir.join("README.md"), "test")?;
+    run(dir, ["git", "add", "README.md"])?;
+    run(
+        dir,
+        [
+            "git",
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-m",
+            "Initial commit",
+        ],
+    )?;
+    Ok(())
+}
+
+fn run(dir: &Path, cmd: impl IntoIterator<Item = &'static str>) -> Result<(), Box<dyn Error>> {
+    let mut iter = cmd.into_iter();
+    let program = iter.next().expect("command must not be empty");
+    let status = StdCommand::new(program)
+        .current_dir(dir)
+        .args(iter)
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("`{program}` exited with status {status}").into());
+    }
+
+    Ok(())
+}
+
+fn install_stub_gh() -> Result<StubGh, Box<dyn Error>> {
+    let stub_dir = TempDir::new()?;
+    let gh_log = stub_dir.path().join("gh.log");
+    let gh_path = stub_dir.path().join("gh");
+    fs::write(
+        &gh_path,
+        "#! /bin/sh\n\nlog() {\n  printf 'args:%s\\n' \"$*\" >> \"$GH_LOG\"\n}\n\ncase \"$1 $2\" in\n  'pr comment')\n    log \"$@\"\n    echo 'https://github.com/o/r/pull/1#issuecomment-1'\n    ;;\n  'pr review' | 'pr close')\n    log \"$@\"\n    ;;\n  *)\n    echo \"unexpected gh invocation: $*\" >&2\n    exit 1\n    ;;\nesac\n\nexit 0\n",
+    )?;
+    let mut perms = fs::metadata(&gh_path)?.permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(&gh_path, perms)?;
+
+    let mut path_value = OsString::from(stub_dir.path());
+    if let Some(existing) = env::var_os("PATH") {
+        path_value.push(":");
+        path_value.push(existing);
+    }
+
+    Ok(StubGh {
+        _dir: stub_dir,
+        path_value,
+        log_path: gh_log,
+    })
+}
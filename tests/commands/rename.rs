@@ -0,0 +1,137 @@
+use std::{error::Error, fs, path::Path, process::Command as StdCommand};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn init_git_repo(dir: &Path) -> Result<(), Box<dyn Error>> {
+    run(dir, ["git", "init"])?;
+    fs::write(dir.join("README.md"), "test")?;
+    run(dir, ["git", "add", "README.md"])?;
+    run(
+        dir,
+        [
+            "git",
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-m",
+            "Initial commit",
+        ],
+    )?;
+    Ok(())
+}
+
+fn run(dir: &Path, cmd: impl IntoIterator<Item = &'static str>) -> Result<(), Box<dyn Error>> {
+    let mut iter = cmd.into_iter();
+    let program = iter.next().expect("command must not be empty");
+    let status = StdCommand::new(program)
+        .current_dir(dir)
+        .args(iter)
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("`{program}` exited with status {status}").into());
+    }
+
+    Ok(())
+}
+
+fn git_output(dir: &Path, args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let output = StdCommand::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()?;
+    Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+}
+
+fn create_worktree(dir: &Path, name: &str) -> Result<(), Box<dyn Error>> {
+    Command::cargo_bin("rsworktree")?
+        .current_dir(dir)
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", name])
+        .assert()
+        .success();
+    Ok(())
+}
+
+#[test]
+fn rename_command_moves_worktree_and_branch() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    create_worktree(repo_dir.path(), "feature/old")?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .args(["rename", "old", "topic/new", "-m"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Renamed worktree `feature/old` to `topic/new`.",
+        ))
+        .stdout(predicate::str::contains(
+            "Renamed branch `feature/old` to `topic/new`.",
+        ));
+
+    let worktrees_dir = repo_dir.path().join(".rsworktree");
+    let new_path = worktrees_dir.join("topic/new");
+    assert!(new_path.is_dir());
+    assert!(!worktrees_dir.join("feature").exists());
+
+    assert_eq!(
+        git_output(&new_path, &["rev-parse", "--abbrev-ref", "HEAD"])?,
+        "topic/new"
+    );
+    let worktrees = git_output(repo_dir.path(), &["worktree", "list", "--porcelain"])?;
+    assert!(worktrees.contains(".rsworktree/topic/new"), "{worktrees}");
+    assert!(!worktrees.contains("feature/old"), "{worktrees}");
+    assert!(!worktrees.contains("prunable"), "{worktrees}");
+
+    Ok(())
+}
+
+#[test]
+fn rename_command_keeps_branch_without_flag() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    create_worktree(repo_dir.path(), "feature/old")?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .args(["rename", "feature/old", "feature/new"])
+        .assert()
+        .success();
+
+    let new_path = repo_dir.path().join(".rsworktree/feature/new");
+    assert_eq!(
+        git_output(&new_path, &["rev-parse", "--abbrev-ref", "HEAD"])?,
+        "feature/old"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn rename_command_refuses_existing_target() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+    create_worktree(repo_dir.path(), "first")?;
+    create_worktree(repo_dir.path(), "second")?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .args(["rename", "first", "second"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+
+    assert!(repo_dir.path().join(".rsworktree/first").is_dir());
+
+    Ok(())
+}
@@ -205,3 +205,83 @@ fn rm_command_force_removes_locked_worktree() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn rm_command_respects_lock_command_until_unlocked() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/usb"])
+        .assert()
+        .success();
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["lock", "usb", "--reason", "on a usb drive"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .args(["rm", "feature/usb"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "is locked (on a usb drive); run `rsworktree unlock feature/usb`",
+        ));
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["unlock", "usb"])
+        .assert()
+        .success();
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .args(["rm", "feature/usb"])
+        .assert()
+        .success();
+    assert!(!repo_dir.path().join(".rsworktree/feature/usb").exists());
+
+    Ok(())
+}
+
+#[test]
+fn remove_alias_resolves_short_name_and_deletes_branch() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/short-name"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .args(["remove", "short-name", "--delete-branch"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed worktree"));
+
+    assert!(
+        !repo_dir
+            .path()
+            .join(".rsworktree/feature/short-name")
+            .exists()
+    );
+    let branches = StdCommand::new("git")
+        .current_dir(repo_dir.path())
+        .args(["branch", "--list", "feature/short-name"])
+        .output()?;
+    assert!(String::from_utf8_lossy(&branches.stdout).trim().is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::{error::Error, fs, path::Path, process::Command as StdCommand};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn init_git_repo(dir: &Path) -> Result<(), Box<dyn Error>> {
+    run(dir, ["git", "init"])?;
+    fs::write(dir.join("README.md"), "test")?;
+    run(dir, ["git", "add", "README.md"])?;
+    run(
+        dir,
+        [
+            "git",
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-m",
+            "Initial commit",
+        ],
+    )?;
+    Ok(())
+}
+
+fn run(dir: &Path, cmd: impl IntoIterator<Item = &'static str>) -> Result<(), Box<dyn Error>> {
+    let mut iter = cmd.into_iter();
+    let program = iter.next().expect("command must not be empty");
+    let status = StdCommand::new(program)
+        .current_dir(dir)
+        .args(iter)
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("`{program}` exited with status {status}").into());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn switch_command_prints_resolved_worktree_path() -> Result<(), Box<dyn Error>> {
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/switch"])
+        .assert()
+        .success();
+
+    let worktree_path = repo_dir
+        .path()
+        .join(".rsworktree/feature/switch")
+        .canonicalize()?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["switch", "switch"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", worktree_path.display()));
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .args(["switch", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("worktree `missing` not found"));
+
+    Ok(())
+}
+
+#[test]
+fn shell_init_works_outside_a_repository() -> Result<(), Box<dyn Error>> {
+    let dir = TempDir::new()?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(dir.path())
+        .args(["shell-init", "zsh"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rsworktree() {"));
+
+    Ok(())
+}
+
+#[test]
+fn bash_integration_changes_directory_on_switch() -> Result<(), Box<dyn Error>> {
+    if StdCommand::new("bash")
+        .arg("-c")
+        .arg("true")
+        .status()
+        .is_err()
+    {
+        return Ok(());
+    }
+
+    let repo_dir = TempDir::new()?;
+    init_git_repo(repo_dir.path())?;
+
+    Command::cargo_bin("rsworktree")?
+        .current_dir(repo_dir.path())
+        .env_remove("TMUX")
+        .env("RSWORKTREE_SHELL", "env")
+        .args(["create", "feature/login"])
+        .assert()
+        .success();
+
+    let binary = assert_cmd::cargo::cargo_bin("rsworktree");
+    let bin_dir = binary.parent().expect("binary has a parent directory");
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let worktree_path = repo_dir
+        .path()
+        .join(".rsworktree/feature/login")
+        .canonicalize()?;
+
+    let output = StdCommand::new("bash")
+        .current_dir(repo_dir.path())
+        .env("PATH", path)
+        .args([
+            "-c",
+            r#"eval "$(rsworktree shell-init bash)" && rsworktree switch login && pwd -P"#,
+        ])
+        .output()?;
+
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        String::from_utf8(output.stdout)?.trim(),
+        worktree_path.display().to_string()
+    );
+
+    Ok(())
+}